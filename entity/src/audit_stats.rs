@@ -2,8 +2,10 @@ use anyhow::Result;
 use chrono::{DateTime, TimeDelta, Utc};
 use sea_orm::{entity::prelude::*, ActiveValue::NotSet, QueryOrder, Set};
 use serde::Serialize;
+use utoipa::ToSchema;
 
-#[derive(Clone, Debug, DeriveEntityModel, Serialize)]
+#[derive(Clone, Debug, DeriveEntityModel, Serialize, ToSchema)]
+#[schema(as = audit_stats::Model)]
 #[sea_orm(table_name = "audit_stats")]
 pub struct Model {
     #[sea_orm(primary_key)]
@@ -27,6 +29,17 @@ pub struct Model {
     pub success_rate_four_fours_headers: f32,
     pub success_rate_four_fours_bodies: f32,
     pub success_rate_four_fours_receipts: f32,
+    pub num_errored: i32,
+    pub num_pending: i32,
+    /// Pending audits marked as errored because they were stale, since the previous record.
+    pub num_stale_pending: i32,
+    /// Beacon audits, unknown for stats recorded before beacon audits were measured.
+    pub num_beacon_audits: Option<i32>,
+    pub success_rate_beacon_all: Option<f32>,
+    pub success_rate_beacon_bootstraps: Option<f32>,
+    pub success_rate_beacon_updates_by_range: Option<f32>,
+    pub success_rate_beacon_finality_updates: Option<f32>,
+    pub success_rate_beacon_optimistic_updates: Option<f32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -34,6 +47,32 @@ pub enum Relation {}
 
 impl ActiveModelBehavior for ActiveModel {}
 
+/// Success rates of beacon audits, by type of light client content.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, ToSchema)]
+#[schema(as = audit_stats::BeaconSuccessRates)]
+pub struct BeaconSuccessRates {
+    pub num_audits: i32,
+    pub all: f32,
+    pub bootstraps: f32,
+    pub updates_by_range: f32,
+    pub finality_updates: f32,
+    pub optimistic_updates: f32,
+}
+
+impl Model {
+    /// The beacon success rates of the record, if it has them.
+    pub fn beacon_success_rates(&self) -> Option<BeaconSuccessRates> {
+        Some(BeaconSuccessRates {
+            num_audits: self.num_beacon_audits?,
+            all: self.success_rate_beacon_all?,
+            bootstraps: self.success_rate_beacon_bootstraps?,
+            updates_by_range: self.success_rate_beacon_updates_by_range?,
+            finality_updates: self.success_rate_beacon_finality_updates?,
+            optimistic_updates: self.success_rate_beacon_optimistic_updates?,
+        })
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 pub async fn create(
     timestamp: DateTime<Utc>,
@@ -55,6 +94,10 @@ pub async fn create(
     success_rate_four_fours_headers: f32,
     success_rate_four_fours_bodies: f32,
     success_rate_four_fours_receipts: f32,
+    num_errored: i32,
+    num_pending: i32,
+    num_stale_pending: i32,
+    beacon: BeaconSuccessRates,
     conn: &DatabaseConnection,
 ) -> Result<Model> {
     let audit_stats = ActiveModel {
@@ -78,6 +121,15 @@ pub async fn create(
         success_rate_four_fours_headers: Set(success_rate_four_fours_headers),
         success_rate_four_fours_bodies: Set(success_rate_four_fours_bodies),
         success_rate_four_fours_receipts: Set(success_rate_four_fours_receipts),
+        num_errored: Set(num_errored),
+        num_pending: Set(num_pending),
+        num_stale_pending: Set(num_stale_pending),
+        num_beacon_audits: Set(Some(beacon.num_audits)),
+        success_rate_beacon_all: Set(Some(beacon.all)),
+        success_rate_beacon_bootstraps: Set(Some(beacon.bootstraps)),
+        success_rate_beacon_updates_by_range: Set(Some(beacon.updates_by_range)),
+        success_rate_beacon_finality_updates: Set(Some(beacon.finality_updates)),
+        success_rate_beacon_optimistic_updates: Set(Some(beacon.optimistic_updates)),
     };
     Ok(audit_stats.insert(conn).await?)
 }
@@ -94,8 +146,17 @@ pub async fn get_recent_stats(
     let beginning = Utc::now() - beginning_days_ago;
     let end = beginning + seven_days;
 
+    get_stats_between(beginning, end, conn).await
+}
+
+/// Returns the stats recorded within the given time range, oldest first.
+pub async fn get_stats_between(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    conn: &DatabaseConnection,
+) -> Result<Vec<Model>, DbErr> {
     Entity::find()
-        .filter(Column::Timestamp.gt(beginning))
+        .filter(Column::Timestamp.gt(start))
         .filter(Column::Timestamp.lt(end))
         .order_by_asc(Column::Timestamp)
         .all(conn)
@@ -1,11 +1,23 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.10.7
+use alloy_primitives::U256;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
-use sea_orm::{entity::prelude::*, ActiveValue::NotSet, Set};
+use enr::NodeId;
+use ethportal_api::utils::bytes::hex_encode;
+use sea_orm::{
+    entity::prelude::*, sea_query::Expr, ActiveValue::NotSet, DbBackend, FromQueryResult,
+    QueryOrder, QuerySelect, Set, Statement, TransactionTrait,
+};
+use serde::Serialize;
+use utoipa::ToSchema;
 
+use crate::census_client_stats::LivenessCounts;
 use crate::content::SubProtocol;
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+/// Census nodes inserted per statement when restoring archived nodes.
+const RESTORE_CHUNK_SIZE: usize = 1000;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
 #[sea_orm(table_name = "census")]
 pub struct Model {
     #[sea_orm(primary_key)]
@@ -13,10 +25,33 @@ pub struct Model {
     pub started_at: DateTime<Utc>,
     pub duration: i32,
     pub sub_network: SubProtocol,
+    /// Liveness check outcomes over all clients, unknown for censuses taken before they were
+    /// counted. See [LivenessCounts].
+    pub num_checked: Option<i32>,
+    pub num_client_info_parsed: Option<i32>,
+    pub num_pongs: Option<i32>,
+    pub num_error_pongs: Option<i32>,
+    pub num_undecodable_pongs: Option<i32>,
+    pub num_unexpected_pongs: Option<i32>,
+    pub num_timeouts: Option<i32>,
+    /// Diversity of the clients of the nodes found, unknown for censuses taken before it was
+    /// computed. See [ClientDiversity].
+    pub client_effective_number: Option<f32>,
+    pub client_entropy: Option<f32>,
+    /// When the nodes found by the census were moved to an archive file and deleted, if they
+    /// were.
+    pub nodes_archived_at: Option<DateTime<Utc>>,
+    /// The random node id the census started its search from, unknown for censuses taken
+    /// before it was kept.
+    pub target_node_id: Option<Vec<u8>>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::census_client_stats::Entity")]
+    CensusClientStats,
+    #[sea_orm(has_one = "super::census_incident::Entity")]
+    CensusIncident,
     #[sea_orm(has_many = "super::census_node::Entity")]
     CensusNode,
 }
@@ -41,7 +76,321 @@ pub async fn create(
         started_at: Set(started_at),
         duration: Set(duration as i32),
         sub_network: Set(subnetwork),
+        num_checked: NotSet,
+        num_client_info_parsed: NotSet,
+        num_pongs: NotSet,
+        num_error_pongs: NotSet,
+        num_undecodable_pongs: NotSet,
+        num_unexpected_pongs: NotSet,
+        num_timeouts: NotSet,
+        client_effective_number: NotSet,
+        client_entropy: NotSet,
+        nodes_archived_at: NotSet,
+        target_node_id: NotSet,
     };
 
     Ok(content_audit.insert(conn).await?)
 }
+
+/// Records the outcomes of all liveness checks of a census.
+pub async fn set_liveness_counts(
+    census: Model,
+    counts: LivenessCounts,
+    conn: &DatabaseConnection,
+) -> Result<Model> {
+    let mut census: ActiveModel = census.into();
+    census.num_checked = Set(Some(counts.checked));
+    census.num_client_info_parsed = Set(Some(counts.client_info_parsed));
+    census.num_pongs = Set(Some(counts.pongs));
+    census.num_error_pongs = Set(Some(counts.error_pongs));
+    census.num_undecodable_pongs = Set(Some(counts.undecodable_pongs));
+    census.num_unexpected_pongs = Set(Some(counts.unexpected_pongs));
+    census.num_timeouts = Set(Some(counts.timeouts));
+    Ok(census.update(conn).await?)
+}
+
+/// Records the node id a census started its search from.
+pub async fn set_target_node_id(
+    census: Model,
+    target_node_id: NodeId,
+    conn: &DatabaseConnection,
+) -> Result<Model> {
+    let mut census: ActiveModel = census.into();
+    census.target_node_id = Set(Some(target_node_id.raw().to_vec()));
+    Ok(census.update(conn).await?)
+}
+
+/// Nodes a census found at a log2 distance from its target node id.
+#[derive(Clone, Debug, PartialEq, Serialize, ToSchema)]
+#[schema(as = census::TargetDistanceBucket)]
+pub struct TargetDistanceBucket {
+    pub distance: u16,
+    pub node_count: i64,
+    /// The nodes there would be at the distance if the node ids found were spread uniformly.
+    pub expected_node_count: f64,
+}
+
+/// How the nodes found by a census are spread around the node id it started its search from.
+///
+/// A census that finds the network evenly has about half of its nodes at distance 256, a
+/// quarter at 255, and so on. More nodes than expected close to the target means the starting
+/// point biased what the census found.
+#[derive(Clone, Debug, PartialEq, Serialize, ToSchema)]
+#[schema(as = census::TargetCoverage)]
+pub struct TargetCoverage {
+    pub census_id: i32,
+    pub target_node_id: String,
+    pub total_nodes: i64,
+    /// Distance of the node closest to the target.
+    pub closest_distance: Option<u16>,
+    /// From distance 256 down to where no nodes are found or expected.
+    #[schema(inline)]
+    pub buckets: Vec<TargetDistanceBucket>,
+}
+
+/// Log2 of the XOR distance between two node ids, as used for routing table buckets.
+pub fn log2_distance(a: &[u8], b: &[u8]) -> u16 {
+    let distance = U256::from_be_slice(a) ^ U256::from_be_slice(b);
+    (256 - distance.leading_zeros()) as u16
+}
+
+#[derive(FromQueryResult)]
+struct CensusNodeId {
+    node_id: Vec<u8>,
+}
+
+/// Returns how the nodes found by a census are spread around its target node id, or None if
+/// the census or its target is unknown.
+pub async fn get_target_coverage(
+    census_id: i32,
+    exclude_self: bool,
+    conn: &DatabaseConnection,
+) -> Result<Option<TargetCoverage>> {
+    let Some(target_node_id) = Entity::find_by_id(census_id)
+        .one(conn)
+        .await?
+        .and_then(|census| census.target_node_id)
+    else {
+        return Ok(None);
+    };
+    let node_ids = CensusNodeId::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "
+        SELECT node.node_id
+        FROM census_node
+        JOIN record ON record.id = census_node.record_id
+        JOIN node ON node.id = record.node_id
+        WHERE census_node.census_id = $1
+            AND NOT (census_node.is_self AND $2)
+        ",
+        vec![census_id.into(), exclude_self.into()],
+    ))
+    .all(conn)
+    .await?;
+
+    let mut node_counts = [0i64; 257];
+    for census_node in &node_ids {
+        node_counts[log2_distance(&target_node_id, &census_node.node_id) as usize] += 1;
+    }
+    let total_nodes = node_ids.len() as i64;
+    let closest_distance = (0..=256u16).find(|distance| node_counts[*distance as usize] > 0);
+    let mut buckets = vec![];
+    for distance in (0..=256u16).rev() {
+        // A uniformly random node id is at distance d with probability 2^(d-257).
+        let expected_node_count = if distance == 0 {
+            0.0
+        } else {
+            total_nodes as f64 * 2f64.powi(distance as i32 - 257)
+        };
+        let past_closest = !closest_distance.is_some_and(|closest| distance >= closest);
+        if past_closest && expected_node_count < 0.5 {
+            break;
+        }
+        buckets.push(TargetDistanceBucket {
+            distance,
+            node_count: node_counts[distance as usize],
+            expected_node_count,
+        });
+    }
+    Ok(Some(TargetCoverage {
+        census_id,
+        target_node_id: hex_encode(target_node_id),
+        total_nodes,
+        closest_distance,
+        buckets,
+    }))
+}
+
+/// Diversity of the clients of a census's nodes, over the nodes whose client is known.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClientDiversity {
+    /// Inverse of the Herfindahl-Hirschman index: the number of equally sized clients that
+    /// would be as concentrated. It ranges from 1, for a single client, to the number of
+    /// clients, when all have as many nodes.
+    pub effective_number: f32,
+    /// Shannon entropy of the client shares, in bits.
+    pub entropy: f32,
+}
+
+impl ClientDiversity {
+    /// Computes the diversity from the number of nodes of each client. Returns None if there
+    /// are no nodes.
+    pub fn from_counts(counts: impl IntoIterator<Item = usize>) -> Option<Self> {
+        let counts: Vec<f64> = counts
+            .into_iter()
+            .filter(|count| *count > 0)
+            .map(|count| count as f64)
+            .collect();
+        let total: f64 = counts.iter().sum();
+        if total == 0.0 {
+            return None;
+        }
+        let shares = counts.iter().map(|count| count / total);
+        let herfindahl: f64 = shares.clone().map(|share| share * share).sum();
+        let entropy: f64 = shares.map(|share| -share * share.log2()).sum();
+        Some(ClientDiversity {
+            effective_number: (1.0 / herfindahl) as f32,
+            entropy: entropy as f32,
+        })
+    }
+}
+
+/// Records the client diversity of a census.
+pub async fn set_client_diversity(
+    census: Model,
+    diversity: ClientDiversity,
+    conn: &DatabaseConnection,
+) -> Result<Model> {
+    let mut census: ActiveModel = census.into();
+    census.client_effective_number = Set(Some(diversity.effective_number));
+    census.client_entropy = Set(Some(diversity.entropy));
+    Ok(census.update(conn).await?)
+}
+
+/// Client diversity of a census.
+#[derive(Clone, Debug, FromQueryResult, Serialize, ToSchema)]
+#[schema(as = census::CensusClientDiversity)]
+pub struct CensusClientDiversity {
+    pub started_at: DateTime<Utc>,
+    pub effective_number: f32,
+    pub entropy: f32,
+}
+
+/// Returns the client diversity of each census of a subnetwork started within the time range,
+/// oldest first. Censuses without a computed diversity are left out.
+pub async fn get_client_diversity(
+    sub_network: SubProtocol,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    conn: &DatabaseConnection,
+) -> Result<Vec<CensusClientDiversity>> {
+    Ok(Entity::find()
+        .select_only()
+        .column(Column::StartedAt)
+        .column_as(Column::ClientEffectiveNumber, "effective_number")
+        .column_as(Column::ClientEntropy, "entropy")
+        .filter(Column::SubNetwork.eq(sub_network))
+        .filter(Column::StartedAt.gte(start))
+        .filter(Column::StartedAt.lt(end))
+        .filter(Column::ClientEffectiveNumber.is_not_null())
+        .filter(Column::ClientEntropy.is_not_null())
+        .order_by_asc(Column::StartedAt)
+        .into_model::<CensusClientDiversity>()
+        .all(conn)
+        .await?)
+}
+
+/// Number of nodes found by a census.
+#[derive(Clone, Debug, FromQueryResult)]
+pub struct CensusNodeCount {
+    pub census_id: i32,
+    pub started_at: DateTime<Utc>,
+    pub node_count: i64,
+}
+
+/// Returns the number of nodes found by each census of a subnetwork started within the time
+/// range, oldest first.
+pub async fn get_node_counts(
+    sub_network: SubProtocol,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    conn: &DatabaseConnection,
+) -> Result<Vec<CensusNodeCount>> {
+    Ok(
+        CensusNodeCount::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT census.id AS census_id, census.started_at, COUNT(census_node.id) AS node_count
+            FROM census
+            LEFT JOIN census_node ON census_node.census_id = census.id
+            WHERE census.sub_network = $1
+                AND census.started_at >= $2
+                AND census.started_at < $3
+            GROUP BY census.id
+            ORDER BY census.started_at ASC",
+            vec![sub_network.into(), start.into(), end.into()],
+        ))
+        .all(conn)
+        .await?,
+    )
+}
+
+/// Returns the censuses started before the given time whose nodes have not been archived,
+/// oldest first.
+pub async fn get_unarchived_before(
+    before: DateTime<Utc>,
+    conn: &DatabaseConnection,
+) -> Result<Vec<Model>> {
+    Ok(Entity::find()
+        .filter(Column::StartedAt.lt(before))
+        .filter(Column::NodesArchivedAt.is_null())
+        .order_by_asc(Column::StartedAt)
+        .all(conn)
+        .await?)
+}
+
+/// Deletes the nodes found by a census, once they are archived, and records when. The census
+/// itself and the stats computed from its nodes are kept. Returns the number of nodes deleted.
+pub async fn archive_nodes(census_id: i32, conn: &DatabaseConnection) -> Result<u64> {
+    let txn = conn.begin().await?;
+    let deleted = super::census_node::Entity::delete_many()
+        .filter(super::census_node::Column::CensusId.eq(census_id))
+        .exec(&txn)
+        .await?
+        .rows_affected;
+    Entity::update_many()
+        .col_expr(Column::NodesArchivedAt, Expr::current_timestamp().into())
+        .filter(Column::Id.eq(census_id))
+        .exec(&txn)
+        .await?;
+    txn.commit().await?;
+    Ok(deleted)
+}
+
+/// Puts back the archived nodes of a census. Records must already exist for the nodes' ENRs.
+pub async fn restore_nodes(
+    census_id: i32,
+    nodes: Vec<super::census_node::Model>,
+    conn: &DatabaseConnection,
+) -> Result<()> {
+    let txn = conn.begin().await?;
+    for chunk in nodes.chunks(RESTORE_CHUNK_SIZE) {
+        super::census_node::Entity::insert_many(chunk.iter().map(|node| {
+            let mut node: super::census_node::ActiveModel = node.clone().into();
+            node.id = NotSet;
+            node.census_id = Set(census_id);
+            node
+        }))
+        .exec_without_returning(&txn)
+        .await?;
+    }
+    Entity::update_many()
+        .col_expr(
+            Column::NodesArchivedAt,
+            Expr::value(Option::<DateTime<Utc>>::None),
+        )
+        .filter(Column::Id.eq(census_id))
+        .exec(&txn)
+        .await?;
+    txn.commit().await?;
+    Ok(())
+}
@@ -1,10 +1,21 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.10.7
+use std::{collections::HashMap, str::FromStr};
+
 use alloy_primitives::U256;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use chrono::{DateTime, Utc};
-use sea_orm::{entity::prelude::*, ActiveValue::NotSet, Set};
+use ethportal_api::{types::enr::Enr, utils::bytes::hex_encode};
+use sea_orm::{
+    entity::prelude::*, ActiveValue::NotSet, DbBackend, FromQueryResult, QueryOrder, Set,
+    Statement, TransactionTrait,
+};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::{census_client_stats, content::SubProtocol};
 
-use crate::content::SubProtocol;
+/// Census nodes inserted per statement when saving a census.
+const INSERT_CHUNK_SIZE: usize = 1000;
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
 #[sea_orm(table_name = "census_node")]
@@ -17,6 +28,20 @@ pub struct Model {
     pub data_radius: Vec<u8>,
     pub data_radius_high: i64,
     pub sub_network: SubProtocol,
+    /// PING requests sent before the node answered, unknown for censuses taken before they
+    /// were counted.
+    pub ping_attempts: Option<i32>,
+    /// Time taken by the PING request the node answered, in milliseconds.
+    pub ping_latency_ms: Option<i32>,
+    /// Whether the node is run by this Glados deployment, like the Portal nodes used for
+    /// audits, rather than by the network's users.
+    pub is_self: bool,
+    /// Whether the UDP endpoint declared in the node's ENR answered a discv5 PING, unknown if
+    /// it was not probed.
+    pub udp_reachable: Option<bool>,
+    /// Whether the TCP endpoint declared in the node's ENR accepted a connection, unknown if
+    /// it was not probed.
+    pub tcp_reachable: Option<bool>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -45,28 +70,730 @@ impl Related<super::census::Entity> for Entity {
     }
 }
 
+impl Related<super::record::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Record.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
 
+/// How a census reached a node with PING requests.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PingMeasurement {
+    /// PING requests sent, including the one the node answered.
+    pub attempts: i32,
+    /// Time taken by the PING request the node answered, in milliseconds.
+    pub latency_ms: i32,
+}
+
+/// Whether the endpoints declared in a node's ENR answered direct probes.
+///
+/// Each endpoint is unknown if the ENR does not declare it or it was not probed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Reachability {
+    pub udp: Option<bool>,
+    pub tcp: Option<bool>,
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn create(
     census_id: i32,
     record_id: i32,
     data_radius: U256,
     surveyed_at: DateTime<Utc>,
     network: SubProtocol,
+    ping: PingMeasurement,
+    is_self: bool,
+    reachability: Reachability,
     conn: &DatabaseConnection,
 ) -> Result<Model> {
-    let data_radius_high: i64 = data_radius.wrapping_shr(193).to::<i64>();
-    let data_radius_raw: [u8; 32] = data_radius.to_be_bytes();
-
-    let census = ActiveModel {
-        id: NotSet,
-        census_id: Set(census_id),
-        record_id: Set(record_id),
-        surveyed_at: Set(surveyed_at),
-        data_radius: Set(data_radius_raw.into()),
-        data_radius_high: Set(data_radius_high),
-        sub_network: Set(network),
+    let node = NewCensusNode {
+        record_id,
+        data_radius,
+        surveyed_at,
+        ping,
+        is_self,
+        reachability,
     };
+    Ok(node
+        .into_active_model(census_id, network)
+        .insert(conn)
+        .await?)
+}
+
+/// A node found by a census, to be saved with [create_many].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NewCensusNode {
+    pub record_id: i32,
+    pub data_radius: U256,
+    pub surveyed_at: DateTime<Utc>,
+    pub ping: PingMeasurement,
+    pub is_self: bool,
+    pub reachability: Reachability,
+}
+
+impl NewCensusNode {
+    fn into_active_model(self, census_id: i32, network: SubProtocol) -> ActiveModel {
+        let data_radius_high: i64 = self.data_radius.wrapping_shr(193).to::<i64>();
+        let data_radius_raw: [u8; 32] = self.data_radius.to_be_bytes();
+        ActiveModel {
+            id: NotSet,
+            census_id: Set(census_id),
+            record_id: Set(self.record_id),
+            surveyed_at: Set(self.surveyed_at),
+            data_radius: Set(data_radius_raw.into()),
+            data_radius_high: Set(data_radius_high),
+            sub_network: Set(network),
+            ping_attempts: Set(Some(self.ping.attempts)),
+            ping_latency_ms: Set(Some(self.ping.latency_ms)),
+            is_self: Set(self.is_self),
+            udp_reachable: Set(self.reachability.udp),
+            tcp_reachable: Set(self.reachability.tcp),
+        }
+    }
+}
+
+/// Saves the nodes found by a census, in chunks of multi-row inserts within one transaction,
+/// rather than a round trip per node.
+pub async fn create_many(
+    census_id: i32,
+    network: SubProtocol,
+    nodes: &[NewCensusNode],
+    conn: &DatabaseConnection,
+) -> Result<()> {
+    let txn = conn.begin().await?;
+    for chunk in nodes.chunks(INSERT_CHUNK_SIZE) {
+        Entity::insert_many(
+            chunk
+                .iter()
+                .map(|node| node.into_active_model(census_id, network)),
+        )
+        .exec_without_returning(&txn)
+        .await?;
+    }
+    txn.commit().await?;
+    Ok(())
+}
+
+/// Returns the nodes found by a census, with the ENRs they were found with.
+pub async fn get_with_records(
+    census_id: i32,
+    conn: &DatabaseConnection,
+) -> Result<Vec<(Model, super::record::Model)>> {
+    Ok(Entity::find()
+        .find_also_related(super::record::Entity)
+        .filter(Column::CensusId.eq(census_id))
+        .order_by_asc(Column::Id)
+        .all(conn)
+        .await?
+        .into_iter()
+        .filter_map(|(node, record)| record.map(|record| (node, record)))
+        .collect())
+}
+
+#[derive(FromQueryResult)]
+struct CensusNodeWithEnr {
+    id: i32,
+    node_id: Vec<u8>,
+    raw: String,
+    data_radius: Vec<u8>,
+    surveyed_at: DateTime<Utc>,
+}
+
+/// A node found by a census, as exported for offline analysis. The client details are read
+/// from the ENR the node was found with, and are None if it does not give them.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, ToSchema)]
+#[schema(as = census_node::ExportedCensusNode)]
+pub struct ExportedCensusNode {
+    /// The census_node row, to page through a census by.
+    #[serde(skip)]
+    pub id: i32,
+    pub node_id: String,
+    pub enr: String,
+    pub client: Option<String>,
+    pub client_version: Option<String>,
+    pub os: Option<String>,
+    /// Hex encoded, big-endian.
+    pub data_radius: String,
+    pub protocol_versions: Option<Vec<u8>>,
+    pub surveyed_at: DateTime<Utc>,
+}
+
+impl From<CensusNodeWithEnr> for ExportedCensusNode {
+    fn from(node: CensusNodeWithEnr) -> Self {
+        let enr = Enr::from_str(&node.raw).ok();
+        ExportedCensusNode {
+            id: node.id,
+            node_id: hex_encode(&node.node_id),
+            client: enr
+                .as_ref()
+                .and_then(census_client_stats::enr_client)
+                .map(str::to_owned),
+            client_version: enr
+                .as_ref()
+                .and_then(census_client_stats::enr_client_version),
+            os: enr.as_ref().and_then(census_client_stats::enr_client_os),
+            protocol_versions: enr
+                .as_ref()
+                .and_then(census_client_stats::enr_protocol_versions),
+            enr: node.raw,
+            data_radius: hex_encode(&node.data_radius),
+            surveyed_at: node.surveyed_at,
+        }
+    }
+}
+
+/// Returns up to `limit` nodes of a census following the node with id `after_id`, by id, for
+/// exporting a census a page at a time.
+pub async fn get_export_page(
+    census_id: i32,
+    after_id: i32,
+    limit: u64,
+    conn: &DatabaseConnection,
+) -> Result<Vec<ExportedCensusNode>> {
+    let nodes = CensusNodeWithEnr::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "SELECT
+            census_node.id,
+            node.node_id,
+            record.raw,
+            census_node.data_radius,
+            census_node.surveyed_at
+        FROM census_node
+        JOIN record ON record.id = census_node.record_id
+        JOIN node ON node.id = record.node_id
+        WHERE census_node.census_id = $1 AND census_node.id > $2
+        ORDER BY census_node.id
+        LIMIT $3",
+        vec![census_id.into(), after_id.into(), (limit as i64).into()],
+    ))
+    .all(conn)
+    .await?;
+    Ok(nodes.into_iter().map(ExportedCensusNode::from).collect())
+}
+
+/// How reliably a census reached the nodes it found.
+#[derive(Clone, Debug, FromQueryResult, PartialEq, Serialize, ToSchema)]
+#[schema(as = census_node::CensusPingSummary)]
+pub struct CensusPingSummary {
+    pub census_id: i32,
+    pub started_at: DateTime<Utc>,
+    /// Nodes found by the census with a recorded number of PING attempts.
+    pub nodes: i64,
+    /// Nodes that only answered after more than one PING request.
+    pub retried_nodes: i64,
+    pub average_latency_ms: Option<f64>,
+    pub p95_latency_ms: Option<f64>,
+}
+
+impl CensusPingSummary {
+    /// Percentage of the nodes that needed PING retries, or None for censuses taken before
+    /// attempts were counted.
+    pub fn retried_percent(&self) -> Option<f64> {
+        if self.nodes == 0 {
+            return None;
+        }
+        Some(100.0 * self.retried_nodes as f64 / self.nodes as f64)
+    }
+}
+
+/// Returns the PING reliability of the latest censuses of a subnetwork, newest first.
+pub async fn get_ping_summaries(
+    sub_network: SubProtocol,
+    limit: u64,
+    conn: &DatabaseConnection,
+) -> Result<Vec<CensusPingSummary>> {
+    Ok(
+        CensusPingSummary::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT
+                census.id AS census_id,
+                census.started_at,
+                COUNT(census_node.ping_attempts) AS nodes,
+                COUNT(*) FILTER (WHERE census_node.ping_attempts > 1) AS retried_nodes,
+                AVG(census_node.ping_latency_ms)::DOUBLE PRECISION AS average_latency_ms,
+                PERCENTILE_CONT(0.95) WITHIN GROUP (ORDER BY census_node.ping_latency_ms)
+                    AS p95_latency_ms
+            FROM (
+                SELECT id, started_at FROM census
+                WHERE sub_network = $1
+                ORDER BY started_at DESC
+                LIMIT $2
+            ) AS census
+            LEFT JOIN census_node ON census_node.census_id = census.id
+            GROUP BY census.id, census.started_at
+            ORDER BY census.started_at DESC",
+            vec![sub_network.into(), (limit as i64).into()],
+        ))
+        .all(conn)
+        .await?,
+    )
+}
+
+/// How reliably a node was found by the latest censuses of a subnetwork.
+#[derive(Clone, Debug, FromQueryResult, PartialEq, Serialize, ToSchema)]
+#[schema(as = census_node::NodeReliability)]
+pub struct NodeReliability {
+    /// Latest censuses of the subnetwork considered.
+    pub num_censuses: i64,
+    /// Censuses that found the node alive.
+    pub num_present: i64,
+    /// Censuses in which the node only answered after more than one PING request.
+    pub num_retried: i64,
+    /// Censuses in which the node was found, but then failed its liveness check.
+    pub num_dropped: i64,
+    pub average_ping_attempts: Option<f64>,
+    pub average_latency_ms: Option<f64>,
+}
 
-    Ok(census.insert(conn).await?)
+impl NodeReliability {
+    /// Fraction of the censuses that found the node alive.
+    pub fn presence(&self) -> Option<f64> {
+        if self.num_censuses == 0 {
+            return None;
+        }
+        Some(self.num_present as f64 / self.num_censuses as f64)
+    }
+
+    /// Fraction of the censuses in which the node answered the first PING request, from 0 for
+    /// a node that is never reachable to 1 for one that always is.
+    ///
+    /// Censuses taken before PING attempts were counted count as answered at once.
+    pub fn score(&self) -> Option<f64> {
+        if self.num_censuses == 0 {
+            return None;
+        }
+        Some((self.num_present - self.num_retried) as f64 / self.num_censuses as f64)
+    }
+}
+
+/// Returns how reliably the latest `num_censuses` censuses of a subnetwork found a node.
+pub async fn get_node_reliability(
+    node_id: i32,
+    sub_network: SubProtocol,
+    num_censuses: u64,
+    conn: &DatabaseConnection,
+) -> Result<NodeReliability> {
+    let reliability = NodeReliability::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "WITH recent_census AS (
+            SELECT id FROM census
+            WHERE sub_network = $2
+            ORDER BY started_at DESC
+            LIMIT $3
+        )
+        SELECT
+            (SELECT COUNT(*) FROM recent_census) AS num_censuses,
+            COUNT(DISTINCT census_node.census_id) AS num_present,
+            COUNT(DISTINCT census_node.census_id) FILTER (WHERE census_node.ping_attempts > 1)
+                AS num_retried,
+            (
+                SELECT COUNT(DISTINCT census_node_error.census_id)
+                FROM census_node_error
+                JOIN record ON record.id = census_node_error.record_id
+                WHERE record.node_id = $1
+                    AND census_node_error.census_id IN (SELECT id FROM recent_census)
+            ) AS num_dropped,
+            AVG(census_node.ping_attempts)::DOUBLE PRECISION AS average_ping_attempts,
+            AVG(census_node.ping_latency_ms)::DOUBLE PRECISION AS average_latency_ms
+        FROM census_node
+        JOIN record ON record.id = census_node.record_id
+        WHERE record.node_id = $1
+            AND census_node.census_id IN (SELECT id FROM recent_census)",
+        vec![
+            node_id.into(),
+            sub_network.into(),
+            (num_censuses as i64).into(),
+        ],
+    ))
+    .one(conn)
+    .await?;
+    reliability.ok_or_else(|| anyhow!("No reliability returned for node {node_id}"))
+}
+
+#[derive(FromQueryResult)]
+struct CensusNodeId {
+    node_id: Vec<u8>,
+}
+
+/// Returns the ids of the nodes found by the censuses of a subnetwork started within the time
+/// range.
+pub async fn get_node_ids_by_subprotocol(
+    sub_network: SubProtocol,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    conn: &DatabaseConnection,
+) -> Result<Vec<Vec<u8>>> {
+    let node_ids = CensusNodeId::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "SELECT DISTINCT node.node_id
+        FROM census
+        JOIN census_node ON census_node.census_id = census.id
+        JOIN record ON record.id = census_node.record_id
+        JOIN node ON node.id = record.node_id
+        WHERE census.sub_network = $1
+            AND census.started_at >= $2
+            AND census.started_at < $3
+        ORDER BY node.node_id",
+        vec![sub_network.into(), start.into(), end.into()],
+    ))
+    .all(conn)
+    .await?;
+    Ok(node_ids.into_iter().map(|node| node.node_id).collect())
+}
+
+#[derive(FromQueryResult)]
+struct RawEnr {
+    raw: String,
+}
+
+/// Returns the base64 encoded ENRs of the nodes found by a census.
+pub async fn get_enrs(census_id: i32, conn: &DatabaseConnection) -> Result<Vec<String>> {
+    let enrs = RawEnr::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "SELECT record.raw
+        FROM census_node
+        JOIN record ON record.id = census_node.record_id
+        WHERE census_node.census_id = $1",
+        vec![census_id.into()],
+    ))
+    .all(conn)
+    .await?;
+    Ok(enrs.into_iter().map(|enr| enr.raw).collect())
+}
+
+#[derive(FromQueryResult)]
+struct NodeRadius {
+    node_id: Vec<u8>,
+    data_radius: Vec<u8>,
+}
+
+/// Returns the node ids and radii of the nodes found by a census.
+pub async fn get_node_radii(
+    census_id: i32,
+    conn: &DatabaseConnection,
+) -> Result<Vec<(U256, U256)>> {
+    let radii = NodeRadius::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "SELECT node.node_id, census_node.data_radius
+        FROM census_node
+        JOIN record ON record.id = census_node.record_id
+        JOIN node ON node.id = record.node_id
+        WHERE census_node.census_id = $1",
+        vec![census_id.into()],
+    ))
+    .all(conn)
+    .await?;
+    Ok(radii
+        .into_iter()
+        .map(|node| {
+            (
+                U256::from_be_slice(&node.node_id),
+                U256::from_be_slice(&node.data_radius),
+            )
+        })
+        .collect())
+}
+
+#[derive(FromQueryResult)]
+struct CensusNodePair {
+    node_id: Vec<u8>,
+    from_enr: Option<String>,
+    to_enr: Option<String>,
+    from_radius: Option<Vec<u8>>,
+    to_radius: Option<Vec<u8>>,
+}
+
+/// A node that differs between two censuses, as found by each of them.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, ToSchema)]
+#[schema(as = census_node::CensusNodeChange)]
+pub struct CensusNodeChange {
+    pub node_id: String,
+    pub from_enr: Option<String>,
+    pub to_enr: Option<String>,
+    pub from_client_version: Option<String>,
+    pub to_client_version: Option<String>,
+    pub from_radius: Option<String>,
+    pub to_radius: Option<String>,
+}
+
+/// The nodes that joined, left or changed between two censuses.
+///
+/// A node whose client version and radius both changed is listed under both.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, ToSchema)]
+#[schema(as = census_node::CensusDiff)]
+pub struct CensusDiff {
+    #[schema(inline)]
+    pub joined: Vec<CensusNodeChange>,
+    #[schema(inline)]
+    pub left: Vec<CensusNodeChange>,
+    #[schema(inline)]
+    pub client_version_changed: Vec<CensusNodeChange>,
+    #[schema(inline)]
+    pub radius_changed: Vec<CensusNodeChange>,
+}
+
+/// Client version from a base64 encoded ENR, None if it has none or can't be decoded.
+fn raw_enr_client_version(raw: &str) -> Option<String> {
+    census_client_stats::enr_client_version(&Enr::from_str(raw).ok()?)
+}
+
+/// Returns the nodes that joined, left, changed client version, or changed radius from one
+/// census to another, each list ordered by node id.
+pub async fn get_diff(
+    from_census_id: i32,
+    to_census_id: i32,
+    include_archived: bool,
+    conn: &DatabaseConnection,
+) -> Result<CensusDiff> {
+    let pairs = CensusNodePair::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "WITH census_nodes AS (
+            SELECT census_node.census_id, node.node_id, record.raw, census_node.data_radius
+            FROM census_node
+            JOIN record ON record.id = census_node.record_id
+            JOIN node ON node.id = record.node_id
+            WHERE census_node.census_id IN ($1, $2) AND ($3 OR NOT node.archived)
+        )
+        SELECT
+            COALESCE(from_nodes.node_id, to_nodes.node_id) AS node_id,
+            from_nodes.raw AS from_enr,
+            to_nodes.raw AS to_enr,
+            from_nodes.data_radius AS from_radius,
+            to_nodes.data_radius AS to_radius
+        FROM (SELECT * FROM census_nodes WHERE census_id = $1) AS from_nodes
+        FULL OUTER JOIN (SELECT * FROM census_nodes WHERE census_id = $2) AS to_nodes
+            ON to_nodes.node_id = from_nodes.node_id
+        WHERE from_nodes.node_id IS NULL
+            OR to_nodes.node_id IS NULL
+            OR from_nodes.raw <> to_nodes.raw
+            OR from_nodes.data_radius <> to_nodes.data_radius
+        ORDER BY node_id",
+        vec![
+            from_census_id.into(),
+            to_census_id.into(),
+            include_archived.into(),
+        ],
+    ))
+    .all(conn)
+    .await?;
+
+    let mut diff = CensusDiff::default();
+    for pair in pairs {
+        let change = CensusNodeChange {
+            node_id: hex_encode(&pair.node_id),
+            from_client_version: pair.from_enr.as_deref().and_then(raw_enr_client_version),
+            to_client_version: pair.to_enr.as_deref().and_then(raw_enr_client_version),
+            from_enr: pair.from_enr,
+            to_enr: pair.to_enr,
+            from_radius: pair.from_radius.as_ref().map(hex_encode),
+            to_radius: pair.to_radius.as_ref().map(hex_encode),
+        };
+        match (&change.from_enr, &change.to_enr) {
+            (None, _) => diff.joined.push(change),
+            (_, None) => diff.left.push(change),
+            _ => {
+                if change.from_client_version != change.to_client_version {
+                    diff.client_version_changed.push(change.clone());
+                }
+                if change.from_radius != change.to_radius {
+                    diff.radius_changed.push(change);
+                }
+            }
+        }
+    }
+    Ok(diff)
+}
+
+/// A node whose declared endpoints could not be reached directly, although it answered the
+/// census through the Portal node.
+#[derive(Clone, Debug, FromQueryResult, Serialize, ToSchema)]
+#[schema(as = census_node::UnreachableEndpoint)]
+pub struct UnreachableEndpoint {
+    #[serde(skip)]
+    pub node_id: Vec<u8>,
+    pub raw: String,
+    pub udp_reachable: Option<bool>,
+    pub tcp_reachable: Option<bool>,
+}
+
+impl UnreachableEndpoint {
+    pub fn node_id_as_hex(&self) -> String {
+        hex_encode(&self.node_id)
+    }
+}
+
+/// Returns the nodes of a census with a declared UDP or TCP endpoint that did not answer its
+/// probe, ordered by node id.
+pub async fn get_unreachable_endpoints(
+    census_id: i32,
+    include_archived: bool,
+    conn: &DatabaseConnection,
+) -> Result<Vec<UnreachableEndpoint>> {
+    Ok(
+        UnreachableEndpoint::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT node.node_id, record.raw, census_node.udp_reachable, census_node.tcp_reachable
+            FROM census_node
+            JOIN record ON record.id = census_node.record_id
+            JOIN node ON node.id = record.node_id
+            WHERE census_node.census_id = $1
+                AND (census_node.udp_reachable = FALSE OR census_node.tcp_reachable = FALSE)
+                AND ($2 OR NOT node.archived)
+            ORDER BY node.node_id",
+            vec![census_id.into(), include_archived.into()],
+        ))
+        .all(conn)
+        .await?,
+    )
+}
+
+/// Trace outcome of a node that served the content it was asked for.
+pub const SERVED: &str = "served";
+/// Trace outcome of a node that answered with ENRs instead of the content.
+pub const NOT_FOUND: &str = "notFound";
+/// Trace outcome of a node that sent content which failed validation.
+pub const INVALID_CONTENT: &str = "invalidContent";
+
+/// How a node a trace shows being asked for some content answered, with the radius the node
+/// advertised in the census the audit was taken against.
+#[derive(FromQueryResult)]
+struct TracedRequest {
+    node_id: Vec<u8>,
+    content_id: Vec<u8>,
+    data_radius: Vec<u8>,
+    raw: String,
+    outcome: String,
+}
+
+/// How often a node failed to serve content within the radius it advertised.
+#[derive(Clone, Debug, PartialEq, Serialize, ToSchema)]
+#[schema(as = census_node::RadiusDiscrepancy)]
+pub struct RadiusDiscrepancy {
+    pub node_id: String,
+    pub client: String,
+    /// Traced requests to the node for content within its radius.
+    pub requests_in_radius: i64,
+    pub served: i64,
+    pub not_found: i64,
+    pub invalid_content: i64,
+    /// Share of the requests in radius the node answered with ENRs or invalid content, as a
+    /// percentage.
+    pub discrepancy_rate: f64,
+}
+
+/// Returns, for each node asked for content within its advertised radius by the traced audits
+/// of a subnetwork started within the time range, how often it did not serve it. Nodes are
+/// ranked by discrepancy rate, then by number of discrepancies.
+///
+/// A node's radius is taken from the census each audit was taken against, and nodes that census
+/// did not find are left out. Failed transfers other than invalid content are not counted, as
+/// the node may well have had the content.
+pub async fn get_radius_discrepancies(
+    sub_network: SubProtocol,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    conn: &DatabaseConnection,
+) -> Result<Vec<RadiusDiscrepancy>> {
+    let requests = TracedRequest::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "WITH traces AS (
+            SELECT
+                content_audit.census_id,
+                content.content_id,
+                content_audit.trace::jsonb AS trace
+            FROM content_audit
+            JOIN content ON content.id = content_audit.content_key
+            WHERE content_audit.created_at >= $1
+                AND content_audit.created_at < $2
+                AND content.protocol_id = $3
+                AND content_audit.census_id IS NOT NULL
+                AND content_audit.trace <> ''
+        ),
+        outcomes AS (
+            SELECT traces.census_id, traces.content_id, failure.key AS node_id, $6 AS outcome
+            FROM traces, jsonb_each(traces.trace->'failures') AS failure
+            WHERE failure.value->>'failure' = $6
+            UNION ALL
+            SELECT traces.census_id, traces.content_id, traces.trace->>'receivedFrom', $4
+            FROM traces
+            WHERE traces.trace->>'receivedFrom' IS NOT NULL
+                AND traces.trace->>'receivedFrom' <> traces.trace->>'origin'
+            UNION ALL
+            SELECT traces.census_id, traces.content_id, response.key, $5
+            FROM traces, jsonb_object_keys(traces.trace->'responses') AS response(key)
+            WHERE response.key <> traces.trace->>'origin'
+                AND response.key IS DISTINCT FROM traces.trace->>'receivedFrom'
+                AND traces.trace->'failures'->response.key IS NULL
+        )
+        SELECT
+            node.node_id,
+            outcomes.content_id,
+            census_node.data_radius,
+            record.raw,
+            outcomes.outcome
+        FROM outcomes
+        JOIN node ON node.node_id = decode(substr(outcomes.node_id, 3), 'hex')
+        JOIN record ON record.node_id = node.id
+        JOIN census_node
+            ON census_node.record_id = record.id
+            AND census_node.census_id = outcomes.census_id",
+        vec![
+            start.into(),
+            end.into(),
+            sub_network.into(),
+            SERVED.into(),
+            NOT_FOUND.into(),
+            INVALID_CONTENT.into(),
+        ],
+    ))
+    .all(conn)
+    .await?;
+
+    let mut by_node: HashMap<Vec<u8>, RadiusDiscrepancy> = HashMap::new();
+    for request in requests {
+        let distance =
+            U256::from_be_slice(&request.node_id) ^ U256::from_be_slice(&request.content_id);
+        if distance > U256::from_be_slice(&request.data_radius) {
+            continue;
+        }
+        let node = by_node
+            .entry(request.node_id.clone())
+            .or_insert_with(|| RadiusDiscrepancy {
+                node_id: hex_encode(&request.node_id),
+                client: Enr::from_str(&request.raw)
+                    .ok()
+                    .as_ref()
+                    .and_then(census_client_stats::enr_client)
+                    .unwrap_or(census_client_stats::UNKNOWN_CLIENT)
+                    .to_owned(),
+                requests_in_radius: 0,
+                served: 0,
+                not_found: 0,
+                invalid_content: 0,
+                discrepancy_rate: 0.0,
+            });
+        node.requests_in_radius += 1;
+        match request.outcome.as_str() {
+            SERVED => node.served += 1,
+            NOT_FOUND => node.not_found += 1,
+            _ => node.invalid_content += 1,
+        }
+    }
+    let mut discrepancies: Vec<RadiusDiscrepancy> = by_node
+        .into_values()
+        .map(|mut node| {
+            node.discrepancy_rate = (node.not_found + node.invalid_content) as f64 * 100.0
+                / node.requests_in_radius as f64;
+            node
+        })
+        .collect();
+    discrepancies.sort_by(|a, b| {
+        b.discrepancy_rate
+            .total_cmp(&a.discrepancy_rate)
+            .then((b.not_found + b.invalid_content).cmp(&(a.not_found + a.invalid_content)))
+            .then(a.node_id.cmp(&b.node_id))
+    });
+    Ok(discrepancies)
 }
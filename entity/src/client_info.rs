@@ -9,6 +9,13 @@ pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i32,
     pub version_info: String,
+    /// Client name parsed from the version string, like "trin", or "unknown". None until the
+    /// version strings recorded before they were parsed are parsed, see [parse_unparsed].
+    pub client_name: Option<String>,
+    /// Version parsed from the version string, like "0.1.0".
+    pub client_version: Option<String>,
+    /// Commit hash parsed from the version string, like "5a6e5b5".
+    pub client_commit: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -33,7 +40,104 @@ impl Related<super::node::Entity> for Entity {
 
 impl ActiveModelBehavior for ActiveModel {}
 
-pub async fn get_or_create(version_info: String, conn: &DatabaseConnection) -> Result<Model> {
+/// SQL expression for the name of the Portal client in `client_info`, like `trin` for
+/// `trin v0.1.0`, or `unknown` when there is no client.
+pub const CLIENT_NAME_SQL: &str = "COALESCE(client_info.client_name, 'unknown')";
+
+/// SQL expression for the name and version of the Portal client in `client_info`, like
+/// `trin v0.1.0` for `trin v0.1.0-5a6e5b5`, or the whole version string if it has no version.
+pub const CLIENT_VERSION_SQL: &str = "COALESCE(
+    client_info.client_name || ' v' || client_info.client_version,
+    client_info.version_info,
+    'unknown'
+)";
+
+/// SQL expression for the code of the Portal client in `value`, the RLP encoded "c" value of
+/// an ENR, as an integer: the first character of the client string. A client string of a single
+/// character is encoded as that byte alone, without the length prefix of longer strings.
+pub fn client_code_sql(value: &str) -> String {
+    format!(
+        "CAST(get_byte(substr(substr({value}, 1, 2), length(substr({value}, 1, 2)), 1), 0) AS INTEGER)"
+    )
+}
+
+/// The known Portal clients, by the code starting the "c" value of their ENRs.
+pub fn client_from_code(code: u8) -> Option<&'static str> {
+    match code {
+        b't' => Some("trin"),
+        b'f' => Some("fluffy"),
+        b'u' => Some("ultralight"),
+        b's' => Some("shisui"),
+        _ => None,
+    }
+}
+
+/// A client name, version and commit hash, parsed from a version string.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ClientVersion {
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub commit: Option<String>,
+}
+
+impl ClientVersion {
+    /// Parses the version strings of Portal clients, either from `web3_clientVersion`, like
+    /// `trin v0.1.1-5a6e5b5` or `fluffy/v0.1.0-28f7b6e/linux-amd64/Nim-2.0.14`, or from the "c"
+    /// value of an ENR, like `t 0.1.1-5a6e5b5`.
+    pub fn parse(version_string: &str) -> Self {
+        let mut parts = version_string
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .filter(|part| !part.is_empty());
+        let name = parts.next().and_then(|name| match name.as_bytes() {
+            [code] => client_from_code(code.to_ascii_lowercase()).map(str::to_owned),
+            _ if name.chars().all(|c| c.is_ascii_alphabetic()) => Some(name.to_lowercase()),
+            _ => None,
+        });
+        let Some(version) = parts.find_map(|part| {
+            let version = part.strip_prefix(['v', 'V']).unwrap_or(part);
+            version
+                .starts_with(|c: char| c.is_ascii_digit())
+                .then_some(version)
+        }) else {
+            return ClientVersion {
+                name,
+                ..Default::default()
+            };
+        };
+        // A trailing hex suffix of at least 7 characters is the commit, as in `0.1.1-5a6e5b5`.
+        match version.rsplit_once('-') {
+            Some((version, commit))
+                if commit.len() >= 7 && commit.chars().all(|c| c.is_ascii_hexdigit()) =>
+            {
+                ClientVersion {
+                    name,
+                    version: Some(version.to_owned()),
+                    commit: Some(commit.to_lowercase()),
+                }
+            }
+            _ => ClientVersion {
+                name,
+                version: Some(version.to_owned()),
+                commit: None,
+            },
+        }
+    }
+}
+
+/// Operating systems that the platform part of a version string may name.
+const OPERATING_SYSTEMS: [&str; 6] = ["linux", "windows", "macos", "darwin", "freebsd", "android"];
+
+/// Parses the operating system from the platform part of a version string, like `linux` in
+/// `fluffy/v0.1.0-28f7b6e/linux-amd64/Nim-2.0.14`. Returns None if it names none.
+pub fn parse_os(version_string: &str) -> Option<String> {
+    version_string
+        .split(|c: char| c.is_whitespace() || c == '/')
+        .filter_map(|part| part.split(['-', '_']).next())
+        .map(str::to_ascii_lowercase)
+        .find(|os| OPERATING_SYSTEMS.contains(&os.as_str()))
+}
+
+pub async fn get_or_create<C: ConnectionTrait>(version_info: String, conn: &C) -> Result<Model> {
     // First try to lookup an existing entry.
     if let Some(client_info) = Entity::find()
         .filter(Column::VersionInfo.eq(version_info.to_owned()))
@@ -45,9 +149,83 @@ pub async fn get_or_create(version_info: String, conn: &DatabaseConnection) -> R
     }
 
     // If no record exists, create one and return it
+    let parsed = ClientVersion::parse(&version_info);
     let client_info = ActiveModel {
         id: NotSet,
         version_info: Set(version_info.to_owned()),
+        client_name: Set(Some(parsed.name.unwrap_or_else(|| "unknown".to_owned()))),
+        client_version: Set(parsed.version),
+        client_commit: Set(parsed.commit),
     };
     Ok(client_info.insert(conn).await?)
 }
+
+/// Parses the version strings of the clients recorded before version strings were parsed.
+///
+/// Returns the number of clients parsed.
+pub async fn parse_unparsed(conn: &DatabaseConnection) -> Result<usize> {
+    let unparsed = Entity::find()
+        .filter(Column::ClientName.is_null())
+        .all(conn)
+        .await?;
+    let count = unparsed.len();
+    for client_info in unparsed {
+        let parsed = ClientVersion::parse(&client_info.version_info);
+        let mut client_info: ActiveModel = client_info.into();
+        client_info.client_name = Set(Some(parsed.name.unwrap_or_else(|| "unknown".to_owned())));
+        client_info.client_version = Set(parsed.version);
+        client_info.client_commit = Set(parsed.commit);
+        client_info.update(conn).await?;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_os, ClientVersion};
+
+    fn parsed(name: &str, version: Option<&str>, commit: Option<&str>) -> ClientVersion {
+        ClientVersion {
+            name: Some(name.to_owned()),
+            version: version.map(str::to_owned),
+            commit: commit.map(str::to_owned),
+        }
+    }
+
+    #[test]
+    fn test_parse_client_version() {
+        assert_eq!(
+            ClientVersion::parse("trin v0.1.1-5a6e5b5"),
+            parsed("trin", Some("0.1.1"), Some("5a6e5b5"))
+        );
+        assert_eq!(
+            ClientVersion::parse("fluffy/v0.1.0-28f7b6e/linux-amd64/Nim-2.0.14"),
+            parsed("fluffy", Some("0.1.0"), Some("28f7b6e"))
+        );
+        assert_eq!(
+            ClientVersion::parse("ultralight v0.0.1-rc.1"),
+            parsed("ultralight", Some("0.0.1-rc.1"), None)
+        );
+        // ENR "c" values start with a client code.
+        assert_eq!(
+            ClientVersion::parse("t 0.1.1-5a6e5b5"),
+            parsed("trin", Some("0.1.1"), Some("5a6e5b5"))
+        );
+        assert_eq!(ClientVersion::parse("f"), parsed("fluffy", None, None));
+        assert_eq!(ClientVersion::parse("???"), ClientVersion::default());
+    }
+
+    #[test]
+    fn test_parse_os() {
+        assert_eq!(
+            parse_os("fluffy/v0.1.0-28f7b6e/linux-amd64/Nim-2.0.14"),
+            Some("linux".to_owned())
+        );
+        assert_eq!(
+            parse_os("f v0.1.0-28f7b6e/Windows_x86_64"),
+            Some("windows".to_owned())
+        );
+        assert_eq!(parse_os("t 0.1.1-5a6e5b5"), None);
+        assert_eq!(parse_os(""), None);
+    }
+}
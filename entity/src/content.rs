@@ -5,11 +5,13 @@ use anyhow::Result;
 use chrono::{DateTime, Utc};
 use ethportal_api::utils::bytes::{hex_encode, hex_encode_compact};
 use ethportal_api::OverlayContentKey;
-use sea_orm::{entity::prelude::*, ActiveValue::NotSet, Set};
+use sea_orm::{entity::prelude::*, ActiveValue::NotSet, DbBackend, Set, Statement};
 use serde::Deserialize;
+use utoipa::ToSchema;
 
 /// Portal network sub-protocol. History, state, transactions etc.
-#[derive(Debug, Clone, Copy, Eq, PartialEq, EnumIter, DeriveActiveEnum, Deserialize)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, EnumIter, DeriveActiveEnum, Deserialize, ToSchema)]
+#[schema(as = content::SubProtocol)]
 #[sea_orm(rs_type = "i32", db_type = "Integer")]
 pub enum SubProtocol {
     History = 0,
@@ -55,6 +57,52 @@ pub struct Model {
     #[sea_orm(unique)]
     pub content_id: Vec<u8>,
     pub first_available_at: DateTime<Utc>,
+    /// Unknown for keys stored before sources were recorded.
+    pub source: Option<ContentSource>,
+    /// Hidden from dashboards, like keys injected during experiments, keeping their audits.
+    pub archived: bool,
+}
+
+/// Which part of Glados first stored a content key.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "i32", db_type = "Integer")]
+pub enum ContentSource {
+    /// Derived from the chain by glados-monitor following the head.
+    Monitor = 0,
+    /// Created by an audit strategy that looks up its own keys, like 4444s or Block.
+    Audit = 1,
+    /// Bulk imported from a range of blocks, or from a file of content keys.
+    Import = 2,
+    /// Synthetic data from glados-simulator.
+    Simulator = 3,
+}
+
+impl ContentSource {
+    pub fn as_text(&self) -> String {
+        match self {
+            ContentSource::Monitor => "Monitor".to_string(),
+            ContentSource::Audit => "Audit".to_string(),
+            ContentSource::Import => "Import".to_string(),
+            ContentSource::Simulator => "Simulator".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct InvalidContentSourceError;
+
+impl TryFrom<&String> for ContentSource {
+    type Error = InvalidContentSourceError;
+
+    fn try_from(value: &String) -> Result<Self, Self::Error> {
+        match value.to_lowercase().as_str() {
+            "monitor" => Ok(ContentSource::Monitor),
+            "audit" => Ok(ContentSource::Audit),
+            "import" => Ok(ContentSource::Import),
+            "simulator" => Ok(ContentSource::Simulator),
+            _ => Err(InvalidContentSourceError),
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -83,6 +131,7 @@ pub async fn get_or_create<T: OverlayContentKey>(
     sub_protocol: SubProtocol,
     content_key: &T,
     available_at: DateTime<Utc>,
+    source: ContentSource,
     conn: &DatabaseConnection,
 ) -> Result<Model> {
     // First try to lookup an existing entry.
@@ -103,10 +152,70 @@ pub async fn get_or_create<T: OverlayContentKey>(
         content_key: Set(content_key.to_bytes().to_vec()),
         first_available_at: Set(available_at),
         protocol_id: Set(sub_protocol),
+        source: Set(Some(source)),
+        archived: Set(false),
     };
     Ok(content_key.insert(conn).await?)
 }
 
+/// Archives or unarchives the given content keys, returning how many were changed.
+pub async fn set_archived(
+    content_keys: Vec<Vec<u8>>,
+    archived: bool,
+    conn: &DatabaseConnection,
+) -> Result<u64> {
+    let update = ActiveModel {
+        archived: Set(archived),
+        ..Default::default()
+    };
+    Ok(Entity::update_many()
+        .set(update)
+        .filter(Column::ContentKey.is_in(content_keys))
+        .filter(Column::Archived.ne(archived))
+        .exec(conn)
+        .await?
+        .rows_affected)
+}
+
+/// Deletes up to `batch_size` content keys first available before the given time, oldest
+/// first, with their audits and execution metadata. Keys with audits that block or propagation
+/// stats are computed from are kept. Returns the number of keys deleted.
+pub async fn delete_old_batch(
+    available_before: DateTime<Utc>,
+    batch_size: u64,
+    conn: &DatabaseConnection,
+) -> Result<u64> {
+    Ok(conn
+        .execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "WITH batch AS (
+                SELECT id FROM content
+                WHERE first_available_at < $1
+                    AND NOT EXISTS (
+                        SELECT 1 FROM propagation_audit
+                        WHERE propagation_audit.content_key = content.id
+                    )
+                    AND NOT EXISTS (
+                        SELECT 1 FROM content_audit
+                        JOIN block_audit ON content_audit.id IN (
+                            block_audit.header_audit_id,
+                            block_audit.body_audit_id,
+                            block_audit.receipts_audit_id
+                        )
+                        WHERE content_audit.content_key = content.id
+                    )
+                ORDER BY id ASC
+                LIMIT $2
+            ), metadata AS (
+                DELETE FROM execution_metadata WHERE content IN (SELECT id FROM batch)
+            )
+            DELETE FROM content WHERE id IN (SELECT id FROM batch)",
+            vec![available_before.into(), (batch_size as i64).into()],
+        ))
+        .await?
+        .rows_affected())
+}
+
 pub async fn get<T: OverlayContentKey>(
     content_key: &T,
     conn: &DatabaseConnection,
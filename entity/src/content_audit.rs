@@ -5,18 +5,25 @@ use anyhow::{bail, Result};
 use chrono::{DateTime, Utc};
 use clap::ValueEnum;
 use content::SubProtocol;
-use ethportal_api::{utils::bytes::hex_encode, OverlayContentKey};
+use ethportal_api::OverlayContentKey;
 use sea_orm::{
     entity::prelude::*, strum::IntoEnumIterator, ActiveValue::NotSet, DbBackend, DeriveActiveEnum,
-    FromQueryResult, Set, Statement, TryGetable,
+    FromQueryResult, QueryOrder, QuerySelect, Set, Statement, TryGetable,
 };
 use sea_query::{ArrayType, Nullable, SeaRc, ValueType, ValueTypeErr};
+use serde::Serialize;
 
 #[derive(Debug, Clone, Eq, PartialEq, EnumIter, DeriveActiveEnum)]
 #[sea_orm(rs_type = "i32", db_type = "Integer")]
 pub enum AuditResult {
     Failure = 0,
     Success = 1,
+    /// The audit could not be completed, e.g. the Portal node returned an error.
+    Errored = 2,
+    /// The audit has been started but no result has been recorded yet.
+    Pending = 3,
+    /// Content was returned, but did not match the canonical data.
+    InvalidContent = 4,
 }
 
 #[derive(Debug, Clone, Eq, Hash, PartialEq, EnumIter, DeriveActiveEnum, ValueEnum)]
@@ -39,10 +46,27 @@ pub enum HistorySelectionStrategy {
     /// 1. Not yet audited.
     /// 2. Sorted by date entered into glados database (oldest first).
     SelectOldestUnaudited = 3,
-    /// Perform a single audit for a previously audited content key.
+    /// Perform a single audit for a previously audited content key. Only used by the `audit`
+    /// command, so it cannot be selected as a strategy.
+    #[value(skip)]
     SpecificContentKey = 4,
     /// Perform audits of random fourfours data.
     FourFours = 5,
+    /// Walk every block from genesis to the chain head in segments, recording the
+    /// retrieval latency of each segment. Progress is stored so it resumes after restarts.
+    Sync = 6,
+    /// Audit the header, body and receipts of random blocks together, recording whether
+    /// each block was fully retrievable.
+    Block = 7,
+    /// Gossip content held by one Portal node, then audit it from another until it is found,
+    /// recording how long the content took to become available.
+    RoundTrip = 8,
+    /// Randomly selected content that was not audited recently, including content that was
+    /// never audited, to spread audits over as many keys as possible.
+    RandomUnaudited = 9,
+    /// Audit a sample of new content at fixed ages after it became available, recording how
+    /// fast content propagates through the network.
+    Propagation = 10,
 }
 
 impl From<i32> for HistorySelectionStrategy {
@@ -54,6 +78,11 @@ impl From<i32> for HistorySelectionStrategy {
             3 => HistorySelectionStrategy::SelectOldestUnaudited,
             4 => HistorySelectionStrategy::SpecificContentKey,
             5 => HistorySelectionStrategy::FourFours,
+            6 => HistorySelectionStrategy::Sync,
+            7 => HistorySelectionStrategy::Block,
+            8 => HistorySelectionStrategy::RoundTrip,
+            9 => HistorySelectionStrategy::RandomUnaudited,
+            10 => HistorySelectionStrategy::Propagation,
             _ => panic!("Invalid value for HistorySelectionStrategy"),
         }
     }
@@ -69,6 +98,11 @@ impl TryFrom<String> for HistorySelectionStrategy {
             "SelectOldestUnaudited" => Ok(HistorySelectionStrategy::SelectOldestUnaudited),
             "SpecificContentKey" => Ok(HistorySelectionStrategy::SpecificContentKey),
             "FourFours" => Ok(HistorySelectionStrategy::FourFours),
+            "Sync" => Ok(HistorySelectionStrategy::Sync),
+            "Block" => Ok(HistorySelectionStrategy::Block),
+            "RoundTrip" => Ok(HistorySelectionStrategy::RoundTrip),
+            "RandomUnaudited" => Ok(HistorySelectionStrategy::RandomUnaudited),
+            "Propagation" => Ok(HistorySelectionStrategy::Propagation),
             _ => bail!("Invalid value for HistorySelectionStrategy {}", value),
         }
     }
@@ -270,15 +304,13 @@ impl AuditResult {
         match self {
             AuditResult::Failure => "fail".to_string(),
             AuditResult::Success => "success".to_string(),
+            AuditResult::Errored => "errored".to_string(),
+            AuditResult::Pending => "pending".to_string(),
+            AuditResult::InvalidContent => "invalid".to_string(),
         }
     }
 }
 
-#[derive(FromQueryResult)]
-struct FailedKeysResult {
-    content_key: Vec<u8>,
-}
-
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
 #[sea_orm(table_name = "content_audit")]
 pub struct Model {
@@ -291,6 +323,25 @@ pub struct Model {
     pub strategy_used: Option<SelectionStrategy>,
     pub result: AuditResult,
     pub trace: String,
+    /// Whether the node was asked for a trace. Traced lookups take a different RPC path, so
+    /// their results are not directly comparable with untraced ones. An empty trace here means
+    /// the node returned none.
+    pub trace_requested: bool,
+    /// Why the audit errored, when known, like [STALE_PENDING_REASON].
+    pub error_reason: Option<String>,
+    /// The census that radii are taken from when displaying the trace. Set on insert to the
+    /// latest census of the content's subnetwork started before the audit.
+    pub census_id: Option<i32>,
+    /// Time from the selection of the content by a strategy to the start of the audit, in
+    /// milliseconds. Unknown for audits that were not selected by a strategy, or recorded
+    /// before the lag was measured.
+    pub selection_lag_ms: Option<i32>,
+    /// Time the Portal node took to answer the audit, in milliseconds. Unknown for audits that
+    /// are pending, or recorded before the duration was measured.
+    pub duration_ms: Option<i32>,
+    /// Time the audit waited for the Portal node's answer before erroring, in milliseconds, as
+    /// configured for the type of content. Unknown for audits recorded before it was recorded.
+    pub timeout_ms: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -319,6 +370,14 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     Node,
+    #[sea_orm(
+        belongs_to = "super::census::Entity",
+        from = "Column::CensusId",
+        to = "super::census::Column::Id",
+        on_update = "Cascade",
+        on_delete = "SetNull"
+    )]
+    Census,
 }
 
 impl Related<super::content::Entity> for Entity {
@@ -333,6 +392,18 @@ impl Related<super::client_info::Entity> for Entity {
     }
 }
 
+impl Related<super::census::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Census.def()
+    }
+}
+
+impl Related<super::node::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Node.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
 
 pub async fn create(
@@ -359,11 +430,344 @@ pub async fn create(
         created_at: Set(Utc::now()),
         result: Set(audit_result),
         strategy_used: Set(Some(strategy_used)),
+        trace_requested: Set(!trace_string.is_empty()),
         trace: Set(trace_string),
+        error_reason: NotSet,
+        census_id: NotSet,
+        selection_lag_ms: NotSet,
+        duration_ms: NotSet,
+        timeout_ms: NotSet,
     };
     Ok(content_audit.insert(conn).await?)
 }
 
+/// Records the start of an audit, before the Portal node has been queried.
+///
+/// The returned audit is `Pending` until [`set_result`] is called with the outcome. The time
+/// since `selected_at`, when the strategy selected the content, is recorded as its selection
+/// lag, along with the `timeout_ms` the Portal node is given to answer.
+#[allow(clippy::too_many_arguments)]
+pub async fn create_pending<C: ConnectionTrait>(
+    content_key_model_id: i32,
+    client_info_id: i32,
+    node_id: i32,
+    strategy_used: SelectionStrategy,
+    trace_requested: bool,
+    selected_at: Option<DateTime<Utc>>,
+    timeout_ms: Option<i32>,
+    conn: &C,
+) -> Result<Model> {
+    let created_at = Utc::now();
+    let selection_lag_ms = selected_at.map(|selected_at| {
+        (created_at - selected_at)
+            .num_milliseconds()
+            .clamp(0, i32::MAX.into()) as i32
+    });
+    let content_audit = ActiveModel {
+        id: NotSet,
+        content_key: Set(content_key_model_id),
+        client_info: Set(Some(client_info_id)),
+        node: Set(Some(node_id)),
+        created_at: Set(created_at),
+        result: Set(AuditResult::Pending),
+        strategy_used: Set(Some(strategy_used)),
+        trace: Set("".to_string()),
+        trace_requested: Set(trace_requested),
+        error_reason: NotSet,
+        census_id: NotSet,
+        selection_lag_ms: Set(selection_lag_ms),
+        duration_ms: NotSet,
+        timeout_ms: Set(timeout_ms),
+    };
+    Ok(content_audit.insert(conn).await?)
+}
+
+/// Percentiles of the time audits of one strategy waited between selection and start.
+#[derive(Clone, Debug, FromQueryResult, PartialEq)]
+pub struct SelectionLagPercentiles {
+    pub strategy_used: Option<SelectionStrategy>,
+    pub num_audits: i64,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: i32,
+}
+
+/// Returns the selection lag percentiles of the audits of each strategy started within the time
+/// range, leaving out audits without a measured lag.
+///
+/// Large lags mean the audit queue is backed up, so strategies select content faster than it
+/// is audited.
+pub async fn get_selection_lag_percentiles(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    conn: &DatabaseConnection,
+) -> Result<Vec<SelectionLagPercentiles>> {
+    Ok(
+        SelectionLagPercentiles::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT
+                strategy_used,
+                COUNT(*) AS num_audits,
+                PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY selection_lag_ms) AS p50_ms,
+                PERCENTILE_CONT(0.9) WITHIN GROUP (ORDER BY selection_lag_ms) AS p90_ms,
+                PERCENTILE_CONT(0.99) WITHIN GROUP (ORDER BY selection_lag_ms) AS p99_ms,
+                MAX(selection_lag_ms) AS max_ms
+            FROM content_audit
+            WHERE created_at >= $1 AND created_at < $2 AND selection_lag_ms IS NOT NULL
+            GROUP BY strategy_used
+            ORDER BY strategy_used",
+            vec![start.into(), end.into()],
+        ))
+        .all(conn)
+        .await?,
+    )
+}
+
+/// Percentiles of how long Portal nodes took to answer audits, for either one type of content
+/// or one auditing client.
+#[derive(Clone, Debug, FromQueryResult, PartialEq)]
+pub struct LatencyPercentiles {
+    /// First byte of the content keys, when grouped by type of content.
+    pub selector: Option<i32>,
+    /// Name of the auditing client, like `trin`, when grouped by client.
+    pub client: Option<String>,
+    pub num_audits: i64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+}
+
+/// Returns the duration percentiles of the subnetwork's audits created within the time range,
+/// both by type of content and by auditing client.
+///
+/// Errored and pending audits, and audits without a measured duration, are left out.
+pub async fn get_latency_percentiles(
+    sub_protocol: SubProtocol,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    conn: &DatabaseConnection,
+) -> Result<Vec<LatencyPercentiles>> {
+    Ok(
+        LatencyPercentiles::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            &format!(
+                "SELECT
+                    get_byte(content.content_key, 0) AS selector,
+                    {client} AS client,
+                    COUNT(*) AS num_audits,
+                    PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY content_audit.duration_ms)
+                        AS p50_ms,
+                    PERCENTILE_CONT(0.95) WITHIN GROUP (ORDER BY content_audit.duration_ms)
+                        AS p95_ms,
+                    PERCENTILE_CONT(0.99) WITHIN GROUP (ORDER BY content_audit.duration_ms)
+                        AS p99_ms
+                FROM content_audit
+                JOIN content ON content.id = content_audit.content_key
+                LEFT JOIN client_info ON client_info.id = content_audit.client_info
+                WHERE content.protocol_id = $1
+                    AND content_audit.created_at >= $2
+                    AND content_audit.created_at < $3
+                    AND content_audit.result IN (0, 1, 4)
+                    AND content_audit.duration_ms IS NOT NULL
+                GROUP BY GROUPING SETS ((get_byte(content.content_key, 0)), ({client}))
+                ORDER BY selector, client",
+                client = super::client_info::CLIENT_NAME_SQL
+            ),
+            vec![sub_protocol.into(), start.into(), end.into()],
+        ))
+        .all(conn)
+        .await?,
+    )
+}
+
+/// Records the outcome of a previously started audit, and how long the Portal node took to
+/// answer.
+///
+/// Accepts a transaction, so the result can be recorded atomically with other writes.
+pub async fn set_result<C: ConnectionTrait>(
+    audit: Model,
+    result: AuditResult,
+    trace_string: String,
+    duration_ms: Option<i32>,
+    conn: &C,
+) -> Result<Model> {
+    let mut content_audit: ActiveModel = audit.into();
+    content_audit.result = Set(result);
+    content_audit.trace = Set(trace_string);
+    content_audit.duration_ms = Set(duration_ms);
+    content_audit.error_reason = Set(None);
+    Ok(content_audit.update(conn).await?)
+}
+
+/// Postgres channel notified with the id of every audit whose result is recorded, so that
+/// dashboards can show audits as they finish. See the `content_audit_recorded` trigger.
+pub const AUDIT_RECORDED_CHANNEL: &str = "audit_recorded";
+
+/// An audit whose result was recorded, as shown on live dashboards.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct RecordedAudit {
+    pub id: i32,
+    pub sub_network: String,
+    pub content_key: String,
+    pub result: String,
+    pub strategy: String,
+    /// Version string of the client of the Portal node audited, if known.
+    pub client: Option<String>,
+    pub duration_ms: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Returns a recorded audit with its content key and client, or None if it does not exist.
+pub async fn get_recorded(
+    audit_id: i32,
+    conn: &DatabaseConnection,
+) -> Result<Option<RecordedAudit>> {
+    let Some((audit, Some(content))) = Entity::find_by_id(audit_id)
+        .find_also_related(content::Entity)
+        .one(conn)
+        .await?
+    else {
+        return Ok(None);
+    };
+    let client = match audit.client_info {
+        Some(client_info_id) => super::client_info::Entity::find_by_id(client_info_id)
+            .one(conn)
+            .await?
+            .map(|client_info| client_info.version_info),
+        None => None,
+    };
+    Ok(Some(RecordedAudit {
+        id: audit.id,
+        sub_network: content.protocol_id.as_text(),
+        content_key: content.key_as_hex(),
+        result: audit.result_label().to_owned(),
+        strategy: audit.strategy_as_text(),
+        client,
+        duration_ms: audit.duration_ms,
+        created_at: audit.created_at,
+    }))
+}
+
+/// Returns audits that are still `Pending` and were started before the given time,
+/// oldest first.
+pub async fn get_stuck_pending(
+    started_before: DateTime<Utc>,
+    limit: u64,
+    conn: &DatabaseConnection,
+) -> Result<Vec<Model>> {
+    Ok(Entity::find()
+        .filter(Column::Result.eq(AuditResult::Pending))
+        .filter(Column::CreatedAt.lt(started_before))
+        .order_by_asc(Column::CreatedAt)
+        .limit(limit)
+        .all(conn)
+        .await?)
+}
+
+/// Counts audits that are still `Pending` and were started before the given time.
+pub async fn count_stuck_pending(
+    started_before: DateTime<Utc>,
+    conn: &DatabaseConnection,
+) -> Result<u64> {
+    Ok(Entity::find()
+        .filter(Column::Result.eq(AuditResult::Pending))
+        .filter(Column::CreatedAt.lt(started_before))
+        .count(conn)
+        .await?)
+}
+
+/// Error reason of audits that stayed `Pending` for too long, usually because the audit worker
+/// crashed mid-audit.
+pub const STALE_PENDING_REASON: &str = "stale";
+
+/// Marks audits that are still `Pending` and were started before the given time as `Errored`,
+/// with [STALE_PENDING_REASON]. Returns the number of audits marked.
+pub async fn mark_stale_pending(
+    started_before: DateTime<Utc>,
+    conn: &DatabaseConnection,
+) -> Result<u64> {
+    let stale = ActiveModel {
+        result: Set(AuditResult::Errored),
+        error_reason: Set(Some(STALE_PENDING_REASON.to_owned())),
+        ..Default::default()
+    };
+    Ok(Entity::update_many()
+        .set(stale)
+        .filter(Column::Result.eq(AuditResult::Pending))
+        .filter(Column::CreatedAt.lt(started_before))
+        .exec(conn)
+        .await?
+        .rows_affected)
+}
+
+/// Error reason of audits that had not finished when the audit daemon shut down.
+pub const SHUTDOWN_REASON: &str = "shutdown";
+
+/// Marks the given audits as `Errored` with the reason, if they are still `Pending`. Returns the
+/// number of audits marked.
+pub async fn mark_pending_errored(
+    ids: &[i32],
+    reason: &str,
+    conn: &DatabaseConnection,
+) -> Result<u64> {
+    if ids.is_empty() {
+        return Ok(0);
+    }
+    let errored = ActiveModel {
+        result: Set(AuditResult::Errored),
+        error_reason: Set(Some(reason.to_owned())),
+        ..Default::default()
+    };
+    Ok(Entity::update_many()
+        .set(errored)
+        .filter(Column::Id.is_in(ids.iter().copied()))
+        .filter(Column::Result.eq(AuditResult::Pending))
+        .exec(conn)
+        .await?
+        .rows_affected)
+}
+
+/// Deletes up to `batch_size` audits started before the given time, oldest first. The latest
+/// audit of each content key is kept, so that `audit_latest` still knows how the key fared, and
+/// so are the audits that block and propagation stats are computed from. Returns the number of
+/// audits deleted.
+pub async fn delete_old_batch(
+    started_before: DateTime<Utc>,
+    batch_size: u64,
+    conn: &DatabaseConnection,
+) -> Result<u64> {
+    Ok(conn
+        .execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "DELETE FROM content_audit
+            WHERE id IN (
+                SELECT id FROM content_audit
+                WHERE created_at < $1
+                    AND NOT EXISTS (
+                        SELECT 1 FROM audit_latest WHERE audit_latest.audit_id = content_audit.id
+                    )
+                    AND NOT EXISTS (
+                        SELECT 1 FROM block_audit
+                        WHERE content_audit.id IN (
+                            block_audit.header_audit_id,
+                            block_audit.body_audit_id,
+                            block_audit.receipts_audit_id
+                        )
+                    )
+                    AND NOT EXISTS (
+                        SELECT 1 FROM propagation_audit
+                        WHERE propagation_audit.audit_id = content_audit.id
+                    )
+                ORDER BY id ASC
+                LIMIT $2
+            )",
+            vec![started_before.into(), (batch_size as i64).into()],
+        ))
+        .await?
+        .rows_affected())
+}
+
 pub async fn get_audits<T: OverlayContentKey>(
     content_key: &T,
     conn: &DatabaseConnection,
@@ -377,65 +781,218 @@ pub async fn get_audits<T: OverlayContentKey>(
         .await?)
 }
 
-pub async fn get_failed_keys(
-    subprotocol: SubProtocol,
-    strategy_used: String,
-    page: u32,
+/// Audit outcomes for one type of content, identified by the first byte of its keys.
+#[derive(Clone, Debug, FromQueryResult)]
+pub struct ContentTypeResults {
+    pub sub_protocol: SubProtocol,
+    pub selector: i32,
+    pub total: i64,
+    pub success: i64,
+    /// Includes content that did not match the canonical data.
+    pub failure: i64,
+    pub errored: i64,
+}
+
+impl ContentTypeResults {
+    /// Percentage of completed audits that succeeded, leaving out errored and pending audits.
+    pub fn success_percent(&self) -> f64 {
+        let completed = self.success + self.failure;
+        if completed == 0 {
+            return 0.0;
+        }
+        100.0 * self.success as f64 / completed as f64
+    }
+}
+
+/// Returns the outcomes of the audits created within the time range, by subnetwork and type of
+/// content.
+pub async fn get_results_by_content_type(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
     conn: &DatabaseConnection,
-) -> Result<Vec<String>> {
-    const PAGE_SIZE: u32 = 1000;
+) -> Result<Vec<ContentTypeResults>> {
+    Ok(
+        ContentTypeResults::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT
+                content.protocol_id AS sub_protocol,
+                get_byte(content.content_key, 0) AS selector,
+                COUNT(*) AS total,
+                COUNT(*) FILTER (WHERE content_audit.result = 1) AS success,
+                COUNT(*) FILTER (WHERE content_audit.result IN (0, 4)) AS failure,
+                COUNT(*) FILTER (WHERE content_audit.result = 2) AS errored
+            FROM content_audit
+            JOIN content ON content.id = content_audit.content_key
+            WHERE content_audit.created_at >= $1 AND content_audit.created_at < $2
+            GROUP BY sub_protocol, selector
+            ORDER BY sub_protocol, selector",
+            vec![start.into(), end.into()],
+        ))
+        .all(conn)
+        .await?,
+    )
+}
 
-    let subprotocol_strategy: SelectionStrategy = match subprotocol {
-        SubProtocol::History => SelectionStrategy::History(strategy_used.try_into()?),
-        SubProtocol::State => SelectionStrategy::State(strategy_used.try_into()?),
-        SubProtocol::Beacon => SelectionStrategy::Beacon(strategy_used.try_into()?),
-    };
+/// Audit outcomes within one hour of one day of the week.
+#[derive(Clone, Debug, FromQueryResult, PartialEq, Eq)]
+pub struct HourOfWeekResults {
+    /// ISO day of the week, from 1 (Monday) to 7 (Sunday).
+    pub day_of_week: i32,
+    /// Hour of the day, from 0 to 23.
+    pub hour: i32,
+    pub success: i64,
+    /// Includes content that did not match the canonical data.
+    pub failure: i64,
+}
 
-    let keys_result = FailedKeysResult::find_by_statement(Statement::from_sql_and_values(
-        DbBackend::Postgres,
-        "
-        SELECT
-              content.content_key
-        FROM content_audit
-        INNER JOIN content ON content.id = content_audit.content_key
-        WHERE
-            content_audit.result = 0 AND
-            content_audit.strategy_used = $1 AND
-            content_audit.created_at >= CURRENT_TIMESTAMP - INTERVAL '24 hours'
-        GROUP BY content.content_key
-        ORDER BY MAX(content_audit.created_at) DESC
-        LIMIT $2
-        OFFSET $3
-          content_key
-        FROM (
-          SELECT
-            content.content_key,
-            MAX(content_audit.created_at) AS created_at
-          FROM content_audit
-          JOIN content ON content.id = content_audit.content_key
-          WHERE
-            content_audit.result = 0  AND
-            content_audit.strategy_used = $1 AND
-            content_audit.created_at >= CURRENT_TIMESTAMP - INTERVAL '24 hours'
-          GROUP BY content.content_key
-        ) prep
-        ORDER BY created_at DESC
-        LIMIT $2
-        OFFSET $3
-        ",
-        vec![
-            subprotocol_strategy.into(),
-            PAGE_SIZE.into(),
-            ((page - 1) * PAGE_SIZE).into(),
-        ],
-    ))
-    .all(conn)
-    .await?;
-
-    Ok(keys_result
-        .into_iter()
-        .map(|result_item| hex_encode(result_item.content_key))
-        .collect::<Vec<String>>())
+/// Returns the outcomes of the subnetwork's audits created within the time range, by day of the
+/// week and hour of the day in the given timezone (e.g. `UTC` or `Europe/Berlin`).
+///
+/// Errored and pending audits are left out. Hours without completed audits are not returned.
+pub async fn get_results_by_hour_of_week(
+    sub_protocol: SubProtocol,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    timezone: &str,
+    conn: &DatabaseConnection,
+) -> Result<Vec<HourOfWeekResults>> {
+    Ok(
+        HourOfWeekResults::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT
+                EXTRACT(ISODOW FROM content_audit.created_at AT TIME ZONE $4)::INTEGER
+                    AS day_of_week,
+                EXTRACT(HOUR FROM content_audit.created_at AT TIME ZONE $4)::INTEGER AS hour,
+                COUNT(*) FILTER (WHERE content_audit.result = 1) AS success,
+                COUNT(*) FILTER (WHERE content_audit.result IN (0, 4)) AS failure
+            FROM content_audit
+            JOIN content ON content.id = content_audit.content_key
+            WHERE content.protocol_id = $1
+                AND content_audit.created_at >= $2
+                AND content_audit.created_at < $3
+                AND content_audit.result IN (0, 1, 4)
+            GROUP BY day_of_week, hour
+            ORDER BY day_of_week, hour",
+            vec![
+                sub_protocol.into(),
+                start.into(),
+                end.into(),
+                timezone.into(),
+            ],
+        ))
+        .all(conn)
+        .await?,
+    )
+}
+
+/// Audit outcomes for one type of content audited by one Portal client version.
+#[derive(Clone, Debug, FromQueryResult, PartialEq, Eq)]
+pub struct ContentTypeClientResults {
+    /// First byte of the content keys.
+    pub selector: i32,
+    /// Name and version of the auditing Portal client, like `trin v0.1.0`, or `unknown`.
+    pub client: String,
+    pub success: i64,
+    /// Includes content that did not match the canonical data.
+    pub failure: i64,
+}
+
+/// Returns the outcomes of the subnetwork's audits created within the time range, by type of
+/// content and auditing client.
+///
+/// Errored and pending audits are left out.
+pub async fn get_results_by_content_type_and_client(
+    sub_protocol: SubProtocol,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    conn: &DatabaseConnection,
+) -> Result<Vec<ContentTypeClientResults>> {
+    Ok(
+        ContentTypeClientResults::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            &format!(
+                "SELECT
+                    get_byte(content.content_key, 0) AS selector,
+                    {} AS client,
+                    COUNT(*) FILTER (WHERE content_audit.result = 1) AS success,
+                    COUNT(*) FILTER (WHERE content_audit.result IN (0, 4)) AS failure
+                FROM content_audit
+                JOIN content ON content.id = content_audit.content_key
+                LEFT JOIN client_info ON client_info.id = content_audit.client_info
+                WHERE content.protocol_id = $1
+                    AND content_audit.created_at >= $2
+                    AND content_audit.created_at < $3
+                    AND content_audit.result IN (0, 1, 4)
+                GROUP BY selector, client
+                ORDER BY selector, client",
+                super::client_info::CLIENT_VERSION_SQL
+            ),
+            vec![sub_protocol.into(), start.into(), end.into()],
+        ))
+        .all(conn)
+        .await?,
+    )
+}
+
+/// Two-sided z score above which a change in success rate is considered significant, for a
+/// 95% confidence level.
+const SIGNIFICANT_Z_SCORE: f64 = 1.96;
+
+/// Audit successes in two time windows, to tell whether the success rate changed between them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SuccessRateChange {
+    pub before_success: i64,
+    /// Completed audits, leaving out errored and pending ones.
+    pub before_completed: i64,
+    pub after_success: i64,
+    pub after_completed: i64,
+}
+
+impl SuccessRateChange {
+    pub fn before_percent(&self) -> Option<f64> {
+        success_percent(self.before_success, self.before_completed)
+    }
+
+    pub fn after_percent(&self) -> Option<f64> {
+        success_percent(self.after_success, self.after_completed)
+    }
+
+    /// Change of the success rate in percentage points, if both windows have completed audits.
+    pub fn delta_percent(&self) -> Option<f64> {
+        Some(self.after_percent()? - self.before_percent()?)
+    }
+
+    /// Z score of a two-proportion z-test of the success rates, if both windows have completed
+    /// audits and they did not all have the same outcome.
+    pub fn z_score(&self) -> Option<f64> {
+        if self.before_completed == 0 || self.after_completed == 0 {
+            return None;
+        }
+        let (n1, n2) = (self.before_completed as f64, self.after_completed as f64);
+        let (p1, p2) = (
+            self.before_success as f64 / n1,
+            self.after_success as f64 / n2,
+        );
+        let pooled = (self.before_success + self.after_success) as f64 / (n1 + n2);
+        let standard_error = (pooled * (1.0 - pooled) * (1.0 / n1 + 1.0 / n2)).sqrt();
+        if standard_error == 0.0 {
+            return None;
+        }
+        Some((p2 - p1) / standard_error)
+    }
+
+    /// Whether the success rate changed by more than chance would explain.
+    pub fn is_significant(&self) -> bool {
+        self.z_score()
+            .is_some_and(|z_score| z_score.abs() >= SIGNIFICANT_Z_SCORE)
+    }
+}
+
+fn success_percent(success: i64, completed: i64) -> Option<f64> {
+    match completed {
+        0 => None,
+        _ => Some(100.0 * success as f64 / completed as f64),
+    }
 }
 
 impl SelectionStrategy {
@@ -452,6 +1009,17 @@ impl SelectionStrategy {
             SelectionStrategy::History(HistorySelectionStrategy::FourFours) => {
                 "FourFours".to_string()
             }
+            SelectionStrategy::History(HistorySelectionStrategy::Sync) => "Sync".to_string(),
+            SelectionStrategy::History(HistorySelectionStrategy::Block) => "Block".to_string(),
+            SelectionStrategy::History(HistorySelectionStrategy::RoundTrip) => {
+                "Round Trip".to_string()
+            }
+            SelectionStrategy::History(HistorySelectionStrategy::RandomUnaudited) => {
+                "Random Unaudited".to_string()
+            }
+            SelectionStrategy::History(HistorySelectionStrategy::Propagation) => {
+                "Propagation".to_string()
+            }
             SelectionStrategy::History(HistorySelectionStrategy::SelectOldestUnaudited) => {
                 "Select Oldest Unaudited".to_string()
             }
@@ -471,6 +1039,26 @@ impl Model {
     pub fn is_success(&self) -> bool {
         self.result == AuditResult::Success
     }
+    /// A short label for the audit result, for display.
+    pub fn result_label(&self) -> &'static str {
+        match self.result {
+            AuditResult::Success => "Success",
+            AuditResult::Failure => "Fail",
+            AuditResult::Errored => "Errored",
+            AuditResult::Pending => "Pending",
+            AuditResult::InvalidContent => "Invalid",
+        }
+    }
+    /// The bootstrap badge color for the audit result.
+    pub fn result_badge_class(&self) -> &'static str {
+        match self.result {
+            AuditResult::Success => "success",
+            AuditResult::Failure => "danger",
+            AuditResult::Errored => "warning",
+            AuditResult::Pending => "secondary",
+            AuditResult::InvalidContent => "dark",
+        }
+    }
     pub fn created_at_local_time(&self) -> String {
         self.created_at.with_timezone(&chrono::Local).to_rfc2822()
     }
@@ -494,7 +1082,9 @@ mod tests {
 
     use crate::content_audit::StateSelectionStrategy;
 
-    use super::{BeaconSelectionStrategy, HistorySelectionStrategy, SelectionStrategy};
+    use super::{
+        BeaconSelectionStrategy, HistorySelectionStrategy, SelectionStrategy, SuccessRateChange,
+    };
 
     #[test]
     fn test_selection_strategy_to_value() {
@@ -522,6 +1112,26 @@ mod tests {
             SelectionStrategy::History(HistorySelectionStrategy::FourFours).to_value(),
             5
         );
+        assert_eq!(
+            SelectionStrategy::History(HistorySelectionStrategy::Sync).to_value(),
+            6
+        );
+        assert_eq!(
+            SelectionStrategy::History(HistorySelectionStrategy::Block).to_value(),
+            7
+        );
+        assert_eq!(
+            SelectionStrategy::History(HistorySelectionStrategy::RoundTrip).to_value(),
+            8
+        );
+        assert_eq!(
+            SelectionStrategy::History(HistorySelectionStrategy::RandomUnaudited).to_value(),
+            9
+        );
+        assert_eq!(
+            SelectionStrategy::History(HistorySelectionStrategy::Propagation).to_value(),
+            10
+        );
         assert_eq!(
             SelectionStrategy::Beacon(BeaconSelectionStrategy::Latest).to_value(),
             0x10000
@@ -558,6 +1168,26 @@ mod tests {
             SelectionStrategy::try_from_value(&5).unwrap(),
             SelectionStrategy::History(HistorySelectionStrategy::FourFours)
         );
+        assert_eq!(
+            SelectionStrategy::try_from_value(&6).unwrap(),
+            SelectionStrategy::History(HistorySelectionStrategy::Sync)
+        );
+        assert_eq!(
+            SelectionStrategy::try_from_value(&7).unwrap(),
+            SelectionStrategy::History(HistorySelectionStrategy::Block)
+        );
+        assert_eq!(
+            SelectionStrategy::try_from_value(&8).unwrap(),
+            SelectionStrategy::History(HistorySelectionStrategy::RoundTrip)
+        );
+        assert_eq!(
+            SelectionStrategy::try_from_value(&9).unwrap(),
+            SelectionStrategy::History(HistorySelectionStrategy::RandomUnaudited)
+        );
+        assert_eq!(
+            SelectionStrategy::try_from_value(&10).unwrap(),
+            SelectionStrategy::History(HistorySelectionStrategy::Propagation)
+        );
         assert_eq!(
             SelectionStrategy::try_from_value(&0x10000).unwrap(),
             SelectionStrategy::Beacon(BeaconSelectionStrategy::Latest)
@@ -594,6 +1224,22 @@ mod tests {
             SelectionStrategy::History(HistorySelectionStrategy::FourFours).as_text(),
             "FourFours"
         );
+        assert_eq!(
+            SelectionStrategy::History(HistorySelectionStrategy::Sync).as_text(),
+            "Sync"
+        );
+        assert_eq!(
+            SelectionStrategy::History(HistorySelectionStrategy::Block).as_text(),
+            "Block"
+        );
+        assert_eq!(
+            SelectionStrategy::History(HistorySelectionStrategy::RoundTrip).as_text(),
+            "Round Trip"
+        );
+        assert_eq!(
+            SelectionStrategy::History(HistorySelectionStrategy::RandomUnaudited).as_text(),
+            "Random Unaudited"
+        );
         assert_eq!(
             SelectionStrategy::Beacon(BeaconSelectionStrategy::Latest).as_text(),
             "Latest"
@@ -630,6 +1276,26 @@ mod tests {
             HistorySelectionStrategy::try_from("FourFours".to_string()).unwrap(),
             HistorySelectionStrategy::FourFours
         );
+        assert_eq!(
+            HistorySelectionStrategy::try_from("Sync".to_string()).unwrap(),
+            HistorySelectionStrategy::Sync
+        );
+        assert_eq!(
+            HistorySelectionStrategy::try_from("Block".to_string()).unwrap(),
+            HistorySelectionStrategy::Block
+        );
+        assert_eq!(
+            HistorySelectionStrategy::try_from("RoundTrip".to_string()).unwrap(),
+            HistorySelectionStrategy::RoundTrip
+        );
+        assert_eq!(
+            HistorySelectionStrategy::try_from("RandomUnaudited".to_string()).unwrap(),
+            HistorySelectionStrategy::RandomUnaudited
+        );
+        assert_eq!(
+            HistorySelectionStrategy::try_from("Propagation".to_string()).unwrap(),
+            HistorySelectionStrategy::Propagation
+        );
         assert_eq!(
             BeaconSelectionStrategy::try_from("Latest".to_string()).unwrap(),
             BeaconSelectionStrategy::Latest
@@ -681,4 +1347,45 @@ mod tests {
             Value::Int(Some(0x20000))
         );
     }
+
+    #[test]
+    fn test_success_rate_change() {
+        let change = SuccessRateChange {
+            before_success: 900,
+            before_completed: 1000,
+            after_success: 800,
+            after_completed: 1000,
+        };
+        assert_eq!(change.before_percent(), Some(90.0));
+        assert_eq!(change.after_percent(), Some(80.0));
+        assert!((change.delta_percent().unwrap() + 10.0).abs() < 1e-9);
+        assert!(change.z_score().unwrap() < -6.0);
+        assert!(change.is_significant());
+
+        // The same rates over a handful of audits could be chance.
+        let small = SuccessRateChange {
+            before_success: 9,
+            before_completed: 10,
+            after_success: 8,
+            after_completed: 10,
+        };
+        assert!(!small.is_significant());
+
+        // Nothing to compare without audits in both windows, or when every audit succeeded.
+        let empty = SuccessRateChange {
+            after_success: 5,
+            after_completed: 5,
+            ..Default::default()
+        };
+        assert_eq!(empty.delta_percent(), None);
+        assert!(!empty.is_significant());
+        let unchanged = SuccessRateChange {
+            before_success: 5,
+            before_completed: 5,
+            after_success: 5,
+            after_completed: 5,
+        };
+        assert_eq!(unchanged.z_score(), None);
+        assert_eq!(unchanged.delta_percent(), Some(0.0));
+    }
 }
@@ -2,16 +2,45 @@
 
 pub mod prelude;
 
+pub mod audit_coverage;
+pub mod audit_latest;
 pub mod audit_stats;
+pub mod audit_stats_client;
+pub mod audit_task;
+pub mod audit_worker;
+pub mod block_audit;
 pub mod census;
+pub mod census_alternate_enr;
+pub mod census_client_stats;
+pub mod census_incident;
 pub mod census_node;
+pub mod census_node_error;
 pub mod client_info;
+pub mod client_version_sighting;
 pub mod content;
 pub mod content_audit;
+pub mod content_first_success;
+pub mod content_flapping;
+pub mod coverage_sample;
+pub mod enr_change;
 pub mod execution_metadata;
+pub mod feature_flag;
 pub mod key_value;
 pub mod node;
+pub mod node_geo;
+pub mod node_operator;
+pub mod node_serve_latency;
+pub mod propagation_audit;
 pub mod record;
+pub mod reseed_attempt;
+pub mod round_trip_audit;
+pub mod saved_view;
+pub mod site_banner;
 pub mod state_roots;
-pub mod test;
+pub mod sync_audit;
+pub mod sync_audit_error;
+pub mod sync_audit_segment;
+#[cfg(test)]
+mod test;
+pub mod transfer_failure_daily;
 pub mod utils;
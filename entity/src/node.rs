@@ -2,14 +2,15 @@
 use std::collections::HashMap;
 
 use alloy_primitives::U256;
-use anyhow::Result;
+use anyhow::{bail, Result};
 use enr::NodeId;
 use ethportal_api::utils::bytes::hex_encode;
 
+use chrono::{DateTime, Utc};
 use sea_orm::{
-    entity::prelude::*, ActiveValue::NotSet, FromQueryResult, QueryOrder, QuerySelect, Set,
+    entity::prelude::*, ActiveValue::NotSet, DbBackend, FromQueryResult, QuerySelect, Set,
+    Statement,
 };
-use sea_query::Expr;
 
 use lazy_static::lazy_static;
 
@@ -20,6 +21,8 @@ pub struct Model {
     pub id: i32,
     pub node_id: Vec<u8>,
     pub node_id_high: i64,
+    /// Hidden from dashboards, like nodes run for experiments, keeping their history.
+    pub archived: bool,
 }
 
 impl Model {
@@ -38,18 +41,60 @@ impl Model {
     }
 }
 
-#[derive(FromQueryResult)]
-pub struct ModelWithDistance {
+/// A node with its exact distance to some target node id.
+#[derive(Clone, Debug)]
+pub struct ModelWithExactDistance {
     pub id: i32,
     pub node_id: Vec<u8>,
-    pub node_id_high: i64,
-    pub distance: i64,
+    pub distance: U256,
 }
 
-impl ModelWithDistance {
+impl ModelWithExactDistance {
     pub fn node_id_as_hex(&self) -> String {
         hex_encode(&self.node_id)
     }
+
+    /// The number of significant bits in the distance, i.e. the routing table bucket.
+    pub fn log2_distance(&self) -> usize {
+        self.distance.bit_len()
+    }
+}
+
+/// How the distance between two node ids is measured.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum DistanceMetric {
+    /// Bitwise XOR, as used by the Portal network overlays.
+    #[default]
+    Xor,
+    /// The shortest way around the ring of 256-bit node ids, in either direction.
+    Circular,
+}
+
+impl DistanceMetric {
+    pub fn distance(&self, a: U256, b: U256) -> U256 {
+        match self {
+            DistanceMetric::Xor => a ^ b,
+            DistanceMetric::Circular => a.wrapping_sub(b).min(b.wrapping_sub(a)),
+        }
+    }
+
+    pub fn as_text(&self) -> &'static str {
+        match self {
+            DistanceMetric::Xor => "xor",
+            DistanceMetric::Circular => "circular",
+        }
+    }
+}
+
+impl TryFrom<&str> for DistanceMetric {
+    type Error = anyhow::Error;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "xor" => Ok(DistanceMetric::Xor),
+            "circular" => Ok(DistanceMetric::Circular),
+            _ => bail!("Invalid value for DistanceMetric {}", value),
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -74,44 +119,45 @@ impl Related<super::client_info::Entity> for Entity {
 
 impl ActiveModelBehavior for ActiveModel {}
 
-#[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
-enum ComputedColumn {
-    Distance,
-}
-
-impl ColumnTrait for ComputedColumn {
-    type EntityName = Entity;
-
-    fn def(&self) -> ColumnDef {
-        match self {
-            Self::Distance => ColumnType::Integer.def(),
-        }
-    }
+/// The id columns of a node, all that is needed to measure its distance.
+#[derive(FromQueryResult)]
+struct NodeIdRow {
+    id: i32,
+    node_id: Vec<u8>,
 }
 
-pub async fn closest_xor(
+/// Returns the nodes closest to the given node id, measured exactly over all 256 bits.
+///
+/// The database only stores the high bits of node ids for comparison, so this fetches the ids
+/// of every node that isn't archived and sorts them in memory.
+pub async fn closest(
     node_id: NodeId,
+    metric: DistanceMetric,
+    limit: usize,
     conn: &DatabaseConnection,
-) -> Result<Vec<ModelWithDistance>> {
-    let raw_node_id = U256::from_be_slice(&node_id.raw());
-    let node_id_high: i64 = raw_node_id.wrapping_shr(193).to::<i64>();
-
-    let distance_expression = Expr::cust_with_values(
-        "(\"node\".\"node_id_high\" | $1) - (\"node\".\"node_id_high\" & $2)",
-        [node_id_high, node_id_high],
-    );
-
-    let nodes = Entity::find()
-        .column_as(distance_expression, "distance")
-        .order_by_asc(Expr::col(ComputedColumn::Distance))
-        .limit(100)
-        .into_model::<ModelWithDistance>()
+) -> Result<Vec<ModelWithExactDistance>> {
+    let target = U256::from_be_slice(&node_id.raw());
+    let mut nodes: Vec<ModelWithExactDistance> = Entity::find()
+        .select_only()
+        .column(Column::Id)
+        .column(Column::NodeId)
+        .filter(Column::Archived.eq(false))
+        .into_model::<NodeIdRow>()
         .all(conn)
-        .await?;
+        .await?
+        .into_iter()
+        .map(|node| ModelWithExactDistance {
+            id: node.id,
+            distance: metric.distance(target, U256::from_be_slice(&node.node_id)),
+            node_id: node.node_id,
+        })
+        .collect();
+    nodes.sort_by_key(|node| node.distance);
+    nodes.truncate(limit);
     Ok(nodes)
 }
 
-pub async fn get_or_create(node_id: NodeId, conn: &DatabaseConnection) -> Result<Model> {
+pub async fn get_or_create<C: ConnectionTrait>(node_id: NodeId, conn: &C) -> Result<Model> {
     // First try to lookup an existing entry.
     if let Some(node_id_model) = Entity::find()
         .filter(Column::NodeId.eq(node_id.raw().to_vec()))
@@ -123,18 +169,64 @@ pub async fn get_or_create(node_id: NodeId, conn: &DatabaseConnection) -> Result
     }
 
     // If no record exists, create one and return it
-    let raw_node_id = U256::from_be_slice(&node_id.raw());
-    let node_id_high: i64 = raw_node_id.wrapping_shr(193).to::<i64>();
-
     let node_id_model = ActiveModel {
         id: NotSet,
         node_id: Set(node_id.raw().into()),
-        node_id_high: Set(node_id_high),
+        node_id_high: Set(node_id_high(node_id)),
+        archived: Set(false),
     };
 
     Ok(node_id_model.insert(conn).await?)
 }
 
+/// Archives or unarchives the nodes with the given node ids, returning how many were changed.
+pub async fn set_archived(
+    node_ids: Vec<Vec<u8>>,
+    archived: bool,
+    conn: &DatabaseConnection,
+) -> Result<u64> {
+    let update = ActiveModel {
+        archived: Set(archived),
+        ..Default::default()
+    };
+    Ok(Entity::update_many()
+        .set(update)
+        .filter(Column::NodeId.is_in(node_ids))
+        .filter(Column::Archived.ne(archived))
+        .exec(conn)
+        .await?
+        .rows_affected)
+}
+
+/// Returns the nodes that ran audits since the given time, which are the Portal nodes of this
+/// Glados deployment.
+pub async fn get_auditing_since(
+    since: DateTime<Utc>,
+    conn: &DatabaseConnection,
+) -> Result<Vec<Model>> {
+    Ok(Entity::find()
+        .from_raw_sql(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT node.*
+            FROM node
+            WHERE node.id IN (
+                SELECT content_audit.node
+                FROM content_audit
+                WHERE content_audit.created_at >= $1
+            )",
+            vec![since.into()],
+        ))
+        .all(conn)
+        .await?)
+}
+
+/// The high bits of a node id, stored alongside it for fast distance calculations in SQL.
+pub fn node_id_high(node_id: NodeId) -> i64 {
+    U256::from_be_slice(&node_id.raw())
+        .wrapping_shr(193)
+        .to::<i64>()
+}
+
 lazy_static! {
     pub static ref NODE_NICKNAME_MAP: HashMap<String, String> = {
         let mut nicknames = HashMap::<String, String>::new();
@@ -1,9 +1,28 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.10.7
 
+pub use super::audit_coverage::Entity as AuditCoverage;
+pub use super::audit_latest::Entity as AuditLatest;
+pub use super::audit_stats_client::Entity as AuditStatsClient;
+pub use super::block_audit::Entity as BlockAudit;
+pub use super::client_version_sighting::Entity as ClientVersionSighting;
 pub use super::content::Entity as Content;
 pub use super::content_audit::Entity as ContentAudit;
+pub use super::content_first_success::Entity as ContentFirstSuccess;
+pub use super::coverage_sample::Entity as CoverageSample;
+pub use super::enr_change::Entity as EnrChange;
 pub use super::execution_metadata::Entity as ExecutionMetadata;
+pub use super::feature_flag::Entity as FeatureFlag;
 pub use super::key_value::Entity as KeyValue;
 pub use super::node::Entity as Node;
+pub use super::node_geo::Entity as NodeGeo;
+pub use super::node_serve_latency::Entity as NodeServeLatency;
+pub use super::propagation_audit::Entity as PropagationAudit;
 pub use super::record::Entity as Record;
+pub use super::reseed_attempt::Entity as ReseedAttempt;
+pub use super::saved_view::Entity as SavedView;
+pub use super::site_banner::Entity as SiteBanner;
 pub use super::state_roots::Entity as StateRoots;
+pub use super::sync_audit::Entity as SyncAudit;
+pub use super::sync_audit_error::Entity as SyncAuditError;
+pub use super::sync_audit_segment::Entity as SyncAuditSegment;
+pub use super::transfer_failure_daily::Entity as TransferFailureDaily;
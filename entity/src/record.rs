@@ -1,7 +1,19 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.10.7
-use anyhow::Result;
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
 use ethportal_api::types::enr::Enr;
-use sea_orm::{entity::prelude::*, ActiveValue::NotSet, Set};
+use sea_orm::{
+    entity::prelude::*, sea_query::OnConflict, ActiveValue::NotSet, Condition, DbBackend,
+    FromQueryResult, Set, Statement,
+};
+
+/// ENRs written per statement by [get_or_create_many].
+const ENR_CHUNK_SIZE: usize = 500;
+
+/// ENR key-value pairs written per statement by [get_or_create_many].
+const KEY_VALUE_CHUNK_SIZE: usize = 2000;
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
 #[sea_orm(table_name = "record")]
@@ -55,31 +67,190 @@ pub async fn get_or_create(enr: &Enr, conn: &DatabaseConnection) -> Result<Model
         return Ok(enr_model);
     }
 
-    // Wrap-around large sequence numbers
-    // TODO: migrate DB schema to use BigInt
-    let seq: i32 = match enr.seq().try_into() {
-        Ok(seq) => seq,
-        Err(_) => {
-            if enr.seq() > i32::MAX as u64 {
-                (enr.seq() % i32::MAX as u64) as i32
-            } else {
-                enr.seq() as i32
-            }
-        }
-    };
-
     // If no record exists, create one and return it
     let enr_model_unsaved = ActiveModel {
         id: NotSet,
         node_id: Set(node_id.id),
         raw: Set(enr.to_base64()),
-        sequence_number: Set(seq),
+        sequence_number: Set(sequence_number(enr)),
     };
     let enr_model = enr_model_unsaved.insert(conn).await?;
 
     for (enr_key, enr_value) in enr.iter() {
         super::key_value::get_or_create(enr_model.id, enr_key, &enr_value.to_vec(), conn).await?;
     }
+    super::enr_change::record_changes(std::slice::from_ref(&enr_model), conn).await?;
 
     Ok(enr_model)
 }
+
+/// Stores many ENRs at once, along with their nodes, key-value pairs, and endpoint changes.
+///
+/// Equivalent to calling [get_or_create] for each ENR, but written in chunks of
+/// insert-on-conflict statements rather than a few round trips per ENR. Returns the records in
+/// the same order as the given ENRs.
+pub async fn get_or_create_many(enrs: &[Enr], conn: &DatabaseConnection) -> Result<Vec<Model>> {
+    let mut records = Vec::with_capacity(enrs.len());
+    for chunk in enrs.chunks(ENR_CHUNK_SIZE) {
+        records.extend(get_or_create_chunk(chunk, conn).await?);
+    }
+    Ok(records)
+}
+
+async fn get_or_create_chunk(enrs: &[Enr], conn: &DatabaseConnection) -> Result<Vec<Model>> {
+    if enrs.is_empty() {
+        return Ok(vec![]);
+    }
+
+    // Nodes
+    let raw_node_ids: Vec<Vec<u8>> = enrs
+        .iter()
+        .map(|enr| enr.node_id().raw().to_vec())
+        .collect();
+    super::node::Entity::insert_many(enrs.iter().map(|enr| super::node::ActiveModel {
+        id: NotSet,
+        node_id: Set(enr.node_id().raw().to_vec()),
+        node_id_high: Set(super::node::node_id_high(enr.node_id())),
+        archived: Set(false),
+    }))
+    .on_conflict(
+        OnConflict::column(super::node::Column::NodeId)
+            .do_nothing()
+            .to_owned(),
+    )
+    .exec_without_returning(conn)
+    .await?;
+    let node_ids: HashMap<Vec<u8>, i32> = super::node::Entity::find()
+        .filter(super::node::Column::NodeId.is_in(raw_node_ids.clone()))
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|node| (node.node_id, node.id))
+        .collect();
+    let node_id_of = |raw_node_id: &Vec<u8>| {
+        node_ids
+            .get(raw_node_id)
+            .copied()
+            .ok_or(anyhow!("node was not stored"))
+    };
+
+    // Records
+    let mut new_records = vec![];
+    for (enr, raw_node_id) in enrs.iter().zip(&raw_node_ids) {
+        new_records.push(ActiveModel {
+            id: NotSet,
+            node_id: Set(node_id_of(raw_node_id)?),
+            raw: Set(enr.to_base64()),
+            sequence_number: Set(sequence_number(enr)),
+        });
+    }
+    Entity::insert_many(new_records)
+        .on_conflict(
+            OnConflict::columns([Column::NodeId, Column::SequenceNumber])
+                .do_nothing()
+                .to_owned(),
+        )
+        .exec_without_returning(conn)
+        .await?;
+    let mut condition = Condition::any();
+    for (enr, raw_node_id) in enrs.iter().zip(&raw_node_ids) {
+        condition = condition.add(
+            Condition::all()
+                .add(Column::NodeId.eq(node_id_of(raw_node_id)?))
+                .add(Column::SequenceNumber.eq(sequence_number(enr))),
+        );
+    }
+    let stored: HashMap<(i32, i32), Model> = Entity::find()
+        .filter(condition)
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|record| ((record.node_id, record.sequence_number), record))
+        .collect();
+    let mut records = vec![];
+    for (enr, raw_node_id) in enrs.iter().zip(&raw_node_ids) {
+        let key = (node_id_of(raw_node_id)?, sequence_number(enr));
+        let record = stored.get(&key).ok_or(anyhow!("record was not stored"))?;
+        records.push(record.clone());
+    }
+
+    // Key-value pairs. Those of records that already existed are stored, so are skipped.
+    let key_values: Vec<super::key_value::ActiveModel> = enrs
+        .iter()
+        .zip(&records)
+        .flat_map(|(enr, record)| {
+            enr.iter()
+                .map(move |(key, value)| super::key_value::ActiveModel {
+                    id: NotSet,
+                    record_id: Set(record.id),
+                    key: Set(key.to_vec()),
+                    value: Set(value.to_vec()),
+                })
+        })
+        .collect();
+    for chunk in key_values.chunks(KEY_VALUE_CHUNK_SIZE) {
+        super::key_value::Entity::insert_many(chunk.to_vec())
+            .on_conflict(
+                OnConflict::columns([
+                    super::key_value::Column::RecordId,
+                    super::key_value::Column::Key,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .exec_without_returning(conn)
+            .await?;
+    }
+    super::enr_change::record_changes(&records, conn).await?;
+
+    Ok(records)
+}
+
+/// The ENR sequence number as stored, wrapping around large sequence numbers.
+// TODO: migrate DB schema to use BigInt
+fn sequence_number(enr: &Enr) -> i32 {
+    match enr.seq().try_into() {
+        Ok(seq) => seq,
+        Err(_) => (enr.seq() % i32::MAX as u64) as i32,
+    }
+}
+
+/// An ENR of a node, with when it was seen in censuses.
+#[derive(Clone, Debug, FromQueryResult)]
+pub struct RecordObservation {
+    pub record_id: i32,
+    pub sequence_number: i32,
+    pub raw: String,
+    /// Time of the first census the ENR was seen in, or None if never seen in a census.
+    pub first_seen: Option<DateTime<Utc>>,
+    pub last_seen: Option<DateTime<Utc>>,
+    /// Number of times the ENR was seen in a census.
+    pub observations: i64,
+}
+
+/// Returns every ENR of a node, in the order they were first seen.
+pub async fn get_history(
+    node_id: i32,
+    conn: &DatabaseConnection,
+) -> Result<Vec<RecordObservation>> {
+    Ok(
+        RecordObservation::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT
+                record.id AS record_id,
+                record.sequence_number,
+                record.raw,
+                MIN(census_node.surveyed_at) AS first_seen,
+                MAX(census_node.surveyed_at) AS last_seen,
+                COUNT(census_node.id) AS observations
+            FROM record
+            LEFT JOIN census_node ON census_node.record_id = record.id
+            WHERE record.node_id = $1
+            GROUP BY record.id
+            ORDER BY first_seen ASC NULLS LAST, record.sequence_number ASC",
+            vec![node_id.into()],
+        ))
+        .all(conn)
+        .await?,
+    )
+}
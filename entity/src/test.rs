@@ -1,4 +1,5 @@
 #![allow(unused_imports)]
+use std::collections::HashMap;
 use std::str::FromStr;
 
 use alloy_primitives::{B256, U256};
@@ -17,11 +18,29 @@ use sea_orm::{
 use ethportal_api::utils::bytes::hex_encode;
 use migration::{Migrator, MigratorTrait};
 
-use crate::content::SubProtocol;
+use crate::census_node::{PingMeasurement, Reachability};
+use crate::content::{ContentSource, SubProtocol};
 use crate::content_audit::{HistorySelectionStrategy, SelectionStrategy};
-use crate::{client_info, content, content_audit, node, record};
+use crate::{
+    audit_coverage, audit_latest, audit_stats, audit_stats_client, audit_task, audit_worker,
+    block_audit, census, census_alternate_enr,
+    census_client_stats::{self, LivenessCounts, PingOutcome},
+    census_incident::{self, CensusDiagnostics},
+    census_node,
+    census_node_error::{self, CensusNodeErrorKind, NodeFailure},
+    client_info, client_version_sighting, content, content_audit, content_first_success,
+    content_flapping, coverage_sample, enr_change, execution_metadata, feature_flag, key_value,
+    node, node_geo, node_operator, node_serve_latency, propagation_audit, record, reseed_attempt,
+    round_trip_audit, saved_view, site_banner, transfer_failure_daily,
+};
 use pgtemp::PgTempDB;
 
+/// A node that answered the first PING of a census.
+const SINGLE_PING: PingMeasurement = PingMeasurement {
+    attempts: 1,
+    latency_ms: 20,
+};
+
 #[allow(dead_code)]
 // Temporary Postgres db will be deleted once PgTempDB goes out of scope, so keep it in scope.
 async fn setup_database() -> Result<(DbConn, PgTempDB), DbErr> {
@@ -49,11 +68,13 @@ async fn test_node_crud() -> Result<(), DbErr> {
         id: NotSet,
         node_id: Set(node_id_a.clone()),
         node_id_high: Set(0),
+        archived: NotSet,
     };
     let node_b = node::ActiveModel {
         id: NotSet,
         node_id: Set(node_id_b.clone()),
         node_id_high: Set(0),
+        archived: NotSet,
     };
 
     assert_eq!(node::Entity::find().count(&conn).await?, 0);
@@ -124,9 +145,15 @@ async fn test_content_id_as_hash() -> Result<(), DbErr> {
     let (conn, _db) = setup_database().await?;
     let key = sample_history_key();
     let content_id_hash = B256::from_slice(&key.content_id());
-    let content_model = content::get_or_create(SubProtocol::History, &key, Utc::now(), &conn)
-        .await
-        .unwrap();
+    let content_model = content::get_or_create(
+        SubProtocol::History,
+        &key,
+        Utc::now(),
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    .unwrap();
     assert_eq!(content_model.id_as_hash(), content_id_hash);
     Ok(())
 }
@@ -138,9 +165,15 @@ async fn test_content_id_as_hex() -> Result<(), DbErr> {
     let key = sample_history_key();
     let content_id_hash = B256::from_slice(&key.content_id());
     let content_id_hex = hex_encode(content_id_hash);
-    let content_model = content::get_or_create(SubProtocol::History, &key, Utc::now(), &conn)
-        .await
-        .unwrap();
+    let content_model = content::get_or_create(
+        SubProtocol::History,
+        &key,
+        Utc::now(),
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    .unwrap();
     assert_eq!(content_model.id_as_hex(), content_id_hex);
     Ok(())
 }
@@ -150,9 +183,15 @@ async fn test_content_id_as_hex() -> Result<(), DbErr> {
 async fn test_content_key_as_hex() -> Result<(), DbErr> {
     let (conn, _db) = setup_database().await?;
     let key = sample_history_key();
-    let content_model = content::get_or_create(SubProtocol::History, &key, Utc::now(), &conn)
-        .await
-        .unwrap();
+    let content_model = content::get_or_create(
+        SubProtocol::History,
+        &key,
+        Utc::now(),
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    .unwrap();
     assert_eq!(
         content_model.key_as_hex(),
         "0x00000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f"
@@ -170,17 +209,29 @@ async fn test_content_get_or_create() -> Result<(), DbErr> {
     // Ensure our database is empty
     assert_eq!(content::Entity::find().count(&conn).await?, 0);
 
-    let content_id_a = content::get_or_create(SubProtocol::History, &key, Utc::now(), &conn)
-        .await
-        .unwrap();
+    let content_id_a = content::get_or_create(
+        SubProtocol::History,
+        &key,
+        Utc::now(),
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    .unwrap();
 
     // Ensure we added a new record to the database.
     assert_eq!(content::Entity::find().count(&conn).await?, 1);
 
-    // Retrieve the key
-    let content_id_b = content::get_or_create(SubProtocol::History, &key, Utc::now(), &conn)
-        .await
-        .unwrap();
+    // Retrieve the key, from another source.
+    let content_id_b = content::get_or_create(
+        SubProtocol::History,
+        &key,
+        Utc::now(),
+        ContentSource::Audit,
+        &conn,
+    )
+    .await
+    .unwrap();
 
     // Key was not saved twice.
     assert_eq!(content::Entity::find().count(&conn).await?, 1);
@@ -188,6 +239,9 @@ async fn test_content_get_or_create() -> Result<(), DbErr> {
     // Ensure that get_or_create found the existing entry.
     assert_eq!(content_id_a.id, content_id_b.id);
     assert_eq!(content_id_a.content_key, content_id_b.content_key);
+
+    // The source is the one that first stored the key.
+    assert_eq!(content_id_b.source, Some(ContentSource::Monitor));
     Ok(())
 }
 
@@ -202,6 +256,8 @@ async fn test_audit_crud() -> Result<(), DbErr> {
         content_key: Set(key.to_bytes().into()),
         protocol_id: Set(SubProtocol::History),
         first_available_at: Set(Utc::now()),
+        source: NotSet,
+        archived: NotSet,
     };
 
     let content_model = content_key_active_model.insert(&conn).await?;
@@ -218,6 +274,9 @@ async fn test_audit_crud() -> Result<(), DbErr> {
     let client_info_active_model = client_info::ActiveModel {
         id: NotSet,
         version_info: Set("trin v0.1.0".to_owned()),
+        client_name: NotSet,
+        client_version: NotSet,
+        client_commit: NotSet,
     };
     let client_info_model = client_info_active_model.insert(&conn).await?;
 
@@ -235,8 +294,14 @@ async fn test_audit_crud() -> Result<(), DbErr> {
         ))),
         result: Set(content_audit::AuditResult::Success),
         trace: Set("".to_owned()),
+        trace_requested: Set(false),
+        error_reason: NotSet,
         client_info: Set(Some(client_info_model.id)),
         node: Set(Some(node.id)),
+        census_id: NotSet,
+        selection_lag_ms: NotSet,
+        duration_ms: NotSet,
+        timeout_ms: NotSet,
     };
 
     let content_audit_model = content_audit_active_model.insert(&conn).await?;
@@ -279,6 +344,8 @@ async fn test_content_table_unique_constraints() {
         content_key: Set(key_a.clone()),
         protocol_id: Set(protocol_a),
         first_available_at: Set(Utc::now()),
+        source: NotSet,
+        archived: NotSet,
     };
     action_a.clone().insert(&conn).await.unwrap();
     assert_eq!(content::Entity::find().count(&conn).await.unwrap(), 1);
@@ -300,6 +367,8 @@ async fn test_content_table_unique_constraints() {
         content_key: Set(key_a.clone()),
         protocol_id: Set(protocol_b),
         first_available_at: Set(Utc::now()),
+        source: NotSet,
+        archived: NotSet,
     };
     action_b.clone().insert(&conn).await.unwrap();
     assert_eq!(content::Entity::find().count(&conn).await.unwrap(), 2);
@@ -320,6 +389,8 @@ async fn test_content_table_unique_constraints() {
         content_key: Set(key_a),
         protocol_id: Set(protocol_a),
         first_available_at: Set(Utc::now()),
+        source: NotSet,
+        archived: NotSet,
     };
     assert!(action_c
         .clone()
@@ -346,6 +417,8 @@ async fn test_content_table_unique_constraints() {
         content_key: Set(key_b),
         protocol_id: Set(protocol_a),
         first_available_at: Set(Utc::now()),
+        source: NotSet,
+        archived: NotSet,
     };
     assert!(action_d
         .insert(&conn)
@@ -356,107 +429,4375 @@ async fn test_content_table_unique_constraints() {
     assert_eq!(content::Entity::find().count(&conn).await.unwrap(), 2);
 }
 
+/// Tests that closest nodes are ordered by their full 256-bit distance, even when
+/// they share the same high bits, and that archived nodes are left out.
+#[tokio::test]
+async fn test_query_closest_exact() {
+    let (conn, _db) = setup_database().await.unwrap();
+
+    let target = NodeId::new(&[0u8; 32]);
+    let mut far = [0u8; 32];
+    far[31] = 0xff;
+    let mut near = [0u8; 32];
+    near[31] = 0x01;
+    let mut wrapped = [0xffu8; 32];
+    wrapped[31] = 0xfe;
+
+    let node_far = node::get_or_create(NodeId::new(&far), &conn).await.unwrap();
+    let node_near = node::get_or_create(NodeId::new(&near), &conn)
+        .await
+        .unwrap();
+    let node_wrapped = node::get_or_create(NodeId::new(&wrapped), &conn)
+        .await
+        .unwrap();
+    assert_eq!(node_far.node_id_high, node_near.node_id_high);
+
+    let nodes = node::closest(target, node::DistanceMetric::Xor, 10, &conn)
+        .await
+        .unwrap();
+    let order: Vec<i32> = nodes.iter().map(|n| n.id).collect();
+    assert_eq!(order, vec![node_near.id, node_far.id, node_wrapped.id]);
+    assert_eq!(nodes[0].distance, U256::from(1));
+    assert_eq!(nodes[0].log2_distance(), 1);
+
+    // Going around the ring, the node just below the maximum id is only 2 away.
+    let nodes = node::closest(target, node::DistanceMetric::Circular, 2, &conn)
+        .await
+        .unwrap();
+    let order: Vec<i32> = nodes.iter().map(|n| n.id).collect();
+    assert_eq!(order, vec![node_near.id, node_wrapped.id]);
+    assert_eq!(nodes[1].distance, U256::from(2));
+
+    // Archived nodes are left out.
+    node::set_archived(vec![near.to_vec()], true, &conn)
+        .await
+        .unwrap();
+    let nodes = node::closest(target, node::DistanceMetric::Xor, 10, &conn)
+        .await
+        .unwrap();
+    let order: Vec<i32> = nodes.iter().map(|n| n.id).collect();
+    assert_eq!(order, vec![node_far.id, node_wrapped.id]);
+}
+
+/// Tests that audits move from `Pending` to a final result, and that only audits
+/// left pending past the cutoff are reported as stuck.
 #[tokio::test]
-async fn test_query_closest() {
-    use env_logger;
-    env_logger::init();
+async fn test_pending_audit_lifecycle() {
     let (conn, _db) = setup_database().await.unwrap();
 
-    let node_id_a = NodeId::random();
-    let node_id_b = NodeId::random();
-    let node_id_c = NodeId::random();
+    let key = sample_history_key();
+    let content_model = content::get_or_create(
+        SubProtocol::History,
+        &key,
+        Utc::now(),
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let client_info_model = client_info::get_or_create("trin v0.1.0".to_owned(), &conn)
+        .await
+        .unwrap();
+    let node = node::get_or_create(NodeId::random(), &conn).await.unwrap();
+    let strategy = SelectionStrategy::History(HistorySelectionStrategy::Random);
+
+    let finished = content_audit::create_pending(
+        content_model.id,
+        client_info_model.id,
+        node.id,
+        strategy.clone(),
+        true,
+        None,
+        Some(30_000),
+        &conn,
+    )
+    .await
+    .unwrap();
+    assert_eq!(finished.result, content_audit::AuditResult::Pending);
+    assert!(finished.trace_requested);
+    assert_eq!(finished.timeout_ms, Some(30_000));
+
+    let stuck = content_audit::create_pending(
+        content_model.id,
+        client_info_model.id,
+        node.id,
+        strategy,
+        false,
+        None,
+        None,
+        &conn,
+    )
+    .await
+    .unwrap();
 
-    let node_a = node::get_or_create(node_id_a, &conn).await.unwrap();
-    let node_b = node::get_or_create(node_id_b, &conn).await.unwrap();
-    let node_c = node::get_or_create(node_id_c, &conn).await.unwrap();
+    let finished = content_audit::set_result(
+        finished,
+        content_audit::AuditResult::Errored,
+        "".to_owned(),
+        Some(1_500),
+        &conn,
+    )
+    .await
+    .unwrap();
+    assert_eq!(finished.result, content_audit::AuditResult::Errored);
+    assert_eq!(finished.duration_ms, Some(1_500));
+    assert!(finished.trace_requested);
+    assert!(!stuck.trace_requested);
 
-    let distance_a_b = node_a.node_id_high ^ node_b.node_id_high;
-    let distance_a_c = node_a.node_id_high ^ node_c.node_id_high;
-    let distance_b_c = node_b.node_id_high ^ node_c.node_id_high;
+    let cutoff = Utc::now();
+    let stuck_audits = content_audit::get_stuck_pending(cutoff, 10, &conn)
+        .await
+        .unwrap();
+    assert_eq!(stuck_audits.len(), 1);
+    assert_eq!(stuck_audits[0].id, stuck.id);
+    assert_eq!(
+        content_audit::count_stuck_pending(cutoff, &conn)
+            .await
+            .unwrap(),
+        1
+    );
 
-    let node_id_a_full = U256::from_be_slice(&node_id_a.raw());
-    let node_id_b_full = U256::from_be_slice(&node_id_b.raw());
-    let node_id_c_full = U256::from_be_slice(&node_id_c.raw());
+    // Nothing was started before this cutoff.
+    let early_cutoff = stuck.created_at - chrono::TimeDelta::try_minutes(1).unwrap();
+    assert_eq!(
+        content_audit::count_stuck_pending(early_cutoff, &conn)
+            .await
+            .unwrap(),
+        0
+    );
 
+    // Stale audits are marked as errored, and are no longer stuck.
+    assert_eq!(
+        content_audit::mark_stale_pending(early_cutoff, &conn)
+            .await
+            .unwrap(),
+        0
+    );
     assert_eq!(
-        node_a.node_id_high as u64,
-        node_id_a_full.wrapping_shr(193).to::<u64>()
+        content_audit::mark_stale_pending(cutoff, &conn)
+            .await
+            .unwrap(),
+        1
     );
+    let stale = content_audit::Entity::find_by_id(stuck.id)
+        .one(&conn)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stale.result, content_audit::AuditResult::Errored);
     assert_eq!(
-        node_b.node_id_high as u64,
-        node_id_b_full.wrapping_shr(193).to::<u64>()
+        stale.error_reason.as_deref(),
+        Some(content_audit::STALE_PENDING_REASON)
     );
     assert_eq!(
-        node_c.node_id_high as u64,
-        node_id_c_full.wrapping_shr(193).to::<u64>()
+        content_audit::count_stuck_pending(cutoff, &conn)
+            .await
+            .unwrap(),
+        0
     );
+}
+
+/// Tests that a node's ENRs are listed in the order they were first seen in censuses,
+/// with the time range and number of sightings of each.
+#[tokio::test]
+async fn test_record_history() {
+    use enr::CombinedKey;
+    use ethportal_api::types::enr::Enr;
+    use std::net::Ipv4Addr;
+
+    let (conn, _db) = setup_database().await.unwrap();
 
-    let distance_a_b_full = node_id_a_full ^ node_id_b_full;
-    let distance_a_c_full = node_id_a_full ^ node_id_c_full;
-    let distance_b_c_full = node_id_b_full ^ node_id_c_full;
+    let key = CombinedKey::generate_secp256k1();
+    let enr_at = |seq: u64, ip: Ipv4Addr| -> Enr {
+        let mut builder = Enr::builder();
+        builder.seq(seq).ip4(ip).udp4(9009).build(&key).unwrap()
+    };
+    let first = record::get_or_create(&enr_at(1, Ipv4Addr::new(10, 0, 0, 1)), &conn)
+        .await
+        .unwrap();
+    let second = record::get_or_create(&enr_at(2, Ipv4Addr::new(10, 0, 0, 2)), &conn)
+        .await
+        .unwrap();
+    let unseen = record::get_or_create(&enr_at(3, Ipv4Addr::new(10, 0, 0, 3)), &conn)
+        .await
+        .unwrap();
 
-    //let distance_a_b_alt = (node_id_a_full | node_id_b_full) - (node_id_a_full & node_id_b_full);
+    // Each observation is made by a census of its own, as a census surveys a record once.
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    for (record_id, minutes) in [(first.id, 0), (first.id, 15), (second.id, 30)] {
+        let surveyed_at = start + chrono::Duration::minutes(minutes);
+        let census = census::create(surveyed_at, 60, SubProtocol::History, &conn)
+            .await
+            .unwrap();
+        census_node::create(
+            census.id,
+            record_id,
+            U256::MAX,
+            surveyed_at,
+            SubProtocol::History,
+            SINGLE_PING,
+            false,
+            Reachability::default(),
+            &conn,
+        )
+        .await
+        .unwrap();
+    }
 
+    let history = record::get_history(first.node_id, &conn).await.unwrap();
+    let ids: Vec<i32> = history.iter().map(|r| r.record_id).collect();
+    assert_eq!(ids, vec![first.id, second.id, unseen.id]);
+    assert_eq!(history[0].first_seen, Some(start));
     assert_eq!(
-        distance_a_b_full.wrapping_shr(193).to::<u64>(),
-        distance_a_b as u64
+        history[0].last_seen,
+        Some(start + chrono::Duration::minutes(15))
     );
+    assert_eq!(history[0].observations, 2);
+    assert_eq!(history[1].observations, 1);
+    assert_eq!(history[2].first_seen, None);
+    assert_eq!(history[2].observations, 0);
+}
+
+/// Tests that ENRs stored in a batch get the same records as when stored one at a time,
+/// including ENRs that are already stored or repeated in the batch.
+#[tokio::test]
+async fn test_record_get_or_create_many() {
+    use enr::CombinedKey;
+    use ethportal_api::types::enr::Enr;
+    use std::net::Ipv4Addr;
+
+    let (conn, _db) = setup_database().await.unwrap();
+
+    let enr_at = |key: &CombinedKey, seq: u64| -> Enr {
+        let mut builder = Enr::builder();
+        builder
+            .seq(seq)
+            .ip4(Ipv4Addr::new(10, 0, 0, 1))
+            .udp4(9009)
+            .build(key)
+            .unwrap()
+    };
+    let key_a = CombinedKey::generate_secp256k1();
+    let key_b = CombinedKey::generate_secp256k1();
+    let existing = enr_at(&key_a, 1);
+    let existing_record = record::get_or_create(&existing, &conn).await.unwrap();
+    let key_value_count = key_value::Entity::find().count(&conn).await.unwrap();
+
+    let repeated = enr_at(&key_b, 1);
+    let enrs = vec![
+        repeated.clone(),
+        existing.clone(),
+        enr_at(&key_a, 2),
+        repeated,
+    ];
+    let records = record::get_or_create_many(&enrs, &conn).await.unwrap();
+
+    assert_eq!(records.len(), 4);
+    assert_eq!(records[1], existing_record);
+    assert_eq!(records[2].node_id, existing_record.node_id);
+    assert_eq!(records[2].sequence_number, 2);
+    assert_eq!(records[0], records[3]);
+    assert_ne!(records[0].node_id, existing_record.node_id);
+    assert_eq!(node::Entity::find().count(&conn).await.unwrap(), 2);
+    assert_eq!(record::Entity::find().count(&conn).await.unwrap(), 3);
+    // Each ENR has the same keys, and those of the existing record were not written again.
     assert_eq!(
-        distance_a_c_full.wrapping_shr(193).to::<u64>(),
-        distance_a_c as u64
+        key_value::Entity::find().count(&conn).await.unwrap(),
+        key_value_count * 3
     );
+    for (enr, record) in enrs.iter().zip(&records) {
+        assert_eq!(record.raw, enr.to_base64());
+    }
+}
+
+/// Tests that new audits are linked to the latest census of their content's subnetwork
+/// that started before the audit.
+#[tokio::test]
+async fn test_audit_census_link() {
+    let (conn, _db) = setup_database().await.unwrap();
+
+    let content_model = content::get_or_create(
+        SubProtocol::History,
+        &sample_history_key(),
+        Utc::now(),
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let client_info_model = client_info::get_or_create("trin v0.1.0".to_owned(), &conn)
+        .await
+        .unwrap();
+    let node = node::get_or_create(NodeId::random(), &conn).await.unwrap();
+    let strategy = SelectionStrategy::History(HistorySelectionStrategy::Random);
+
+    // No census yet.
+    let audit = content_audit::create_pending(
+        content_model.id,
+        client_info_model.id,
+        node.id,
+        strategy.clone(),
+        false,
+        None,
+        None,
+        &conn,
+    )
+    .await
+    .unwrap();
+    assert_eq!(audit.census_id, None);
+
+    let now = Utc::now();
+    let older = census::create(
+        now - chrono::Duration::hours(1),
+        60,
+        SubProtocol::History,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let latest = census::create(
+        now - chrono::Duration::minutes(10),
+        60,
+        SubProtocol::History,
+        &conn,
+    )
+    .await
+    .unwrap();
+    // Censuses of other subnetworks, or that start after the audit, are not used.
+    census::create(
+        now - chrono::Duration::minutes(5),
+        60,
+        SubProtocol::State,
+        &conn,
+    )
+    .await
+    .unwrap();
+    census::create(
+        now + chrono::Duration::hours(1),
+        60,
+        SubProtocol::History,
+        &conn,
+    )
+    .await
+    .unwrap();
+
+    let audit = content_audit::create_pending(
+        content_model.id,
+        client_info_model.id,
+        node.id,
+        strategy,
+        false,
+        None,
+        None,
+        &conn,
+    )
+    .await
+    .unwrap();
+    assert_ne!(audit.census_id, Some(older.id));
+    assert_eq!(audit.census_id, Some(latest.id));
+}
+
+#[tokio::test]
+async fn test_audit_coverage_upsert() {
+    let (conn, _db) = setup_database().await.unwrap();
+    let day = Utc.with_ymd_and_hms(2024, 10, 22, 0, 0, 0).unwrap();
+    let latest = SelectionStrategy::History(HistorySelectionStrategy::Latest);
+    let random = SelectionStrategy::History(HistorySelectionStrategy::Random);
+
+    audit_coverage::upsert(day, latest.clone(), 100, 10, &conn)
+        .await
+        .unwrap();
+    audit_coverage::upsert(day, random.clone(), 1000, 5, &conn)
+        .await
+        .unwrap();
+    // Recording the same day and strategy again replaces the earlier value.
+    audit_coverage::upsert(day, latest.clone(), 120, 60, &conn)
+        .await
+        .unwrap();
+
+    let coverage = audit_coverage::get_between(day, day + chrono::Duration::days(1), &conn)
+        .await
+        .unwrap();
+    assert_eq!(coverage.len(), 2);
+    let latest_coverage = coverage.iter().find(|c| c.strategy == latest).unwrap();
+    assert_eq!(latest_coverage.num_eligible, 120);
+    assert_eq!(latest_coverage.num_audited, 60);
+    assert_eq!(latest_coverage.coverage_percent(), 50.0);
+}
+
+/// Tests that consecutive failures of a key back off further each time, that invalid content
+/// counts as a failure, and that finding the key ends the streak.
+#[tokio::test]
+async fn test_failed_audit_backoff() {
+    let (conn, _db) = setup_database().await.unwrap();
+
+    let key = sample_history_key();
+    let content_model = content::get_or_create(
+        SubProtocol::History,
+        &key,
+        Utc::now(),
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let client_info_model = client_info::get_or_create("trin v0.1.0".to_owned(), &conn)
+        .await
+        .unwrap();
+    let node = node::get_or_create(NodeId::random(), &conn).await.unwrap();
+    let strategy = SelectionStrategy::History(HistorySelectionStrategy::Random);
+
+    let audit = |query_successful: bool| {
+        content_audit::create(
+            content_model.id,
+            client_info_model.id,
+            node.id,
+            query_successful,
+            strategy.clone(),
+            "".to_owned(),
+            &conn,
+        )
+    };
+    let latest = || async {
+        audit_latest::Entity::find_by_id(content_model.id)
+            .one(&conn)
+            .await
+            .unwrap()
+            .unwrap()
+    };
+
+    let first = audit(false).await.unwrap();
+    let state = latest().await;
+    assert_eq!(state.consecutive_failures, 1);
     assert_eq!(
-        distance_b_c_full.wrapping_shr(193).to::<u64>(),
-        distance_b_c as u64
+        state.retry_after,
+        Some(first.created_at + chrono::Duration::hours(1))
     );
 
+    let second = audit(false).await.unwrap();
+    let state = latest().await;
+    assert_eq!(state.consecutive_failures, 2);
     assert_eq!(
-        distance_a_b_full > distance_a_c_full,
-        distance_a_b > distance_a_c
+        state.retry_after,
+        Some(second.created_at + chrono::Duration::hours(6))
     );
+
+    // Not yet due right after the failure, but due once the backoff has elapsed.
+    let due = audit_latest::due_for_retry(second.created_at, 10, &conn)
+        .await
+        .unwrap();
+    assert!(due.is_empty());
+    let due = audit_latest::due_for_retry(second.created_at + chrono::Duration::days(1), 10, &conn)
+        .await
+        .unwrap();
+    assert_eq!(due.len(), 1);
+
+    // Invalid content extends the streak like a failure.
+    let pending = content_audit::create_pending(
+        content_model.id,
+        client_info_model.id,
+        node.id,
+        strategy.clone(),
+        false,
+        None,
+        None,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let third = content_audit::set_result(
+        pending,
+        content_audit::AuditResult::InvalidContent,
+        "".to_owned(),
+        None,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let state = latest().await;
+    assert_eq!(state.consecutive_failures, 3);
     assert_eq!(
-        distance_a_c_full > distance_b_c_full,
-        distance_a_c > distance_b_c
+        state.retry_after,
+        Some(third.created_at + chrono::Duration::days(1))
     );
+
+    audit(true).await.unwrap();
+    let state = latest().await;
+    assert_eq!(state.consecutive_failures, 0);
+    assert_eq!(state.retry_after, None);
+}
+
+/// Tests that a failing key that then errors, e.g. times out, is still selected for a retry
+/// once its backoff has elapsed.
+#[tokio::test]
+async fn test_failed_then_errored_still_selected() {
+    let (conn, _db) = setup_database().await.unwrap();
+
+    let content_model = content::get_or_create(
+        SubProtocol::History,
+        &sample_history_key(),
+        Utc::now(),
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let client_info_model = client_info::get_or_create("trin v0.1.0".to_owned(), &conn)
+        .await
+        .unwrap();
+    let node = node::get_or_create(NodeId::random(), &conn).await.unwrap();
+    let strategy = SelectionStrategy::History(HistorySelectionStrategy::Failed);
+
+    content_audit::create(
+        content_model.id,
+        client_info_model.id,
+        node.id,
+        false,
+        strategy.clone(),
+        "".to_owned(),
+        &conn,
+    )
+    .await
+    .unwrap();
+    let pending = content_audit::create_pending(
+        content_model.id,
+        client_info_model.id,
+        node.id,
+        strategy,
+        false,
+        None,
+        None,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let errored = content_audit::set_result(
+        pending,
+        content_audit::AuditResult::Errored,
+        "".to_owned(),
+        None,
+        &conn,
+    )
+    .await
+    .unwrap();
+
+    let failed = audit_latest::get_failed_oldest_first(
+        SubProtocol::History,
+        DateTime::<Utc>::UNIX_EPOCH,
+        errored.created_at + chrono::Duration::days(1),
+        10,
+        &conn,
+    )
+    .await
+    .unwrap();
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].0.id, content_model.id);
+    assert_eq!(failed[0].1.result, content_audit::AuditResult::Errored);
+    assert_eq!(failed[0].1.consecutive_failures, 1);
+}
+
+/// Tests that saving a view under an existing name replaces its filters, and that views are
+/// listed per network.
+#[tokio::test]
+async fn test_saved_view_upsert() {
+    let (conn, _db) = setup_database().await.unwrap();
+
+    let save = |name: &str, network: SubProtocol, success: &str| {
+        saved_view::upsert(
+            name.to_owned(),
+            network,
+            "All".to_owned(),
+            "Headers".to_owned(),
+            success.to_owned(),
+            &conn,
+        )
+    };
+    let failures = save("Header failures", SubProtocol::History, "Success")
+        .await
+        .unwrap();
+    let replaced = save("Header failures", SubProtocol::History, "Failure")
+        .await
+        .unwrap();
+    save("All pending", SubProtocol::History, "Pending")
+        .await
+        .unwrap();
+    save("Header failures", SubProtocol::Beacon, "Failure")
+        .await
+        .unwrap();
+
+    assert_eq!(replaced.id, failures.id);
+    assert_eq!(replaced.success, "Failure");
+    let history_views = saved_view::get_all(SubProtocol::History, &conn)
+        .await
+        .unwrap();
+    let names: Vec<&str> = history_views.iter().map(|v| v.name.as_str()).collect();
+    assert_eq!(names, vec!["All pending", "Header failures"]);
+
+    assert!(saved_view::delete(failures.id, &conn).await.unwrap());
+    assert!(!saved_view::delete(failures.id, &conn).await.unwrap());
     assert_eq!(
-        distance_a_b_full > distance_b_c_full,
-        distance_a_b > distance_b_c
+        saved_view::get_all(SubProtocol::History, &conn)
+            .await
+            .unwrap()
+            .len(),
+        1
     );
+}
+
+#[tokio::test]
+async fn test_census_client_stats() {
+    let (conn, _db) = setup_database().await.unwrap();
+    let day = Utc.with_ymd_and_hms(2024, 10, 26, 0, 0, 0).unwrap();
+
+    let mut trin = LivenessCounts::default();
+    trin.add(true, PingOutcome::Pong);
+    trin.add(true, PingOutcome::ErrorPong);
+    let mut unknown = LivenessCounts::default();
+    unknown.add(false, PingOutcome::UndecodablePong);
+    unknown.add(false, PingOutcome::Timeout);
+    let mut counts = HashMap::new();
+    counts.insert("trin".to_owned(), trin);
+    counts.insert(census_client_stats::UNKNOWN_CLIENT.to_owned(), unknown);
+
+    let mut totals = LivenessCounts::default();
+    totals.merge(&trin);
+    totals.merge(&unknown);
+    assert_eq!(totals.checked, 4);
+    assert_eq!(totals.client_info_parsed, 2);
+
+    for hour in [1, 13] {
+        let census = census::create(
+            day + chrono::Duration::hours(hour),
+            60,
+            SubProtocol::History,
+            &conn,
+        )
+        .await
+        .unwrap();
+        assert_eq!(census.num_checked, None);
+        census_client_stats::create_many(census.id, &counts, &conn)
+            .await
+            .unwrap();
+        let census = census::set_liveness_counts(census, totals, &conn)
+            .await
+            .unwrap();
+        assert_eq!(census.num_checked, Some(4));
+        assert_eq!(census.num_timeouts, Some(1));
+    }
+    // Censuses of other subnetworks are not counted.
+    let state_census = census::create(
+        day + chrono::Duration::hours(2),
+        60,
+        SubProtocol::State,
+        &conn,
+    )
+    .await
+    .unwrap();
+    census_client_stats::create_many(state_census.id, &counts, &conn)
+        .await
+        .unwrap();
 
-    let nodes_near_a = node::closest_xor(node_id_a, &conn).await.unwrap();
-    assert_eq!(nodes_near_a.len(), 3);
+    let compliance = census_client_stats::get_daily_compliance(
+        day,
+        day + chrono::Duration::days(1),
+        SubProtocol::History,
+        &conn,
+    )
+    .await
+    .unwrap();
+    assert_eq!(compliance.len(), 2);
+    let trin_day = compliance.iter().find(|c| c.client == "trin").unwrap();
+    assert_eq!(trin_day.day, day);
+    assert_eq!(trin_day.num_censuses, 2);
+    assert_eq!(trin_day.num_checked, 4);
+    assert_eq!(trin_day.num_pongs, 2);
+    assert_eq!(trin_day.num_error_pongs, 2);
+    let unknown_day = compliance
+        .iter()
+        .find(|c| c.client == census_client_stats::UNKNOWN_CLIENT)
+        .unwrap();
+    assert_eq!(unknown_day.num_client_info_parsed, 0);
+    assert_eq!(unknown_day.num_undecodable_pongs, 2);
+}
+
+/// Tests that the trailing node count averages the latest earlier censuses of the subnetwork,
+/// and that incidents are found by census.
+#[tokio::test]
+async fn test_census_incident() {
+    use enr::CombinedKey;
+    use ethportal_api::types::enr::Enr;
+
+    let (conn, _db) = setup_database().await.unwrap();
+    let start = Utc.with_ymd_and_hms(2024, 10, 27, 0, 0, 0).unwrap();
+
+    let mut records = vec![];
+    for _ in 0..4 {
+        let enr = Enr::builder()
+            .build(&CombinedKey::generate_secp256k1())
+            .unwrap();
+        records.push(record::get_or_create(&enr, &conn).await.unwrap());
+    }
+
+    // The oldest census falls outside the trailing window of two censuses.
+    for (hours, num_nodes) in [(0, 1), (1, 4), (2, 2)] {
+        let census = census::create(
+            start + chrono::Duration::hours(hours),
+            60,
+            SubProtocol::History,
+            &conn,
+        )
+        .await
+        .unwrap();
+        for record in &records[..num_nodes] {
+            census_node::create(
+                census.id,
+                record.id,
+                U256::MAX,
+                census.started_at,
+                SubProtocol::History,
+                SINGLE_PING,
+                false,
+                Reachability::default(),
+                &conn,
+            )
+            .await
+            .unwrap();
+        }
+    }
+    let latest = census::create(
+        start + chrono::Duration::hours(3),
+        60,
+        SubProtocol::History,
+        &conn,
+    )
+    .await
+    .unwrap();
 
-    let expected_distances_a = match distance_a_b > distance_a_c {
-        true => [0, distance_a_c, distance_a_b],
-        false => [0, distance_a_b, distance_a_c],
+    let average =
+        census_incident::trailing_node_count(SubProtocol::History, latest.started_at, 2, &conn)
+            .await
+            .unwrap();
+    assert_eq!(average, Some(3.0));
+    let none =
+        census_incident::trailing_node_count(SubProtocol::State, latest.started_at, 2, &conn)
+            .await
+            .unwrap();
+    assert_eq!(none, None);
+
+    let diagnostics = CensusDiagnostics {
+        initial_nodes: 8,
+        liveness_checks: 10,
+        liveness_errors: 9,
+        enumerations: 20,
+        enumeration_errors: 5,
+        routing_table: None,
     };
-    let actual_distances_a = [
-        nodes_near_a[0].distance,
-        nodes_near_a[1].distance,
-        nodes_near_a[2].distance,
+    census_incident::create(latest.id, 1, 3.0, diagnostics, &conn)
+        .await
+        .unwrap();
+    let incident = census_incident::get_by_census(latest.id, &conn)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(incident.liveness_error_percent(), 90.0);
+    assert_eq!(incident.enumeration_error_percent(), 25.0);
+    assert!((incident.drop_percent() - 100.0 * 2.0 / 3.0).abs() < 1e-9);
+    let latest_incidents = census_incident::get_latest(SubProtocol::History, 10, &conn)
+        .await
+        .unwrap();
+    assert_eq!(latest_incidents, vec![incident]);
+}
+
+/// Tests the period summaries used for reports: audit outcomes by content type, census sizes
+/// and the ENRs of census nodes.
+#[tokio::test]
+async fn test_report_summaries() {
+    use enr::CombinedKey;
+    use ethportal_api::types::enr::Enr;
+    use ethportal_api::BlockBodyKey;
+
+    let (conn, _db) = setup_database().await.unwrap();
+    let now = Utc::now();
+
+    let header = content::get_or_create(
+        SubProtocol::History,
+        &sample_history_key(),
+        now,
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let body_key = HistoryContentKey::BlockBody(BlockBodyKey {
+        block_hash: [1; 32],
+    });
+    let body = content::get_or_create(
+        SubProtocol::History,
+        &body_key,
+        now,
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let client_info_model = client_info::get_or_create("trin v0.1.0".to_owned(), &conn)
+        .await
+        .unwrap();
+    let node = node::get_or_create(NodeId::random(), &conn).await.unwrap();
+    let strategy = SelectionStrategy::History(HistorySelectionStrategy::Random);
+    for (content_id, query_successful) in [(header.id, true), (header.id, false), (body.id, true)] {
+        content_audit::create(
+            content_id,
+            client_info_model.id,
+            node.id,
+            query_successful,
+            strategy.clone(),
+            "".to_owned(),
+            &conn,
+        )
+        .await
+        .unwrap();
+    }
+
+    let results = content_audit::get_results_by_content_type(
+        now - chrono::Duration::hours(1),
+        now + chrono::Duration::hours(1),
+        &conn,
+    )
+    .await
+    .unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].selector, 0x00);
+    assert_eq!(results[0].total, 2);
+    assert_eq!(results[0].success_percent(), 50.0);
+    assert_eq!(results[1].selector, 0x01);
+    assert_eq!(results[1].success, 1);
+
+    let enr = Enr::builder()
+        .build(&CombinedKey::generate_secp256k1())
+        .unwrap();
+    let record = record::get_or_create(&enr, &conn).await.unwrap();
+    let empty = census::create(now, 60, SubProtocol::History, &conn)
+        .await
+        .unwrap();
+    let found = census::create(
+        now + chrono::Duration::minutes(15),
+        60,
+        SubProtocol::History,
+        &conn,
+    )
+    .await
+    .unwrap();
+    census_node::create(
+        found.id,
+        record.id,
+        U256::MAX,
+        found.started_at,
+        SubProtocol::History,
+        SINGLE_PING,
+        false,
+        Reachability::default(),
+        &conn,
+    )
+    .await
+    .unwrap();
+
+    let node_counts = census::get_node_counts(
+        SubProtocol::History,
+        now - chrono::Duration::hours(1),
+        now + chrono::Duration::hours(1),
+        &conn,
+    )
+    .await
+    .unwrap();
+    let counts: Vec<(i32, i64)> = node_counts
+        .iter()
+        .map(|c| (c.census_id, c.node_count))
+        .collect();
+    assert_eq!(counts, vec![(empty.id, 0), (found.id, 1)]);
+    assert_eq!(
+        census_node::get_enrs(found.id, &conn).await.unwrap(),
+        vec![enr.to_base64()]
+    );
+}
+
+#[tokio::test]
+async fn test_node_operator_claim() {
+    use enr::{CombinedKey, EnrKey};
+    use ethportal_api::types::enr::Enr;
+
+    let (conn, _db) = setup_database().await.unwrap();
+    let key = CombinedKey::generate_secp256k1();
+    let enr = Enr::builder().build(&key).unwrap();
+    let record = record::get_or_create(&enr, &conn).await.unwrap();
+    let signed_at = Utc.with_ymd_and_hms(2024, 10, 28, 10, 20, 30).unwrap();
+
+    let message =
+        node_operator::claim_message(enr.node_id(), "Alice", Some("alice@example.com"), signed_at);
+    let signature = key.sign_v4(message.as_bytes()).unwrap();
+    assert!(node_operator::verify_claim(&enr, &message, &signature));
+
+    // The signature does not cover a different name, or the key of another node.
+    let tampered = node_operator::claim_message(
+        enr.node_id(),
+        "Mallory",
+        Some("alice@example.com"),
+        signed_at,
+    );
+    assert!(!node_operator::verify_claim(&enr, &tampered, &signature));
+    let other = Enr::builder()
+        .build(&CombinedKey::generate_secp256k1())
+        .unwrap();
+    assert!(!node_operator::verify_claim(&other, &message, &signature));
+
+    let census = census::create(signed_at, 60, SubProtocol::History, &conn)
+        .await
+        .unwrap();
+    census_node::create(
+        census.id,
+        record.id,
+        U256::MAX,
+        signed_at,
+        SubProtocol::History,
+        SINGLE_PING,
+        false,
+        Reachability::default(),
+        &conn,
+    )
+    .await
+    .unwrap();
+    assert!(node_operator::get_by_census(census.id, &conn)
+        .await
+        .unwrap()
+        .is_empty());
+
+    node_operator::upsert(
+        record.node_id,
+        "Alice".to_owned(),
+        Some("alice@example.com".to_owned()),
+        signature,
+        signed_at,
+        &conn,
+    )
+    .await
+    .unwrap();
+    // A later claim replaces the earlier one.
+    let operator = node_operator::upsert(
+        record.node_id,
+        "Alice's team".to_owned(),
+        None,
+        vec![],
+        signed_at + chrono::Duration::minutes(1),
+        &conn,
+    )
+    .await
+    .unwrap();
+    assert_eq!(operator.display_name, "Alice's team");
+    assert_eq!(
+        node_operator::get(record.node_id, &conn).await.unwrap(),
+        Some(operator)
+    );
+
+    let operators = node_operator::get_by_census(census.id, &conn)
+        .await
+        .unwrap();
+    assert_eq!(operators.len(), 1);
+    assert_eq!(operators[0].display_name, "Alice's team");
+    assert_eq!(operators[0].contact, None);
+    assert_eq!(operators[0].node_id, enr.node_id().raw().to_vec());
+}
+
+/// Tests that keys whose audits alternate between passing and failing get a flapping score,
+/// while keys that flipped once are left out.
+#[tokio::test]
+async fn test_content_flapping() {
+    use ethportal_api::BlockBodyKey;
+
+    let (conn, _db) = setup_database().await.unwrap();
+    let now = Utc::now();
+    let client_info_model = client_info::get_or_create("trin v0.1.0".to_owned(), &conn)
+        .await
+        .unwrap();
+    let node = node::get_or_create(NodeId::random(), &conn).await.unwrap();
+    let strategy = SelectionStrategy::History(HistorySelectionStrategy::Random);
+
+    let alternating = content::get_or_create(
+        SubProtocol::History,
+        &sample_history_key(),
+        now,
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let went_missing = content::get_or_create(
+        SubProtocol::History,
+        &HistoryContentKey::BlockBody(BlockBodyKey {
+            block_hash: [1; 32],
+        }),
+        now,
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let audits = [
+        (alternating.id, [true, false, true, false, true].as_slice()),
+        (went_missing.id, [true, true, false, false].as_slice()),
     ];
-    assert_eq!(expected_distances_a, actual_distances_a);
+    for (content_id, outcomes) in audits {
+        for query_successful in outcomes {
+            content_audit::create(
+                content_id,
+                client_info_model.id,
+                node.id,
+                *query_successful,
+                strategy.clone(),
+                "".to_owned(),
+                &conn,
+            )
+            .await
+            .unwrap();
+        }
+    }
 
-    let expected_from_a = match distance_a_b > distance_a_c {
-        true => [node_a.id, node_c.id, node_b.id],
-        false => [node_a.id, node_b.id, node_c.id],
-    };
-    let order_from_a = [nodes_near_a[0].id, nodes_near_a[1].id, nodes_near_a[2].id];
-    assert_eq!(order_from_a, expected_from_a);
+    let num_keys = content_flapping::refresh(now - chrono::Duration::hours(1), 2, &conn)
+        .await
+        .unwrap();
+    assert_eq!(num_keys, 1);
+    let unstable = content_flapping::get_most_unstable(SubProtocol::History, 10, false, &conn)
+        .await
+        .unwrap();
+    assert_eq!(unstable.len(), 1);
+    assert_eq!(unstable[0].content_key, alternating.key_as_hex());
+    assert_eq!(unstable[0].num_audits, 5);
+    assert_eq!(unstable[0].num_flips, 4);
+    assert_eq!(unstable[0].flapping_score, 1.0);
 
-    let nodes_near_b = node::closest_xor(node_id_b, &conn).await.unwrap();
-    let expected_from_b = match distance_a_b > distance_b_c {
-        true => [node_b.id, node_c.id, node_a.id],
-        false => [node_b.id, node_a.id, node_c.id],
-    };
-    let order_from_b = [nodes_near_b[0].id, nodes_near_b[1].id, nodes_near_b[2].id];
-    assert_eq!(order_from_b, expected_from_b);
+    // Audits before the window are not counted.
+    let num_keys = content_flapping::refresh(Utc::now(), 2, &conn)
+        .await
+        .unwrap();
+    assert_eq!(num_keys, 0);
+    assert!(
+        content_flapping::get_most_unstable(SubProtocol::History, 10, false, &conn)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
 
-    let nodes_near_c = node::closest_xor(node_id_c, &conn).await.unwrap();
-    let expected_from_c = match distance_a_c > distance_b_c {
-        true => [node_c.id, node_b.id, node_a.id],
-        false => [node_c.id, node_a.id, node_b.id],
-    };
-    let order_from_c = [nodes_near_c[0].id, nodes_near_c[1].id, nodes_near_c[2].id];
-    assert_eq!(order_from_c, expected_from_c);
+/// Tests that round tripped content is recorded with its latency, and is no longer a candidate.
+#[tokio::test]
+async fn test_round_trip_audit() {
+    let (conn, _db) = setup_database().await.unwrap();
+    let offered_at = Utc.with_ymd_and_hms(2024, 10, 30, 14, 0, 0).unwrap();
+    let content = content::get_or_create(
+        SubProtocol::History,
+        &sample_history_key(),
+        offered_at,
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    .unwrap();
+
+    let candidates = round_trip_audit::get_candidates(10, &conn).await.unwrap();
+    assert_eq!(candidates, vec![content.clone()]);
+
+    let client_info_model = client_info::get_or_create("trin v0.1.0".to_owned(), &conn)
+        .await
+        .unwrap();
+    let node = node::get_or_create(NodeId::random(), &conn).await.unwrap();
+    let audit = content_audit::create(
+        content.id,
+        client_info_model.id,
+        node.id,
+        true,
+        SelectionStrategy::History(HistorySelectionStrategy::RoundTrip),
+        "".to_owned(),
+        &conn,
+    )
+    .await
+    .unwrap();
+    let round_trip = round_trip_audit::create(
+        content.id,
+        offered_at,
+        8,
+        3,
+        Some(audit.id),
+        Some(offered_at + chrono::Duration::seconds(45)),
+        &conn,
+    )
+    .await
+    .unwrap();
+    assert_eq!(round_trip.latency_ms(), Some(45_000));
+
+    assert!(round_trip_audit::get_candidates(10, &conn)
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        round_trip_audit::get_latest(10, &conn).await.unwrap(),
+        vec![round_trip]
+    );
+}
+
+/// Tests that history keys whose latest audit failed are counted by block range and type.
+#[tokio::test]
+async fn test_failed_by_block_range() {
+    use ethportal_api::{BlockBodyKey, BlockReceiptsKey};
+
+    let (conn, _db) = setup_database().await.unwrap();
+    let client_info_model = client_info::get_or_create("trin v0.1.0".to_owned(), &conn)
+        .await
+        .unwrap();
+    let node = node::get_or_create(NodeId::random(), &conn).await.unwrap();
+    let strategy = SelectionStrategy::History(HistorySelectionStrategy::FourFours);
+
+    // Only the latest audit of a key counts, so the last key is no longer failing.
+    let keys = [
+        (4_100_000, receipts_key(1), vec![false]),
+        (4_199_999, receipts_key(2), vec![true, false]),
+        (4_150_000, body_key(3), vec![false]),
+        (4_250_000, receipts_key(4), vec![false]),
+        (4_300_000, receipts_key(5), vec![false, true]),
+    ];
+    for (block_number, content_key, outcomes) in keys {
+        let content = content::get_or_create(
+            SubProtocol::History,
+            &content_key,
+            Utc::now(),
+            ContentSource::Monitor,
+            &conn,
+        )
+        .await
+        .unwrap();
+        execution_metadata::get_or_create(content.id, block_number, &conn)
+            .await
+            .unwrap();
+        for query_successful in outcomes {
+            content_audit::create(
+                content.id,
+                client_info_model.id,
+                node.id,
+                query_successful,
+                strategy.clone(),
+                "".to_owned(),
+                &conn,
+            )
+            .await
+            .unwrap();
+        }
+    }
+
+    let ranges = audit_latest::get_failed_by_block_range(100_000, false, &conn)
+        .await
+        .unwrap();
+    assert_eq!(
+        ranges,
+        vec![
+            audit_latest::FailedBlockRange {
+                range_start: 4_100_000,
+                selector: 0x01,
+                num_failed: 1,
+            },
+            audit_latest::FailedBlockRange {
+                range_start: 4_100_000,
+                selector: 0x02,
+                num_failed: 2,
+            },
+            audit_latest::FailedBlockRange {
+                range_start: 4_200_000,
+                selector: 0x02,
+                num_failed: 1,
+            },
+        ]
+    );
+
+    fn receipts_key(seed: u8) -> HistoryContentKey {
+        HistoryContentKey::BlockReceipts(BlockReceiptsKey {
+            block_hash: [seed; 32],
+        })
+    }
+
+    fn body_key(seed: u8) -> HistoryContentKey {
+        HistoryContentKey::BlockBody(BlockBodyKey {
+            block_hash: [seed; 32],
+        })
+    }
+}
+
+/// Tests that feature flags are created, toggled and listed by name.
+#[tokio::test]
+async fn test_feature_flags() {
+    let (conn, _db) = setup_database().await.unwrap();
+    assert!(feature_flag::get_all(&conn).await.unwrap().is_empty());
+
+    feature_flag::set("offer_audits", true, None, &conn)
+        .await
+        .unwrap();
+    let scanner = feature_flag::set(
+        "deadzone_scanner",
+        true,
+        Some("Scan for content nobody stores".to_owned()),
+        &conn,
+    )
+    .await
+    .unwrap();
+    assert!(scanner.enabled);
+
+    let toggled = feature_flag::set("deadzone_scanner", false, None, &conn)
+        .await
+        .unwrap();
+    assert!(!toggled.enabled);
+    assert!(toggled.updated_at >= scanner.updated_at);
+
+    let flags: Vec<(String, bool)> = feature_flag::get_all(&conn)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|flag| (flag.name, flag.enabled))
+        .collect();
+    assert_eq!(
+        flags,
+        vec![
+            ("deadzone_scanner".to_owned(), false),
+            ("offer_audits".to_owned(), true),
+        ]
+    );
+}
+
+/// Tests that queued audit tasks are claimed by one worker at a time, reclaimed after the
+/// visibility timeout, and queued again once completed.
+#[tokio::test]
+async fn test_audit_task_queue() {
+    let (conn, _db) = setup_database().await.unwrap();
+    let content = content::get_or_create(
+        SubProtocol::History,
+        &sample_history_key(),
+        Utc::now(),
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let strategy = SelectionStrategy::History(HistorySelectionStrategy::Latest);
+    let timeout = chrono::TimeDelta::minutes(10);
+
+    assert!(
+        audit_task::enqueue(content.id, strategy.clone(), Utc::now(), &conn)
+            .await
+            .unwrap()
+    );
+    // The key is not queued again while its audit is open.
+    assert!(
+        !audit_task::enqueue(content.id, strategy.clone(), Utc::now(), &conn)
+            .await
+            .unwrap()
+    );
+    assert_eq!(audit_task::count_open(&conn).await.unwrap(), 1);
+
+    let claimed = audit_task::claim("worker-a", 10, timeout, &conn)
+        .await
+        .unwrap();
+    assert_eq!(claimed.len(), 1);
+    assert_eq!(claimed[0].claimed_by.as_deref(), Some("worker-a"));
+    assert_eq!(claimed[0].strategy_used, strategy);
+    assert!(audit_task::claim("worker-b", 10, timeout, &conn)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // A task not completed within the timeout is claimed again.
+    let reclaimed = audit_task::claim("worker-b", 10, chrono::TimeDelta::zero(), &conn)
+        .await
+        .unwrap();
+    assert_eq!(reclaimed.len(), 1);
+    assert_eq!(reclaimed[0].claimed_by.as_deref(), Some("worker-b"));
+
+    let client_info_model = client_info::get_or_create("trin v0.1.0".to_owned(), &conn)
+        .await
+        .unwrap();
+    let node = node::get_or_create(NodeId::random(), &conn).await.unwrap();
+    let audit = content_audit::create(
+        content.id,
+        client_info_model.id,
+        node.id,
+        true,
+        strategy.clone(),
+        "".to_owned(),
+        &conn,
+    )
+    .await
+    .unwrap();
+    audit_task::complete(reclaimed[0].id, audit.id, &conn)
+        .await
+        .unwrap();
+    assert_eq!(audit_task::count_open(&conn).await.unwrap(), 0);
+    assert!(
+        audit_task::claim("worker-a", 10, chrono::TimeDelta::zero(), &conn)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    assert!(audit_task::enqueue(content.id, strategy, Utc::now(), &conn)
+        .await
+        .unwrap());
+
+    let deleted = audit_task::delete_completed(Utc::now() + chrono::TimeDelta::seconds(1), &conn)
+        .await
+        .unwrap();
+    assert_eq!(deleted, 1);
+    assert_eq!(audit_task::count_open(&conn).await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn test_audit_worker_heartbeat() {
+    let (conn, _db) = setup_database().await.unwrap();
+    let now = Utc::now();
+    let timeout = chrono::TimeDelta::minutes(2);
+    assert_eq!(
+        audit_worker::count_active(now - timeout, &conn)
+            .await
+            .unwrap(),
+        0
+    );
+
+    audit_worker::heartbeat("worker-a", now - chrono::TimeDelta::minutes(5), &conn)
+        .await
+        .unwrap();
+    audit_worker::heartbeat("worker-b", now, &conn)
+        .await
+        .unwrap();
+    assert_eq!(
+        audit_worker::count_active(now - timeout, &conn)
+            .await
+            .unwrap(),
+        1
+    );
+
+    // A later heartbeat updates the worker instead of adding one.
+    audit_worker::heartbeat("worker-a", now, &conn)
+        .await
+        .unwrap();
+    assert_eq!(
+        audit_worker::count_active(now - timeout, &conn)
+            .await
+            .unwrap(),
+        2
+    );
+
+    audit_worker::remove("worker-b", &conn).await.unwrap();
+    assert_eq!(
+        audit_worker::count_active(now - timeout, &conn)
+            .await
+            .unwrap(),
+        1
+    );
+}
+
+/// Tests that audit outcomes are grouped by day of the week and hour of the day in the
+/// requested timezone.
+#[tokio::test]
+async fn test_results_by_hour_of_week() {
+    let (conn, _db) = setup_database().await.unwrap();
+    // A Monday.
+    let monday = Utc.with_ymd_and_hms(2024, 11, 4, 23, 30, 0).unwrap();
+
+    let content = content::get_or_create(
+        SubProtocol::History,
+        &sample_history_key(),
+        monday,
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let client_info_model = client_info::get_or_create("trin v0.1.0".to_owned(), &conn)
+        .await
+        .unwrap();
+    let node = node::get_or_create(NodeId::random(), &conn).await.unwrap();
+    let strategy = SelectionStrategy::History(HistorySelectionStrategy::Random);
+    for query_successful in [true, true, false] {
+        let audit = content_audit::create(
+            content.id,
+            client_info_model.id,
+            node.id,
+            query_successful,
+            strategy.clone(),
+            "".to_owned(),
+            &conn,
+        )
+        .await
+        .unwrap();
+        let mut audit: content_audit::ActiveModel = audit.into();
+        audit.created_at = Set(monday);
+        audit.update(&conn).await.unwrap();
+    }
+
+    let start = monday - chrono::Duration::days(1);
+    let end = monday + chrono::Duration::days(1);
+    let results =
+        content_audit::get_results_by_hour_of_week(SubProtocol::History, start, end, "UTC", &conn)
+            .await
+            .unwrap();
+    assert_eq!(
+        results,
+        vec![content_audit::HourOfWeekResults {
+            day_of_week: 1,
+            hour: 23,
+            success: 2,
+            failure: 1,
+        }]
+    );
+
+    // Monday 23:30 UTC is Tuesday 00:30 in Berlin.
+    let results = content_audit::get_results_by_hour_of_week(
+        SubProtocol::History,
+        start,
+        end,
+        "Europe/Berlin",
+        &conn,
+    )
+    .await
+    .unwrap();
+    assert_eq!((results[0].day_of_week, results[0].hour), (2, 0));
+
+    let results =
+        content_audit::get_results_by_hour_of_week(SubProtocol::State, start, end, "UTC", &conn)
+            .await
+            .unwrap();
+    assert!(results.is_empty());
+}
+
+/// Tests the decoding of ENR protocol versions and the lookup of the nodes found by the
+/// censuses of a subnetwork.
+#[tokio::test]
+async fn test_census_node_capabilities() {
+    use enr::CombinedKey;
+    use ethportal_api::types::enr::Enr;
+
+    let legacy_enr = Enr::builder()
+        .build(&CombinedKey::generate_secp256k1())
+        .unwrap();
+    let v1_enr = Enr::builder()
+        .add_value("pv", &vec![0u8, 1u8])
+        .build(&CombinedKey::generate_secp256k1())
+        .unwrap();
+    assert_eq!(
+        census_client_stats::enr_protocol_versions(&legacy_enr),
+        Some(vec![0])
+    );
+    assert_eq!(
+        census_client_stats::enr_protocol_versions(&v1_enr),
+        Some(vec![0, 1])
+    );
+
+    let (conn, _db) = setup_database().await.unwrap();
+    let now = Utc::now();
+    let legacy_record = record::get_or_create(&legacy_enr, &conn).await.unwrap();
+    let v1_record = record::get_or_create(&v1_enr, &conn).await.unwrap();
+    let history_census = census::create(now, 60, SubProtocol::History, &conn)
+        .await
+        .unwrap();
+    let state_census = census::create(now, 60, SubProtocol::State, &conn)
+        .await
+        .unwrap();
+    for (census, record) in [
+        (&history_census, &legacy_record),
+        (&history_census, &v1_record),
+        (&state_census, &v1_record),
+    ] {
+        census_node::create(
+            census.id,
+            record.id,
+            U256::MAX,
+            census.started_at,
+            census.sub_network,
+            SINGLE_PING,
+            false,
+            Reachability::default(),
+            &conn,
+        )
+        .await
+        .unwrap();
+    }
+
+    let start = now - chrono::Duration::hours(1);
+    let end = now + chrono::Duration::hours(1);
+    let state_nodes =
+        census_node::get_node_ids_by_subprotocol(SubProtocol::State, start, end, &conn)
+            .await
+            .unwrap();
+    assert_eq!(state_nodes, vec![v1_enr.node_id().raw().to_vec()]);
+    let history_nodes =
+        census_node::get_node_ids_by_subprotocol(SubProtocol::History, start, end, &conn)
+            .await
+            .unwrap();
+    assert_eq!(history_nodes.len(), 2);
+    let beacon_nodes =
+        census_node::get_node_ids_by_subprotocol(SubProtocol::Beacon, start, end, &conn)
+            .await
+            .unwrap();
+    assert!(beacon_nodes.is_empty());
+}
+
+/// Tests the summary of the PING attempts and latencies of the nodes of a census.
+#[tokio::test]
+async fn test_census_ping_summaries() {
+    use enr::CombinedKey;
+    use ethportal_api::types::enr::Enr;
+
+    let (conn, _db) = setup_database().await.unwrap();
+    let now = Utc::now();
+    let older = census::create(
+        now - chrono::Duration::minutes(15),
+        60,
+        SubProtocol::History,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let latest = census::create(now, 60, SubProtocol::History, &conn)
+        .await
+        .unwrap();
+    for (attempts, latency_ms) in [(1, 10), (1, 30), (2, 50), (3, 70)] {
+        let enr = Enr::builder()
+            .build(&CombinedKey::generate_secp256k1())
+            .unwrap();
+        let record = record::get_or_create(&enr, &conn).await.unwrap();
+        census_node::create(
+            latest.id,
+            record.id,
+            U256::MAX,
+            latest.started_at,
+            SubProtocol::History,
+            PingMeasurement {
+                attempts,
+                latency_ms,
+            },
+            false,
+            Reachability::default(),
+            &conn,
+        )
+        .await
+        .unwrap();
+    }
+
+    let summaries = census_node::get_ping_summaries(SubProtocol::History, 10, &conn)
+        .await
+        .unwrap();
+    assert_eq!(summaries.len(), 2);
+    assert_eq!(summaries[0].census_id, latest.id);
+    assert_eq!(summaries[0].nodes, 4);
+    assert_eq!(summaries[0].retried_nodes, 2);
+    assert_eq!(summaries[0].retried_percent(), Some(50.0));
+    assert_eq!(summaries[0].average_latency_ms, Some(40.0));
+    assert_eq!(summaries[1].census_id, older.id);
+    assert_eq!(summaries[1].nodes, 0);
+    assert_eq!(summaries[1].retried_percent(), None);
+    assert_eq!(summaries[1].p95_latency_ms, None);
+
+    let summaries = census_node::get_ping_summaries(SubProtocol::History, 1, &conn)
+        .await
+        .unwrap();
+    assert_eq!(summaries.len(), 1);
+}
+
+/// Tests that the sightings of client versions keep the earliest and latest census times.
+#[tokio::test]
+async fn test_client_version_sightings() {
+    use enr::CombinedKey;
+    use ethportal_api::types::enr::Enr;
+
+    let versioned = Enr::builder()
+        .add_value("c", &"t 0.1.1-5a6e5b5".to_owned())
+        .build(&CombinedKey::generate_secp256k1())
+        .unwrap();
+    let unversioned = Enr::builder()
+        .add_value("c", &"f".to_owned())
+        .build(&CombinedKey::generate_secp256k1())
+        .unwrap();
+    assert_eq!(
+        census_client_stats::enr_client_version(&versioned),
+        Some("0.1.1-5a6e5b5".to_owned())
+    );
+    assert_eq!(census_client_stats::enr_client_version(&unversioned), None);
+
+    let (conn, _db) = setup_database().await.unwrap();
+    let now = Utc::now().trunc_subsecs(6);
+    let trin_old = ("trin".to_owned(), "0.1.0".to_owned());
+    let trin_new = ("trin".to_owned(), "0.1.1".to_owned());
+    let fluffy = ("fluffy".to_owned(), "0.1.0".to_owned());
+    let earlier = now - chrono::Duration::hours(1);
+    client_version_sighting::record_many(
+        &[trin_old.clone(), fluffy].into_iter().collect(),
+        now,
+        &conn,
+    )
+    .await
+    .unwrap();
+    // A census recorded late, such as one started before the previous census finished.
+    client_version_sighting::record_many(&[trin_old].into_iter().collect(), earlier, &conn)
+        .await
+        .unwrap();
+    client_version_sighting::record_many(
+        &[trin_new].into_iter().collect(),
+        now + chrono::Duration::hours(1),
+        &conn,
+    )
+    .await
+    .unwrap();
+
+    let timeline = client_version_sighting::get_timeline(None, &conn)
+        .await
+        .unwrap();
+    let versions: Vec<(&str, &str)> = timeline
+        .iter()
+        .map(|sighting| (sighting.client.as_str(), sighting.version.as_str()))
+        .collect();
+    assert_eq!(
+        versions,
+        vec![("fluffy", "0.1.0"), ("trin", "0.1.0"), ("trin", "0.1.1")]
+    );
+    assert_eq!(timeline[1].first_seen_at, earlier);
+    assert_eq!(timeline[1].last_seen_at, now);
+
+    let trin = client_version_sighting::get_timeline(Some("trin"), &conn)
+        .await
+        .unwrap();
+    assert_eq!(trin.len(), 2);
+}
+
+/// Tests that the daily transfer summary counts the successful and failed transfers of the
+/// audit traces by content type, and that refreshing a day replaces its counts.
+#[tokio::test]
+async fn test_transfer_failure_daily() {
+    use ethportal_api::BlockBodyKey;
+
+    let (conn, _db) = setup_database().await.unwrap();
+    let now = Utc::now();
+
+    let header = content::get_or_create(
+        SubProtocol::History,
+        &sample_history_key(),
+        now,
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let body_key = HistoryContentKey::BlockBody(BlockBodyKey {
+        block_hash: [1; 32],
+    });
+    let body = content::get_or_create(
+        SubProtocol::History,
+        &body_key,
+        now,
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let client_info_model = client_info::get_or_create("trin v0.1.0".to_owned(), &conn)
+        .await
+        .unwrap();
+    let node = node::get_or_create(NodeId::random(), &conn).await.unwrap();
+    let strategy = SelectionStrategy::History(HistorySelectionStrategy::Random);
+    let traces = [
+        // Received from another node after one failed transfer.
+        (
+            header.id,
+            r#"{"origin":"0xaa","receivedFrom":"0xbb","failures":{"0xcc":{"durationMs":5,"failure":"utpTransferFailed"}}}"#,
+        ),
+        // Not found, after two failed connections.
+        (
+            body.id,
+            r#"{"origin":"0xaa","failures":{"0xcc":{"durationMs":5,"failure":"utpConnectionFailed"},"0xdd":{"durationMs":7,"failure":"utpConnectionFailed"}}}"#,
+        ),
+        // Audits without a trace are skipped.
+        (body.id, ""),
+    ];
+    let mut day = now;
+    for (content_id, trace) in traces {
+        let audit = content_audit::create(
+            content_id,
+            client_info_model.id,
+            node.id,
+            true,
+            strategy.clone(),
+            trace.to_owned(),
+            &conn,
+        )
+        .await
+        .unwrap();
+        day = audit.created_at;
+    }
+    let day = day.date_naive().and_hms_opt(0, 0, 0).unwrap().and_utc();
+
+    assert_eq!(
+        transfer_failure_daily::refresh(day, &conn).await.unwrap(),
+        3
+    );
+    // Refreshing the day again replaces its counts.
+    assert_eq!(
+        transfer_failure_daily::refresh(day, &conn).await.unwrap(),
+        3
+    );
+
+    let counts = transfer_failure_daily::get_between(
+        day,
+        day + chrono::Duration::days(1),
+        SubProtocol::History,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let counts: Vec<(i32, &str, i64)> = counts
+        .iter()
+        .map(|c| (c.content_type, c.failure_type.as_str(), c.num_transfers))
+        .collect();
+    assert_eq!(
+        counts,
+        vec![
+            (0x00, transfer_failure_daily::NO_FAILURE, 1),
+            (0x00, "utpTransferFailed", 1),
+            (0x01, "utpConnectionFailed", 2),
+        ]
+    );
+}
+
+/// Tests that the nodes that recently ran audits are found, so censuses can flag them as run
+/// by this deployment.
+#[tokio::test]
+async fn test_auditing_nodes() {
+    let (conn, _db) = setup_database().await.unwrap();
+    let now = Utc::now();
+
+    let content_model = content::get_or_create(
+        SubProtocol::History,
+        &sample_history_key(),
+        now,
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let client_info_model = client_info::get_or_create("trin v0.1.0".to_owned(), &conn)
+        .await
+        .unwrap();
+    let auditor = node::get_or_create(NodeId::random(), &conn).await.unwrap();
+    // Found by a census, but never audited anything.
+    node::get_or_create(NodeId::random(), &conn).await.unwrap();
+    content_audit::create(
+        content_model.id,
+        client_info_model.id,
+        auditor.id,
+        true,
+        SelectionStrategy::History(HistorySelectionStrategy::Random),
+        "".to_owned(),
+        &conn,
+    )
+    .await
+    .unwrap();
+
+    let auditing = node::get_auditing_since(now - chrono::Duration::hours(1), &conn)
+        .await
+        .unwrap();
+    assert_eq!(auditing, vec![auditor]);
+
+    let later = node::get_auditing_since(now + chrono::Duration::hours(1), &conn)
+        .await
+        .unwrap();
+    assert!(later.is_empty());
+}
+
+/// Tests that completed audits are counted by content type and auditing client version.
+#[tokio::test]
+async fn test_results_by_content_type_and_client() {
+    use ethportal_api::BlockBodyKey;
+
+    let (conn, _db) = setup_database().await.unwrap();
+    let now = Utc::now();
+
+    let header = content::get_or_create(
+        SubProtocol::History,
+        &sample_history_key(),
+        now,
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let body_key = HistoryContentKey::BlockBody(BlockBodyKey {
+        block_hash: [1; 32],
+    });
+    let body = content::get_or_create(
+        SubProtocol::History,
+        &body_key,
+        now,
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let trin = client_info::get_or_create("trin v0.1.0".to_owned(), &conn)
+        .await
+        .unwrap();
+    let fluffy = client_info::get_or_create("fluffy v0.2.0".to_owned(), &conn)
+        .await
+        .unwrap();
+    let node = node::get_or_create(NodeId::random(), &conn).await.unwrap();
+    let strategy = SelectionStrategy::History(HistorySelectionStrategy::Random);
+    for (content_id, client_info_id, query_successful) in [
+        (header.id, trin.id, true),
+        (header.id, trin.id, false),
+        (header.id, fluffy.id, true),
+        (body.id, trin.id, true),
+    ] {
+        content_audit::create(
+            content_id,
+            client_info_id,
+            node.id,
+            query_successful,
+            strategy.clone(),
+            "".to_owned(),
+            &conn,
+        )
+        .await
+        .unwrap();
+    }
+
+    let results = content_audit::get_results_by_content_type_and_client(
+        SubProtocol::History,
+        now - chrono::Duration::hours(1),
+        now + chrono::Duration::hours(1),
+        &conn,
+    )
+    .await
+    .unwrap();
+    let results: Vec<(i32, &str, i64, i64)> = results
+        .iter()
+        .map(|r| (r.selector, r.client.as_str(), r.success, r.failure))
+        .collect();
+    assert_eq!(
+        results,
+        vec![
+            (0x00, "fluffy v0.2.0", 1, 0),
+            (0x00, "trin v0.1.0", 1, 1),
+            (0x01, "trin v0.1.0", 1, 0),
+        ]
+    );
+}
+
+/// Tests that the nodes that dropped out of a census are listed with why they failed, and
+/// summarized by failure.
+#[tokio::test]
+async fn test_census_node_errors() {
+    use enr::CombinedKey;
+    use ethportal_api::types::enr::Enr;
+
+    let (conn, _db) = setup_database().await.unwrap();
+
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let census = census::create(start, 60, SubProtocol::History, &conn)
+        .await
+        .unwrap();
+    let other_census = census::create(start, 60, SubProtocol::History, &conn)
+        .await
+        .unwrap();
+
+    let mut records = vec![];
+    for _ in 0..3 {
+        let enr = Enr::builder()
+            .build(&CombinedKey::generate_secp256k1())
+            .unwrap();
+        records.push(record::get_or_create(&enr, &conn).await.unwrap());
+    }
+    let failure = |record_id: i32, kind: CensusNodeErrorKind, attempts: i32| NodeFailure {
+        record_id,
+        surveyed_at: start,
+        kind,
+        attempts,
+        duration_ms: attempts * 2000,
+        message: None,
+    };
+    census_node_error::create_many(
+        census.id,
+        &[
+            failure(records[0].id, CensusNodeErrorKind::Timeout, 3),
+            failure(records[1].id, CensusNodeErrorKind::Timeout, 1),
+            NodeFailure {
+                message: Some("failed to decode PONG".to_string()),
+                ..failure(records[2].id, CensusNodeErrorKind::DecodeError, 1)
+            },
+        ],
+        &conn,
+    )
+    .await
+    .unwrap();
+    census_node_error::create_many(
+        other_census.id,
+        &[failure(records[0].id, CensusNodeErrorKind::ErrorPong, 1)],
+        &conn,
+    )
+    .await
+    .unwrap();
+    census_node_error::create_many(other_census.id, &[], &conn)
+        .await
+        .unwrap();
+
+    let failures = census_node_error::get_by_census(census.id, &conn)
+        .await
+        .unwrap();
+    let kinds: Vec<CensusNodeErrorKind> = failures.iter().map(|f| f.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            CensusNodeErrorKind::Timeout,
+            CensusNodeErrorKind::Timeout,
+            CensusNodeErrorKind::DecodeError
+        ]
+    );
+    let node = node::Entity::find_by_id(records[2].node_id)
+        .one(&conn)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(failures[2].node_id, node.node_id);
+    assert_eq!(
+        failures[2].message.as_deref(),
+        Some("failed to decode PONG")
+    );
+
+    let summary = census_node_error::get_summary(census.id, &conn)
+        .await
+        .unwrap();
+    assert_eq!(summary.len(), 2);
+    assert_eq!(summary[0].kind, CensusNodeErrorKind::Timeout);
+    assert_eq!(summary[0].nodes, 2);
+    assert_eq!(summary[0].average_attempts, 2.0);
+    assert_eq!(summary[0].average_duration_ms, 4000.0);
+    assert_eq!(summary[1].kind, CensusNodeErrorKind::DecodeError);
+    assert_eq!(summary[1].nodes, 1);
+}
+
+/// Tests that comparing two censuses finds the nodes that joined, left, changed client version
+/// and changed radius.
+#[tokio::test]
+async fn test_census_diff() {
+    use enr::CombinedKey;
+    use ethportal_api::types::enr::Enr;
+
+    let (conn, _db) = setup_database().await.unwrap();
+
+    let enr_with = |key: &CombinedKey, seq: u64, client: &str| -> Enr {
+        Enr::builder()
+            .seq(seq)
+            .add_value("c", &client.to_owned())
+            .build(key)
+            .unwrap()
+    };
+    let keys: Vec<CombinedKey> = (0..4).map(|_| CombinedKey::generate_secp256k1()).collect();
+    let upgraded = record::get_or_create(&enr_with(&keys[0], 1, "t 0.1.0"), &conn)
+        .await
+        .unwrap();
+    let upgraded_after = record::get_or_create(&enr_with(&keys[0], 2, "t 0.1.1"), &conn)
+        .await
+        .unwrap();
+    let shrunk = record::get_or_create(&enr_with(&keys[1], 1, "f 0.1.0"), &conn)
+        .await
+        .unwrap();
+    let left = record::get_or_create(&enr_with(&keys[2], 1, "t 0.1.0"), &conn)
+        .await
+        .unwrap();
+    let joined = record::get_or_create(&enr_with(&keys[3], 1, "t 0.1.1"), &conn)
+        .await
+        .unwrap();
+
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let from = census::create(start, 60, SubProtocol::History, &conn)
+        .await
+        .unwrap();
+    let to = census::create(
+        start + chrono::Duration::hours(1),
+        60,
+        SubProtocol::History,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let smaller_radius = U256::MAX >> 1;
+    for (census_id, record_id, radius) in [
+        (from.id, upgraded.id, U256::MAX),
+        (from.id, shrunk.id, U256::MAX),
+        (from.id, left.id, U256::MAX),
+        (to.id, upgraded_after.id, U256::MAX),
+        (to.id, shrunk.id, smaller_radius),
+        (to.id, joined.id, U256::MAX),
+    ] {
+        census_node::create(
+            census_id,
+            record_id,
+            radius,
+            start,
+            SubProtocol::History,
+            SINGLE_PING,
+            false,
+            Reachability::default(),
+            &conn,
+        )
+        .await
+        .unwrap();
+    }
+
+    let node_id = |key: &CombinedKey| hex_encode(enr_with(key, 1, "t").node_id().raw());
+    let diff = census_node::get_diff(from.id, to.id, false, &conn)
+        .await
+        .unwrap();
+    let node_ids = |changes: &[census_node::CensusNodeChange]| -> Vec<String> {
+        changes.iter().map(|c| c.node_id.clone()).collect()
+    };
+    assert_eq!(node_ids(&diff.joined), vec![node_id(&keys[3])]);
+    assert_eq!(node_ids(&diff.left), vec![node_id(&keys[2])]);
+    assert_eq!(
+        node_ids(&diff.client_version_changed),
+        vec![node_id(&keys[0])]
+    );
+    assert_eq!(
+        diff.client_version_changed[0]
+            .from_client_version
+            .as_deref(),
+        Some("0.1.0")
+    );
+    assert_eq!(
+        diff.client_version_changed[0].to_client_version.as_deref(),
+        Some("0.1.1")
+    );
+    assert_eq!(node_ids(&diff.radius_changed), vec![node_id(&keys[1])]);
+    assert_eq!(
+        diff.radius_changed[0].to_radius,
+        Some(hex_encode(smaller_radius.to_be_bytes::<32>()))
+    );
+
+    let unchanged = census_node::get_diff(from.id, from.id, false, &conn)
+        .await
+        .unwrap();
+    assert_eq!(unchanged, census_node::CensusDiff::default());
+}
+
+/// Tests that only the nodes with a declared endpoint that failed its probe are listed as
+/// unreachable.
+#[tokio::test]
+async fn test_unreachable_endpoints() {
+    use enr::CombinedKey;
+    use ethportal_api::types::enr::Enr;
+
+    let (conn, _db) = setup_database().await.unwrap();
+
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let census = census::create(start, 60, SubProtocol::History, &conn)
+        .await
+        .unwrap();
+    let probes = [
+        (Some(true), Some(true)),
+        (Some(true), Some(false)),
+        (Some(false), None),
+        (None, None),
+    ];
+    let mut records = vec![];
+    for (udp, tcp) in probes {
+        let enr = Enr::builder()
+            .build(&CombinedKey::generate_secp256k1())
+            .unwrap();
+        let record = record::get_or_create(&enr, &conn).await.unwrap();
+        census_node::create(
+            census.id,
+            record.id,
+            U256::MAX,
+            start,
+            SubProtocol::History,
+            SINGLE_PING,
+            false,
+            Reachability { udp, tcp },
+            &conn,
+        )
+        .await
+        .unwrap();
+        records.push(record);
+    }
+
+    let unreachable = census_node::get_unreachable_endpoints(census.id, false, &conn)
+        .await
+        .unwrap();
+    let mut raws: Vec<&str> = unreachable.iter().map(|e| e.raw.as_str()).collect();
+    raws.sort();
+    let mut expected = vec![records[1].raw.as_str(), records[2].raw.as_str()];
+    expected.sort();
+    assert_eq!(raws, expected);
+}
+
+/// Tests that the latest audits of a batch of content keys are found, leaving out keys that are
+/// unknown, never audited, or of another subnetwork.
+#[tokio::test]
+async fn test_latest_audits_by_content_keys() {
+    let (conn, _db) = setup_database().await.unwrap();
+
+    let keys: Vec<HistoryContentKey> = (0..3u8)
+        .map(|i| HistoryContentKey::new_block_header_by_hash([i; 32]))
+        .collect();
+    let mut contents = vec![];
+    for key in &keys {
+        contents.push(
+            content::get_or_create(
+                SubProtocol::History,
+                key,
+                Utc::now(),
+                ContentSource::Monitor,
+                &conn,
+            )
+            .await
+            .unwrap(),
+        );
+    }
+    let client_info_model = client_info::get_or_create("trin v0.1.0".to_owned(), &conn)
+        .await
+        .unwrap();
+    let node = node::get_or_create(NodeId::random(), &conn).await.unwrap();
+    let strategy = SelectionStrategy::History(HistorySelectionStrategy::Random);
+    // The first key was found after failing, the second key failed, the third was not audited.
+    for (content_model, query_successful) in [
+        (&contents[0], false),
+        (&contents[0], true),
+        (&contents[1], false),
+    ] {
+        content_audit::create(
+            content_model.id,
+            client_info_model.id,
+            node.id,
+            query_successful,
+            strategy.clone(),
+            "".to_owned(),
+            &conn,
+        )
+        .await
+        .unwrap();
+    }
+
+    let mut requested: Vec<Vec<u8>> = keys.iter().map(|key| key.to_bytes().to_vec()).collect();
+    requested.push(vec![0xff; 33]);
+    let mut latest =
+        audit_latest::get_by_content_keys(requested.clone(), SubProtocol::History, &conn)
+            .await
+            .unwrap();
+    latest.sort_by_key(|(_, audit)| audit.content_key);
+    assert_eq!(latest.len(), 2);
+    assert_eq!(latest[0].0, requested[0]);
+    assert_eq!(latest[0].1.result, content_audit::AuditResult::Success);
+    assert_eq!(latest[0].1.consecutive_failures, 0);
+    assert_eq!(latest[1].0, requested[1]);
+    assert_eq!(latest[1].1.result, content_audit::AuditResult::Failure);
+    assert_eq!(latest[1].1.consecutive_failures, 1);
+
+    let other_network = audit_latest::get_by_content_keys(requested, SubProtocol::State, &conn)
+        .await
+        .unwrap();
+    assert!(other_network.is_empty());
+}
+
+/// Tests that audits record how long they waited after selection, and that the lag percentiles
+/// are grouped by strategy, leaving out audits without a measured lag.
+#[tokio::test]
+async fn test_selection_lag_percentiles() {
+    let (conn, _db) = setup_database().await.unwrap();
+
+    let content_model = content::get_or_create(
+        SubProtocol::History,
+        &sample_history_key(),
+        Utc::now(),
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let client_info_model = client_info::get_or_create("trin v0.1.0".to_owned(), &conn)
+        .await
+        .unwrap();
+    let node = node::get_or_create(NodeId::random(), &conn).await.unwrap();
+    let latest = SelectionStrategy::History(HistorySelectionStrategy::Latest);
+    let random = SelectionStrategy::History(HistorySelectionStrategy::Random);
+
+    for (strategy, selected_secs_ago) in [
+        (&latest, Some(10)),
+        (&latest, Some(20)),
+        (&latest, Some(30)),
+        (&random, Some(60)),
+        (&random, None),
+    ] {
+        let selected_at =
+            selected_secs_ago.map(|secs| Utc::now() - chrono::TimeDelta::seconds(secs));
+        let audit = content_audit::create_pending(
+            content_model.id,
+            client_info_model.id,
+            node.id,
+            strategy.clone(),
+            false,
+            selected_at,
+            None,
+            &conn,
+        )
+        .await
+        .unwrap();
+        match selected_secs_ago {
+            Some(secs) => {
+                let lag = i64::from(audit.selection_lag_ms.unwrap());
+                assert!(lag >= secs * 1000 && lag < (secs + 5) * 1000);
+            }
+            None => assert_eq!(audit.selection_lag_ms, None),
+        }
+    }
+
+    let percentiles = content_audit::get_selection_lag_percentiles(
+        Utc::now() - chrono::TimeDelta::hours(1),
+        Utc::now() + chrono::TimeDelta::hours(1),
+        &conn,
+    )
+    .await
+    .unwrap();
+    assert_eq!(percentiles.len(), 2);
+    let latest_lag = percentiles
+        .iter()
+        .find(|p| p.strategy_used == Some(latest.clone()))
+        .unwrap();
+    assert_eq!(latest_lag.num_audits, 3);
+    assert!(latest_lag.p50_ms >= 20_000.0 && latest_lag.p50_ms < 25_000.0);
+    assert!(latest_lag.p90_ms > latest_lag.p50_ms);
+    assert!(latest_lag.max_ms >= 30_000);
+    let random_lag = percentiles
+        .iter()
+        .find(|p| p.strategy_used == Some(random.clone()))
+        .unwrap();
+    assert_eq!(random_lag.num_audits, 1);
+    assert!(random_lag.p99_ms >= 60_000.0);
+
+    let earlier = content_audit::get_selection_lag_percentiles(
+        Utc::now() - chrono::TimeDelta::hours(2),
+        Utc::now() - chrono::TimeDelta::hours(1),
+        &conn,
+    )
+    .await
+    .unwrap();
+    assert!(earlier.is_empty());
+}
+
+/// Tests that audits requested by users are claimed before the selected ones, and that a key
+/// already queued by a strategy is moved ahead rather than queued twice.
+#[tokio::test]
+async fn test_requested_audit_task() {
+    let (conn, _db) = setup_database().await.unwrap();
+
+    let mut contents = vec![];
+    for i in 0..2u8 {
+        contents.push(
+            content::get_or_create(
+                SubProtocol::History,
+                &HistoryContentKey::new_block_header_by_hash([i; 32]),
+                Utc::now(),
+                ContentSource::Monitor,
+                &conn,
+            )
+            .await
+            .unwrap(),
+        );
+    }
+    let latest = SelectionStrategy::History(HistorySelectionStrategy::Latest);
+    let requested = SelectionStrategy::History(HistorySelectionStrategy::SpecificContentKey);
+    for content_model in &contents {
+        assert!(
+            audit_task::enqueue(content_model.id, latest.clone(), Utc::now(), &conn)
+                .await
+                .unwrap()
+        );
+    }
+
+    let task = audit_task::request(contents[1].id, &conn).await.unwrap();
+    assert_eq!(task.content_key, contents[1].id);
+    assert_eq!(task.strategy_used, requested);
+    assert_eq!(audit_task::count_open(&conn).await.unwrap(), 2);
+    // Requesting the key again returns the same task.
+    assert_eq!(
+        audit_task::request(contents[1].id, &conn).await.unwrap().id,
+        task.id
+    );
+
+    let claimed = audit_task::claim("worker-a", 1, chrono::TimeDelta::minutes(10), &conn)
+        .await
+        .unwrap();
+    assert_eq!(claimed.len(), 1);
+    assert_eq!(claimed[0].id, task.id);
+    assert_eq!(claimed[0].strategy_used, requested);
+}
+
+/// Tests that the delay to the first successful audit is backfilled for keys that were found,
+/// and that running the backfill again updates the rows rather than duplicating them.
+#[tokio::test]
+async fn test_content_first_success_backfill() {
+    let (conn, _db) = setup_database().await.unwrap();
+
+    let client_info_model = client_info::get_or_create("trin v0.1.0".to_owned(), &conn)
+        .await
+        .unwrap();
+    let node = node::get_or_create(NodeId::random(), &conn).await.unwrap();
+    let strategy = SelectionStrategy::History(HistorySelectionStrategy::Random);
+    let mut contents = vec![];
+    for (i, hours_ago) in [(0u8, 2), (1, 1), (2, 3)] {
+        contents.push(
+            content::get_or_create(
+                SubProtocol::History,
+                &HistoryContentKey::new_block_header_by_hash([i; 32]),
+                Utc::now() - chrono::TimeDelta::hours(hours_ago),
+                ContentSource::Monitor,
+                &conn,
+            )
+            .await
+            .unwrap(),
+        );
+    }
+    // The first key was found after failing, the second only failed, the third was found.
+    for (content_model, query_successful) in [
+        (&contents[0], false),
+        (&contents[0], true),
+        (&contents[0], true),
+        (&contents[1], false),
+        (&contents[2], true),
+    ] {
+        content_audit::create(
+            content_model.id,
+            client_info_model.id,
+            node.id,
+            query_successful,
+            strategy.clone(),
+            "".to_owned(),
+            &conn,
+        )
+        .await
+        .unwrap();
+    }
+
+    let written = content_first_success::backfill_range(0, i32::MAX, &conn)
+        .await
+        .unwrap();
+    assert_eq!(written, 2);
+    let first = content_first_success::Entity::find_by_id(contents[0].id)
+        .one(&conn)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(first.delay_seconds >= 7200 && first.delay_seconds < 7300);
+    assert!(content_first_success::Entity::find_by_id(contents[1].id)
+        .one(&conn)
+        .await
+        .unwrap()
+        .is_none());
+
+    // Backfilling again updates the same rows.
+    assert_eq!(
+        content_first_success::backfill_range(0, i32::MAX, &conn)
+            .await
+            .unwrap(),
+        2
+    );
+    assert_eq!(
+        content_first_success::Entity::find()
+            .count(&conn)
+            .await
+            .unwrap(),
+        2
+    );
+
+    let months = content_first_success::get_monthly_percentiles(SubProtocol::History, &conn)
+        .await
+        .unwrap();
+    assert_eq!(months.iter().map(|m| m.num_content).sum::<i64>(), 2);
+    assert!(months.iter().all(|m| m.p50_seconds >= 7200.0));
+    assert!(
+        content_first_success::get_monthly_percentiles(SubProtocol::State, &conn)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn test_audit_stats_by_client() {
+    let (conn, _db) = setup_database().await.unwrap();
+    let now = Utc::now();
+    for (client, passes) in [("trin", 9), ("fluffy", 6)] {
+        audit_stats_client::create(
+            now,
+            SubProtocol::History,
+            client.to_string(),
+            10,
+            passes,
+            10 - passes,
+            passes as f32 * 10.0,
+            &conn,
+        )
+        .await
+        .unwrap();
+    }
+    audit_stats_client::create(
+        now,
+        SubProtocol::State,
+        "trin".to_string(),
+        4,
+        4,
+        0,
+        100.0,
+        &conn,
+    )
+    .await
+    .unwrap();
+
+    let stats = audit_stats_client::get_stats_between(
+        SubProtocol::History,
+        now - chrono::Duration::hours(1),
+        now + chrono::Duration::hours(1),
+        &conn,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        stats.iter().map(|s| s.client.as_str()).collect::<Vec<_>>(),
+        vec!["fluffy", "trin"]
+    );
+    assert_eq!(stats[1].num_passes, 9);
+    assert_eq!(stats[1].success_rate, 90.0);
+
+    assert!(audit_stats_client::get_stats_between(
+        SubProtocol::History,
+        now + chrono::Duration::hours(1),
+        now + chrono::Duration::hours(2),
+        &conn,
+    )
+    .await
+    .unwrap()
+    .is_empty());
+}
+
+#[tokio::test]
+async fn test_reseed_attempt() {
+    use crate::reseed_attempt::ReseedOutcome;
+
+    let (conn, _db) = setup_database().await.unwrap();
+    let content = content::get_or_create(
+        SubProtocol::History,
+        &sample_history_key(),
+        Utc::now(),
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let client_info_model = client_info::get_or_create("trin v0.1.0".to_owned(), &conn)
+        .await
+        .unwrap();
+    let node = node::get_or_create(NodeId::random(), &conn).await.unwrap();
+    let strategy = SelectionStrategy::History(HistorySelectionStrategy::Random);
+    let failed = content_audit::create(
+        content.id,
+        client_info_model.id,
+        node.id,
+        false,
+        strategy.clone(),
+        "".to_owned(),
+        &conn,
+    )
+    .await
+    .unwrap();
+
+    let since = Utc::now() - chrono::Duration::days(1);
+    let candidates = reseed_attempt::get_candidates(since, 10, &conn)
+        .await
+        .unwrap();
+    assert_eq!(candidates.len(), 1);
+    assert_eq!(candidates[0].content_id, content.id);
+    assert_eq!(candidates[0].audit_id, failed.id);
+
+    reseed_attempt::create(
+        content.id,
+        Some(failed.id),
+        Utc::now(),
+        ReseedOutcome::Offered,
+        Some(4),
+        Some(true),
+        None,
+        &conn,
+    )
+    .await
+    .unwrap();
+    // A reseeded key is not reseeded again within the cooldown.
+    assert!(reseed_attempt::get_candidates(since, 10, &conn)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let start = Utc::now() - chrono::Duration::hours(1);
+    let end = Utc::now() + chrono::Duration::hours(1);
+    let summary = reseed_attempt::get_summary(start, end, &conn)
+        .await
+        .unwrap();
+    assert_eq!(summary.len(), 1);
+    assert_eq!(summary[0].outcome, ReseedOutcome::Offered);
+    assert_eq!((summary[0].attempts, summary[0].found_after), (1, 0));
+
+    // The reseed stuck once a later audit finds the content.
+    content_audit::create(
+        content.id,
+        client_info_model.id,
+        node.id,
+        true,
+        strategy,
+        "".to_owned(),
+        &conn,
+    )
+    .await
+    .unwrap();
+    let summary = reseed_attempt::get_summary(start, end, &conn)
+        .await
+        .unwrap();
+    assert_eq!((summary[0].attempts, summary[0].found_after), (1, 1));
+}
+
+/// Tests that archived content is hidden from the failing block ranges unless asked for.
+#[tokio::test]
+async fn test_archived() {
+    let (conn, _db) = setup_database().await.unwrap();
+    let content_key = sample_history_key();
+    let content = content::get_or_create(
+        SubProtocol::History,
+        &content_key,
+        Utc::now(),
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    .unwrap();
+    assert!(!content.archived);
+    execution_metadata::get_or_create(content.id, 4_100_000, &conn)
+        .await
+        .unwrap();
+    let client_info_model = client_info::get_or_create("trin v0.1.0".to_owned(), &conn)
+        .await
+        .unwrap();
+    let node_id = NodeId::random();
+    let node = node::get_or_create(node_id, &conn).await.unwrap();
+    assert!(!node.archived);
+    content_audit::create(
+        content.id,
+        client_info_model.id,
+        node.id,
+        false,
+        SelectionStrategy::History(HistorySelectionStrategy::Random),
+        "".to_owned(),
+        &conn,
+    )
+    .await
+    .unwrap();
+
+    let archived = content::set_archived(vec![content_key.to_bytes().to_vec()], true, &conn)
+        .await
+        .unwrap();
+    assert_eq!(archived, 1);
+    // Archiving again changes nothing.
+    let archived = content::set_archived(vec![content_key.to_bytes().to_vec()], true, &conn)
+        .await
+        .unwrap();
+    assert_eq!(archived, 0);
+    let archived = node::set_archived(vec![node_id.raw().to_vec()], true, &conn)
+        .await
+        .unwrap();
+    assert_eq!(archived, 1);
+
+    assert!(
+        audit_latest::get_failed_by_block_range(100_000, false, &conn)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    assert_eq!(
+        audit_latest::get_failed_by_block_range(100_000, true, &conn)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+
+    let unarchived = content::set_archived(vec![content_key.to_bytes().to_vec()], false, &conn)
+        .await
+        .unwrap();
+    assert_eq!(unarchived, 1);
+    assert_eq!(
+        audit_latest::get_failed_by_block_range(100_000, false, &conn)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+/// Tests that audit durations are summarized both by type of content and by client, leaving out
+/// errored audits.
+#[tokio::test]
+async fn test_latency_percentiles() {
+    use ethportal_api::BlockBodyKey;
+
+    let (conn, _db) = setup_database().await.unwrap();
+    let header = content::get_or_create(
+        SubProtocol::History,
+        &sample_history_key(),
+        Utc::now(),
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let body = content::get_or_create(
+        SubProtocol::History,
+        &HistoryContentKey::BlockBody(BlockBodyKey {
+            block_hash: [0x02; 32],
+        }),
+        Utc::now(),
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let trin = client_info::get_or_create("trin v0.1.0".to_owned(), &conn)
+        .await
+        .unwrap();
+    let fluffy = client_info::get_or_create("fluffy v0.2.0".to_owned(), &conn)
+        .await
+        .unwrap();
+    let node = node::get_or_create(NodeId::random(), &conn).await.unwrap();
+    let strategy = SelectionStrategy::History(HistorySelectionStrategy::Random);
+
+    for (content_id, client_info_id, result, duration_ms) in [
+        (header.id, trin.id, content_audit::AuditResult::Success, 100),
+        (header.id, trin.id, content_audit::AuditResult::Failure, 300),
+        (body.id, fluffy.id, content_audit::AuditResult::Success, 500),
+        (
+            body.id,
+            fluffy.id,
+            content_audit::AuditResult::Errored,
+            60_000,
+        ),
+    ] {
+        let audit = content_audit::create_pending(
+            content_id,
+            client_info_id,
+            node.id,
+            strategy.clone(),
+            false,
+            None,
+            None,
+            &conn,
+        )
+        .await
+        .unwrap();
+        content_audit::set_result(audit, result, "".to_owned(), Some(duration_ms), &conn)
+            .await
+            .unwrap();
+    }
+
+    let percentiles = content_audit::get_latency_percentiles(
+        SubProtocol::History,
+        Utc::now() - chrono::TimeDelta::hours(1),
+        Utc::now() + chrono::TimeDelta::hours(1),
+        &conn,
+    )
+    .await
+    .unwrap();
+    assert_eq!(percentiles.len(), 4);
+    let header_latency = percentiles
+        .iter()
+        .find(|p| p.selector == Some(0x00))
+        .unwrap();
+    assert_eq!(header_latency.num_audits, 2);
+    assert_eq!(header_latency.p50_ms, 200.0);
+    assert!(header_latency.p99_ms > header_latency.p95_ms);
+    let fluffy_latency = percentiles
+        .iter()
+        .find(|p| p.client.as_deref() == Some("fluffy"))
+        .unwrap();
+    assert_eq!(fluffy_latency.num_audits, 1);
+    assert_eq!(fluffy_latency.p99_ms, 500.0);
+}
+
+/// Tests that client version strings are parsed when recorded, and that those recorded before
+/// are parsed afterwards.
+#[tokio::test]
+async fn test_client_info_parsed_version() {
+    let (conn, _db) = setup_database().await.unwrap();
+
+    let trin = client_info::get_or_create("trin v0.1.1-5a6e5b5".to_owned(), &conn)
+        .await
+        .unwrap();
+    assert_eq!(trin.client_name.as_deref(), Some("trin"));
+    assert_eq!(trin.client_version.as_deref(), Some("0.1.1"));
+    assert_eq!(trin.client_commit.as_deref(), Some("5a6e5b5"));
+
+    let unparsed = client_info::ActiveModel {
+        id: NotSet,
+        version_info: Set("fluffy/v0.2.0-28f7b6e/linux-amd64".to_owned()),
+        client_name: NotSet,
+        client_version: NotSet,
+        client_commit: NotSet,
+    }
+    .insert(&conn)
+    .await
+    .unwrap();
+    assert_eq!(unparsed.client_name, None);
+
+    assert_eq!(client_info::parse_unparsed(&conn).await.unwrap(), 1);
+    let fluffy = client_info::get_or_create(unparsed.version_info, &conn)
+        .await
+        .unwrap();
+    assert_eq!(fluffy.client_name.as_deref(), Some("fluffy"));
+    assert_eq!(fluffy.client_version.as_deref(), Some("0.2.0"));
+    assert_eq!(fluffy.client_commit.as_deref(), Some("28f7b6e"));
+    assert_eq!(client_info::parse_unparsed(&conn).await.unwrap(), 0);
+}
+
+/// Tests that only finished censuses are sampled for coverage, once, and that their samples are
+/// listed by census.
+#[tokio::test]
+async fn test_coverage_samples() {
+    use enr::CombinedKey;
+    use ethportal_api::types::enr::Enr;
+    use std::collections::BTreeMap;
+
+    let (conn, _db) = setup_database().await.unwrap();
+    let started_at = Utc::now() - chrono::Duration::hours(1);
+    let finished = census::create(started_at, 60, SubProtocol::History, &conn)
+        .await
+        .unwrap();
+    census::set_liveness_counts(finished.clone(), LivenessCounts::default(), &conn)
+        .await
+        .unwrap();
+    // A census that is still running has no liveness counts yet.
+    census::create(Utc::now(), 60, SubProtocol::History, &conn)
+        .await
+        .unwrap();
+
+    let enr = Enr::builder()
+        .build(&CombinedKey::generate_secp256k1())
+        .unwrap();
+    let record = record::get_or_create(&enr, &conn).await.unwrap();
+    census_node::create(
+        finished.id,
+        record.id,
+        U256::MAX,
+        started_at,
+        SubProtocol::History,
+        SINGLE_PING,
+        false,
+        Reachability::default(),
+        &conn,
+    )
+    .await
+    .unwrap();
+    let radii = census_node::get_node_radii(finished.id, &conn)
+        .await
+        .unwrap();
+    assert_eq!(radii.len(), 1);
+    assert_eq!(radii[0].1, U256::MAX);
+
+    let since = Utc::now() - chrono::Duration::days(1);
+    assert_eq!(
+        coverage_sample::get_unsampled_census_ids(since, &conn)
+            .await
+            .unwrap(),
+        vec![finished.id]
+    );
+    coverage_sample::create_many(finished.id, &BTreeMap::from([(0, 3), (1, 7)]), &conn)
+        .await
+        .unwrap();
+    assert!(coverage_sample::get_unsampled_census_ids(since, &conn)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let history = coverage_sample::get_history(
+        SubProtocol::History,
+        since,
+        Utc::now() + chrono::Duration::hours(1),
+        &conn,
+    )
+    .await
+    .unwrap();
+    let history: Vec<(i32, i32, i32)> = history
+        .iter()
+        .map(|c| (c.census_id, c.nodes_covering, c.num_samples))
+        .collect();
+    assert_eq!(history, vec![(finished.id, 0, 3), (finished.id, 1, 7)]);
+}
+
+/// Tests that failing keys are filtered by type, block range and failure streak, and ordered.
+#[tokio::test]
+async fn test_get_failed_keys() {
+    use crate::audit_latest::{FailedKeysFilter, FailedKeysOrder};
+    use ethportal_api::{BlockBodyKey, BlockReceiptsKey};
+
+    let (conn, _db) = setup_database().await.unwrap();
+    let client_info_model = client_info::get_or_create("trin v0.1.0".to_owned(), &conn)
+        .await
+        .unwrap();
+    let node = node::get_or_create(NodeId::random(), &conn).await.unwrap();
+    let strategy = SelectionStrategy::History(HistorySelectionStrategy::Random);
+
+    let receipts = |seed: u8| {
+        HistoryContentKey::BlockReceipts(BlockReceiptsKey {
+            block_hash: [seed; 32],
+        })
+    };
+    let keys = [
+        (4_100_000, receipts(1), vec![false, false, false]),
+        (4_200_000, receipts(2), vec![false]),
+        (
+            4_150_000,
+            HistoryContentKey::BlockBody(BlockBodyKey {
+                block_hash: [3; 32],
+            }),
+            vec![false, false],
+        ),
+        (4_120_000, receipts(4), vec![false, true]),
+    ];
+    for (block_number, content_key, outcomes) in &keys {
+        let content = content::get_or_create(
+            SubProtocol::History,
+            content_key,
+            Utc::now(),
+            ContentSource::Monitor,
+            &conn,
+        )
+        .await
+        .unwrap();
+        execution_metadata::get_or_create(content.id, *block_number, &conn)
+            .await
+            .unwrap();
+        for query_successful in outcomes {
+            content_audit::create(
+                content.id,
+                client_info_model.id,
+                node.id,
+                *query_successful,
+                strategy.clone(),
+                "".to_owned(),
+                &conn,
+            )
+            .await
+            .unwrap();
+        }
+    }
+    let hex = |index: usize| hex_encode(keys[index].1.to_bytes());
+
+    let all = FailedKeysFilter {
+        sub_protocol: SubProtocol::History,
+        strategy: None,
+        selector: None,
+        block_start: None,
+        block_end: None,
+        available_after: None,
+        available_before: None,
+        failed_after: None,
+        min_consecutive_failures: 0,
+        include_archived: false,
+    };
+    let by_block = audit_latest::get_failed_keys(&all, FailedKeysOrder::BlockNumber, 1, 10, &conn)
+        .await
+        .unwrap();
+    assert_eq!(by_block, vec![hex(0), hex(2), hex(1)]);
+
+    let receipts_in_range = FailedKeysFilter {
+        selector: Some(0x02),
+        block_start: Some(4_000_000),
+        block_end: Some(4_200_000),
+        ..all.clone()
+    };
+    let filtered = audit_latest::get_failed_keys(
+        &receipts_in_range,
+        FailedKeysOrder::BlockNumber,
+        1,
+        10,
+        &conn,
+    )
+    .await
+    .unwrap();
+    // The range includes its end block.
+    assert_eq!(filtered, vec![hex(0), hex(1)]);
+
+    let streaks = FailedKeysFilter {
+        min_consecutive_failures: 2,
+        ..all.clone()
+    };
+    let most_failures =
+        audit_latest::get_failed_keys(&streaks, FailedKeysOrder::MostFailures, 1, 10, &conn)
+            .await
+            .unwrap();
+    assert_eq!(most_failures, vec![hex(0), hex(2)]);
+
+    let second_page =
+        audit_latest::get_failed_keys(&all, FailedKeysOrder::BlockNumber, 2, 2, &conn)
+            .await
+            .unwrap();
+    assert_eq!(second_page, vec![hex(1)]);
+}
+
+/// Tests that new content is scheduled for audits at each age, and that the curve counts the
+/// content found at each age.
+#[tokio::test]
+async fn test_propagation_audit() {
+    let (conn, _db) = setup_database().await.unwrap();
+    let client_info_model = client_info::get_or_create("trin v0.1.0".to_owned(), &conn)
+        .await
+        .unwrap();
+    let node = node::get_or_create(NodeId::random(), &conn).await.unwrap();
+    let strategy = SelectionStrategy::History(HistorySelectionStrategy::Propagation);
+    let first_available_at = Utc::now() - chrono::TimeDelta::minutes(10);
+    let new_content = content::get_or_create(
+        SubProtocol::History,
+        &sample_history_key(),
+        first_available_at,
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    .unwrap();
+
+    let candidates =
+        propagation_audit::get_candidates(Utc::now() - chrono::TimeDelta::hours(1), 10, &conn)
+            .await
+            .unwrap();
+    assert_eq!(
+        candidates
+            .iter()
+            .map(|content| content.id)
+            .collect::<Vec<_>>(),
+        vec![new_content.id]
+    );
+    propagation_audit::schedule(&new_content, &[60, 300, 3_600], &conn)
+        .await
+        .unwrap();
+    // Scheduling again leaves the scheduled audits as they are.
+    propagation_audit::schedule(&new_content, &[60, 300, 3_600], &conn)
+        .await
+        .unwrap();
+    assert!(
+        propagation_audit::get_candidates(Utc::now() - chrono::TimeDelta::hours(1), 10, &conn)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    let due = propagation_audit::get_due(Utc::now(), 10, &conn)
+        .await
+        .unwrap();
+    assert_eq!(
+        due.iter()
+            .map(|(scheduled, content)| (scheduled.offset_seconds, content.id))
+            .collect::<Vec<_>>(),
+        vec![(60, new_content.id), (300, new_content.id)]
+    );
+    for ((scheduled, _), query_successful) in due.iter().zip([false, true]) {
+        let audit = content_audit::create(
+            new_content.id,
+            client_info_model.id,
+            node.id,
+            query_successful,
+            strategy.clone(),
+            "".to_owned(),
+            &conn,
+        )
+        .await
+        .unwrap();
+        propagation_audit::complete(scheduled.id, Some(audit.id), &conn)
+            .await
+            .unwrap();
+    }
+    assert!(propagation_audit::get_due(Utc::now(), 10, &conn)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let curve =
+        propagation_audit::get_curve(Utc::now() - chrono::TimeDelta::days(1), Utc::now(), &conn)
+            .await
+            .unwrap();
+    assert_eq!(
+        curve,
+        vec![
+            propagation_audit::PropagationPoint {
+                offset_seconds: 60,
+                num_audits: 1,
+                num_found: 0,
+                found_percent: 0.0,
+            },
+            propagation_audit::PropagationPoint {
+                offset_seconds: 300,
+                num_audits: 1,
+                num_found: 1,
+                found_percent: 100.0,
+            },
+        ]
+    );
+}
+
+/// Tests that a node's reliability counts the latest censuses that found it, retried it or
+/// dropped it.
+#[tokio::test]
+async fn test_node_reliability() {
+    use enr::CombinedKey;
+    use ethportal_api::types::enr::Enr;
+
+    let (conn, _db) = setup_database().await.unwrap();
+    let start = Utc.with_ymd_and_hms(2024, 11, 1, 0, 0, 0).unwrap();
+
+    let mut records = vec![];
+    for _ in 0..2 {
+        let enr = Enr::builder()
+            .build(&CombinedKey::generate_secp256k1())
+            .unwrap();
+        records.push(record::get_or_create(&enr, &conn).await.unwrap());
+    }
+    let mut censuses = vec![];
+    for hours in 0..4 {
+        censuses.push(
+            census::create(
+                start + chrono::Duration::hours(hours),
+                60,
+                SubProtocol::History,
+                &conn,
+            )
+            .await
+            .unwrap(),
+        );
+    }
+    // Censuses of other subnetworks are left out.
+    census::create(start, 60, SubProtocol::Beacon, &conn)
+        .await
+        .unwrap();
+
+    // The oldest census falls outside the latest three.
+    let pings = [
+        (0, SINGLE_PING),
+        (1, SINGLE_PING),
+        (
+            2,
+            PingMeasurement {
+                attempts: 2,
+                latency_ms: 40,
+            },
+        ),
+    ];
+    for (index, ping) in pings {
+        census_node::create(
+            censuses[index].id,
+            records[0].id,
+            U256::MAX,
+            censuses[index].started_at,
+            SubProtocol::History,
+            ping,
+            false,
+            Reachability::default(),
+            &conn,
+        )
+        .await
+        .unwrap();
+    }
+    census_node_error::create_many(
+        censuses[3].id,
+        &[NodeFailure {
+            record_id: records[0].id,
+            surveyed_at: censuses[3].started_at,
+            kind: CensusNodeErrorKind::Timeout,
+            attempts: 3,
+            duration_ms: 6000,
+            message: None,
+        }],
+        &conn,
+    )
+    .await
+    .unwrap();
+
+    let reliability =
+        census_node::get_node_reliability(records[0].node_id, SubProtocol::History, 3, &conn)
+            .await
+            .unwrap();
+    assert_eq!(
+        reliability,
+        census_node::NodeReliability {
+            num_censuses: 3,
+            num_present: 2,
+            num_retried: 1,
+            num_dropped: 1,
+            average_ping_attempts: Some(1.5),
+            average_latency_ms: Some(30.0),
+        }
+    );
+    assert_eq!(reliability.presence(), Some(2.0 / 3.0));
+    assert_eq!(reliability.score(), Some(1.0 / 3.0));
+
+    let unseen =
+        census_node::get_node_reliability(records[1].node_id, SubProtocol::History, 3, &conn)
+            .await
+            .unwrap();
+    assert_eq!(unseen.num_present, 0);
+    assert_eq!(unseen.average_latency_ms, None);
+    assert_eq!(unseen.score(), Some(0.0));
+
+    let no_censuses =
+        census_node::get_node_reliability(records[0].node_id, SubProtocol::State, 3, &conn)
+            .await
+            .unwrap();
+    assert_eq!(no_censuses.num_censuses, 0);
+    assert_eq!(no_censuses.score(), None);
+}
+
+/// Tests that the nodes of old censuses are deleted when archived, and put back when restored.
+#[tokio::test]
+async fn test_archive_census_nodes() {
+    use enr::CombinedKey;
+    use ethportal_api::types::enr::Enr;
+
+    let (conn, _db) = setup_database().await.unwrap();
+    let start = Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap();
+    let old = census::create(start, 60, SubProtocol::History, &conn)
+        .await
+        .unwrap();
+    let recent = census::create(
+        start + chrono::Duration::days(30),
+        60,
+        SubProtocol::History,
+        &conn,
+    )
+    .await
+    .unwrap();
+    for census in [&old, &recent] {
+        for _ in 0..2 {
+            let enr = Enr::builder()
+                .build(&CombinedKey::generate_secp256k1())
+                .unwrap();
+            let record = record::get_or_create(&enr, &conn).await.unwrap();
+            census_node::create(
+                census.id,
+                record.id,
+                U256::MAX,
+                census.started_at,
+                SubProtocol::History,
+                SINGLE_PING,
+                false,
+                Reachability::default(),
+                &conn,
+            )
+            .await
+            .unwrap();
+        }
+    }
+
+    let archivable = census::get_unarchived_before(start + chrono::Duration::days(1), &conn)
+        .await
+        .unwrap();
+    assert_eq!(
+        archivable
+            .iter()
+            .map(|census| census.id)
+            .collect::<Vec<_>>(),
+        vec![old.id]
+    );
+    let nodes = census_node::get_with_records(old.id, &conn).await.unwrap();
+    assert_eq!(nodes.len(), 2);
+    assert!(nodes
+        .iter()
+        .all(|(node, record)| node.record_id == record.id && node.census_id == old.id));
+
+    assert_eq!(census::archive_nodes(old.id, &conn).await.unwrap(), 2);
+    assert!(census_node::get_with_records(old.id, &conn)
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        census_node::get_with_records(recent.id, &conn)
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+    assert!(
+        census::get_unarchived_before(start + chrono::Duration::days(1), &conn)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    census::restore_nodes(
+        old.id,
+        nodes.iter().map(|(node, _)| node.clone()).collect(),
+        &conn,
+    )
+    .await
+    .unwrap();
+    let restored = census_node::get_with_records(old.id, &conn).await.unwrap();
+    assert_eq!(
+        restored
+            .iter()
+            .map(|(node, _)| (node.record_id, node.data_radius.clone(), node.ping_attempts))
+            .collect::<Vec<_>>(),
+        nodes
+            .iter()
+            .map(|(node, _)| (node.record_id, node.data_radius.clone(), node.ping_attempts))
+            .collect::<Vec<_>>()
+    );
+    let old = census::Entity::find_by_id(old.id)
+        .one(&conn)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(old.nodes_archived_at, None);
+}
+
+/// Tests that the alternate ENRs of a census are counted by node and listed with the canonical
+/// ENR they were passed over for.
+#[tokio::test]
+async fn test_census_alternate_enrs() {
+    use enr::CombinedKey;
+    use ethportal_api::types::enr::Enr;
+
+    let (conn, _db) = setup_database().await.unwrap();
+
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let census = census::create(start, 60, SubProtocol::History, &conn)
+        .await
+        .unwrap();
+    let other_census = census::create(start, 60, SubProtocol::History, &conn)
+        .await
+        .unwrap();
+
+    // The first node presented three ENRs, the second two.
+    let mut nodes = vec![];
+    for num_enrs in [3, 2] {
+        let key = CombinedKey::generate_secp256k1();
+        let mut records = vec![];
+        for seq in 1..=num_enrs {
+            let enr = Enr::builder().seq(seq).build(&key).unwrap();
+            records.push(record::get_or_create(&enr, &conn).await.unwrap());
+        }
+        nodes.push(records);
+    }
+    let pairs = [
+        (nodes[0][2].id, nodes[0][0].id),
+        (nodes[0][2].id, nodes[0][1].id),
+        (nodes[1][1].id, nodes[1][0].id),
+    ];
+    census_alternate_enr::create_many(census.id, &pairs, &conn)
+        .await
+        .unwrap();
+    // Recording the same alternates again is a no-op.
+    census_alternate_enr::create_many(census.id, &pairs[..1], &conn)
+        .await
+        .unwrap();
+    census_alternate_enr::create_many(other_census.id, &[], &conn)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        census_alternate_enr::count_nodes(census.id, &conn)
+            .await
+            .unwrap(),
+        2
+    );
+    assert_eq!(
+        census_alternate_enr::count_nodes(other_census.id, &conn)
+            .await
+            .unwrap(),
+        0
+    );
+
+    let alternates = census_alternate_enr::get_by_census(census.id, &conn)
+        .await
+        .unwrap();
+    assert_eq!(alternates.len(), 3);
+    let first_node = node::Entity::find_by_id(nodes[0][0].node_id)
+        .one(&conn)
+        .await
+        .unwrap()
+        .unwrap();
+    let first_node_alternates: Vec<_> = alternates
+        .iter()
+        .filter(|alternate| alternate.node_id == first_node.node_id)
+        .collect();
+    assert_eq!(first_node_alternates.len(), 2);
+    assert!(first_node_alternates
+        .iter()
+        .all(|alternate| alternate.canonical_sequence_number == 3
+            && alternate.canonical_raw == nodes[0][2].raw));
+    assert_eq!(first_node_alternates[0].sequence_number, 2);
+    assert_eq!(first_node_alternates[1].sequence_number, 1);
+}
+
+/// Tests that only the given audits still `Pending` are marked as errored on shutdown.
+#[tokio::test]
+async fn test_mark_pending_errored() {
+    let (conn, _db) = setup_database().await.unwrap();
+
+    let key = sample_history_key();
+    let content_model = content::get_or_create(
+        SubProtocol::History,
+        &key,
+        Utc::now(),
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let client_info_model = client_info::get_or_create("trin v0.1.0".to_owned(), &conn)
+        .await
+        .unwrap();
+    let node = node::get_or_create(NodeId::random(), &conn).await.unwrap();
+    let strategy = SelectionStrategy::History(HistorySelectionStrategy::Random);
+
+    let mut audits = vec![];
+    for _ in 0..3 {
+        let audit = content_audit::create_pending(
+            content_model.id,
+            client_info_model.id,
+            node.id,
+            strategy.clone(),
+            false,
+            None,
+            None,
+            &conn,
+        )
+        .await
+        .unwrap();
+        audits.push(audit);
+    }
+    let finished = content_audit::set_result(
+        audits[0].clone(),
+        content_audit::AuditResult::Success,
+        "".to_owned(),
+        Some(100),
+        &conn,
+    )
+    .await
+    .unwrap();
+
+    // The finished audit keeps its result, and the audit not given stays pending.
+    let marked = content_audit::mark_pending_errored(
+        &[finished.id, audits[1].id],
+        content_audit::SHUTDOWN_REASON,
+        &conn,
+    )
+    .await
+    .unwrap();
+    assert_eq!(marked, 1);
+    assert_eq!(
+        content_audit::mark_pending_errored(&[], content_audit::SHUTDOWN_REASON, &conn)
+            .await
+            .unwrap(),
+        0
+    );
+
+    let mut results = vec![];
+    for audit in &audits {
+        let audit = content_audit::Entity::find_by_id(audit.id)
+            .one(&conn)
+            .await
+            .unwrap()
+            .unwrap();
+        results.push((audit.result, audit.error_reason));
+    }
+    assert_eq!(
+        results,
+        vec![
+            (content_audit::AuditResult::Success, None),
+            (
+                content_audit::AuditResult::Errored,
+                Some(content_audit::SHUTDOWN_REASON.to_owned())
+            ),
+            (content_audit::AuditResult::Pending, None),
+        ]
+    );
+}
+
+/// Tests that beacon success rates are recorded with the audit stats and read back.
+#[tokio::test]
+async fn test_audit_stats_beacon_success_rates() {
+    let (conn, _db) = setup_database().await.unwrap();
+    let now = Utc::now();
+    let beacon = audit_stats::BeaconSuccessRates {
+        num_audits: 40,
+        all: 75.0,
+        bootstraps: 100.0,
+        updates_by_range: 50.0,
+        finality_updates: 90.0,
+        optimistic_updates: 60.0,
+    };
+    let record = audit_stats::create(
+        now,
+        100,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0,
+        0,
+        0,
+        beacon.clone(),
+        &conn,
+    )
+    .await
+    .unwrap();
+    assert_eq!(record.num_beacon_audits, Some(40));
+
+    let stats = audit_stats::get_stats_between(
+        now - chrono::Duration::hours(1),
+        now + chrono::Duration::hours(1),
+        &conn,
+    )
+    .await
+    .unwrap();
+    assert_eq!(stats.len(), 1);
+    assert_eq!(stats[0].beacon_success_rates(), Some(beacon));
+
+    // Stats recorded before beacon audits were measured have no beacon success rates.
+    let mut earlier = stats[0].clone();
+    earlier.success_rate_beacon_finality_updates = None;
+    assert_eq!(earlier.beacon_success_rates(), None);
+}
+
+/// Tests that old audits are deleted in batches except the latest audit of each key, and that
+/// old content is deleted with its audits and execution metadata.
+#[tokio::test]
+async fn test_delete_old_audits_and_content() {
+    let (conn, _db) = setup_database().await.unwrap();
+    let client_info_model = client_info::get_or_create("trin v0.1.0".to_owned(), &conn)
+        .await
+        .unwrap();
+    let node = node::get_or_create(NodeId::random(), &conn).await.unwrap();
+    let strategy = SelectionStrategy::History(HistorySelectionStrategy::Random);
+
+    let old_content = content::get_or_create(
+        SubProtocol::History,
+        &HistoryContentKey::new_block_header_by_hash([1; 32]),
+        Utc::now() - chrono::TimeDelta::days(100),
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let new_content = content::get_or_create(
+        SubProtocol::History,
+        &HistoryContentKey::new_block_header_by_hash([2; 32]),
+        Utc::now(),
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    .unwrap();
+    // Content whose earlier audit is part of a block audit, which the block stats count.
+    let block_content = content::get_or_create(
+        SubProtocol::History,
+        &HistoryContentKey::new_block_header_by_hash([3; 32]),
+        Utc::now() - chrono::TimeDelta::days(100),
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    .unwrap();
+    execution_metadata::get_or_create(old_content.id, 1, &conn)
+        .await
+        .unwrap();
+    let mut block_audits = vec![];
+    for _ in 0..2 {
+        let audit = content_audit::create(
+            block_content.id,
+            client_info_model.id,
+            node.id,
+            true,
+            strategy.clone(),
+            "".to_owned(),
+            &conn,
+        )
+        .await
+        .unwrap();
+        block_audits.push(audit.id);
+    }
+    block_audit::create(
+        1,
+        block_audits[0],
+        block_audits[0],
+        block_audits[0],
+        block_audit::BlockVerdict::Complete,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let mut latest_old_audit = None;
+    for content_id in [
+        old_content.id,
+        old_content.id,
+        old_content.id,
+        new_content.id,
+    ] {
+        let audit = content_audit::create(
+            content_id,
+            client_info_model.id,
+            node.id,
+            true,
+            strategy.clone(),
+            "".to_owned(),
+            &conn,
+        )
+        .await
+        .unwrap();
+        if content_id == old_content.id {
+            latest_old_audit = Some(audit.id);
+        }
+    }
+
+    // Only the two earlier audits of the old content are not the latest of their key, or part
+    // of a block audit.
+    let cutoff = Utc::now();
+    assert_eq!(
+        content_audit::delete_old_batch(cutoff, 1, &conn)
+            .await
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        content_audit::delete_old_batch(cutoff, 10, &conn)
+            .await
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        content_audit::delete_old_batch(cutoff, 10, &conn)
+            .await
+            .unwrap(),
+        0
+    );
+    let latest = audit_latest::Entity::find_by_id(old_content.id)
+        .one(&conn)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(Some(latest.audit_id), latest_old_audit);
+
+    let cutoff = Utc::now() - chrono::TimeDelta::days(30);
+    assert_eq!(
+        content::delete_old_batch(cutoff, 10, &conn).await.unwrap(),
+        1
+    );
+    assert_eq!(content::Entity::find().count(&conn).await.unwrap(), 2);
+    assert_eq!(content_audit::Entity::find().count(&conn).await.unwrap(), 3);
+    assert_eq!(block_audit::Entity::find().count(&conn).await.unwrap(), 1);
+    assert_eq!(
+        execution_metadata::Entity::find()
+            .count(&conn)
+            .await
+            .unwrap(),
+        0
+    );
+}
+
+#[test]
+fn test_client_diversity_from_counts() {
+    assert_eq!(census::ClientDiversity::from_counts([]), None);
+    assert_eq!(census::ClientDiversity::from_counts([0, 0]), None);
+
+    let single = census::ClientDiversity::from_counts([10]).unwrap();
+    assert_eq!(single.effective_number, 1.0);
+    assert_eq!(single.entropy, 0.0);
+
+    // Clients without nodes do not count.
+    let even = census::ClientDiversity::from_counts([5, 5, 5, 5, 0]).unwrap();
+    assert!((even.effective_number - 4.0).abs() < 1e-6);
+    assert!((even.entropy - 2.0).abs() < 1e-6);
+
+    // A dominant client brings the diversity down.
+    let skewed = census::ClientDiversity::from_counts([18, 1, 1]).unwrap();
+    assert!((skewed.effective_number - 400.0 / 326.0).abs() < 1e-6);
+    assert!(skewed.entropy < even.entropy);
+}
+
+#[tokio::test]
+async fn test_census_client_diversity() {
+    let (conn, _db) = setup_database().await.unwrap();
+    let start = Utc::now() - chrono::Duration::hours(2);
+    let older = census::create(start, 60, SubProtocol::History, &conn)
+        .await
+        .unwrap();
+    let diversity = census::ClientDiversity::from_counts([1, 1]).unwrap();
+    let older = census::set_client_diversity(older, diversity, &conn)
+        .await
+        .unwrap();
+    assert_eq!(older.client_effective_number, Some(2.0));
+    assert_eq!(older.client_entropy, Some(1.0));
+    // Censuses without a computed diversity and of other subnetworks are left out.
+    census::create(
+        start + chrono::Duration::minutes(15),
+        60,
+        SubProtocol::History,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let state_census = census::create(start, 60, SubProtocol::State, &conn)
+        .await
+        .unwrap();
+    census::set_client_diversity(state_census, diversity, &conn)
+        .await
+        .unwrap();
+    let latest = census::create(
+        start + chrono::Duration::minutes(30),
+        60,
+        SubProtocol::History,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let single = census::ClientDiversity::from_counts([3]).unwrap();
+    census::set_client_diversity(latest, single, &conn)
+        .await
+        .unwrap();
+
+    let history = census::get_client_diversity(SubProtocol::History, start, Utc::now(), &conn)
+        .await
+        .unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].effective_number, 2.0);
+    assert_eq!(history[0].entropy, 1.0);
+    assert_eq!(history[1].effective_number, 1.0);
+    assert_eq!(history[1].entropy, 0.0);
+}
+
+/// Tests that census nodes saved in batches, over several chunks, are stored as when saved one
+/// at a time.
+#[tokio::test]
+async fn test_census_node_create_many() {
+    use enr::CombinedKey;
+    use ethportal_api::types::enr::Enr;
+    use sea_orm::QueryOrder;
+
+    let (conn, _db) = setup_database().await.unwrap();
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let census = census::create(start, 60, SubProtocol::State, &conn)
+        .await
+        .unwrap();
+    let enrs: Vec<Enr> = (0..2500)
+        .map(|_| {
+            Enr::builder()
+                .build(&CombinedKey::generate_secp256k1())
+                .unwrap()
+        })
+        .collect();
+    let records = record::get_or_create_many(&enrs, &conn).await.unwrap();
+    let mut nodes: Vec<census_node::NewCensusNode> = records
+        .iter()
+        .zip(0..)
+        .map(|(record, i)| census_node::NewCensusNode {
+            record_id: record.id,
+            data_radius: U256::from(i as u64),
+            surveyed_at: start + chrono::Duration::seconds(i),
+            ping: SINGLE_PING,
+            is_self: false,
+            reachability: Reachability::default(),
+        })
+        .collect();
+    nodes[0].data_radius = U256::MAX;
+    nodes[0].is_self = true;
+    nodes[0].reachability = Reachability {
+        udp: Some(true),
+        tcp: Some(false),
+    };
+    census_node::create_many(census.id, SubProtocol::State, &nodes, &conn)
+        .await
+        .unwrap();
+    census_node::create_many(census.id, SubProtocol::State, &[], &conn)
+        .await
+        .unwrap();
+
+    let saved = census_node::Entity::find()
+        .filter(census_node::Column::CensusId.eq(census.id))
+        .order_by_asc(census_node::Column::Id)
+        .all(&conn)
+        .await
+        .unwrap();
+    assert_eq!(saved.len(), 2500);
+    let first = &saved[0];
+    assert_eq!(first.sub_network, SubProtocol::State);
+    assert_eq!(first.data_radius, U256::MAX.to_be_bytes::<32>().to_vec());
+    assert_eq!(
+        first.data_radius_high,
+        U256::MAX.wrapping_shr(193).to::<i64>()
+    );
+    assert_eq!(first.ping_attempts, Some(SINGLE_PING.attempts));
+    assert!(first.is_self);
+    assert_eq!(first.udp_reachable, Some(true));
+    assert_eq!(first.tcp_reachable, Some(false));
+    let last = &saved[2499];
+    assert_eq!(last.surveyed_at, start + chrono::Duration::seconds(2499));
+    assert!(!last.is_self);
+}
+
+/// Tests that nodes are only held to the content within the radius they advertised in the
+/// census an audit was taken against, and that only missing or invalid content counts against
+/// them.
+#[tokio::test]
+async fn test_radius_discrepancies() {
+    use enr::CombinedKey;
+    use ethportal_api::types::enr::Enr;
+
+    let (conn, _db) = setup_database().await.unwrap();
+    let now = Utc::now();
+    let census = census::create(
+        now - chrono::Duration::minutes(1),
+        60,
+        SubProtocol::History,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let full_enr = Enr::builder()
+        .add_value("c", &"t 0.1.1-5a6e5b5".to_owned())
+        .build(&CombinedKey::generate_secp256k1())
+        .unwrap();
+    let empty_enr = Enr::builder()
+        .add_value("c", &"f".to_owned())
+        .build(&CombinedKey::generate_secp256k1())
+        .unwrap();
+    let mut nodes = vec![];
+    for (enr, data_radius) in [(&full_enr, U256::MAX), (&empty_enr, U256::ZERO)] {
+        let record = record::get_or_create(enr, &conn).await.unwrap();
+        nodes.push(census_node::NewCensusNode {
+            record_id: record.id,
+            data_radius,
+            surveyed_at: census.started_at,
+            ping: SINGLE_PING,
+            is_self: false,
+            reachability: Reachability::default(),
+        });
+    }
+    census_node::create_many(census.id, SubProtocol::History, &nodes, &conn)
+        .await
+        .unwrap();
+
+    let content = content::get_or_create(
+        SubProtocol::History,
+        &sample_history_key(),
+        now,
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let client_info_model = client_info::get_or_create("trin v0.1.0".to_owned(), &conn)
+        .await
+        .unwrap();
+    let auditor = node::get_or_create(NodeId::random(), &conn).await.unwrap();
+    let origin = hex_encode(NodeId::random().raw());
+    let full = hex_encode(full_enr.node_id().raw());
+    let empty = hex_encode(empty_enr.node_id().raw());
+    let traces = [
+        // Served by the node covering everything.
+        format!(r#"{{"origin":"{origin}","receivedFrom":"{full}","responses":{{}}}}"#),
+        // Neither node had it, but the node with an empty radius is not expected to.
+        format!(
+            r#"{{"origin":"{origin}","responses":{{"{origin}":{{}},"{full}":{{}},"{empty}":{{}}}}}}"#
+        ),
+        // Invalid content from the node covering everything.
+        format!(
+            r#"{{"origin":"{origin}","receivedFrom":"{empty}","failures":{{"{full}":{{"durationMs":5,"failure":"invalidContent"}}}}}}"#
+        ),
+        // Failed transfers do not show the node lacks the content.
+        format!(
+            r#"{{"origin":"{origin}","failures":{{"{full}":{{"durationMs":5,"failure":"utpTransferFailed"}}}}}}"#
+        ),
+    ];
+    for trace in traces {
+        let audit = content_audit::create(
+            content.id,
+            client_info_model.id,
+            auditor.id,
+            true,
+            SelectionStrategy::History(HistorySelectionStrategy::Random),
+            trace,
+            &conn,
+        )
+        .await
+        .unwrap();
+        assert_eq!(audit.census_id, Some(census.id));
+    }
+
+    let discrepancies = census_node::get_radius_discrepancies(
+        SubProtocol::History,
+        now - chrono::Duration::hours(1),
+        Utc::now() + chrono::Duration::minutes(1),
+        &conn,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        discrepancies,
+        vec![census_node::RadiusDiscrepancy {
+            node_id: full,
+            client: "trin".to_owned(),
+            requests_in_radius: 3,
+            served: 1,
+            not_found: 1,
+            invalid_content: 1,
+            discrepancy_rate: 200.0 / 3.0,
+        }]
+    );
+}
+
+/// Tests that the latest banner is shown until it expires or is cleared.
+#[tokio::test]
+async fn test_site_banner() {
+    let (conn, _db) = setup_database().await.unwrap();
+    assert_eq!(
+        site_banner::get_active(Utc::now(), &conn).await.unwrap(),
+        None
+    );
+    assert!(!site_banner::clear(&conn).await.unwrap());
+
+    let until = Utc::now() + chrono::Duration::hours(1);
+    let banner = site_banner::set(
+        "Census paused for a database migration until 15:00 UTC.".to_owned(),
+        Some(until),
+        &conn,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        site_banner::get_active(Utc::now(), &conn).await.unwrap(),
+        Some(banner)
+    );
+    assert_eq!(site_banner::get_active(until, &conn).await.unwrap(), None);
+
+    // A newer banner replaces it, and shows until it is cleared.
+    let replacement = site_banner::set("Audits are delayed.".to_owned(), None, &conn)
+        .await
+        .unwrap();
+    assert_eq!(
+        site_banner::get_active(until, &conn).await.unwrap(),
+        Some(replacement)
+    );
+    assert!(site_banner::clear(&conn).await.unwrap());
+    assert_eq!(
+        site_banner::get_active(Utc::now(), &conn).await.unwrap(),
+        None
+    );
+}
+
+/// Tests that census nodes are exported a page at a time, with the client details of their
+/// ENRs.
+#[tokio::test]
+async fn test_census_node_export() {
+    use enr::CombinedKey;
+    use ethportal_api::types::enr::Enr;
+
+    let (conn, _db) = setup_database().await.unwrap();
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let census = census::create(start, 60, SubProtocol::History, &conn)
+        .await
+        .unwrap();
+    let fluffy = Enr::builder()
+        .add_value("c", &"f v0.1.0-28f7b6e/linux-amd64".to_owned())
+        .add_value("pv", &vec![0u8, 1])
+        .build(&CombinedKey::generate_secp256k1())
+        .unwrap();
+    let anonymous = Enr::builder()
+        .build(&CombinedKey::generate_secp256k1())
+        .unwrap();
+    let trin = Enr::builder()
+        .add_value("c", &"t 0.1.1-5a6e5b5".to_owned())
+        .build(&CombinedKey::generate_secp256k1())
+        .unwrap();
+    let mut nodes = vec![];
+    for enr in [&fluffy, &anonymous, &trin] {
+        let record = record::get_or_create(enr, &conn).await.unwrap();
+        nodes.push(census_node::NewCensusNode {
+            record_id: record.id,
+            data_radius: U256::MAX,
+            surveyed_at: start,
+            ping: SINGLE_PING,
+            is_self: false,
+            reachability: Reachability::default(),
+        });
+    }
+    census_node::create_many(census.id, SubProtocol::History, &nodes, &conn)
+        .await
+        .unwrap();
+
+    let first_page = census_node::get_export_page(census.id, 0, 2, &conn)
+        .await
+        .unwrap();
+    assert_eq!(first_page.len(), 2);
+    assert_eq!(
+        first_page[0],
+        census_node::ExportedCensusNode {
+            id: first_page[0].id,
+            node_id: hex_encode(fluffy.node_id().raw()),
+            enr: fluffy.to_base64(),
+            client: Some("fluffy".to_owned()),
+            client_version: Some("v0.1.0-28f7b6e/linux-amd64".to_owned()),
+            os: Some("linux".to_owned()),
+            data_radius: hex_encode(U256::MAX.to_be_bytes::<32>()),
+            protocol_versions: Some(vec![0, 1]),
+            surveyed_at: start,
+        }
+    );
+    assert_eq!(first_page[1].client, None);
+    assert_eq!(first_page[1].protocol_versions, Some(vec![0]));
+
+    let second_page = census_node::get_export_page(census.id, first_page[1].id, 2, &conn)
+        .await
+        .unwrap();
+    assert_eq!(second_page.len(), 1);
+    assert_eq!(second_page[0].client.as_deref(), Some("trin"));
+    assert_eq!(second_page[0].os, None);
+    assert!(
+        census_node::get_export_page(census.id, second_page[0].id, 2, &conn)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn test_node_geo() {
+    use enr::CombinedKey;
+    use ethportal_api::types::enr::Enr;
+    use std::net::Ipv4Addr;
+
+    let (conn, _db) = setup_database().await.unwrap();
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let census = census::create(start, 60, SubProtocol::History, &conn)
+        .await
+        .unwrap();
+    let located_key = CombinedKey::generate_secp256k1();
+    let located = Enr::builder()
+        .seq(1)
+        .ip4(Ipv4Addr::new(10, 0, 0, 1))
+        .build(&located_key)
+        .unwrap();
+    let unlocated = Enr::builder()
+        .build(&CombinedKey::generate_secp256k1())
+        .unwrap();
+    let located_record = record::get_or_create(&located, &conn).await.unwrap();
+    let unlocated_record = record::get_or_create(&unlocated, &conn).await.unwrap();
+    let nodes: Vec<census_node::NewCensusNode> = [&located_record, &unlocated_record]
+        .into_iter()
+        .map(|record| census_node::NewCensusNode {
+            record_id: record.id,
+            data_radius: U256::MAX,
+            surveyed_at: start,
+            ping: SINGLE_PING,
+            is_self: false,
+            reachability: Reachability::default(),
+        })
+        .collect();
+    census_node::create_many(census.id, SubProtocol::History, &nodes, &conn)
+        .await
+        .unwrap();
+
+    let pending = node_geo::get_unlocated(10, &conn).await.unwrap();
+    assert_eq!(
+        pending,
+        vec![
+            node_geo::UnlocatedRecord {
+                node_id: located_record.node_id,
+                record_id: located_record.id,
+                raw: located.to_base64(),
+            },
+            node_geo::UnlocatedRecord {
+                node_id: unlocated_record.node_id,
+                record_id: unlocated_record.id,
+                raw: unlocated.to_base64(),
+            },
+        ]
+    );
+
+    node_geo::upsert(
+        located_record.node_id,
+        Some(located_record.id),
+        Some("DE".to_owned()),
+        Some(24940),
+        Some("Hetzner Online GmbH".to_owned()),
+        &conn,
+    )
+    .await
+    .unwrap();
+    node_geo::upsert(
+        unlocated_record.node_id,
+        Some(unlocated_record.id),
+        None,
+        None,
+        None,
+        &conn,
+    )
+    .await
+    .unwrap();
+    assert!(node_geo::get_unlocated(10, &conn).await.unwrap().is_empty());
+
+    // A new ENR is located again.
+    let moved = Enr::builder()
+        .seq(2)
+        .ip4(Ipv4Addr::new(10, 0, 0, 2))
+        .build(&located_key)
+        .unwrap();
+    let moved_record = record::get_or_create(&moved, &conn).await.unwrap();
+    let pending = node_geo::get_unlocated(10, &conn).await.unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].record_id, moved_record.id);
+
+    let census_geo = node_geo::get_census_geo(census.id, false, &conn)
+        .await
+        .unwrap();
+    assert_eq!(
+        census_geo,
+        node_geo::CensusGeo {
+            census_id: census.id,
+            total_nodes: 2,
+            unknown_country: 1,
+            unknown_asn: 1,
+            countries: vec![node_geo::CountryCount {
+                country_code: "DE".to_owned(),
+                node_count: 1,
+            }],
+            asns: vec![node_geo::AsnCount {
+                asn: 24940,
+                organization: Some("Hetzner Online GmbH".to_owned()),
+                node_count: 1,
+            }],
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_enr_change() {
+    use enr::CombinedKey;
+    use ethportal_api::types::enr::Enr;
+    use std::net::Ipv4Addr;
+
+    let (conn, _db) = setup_database().await.unwrap();
+    let key = CombinedKey::generate_secp256k1();
+    let enr_at = |seq: u64, ip: Ipv4Addr, client: &str| {
+        Enr::builder()
+            .seq(seq)
+            .ip4(ip)
+            .udp4(9009)
+            .add_value("c", &client.to_owned())
+            .build(&key)
+            .unwrap()
+    };
+    let first = enr_at(1, Ipv4Addr::new(10, 0, 0, 1), "t 0.1.0");
+    // Same endpoint, new client version: not an endpoint change.
+    let second = enr_at(2, Ipv4Addr::new(10, 0, 0, 1), "t 0.1.1");
+    let third = enr_at(3, Ipv4Addr::new(10, 0, 0, 2), "t 0.1.1");
+
+    let first_record = record::get_or_create(&first, &conn).await.unwrap();
+    assert!(enr_change::get_by_node(first_record.node_id, &conn)
+        .await
+        .unwrap()
+        .is_empty());
+
+    record::get_or_create_many(&[second.clone(), third.clone()], &conn)
+        .await
+        .unwrap();
+    let changes = enr_change::get_by_node(first_record.node_id, &conn)
+        .await
+        .unwrap();
+    assert_eq!(changes.len(), 1);
+    let change = &changes[0];
+    assert_eq!(change.old_sequence_number, 2);
+    assert_eq!(change.new_sequence_number, 3);
+    assert_eq!(change.old_endpoint(), "10.0.0.1:9009");
+    assert_eq!(change.new_endpoint(), "10.0.0.2:9009");
+
+    // Seeing the same ENRs again does not record the change twice.
+    record::get_or_create_many(&[second, third], &conn)
+        .await
+        .unwrap();
+    assert_eq!(
+        enr_change::get_by_node(first_record.node_id, &conn)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn test_census_target_coverage() {
+    use enr::CombinedKey;
+    use ethportal_api::types::enr::Enr;
+
+    let (conn, _db) = setup_database().await.unwrap();
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let census = census::create(start, 60, SubProtocol::History, &conn)
+        .await
+        .unwrap();
+    let enrs: Vec<Enr> = (0..20)
+        .map(|_| {
+            Enr::builder()
+                .build(&CombinedKey::generate_secp256k1())
+                .unwrap()
+        })
+        .collect();
+    let records = record::get_or_create_many(&enrs, &conn).await.unwrap();
+    let nodes: Vec<census_node::NewCensusNode> = records
+        .iter()
+        .map(|record| census_node::NewCensusNode {
+            record_id: record.id,
+            data_radius: U256::MAX,
+            surveyed_at: start,
+            ping: SINGLE_PING,
+            is_self: false,
+            reachability: Reachability::default(),
+        })
+        .collect();
+    census_node::create_many(census.id, SubProtocol::History, &nodes, &conn)
+        .await
+        .unwrap();
+
+    // Censuses taken before the target was kept have no coverage.
+    assert_eq!(
+        census::get_target_coverage(census.id, false, &conn)
+            .await
+            .unwrap(),
+        None
+    );
+
+    // Targeting one of the nodes puts it at distance 0.
+    let target = enrs[0].node_id();
+    let census = census::set_target_node_id(census, target, &conn)
+        .await
+        .unwrap();
+    assert_eq!(census.target_node_id, Some(target.raw().to_vec()));
+    let coverage = census::get_target_coverage(census.id, false, &conn)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(coverage.target_node_id, hex_encode(target.raw()));
+    assert_eq!(coverage.total_nodes, 20);
+    assert_eq!(coverage.closest_distance, Some(0));
+    assert_eq!(coverage.buckets.first().unwrap().distance, 256);
+    assert_eq!(coverage.buckets.last().unwrap().distance, 0);
+    assert_eq!(coverage.buckets.last().unwrap().node_count, 1);
+    assert_eq!(
+        coverage
+            .buckets
+            .iter()
+            .map(|bucket| bucket.node_count)
+            .sum::<i64>(),
+        20
+    );
+    assert_eq!(coverage.buckets[0].expected_node_count, 10.0);
+
+    assert_eq!(census::log2_distance(&[0u8; 32], &[0u8; 32]), 0);
+    let mut one = [0u8; 32];
+    one[31] = 1;
+    assert_eq!(census::log2_distance(&[0u8; 32], &one), 1);
+    assert_eq!(census::log2_distance(&[0u8; 32], &[0xff; 32]), 256);
+}
+
+/// Tests that recorded audits are looked up with their content key and client for live
+/// dashboards.
+#[tokio::test]
+async fn test_get_recorded_audit() {
+    let (conn, _db) = setup_database().await.unwrap();
+
+    let key = sample_history_key();
+    let content_model = content::get_or_create(
+        SubProtocol::History,
+        &key,
+        Utc::now(),
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let client_info_model = client_info::get_or_create("trin v0.1.0".to_owned(), &conn)
+        .await
+        .unwrap();
+    let node = node::get_or_create(NodeId::random(), &conn).await.unwrap();
+    let audit = content_audit::create_pending(
+        content_model.id,
+        client_info_model.id,
+        node.id,
+        SelectionStrategy::History(HistorySelectionStrategy::Random),
+        false,
+        None,
+        None,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let audit = content_audit::set_result(
+        audit,
+        content_audit::AuditResult::Success,
+        "".to_owned(),
+        Some(120),
+        &conn,
+    )
+    .await
+    .unwrap();
+
+    let recorded = content_audit::get_recorded(audit.id, &conn)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        recorded,
+        content_audit::RecordedAudit {
+            id: audit.id,
+            sub_network: "History".to_owned(),
+            content_key: content_model.key_as_hex(),
+            result: "Success".to_owned(),
+            strategy: "Random".to_owned(),
+            client: Some("trin v0.1.0".to_owned()),
+            duration_ms: Some(120),
+            created_at: audit.created_at,
+        }
+    );
+    assert_eq!(
+        content_audit::get_recorded(audit.id + 1, &conn)
+            .await
+            .unwrap(),
+        None
+    );
+}
+
+/// Tests that the latency of each node is summarized from the responses in audit traces,
+/// leaving out the node that ran the lookups.
+#[tokio::test]
+async fn test_node_serve_latency() {
+    use enr::CombinedKey;
+    use ethportal_api::types::enr::Enr;
+
+    let (conn, _db) = setup_database().await.unwrap();
+    let now = Utc::now();
+    let content_model = content::get_or_create(
+        SubProtocol::History,
+        &sample_history_key(),
+        now,
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    .unwrap();
+    let client_info_model = client_info::get_or_create("trin v0.1.0".to_owned(), &conn)
+        .await
+        .unwrap();
+    let node = node::get_or_create(NodeId::random(), &conn).await.unwrap();
+    let trin_enr = Enr::builder()
+        .add_value("c", &"t 0.1.0".to_owned())
+        .build(&CombinedKey::generate_secp256k1())
+        .unwrap();
+    record::get_or_create(&trin_enr, &conn).await.unwrap();
+    let trin_node_id = hex_encode(trin_enr.node_id().raw());
+    // A client string of a single character is RLP encoded without a length prefix.
+    let fluffy_enr = Enr::builder()
+        .add_value("c", &"f".to_owned())
+        .build(&CombinedKey::generate_secp256k1())
+        .unwrap();
+    record::get_or_create(&fluffy_enr, &conn).await.unwrap();
+    let fluffy_node_id = hex_encode(fluffy_enr.node_id().raw());
+
+    let traces = [
+        // The lookup learns of the trin node from the fluffy node, 100ms in, so the trin node
+        // took 10ms. Responses of nodes with an invalid id are skipped.
+        format!(
+            r#"{{"origin":"0xaa","responses":{{"0xaa":{{"durationMs":0,"respondedWith":["{fluffy_node_id}"]}},"{fluffy_node_id}":{{"durationMs":100,"respondedWith":["{trin_node_id}"]}},"{trin_node_id}":{{"durationMs":110,"respondedWith":[]}},"0xzz":{{"durationMs":5,"respondedWith":[]}}}}}}"#
+        ),
+        format!(
+            r#"{{"origin":"0xaa","responses":{{"0xaa":{{"durationMs":0,"respondedWith":["{trin_node_id}"]}},"{trin_node_id}":{{"durationMs":20,"respondedWith":[]}}}}}}"#
+        ),
+        // Nodes that no response returned were known at the start of the lookup.
+        format!(
+            r#"{{"origin":"0xaa","responses":{{"0xaa":{{"durationMs":0}},"{trin_node_id}":{{"durationMs":30,"respondedWith":[]}}}}}}"#
+        ),
+    ];
+    for trace in traces {
+        content_audit::create(
+            content_model.id,
+            client_info_model.id,
+            node.id,
+            true,
+            SelectionStrategy::History(HistorySelectionStrategy::Random),
+            trace,
+            &conn,
+        )
+        .await
+        .unwrap();
+    }
+
+    let window_start = now - chrono::Duration::hours(1);
+    assert_eq!(
+        node_serve_latency::refresh(window_start, &conn)
+            .await
+            .unwrap(),
+        2
+    );
+    // Refreshing again replaces the stats.
+    assert_eq!(
+        node_serve_latency::refresh(window_start, &conn)
+            .await
+            .unwrap(),
+        2
+    );
+
+    let slowest = node_serve_latency::get_slowest(SubProtocol::History, 1, &conn)
+        .await
+        .unwrap();
+    assert_eq!(slowest.len(), 2);
+    assert_eq!(slowest[0].node_id, fluffy_node_id);
+    assert_eq!(slowest[0].client_code, Some(b'f' as i32));
+    assert_eq!(slowest[0].num_responses, 1);
+    assert_eq!(slowest[0].p95_ms, 100.0);
+    assert_eq!(slowest[1].node_id, trin_node_id);
+    assert_eq!(slowest[1].client_code, Some(b't' as i32));
+    assert_eq!(slowest[1].num_responses, 3);
+    assert_eq!(slowest[1].p50_ms, 20.0);
+    assert!((slowest[1].p95_ms - 29.0).abs() < 1e-9);
+
+    // Nodes with too few responses are left out.
+    let slowest = node_serve_latency::get_slowest(SubProtocol::History, 2, &conn)
+        .await
+        .unwrap();
+    assert_eq!(slowest.len(), 1);
+    assert_eq!(slowest[0].node_id, trin_node_id);
+    assert!(
+        node_serve_latency::get_slowest(SubProtocol::State, 1, &conn)
+            .await
+            .unwrap()
+            .is_empty()
+    );
 }
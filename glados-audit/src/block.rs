@@ -0,0 +1,168 @@
+//! Audits the header, body and receipts of a block together ([HistorySelectionStrategy::Block]).
+//!
+//! The three audits run concurrently against the same Portal node, and are combined into a
+//! single verdict recording whether the block was fully, partially or not at all retrievable.
+//!
+//! [HistorySelectionStrategy::Block]: entity::content_audit::HistorySelectionStrategy::Block
+use std::sync::atomic::Ordering;
+
+use anyhow::{anyhow, Result};
+use chrono::{TimeZone, Utc};
+use rand::{thread_rng, Rng};
+use sea_orm::DatabaseConnection;
+use tokio::time::{interval, Duration};
+use tracing::{debug, error, info};
+use web3::types::{BlockId, BlockNumber};
+
+use entity::{
+    block_audit::{self, BlockVerdict},
+    content::{self, ContentSource},
+    content_audit::{HistorySelectionStrategy, SelectionStrategy},
+};
+use glados_core::{db::store_block_keys, history::HistoryContentKind, jsonrpc::PortalClient};
+
+use crate::{task::perform_audit, AuditConfig, AuditTask};
+
+/// Time between the start of consecutive block audits.
+const BLOCK_AUDIT_PERIOD: Duration = Duration::from_secs(5);
+
+/// Number of content audits of each block audit, performed at the same time.
+const AUDITS_PER_BLOCK: u8 = 3;
+
+pub async fn run_block_audit(conn: DatabaseConnection, config: AuditConfig) {
+    let w3 = match web3::transports::Http::new(&config.provider_url) {
+        Ok(transport) => web3::Web3::new(transport),
+        Err(e) => {
+            error!(err=?e, "Could not connect to provider for block audit.");
+            return;
+        }
+    };
+    if config.portal_clients.is_empty() {
+        error!("Empty list of clients for block audit.");
+        return;
+    }
+
+    let mut clients = config.portal_clients.iter().cycle();
+    let mut interval = interval(BLOCK_AUDIT_PERIOD);
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = config.shutdown.triggered() => return,
+        }
+        let Some(client) = clients.next() else {
+            continue;
+        };
+        if let Err(e) = audit_random_block(client, &config, &w3, &conn).await {
+            error!(err=?e, "Could not audit block.");
+        }
+    }
+}
+
+/// Audits the content of a random block, up to the current chain head.
+async fn audit_random_block(
+    client: &PortalClient,
+    config: &AuditConfig,
+    w3: &web3::Web3<web3::transports::Http>,
+    conn: &DatabaseConnection,
+) -> Result<()> {
+    // The audits count toward the concurrency shared with the other audits. A block is audited
+    // on its own if the concurrency is lower than its number of audits.
+    let active_count = config.active_threads.load(Ordering::Relaxed);
+    if active_count > 0 && active_count.saturating_add(AUDITS_PER_BLOCK) > config.concurrency {
+        debug!(
+            active.threads = active_count,
+            max.threads = config.concurrency,
+            "No capacity for a block audit, skipping this period."
+        );
+        return Ok(());
+    }
+
+    let head = w3.eth().block_number().await?.as_u64() as i32;
+    let block_number = thread_rng().gen_range(0..=head);
+    let block = w3
+        .eth()
+        .block(BlockId::Number(BlockNumber::Number(block_number.into())))
+        .await?
+        .ok_or(anyhow!("block {block_number} not found"))?;
+    let block_hash = block
+        .hash
+        .ok_or(anyhow!("block {block_number} has no hash"))?;
+    let block_timestamp = Utc
+        .timestamp_opt(block.timestamp.as_u64() as i64, 0)
+        .single()
+        .ok_or(anyhow!("block {block_number} has an invalid timestamp"))?;
+
+    // The header by number, only stored for pre-merge blocks, is left to the other strategies.
+    let stored = store_block_keys(
+        block_number,
+        block_hash.as_fixed_bytes(),
+        block_timestamp,
+        ContentSource::Audit,
+        conn,
+    )
+    .await;
+    let stored_content = |kind: HistoryContentKind| {
+        stored
+            .iter()
+            .find(|(stored_kind, _)| *stored_kind == kind)
+            .map(|(_, content)| content.clone())
+            .ok_or(anyhow!(
+                "could not store {} content key for block {block_number}",
+                kind.as_text()
+            ))
+    };
+    let header = stored_content(HistoryContentKind::BlockHeader)?;
+    let body = stored_content(HistoryContentKind::BlockBody)?;
+    let receipts = stored_content(HistoryContentKind::BlockReceipts)?;
+
+    config
+        .active_threads
+        .fetch_add(AUDITS_PER_BLOCK, Ordering::Relaxed);
+
+    let audit = |content: content::Model| {
+        let task = AuditTask {
+            strategy: SelectionStrategy::History(HistorySelectionStrategy::Block),
+            content,
+            selected_at: Utc::now(),
+        };
+        perform_audit(
+            config.active_threads.clone(),
+            task,
+            client.clone(),
+            config.audit_timeouts.clone(),
+            config.era1.clone(),
+            conn.clone(),
+            config.shutdown.clone(),
+        )
+    };
+    let (header_audit, body_audit, receipts_audit) =
+        tokio::join!(audit(header), audit(body), audit(receipts));
+    let (Some(header_audit), Some(body_audit), Some(receipts_audit)) =
+        (header_audit, body_audit, receipts_audit)
+    else {
+        return Err(anyhow!(
+            "could not record all content audits for block {block_number}"
+        ));
+    };
+
+    let verdict = BlockVerdict::from_results(&[
+        header_audit.result.clone(),
+        body_audit.result.clone(),
+        receipts_audit.result.clone(),
+    ]);
+    block_audit::create(
+        block_number,
+        header_audit.id,
+        body_audit.id,
+        receipts_audit.id,
+        verdict,
+        conn,
+    )
+    .await?;
+    info!(
+        block.number = block_number,
+        block.verdict = verdict.as_text(),
+        "Block audit complete."
+    );
+    Ok(())
+}
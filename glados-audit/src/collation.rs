@@ -0,0 +1,262 @@
+//! Collation of the tasks generated by the selection strategies into a single stream of audits.
+//!
+//! Tasks are taken in rounds. In each round a strategy gets as many tasks as its weight, or as
+//! many as it has waiting if that is fewer, so any strategy with tasks waiting gets at least its
+//! weight's share of every round, whatever the other strategies have waiting. Strategies with
+//! nothing waiting give up the rest of their round, so the audits are not held back for them.
+//! When no strategy has a task waiting, collation sleeps until one sends a task.
+use std::{future::poll_fn, task::Poll};
+
+use tokio::sync::mpsc::{self, error::TryRecvError};
+use tracing::{debug, error};
+
+use crate::{AuditTask, TaskChannel};
+
+/// Takes tasks from the strategy channels in proportion to their weights.
+pub struct WeightedCollator {
+    channels: Vec<TaskChannel>,
+    /// Tasks each channel may still give in the current round.
+    credits: Vec<u8>,
+    /// Whether each channel may still send tasks.
+    open: Vec<bool>,
+    /// The channel tasks are taken from in the current round.
+    current: usize,
+}
+
+impl WeightedCollator {
+    pub fn new(channels: Vec<TaskChannel>) -> Self {
+        let credits = channels.iter().map(|channel| channel.weight).collect();
+        let open = vec![true; channels.len()];
+        WeightedCollator {
+            channels,
+            credits,
+            open,
+            current: 0,
+        }
+    }
+
+    /// Returns the next task, waiting for one if no strategy has a task waiting. Returns None
+    /// once every strategy stopped sending tasks.
+    pub async fn next(&mut self) -> Option<AuditTask> {
+        if let Some(task) = self.next_in_round() {
+            return Some(task);
+        }
+        // Every channel used its weight or ran out of tasks.
+        self.start_round();
+        if let Some(task) = self.next_in_round() {
+            return Some(task);
+        }
+        let (index, task) = self.recv_any().await?;
+        self.start_round();
+        self.credits[index] -= 1;
+        Some(task)
+    }
+
+    fn start_round(&mut self) {
+        for (credits, channel) in self.credits.iter_mut().zip(&self.channels) {
+            *credits = channel.weight;
+        }
+        self.current = 0;
+    }
+
+    /// Takes a task from the channels that have credits left in the current round, in order.
+    fn next_in_round(&mut self) -> Option<AuditTask> {
+        while self.current < self.channels.len() {
+            let index = self.current;
+            if self.credits[index] > 0 && self.open[index] {
+                match self.channels[index].rx.try_recv() {
+                    Ok(task) => {
+                        self.credits[index] -= 1;
+                        return Some(task);
+                    }
+                    Err(TryRecvError::Empty) => {}
+                    Err(TryRecvError::Disconnected) => self.open[index] = false,
+                }
+            }
+            debug!(
+                strategy=?self.channels[index].strategy,
+                unused=self.credits[index],
+                "Collated strategy for this round"
+            );
+            self.credits[index] = 0;
+            self.current += 1;
+        }
+        None
+    }
+
+    /// Waits for a task from any channel with a weight, returning it with the index of its
+    /// channel.
+    async fn recv_any(&mut self) -> Option<(usize, AuditTask)> {
+        poll_fn(|cx| {
+            let mut pending = false;
+            for (index, channel) in self.channels.iter_mut().enumerate() {
+                if channel.weight == 0 || !self.open[index] {
+                    continue;
+                }
+                match channel.rx.poll_recv(cx) {
+                    Poll::Ready(Some(task)) => return Poll::Ready(Some((index, task))),
+                    Poll::Ready(None) => self.open[index] = false,
+                    Poll::Pending => pending = true,
+                }
+            }
+            if pending {
+                Poll::Pending
+            } else {
+                Poll::Ready(None)
+            }
+        })
+        .await
+    }
+}
+
+/// Listens to tasks coming on different strategy channels and selects according to strategy
+/// weight. Collated audit tasks are sent in a single channel for completion.
+pub async fn start_collation(
+    collation_tx: mpsc::Sender<AuditTask>,
+    task_channels: Vec<TaskChannel>,
+) {
+    let mut collator = WeightedCollator::new(task_channels);
+    while let Some(task) = collator.next().await {
+        if collation_tx.send(task).await.is_err() {
+            error!("Audits stopped, no more tasks are collated.");
+            return;
+        }
+    }
+    debug!("Every strategy stopped, no more tasks are collated.");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chrono::Utc;
+    use entity::{
+        content::{self, SubProtocol},
+        content_audit::{HistorySelectionStrategy, SelectionStrategy},
+    };
+    use tokio::time::timeout;
+
+    use super::*;
+
+    /// A task from the channel with the given index.
+    fn task(channel: i32) -> AuditTask {
+        AuditTask {
+            strategy: SelectionStrategy::History(HistorySelectionStrategy::Random),
+            content: content::Model {
+                id: channel,
+                protocol_id: SubProtocol::History,
+                content_key: vec![0x00; 33],
+                content_id: vec![0x01; 32],
+                first_available_at: Utc::now(),
+                source: None,
+                archived: false,
+            },
+            selected_at: Utc::now(),
+        }
+    }
+
+    /// Channels with the given weights, and the senders of their tasks.
+    fn channels(weights: &[u8]) -> (Vec<mpsc::Sender<AuditTask>>, Vec<TaskChannel>) {
+        weights
+            .iter()
+            .map(|weight| {
+                let (tx, rx) = mpsc::channel(100);
+                let channel = TaskChannel {
+                    strategy: SelectionStrategy::History(HistorySelectionStrategy::Random),
+                    weight: *weight,
+                    rx,
+                };
+                (tx, channel)
+            })
+            .unzip()
+    }
+
+    async fn fill(tx: &mpsc::Sender<AuditTask>, channel: i32, tasks: usize) {
+        for _ in 0..tasks {
+            tx.send(task(channel)).await.unwrap();
+        }
+    }
+
+    async fn collate(collator: &mut WeightedCollator, tasks: usize) -> Vec<i32> {
+        let mut collated = vec![];
+        for _ in 0..tasks {
+            let task = timeout(Duration::from_secs(1), collator.next())
+                .await
+                .expect("a task should be waiting")
+                .unwrap();
+            collated.push(task.content.id);
+        }
+        collated
+    }
+
+    #[tokio::test]
+    async fn test_strategies_get_their_weight_share_of_each_round() {
+        let (txs, channels) = channels(&[3, 1, 2]);
+        for (channel, tx) in txs.iter().enumerate() {
+            fill(tx, channel as i32, 60).await;
+        }
+        let mut collator = WeightedCollator::new(channels);
+
+        let collated = collate(&mut collator, 60).await;
+        for round in collated.chunks(6) {
+            assert_eq!(round, [0, 0, 0, 1, 2, 2]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_low_weight_strategy_is_not_starved() {
+        let (txs, channels) = channels(&[50, 1]);
+        fill(&txs[0], 0, 100).await;
+        fill(&txs[1], 1, 100).await;
+        let mut collator = WeightedCollator::new(channels);
+
+        let collated = collate(&mut collator, 102).await;
+        assert_eq!(collated.iter().filter(|channel| **channel == 1).count(), 2);
+
+        // With the heavy strategy out of tasks, the light one gets everything.
+        let collated = collate(&mut collator, 50).await;
+        assert!(collated.iter().all(|channel| *channel == 1));
+    }
+
+    #[tokio::test]
+    async fn test_strategies_with_tasks_take_unused_share() {
+        let (txs, channels) = channels(&[1, 4]);
+        fill(&txs[0], 0, 10).await;
+        let mut collator = WeightedCollator::new(channels);
+
+        assert_eq!(collate(&mut collator, 10).await, vec![0; 10]);
+    }
+
+    #[tokio::test]
+    async fn test_waits_for_tasks_when_all_channels_are_empty() {
+        let (txs, channels) = channels(&[2, 1]);
+        let mut collator = WeightedCollator::new(channels);
+
+        assert!(timeout(Duration::from_millis(50), collator.next())
+            .await
+            .is_err());
+
+        let tx = txs[1].clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            tx.send(task(1)).await.unwrap();
+        });
+        assert_eq!(collate(&mut collator, 1).await, vec![1]);
+
+        drop(txs);
+        assert!(collator.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_zero_weight_strategy_is_ignored() {
+        let (txs, channels) = channels(&[0, 1]);
+        fill(&txs[0], 0, 5).await;
+        fill(&txs[1], 1, 5).await;
+        let mut collator = WeightedCollator::new(channels);
+
+        assert_eq!(collate(&mut collator, 5).await, vec![1; 5]);
+        assert!(timeout(Duration::from_millis(50), collator.next())
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,334 @@
+//! Reading of era1 archive files, used to cross-verify audited pre-merge history content.
+//!
+//! An era1 file is an e2store file holding 8192 consecutive pre-merge blocks. Each block
+//! is stored as snappy-compressed RLP entries (header, body, receipts, total difficulty),
+//! followed by an accumulator root and a block index recording the offset of each block.
+//!
+//! See <https://github.com/eth-clients/e2store-format-specs/blob/main/formats/era1.md>
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use alloy_primitives::keccak256;
+use alloy_rlp::Decodable;
+use anyhow::{anyhow, ensure, Result};
+use ethportal_api::{
+    types::execution::header::Header, ContentValue, HistoryContentKey, HistoryContentValue,
+};
+
+/// Number of blocks stored in each era1 file.
+pub const BLOCKS_PER_ERA1: u64 = 8192;
+
+/// Size of the header preceding every e2store entry.
+const ENTRY_HEADER_LENGTH: u64 = 8;
+
+const COMPRESSED_HEADER: [u8; 2] = [0x03, 0x00];
+const COMPRESSED_BODY: [u8; 2] = [0x04, 0x00];
+const COMPRESSED_RECEIPTS: [u8; 2] = [0x05, 0x00];
+const BLOCK_INDEX: [u8; 2] = [0x66, 0x32];
+
+/// The canonical RLP-encoded data for a single block, as read from an era1 file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Era1Block {
+    pub header: Vec<u8>,
+    pub body: Vec<u8>,
+    pub receipts: Vec<u8>,
+}
+
+/// A directory of era1 files, e.g. `mainnet-00000-5ec1ffb8.era1`.
+///
+/// The directory is listed once, when the store is created, so files added later are not used
+/// until the next start.
+#[derive(Clone, Debug)]
+pub struct Era1Store {
+    /// The file of each era (a group of 8192 blocks) present.
+    files: Arc<HashMap<u64, PathBuf>>,
+}
+
+impl Era1Store {
+    pub fn new(dir: PathBuf) -> Result<Self> {
+        ensure!(
+            dir.is_dir(),
+            "era1 path {} is not a directory",
+            dir.display()
+        );
+        let mut files = HashMap::new();
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let Some(era) = name
+                .strip_suffix(".era1")
+                .and_then(|name| name.split('-').nth(1))
+                .and_then(|era| era.parse::<u64>().ok())
+            else {
+                continue;
+            };
+            files.insert(era, path);
+        }
+        Ok(Era1Store {
+            files: Arc::new(files),
+        })
+    }
+
+    /// Reads the block with the given number, or returns None if no era1 file covers it.
+    pub fn read_block(&self, block_number: u64) -> Result<Option<Era1Block>> {
+        let Some(path) = self.files.get(&(block_number / BLOCKS_PER_ERA1)) else {
+            return Ok(None);
+        };
+        read_block_from_file(path, block_number).map(Some)
+    }
+
+    /// Compares content returned by a Portal node against the canonical era1 data.
+    ///
+    /// Returns None if the block is not covered by the era1 files available.
+    pub fn content_matches(
+        &self,
+        content_key: &HistoryContentKey,
+        content_bytes: &[u8],
+        block_number: u64,
+    ) -> Result<Option<bool>> {
+        let Some(block) = self.read_block(block_number)? else {
+            return Ok(None);
+        };
+        let content = HistoryContentValue::decode(content_key, content_bytes)
+            .map_err(|e| anyhow!("could not decode history content: {e:?}"))?;
+        let canonical_header = Header::decode(&mut block.header.as_slice())
+            .map_err(|e| anyhow!("could not decode era1 header: {e:?}"))?;
+
+        let matches = match content {
+            HistoryContentValue::BlockHeaderWithProof(h) => {
+                h.header.hash() == keccak256(&block.header)
+            }
+            HistoryContentValue::BlockBody(b) => {
+                b.transactions_root()? == canonical_header.transactions_root
+                    && b.uncles_root() == canonical_header.uncles_hash
+            }
+            HistoryContentValue::Receipts(r) => r.root()? == canonical_header.receipts_root,
+        };
+        Ok(Some(matches))
+    }
+}
+
+/// Reads a single block from an era1 file, locating it via the block index at the end of the file.
+fn read_block_from_file(path: &Path, block_number: u64) -> Result<Era1Block> {
+    let mut file = File::open(path)?;
+    let file_length = file.metadata()?.len();
+
+    // The block index ends with the count of blocks in the file. Files may be truncated or
+    // corrupt, so every length read from them is checked before it is used.
+    let count_start = file_length
+        .checked_sub(8)
+        .ok_or(anyhow!("era1 file {} too short", path.display()))?;
+    file.seek(SeekFrom::Start(count_start))?;
+    let count = read_u64(&mut file)?;
+    let index_start = count
+        .checked_mul(8)
+        .and_then(|offsets_length| offsets_length.checked_add(ENTRY_HEADER_LENGTH + 8 + 8))
+        .and_then(|index_length| file_length.checked_sub(index_length))
+        .ok_or(anyhow!(
+            "era1 block index of {count} blocks longer than file {}",
+            path.display()
+        ))?;
+
+    file.seek(SeekFrom::Start(index_start))?;
+    let (entry_type, _) = read_entry_header(&mut file)?;
+    ensure!(entry_type == BLOCK_INDEX, "missing era1 block index");
+    let starting_number = read_u64(&mut file)?;
+    let position = block_number
+        .checked_sub(starting_number)
+        .filter(|position| *position < count)
+        .ok_or(anyhow!(
+            "block {block_number} not in era1 file {} ({count} blocks from {starting_number})",
+            path.display(),
+        ))?;
+
+    // Offsets are relative to the start of the block index entry. The position is below the
+    // count, so the offset lies within the index.
+    file.seek(SeekFrom::Current(8 * position as i64))?;
+    let offset = read_u64(&mut file)? as i64;
+    let block_start = index_start
+        .checked_add_signed(offset)
+        .filter(|block_start| *block_start < index_start)
+        .ok_or(anyhow!(
+            "era1 block offset {offset} outside file {}",
+            path.display()
+        ))?;
+    file.seek(SeekFrom::Start(block_start))?;
+
+    Ok(Era1Block {
+        header: read_compressed_entry(&mut file, COMPRESSED_HEADER)?,
+        body: read_compressed_entry(&mut file, COMPRESSED_BODY)?,
+        receipts: read_compressed_entry(&mut file, COMPRESSED_RECEIPTS)?,
+    })
+}
+
+fn read_u64(file: &mut File) -> Result<u64> {
+    let mut buf = [0u8; 8];
+    file.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+/// Reads an e2store entry header: type (2 bytes), length (4 bytes LE), reserved (2 bytes).
+fn read_entry_header(file: &mut File) -> Result<([u8; 2], u32)> {
+    let mut buf = [0u8; ENTRY_HEADER_LENGTH as usize];
+    file.read_exact(&mut buf)?;
+    let length = u32::from_le_bytes([buf[2], buf[3], buf[4], buf[5]]);
+    Ok(([buf[0], buf[1]], length))
+}
+
+/// Reads the next entry, checks its type and returns the snappy-decompressed data.
+///
+/// The length of the entry is checked against the rest of the file before it is read, so a
+/// corrupt length cannot make it allocate more than the file holds.
+fn read_compressed_entry(file: &mut File, expected_type: [u8; 2]) -> Result<Vec<u8>> {
+    let (entry_type, length) = read_entry_header(file)?;
+    ensure!(
+        entry_type == expected_type,
+        "unexpected era1 entry type {entry_type:?}, expected {expected_type:?}"
+    );
+    let remaining = file
+        .metadata()?
+        .len()
+        .saturating_sub(file.stream_position()?);
+    ensure!(
+        u64::from(length) <= remaining,
+        "era1 entry of {length} bytes longer than the {remaining} bytes left in the file"
+    );
+    let mut compressed = vec![0u8; length as usize];
+    file.read_exact(&mut compressed)?;
+    let mut decompressed = vec![];
+    snap::read::FrameDecoder::new(compressed.as_slice()).read_to_end(&mut decompressed)?;
+    Ok(decompressed)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    fn compressed_entry(entry_type: [u8; 2], data: &[u8]) -> Vec<u8> {
+        let mut encoder = snap::write::FrameEncoder::new(vec![]);
+        encoder.write_all(data).unwrap();
+        let compressed = encoder.into_inner().unwrap();
+        raw_entry(entry_type, &compressed)
+    }
+
+    fn raw_entry(entry_type: [u8; 2], data: &[u8]) -> Vec<u8> {
+        let mut entry = entry_type.to_vec();
+        entry.extend((data.len() as u32).to_le_bytes());
+        entry.extend([0, 0]);
+        entry.extend(data);
+        entry
+    }
+
+    /// Builds a minimal era1 file for blocks starting at `starting_number`.
+    fn build_era1(starting_number: u64, blocks: &[Era1Block]) -> Vec<u8> {
+        let mut file = raw_entry([0x65, 0x32], &[]);
+        let mut positions = vec![];
+        for block in blocks {
+            positions.push(file.len() as i64);
+            file.extend(compressed_entry(COMPRESSED_HEADER, &block.header));
+            file.extend(compressed_entry(COMPRESSED_BODY, &block.body));
+            file.extend(compressed_entry(COMPRESSED_RECEIPTS, &block.receipts));
+            file.extend(compressed_entry([0x06, 0x00], &[0u8; 32]));
+        }
+        file.extend(raw_entry([0x07, 0x00], &[0u8; 32]));
+
+        let index_start = file.len() as i64;
+        let mut index = starting_number.to_le_bytes().to_vec();
+        for position in positions {
+            index.extend((position - index_start).to_le_bytes());
+        }
+        index.extend((blocks.len() as u64).to_le_bytes());
+        file.extend(raw_entry(BLOCK_INDEX, &index));
+        file
+    }
+
+    #[test]
+    fn test_read_block_from_era1() {
+        let blocks: Vec<Era1Block> = (0..3u8)
+            .map(|i| Era1Block {
+                header: vec![i; 10],
+                body: vec![i + 10; 20],
+                receipts: vec![i + 20; 30],
+            })
+            .collect();
+        let dir = std::env::temp_dir().join(format!("glados-era1-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("mainnet-00001-00000000.era1"),
+            build_era1(BLOCKS_PER_ERA1, &blocks),
+        )
+        .unwrap();
+
+        let store = Era1Store::new(dir.clone()).unwrap();
+        assert_eq!(
+            store.read_block(BLOCKS_PER_ERA1 + 1).unwrap(),
+            Some(blocks[1].clone())
+        );
+        assert_eq!(
+            store.read_block(BLOCKS_PER_ERA1 + 2).unwrap(),
+            Some(blocks[2].clone())
+        );
+        // Blocks in an era without a file are skipped.
+        assert_eq!(store.read_block(0).unwrap(), None);
+        // Blocks beyond the end of the index are an error.
+        assert!(store.read_block(BLOCKS_PER_ERA1 + 3).is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_read_block_from_malformed_era1() {
+        let dir =
+            std::env::temp_dir().join(format!("glados-era1-malformed-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // Shorter than the block count at the end of the index.
+        let truncated = dir.join("truncated.era1");
+        std::fs::write(&truncated, [0u8; 5]).unwrap();
+        assert!(read_block_from_file(&truncated, 0).is_err());
+        std::fs::write(&truncated, b"").unwrap();
+        assert!(read_block_from_file(&truncated, 0).is_err());
+
+        // A block count so large that the index length overflows.
+        let blocks = [Era1Block {
+            header: vec![1; 10],
+            body: vec![2; 20],
+            receipts: vec![3; 30],
+        }];
+        let mut bogus_count = build_era1(BLOCKS_PER_ERA1, &blocks);
+        let count_start = bogus_count.len() - 8;
+        bogus_count[count_start..].copy_from_slice(&u64::MAX.to_le_bytes());
+        let bogus_count_path = dir.join("bogus-count.era1");
+        std::fs::write(&bogus_count_path, bogus_count).unwrap();
+        assert!(read_block_from_file(&bogus_count_path, BLOCKS_PER_ERA1).is_err());
+
+        // Blocks before the start of the file are an error rather than an underflow.
+        let valid = dir.join("valid.era1");
+        std::fs::write(&valid, build_era1(BLOCKS_PER_ERA1, &blocks)).unwrap();
+        assert!(read_block_from_file(&valid, BLOCKS_PER_ERA1 - 1).is_err());
+        assert_eq!(
+            read_block_from_file(&valid, BLOCKS_PER_ERA1).unwrap(),
+            blocks[0]
+        );
+
+        // An entry longer than the rest of the file is an error rather than a huge allocation.
+        let mut bogus_length = build_era1(BLOCKS_PER_ERA1, &blocks);
+        // The header entry of the first block follows the version entry.
+        let length_start = ENTRY_HEADER_LENGTH as usize + 2;
+        bogus_length[length_start..length_start + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        let bogus_length_path = dir.join("bogus-length.era1");
+        std::fs::write(&bogus_length_path, bogus_length).unwrap();
+        assert!(read_block_from_file(&bogus_length_path, BLOCKS_PER_ERA1).is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
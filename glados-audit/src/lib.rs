@@ -1,7 +1,7 @@
 use anyhow::Result;
-use chrono::Utc;
+use chrono::{DateTime, TimeDelta, Utc};
 use cli::Args;
-use ethportal_api::{utils::bytes::hex_encode, HistoryContentKey, OverlayContentKey};
+use ethportal_api::{HistoryContentKey, OverlayContentKey};
 use sea_orm::DatabaseConnection;
 use std::{
     collections::HashMap,
@@ -18,26 +18,59 @@ use tokio::{
     time::{sleep, Duration},
 };
 use tracing::{debug, error, info, warn};
+use url::Url;
 
 use entity::{
-    client_info,
-    content::{self, SubProtocol},
+    content::{self, ContentSource, SubProtocol},
     content_audit::{
         self, BeaconSelectionStrategy, HistorySelectionStrategy, SelectionStrategy,
         StateSelectionStrategy,
     },
-    execution_metadata, node,
 };
-use glados_core::jsonrpc::PortalClient;
+use glados_core::{
+    connection::ConnectionSettings,
+    feature_flags::{FeatureFlags, OFFER_AUDITS},
+    jsonrpc::{PortalClient, REQUEST_TIMEOUT},
+    proxy::ProxySettings,
+};
 
 use crate::{
-    selection::start_audit_selection_task, state::spawn_state_audit, validation::content_is_valid,
+    block::run_block_audit,
+    collation::start_collation,
+    era1::Era1Store,
+    propagation::run_propagation_audit,
+    queue::{enqueue_tasks, perform_queued_audits},
+    remote_write::RemoteWrite,
+    reseed::run_reseed,
+    retention::RetentionPolicy,
+    round_trip::run_round_trip_audit,
+    selection::start_audit_selection_task,
+    shutdown::Shutdown,
+    state::spawn_state_audit,
+    sync::run_sync_audit,
+    task::perform_audit,
+    timeout::AuditTimeouts,
 };
 
+mod block;
 pub mod cli;
+mod collation;
+pub mod era1;
+mod maintenance;
+mod propagation;
+mod queue;
+pub mod remote_write;
+mod report;
+mod reseed;
+pub mod retention;
+mod round_trip;
 pub(crate) mod selection;
+pub mod shutdown;
 mod state;
 pub mod stats;
+mod sync;
+mod task;
+pub mod timeout;
 pub(crate) mod validation;
 
 /// Configuration created from CLI arguments.
@@ -63,10 +96,44 @@ pub struct AuditConfig {
     pub weights: HashMap<HistorySelectionStrategy, u8>,
     /// Number requests to a Portal node active at the same time.
     pub concurrency: u8,
+    /// Number of audits being performed, shared by everything that starts audits so that
+    /// together they stay within the concurrency.
+    pub active_threads: Arc<AtomicU8>,
     /// Portal Clients
     pub portal_clients: Vec<PortalClient>,
+    /// Time audits wait for the Portal node to find the content.
+    pub audit_timeouts: AuditTimeouts,
     /// Number of seconds between recording the current audit performance in audit_stats table.
     pub stats_recording_period: u64,
+    /// Number of seconds between recording the audit coverage of the strategies.
+    pub coverage_recording_period: u64,
+    /// Number of minutes after which a pending audit is marked as errored.
+    pub stale_pending_minutes: u32,
+    /// Where the recorded audit stats are also pushed, if anywhere.
+    pub remote_write: Option<RemoteWrite>,
+    /// How long audits and content are kept.
+    pub retention: RetentionPolicy,
+    /// Number of blocks audited per segment by the Sync strategy.
+    pub sync_segment_size: u32,
+    /// Number of days after its latest audit before the RandomUnaudited strategy can select a
+    /// key again.
+    pub unaudited_days: u32,
+    /// Era1 files for cross-checking pre-merge history content.
+    pub era1: Option<Era1Store>,
+    /// Whether selected tasks go through the database task queue.
+    pub task_queue: bool,
+    /// Whether to only perform audits from the task queue, without selecting any.
+    pub worker_only: bool,
+    /// Content key to audit once, instead of running the strategies.
+    pub audit_key: Option<HistoryContentKey>,
+    /// Whether to offer content that failed its audit back to the network.
+    pub reseed: bool,
+    /// Feature flags of the deployment, read from the database at startup.
+    pub feature_flags: FeatureFlags,
+    /// Shutdown signal of the audit tasks.
+    pub shutdown: Shutdown,
+    /// Time to wait on shutdown for audits in flight to finish.
+    pub shutdown_timeout: Duration,
 }
 
 impl AuditConfig {
@@ -86,7 +153,31 @@ impl AuditConfig {
             )
         }
 
-        let strategies = match args.history_strategy {
+        // Strategies given as `--strategy <subprotocol>:<strategy>` add to those of the
+        // subprotocol specific flags, whose audits must be enabled.
+        args.check_qualified_strategies()
+            .map_err(|e| anyhow::anyhow!(e))?;
+        let mut history_strategy = args.history_strategy;
+        let mut beacon_strategy = args.beacon_strategy;
+        let mut state_strategy = args.state_strategy;
+        let mut qualified_weights: HashMap<HistorySelectionStrategy, u8> = HashMap::new();
+        for qualified in args.strategy {
+            match qualified.strategy {
+                SelectionStrategy::History(strategy) => {
+                    if let Some(weight) = qualified.weight {
+                        qualified_weights.insert(strategy.clone(), weight);
+                    }
+                    history_strategy.get_or_insert_with(Vec::new).push(strategy);
+                }
+                SelectionStrategy::Beacon(strategy) => {
+                    beacon_strategy.get_or_insert_with(Vec::new).push(strategy)
+                }
+                SelectionStrategy::State(strategy) => {
+                    state_strategy.get_or_insert_with(Vec::new).push(strategy)
+                }
+            }
+        }
+        let strategies = match history_strategy {
             Some(s) => s,
             None => {
                 vec![
@@ -102,11 +193,19 @@ impl AuditConfig {
             let weight = match strat {
                 HistorySelectionStrategy::Latest => args.latest_strategy_weight,
                 HistorySelectionStrategy::Random => args.random_strategy_weight,
+                HistorySelectionStrategy::RandomUnaudited => args.random_unaudited_strategy_weight,
                 HistorySelectionStrategy::Failed => args.failed_strategy_weight,
                 HistorySelectionStrategy::SelectOldestUnaudited => args.oldest_strategy_weight,
                 HistorySelectionStrategy::FourFours => args.four_fours_strategy_weight,
                 HistorySelectionStrategy::SpecificContentKey => 0,
+                // Sync, Block, RoundTrip and Propagation audits run outside of the weighted task
+                // channels.
+                HistorySelectionStrategy::Sync
+                | HistorySelectionStrategy::Block
+                | HistorySelectionStrategy::RoundTrip
+                | HistorySelectionStrategy::Propagation => 0,
             };
+            let weight = qualified_weights.get(strat).copied().unwrap_or(weight);
             weights.insert(strat.clone(), weight);
         }
         if args.provider_url.is_empty()
@@ -117,9 +216,91 @@ impl AuditConfig {
                 "No provider URL provided, required when `four_fours` strategy is enabled."
             ));
         }
+        if args.provider_url.is_empty()
+            && args.history
+            && strategies.contains(&HistorySelectionStrategy::Sync)
+        {
+            return Err(anyhow::anyhow!(
+                "No provider URL provided, required when `sync` strategy is enabled."
+            ));
+        }
+        if args.provider_url.is_empty()
+            && args.history
+            && strategies.contains(&HistorySelectionStrategy::Block)
+        {
+            return Err(anyhow::anyhow!(
+                "No provider URL provided, required when `block` strategy is enabled."
+            ));
+        }
+        if args.provider_url.is_empty() && args.reseed {
+            return Err(anyhow::anyhow!(
+                "No provider URL provided, required when reseeding is enabled."
+            ));
+        }
+        if args.history
+            && strategies.contains(&HistorySelectionStrategy::RoundTrip)
+            && args.portal_client.len() < 2
+        {
+            return Err(anyhow::anyhow!(
+                "Two portal clients required when `round_trip` strategy is enabled: the first gossips, the others audit."
+            ));
+        }
+        let audit_key = match args.audit_key {
+            Some(audit_key) => Some(HistoryContentKey::try_from_hex(&audit_key).map_err(|e| {
+                anyhow::anyhow!("Invalid history content key `{audit_key}`: {e:?}")
+            })?),
+            None => None,
+        };
+        let era1 = match args.era1_dir {
+            Some(dir) => {
+                info!(era1.dir=?dir, "Cross-checking pre-merge history audits with era1 files.");
+                Some(Era1Store::new(dir)?)
+            }
+            None => None,
+        };
+        let proxies = ProxySettings {
+            default: args.proxy,
+            overrides: args.client_proxy,
+        };
+        let audit_timeouts = AuditTimeouts {
+            default: Duration::from_secs(args.audit_timeout_seconds),
+            by_content_type: args
+                .audit_timeout
+                .iter()
+                .map(|timeout| (timeout.selector, Duration::from_secs(timeout.seconds)))
+                .collect(),
+        };
+        let longest_timeout = audit_timeouts
+            .by_content_type
+            .values()
+            .fold(audit_timeouts.default, |longest, timeout| {
+                longest.max(*timeout)
+            });
+        if longest_timeout > REQUEST_TIMEOUT {
+            return Err(anyhow::anyhow!(
+                "Audit timeouts cannot be longer than the {} seconds Portal client requests are given.",
+                REQUEST_TIMEOUT.as_secs()
+            ));
+        }
         let mut portal_clients: Vec<PortalClient> = vec![];
         for client_url in args.portal_client {
-            let client = PortalClient::from(client_url).await?;
+            let connection = ConnectionSettings {
+                proxy: Url::parse(&client_url)
+                    .ok()
+                    .and_then(|url| proxies.for_client(&url).cloned()),
+                max_idle_connections: args.portal_client_max_idle_connections,
+                http2: args.portal_client_http2,
+                http2_keep_alive: args
+                    .portal_client_http2_keep_alive_seconds
+                    .map(Duration::from_secs),
+            };
+            let client = PortalClient::from(
+                client_url,
+                args.portal_client_max_concurrent_requests,
+                &connection,
+                args.portal_client_compression,
+            )
+            .await?;
             info!("Found a portal client with type: {:?}", client.client_info);
             portal_clients.push(client);
         }
@@ -128,14 +309,39 @@ impl AuditConfig {
             provider_url: args.provider_url,
             weights,
             concurrency: args.concurrency,
+            active_threads: Arc::default(),
             portal_clients,
+            audit_timeouts,
             stats_recording_period: args.stats_recording_period,
+            coverage_recording_period: args.coverage_recording_period,
+            stale_pending_minutes: args.stale_pending_minutes,
+            remote_write: args.remote_write_url.map(RemoteWrite::new).transpose()?,
+            retention: RetentionPolicy {
+                audit_retention: args
+                    .audit_retention_days
+                    .map(|days| TimeDelta::days(days.into())),
+                content_retention: args
+                    .content_retention_days
+                    .map(|days| TimeDelta::days(days.into())),
+            },
+            sync_segment_size: args.sync_segment_size,
+            unaudited_days: args.unaudited_days,
             history: args.history,
             history_strategies: strategies,
             beacon: args.beacon,
-            beacon_strategies: args.beacon_strategy.unwrap_or_default(),
+            beacon_strategies: beacon_strategy
+                .unwrap_or_else(|| vec![BeaconSelectionStrategy::Latest]),
             state: args.state,
-            state_strategies: args.state_strategy.unwrap_or_default(),
+            state_strategies: state_strategy
+                .unwrap_or_else(|| vec![StateSelectionStrategy::StateRoots]),
+            era1,
+            task_queue: args.task_queue,
+            worker_only: args.worker_only,
+            audit_key,
+            reseed: args.reseed,
+            feature_flags: FeatureFlags::default(),
+            shutdown: Shutdown::default(),
+            shutdown_timeout: Duration::from_secs(args.shutdown_timeout_seconds),
         })
     }
 }
@@ -144,6 +350,8 @@ impl AuditConfig {
 pub struct AuditTask {
     pub strategy: SelectionStrategy,
     pub content: content::Model,
+    /// When the strategy selected the content, to measure how long the audit waited to run.
+    pub selected_at: DateTime<Utc>,
 }
 
 // Associates strategies with their channels and weights.
@@ -161,22 +369,126 @@ pub async fn run_glados_command(conn: DatabaseConnection, command: cli::Command)
             portal_client,
             ..
         } => (content_key, portal_client),
+        cli::Command::RebuildAuditLatest { batch_size, .. } => {
+            return maintenance::rebuild_audit_latest(batch_size, &conn).await;
+        }
+        cli::Command::BackfillFirstSuccess { batch_size, .. } => {
+            return maintenance::backfill_first_success(batch_size, &conn).await;
+        }
+        cli::Command::Archive {
+            content_keys,
+            node_ids,
+            unarchive,
+            ..
+        } => {
+            return maintenance::set_archived(content_keys, node_ids, !unarchive, &conn).await;
+        }
+        cli::Command::Stats { start, end, .. } => {
+            return report::print_report(start, end.unwrap_or_else(Utc::now), &conn).await;
+        }
     };
-    let content_key =
-        HistoryContentKey::try_from_hex(&content_key).expect("needs valid hex-encoded history key");
+    let content_key = HistoryContentKey::try_from_hex(&content_key)
+        .map_err(|e| anyhow::anyhow!("Invalid history content key `{content_key}`: {e:?}"))?;
 
+    let client =
+        PortalClient::from(portal_client, None, &ConnectionSettings::default(), false).await?;
+    print_specific_audit(&content_key, client, AuditTimeouts::default(), None, &conn).await
+}
+
+/// Audits a single history content key with the first portal client, for the `--audit-key`
+/// one-shot mode, and prints the outcome.
+pub async fn audit_content_key(
+    content_key: HistoryContentKey,
+    config: AuditConfig,
+    conn: DatabaseConnection,
+) -> Result<()> {
+    let client = config
+        .portal_clients
+        .first()
+        .ok_or_else(|| anyhow::anyhow!("No portal client to audit with."))?;
+    print_specific_audit(
+        &content_key,
+        client.clone(),
+        config.audit_timeouts,
+        config.era1,
+        &conn,
+    )
+    .await
+}
+
+/// Audits the content key and prints the audit id and outcome.
+async fn print_specific_audit(
+    content_key: &HistoryContentKey,
+    client: PortalClient,
+    timeouts: AuditTimeouts,
+    era1: Option<Era1Store>,
+    conn: &DatabaseConnection,
+) -> Result<()> {
+    let audit = audit_specific_key(content_key, client.clone(), timeouts, era1, conn).await?;
+    println!(
+        "Audit {} of {} with {}: {}",
+        audit.id,
+        content_key.to_hex(),
+        client.client_info,
+        audit.result.as_text()
+    );
+    Ok(())
+}
+
+/// Audits a content key requested by a user, storing the key if Glados doesn't know it yet.
+async fn audit_specific_key(
+    content_key: &HistoryContentKey,
+    client: PortalClient,
+    timeouts: AuditTimeouts,
+    era1: Option<Era1Store>,
+    conn: &DatabaseConnection,
+) -> Result<content_audit::Model> {
     let task = AuditTask {
         strategy: SelectionStrategy::History(HistorySelectionStrategy::SpecificContentKey),
-        content: content::get_or_create(SubProtocol::History, &content_key, Utc::now(), &conn)
-            .await?,
+        content: content::get_or_create(
+            SubProtocol::History,
+            content_key,
+            Utc::now(),
+            ContentSource::Audit,
+            conn,
+        )
+        .await?,
+        selected_at: Utc::now(),
     };
-    let client = PortalClient::from(portal_client).await?;
-    let active_threads = Arc::new(AtomicU8::new(0));
-    perform_single_audit(active_threads, task, client.clone(), conn).await;
-    Ok(())
+    let active_threads = Arc::new(AtomicU8::new(1));
+    perform_audit(
+        active_threads,
+        task,
+        client,
+        timeouts,
+        era1,
+        conn.clone(),
+        Shutdown::default(),
+    )
+    .await
+    .ok_or_else(|| anyhow::anyhow!("The audit of {} was not recorded.", content_key.to_hex()))
 }
 
+/// Runs the audits until CTRL+C, then stops starting new ones and drains those in flight.
 pub async fn run_glados_audit(conn: DatabaseConnection, config: AuditConfig) {
+    if config.task_queue {
+        tokio::spawn(perform_queued_audits(config.clone(), conn.clone()));
+    }
+    if !config.worker_only {
+        start_selected_audits(conn.clone(), config.clone()).await;
+    }
+
+    debug!("setting up CTRL+C listener");
+    tokio::signal::ctrl_c()
+        .await
+        .expect("failed to pause until ctrl-c");
+
+    info!("got CTRL+C. shutting down...");
+    config.shutdown.drain(config.shutdown_timeout, &conn).await;
+}
+
+/// Starts the audits of the configured subnetworks and strategies.
+async fn start_selected_audits(conn: DatabaseConnection, config: AuditConfig) {
     // if state network is enabled, run state audits
     if config.state {
         spawn_state_audit(conn.clone(), config.clone()).await;
@@ -197,9 +509,52 @@ pub async fn run_glados_audit(conn: DatabaseConnection, config: AuditConfig) {
     }
 
     if config.history {
+        if config
+            .history_strategies
+            .contains(&HistorySelectionStrategy::Sync)
+        {
+            tokio::spawn(run_sync_audit(conn.clone(), config.clone()));
+        }
+        if config
+            .history_strategies
+            .contains(&HistorySelectionStrategy::Block)
+        {
+            tokio::spawn(run_block_audit(conn.clone(), config.clone()));
+        }
+        if config
+            .history_strategies
+            .contains(&HistorySelectionStrategy::RoundTrip)
+        {
+            tokio::spawn(run_round_trip_audit(conn.clone(), config.clone()));
+        }
+        if config
+            .history_strategies
+            .contains(&HistorySelectionStrategy::Propagation)
+        {
+            tokio::spawn(run_propagation_audit(conn.clone(), config.clone()));
+        }
+        if config.reseed {
+            if config.feature_flags.is_enabled(OFFER_AUDITS) {
+                tokio::spawn(run_reseed(conn.clone(), config.clone()));
+            } else {
+                warn!(
+                    flag = OFFER_AUDITS,
+                    "Reseeding requested, but its feature flag is not enabled."
+                );
+            }
+        }
         let strategies = config
             .history_strategies
             .iter()
+            .filter(|strats| {
+                !matches!(
+                    strats,
+                    HistorySelectionStrategy::Sync
+                        | HistorySelectionStrategy::Block
+                        | HistorySelectionStrategy::RoundTrip
+                        | HistorySelectionStrategy::Propagation
+                )
+            })
             .filter_map(|strats| {
                 let strategy = SelectionStrategy::History(strats.clone());
                 match config.weights.get(strats) {
@@ -213,14 +568,6 @@ pub async fn run_glados_audit(conn: DatabaseConnection, config: AuditConfig) {
             .collect();
         start_audit(conn.clone(), config.clone(), strategies).await;
     }
-
-    debug!("setting up CTRL+C listener");
-    tokio::signal::ctrl_c()
-        .await
-        .expect("failed to pause until ctrl-c");
-
-    info!("got CTRL+C. shutting down...");
-    std::process::exit(0);
 }
 
 async fn start_audit(
@@ -249,32 +596,11 @@ async fn start_audit(
     // Collation of generated tasks, taken proportional to weights.
     let (collation_tx, collation_rx) = mpsc::channel::<AuditTask>(100);
     tokio::spawn(start_collation(collation_tx, task_channels));
-    // Perform collated audit tasks.
-    tokio::spawn(perform_content_audits(config, collation_rx, conn));
-}
-
-/// Listens to tasks coming on different strategy channels and selects
-/// according to strategy weight. Collated audit tasks are sent in a single
-/// channel for completion.
-async fn start_collation(
-    collation_tx: mpsc::Sender<AuditTask>,
-    mut task_channels: Vec<TaskChannel>,
-) {
-    loop {
-        for tasks in task_channels.iter_mut() {
-            debug!(strategy=?tasks.strategy, max=tasks.weight, "collating");
-            for _ in 0..tasks.weight {
-                match tasks.rx.try_recv() {
-                    Ok(task) => collation_tx
-                        .send(task)
-                        .await
-                        .expect("Unable to collate task"),
-                    Err(_) => break,
-                }
-            }
-            // Limit check for new tasks to 5/sec
-            sleep(Duration::from_millis(200)).await;
-        }
+    // Perform collated audit tasks, or queue them for the queue workers.
+    if config.task_queue {
+        tokio::spawn(enqueue_tasks(collation_rx, conn));
+    } else {
+        tokio::spawn(perform_content_audits(config, collation_rx, conn));
     }
 }
 
@@ -284,7 +610,7 @@ async fn perform_content_audits(
     conn: DatabaseConnection,
 ) {
     let concurrency = config.concurrency;
-    let active_threads = Arc::new(AtomicU8::new(0));
+    let active_threads = config.active_threads.clone();
 
     let mut cycle_of_clients = config.portal_clients.iter().cycle();
 
@@ -307,7 +633,14 @@ async fn perform_content_audits(
             "Checking Rx channel for audits"
         );
 
-        match rx.recv().await {
+        let task = tokio::select! {
+            task = rx.recv() => task,
+            _ = config.shutdown.triggered() => {
+                info!("Shutting down, no more audits are started.");
+                return;
+            }
+        };
+        match task {
             Some(task) => {
                 active_threads.fetch_add(1, Ordering::Relaxed);
                 let client = match cycle_of_clients.next() {
@@ -317,11 +650,14 @@ async fn perform_content_audits(
                         return;
                     }
                 };
-                tokio::spawn(perform_single_audit(
+                tokio::spawn(perform_audit(
                     active_threads.clone(),
                     task,
                     client.clone(),
+                    config.audit_timeouts.clone(),
+                    config.era1.clone(),
                     conn.clone(),
+                    config.shutdown.clone(),
                 ))
             }
             None => {
@@ -330,147 +666,3 @@ async fn perform_content_audits(
         };
     }
 }
-
-/// Performs an audit against a Portal node.
-///
-/// After auditing finishes the thread counter is deprecated. This
-/// applies even if the audit process encounters an error.
-async fn perform_single_audit(
-    active_threads: Arc<AtomicU8>,
-    task: AuditTask,
-    client: PortalClient,
-    conn: DatabaseConnection,
-) {
-    let client_info = client.client_info.clone();
-
-    debug!(
-        content.key = hex_encode(&task.content.content_key),
-        client.url =? client.api.client,
-        "auditing content",
-    );
-    let (content_response, trace) = if client.supports_trace() {
-        match client.api.get_content_with_trace(&task.content).await {
-            Ok(c) => c,
-            Err(e) => {
-                error!(
-                    content.key=hex_encode(&task.content.content_key),
-                    err=?e,
-                    "Problem requesting content with trace from Portal node."
-                );
-                active_threads.fetch_sub(1, Ordering::Relaxed);
-                return;
-            }
-        }
-    } else {
-        match client.api.get_content(&task.content).await {
-            Ok(c) => (c, "".to_owned()),
-            Err(e) => {
-                error!(
-                    content.key=hex_encode(task.content.content_key),
-                    err=?e,
-                    "Problem requesting content from Portal node."
-                );
-                active_threads.fetch_sub(1, Ordering::Relaxed);
-                return;
-            }
-        }
-    };
-
-    // If content was absent audit result is 'fail'.
-    let audit_result = match content_response {
-        Some(content_bytes) => content_is_valid(&task.content, &content_bytes.raw),
-        None => false,
-    };
-
-    let client_info_id = match client_info::get_or_create(client_info, &conn).await {
-        Ok(client_info) => client_info.id,
-        Err(error) => {
-            error!(content.key=?task.content,
-                err=?error,
-                "Could not create/lookup client info in db."
-            );
-            return;
-        }
-    };
-
-    let node_id = match node::get_or_create(client.enr.node_id(), &conn).await {
-        Ok(enr) => enr.id,
-        Err(err) => {
-            error!(
-                err=?err,
-                "Failed to created node."
-            );
-            return;
-        }
-    };
-    if let Err(e) = content_audit::create(
-        task.content.id,
-        client_info_id,
-        node_id,
-        audit_result,
-        task.strategy,
-        trace,
-        &conn,
-    )
-    .await
-    {
-        error!(
-            content.key=?task.content,
-            err=?e,
-            "Could not create audit entry in db."
-        );
-        active_threads.fetch_sub(1, Ordering::Relaxed);
-        return;
-    };
-
-    // Display audit result.
-    match task.content.protocol_id {
-        SubProtocol::History => {
-            display_history_audit_result(task.content, audit_result, &conn).await;
-        }
-        SubProtocol::Beacon => {
-            info!(
-                content.key = hex_encode(task.content.content_key),
-                audit.pass = audit_result,
-                content.protocol = "Beacon",
-            );
-        }
-        SubProtocol::State => {
-            info!(
-                content.key = hex_encode(task.content.content_key),
-                audit.pass = audit_result,
-                content.protocol = "State",
-            );
-        }
-    }
-
-    active_threads.fetch_sub(1, Ordering::Relaxed);
-}
-
-async fn display_history_audit_result(
-    content: content::Model,
-    audit_result: bool,
-    conn: &DatabaseConnection,
-) {
-    match execution_metadata::get(content.id, conn).await {
-        Ok(Some(b)) => {
-            info!(
-                content.key=hex_encode(content.content_key),
-                audit.pass=?audit_result,
-                block = b.block_number,
-                "History content audit"
-            );
-        }
-        Ok(None) => {
-            info!(
-                content.key=hex_encode(content.content_key),
-                audit.pass=?audit_result,
-                "Block metadata absent for history key."
-            );
-        }
-        Err(e) => error!(
-                    content.key=hex_encode(content.content_key),
-                    err=?e,
-                    "Problem getting block metadata for history key."),
-    };
-}
@@ -1,12 +1,18 @@
 use anyhow::Result;
+use chrono::TimeDelta;
 use clap::Parser;
-use glados_audit::stats::periodically_record_stats;
+use entity::client_info;
+use glados_audit::{
+    retention::periodically_apply_retention,
+    stats::{periodically_record_coverage, periodically_record_stats},
+};
 use sea_orm::Database;
 use tokio::time::Duration;
 use tracing::{debug, info};
 
 use glados_audit::cli::{Args, Command};
-use glados_audit::{run_glados_audit, run_glados_command, AuditConfig};
+use glados_audit::{audit_content_key, run_glados_audit, run_glados_command, AuditConfig};
+use glados_core::feature_flags::FeatureFlags;
 use migration::{Migrator, MigratorTrait};
 
 #[tokio::main]
@@ -34,6 +40,10 @@ async fn run_command(command: Command) -> Result<()> {
     //
     let database_url = match &command {
         Command::Audit { database_url, .. } => database_url,
+        Command::RebuildAuditLatest { database_url, .. } => database_url,
+        Command::BackfillFirstSuccess { database_url, .. } => database_url,
+        Command::Archive { database_url, .. } => database_url,
+        Command::Stats { database_url, .. } => database_url,
     };
     debug!(database_url = database_url, "Connecting to database");
 
@@ -51,7 +61,7 @@ async fn run_audit(args: Args) -> Result<()> {
     //
     // Database Connection
     //
-    let config = AuditConfig::from_args(args).await?;
+    let mut config = AuditConfig::from_args(args).await?;
     debug!(
         database_url = &config.database_url,
         "Connecting to database"
@@ -64,10 +74,33 @@ async fn run_audit(args: Args) -> Result<()> {
     );
 
     Migrator::up(&conn, None).await?;
+    let parsed = client_info::parse_unparsed(&conn).await?;
+    if parsed > 0 {
+        info!(
+            clients = parsed,
+            "Parsed the version strings of earlier clients."
+        );
+    }
+    config.feature_flags = FeatureFlags::load(&conn).await?;
+    if let Some(content_key) = config.audit_key.clone() {
+        return audit_content_key(content_key, config, conn).await;
+    }
     tokio::spawn(periodically_record_stats(
         Duration::from_secs(config.stats_recording_period),
+        TimeDelta::minutes(config.stale_pending_minutes.into()),
+        config.remote_write.clone(),
         conn.clone(),
     ));
+    tokio::spawn(periodically_record_coverage(
+        Duration::from_secs(config.coverage_recording_period),
+        conn.clone(),
+    ));
+    if config.retention.is_enabled() {
+        tokio::spawn(periodically_apply_retention(
+            config.retention.clone(),
+            conn.clone(),
+        ));
+    }
     run_glados_audit(conn, config).await;
     Ok(())
 }
@@ -0,0 +1,105 @@
+//! One-off maintenance tasks, run via CLI subcommands.
+use anyhow::{bail, Result};
+use entity::{audit_latest, content, content_first_success, node};
+use ethportal_api::utils::bytes::hex_decode;
+use sea_orm::DatabaseConnection;
+use tracing::info;
+
+/// Rebuilds the `audit_latest` table from `content_audit`, one batch of content ids at a time.
+///
+/// Each batch is replaced within its own transaction, so the web UI stays usable while the
+/// rebuild is running. Audits recorded during the rebuild are kept current by the trigger.
+pub async fn rebuild_audit_latest(batch_size: u32, conn: &DatabaseConnection) -> Result<()> {
+    if batch_size == 0 {
+        bail!("batch size must be greater than zero");
+    }
+    let Some(max_id) = audit_latest::max_audited_content_id(conn).await? else {
+        info!("no audits recorded, nothing to rebuild");
+        return Ok(());
+    };
+
+    let mut rows_written = 0;
+    let mut start_id = 0;
+    while start_id <= max_id {
+        let end_id = start_id.saturating_add(batch_size as i32);
+        if end_id == start_id {
+            break;
+        }
+        rows_written += audit_latest::rebuild_range(start_id, end_id, conn).await?;
+        info!(
+            start_id,
+            end_id, max_id, rows_written, "rebuilt audit_latest batch"
+        );
+        start_id = end_id;
+    }
+
+    let (actual, expected) = audit_latest::row_counts(conn).await?;
+    if actual != expected {
+        bail!("audit_latest has {actual} rows after rebuild, expected {expected}");
+    }
+    info!(rows = actual, "audit_latest rebuilt and verified");
+    Ok(())
+}
+
+/// Records how long after becoming available each content key was first found, from the audit
+/// history, one batch of content ids at a time.
+///
+/// Safe to run again, for example to include the content audited since the last run.
+pub async fn backfill_first_success(batch_size: u32, conn: &DatabaseConnection) -> Result<()> {
+    if batch_size == 0 {
+        bail!("batch size must be greater than zero");
+    }
+    let Some(max_id) = audit_latest::max_audited_content_id(conn).await? else {
+        info!("no audits recorded, nothing to backfill");
+        return Ok(());
+    };
+
+    let mut rows_written = 0;
+    let mut start_id = 0;
+    while start_id <= max_id {
+        let end_id = start_id.saturating_add(batch_size as i32);
+        if end_id == start_id {
+            break;
+        }
+        rows_written += content_first_success::backfill_range(start_id, end_id, conn).await?;
+        info!(
+            start_id,
+            end_id, max_id, rows_written, "backfilled content_first_success batch"
+        );
+        start_id = end_id;
+    }
+    info!(rows = rows_written, "content_first_success backfilled");
+    Ok(())
+}
+
+/// Archives, or unarchives, the content keys and nodes given as hex strings, hiding them from
+/// the dashboards while keeping their history.
+pub async fn set_archived(
+    content_keys: Vec<String>,
+    node_ids: Vec<String>,
+    archived: bool,
+    conn: &DatabaseConnection,
+) -> Result<()> {
+    if content_keys.is_empty() && node_ids.is_empty() {
+        bail!("no content keys or node ids given");
+    }
+    let content_keys = content_keys
+        .iter()
+        .map(|content_key| hex_decode(content_key))
+        .collect::<Result<Vec<_>, _>>()?;
+    let node_ids = node_ids
+        .iter()
+        .map(|node_id| hex_decode(node_id))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let num_content = match content_keys.is_empty() {
+        true => 0,
+        false => content::set_archived(content_keys, archived, conn).await?,
+    };
+    let num_nodes = match node_ids.is_empty() {
+        true => 0,
+        false => node::set_archived(node_ids, archived, conn).await?,
+    };
+    info!(archived, num_content, num_nodes, "updated archived flags");
+    Ok(())
+}
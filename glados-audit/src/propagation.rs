@@ -0,0 +1,150 @@
+//! Audits a sample of new content at fixed ages ([HistorySelectionStrategy::Propagation]).
+//!
+//! Each sampled key is audited at every age in [PROPAGATION_OFFSETS], counted from when it was
+//! first available. Comparing how much of the sample was found at each age gives the
+//! propagation curve: how fast new content becomes retrievable across the network.
+//!
+//! [HistorySelectionStrategy::Propagation]: entity::content_audit::HistorySelectionStrategy::Propagation
+use std::sync::atomic::Ordering;
+
+use anyhow::Result;
+use chrono::{TimeDelta, Utc};
+use futures::future::join_all;
+use sea_orm::DatabaseConnection;
+use tokio::time::{interval, Duration};
+use tracing::{debug, error, info};
+
+use entity::{
+    content_audit::{HistorySelectionStrategy, SelectionStrategy},
+    propagation_audit,
+};
+use glados_core::jsonrpc::PortalClient;
+
+use crate::{shutdown::Shutdown, task::perform_audit, AuditConfig, AuditTask};
+
+/// Ages of the content when it is audited, in seconds after it was first available.
+pub const PROPAGATION_OFFSETS: [i32; 6] = [60, 300, 900, 3_600, 21_600, 86_400];
+
+/// Time between samples of new content.
+///
+/// Kept well below the first offset, as content is only scheduled once sampled: its audits can
+/// be late by up to this period, plus up to [DUE_CHECK_PERIOD].
+const SAMPLE_PERIOD: Duration = Duration::from_secs(12);
+
+/// Number of new keys sampled each period.
+const KEYS_PER_SAMPLE: u64 = 1;
+
+/// Time between checks for audits that are due.
+const DUE_CHECK_PERIOD: Duration = Duration::from_secs(10);
+
+/// Maximum number of due audits made at each check.
+const DUE_AUDIT_LIMIT: u64 = 50;
+
+pub async fn run_propagation_audit(conn: DatabaseConnection, config: AuditConfig) {
+    if config.portal_clients.is_empty() {
+        error!("Empty list of clients for propagation audit.");
+        return;
+    }
+
+    tokio::spawn(sample_new_content(conn.clone(), config.shutdown.clone()));
+
+    let mut clients = config.portal_clients.iter().cycle();
+    let mut interval = interval(DUE_CHECK_PERIOD);
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = config.shutdown.triggered() => return,
+        }
+        if let Err(e) = audit_due(&mut clients, &config, &conn).await {
+            error!(err=?e, "Could not make due propagation audits.");
+        }
+    }
+}
+
+/// Schedules the audits of a few random keys first available since the previous sample.
+async fn sample_new_content(conn: DatabaseConnection, shutdown: Shutdown) {
+    let mut interval = interval(SAMPLE_PERIOD);
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = shutdown.triggered() => return,
+        }
+        let since = Utc::now() - TimeDelta::seconds(SAMPLE_PERIOD.as_secs() as i64);
+        let candidates =
+            match propagation_audit::get_candidates(since, KEYS_PER_SAMPLE, &conn).await {
+                Ok(candidates) => candidates,
+                Err(e) => {
+                    error!(err=?e, "Could not sample new content for propagation audits.");
+                    continue;
+                }
+            };
+        for content in &candidates {
+            if let Err(e) = propagation_audit::schedule(content, &PROPAGATION_OFFSETS, &conn).await
+            {
+                error!(content.id = content.id, err=?e, "Could not schedule propagation audits.");
+            }
+        }
+        debug!(
+            num_keys = candidates.len(),
+            "Scheduled propagation audits of new content."
+        );
+    }
+}
+
+/// Makes the audits that are due at the same time, taking turns between the Portal clients.
+///
+/// The audits count toward the concurrency shared with the other audits, so only as many are
+/// made as there is capacity for. They are all finished before returning, so that an audit in
+/// progress is not picked up again at the next check.
+async fn audit_due<'a>(
+    clients: &mut impl Iterator<Item = &'a PortalClient>,
+    config: &AuditConfig,
+    conn: &DatabaseConnection,
+) -> Result<()> {
+    let active_count = config.active_threads.load(Ordering::Relaxed);
+    let capacity = config.concurrency.saturating_sub(active_count);
+    if capacity == 0 {
+        debug!(
+            active.threads = active_count,
+            max.threads = config.concurrency,
+            "No capacity for due propagation audits, checking again later."
+        );
+        return Ok(());
+    }
+    let limit = DUE_AUDIT_LIMIT.min(capacity.into());
+    let due = propagation_audit::get_due(Utc::now(), limit, conn).await?;
+
+    let mut audits = vec![];
+    for (scheduled, content) in due {
+        let Some(client) = clients.next() else {
+            break;
+        };
+        let task = AuditTask {
+            strategy: SelectionStrategy::History(HistorySelectionStrategy::Propagation),
+            content,
+            selected_at: Utc::now(),
+        };
+        config.active_threads.fetch_add(1, Ordering::Relaxed);
+        let audit = perform_audit(
+            config.active_threads.clone(),
+            task,
+            client.clone(),
+            config.audit_timeouts.clone(),
+            config.era1.clone(),
+            conn.clone(),
+            config.shutdown.clone(),
+        );
+        audits.push(async move {
+            let audit = audit.await;
+            propagation_audit::complete(scheduled.id, audit.as_ref().map(|audit| audit.id), conn)
+                .await?;
+            info!(
+                offset_seconds = scheduled.offset_seconds,
+                found = audit.is_some_and(|audit| audit.is_success()),
+                "Propagation audit complete."
+            );
+            Ok::<_, anyhow::Error>(())
+        });
+    }
+    join_all(audits).await.into_iter().collect()
+}
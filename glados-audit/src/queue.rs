@@ -0,0 +1,191 @@
+//! Persistent queue of audit tasks, used instead of the in-memory channel with `--task-queue`.
+//!
+//! Selected tasks are written to the `audit_task` table, and audit workers claim them from it.
+//! Tasks left over by a restart are audited once it is back, and any number of processes
+//! started with `--worker-only` can share the audits.
+use std::sync::atomic::Ordering;
+
+use chrono::{TimeDelta, Utc};
+use sea_orm::{DatabaseConnection, EntityTrait};
+use tokio::{
+    sync::mpsc,
+    time::{interval, sleep, Duration},
+};
+use tracing::{debug, error, info, warn};
+
+use entity::{audit_task, audit_worker, content};
+
+use crate::{shutdown::Shutdown, task::perform_audit, AuditConfig, AuditTask};
+
+/// Number of open tasks above which no new tasks are queued.
+const MAX_OPEN_TASKS: u64 = 1000;
+
+/// Time after which a task claimed by a worker that did not complete it can be claimed again.
+const VISIBILITY_TIMEOUT: TimeDelta = TimeDelta::minutes(10);
+
+/// Time a completed task is kept before it is deleted.
+const COMPLETED_RETENTION: TimeDelta = TimeDelta::days(1);
+
+/// Time between deletions of old completed tasks.
+const CLEANUP_PERIOD: Duration = Duration::from_secs(3600);
+
+/// Time between the records that this worker is running, which glados-web checks before
+/// accepting requested audits.
+const HEARTBEAT_PERIOD: Duration = Duration::from_secs(30);
+
+/// Writes the collated tasks to the queue, pausing while it is full.
+pub(crate) async fn enqueue_tasks(mut rx: mpsc::Receiver<AuditTask>, conn: DatabaseConnection) {
+    loop {
+        match audit_task::count_open(&conn).await {
+            Ok(open) if open >= MAX_OPEN_TASKS => {
+                debug!(open, "Audit task queue is full... Sleeping...");
+                sleep(Duration::from_secs(5)).await;
+                continue;
+            }
+            Ok(_) => {}
+            Err(e) => {
+                error!(err=?e, "Could not count open audit tasks.");
+                sleep(Duration::from_secs(5)).await;
+                continue;
+            }
+        }
+        let Some(task) = rx.recv().await else {
+            return;
+        };
+        if let Err(e) = audit_task::enqueue(
+            task.content.id,
+            task.strategy.clone(),
+            task.selected_at,
+            &conn,
+        )
+        .await
+        {
+            error!(content.id = task.content.id, err=?e, "Could not queue audit task.");
+        }
+    }
+}
+
+/// Claims tasks from the queue and audits them, up to the configured concurrency.
+pub(crate) async fn perform_queued_audits(config: AuditConfig, conn: DatabaseConnection) {
+    let worker = worker_name();
+    info!(worker, "Performing audits from the task queue.");
+    tokio::spawn(delete_completed_tasks(conn.clone()));
+    tokio::spawn(heartbeat(
+        worker.clone(),
+        config.shutdown.clone(),
+        conn.clone(),
+    ));
+
+    let concurrency = config.concurrency;
+    let active_threads = config.active_threads.clone();
+    let mut cycle_of_clients = config.portal_clients.iter().cycle();
+    loop {
+        if config.shutdown.is_triggered() {
+            info!(worker, "Shutting down, no more audit tasks are claimed.");
+            return;
+        }
+        let active_count = active_threads.load(Ordering::Relaxed);
+        if active_count >= concurrency {
+            sleep(Duration::from_millis(500)).await;
+            continue;
+        }
+        let tasks = match audit_task::claim(
+            &worker,
+            (concurrency - active_count) as u64,
+            VISIBILITY_TIMEOUT,
+            &conn,
+        )
+        .await
+        {
+            Ok(tasks) => tasks,
+            Err(e) => {
+                error!(err=?e, "Could not claim audit tasks.");
+                sleep(Duration::from_secs(5)).await;
+                continue;
+            }
+        };
+        if tasks.is_empty() {
+            sleep(Duration::from_secs(1)).await;
+            continue;
+        }
+        for task in tasks {
+            let content = match content::Entity::find_by_id(task.content_key)
+                .one(&conn)
+                .await
+            {
+                Ok(Some(content)) => content,
+                Ok(None) => {
+                    warn!(task.id, "Content of the audit task no longer exists.");
+                    continue;
+                }
+                Err(e) => {
+                    error!(task.id, err=?e, "Could not look up content of audit task.");
+                    continue;
+                }
+            };
+            let Some(client) = cycle_of_clients.next() else {
+                error!("Empty list of clients for audit.");
+                return;
+            };
+            active_threads.fetch_add(1, Ordering::Relaxed);
+            let audit = perform_audit(
+                active_threads.clone(),
+                AuditTask {
+                    strategy: task.strategy_used,
+                    content,
+                    selected_at: task.created_at,
+                },
+                client.clone(),
+                config.audit_timeouts.clone(),
+                config.era1.clone(),
+                conn.clone(),
+                config.shutdown.clone(),
+            );
+            let conn = conn.clone();
+            tokio::spawn(async move {
+                // Tasks whose audit was not recorded are claimed again after the timeout.
+                let Some(audit) = audit.await else {
+                    return;
+                };
+                if let Err(e) = audit_task::complete(task.id, audit.id, &conn).await {
+                    error!(task.id, err=?e, "Could not complete audit task.");
+                }
+            });
+        }
+    }
+}
+
+/// Periodically deletes tasks that were completed long ago.
+async fn delete_completed_tasks(conn: DatabaseConnection) {
+    let mut interval = interval(CLEANUP_PERIOD);
+    loop {
+        interval.tick().await;
+        match audit_task::delete_completed(Utc::now() - COMPLETED_RETENTION, &conn).await {
+            Ok(deleted) => debug!(deleted, "Deleted completed audit tasks."),
+            Err(e) => error!(err=?e, "Could not delete completed audit tasks."),
+        }
+    }
+}
+
+/// Periodically records that the worker is running, until shutdown.
+async fn heartbeat(worker: String, shutdown: Shutdown, conn: DatabaseConnection) {
+    let mut interval = interval(HEARTBEAT_PERIOD);
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = shutdown.triggered() => break,
+        }
+        if let Err(e) = audit_worker::heartbeat(&worker, Utc::now(), &conn).await {
+            error!(worker, err=?e, "Could not record audit worker heartbeat.");
+        }
+    }
+    if let Err(e) = audit_worker::remove(&worker, &conn).await {
+        error!(worker, err=?e, "Could not remove audit worker.");
+    }
+}
+
+/// Name identifying this process in the tasks it claims.
+fn worker_name() -> String {
+    let host = std::env::var("HOSTNAME").unwrap_or_else(|_| "glados-audit".to_owned());
+    format!("{host}:{}", std::process::id())
+}
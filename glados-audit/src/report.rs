@@ -0,0 +1,173 @@
+//! Summary of a period of audits and censuses as markdown, for community updates.
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::str::FromStr;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use entity::{
+    census,
+    census_client_stats::{self, UNKNOWN_CLIENT},
+    census_node,
+    content::SubProtocol,
+    content_audit::{self, ContentTypeResults},
+};
+use ethportal_api::types::enr::Enr;
+use sea_orm::DatabaseConnection;
+use tracing::warn;
+
+const SUB_PROTOCOLS: [SubProtocol; 3] = [
+    SubProtocol::History,
+    SubProtocol::State,
+    SubProtocol::Beacon,
+];
+
+/// Prints the report for the audits and censuses started within the time range.
+pub async fn print_report(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    conn: &DatabaseConnection,
+) -> Result<()> {
+    println!("{}", build_report(start, end, conn).await?);
+    Ok(())
+}
+
+async fn build_report(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    conn: &DatabaseConnection,
+) -> Result<String> {
+    let results = content_audit::get_results_by_content_type(start, end, conn).await?;
+
+    let mut report = String::new();
+    writeln!(
+        report,
+        "# Glados report: {} to {}",
+        start.format("%Y-%m-%d %H:%M UTC"),
+        end.format("%Y-%m-%d %H:%M UTC")
+    )?;
+    for sub_protocol in SUB_PROTOCOLS {
+        let results: Vec<&ContentTypeResults> = results
+            .iter()
+            .filter(|results| results.sub_protocol == sub_protocol)
+            .collect();
+        let node_counts = census::get_node_counts(sub_protocol, start, end, conn).await?;
+        if results.is_empty() && node_counts.is_empty() {
+            continue;
+        }
+        writeln!(report, "\n## {} network", sub_protocol.as_text())?;
+        write_audit_results(&mut report, &results)?;
+        write_census_sizes(&mut report, &node_counts)?;
+        if let (Some(first), Some(last)) = (node_counts.first(), node_counts.last()) {
+            let first_shares = client_shares(first.census_id, conn).await?;
+            let last_shares = client_shares(last.census_id, conn).await?;
+            write_client_shares(&mut report, &first_shares, &last_shares)?;
+        }
+    }
+    Ok(report)
+}
+
+fn write_audit_results(report: &mut String, results: &[&ContentTypeResults]) -> Result<()> {
+    writeln!(report, "\n### Audits by content type\n")?;
+    if results.is_empty() {
+        writeln!(report, "No audits.")?;
+        return Ok(());
+    }
+    writeln!(
+        report,
+        "| Content type | Audits | Success | Failure | Errored | Success rate |"
+    )?;
+    writeln!(report, "|---|---:|---:|---:|---:|---:|")?;
+    for results in results {
+        writeln!(
+            report,
+            "| {} | {} | {} | {} | {} | {:.1}% |",
+            content_type_name(results.sub_protocol, results.selector),
+            results.total,
+            results.success,
+            results.failure,
+            results.errored,
+            results.success_percent()
+        )?;
+    }
+    Ok(())
+}
+
+fn write_census_sizes(report: &mut String, node_counts: &[census::CensusNodeCount]) -> Result<()> {
+    writeln!(report, "\n### Census size\n")?;
+    let counts: Vec<i64> = node_counts.iter().map(|c| c.node_count).collect();
+    let (Some(min), Some(max)) = (counts.iter().min(), counts.iter().max()) else {
+        writeln!(report, "No censuses.")?;
+        return Ok(());
+    };
+    let average = counts.iter().sum::<i64>() as f64 / counts.len() as f64;
+    writeln!(
+        report,
+        "{} censuses found between {min} and {max} nodes, {average:.1} on average.",
+        counts.len()
+    )?;
+    Ok(())
+}
+
+fn write_client_shares(
+    report: &mut String,
+    first: &HashMap<String, f64>,
+    last: &HashMap<String, f64>,
+) -> Result<()> {
+    writeln!(report, "\n### Client share\n")?;
+    writeln!(report, "| Client | First census | Last census | Change |")?;
+    writeln!(report, "|---|---:|---:|---:|")?;
+    let mut clients: Vec<&String> = first.keys().chain(last.keys()).collect();
+    clients.sort();
+    clients.dedup();
+    for client in clients {
+        let before = first.get(client).copied().unwrap_or(0.0);
+        let after = last.get(client).copied().unwrap_or(0.0);
+        writeln!(
+            report,
+            "| {client} | {before:.1}% | {after:.1}% | {:+.1} pp |",
+            after - before
+        )?;
+    }
+    Ok(())
+}
+
+/// Percentage of the nodes found by a census that run each client.
+async fn client_shares(census_id: i32, conn: &DatabaseConnection) -> Result<HashMap<String, f64>> {
+    let enrs = census_node::get_enrs(census_id, conn).await?;
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for raw in &enrs {
+        let client = match Enr::from_str(raw) {
+            Ok(enr) => census_client_stats::enr_client(&enr).unwrap_or(UNKNOWN_CLIENT),
+            Err(err) => {
+                warn!(census.id = census_id, err=?err, "Could not decode census ENR");
+                UNKNOWN_CLIENT
+            }
+        };
+        *counts.entry(client.to_owned()).or_default() += 1;
+    }
+    Ok(counts
+        .into_iter()
+        .map(|(client, count)| (client, 100.0 * count as f64 / enrs.len() as f64))
+        .collect())
+}
+
+/// Name of a type of content, from the first byte of its keys.
+fn content_type_name(sub_protocol: SubProtocol, selector: i32) -> String {
+    let name = match (sub_protocol, selector) {
+        (SubProtocol::History, 0x00) => "Block headers by hash",
+        (SubProtocol::History, 0x01) => "Block bodies",
+        (SubProtocol::History, 0x02) => "Receipts",
+        (SubProtocol::History, 0x03) => "Block headers by number",
+        (SubProtocol::State, 0x20) => "Account trie nodes",
+        (SubProtocol::State, 0x21) => "Contract storage trie nodes",
+        (SubProtocol::State, 0x22) => "Contract bytecode",
+        (SubProtocol::Beacon, 0x10) => "Light client bootstraps",
+        (SubProtocol::Beacon, 0x11) => "Light client updates by range",
+        (SubProtocol::Beacon, 0x12) => "Light client finality updates",
+        (SubProtocol::Beacon, 0x13) => "Light client optimistic updates",
+        (SubProtocol::Beacon, 0x14) => "Historical summaries",
+        _ => return format!("Content type {selector:#04x}"),
+    };
+    name.to_owned()
+}
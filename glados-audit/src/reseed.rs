@@ -0,0 +1,344 @@
+//! Re-seeds history content that failed its audit, by offering it back to the network.
+//!
+//! Bodies and receipts of failing keys are rebuilt from the raw block data of the execution
+//! layer provider, checked against the block header, and offered by the first Portal client
+//! with `portal_historyPutContent`. Each attempt is recorded in `reseed_attempt`, so that later
+//! audits of the key show whether the reseed stuck. Headers are not reseeded, as their proofs
+//! cannot be built from the provider.
+//!
+//! The provider must support the `debug_getRawBlock`, `debug_getRawHeader` and
+//! `debug_getRawReceipts` methods.
+use alloy_rlp::{Decodable, Header as RlpHeader};
+use anyhow::{anyhow, ensure, Result};
+use chrono::{TimeDelta, Utc};
+use ethportal_api::{
+    types::execution::header::Header,
+    utils::bytes::{hex_decode, hex_encode},
+    ContentValue, HistoryContentKey, HistoryContentValue, OverlayContentKey,
+};
+use sea_orm::DatabaseConnection;
+use serde_json::json;
+use tokio::time::{interval, Duration};
+use tracing::{error, info, warn};
+use web3::Transport;
+
+use entity::reseed_attempt::{self, ReseedOutcome};
+use glados_core::jsonrpc::PortalClient;
+
+use crate::{validation::matches_header, AuditConfig};
+
+/// Time between looking for failed content to reseed.
+const RESEED_PERIOD: Duration = Duration::from_secs(60);
+
+/// Time after a reseed before the same key can be reseeded again, if it keeps failing.
+const RESEED_COOLDOWN: TimeDelta = TimeDelta::days(1);
+
+/// Number of failed keys reseeded each period.
+const RESEED_BATCH_SIZE: u64 = 10;
+
+pub async fn run_reseed(conn: DatabaseConnection, config: AuditConfig) {
+    let w3 = match web3::transports::Http::new(&config.provider_url) {
+        Ok(transport) => web3::Web3::new(transport),
+        Err(e) => {
+            error!(err=?e, "Could not connect to provider for reseeding.");
+            return;
+        }
+    };
+    let Some(client) = config.portal_clients.first() else {
+        error!("Empty list of clients for reseeding.");
+        return;
+    };
+
+    let mut interval = interval(RESEED_PERIOD);
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = config.shutdown.triggered() => return,
+        }
+        if let Err(e) = reseed_failed_content(client, &w3, &conn).await {
+            error!(err=?e, "Could not reseed failed content.");
+        }
+    }
+}
+
+/// Reseeds the most recently failed keys that have not been reseeded within the cooldown.
+async fn reseed_failed_content(
+    client: &PortalClient,
+    w3: &web3::Web3<web3::transports::Http>,
+    conn: &DatabaseConnection,
+) -> Result<()> {
+    let since = Utc::now() - RESEED_COOLDOWN;
+    for candidate in reseed_attempt::get_candidates(since, RESEED_BATCH_SIZE, conn).await? {
+        let content_key = HistoryContentKey::try_from_bytes(&candidate.content_key)?;
+        let attempt = reseed(&content_key, client, w3).await;
+        match attempt.outcome {
+            ReseedOutcome::Offered => info!(
+                content.key = content_key.to_hex(),
+                peer_count = ?attempt.peer_count,
+                "Reseeded failed content."
+            ),
+            ReseedOutcome::Unsupported => {}
+            outcome => warn!(
+                content.key = content_key.to_hex(),
+                outcome = outcome.as_text(),
+                err = ?attempt.error,
+                "Could not reseed failed content."
+            ),
+        }
+        reseed_attempt::create(
+            candidate.content_id,
+            Some(candidate.audit_id),
+            Utc::now(),
+            attempt.outcome,
+            attempt.peer_count,
+            attempt.stored_locally,
+            attempt.error,
+            conn,
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// How a reseed went, before it is recorded.
+struct Attempt {
+    outcome: ReseedOutcome,
+    peer_count: Option<i32>,
+    stored_locally: Option<bool>,
+    error: Option<String>,
+}
+
+impl Attempt {
+    fn not_offered(outcome: ReseedOutcome, error: Option<String>) -> Self {
+        Attempt {
+            outcome,
+            peer_count: None,
+            stored_locally: None,
+            error,
+        }
+    }
+}
+
+/// Rebuilds the content from the provider and offers it to the network.
+async fn reseed(
+    content_key: &HistoryContentKey,
+    client: &PortalClient,
+    w3: &web3::Web3<web3::transports::Http>,
+) -> Attempt {
+    let value = match build_content_value(content_key, w3).await {
+        Ok(Some(value)) => value,
+        Ok(None) => return Attempt::not_offered(ReseedOutcome::Unsupported, None),
+        Err(e) => {
+            return Attempt::not_offered(ReseedOutcome::SourceError, Some(e.to_string()));
+        }
+    };
+    match client
+        .api
+        .put_history_content(content_key.clone(), value)
+        .await
+    {
+        Ok(info) => Attempt {
+            outcome: match info.peer_count {
+                0 => ReseedOutcome::NoPeers,
+                _ => ReseedOutcome::Offered,
+            },
+            peer_count: Some(info.peer_count.try_into().unwrap_or(i32::MAX)),
+            stored_locally: Some(info.stored_locally),
+            error: None,
+        },
+        Err(e) => Attempt::not_offered(ReseedOutcome::RpcError, Some(e.to_string())),
+    }
+}
+
+/// Builds the content value of a block body or receipts key from the provider, or returns None
+/// for content that cannot be rebuilt.
+///
+/// The value is checked against the roots of the block header, so that content the provider
+/// got wrong is never offered.
+async fn build_content_value(
+    content_key: &HistoryContentKey,
+    w3: &web3::Web3<web3::transports::Http>,
+) -> Result<Option<Vec<u8>>> {
+    let (value, header) = match content_key {
+        HistoryContentKey::BlockBody(key) => {
+            let raw_block = get_raw(w3, "debug_getRawBlock", hex_encode(key.block_hash)).await?;
+            let block = rlp_list_items(&raw_block)?;
+            let header = block.first().ok_or(anyhow!("raw block has no header"))?;
+            (encode_block_body(&block)?, header.encoded.to_vec())
+        }
+        HistoryContentKey::BlockReceipts(key) => {
+            let block_hash = hex_encode(key.block_hash);
+            let response = w3
+                .transport()
+                .execute("debug_getRawReceipts", vec![json!(block_hash)])
+                .await?;
+            let receipts = serde_json::from_value::<Vec<String>>(response)?
+                .iter()
+                .map(|receipt| hex_decode(receipt))
+                .collect::<Result<Vec<_>, _>>()?;
+            let receipts: Vec<&[u8]> = receipts.iter().map(Vec::as_slice).collect();
+            let header = get_raw(w3, "debug_getRawHeader", block_hash).await?;
+            (ssz_encode_variable(&receipts), header)
+        }
+        _ => return Ok(None),
+    };
+
+    let header = Header::decode(&mut header.as_slice())
+        .map_err(|e| anyhow!("could not decode header from provider: {e:?}"))?;
+    let content = HistoryContentValue::decode(content_key, &value)
+        .map_err(|e| anyhow!("could not decode rebuilt content: {e:?}"))?;
+    ensure!(
+        matches_header(&content, &header)?,
+        "rebuilt content does not match the block header"
+    );
+    Ok(Some(value))
+}
+
+/// Calls a provider method that returns hex-encoded bytes for a block hash.
+async fn get_raw(
+    w3: &web3::Web3<web3::transports::Http>,
+    method: &str,
+    block_hash: String,
+) -> Result<Vec<u8>> {
+    let response = w3
+        .transport()
+        .execute(method, vec![json!(block_hash)])
+        .await?;
+    Ok(hex_decode(&serde_json::from_value::<String>(response)?)?)
+}
+
+/// An item of an RLP list.
+struct RlpItem<'a> {
+    /// The whole encoding of the item, with its RLP header.
+    encoded: &'a [u8],
+    /// The item without its RLP header.
+    payload: &'a [u8],
+    list: bool,
+}
+
+/// Splits the RLP encoding of a list into its items.
+fn rlp_list_items(mut encoded: &[u8]) -> Result<Vec<RlpItem<'_>>> {
+    let header = RlpHeader::decode(&mut encoded).map_err(|e| anyhow!("invalid RLP: {e:?}"))?;
+    ensure!(header.list, "expected an RLP list");
+    ensure!(
+        header.payload_length == encoded.len(),
+        "RLP list length does not match its encoding"
+    );
+
+    let mut items = vec![];
+    while !encoded.is_empty() {
+        let start = encoded;
+        let header = RlpHeader::decode(&mut encoded).map_err(|e| anyhow!("invalid RLP: {e:?}"))?;
+        ensure!(
+            header.payload_length <= encoded.len(),
+            "RLP item longer than its list"
+        );
+        let header_length = start.len() - encoded.len();
+        items.push(RlpItem {
+            encoded: &start[..header_length + header.payload_length],
+            payload: &encoded[..header.payload_length],
+            list: header.list,
+        });
+        encoded = &encoded[header.payload_length..];
+    }
+    Ok(items)
+}
+
+/// Encodes the body of a raw block (header, transactions, uncles, and withdrawals after
+/// Shanghai) as a Portal block body.
+///
+/// Portal stores transactions in their EIP-2718 encoding, which for typed transactions is the
+/// payload of the RLP string wrapping them in the block.
+fn encode_block_body(block: &[RlpItem]) -> Result<Vec<u8>> {
+    let [_, transactions, uncles, rest @ ..] = block else {
+        return Err(anyhow!("raw block has {} fields", block.len()));
+    };
+    let transactions: Vec<&[u8]> = rlp_list_items(transactions.encoded)?
+        .iter()
+        .map(|tx| if tx.list { tx.encoded } else { tx.payload })
+        .collect();
+    let transactions = ssz_encode_variable(&transactions);
+    match rest {
+        [] => Ok(ssz_encode_variable(&[&transactions, uncles.encoded])),
+        [withdrawals] => {
+            let withdrawals: Vec<&[u8]> = rlp_list_items(withdrawals.encoded)?
+                .iter()
+                .map(|withdrawal| withdrawal.encoded)
+                .collect();
+            Ok(ssz_encode_variable(&[
+                &transactions,
+                uncles.encoded,
+                &ssz_encode_variable(&withdrawals),
+            ]))
+        }
+        _ => Err(anyhow!("raw block has {} fields", block.len())),
+    }
+}
+
+/// SSZ encoding of variable length values: their offsets, then the values.
+///
+/// This is both a list of byte lists, and a container whose fields are all variable length.
+fn ssz_encode_variable(values: &[&[u8]]) -> Vec<u8> {
+    let mut offset = 4 * values.len();
+    let mut encoded = Vec::with_capacity(offset + values.iter().map(|v| v.len()).sum::<usize>());
+    for value in values {
+        encoded.extend_from_slice(&(offset as u32).to_le_bytes());
+        offset += value.len();
+    }
+    for value in values {
+        encoded.extend_from_slice(value);
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ssz_encode_variable() {
+        assert_eq!(ssz_encode_variable(&[]), Vec::<u8>::new());
+        assert_eq!(
+            ssz_encode_variable(&[&[0xaa], &[], &[0xbb, 0xcc]]),
+            vec![12, 0, 0, 0, 13, 0, 0, 0, 13, 0, 0, 0, 0xaa, 0xbb, 0xcc]
+        );
+    }
+
+    #[test]
+    fn test_rlp_list_items() {
+        // [0x05, "dog", [0x01]]
+        let encoded = [0xc7, 0x05, 0x83, b'd', b'o', b'g', 0xc1, 0x01];
+        let items = rlp_list_items(&encoded).unwrap();
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].encoded, [0x05]);
+        assert_eq!(items[0].payload, [0x05]);
+        assert_eq!(items[1].encoded, [0x83, b'd', b'o', b'g']);
+        assert_eq!(items[1].payload, b"dog");
+        assert!(!items[1].list);
+        assert_eq!(items[2].encoded, [0xc1, 0x01]);
+        assert!(items[2].list);
+
+        assert!(rlp_list_items(&[0x83, b'd', b'o', b'g']).is_err());
+        assert!(rlp_list_items(&[0xc3, 0x83, b'd']).is_err());
+    }
+
+    #[test]
+    fn test_encode_block_body() {
+        // A legacy transaction, a typed transaction wrapped in an RLP string, and no uncles.
+        let block = [
+            0xcc, 0xc0, 0xc6, 0xc1, 0x01, 0x83, 0x02, 0xc1, 0x03, 0xc0, 0xc2, 0xc1, 0x04,
+        ];
+        let items = rlp_list_items(&block).unwrap();
+        let transactions = ssz_encode_variable(&[&[0xc1, 0x01], &[0x02, 0xc1, 0x03]]);
+        let withdrawals = ssz_encode_variable(&[&[0xc1, 0x04]]);
+        assert_eq!(
+            encode_block_body(&items).unwrap(),
+            ssz_encode_variable(&[&transactions, &[0xc0], &withdrawals])
+        );
+        assert_eq!(
+            encode_block_body(&items[..3]).unwrap(),
+            ssz_encode_variable(&[&transactions, &[0xc0]])
+        );
+        assert!(encode_block_body(&items[..2]).is_err());
+    }
+}
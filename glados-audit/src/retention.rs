@@ -0,0 +1,122 @@
+//! Deletion of old audits and content, so that their tables do not grow without bound.
+//!
+//! Rows are deleted in batches, so that no single statement holds locks on the tables for long.
+//! Stats recorded from the audits, like `audit_stats`, are kept.
+use std::future::Future;
+
+use anyhow::Result;
+use chrono::{TimeDelta, Utc};
+use sea_orm::DatabaseConnection;
+use tokio::time::{interval, sleep, Duration};
+use tracing::{debug, error, info};
+
+use entity::{content, content_audit};
+
+/// Time between applications of the retention policy.
+const RETENTION_PERIOD: Duration = Duration::from_secs(3600);
+
+/// Number of rows deleted by each statement.
+const BATCH_SIZE: u64 = 10_000;
+
+/// Pause between batches, to leave room for the audits' own queries.
+const BATCH_PAUSE: Duration = Duration::from_millis(100);
+
+/// How long audits and content are kept. Nothing is deleted without a retention.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RetentionPolicy {
+    /// Audits started longer ago are deleted, except the latest audit of each content key.
+    pub audit_retention: Option<TimeDelta>,
+    /// Content first available longer ago is deleted, with its audits.
+    pub content_retention: Option<TimeDelta>,
+}
+
+impl RetentionPolicy {
+    pub fn is_enabled(&self) -> bool {
+        self.audit_retention.is_some() || self.content_retention.is_some()
+    }
+}
+
+/// Loops indefinitely, periodically deleting the audits and content older than the policy
+/// keeps.
+pub async fn periodically_apply_retention(policy: RetentionPolicy, conn: DatabaseConnection) -> ! {
+    debug!(
+        ?policy,
+        "initializing task for deleting old audits and content"
+    );
+    let mut interval = interval(RETENTION_PERIOD);
+    loop {
+        interval.tick().await;
+        if let Err(e) = apply_retention(&policy, &conn).await {
+            error!(err=?e, "Could not delete old audits and content.");
+        }
+    }
+}
+
+async fn apply_retention(policy: &RetentionPolicy, conn: &DatabaseConnection) -> Result<()> {
+    // Content goes first, as its audits are deleted with it.
+    if let Some(retention) = policy.content_retention {
+        let before = Utc::now() - retention;
+        let deleted = delete_in_batches("content keys", || {
+            content::delete_old_batch(before, BATCH_SIZE, conn)
+        })
+        .await?;
+        info!(deleted, %before, "Deleted content first available before the retention.");
+    }
+    if let Some(retention) = policy.audit_retention {
+        let before = Utc::now() - retention;
+        let deleted = delete_in_batches("audits", || {
+            content_audit::delete_old_batch(before, BATCH_SIZE, conn)
+        })
+        .await?;
+        info!(deleted, %before, "Deleted audits started before the retention.");
+    }
+    Ok(())
+}
+
+/// Deletes batches until one is not full, logging the progress. Returns the number of rows
+/// deleted.
+async fn delete_in_batches<F, Fut>(what: &str, delete_batch: F) -> Result<u64>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<u64>>,
+{
+    let mut total = 0;
+    loop {
+        let deleted = delete_batch().await?;
+        total += deleted;
+        if deleted < BATCH_SIZE {
+            return Ok(total);
+        }
+        info!(deleted = total, "Deleting old {what}...");
+        sleep(BATCH_PAUSE).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn deletes_until_a_batch_is_not_full() {
+        let batches = Mutex::new(vec![3, BATCH_SIZE, BATCH_SIZE]);
+        let deleted = delete_in_batches("rows", || async {
+            Ok(batches.lock().unwrap().pop().unwrap())
+        })
+        .await
+        .unwrap();
+        assert_eq!(deleted, 2 * BATCH_SIZE + 3);
+        assert!(batches.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn policy_without_retention_is_disabled() {
+        assert!(!RetentionPolicy::default().is_enabled());
+        assert!(RetentionPolicy {
+            audit_retention: Some(TimeDelta::days(90)),
+            content_retention: None,
+        }
+        .is_enabled());
+    }
+}
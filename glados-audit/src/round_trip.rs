@@ -0,0 +1,146 @@
+//! Gossips content and then audits it until it is found ([HistorySelectionStrategy::RoundTrip]).
+//!
+//! The first Portal client is the source: content it already stores is gossiped from it, the
+//! way a bridge injects new content. The other clients take turns auditing the content until it
+//! is found or the round trip times out, measuring the whole path from injection to
+//! availability.
+//!
+//! [HistorySelectionStrategy::RoundTrip]: entity::content_audit::HistorySelectionStrategy::RoundTrip
+use std::sync::{atomic::AtomicU8, Arc};
+
+use anyhow::Result;
+use chrono::Utc;
+use ethportal_api::{HistoryContentKey, OverlayContentKey};
+use sea_orm::DatabaseConnection;
+use tokio::time::{interval, sleep, Duration, Instant};
+use tracing::{error, info, warn};
+
+use entity::{
+    content,
+    content_audit::{HistorySelectionStrategy, SelectionStrategy},
+    round_trip_audit,
+};
+use glados_core::jsonrpc::PortalClient;
+
+use crate::{task::perform_audit, AuditConfig, AuditTask};
+
+/// Time between the start of consecutive round trips.
+const ROUND_TRIP_PERIOD: Duration = Duration::from_secs(60);
+
+/// Time between audits of the gossiped content, starting from the gossip.
+const ROUND_TRIP_RETRY_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Time after the gossip when the content is given up on.
+const ROUND_TRIP_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Number of the newest keys checked for content stored by the source node.
+const CANDIDATE_LIMIT: u64 = 20;
+
+pub async fn run_round_trip_audit(conn: DatabaseConnection, config: AuditConfig) {
+    let Some((source, auditors)) = config.portal_clients.split_first() else {
+        error!("Empty list of clients for round trip audit.");
+        return;
+    };
+    if auditors.is_empty() {
+        error!("Round trip audits need a second Portal client to audit from.");
+        return;
+    }
+
+    let mut auditors = auditors.iter().cycle();
+    let mut interval = interval(ROUND_TRIP_PERIOD);
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = config.shutdown.triggered() => return,
+        }
+        let Some(auditor) = auditors.next() else {
+            continue;
+        };
+        if let Err(e) = round_trip(source, auditor, &config, &conn).await {
+            error!(err=?e, "Could not complete round trip audit.");
+        }
+    }
+}
+
+/// Gossips content from the source node, then audits it from the auditor until it is found.
+async fn round_trip(
+    source: &PortalClient,
+    auditor: &PortalClient,
+    config: &AuditConfig,
+    conn: &DatabaseConnection,
+) -> Result<()> {
+    let Some((content, value)) = find_source_content(source, conn).await? else {
+        info!("No new content stored by the source node to round trip.");
+        return Ok(());
+    };
+    let content_key = HistoryContentKey::try_from_bytes(&content.content_key)?;
+    let offered_at = Utc::now();
+    let num_peers = source
+        .api
+        .gossip_history_content(content_key.clone(), value)
+        .await?;
+
+    let deadline = Instant::now() + ROUND_TRIP_TIMEOUT;
+    let mut num_attempts = 0;
+    let found = loop {
+        sleep(ROUND_TRIP_RETRY_INTERVAL).await;
+        num_attempts += 1;
+        let task = AuditTask {
+            strategy: SelectionStrategy::History(HistorySelectionStrategy::RoundTrip),
+            content: content.clone(),
+            selected_at: Utc::now(),
+        };
+        let audit = perform_audit(
+            Arc::new(AtomicU8::new(1)),
+            task,
+            auditor.clone(),
+            config.audit_timeouts.clone(),
+            config.era1.clone(),
+            conn.clone(),
+            config.shutdown.clone(),
+        )
+        .await;
+        if let Some(audit) = audit.filter(|audit| audit.is_success()) {
+            break Some((audit.id, Utc::now()));
+        }
+        if Instant::now() >= deadline {
+            break None;
+        }
+    };
+
+    let round_trip = round_trip_audit::create(
+        content.id,
+        offered_at,
+        num_peers as i32,
+        num_attempts,
+        found.map(|(audit_id, _)| audit_id),
+        found.map(|(_, available_at)| available_at),
+        conn,
+    )
+    .await?;
+    match round_trip.latency_ms() {
+        Some(latency_ms) => info!(
+            content.key = content_key.to_hex(),
+            num_peers, num_attempts, latency_ms, "Round trip audit complete."
+        ),
+        None => warn!(
+            content.key = content_key.to_hex(),
+            num_peers, num_attempts, "Gossiped content was not found before the timeout."
+        ),
+    }
+    Ok(())
+}
+
+/// Finds the newest content not yet round tripped that the source node stores, with its value.
+async fn find_source_content(
+    source: &PortalClient,
+    conn: &DatabaseConnection,
+) -> Result<Option<(content::Model, Vec<u8>)>> {
+    for content in round_trip_audit::get_candidates(CANDIDATE_LIMIT, conn).await? {
+        let content_key = HistoryContentKey::try_from_bytes(&content.content_key)?;
+        if let Some(value) = source.api.get_local_history_content(content_key).await? {
+            return Ok(Some((content, value.raw)));
+        }
+    }
+    Ok(None)
+}
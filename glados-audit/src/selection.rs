@@ -1,28 +1,29 @@
 use std::collections::HashSet;
 
-use chrono::{DateTime, TimeZone, Utc};
+use chrono::{DateTime, TimeDelta, TimeZone, Utc};
 use glados_core::db::store_block_keys;
 use migration::{Alias, Expr, Query};
 use rand::{thread_rng, Rng};
 use sea_orm::{
-    ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, EntityTrait, FromQueryResult,
-    QueryFilter, QueryOrder, QuerySelect, Statement, Value,
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, DbErr, EntityTrait,
+    FromQueryResult, QueryFilter, QueryOrder, QuerySelect, Statement, Value,
 };
 use tokio::{
     sync::mpsc,
     time::{interval, Duration},
 };
-use tracing::{debug, error, warn};
+use tracing::{debug, error};
 
 use entity::{
-    content::{self, SubProtocol},
+    audit_latest,
+    content::{self, ContentSource, SubProtocol},
     content_audit::{self, BeaconSelectionStrategy, HistorySelectionStrategy, SelectionStrategy},
 };
 use web3::types::{BlockId, BlockNumber};
 
-use crate::{AuditConfig, AuditTask};
+use crate::{shutdown::Shutdown, AuditConfig, AuditTask};
 
-pub const MERGE_BLOCK_HEIGHT: i32 = 15537393;
+pub use glados_core::history::MERGE_BLOCK_HEIGHT;
 
 pub async fn start_audit_selection_task(
     strategy: SelectionStrategy,
@@ -33,25 +34,46 @@ pub async fn start_audit_selection_task(
     match &strategy {
         SelectionStrategy::History(HistorySelectionStrategy::Latest)
         | SelectionStrategy::Beacon(BeaconSelectionStrategy::Latest) => {
-            select_latest_content_for_audit(tx, conn, strategy).await
+            select_latest_content_for_audit(tx, conn, strategy, config.shutdown).await
         }
         SelectionStrategy::History(HistorySelectionStrategy::Random) => {
-            select_random_content_for_audit(tx, conn).await
+            select_random_content_for_audit(tx, conn, config.shutdown).await
+        }
+        SelectionStrategy::History(HistorySelectionStrategy::RandomUnaudited) => {
+            select_random_unaudited_content_for_audit(
+                tx,
+                conn,
+                config.unaudited_days,
+                config.shutdown,
+            )
+            .await
         }
         SelectionStrategy::History(HistorySelectionStrategy::FourFours) => {
             // Fourfours strategy downloads its own keys rather than waiting on glados-monitor to put them in the DB.
             let w3 = web3::Web3::new(web3::transports::Http::new(&config.provider_url).unwrap());
-            select_fourfours_content_for_audit(tx, conn, w3).await
+            select_fourfours_content_for_audit(tx, conn, w3, config.shutdown).await
         }
         SelectionStrategy::History(HistorySelectionStrategy::Failed) => {
-            warn!("Need to implement SelectionStrategy::Failed")
+            select_failed_content_for_audit(tx, conn, config.shutdown).await
         }
         SelectionStrategy::History(HistorySelectionStrategy::SelectOldestUnaudited) => {
-            select_oldest_unaudited_content_for_audit(tx, conn).await
+            select_oldest_unaudited_content_for_audit(tx, conn, config.shutdown).await
         }
         SelectionStrategy::History(HistorySelectionStrategy::SpecificContentKey) => {
             error!("SpecificContentKey is not a valid audit strategy")
         }
+        SelectionStrategy::History(HistorySelectionStrategy::Sync) => {
+            error!("Sync audits are run by the sync task, not a selection task")
+        }
+        SelectionStrategy::History(HistorySelectionStrategy::Block) => {
+            error!("Block audits are run by the block task, not a selection task")
+        }
+        SelectionStrategy::History(HistorySelectionStrategy::RoundTrip) => {
+            error!("Round trip audits are run by the round trip task, not a selection task")
+        }
+        SelectionStrategy::History(HistorySelectionStrategy::Propagation) => {
+            error!("Propagation audits are run by the propagation task, not a selection task")
+        }
         _ => {
             error!("Strategy not implemented")
         }
@@ -71,7 +93,8 @@ async fn select_latest_content_for_audit(
     tx: mpsc::Sender<AuditTask>,
     conn: DatabaseConnection,
     strategy: SelectionStrategy,
-) -> ! {
+    shutdown: Shutdown,
+) {
     debug!("initializing audit process for 'latest' strategy");
     let mut interval = interval(Duration::from_secs(10));
     let protocol_id = match &strategy {
@@ -80,7 +103,10 @@ async fn select_latest_content_for_audit(
         SelectionStrategy::State(_) => SubProtocol::State as i32,
     };
     loop {
-        interval.tick().await;
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = shutdown.triggered() => return,
+        }
         if tx.is_closed() {
             error!("Channel is closed.");
             panic!();
@@ -152,11 +178,15 @@ async fn select_fourfours_content_for_audit(
     tx: mpsc::Sender<AuditTask>,
     conn: DatabaseConnection,
     w3: web3::Web3<web3::transports::Http>,
-) -> ! {
+    shutdown: Shutdown,
+) {
     let mut interval = interval(Duration::from_secs(5));
 
     loop {
-        interval.tick().await;
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = shutdown.triggered() => return,
+        }
         let block_number = thread_rng().gen_range(1..MERGE_BLOCK_HEIGHT);
         debug!(
             strategy = "4444s",
@@ -189,13 +219,17 @@ async fn select_fourfours_content_for_audit(
             }
         };
 
-        let items_to_audit = store_block_keys(
+        let items_to_audit: Vec<content::Model> = store_block_keys(
             block_number,
             block_hash.as_fixed_bytes(),
             block_timestamp,
+            ContentSource::Audit,
             &conn,
         )
-        .await;
+        .await
+        .into_iter()
+        .map(|(_, content)| content)
+        .collect();
         debug!(
             strategy = "4444s",
             item_count = items_to_audit.len(),
@@ -228,6 +262,7 @@ async fn add_to_queue(
         let task = AuditTask {
             strategy: strategy.clone(),
             content: content_key_model,
+            selected_at: Utc::now(),
         };
         if let Err(e) = tx.send(task).await {
             error!(audit.strategy=?strategy, err=?e, "Could not send key for audit, channel might be full or closed.")
@@ -247,25 +282,19 @@ async fn add_to_queue(
 async fn select_random_content_for_audit(
     tx: mpsc::Sender<AuditTask>,
     conn: DatabaseConnection,
-) -> ! {
+    shutdown: Shutdown,
+) {
     debug!("initializing audit process for 'random' strategy");
 
     let mut interval = interval(Duration::from_secs(10));
     loop {
-        interval.tick().await;
-
-        let max_content_id = match MaxContentId::find_by_statement(
-            conn.get_database_backend().build(
-                &Query::select()
-                    .from(content::Entity)
-                    .expr_as(Expr::max(Expr::col(content::Column::Id)), Alias::new("id"))
-                    .take(),
-            ),
-        )
-        .one(&conn)
-        .await
-        {
-            Ok(Some(value)) => value.id,
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = shutdown.triggered() => return,
+        }
+
+        let max_content_id = match get_max_content_id(&conn).await {
+            Ok(Some(value)) => value,
             Ok(None) => {
                 error!("Could not find max content id");
                 continue;
@@ -318,6 +347,107 @@ pub struct MaxContentId {
     pub id: i32,
 }
 
+/// Returns the highest content id, used by the random strategies to draw ids.
+async fn get_max_content_id(conn: &DatabaseConnection) -> Result<Option<i32>, DbErr> {
+    Ok(MaxContentId::find_by_statement(
+        conn.get_database_backend().build(
+            &Query::select()
+                .from(content::Entity)
+                .expr_as(Expr::max(Expr::col(content::Column::Id)), Alias::new("id"))
+                .take(),
+        ),
+    )
+    .one(conn)
+    .await?
+    .map(|value| value.id))
+}
+
+/// Number of random ids drawn for each key required by [HistorySelectionStrategy::RandomUnaudited],
+/// since many of them belong to recently audited keys.
+const RANDOM_UNAUDITED_OVERSAMPLING: usize = 4;
+
+/// Finds and sends audit tasks for [HistorySelectionStrategy::RandomUnaudited].
+///
+/// Strategy achieved by:
+/// 1. Generating several random ids for each key required.
+/// 2. Looking up the history keys with those ids, leaving out those whose latest audit in the
+///    audit_latest table is less than `unaudited_days` days old.
+/// 3. Sending them all in the channel.
+///
+/// At regular intervals the channel capacity is assessed and new tasks are added to reach capacity.
+async fn select_random_unaudited_content_for_audit(
+    tx: mpsc::Sender<AuditTask>,
+    conn: DatabaseConnection,
+    unaudited_days: u32,
+    shutdown: Shutdown,
+) {
+    debug!("initializing audit process for 'random unaudited' strategy");
+
+    let mut interval = interval(Duration::from_secs(10));
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = shutdown.triggered() => return,
+        }
+
+        let max_content_id = match get_max_content_id(&conn).await {
+            Ok(Some(value)) => value,
+            Ok(None) => {
+                error!("Could not find max content id");
+                continue;
+            }
+            Err(err) => {
+                error!(audit.strategy="random unaudited", err=?err, "Could not make audit query");
+                continue;
+            }
+        };
+
+        let keys_required = tx.capacity();
+        if keys_required == 0 {
+            continue;
+        };
+        let mut random_ids: HashSet<u32> = HashSet::new();
+        {
+            // Thread safe block for the rng, which is not `Send`.
+            let mut rng = thread_rng();
+            for _ in 0..keys_required * RANDOM_UNAUDITED_OVERSAMPLING {
+                random_ids.insert(rng.gen_range(0..=max_content_id as u32));
+            }
+        }
+        let audited_since = Utc::now() - TimeDelta::days(unaudited_days.into());
+        let recently_audited = Query::select()
+            .column(audit_latest::Column::ContentKey)
+            .from(audit_latest::Entity)
+            .and_where(audit_latest::Column::CreatedAt.gt(audited_since))
+            .to_owned();
+        let content_key_db_entries = match content::Entity::find()
+            .filter(content::Column::Id.is_in(random_ids))
+            .filter(content::Column::ProtocolId.eq(SubProtocol::History))
+            .filter(content::Column::Id.not_in_subquery(recently_audited))
+            .limit(keys_required as u64)
+            .all(&conn)
+            .await
+        {
+            Ok(found) => found,
+            Err(err) => {
+                error!(audit.strategy="random unaudited", err=?err, "Could not make audit query");
+                continue;
+            }
+        };
+        let item_count = content_key_db_entries.len();
+        debug!(
+            strategy = "random unaudited",
+            item_count, "Adding content keys to the audit queue."
+        );
+        add_to_queue(
+            tx.clone(),
+            SelectionStrategy::History(HistorySelectionStrategy::RandomUnaudited),
+            content_key_db_entries,
+        )
+        .await;
+    }
+}
+
 /// Finds and sends audit tasks for [SelectionStrategy::SelectOldestUnaudited].
 ///
 /// Strategy achieved by:
@@ -329,6 +459,7 @@ pub struct MaxContentId {
 async fn select_oldest_unaudited_content_for_audit(
     tx: mpsc::Sender<AuditTask>,
     conn: DatabaseConnection,
+    shutdown: Shutdown,
 ) {
     debug!("initializing audit process for 'select oldest unaudited' strategy");
     let mut interval = interval(Duration::from_secs(10));
@@ -343,7 +474,10 @@ async fn select_oldest_unaudited_content_for_audit(
     };
 
     loop {
-        interval.tick().await;
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = shutdown.triggered() => return,
+        }
         if tx.is_closed() {
             error!("Channel is closed.");
             panic!();
@@ -393,6 +527,84 @@ async fn select_oldest_unaudited_content_for_audit(
     }
 }
 
+/// Finds and sends audit tasks for [HistorySelectionStrategy::Failed], to detect when missing
+/// content becomes available.
+///
+/// Strategy achieved by:
+/// 1. Selecting keys that have failed since they were last found, from the audit_latest table.
+///    Errored audits do not end the streak, so a timeout does not drop a failing key.
+/// 2. Leaving out keys whose failure backoff has not elapsed, so keys that keep failing are
+///    audited less and less often.
+/// 3. Sorting ascending to have the oldest failed audits first.
+///
+/// Each pass over the failed keys only selects keys whose latest audit is newer than the last
+/// key sent, so queued keys are not sent again before they are audited. Once no keys are left,
+/// the next pass starts over from the oldest failure.
+///
+/// At regular intervals the channel capacity is assessed and new tasks are added to reach capacity.
+async fn select_failed_content_for_audit(
+    tx: mpsc::Sender<AuditTask>,
+    conn: DatabaseConnection,
+    shutdown: Shutdown,
+) {
+    debug!("initializing audit process for 'failed' strategy");
+    let mut interval = interval(Duration::from_secs(10));
+    let start_of_pass = DateTime::<Utc>::UNIX_EPOCH;
+    let mut failed_after = start_of_pass;
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = shutdown.triggered() => return,
+        }
+        if tx.is_closed() {
+            error!("Channel is closed.");
+            panic!();
+        }
+        let keys_required = tx.capacity();
+        if keys_required == 0 {
+            continue;
+        };
+        let failed = match audit_latest::get_failed_oldest_first(
+            SubProtocol::History,
+            failed_after,
+            Utc::now(),
+            keys_required as u64,
+            &conn,
+        )
+        .await
+        {
+            Ok(failed) => failed,
+            Err(err) => {
+                error!(audit.strategy="failed", err=?err, "Could not make audit query");
+                continue;
+            }
+        };
+        if failed.is_empty() {
+            failed_after = start_of_pass;
+            continue;
+        }
+        let content_key_db_entries: Vec<content::Model> = failed
+            .into_iter()
+            .map(|(content, latest)| {
+                failed_after = failed_after.max(latest.created_at);
+                content
+            })
+            .collect();
+        let item_count = content_key_db_entries.len();
+        debug!(
+            strategy = "failed",
+            item_count, "Adding content keys to the audit queue."
+        );
+        add_to_queue(
+            tx.clone(),
+            SelectionStrategy::History(HistorySelectionStrategy::Failed),
+            content_key_db_entries,
+        )
+        .await;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashSet;
@@ -470,6 +682,8 @@ mod tests {
                 content_key: Set(content_key.to_bytes().to_vec()),
                 first_available_at: Set(available_at),
                 protocol_id: Set(SubProtocol::History),
+                source: NotSet,
+                archived: Set(false),
             };
             let content_key_model = content_key_active_model.insert(&conn).await?;
 
@@ -483,6 +697,9 @@ mod tests {
             let client_info_active_model = client_info::ActiveModel {
                 id: NotSet,
                 version_info: Set("trin v0.1.0".to_owned()),
+                client_name: NotSet,
+                client_version: NotSet,
+                client_commit: NotSet,
             };
 
             let node_id = NodeId::random();
@@ -504,8 +721,14 @@ mod tests {
                     ))),
                     result: Set(result),
                     trace: Set("".to_owned()),
+                    trace_requested: Set(false),
+                    error_reason: NotSet,
                     client_info: Set(Some(client_info_model.id)),
                     node: Set(Some(node.id)),
+                    census_id: NotSet,
+                    selection_lag_ms: NotSet,
+                    duration_ms: NotSet,
+                    timeout_ms: NotSet,
                 };
                 content_audit_active_model.insert(&conn).await?;
             }
@@ -535,6 +758,7 @@ mod tests {
             tx.clone(),
             conn.clone(),
             SelectionStrategy::History(HistorySelectionStrategy::Latest),
+            Shutdown::default(),
         ));
         let mut checked_ids: HashSet<i32> = HashSet::new();
         // There are 15 correct values: [31, 32, ... 45], after which the queue should be empty
@@ -579,6 +803,7 @@ mod tests {
         tokio::spawn(select_oldest_unaudited_content_for_audit(
             tx.clone(),
             conn.clone(),
+            Shutdown::default(),
         ));
         let mut checked_ids: HashSet<i32> = HashSet::new();
         // There are 10 correct values: [2, ..., 11]
@@ -602,6 +827,53 @@ mod tests {
         assert_eq!(checked_ids.len(), CHANNEL_SIZE);
     }
 
+    /// Tests that the `SelectionStrategy::Failed` selects the keys whose latest audit failed
+    /// once their backoff has elapsed, each once per pass.
+    #[tokio::test]
+    async fn test_failed_strategy() {
+        // Orchestration
+        let (conn, _db) = get_populated_test_audit_db().await.unwrap();
+        // Failed keys are 1 and the odd keys in [17, ..., 29]. Key 29 is still backing off.
+        audit_latest::Entity::update_many()
+            .col_expr(
+                audit_latest::Column::RetryAfter,
+                Expr::value(Utc::now() - chrono::TimeDelta::try_hours(1).unwrap()),
+            )
+            .filter(audit_latest::Column::ContentKey.ne(29))
+            .exec(&conn)
+            .await
+            .unwrap();
+        const CHANNEL_SIZE: usize = 10;
+        let (tx, mut rx) = channel::<AuditTask>(CHANNEL_SIZE);
+        // Start strategy
+        tokio::spawn(select_failed_content_for_audit(
+            tx.clone(),
+            conn.clone(),
+            Shutdown::default(),
+        ));
+        let mut checked_ids: HashSet<i32> = HashSet::new();
+        let expected_key_ids: Vec<i32> = vec![1, 17, 19, 21, 23, 25, 27];
+        // Await strategy results
+        while let Some(task) = rx.recv().await {
+            assert_eq!(
+                task.strategy,
+                SelectionStrategy::History(HistorySelectionStrategy::Failed)
+            );
+            let key_model = content::Entity::find()
+                .filter(content::Column::ContentKey.eq(task.content.content_key))
+                .one(&conn)
+                .await
+                .unwrap()
+                .unwrap();
+            // Check that strategy only yields expected keys, once each.
+            assert!(expected_key_ids.contains(&key_model.id));
+            assert!(checked_ids.insert(key_model.id));
+            if checked_ids.len() == expected_key_ids.len() {
+                break;
+            }
+        }
+    }
+
     /// Tests that the `SelectionStrategy::Random` selects the correct values
     /// from the test database.
     #[tokio::test]
@@ -615,6 +887,7 @@ mod tests {
             tx.clone(),
             conn.clone(),
             SelectionStrategy::History(HistorySelectionStrategy::Latest),
+            Shutdown::default(),
         ));
         let mut checked_ids: HashSet<i32> = HashSet::new();
         // There are 45 possible correct values: [1, 2, ... 45]
@@ -637,4 +910,37 @@ mod tests {
         // Make sure no key was audited twice by pushing to a hashmap and checking it's length.
         assert_eq!(checked_ids.len(), CHANNEL_SIZE);
     }
+
+    /// Tests that the `SelectionStrategy::RandomUnaudited` leaves out recently audited keys.
+    #[tokio::test]
+    async fn test_random_unaudited_strategy() {
+        // Orchestration
+        let (conn, _db) = get_populated_test_audit_db().await.unwrap();
+        const CHANNEL_SIZE: usize = 10;
+        let (tx, mut rx) = channel::<AuditTask>(CHANNEL_SIZE);
+        // Start strategy
+        tokio::spawn(select_random_unaudited_content_for_audit(
+            tx.clone(),
+            conn.clone(),
+            7,
+            Shutdown::default(),
+        ));
+        let mut checked_ids: HashSet<i32> = HashSet::new();
+        // Keys 1 and 16-30 were just audited, leaving [2, ... 15] and [31, ... 45].
+        let expected_key_ids: Vec<i32> = (2..=15).chain(31..=45).collect();
+        // Await strategy results
+        while let Some(task) = rx.recv().await {
+            assert_eq!(
+                task.strategy,
+                SelectionStrategy::History(HistorySelectionStrategy::RandomUnaudited)
+            );
+            // Check that strategy only yields expected keys.
+            assert!(expected_key_ids.contains(&task.content.id));
+            checked_ids.insert(task.content.id);
+            if checked_ids.len() == CHANNEL_SIZE {
+                break;
+            }
+        }
+        assert_eq!(checked_ids.len(), CHANNEL_SIZE);
+    }
 }
@@ -0,0 +1,185 @@
+//! Graceful shutdown of the audit daemon.
+//!
+//! On shutdown no new audits are started, audits in flight are given some time to finish, and
+//! those still `Pending` after that are marked as errored, so that none is left pending.
+use std::{
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    time::Instant,
+};
+
+use sea_orm::DatabaseConnection;
+use tokio::{
+    sync::watch,
+    time::{sleep, Duration},
+};
+use tracing::{error, info, warn};
+
+use entity::content_audit;
+
+/// Time between checks for audits in flight while draining.
+const DRAIN_POLL_PERIOD: Duration = Duration::from_millis(250);
+
+/// Shutdown signal shared by the audit tasks, and the audits started before it.
+#[derive(Clone, Debug)]
+pub struct Shutdown {
+    tx: Arc<watch::Sender<bool>>,
+    rx: watch::Receiver<bool>,
+    /// Number of audits started before shutdown that have not ended, whether or not they are
+    /// recorded yet.
+    in_flight: Arc<AtomicUsize>,
+    /// Ids of the audits recorded as `Pending` that have not finished.
+    pending: Arc<Mutex<HashSet<i32>>>,
+}
+
+/// An audit counted as in flight by the shutdown, until dropped.
+pub(crate) struct InFlightAudit {
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl Drop for InFlightAudit {
+    fn drop(&mut self) {
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        let (tx, rx) = watch::channel(false);
+        Shutdown {
+            tx: Arc::new(tx),
+            rx,
+            in_flight: Arc::default(),
+            pending: Arc::default(),
+        }
+    }
+}
+
+impl Shutdown {
+    /// Signals all tasks to stop taking new audits.
+    pub fn trigger(&self) {
+        self.tx.send_replace(true);
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        *self.rx.borrow()
+    }
+
+    /// Waits until shutdown is triggered.
+    pub async fn triggered(&self) {
+        let mut rx = self.rx.clone();
+        while !*rx.borrow_and_update() {
+            if rx.changed().await.is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Counts an audit as in flight until the returned guard is dropped, or returns None if
+    /// shutdown is triggered and the audit must not start.
+    ///
+    /// The audit is counted before checking for shutdown, so that [Shutdown::drain] either waits
+    /// for it or it sees the shutdown.
+    pub(crate) fn begin_audit(&self) -> Option<InFlightAudit> {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        let audit = InFlightAudit {
+            in_flight: self.in_flight.clone(),
+        };
+        match self.is_triggered() {
+            true => None,
+            false => Some(audit),
+        }
+    }
+
+    fn in_flight_audits(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Tracks an audit recorded as `Pending`, until [Shutdown::finish_audit].
+    pub(crate) fn start_audit(&self, audit_id: i32) {
+        self.pending_audits().insert(audit_id);
+    }
+
+    pub(crate) fn finish_audit(&self, audit_id: i32) {
+        self.pending_audits().remove(&audit_id);
+    }
+
+    fn pending_audits(&self) -> MutexGuard<'_, HashSet<i32>> {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Triggers shutdown and waits up to the timeout for the audits in flight to finish,
+    /// including those not yet recorded as `Pending`. Audits still pending after the timeout are
+    /// marked as errored.
+    pub async fn drain(&self, timeout: Duration, conn: &DatabaseConnection) {
+        self.trigger();
+        let deadline = Instant::now() + timeout;
+        let mut in_flight = self.in_flight_audits();
+        info!(
+            audits = in_flight,
+            "Waiting for audits in flight to finish."
+        );
+        while in_flight > 0 && Instant::now() < deadline {
+            sleep(DRAIN_POLL_PERIOD).await;
+            in_flight = self.in_flight_audits();
+        }
+        let unfinished: Vec<i32> = self.pending_audits().iter().copied().collect();
+        if unfinished.is_empty() {
+            info!("All audits in flight finished.");
+            return;
+        }
+        warn!(
+            audits = unfinished.len(),
+            timeout.seconds = timeout.as_secs(),
+            "Audits did not finish before the shutdown timeout."
+        );
+        match content_audit::mark_pending_errored(&unfinished, content_audit::SHUTDOWN_REASON, conn)
+            .await
+        {
+            Ok(marked) => info!(audits = marked, "Marked unfinished audits as errored."),
+            Err(e) => error!(err=?e, "Could not mark unfinished audits as errored."),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracks_pending_audits() {
+        let shutdown = Shutdown::default();
+        let clone = shutdown.clone();
+        clone.start_audit(1);
+        clone.start_audit(2);
+        shutdown.finish_audit(1);
+        assert_eq!(*shutdown.pending_audits(), HashSet::from([2]));
+    }
+
+    #[test]
+    fn counts_audits_in_flight_until_dropped() {
+        let shutdown = Shutdown::default();
+        let audit = shutdown.begin_audit();
+        assert!(audit.is_some());
+        assert_eq!(shutdown.in_flight_audits(), 1);
+        drop(audit);
+        assert_eq!(shutdown.in_flight_audits(), 0);
+
+        // No audit starts after shutdown, and none is left counted.
+        shutdown.trigger();
+        assert!(shutdown.begin_audit().is_none());
+        assert_eq!(shutdown.in_flight_audits(), 0);
+    }
+
+    #[test]
+    fn trigger_is_seen_by_clones() {
+        let shutdown = Shutdown::default();
+        let clone = shutdown.clone();
+        assert!(!clone.is_triggered());
+        shutdown.trigger();
+        assert!(clone.is_triggered());
+    }
+}
@@ -5,17 +5,19 @@ use anyhow::anyhow;
 use chrono::{DateTime, Utc};
 use entity::{
     client_info,
-    content::SubProtocol,
+    content::{ContentSource, SubProtocol},
     content_audit::{self, SelectionStrategy, StateSelectionStrategy},
     node, state_roots,
 };
 use eth_trie::node::Node;
 use ethportal_api::{
-    jsonrpsee::http_client::HttpClient,
     types::{content_key::state::AccountTrieNodeKey, state_trie::nibbles::Nibbles},
     ContentValue, StateContentKey, StateContentValue, StateNetworkApiClient,
 };
-use glados_core::{db::store_content_key, jsonrpc::PortalClient};
+use glados_core::{
+    db::store_content_key,
+    jsonrpc::{PortalClient, PortalRpcClient},
+};
 use rand::seq::IteratorRandom;
 use sea_orm::DatabaseConnection;
 use tokio::time::sleep;
@@ -36,6 +38,7 @@ async fn save_state_audit(
         "state_intermediate_trie_node",
         block_number,
         available_at,
+        ContentSource::Audit,
         conn,
         SubProtocol::State,
     )
@@ -149,7 +152,7 @@ async fn process_trie_node(
 
 async fn random_state_walk(
     state_root: B256,
-    client: HttpClient,
+    client: PortalRpcClient,
 ) -> Result<AccountTrieNodeKey, (anyhow::Error, AccountTrieNodeKey)> {
     let root_content_key = AccountTrieNodeKey {
         path: Nibbles::try_from_unpacked_nibbles(&[])
@@ -1,29 +1,138 @@
-use chrono::Utc;
-use entity::{audit_stats, content::SubProtocol};
+use std::collections::BTreeMap;
+
+use alloy_primitives::U256;
+use chrono::{DateTime, TimeDelta, Utc};
+use entity::{
+    audit_coverage,
+    audit_stats::{self, BeaconSuccessRates},
+    audit_stats_client, census_node,
+    content::SubProtocol,
+    content_audit::{self, HistorySelectionStrategy, SelectionStrategy},
+    content_flapping, coverage_sample, node_serve_latency, transfer_failure_daily,
+};
+use glados_core::history::BlockEra;
 use glados_core::stats::{
-    filter_audits, get_audit_stats, AuditFilters, ContentTypeFilter, Period, StrategyFilter,
-    SuccessFilter,
+    filter_audits, get_audit_stats, get_audit_stats_by_client, AuditFilters, ContentTypeFilter,
+    Period, StrategyFilter, SuccessFilter,
 };
-use sea_orm::{DatabaseConnection, DbErr};
+use rand::Rng;
+use sea_orm::{DatabaseConnection, DbBackend, DbErr, FromQueryResult, Statement, Value};
 use tokio::time::{interval, Duration};
-use tracing::{debug, error};
+use tracing::{debug, error, warn};
+
+use crate::{
+    remote_write::{audit_stats_series, RemoteWrite},
+    selection::MERGE_BLOCK_HEIGHT,
+};
+
+/// Number of random content ids whose coverage is sampled for each census.
+const COVERAGE_SAMPLES_PER_CENSUS: usize = 1000;
+
+/// How far back censuses are sampled, so that a restart does not sample old censuses.
+const COVERAGE_SAMPLE_WINDOW: TimeDelta = TimeDelta::days(1);
+
+/// How far back audit traces are summarized into the serve latency of each node.
+const SERVE_LATENCY_WINDOW: TimeDelta = TimeDelta::days(1);
 
 /// Loops indefinitely, periodically recording audit stats to the database.
-pub async fn periodically_record_stats(period: Duration, conn: DatabaseConnection) -> ! {
+///
+/// Before each record, audits pending for longer than `stale_pending_after` are marked as
+/// errored, so that audits abandoned by a crashed worker do not stay pending forever. With a
+/// remote write endpoint, each record of the audit stats is also pushed to it.
+pub async fn periodically_record_stats(
+    period: Duration,
+    stale_pending_after: TimeDelta,
+    remote_write: Option<RemoteWrite>,
+    conn: DatabaseConnection,
+) -> ! {
     debug!("initializing task for logging audit stats");
     let mut interval = interval(period);
 
     loop {
-        record_current_stats(&conn).await.unwrap_or_else(|e| {
-            error!("failed to record audit stats: {e}");
+        let num_stale_pending = prune_stale_pending(stale_pending_after, &conn)
+            .await
+            .unwrap_or_else(|e| {
+                error!("failed to mark stale pending audits: {e}");
+                0
+            });
+        let stats = record_current_stats(num_stale_pending, &conn)
+            .await
+            .unwrap_or_else(|e| {
+                error!("failed to record audit stats: {e}");
+                None
+            });
+        if let (Some(remote_write), Some(stats)) = (&remote_write, stats) {
+            remote_write
+                .push(&audit_stats_series(&stats), stats.timestamp)
+                .await
+                .unwrap_or_else(|e| {
+                    error!("failed to push audit stats to the remote write endpoint: {e}");
+                });
+        }
+        record_current_client_stats(&conn)
+            .await
+            .unwrap_or_else(|e| {
+                error!("failed to record audit stats by client: {e}");
+            });
+        record_current_flapping(&conn).await.unwrap_or_else(|e| {
+            error!("failed to record content flapping: {e}");
+        });
+        record_current_transfer_failures(&conn)
+            .await
+            .unwrap_or_else(|e| {
+                error!("failed to record transfer failures: {e}");
+            });
+        record_current_serve_latency(&conn)
+            .await
+            .unwrap_or_else(|e| {
+                error!("failed to record node serve latency: {e}");
+            });
+        record_coverage_samples(&conn).await.unwrap_or_else(|e| {
+            error!("failed to record census coverage samples: {e}");
         });
         interval.tick().await;
     }
 }
 
-/// Records audit stats for the current moment to the database.
+/// Loops indefinitely, periodically recording the audit coverage of the strategies.
+///
+/// Coverage counts the keys each strategy has ever audited, which takes longer than the other
+/// stats, so it is recorded on its own, less frequent, period.
+pub async fn periodically_record_coverage(period: Duration, conn: DatabaseConnection) -> ! {
+    debug!("initializing task for recording audit coverage");
+    let mut interval = interval(period);
+
+    loop {
+        record_current_coverage(&conn).await.unwrap_or_else(|e| {
+            error!("failed to record audit coverage: {e}");
+        });
+        interval.tick().await;
+    }
+}
+
+/// Marks audits pending for longer than `stale_after` as errored, returning how many were.
+async fn prune_stale_pending(
+    stale_after: TimeDelta,
+    conn: &DatabaseConnection,
+) -> anyhow::Result<i32> {
+    let num_stale = content_audit::mark_stale_pending(Utc::now() - stale_after, conn).await?;
+    if num_stale > 0 {
+        warn!(
+            audits = num_stale,
+            "Marked stale pending audits as errored."
+        );
+    }
+    Ok(num_stale.try_into()?)
+}
+
+/// Records audit stats for the current moment to the database, returning the record if it
+/// was stored.
+///
 /// Calculates success rate for many combinations of strategy and content type.
-async fn record_current_stats(conn: &DatabaseConnection) -> Result<(), DbErr> {
+async fn record_current_stats(
+    num_stale_pending: i32,
+    conn: &DatabaseConnection,
+) -> Result<Option<audit_stats::Model>, DbErr> {
     // Run audit stat queries in parallel.
     let (
         all,
@@ -48,7 +157,8 @@ async fn record_current_stats(conn: &DatabaseConnection) -> Result<(), DbErr> {
                 strategy: StrategyFilter::All,
                 content_type: ContentTypeFilter::All,
                 success: SuccessFilter::All,
-                network: SubProtocol::History
+                network: SubProtocol::History,
+                include_archived: false,
             },),
             Period::Hour,
             conn
@@ -58,7 +168,8 @@ async fn record_current_stats(conn: &DatabaseConnection) -> Result<(), DbErr> {
                 strategy: StrategyFilter::Latest,
                 content_type: ContentTypeFilter::All,
                 success: SuccessFilter::All,
-                network: SubProtocol::History
+                network: SubProtocol::History,
+                include_archived: false,
             },),
             Period::Hour,
             conn
@@ -68,7 +179,8 @@ async fn record_current_stats(conn: &DatabaseConnection) -> Result<(), DbErr> {
                 strategy: StrategyFilter::Random,
                 content_type: ContentTypeFilter::All,
                 success: SuccessFilter::All,
-                network: SubProtocol::History
+                network: SubProtocol::History,
+                include_archived: false,
             },),
             Period::Hour,
             conn
@@ -78,7 +190,8 @@ async fn record_current_stats(conn: &DatabaseConnection) -> Result<(), DbErr> {
                 strategy: StrategyFilter::FourFours,
                 content_type: ContentTypeFilter::All,
                 success: SuccessFilter::All,
-                network: SubProtocol::History
+                network: SubProtocol::History,
+                include_archived: false,
             },),
             Period::Hour,
             conn
@@ -88,7 +201,8 @@ async fn record_current_stats(conn: &DatabaseConnection) -> Result<(), DbErr> {
                 strategy: StrategyFilter::All,
                 content_type: ContentTypeFilter::Headers,
                 success: SuccessFilter::All,
-                network: SubProtocol::History
+                network: SubProtocol::History,
+                include_archived: false,
             },),
             Period::Hour,
             conn
@@ -98,7 +212,8 @@ async fn record_current_stats(conn: &DatabaseConnection) -> Result<(), DbErr> {
                 strategy: StrategyFilter::All,
                 content_type: ContentTypeFilter::Bodies,
                 success: SuccessFilter::All,
-                network: SubProtocol::History
+                network: SubProtocol::History,
+                include_archived: false,
             },),
             Period::Hour,
             conn
@@ -108,7 +223,8 @@ async fn record_current_stats(conn: &DatabaseConnection) -> Result<(), DbErr> {
                 strategy: StrategyFilter::All,
                 content_type: ContentTypeFilter::Receipts,
                 success: SuccessFilter::All,
-                network: SubProtocol::History
+                network: SubProtocol::History,
+                include_archived: false,
             },),
             Period::Hour,
             conn
@@ -118,7 +234,8 @@ async fn record_current_stats(conn: &DatabaseConnection) -> Result<(), DbErr> {
                 strategy: StrategyFilter::Latest,
                 content_type: ContentTypeFilter::Headers,
                 success: SuccessFilter::All,
-                network: SubProtocol::History
+                network: SubProtocol::History,
+                include_archived: false,
             },),
             Period::Hour,
             conn
@@ -128,7 +245,8 @@ async fn record_current_stats(conn: &DatabaseConnection) -> Result<(), DbErr> {
                 strategy: StrategyFilter::Latest,
                 content_type: ContentTypeFilter::Bodies,
                 success: SuccessFilter::All,
-                network: SubProtocol::History
+                network: SubProtocol::History,
+                include_archived: false,
             },),
             Period::Hour,
             conn
@@ -138,7 +256,8 @@ async fn record_current_stats(conn: &DatabaseConnection) -> Result<(), DbErr> {
                 strategy: StrategyFilter::Latest,
                 content_type: ContentTypeFilter::Receipts,
                 success: SuccessFilter::All,
-                network: SubProtocol::History
+                network: SubProtocol::History,
+                include_archived: false,
             },),
             Period::Hour,
             conn
@@ -148,7 +267,8 @@ async fn record_current_stats(conn: &DatabaseConnection) -> Result<(), DbErr> {
                 strategy: StrategyFilter::Random,
                 content_type: ContentTypeFilter::Headers,
                 success: SuccessFilter::All,
-                network: SubProtocol::History
+                network: SubProtocol::History,
+                include_archived: false,
             },),
             Period::Hour,
             conn
@@ -158,7 +278,8 @@ async fn record_current_stats(conn: &DatabaseConnection) -> Result<(), DbErr> {
                 strategy: StrategyFilter::Random,
                 content_type: ContentTypeFilter::Bodies,
                 success: SuccessFilter::All,
-                network: SubProtocol::History
+                network: SubProtocol::History,
+                include_archived: false,
             },),
             Period::Hour,
             conn
@@ -168,7 +289,8 @@ async fn record_current_stats(conn: &DatabaseConnection) -> Result<(), DbErr> {
                 strategy: StrategyFilter::Random,
                 content_type: ContentTypeFilter::Receipts,
                 success: SuccessFilter::All,
-                network: SubProtocol::History
+                network: SubProtocol::History,
+                include_archived: false,
             },),
             Period::Hour,
             conn
@@ -178,7 +300,8 @@ async fn record_current_stats(conn: &DatabaseConnection) -> Result<(), DbErr> {
                 strategy: StrategyFilter::FourFours,
                 content_type: ContentTypeFilter::Headers,
                 success: SuccessFilter::All,
-                network: SubProtocol::History
+                network: SubProtocol::History,
+                include_archived: false,
             },),
             Period::Hour,
             conn
@@ -188,7 +311,8 @@ async fn record_current_stats(conn: &DatabaseConnection) -> Result<(), DbErr> {
                 strategy: StrategyFilter::FourFours,
                 content_type: ContentTypeFilter::Bodies,
                 success: SuccessFilter::All,
-                network: SubProtocol::History
+                network: SubProtocol::History,
+                include_archived: false,
             },),
             Period::Hour,
             conn
@@ -198,7 +322,8 @@ async fn record_current_stats(conn: &DatabaseConnection) -> Result<(), DbErr> {
                 strategy: StrategyFilter::FourFours,
                 content_type: ContentTypeFilter::Receipts,
                 success: SuccessFilter::All,
-                network: SubProtocol::History
+                network: SubProtocol::History,
+                include_archived: false,
             },),
             Period::Hour,
             conn
@@ -206,7 +331,11 @@ async fn record_current_stats(conn: &DatabaseConnection) -> Result<(), DbErr> {
     );
 
     // Handle errors and get success rates.
-    let success_rate_all = all?.pass_percent;
+    let all = all?;
+    let num_audits = all.total_audits as i32;
+    let num_errored = all.total_errored as i32;
+    let num_pending = all.total_pending as i32;
+    let success_rate_all = all.pass_percent;
     let success_rate_latest = latest?.pass_percent;
     let success_rate_random = random?.pass_percent;
     let success_rate_oldest = 0.0;
@@ -223,11 +352,12 @@ async fn record_current_stats(conn: &DatabaseConnection) -> Result<(), DbErr> {
     let success_rate_fourfours_headers = fourfours_headers?.pass_percent;
     let success_rate_fourfours_bodies = fourfours_bodies?.pass_percent;
     let success_rate_fourfours_receipts = fourfours_receipts?.pass_percent;
+    let beacon = beacon_success_rates(conn).await?;
 
     // Record the values.
     match audit_stats::create(
         Utc::now(),
-        0,
+        num_audits,
         success_rate_all,
         success_rate_latest,
         success_rate_random,
@@ -245,12 +375,325 @@ async fn record_current_stats(conn: &DatabaseConnection) -> Result<(), DbErr> {
         success_rate_fourfours_headers,
         success_rate_fourfours_bodies,
         success_rate_fourfours_receipts,
+        num_errored,
+        num_pending,
+        num_stale_pending,
+        beacon,
         conn,
     )
     .await
     {
-        Ok(_) => debug!("successfully recorded audit stats"),
-        Err(e) => error!("failed to record audit stats: {e}",),
+        Ok(stats) => {
+            debug!("successfully recorded audit stats");
+            Ok(Some(stats))
+        }
+        Err(e) => {
+            error!("failed to record audit stats: {e}",);
+            Ok(None)
+        }
+    }
+}
+
+/// Calculates the success rates of beacon audits over the last hour, by type of light client
+/// content.
+async fn beacon_success_rates(conn: &DatabaseConnection) -> Result<BeaconSuccessRates, DbErr> {
+    let beacon_stats = |content_type| {
+        get_audit_stats(
+            filter_audits(AuditFilters {
+                strategy: StrategyFilter::All,
+                content_type,
+                success: SuccessFilter::All,
+                network: SubProtocol::Beacon,
+                include_archived: false,
+            }),
+            Period::Hour,
+            conn,
+        )
+    };
+    let (all, bootstraps, updates_by_range, finality_updates, optimistic_updates) = tokio::join!(
+        beacon_stats(ContentTypeFilter::All),
+        beacon_stats(ContentTypeFilter::BlockRoots),
+        beacon_stats(ContentTypeFilter::UpdatesByRange),
+        beacon_stats(ContentTypeFilter::FinalityUpdates),
+        beacon_stats(ContentTypeFilter::OptimisticUpdates),
+    );
+    let all = all?;
+    Ok(BeaconSuccessRates {
+        num_audits: all.total_audits as i32,
+        all: all.pass_percent,
+        bootstraps: bootstraps?.pass_percent,
+        updates_by_range: updates_by_range?.pass_percent,
+        finality_updates: finality_updates?.pass_percent,
+        optimistic_updates: optimistic_updates?.pass_percent,
+    })
+}
+
+/// Records the success rate of each auditing client over the last hour, for each subnetwork.
+async fn record_current_client_stats(conn: &DatabaseConnection) -> anyhow::Result<()> {
+    let timestamp = Utc::now();
+    for network in [
+        SubProtocol::History,
+        SubProtocol::State,
+        SubProtocol::Beacon,
+    ] {
+        let audits = filter_audits(AuditFilters {
+            strategy: StrategyFilter::All,
+            content_type: ContentTypeFilter::All,
+            success: SuccessFilter::All,
+            network,
+            include_archived: false,
+        });
+        for stats in get_audit_stats_by_client(audits, Period::Hour, conn).await? {
+            audit_stats_client::create(
+                timestamp,
+                network,
+                stats.client.clone(),
+                stats.total_audits.try_into()?,
+                stats.total_passes.try_into()?,
+                stats.total_failures.try_into()?,
+                stats.pass_percent(),
+                conn,
+            )
+            .await?;
+        }
+    }
+    debug!("successfully recorded audit stats by client");
+    Ok(())
+}
+
+#[derive(FromQueryResult)]
+struct Count {
+    count: i64,
+}
+
+/// Which keys count as eligible when measuring a strategy's coverage.
+enum Eligible {
+    /// History keys that became available during the day.
+    AvailableThatDay,
+    /// History keys that were available by the end of the day.
+    AvailableByEndOfDay,
+    /// The header, body and receipts of every pre-merge block, whether stored or not.
+    PreMergeBlocks,
+}
+
+/// Strategies whose coverage is recorded, and the keys each of them selects from.
+const COVERAGE_STRATEGIES: [(HistorySelectionStrategy, Eligible); 5] = [
+    (HistorySelectionStrategy::Latest, Eligible::AvailableThatDay),
+    (
+        HistorySelectionStrategy::Random,
+        Eligible::AvailableByEndOfDay,
+    ),
+    (
+        HistorySelectionStrategy::RandomUnaudited,
+        Eligible::AvailableByEndOfDay,
+    ),
+    (
+        HistorySelectionStrategy::SelectOldestUnaudited,
+        Eligible::AvailableByEndOfDay,
+    ),
+    (
+        HistorySelectionStrategy::FourFours,
+        Eligible::PreMergeBlocks,
+    ),
+];
+
+/// Records, for each strategy, how many of its eligible keys have been audited at least once
+/// by the strategy so far today.
+async fn record_current_coverage(conn: &DatabaseConnection) -> anyhow::Result<()> {
+    let day = Utc::now()
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .expect("midnight is a valid time")
+        .and_utc();
+    let day_end = day + TimeDelta::days(1);
+
+    for (strategy, eligible) in COVERAGE_STRATEGIES {
+        let strategy = SelectionStrategy::History(strategy);
+        let (num_eligible, num_audited) =
+            count_coverage(&strategy, eligible, day, day_end, conn).await?;
+        audit_coverage::upsert(day, strategy, num_eligible, num_audited, conn).await?;
+    }
+    debug!("successfully recorded audit coverage");
+    Ok(())
+}
+
+/// Returns the number of keys eligible for a strategy, and how many of those it has audited,
+/// as of the end of the day.
+async fn count_coverage(
+    strategy: &SelectionStrategy,
+    eligible: Eligible,
+    day: DateTime<Utc>,
+    day_end: DateTime<Utc>,
+    conn: &DatabaseConnection,
+) -> Result<(i64, i64), DbErr> {
+    let strategy: Value = strategy.clone().into();
+    let (num_eligible, audited) = match eligible {
+        Eligible::AvailableThatDay => (
+            count(
+                "SELECT COUNT(*) AS count
+                FROM content
+                WHERE protocol_id = 0 AND first_available_at >= $1 AND first_available_at < $2",
+                vec![day.into(), day_end.into()],
+                conn,
+            )
+            .await?,
+            Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "SELECT COUNT(DISTINCT content_audit.content_key) AS count
+                FROM content_audit
+                JOIN content ON content.id = content_audit.content_key
+                WHERE content.protocol_id = 0
+                    AND content.first_available_at >= $1
+                    AND content.first_available_at < $2
+                    AND content_audit.strategy_used = $3
+                    AND content_audit.created_at < $2",
+                vec![day.into(), day_end.into(), strategy],
+            ),
+        ),
+        Eligible::AvailableByEndOfDay => (
+            count(
+                "SELECT COUNT(*) AS count
+                FROM content
+                WHERE protocol_id = 0 AND first_available_at < $1",
+                vec![day_end.into()],
+                conn,
+            )
+            .await?,
+            Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "SELECT COUNT(DISTINCT content_audit.content_key) AS count
+                FROM content_audit
+                JOIN content ON content.id = content_audit.content_key
+                WHERE content.protocol_id = 0
+                    AND content.first_available_at < $1
+                    AND content_audit.strategy_used = $2
+                    AND content_audit.created_at < $1",
+                vec![day_end.into(), strategy],
+            ),
+        ),
+        // Block numbers are drawn from 1 up to, but excluding, the merge block.
+        Eligible::PreMergeBlocks => (
+            BlockEra::PreMerge.content_kinds().len() as i64 * (MERGE_BLOCK_HEIGHT as i64 - 1),
+            Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "SELECT COUNT(DISTINCT content_key) AS count
+                FROM content_audit
+                WHERE strategy_used = $1 AND created_at < $2",
+                vec![strategy, day_end.into()],
+            ),
+        ),
     };
+    let num_audited = Count::find_by_statement(audited)
+        .one(conn)
+        .await?
+        .map_or(0, |c| c.count);
+    Ok((num_eligible, num_audited))
+}
+
+async fn count(sql: &str, values: Vec<Value>, conn: &DatabaseConnection) -> Result<i64, DbErr> {
+    Ok(Count::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        sql,
+        values,
+    ))
+    .one(conn)
+    .await?
+    .map_or(0, |c| c.count))
+}
+
+/// How far back audits are compared to find keys that flip between passing and failing.
+const FLAPPING_WINDOW: TimeDelta = TimeDelta::days(7);
+
+/// Fewest flips between passing and failing for a key to be considered flapping. A single
+/// flip is a key becoming available or going missing, not an unstable one.
+const MIN_FLAPPING_FLIPS: i32 = 2;
+
+/// Recomputes the flapping score of every key audited within the window.
+async fn record_current_flapping(conn: &DatabaseConnection) -> anyhow::Result<()> {
+    let window_start = Utc::now() - FLAPPING_WINDOW;
+    let num_keys = content_flapping::refresh(window_start, MIN_FLAPPING_FLIPS, conn).await?;
+    debug!(num_keys, "successfully recorded content flapping");
     Ok(())
 }
+
+/// Recounts the transfers in the audit traces of yesterday and today, so that audits recorded
+/// after the last refresh of yesterday are included.
+async fn record_current_transfer_failures(conn: &DatabaseConnection) -> anyhow::Result<()> {
+    let today = Utc::now()
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .expect("midnight is a valid time")
+        .and_utc();
+    for day in [today - TimeDelta::days(1), today] {
+        let num_rows = transfer_failure_daily::refresh(day, conn).await?;
+        debug!(%day, num_rows, "successfully recorded transfer failures");
+    }
+    Ok(())
+}
+
+/// Recomputes the serve latency of every node that responded to a lookup within the window.
+async fn record_current_serve_latency(conn: &DatabaseConnection) -> anyhow::Result<()> {
+    let window_start = Utc::now() - SERVE_LATENCY_WINDOW;
+    let num_nodes = node_serve_latency::refresh(window_start, conn).await?;
+    debug!(num_nodes, "successfully recorded node serve latency");
+    Ok(())
+}
+
+/// Samples the coverage of the censuses that finished since the last samples, counting how many
+/// nodes of each census have a radius covering random content ids.
+async fn record_coverage_samples(conn: &DatabaseConnection) -> anyhow::Result<()> {
+    let since = Utc::now() - COVERAGE_SAMPLE_WINDOW;
+    for census_id in coverage_sample::get_unsampled_census_ids(since, conn).await? {
+        let radii = census_node::get_node_radii(census_id, conn).await?;
+        let histogram =
+            sample_coverage(&radii, COVERAGE_SAMPLES_PER_CENSUS, &mut rand::thread_rng());
+        coverage_sample::create_many(census_id, &histogram, conn).await?;
+        debug!(
+            census.id = census_id,
+            nodes = radii.len(),
+            "successfully sampled census coverage"
+        );
+    }
+    Ok(())
+}
+
+/// Counts, for random content ids, the nodes whose radius covers them, returning how many
+/// content ids were covered by each number of nodes.
+fn sample_coverage<R: Rng>(
+    node_radii: &[(U256, U256)],
+    num_samples: usize,
+    rng: &mut R,
+) -> BTreeMap<i32, i32> {
+    let mut histogram = BTreeMap::new();
+    for _ in 0..num_samples {
+        let content_id = U256::from_be_bytes(rng.gen::<[u8; 32]>());
+        let nodes_covering = node_radii
+            .iter()
+            .filter(|(node_id, radius)| (*node_id ^ content_id) <= *radius)
+            .count();
+        *histogram.entry(nodes_covering as i32).or_insert(0) += 1;
+    }
+    histogram
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_coverage() {
+        let node_radii = [
+            (U256::ZERO, U256::MAX),
+            (U256::MAX, U256::MAX),
+            (U256::ZERO, U256::ZERO),
+        ];
+        let histogram = sample_coverage(&node_radii, 50, &mut rand::thread_rng());
+        // Two nodes cover everything, and a zero radius only covers the node's own id.
+        assert_eq!(histogram, BTreeMap::from([(2, 50)]));
+        assert!(sample_coverage(&[], 0, &mut rand::thread_rng()).is_empty());
+        assert_eq!(
+            sample_coverage(&[], 10, &mut rand::thread_rng()),
+            BTreeMap::from([(0, 10)])
+        );
+    }
+}
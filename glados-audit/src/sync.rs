@@ -0,0 +1,242 @@
+//! Audits all of history in order, from genesis to the chain head ([HistorySelectionStrategy::Sync]).
+//!
+//! Blocks are audited in fixed size segments. After each segment the retrieval latency and
+//! error counts are recorded, and the run's progress is stored, so that a restarted auditor
+//! resumes from the last completed segment rather than starting over.
+//!
+//! [HistorySelectionStrategy::Sync]: entity::content_audit::HistorySelectionStrategy::Sync
+use std::time::Instant;
+
+use anyhow::{anyhow, Result};
+use ethportal_api::OverlayContentKey;
+use sea_orm::DatabaseConnection;
+use tokio::time::{sleep, Duration};
+use tracing::{error, info, warn};
+use web3::types::{BlockId, BlockNumber};
+
+use entity::{
+    sync_audit, sync_audit_error,
+    sync_audit_segment::{self, LatencySummary},
+};
+use glados_core::{history::block_content_keys, jsonrpc::PortalClient};
+
+use crate::AuditConfig;
+
+/// Time to wait before retrying after a failure to read or write progress.
+const RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Time to wait after a run reaches the chain head before starting the next run.
+const RESTART_DELAY: Duration = Duration::from_secs(60 * 60);
+
+/// The stages of a sync audit.
+enum SyncState {
+    /// Resuming an unfinished run, or starting a new one at the current chain head.
+    Starting,
+    /// Auditing the segment beginning at the run's `next_block`.
+    Auditing(sync_audit::Model),
+    /// All blocks of the run have been audited.
+    Completed,
+}
+
+/// Outcome of retrieving all content for a segment.
+#[derive(Default)]
+struct SegmentResult {
+    num_found: i32,
+    num_not_found: i32,
+    num_errored: i32,
+    latencies_ms: Vec<i32>,
+}
+
+pub async fn run_sync_audit(conn: DatabaseConnection, config: AuditConfig) {
+    let Some(client) = config.portal_clients.first().cloned() else {
+        error!("Empty list of clients for sync audit.");
+        return;
+    };
+    let w3 = match web3::transports::Http::new(&config.provider_url) {
+        Ok(transport) => web3::Web3::new(transport),
+        Err(e) => {
+            error!(err=?e, "Could not connect to provider for sync audit.");
+            return;
+        }
+    };
+
+    let mut state = SyncState::Starting;
+    loop {
+        // Progress is recorded by segment, so a segment cut short is audited again on restart.
+        state = tokio::select! {
+            state = next_state(state, config.sync_segment_size, &client, &w3, &conn) => state,
+            _ = config.shutdown.triggered() => {
+                info!("Shutting down, sync audit stopped.");
+                return;
+            }
+        };
+    }
+}
+
+/// Takes the next step of the sync audit, retrying failed steps after a delay.
+async fn next_state(
+    state: SyncState,
+    segment_size: u32,
+    client: &PortalClient,
+    w3: &web3::Web3<web3::transports::Http>,
+    conn: &DatabaseConnection,
+) -> SyncState {
+    match state {
+        SyncState::Starting => match start_or_resume(segment_size, w3, conn).await {
+            Ok(run) => SyncState::Auditing(run),
+            Err(e) => {
+                error!(err=?e, "Could not start sync audit.");
+                sleep(RETRY_DELAY).await;
+                SyncState::Starting
+            }
+        },
+        SyncState::Auditing(run) if run.next_block > run.end_block => {
+            match sync_audit::complete(run.clone(), conn).await {
+                Ok(run) => {
+                    info!(sync_audit.id = run.id, "Sync audit reached the end block.");
+                    SyncState::Completed
+                }
+                Err(e) => {
+                    error!(sync_audit.id = run.id, err=?e, "Could not complete sync audit.");
+                    sleep(RETRY_DELAY).await;
+                    SyncState::Auditing(run)
+                }
+            }
+        }
+        SyncState::Auditing(run) => match audit_segment(&run, client, w3, conn).await {
+            Ok(next_block) => match sync_audit::advance(run.clone(), next_block, conn).await {
+                Ok(run) => SyncState::Auditing(run),
+                Err(e) => {
+                    error!(sync_audit.id = run.id, err=?e, "Could not record sync progress.");
+                    sleep(RETRY_DELAY).await;
+                    SyncState::Auditing(run)
+                }
+            },
+            Err(e) => {
+                error!(sync_audit.id = run.id, err=?e, "Could not audit sync segment.");
+                sleep(RETRY_DELAY).await;
+                SyncState::Auditing(run)
+            }
+        },
+        SyncState::Completed => {
+            sleep(RESTART_DELAY).await;
+            SyncState::Starting
+        }
+    }
+}
+
+/// Returns the unfinished run, or starts a new run from genesis to the current chain head.
+async fn start_or_resume(
+    segment_size: u32,
+    w3: &web3::Web3<web3::transports::Http>,
+    conn: &DatabaseConnection,
+) -> Result<sync_audit::Model> {
+    if let Some(run) = sync_audit::get_running(conn).await? {
+        info!(
+            sync_audit.id = run.id,
+            next_block = run.next_block,
+            end_block = run.end_block,
+            "Resuming sync audit."
+        );
+        return Ok(run);
+    }
+    let head = w3.eth().block_number().await?.as_u64() as i32;
+    let run = sync_audit::create(0, head, segment_size as i32, conn).await?;
+    info!(
+        sync_audit.id = run.id,
+        end_block = head,
+        "Starting sync audit."
+    );
+    Ok(run)
+}
+
+/// Audits the headers, body and receipts of each block in the run's next segment.
+///
+/// Returns the first block of the following segment.
+async fn audit_segment(
+    run: &sync_audit::Model,
+    client: &PortalClient,
+    w3: &web3::Web3<web3::transports::Http>,
+    conn: &DatabaseConnection,
+) -> Result<i32> {
+    let start_block = run.next_block;
+    let end_block = (start_block + run.segment_size).min(run.end_block + 1);
+
+    let mut result = SegmentResult::default();
+    for block_number in start_block..end_block {
+        let block_hash = match get_block_hash(block_number, w3).await {
+            Ok(block_hash) => block_hash,
+            Err(e) => {
+                warn!(block.number = block_number, err=?e, "Could not look up block for sync audit.");
+                result.num_errored += 1;
+                sync_audit_error::create(run.id, block_number, None, e.to_string(), conn).await?;
+                continue;
+            }
+        };
+        for (_, content_key) in block_content_keys(block_number, &block_hash) {
+            let started = Instant::now();
+            let error = match client.api.get_history_content(content_key.clone()).await {
+                Ok(Some(_)) => {
+                    result.num_found += 1;
+                    result
+                        .latencies_ms
+                        .push(started.elapsed().as_millis().min(i32::MAX as u128) as i32);
+                    continue;
+                }
+                Ok(None) => {
+                    result.num_not_found += 1;
+                    "content not found".to_string()
+                }
+                Err(e) => {
+                    result.num_errored += 1;
+                    e.to_string()
+                }
+            };
+            sync_audit_error::create(
+                run.id,
+                block_number,
+                Some(content_key.to_bytes().to_vec()),
+                error,
+                conn,
+            )
+            .await?;
+        }
+    }
+
+    let latency = LatencySummary::from_samples(&mut result.latencies_ms);
+    sync_audit_segment::create(
+        run.id,
+        start_block,
+        end_block,
+        result.num_found,
+        result.num_not_found,
+        result.num_errored,
+        latency,
+        conn,
+    )
+    .await?;
+    info!(
+        sync_audit.id = run.id,
+        segment.start = start_block,
+        segment.end = end_block,
+        found = result.num_found,
+        not_found = result.num_not_found,
+        errored = result.num_errored,
+        latency.median_ms = latency.map(|l| l.median_ms),
+        "Sync audit segment complete."
+    );
+    Ok(end_block)
+}
+
+async fn get_block_hash(
+    block_number: i32,
+    w3: &web3::Web3<web3::transports::Http>,
+) -> Result<[u8; 32]> {
+    let block = w3
+        .eth()
+        .block(BlockId::Number(BlockNumber::Number(block_number.into())))
+        .await?
+        .ok_or(anyhow!("block not found"))?;
+    let block_hash = block.hash.ok_or(anyhow!("block has no hash"))?;
+    Ok(block_hash.to_fixed_bytes())
+}
@@ -0,0 +1,661 @@
+//! Performing a single audit of a content key, and recording its result.
+//!
+//! An audit produces an [AuditOutcome], which is recorded by an [AuditSink]. Audits are
+//! recorded in the database by [DatabaseSink].
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use enr::NodeId;
+use ethportal_api::{utils::bytes::hex_encode, HistoryContentKey, OverlayContentKey};
+use sea_orm::{DatabaseConnection, DbErr, RuntimeErr, TransactionTrait};
+use std::future::Future;
+use std::sync::{
+    atomic::{AtomicU8, Ordering},
+    Arc,
+};
+use std::time::Instant;
+use tokio::time::{sleep, Duration};
+use tracing::{debug, error, info, warn};
+
+use entity::{
+    client_info,
+    content::{self, SubProtocol},
+    content_audit::{self, AuditResult, SelectionStrategy},
+    execution_metadata, node,
+};
+use glados_core::jsonrpc::PortalClient;
+
+use crate::{
+    era1::Era1Store, selection::MERGE_BLOCK_HEIGHT, shutdown::Shutdown, timeout::AuditTimeouts,
+    validation::content_is_valid, AuditTask,
+};
+
+/// Number of times recording an audit is attempted before giving up.
+const PERSIST_ATTEMPTS: u32 = 3;
+
+/// Postgres error codes of transactions aborted because of concurrent writes, a serialization
+/// failure or a deadlock, which may succeed if retried.
+const RETRYABLE_ERROR_CODES: [&str; 2] = ["40001", "40P01"];
+
+/// Everything an audit found out, independent of where it is recorded.
+#[derive(Clone, Debug)]
+pub(crate) struct AuditOutcome {
+    pub content: content::Model,
+    pub strategy: SelectionStrategy,
+    pub result: AuditResult,
+    /// Trace of the content lookup, empty if the Portal node does not support traces or the
+    /// request errored.
+    pub trace: String,
+    /// Whether a trace was requested from the Portal node.
+    pub trace_requested: bool,
+    /// When the Portal node was asked for the content.
+    pub started_at: DateTime<Utc>,
+    /// Time the Portal node took to answer.
+    pub duration: Duration,
+    /// Version string of the Portal node.
+    pub client_info: String,
+    /// Node id of the Portal node.
+    pub node_id: NodeId,
+}
+
+/// Records audits as they start and finish.
+pub(crate) trait AuditSink {
+    /// What the sink keeps of an audit, like a database row.
+    type Record: Send;
+
+    /// Records that an audit is starting, before the Portal node is queried for at most the
+    /// timeout.
+    fn start(
+        &self,
+        task: &AuditTask,
+        client: &PortalClient,
+        timeout: Duration,
+    ) -> impl Future<Output = Result<Self::Record>> + Send;
+
+    /// Records the outcome of an audit that was started.
+    fn finish(
+        &self,
+        started: Self::Record,
+        outcome: &AuditOutcome,
+    ) -> impl Future<Output = Result<Self::Record>> + Send;
+}
+
+/// Records audits in the `content_audit` table.
+///
+/// The audit is recorded as `Pending` before the Portal node is queried, and updated with the
+/// outcome once known. Each of the two is written atomically: the Portal node and its client
+/// with the pending audit in one transaction, and the outcome, with the `audit_latest` row the
+/// `audit_result_latest` trigger updates, in a single statement. Writes aborted by a concurrent
+/// write are retried.
+///
+/// Audits are tracked by the shutdown while they are pending, so that those that do not finish
+/// before exit can be marked as errored.
+pub(crate) struct DatabaseSink {
+    pub conn: DatabaseConnection,
+    pub shutdown: Shutdown,
+}
+
+impl AuditSink for DatabaseSink {
+    type Record = content_audit::Model;
+
+    async fn start(
+        &self,
+        task: &AuditTask,
+        client: &PortalClient,
+        timeout: Duration,
+    ) -> Result<content_audit::Model> {
+        let timeout_ms = timeout.as_millis().try_into().unwrap_or(i32::MAX);
+        let audit = with_retries("start", || async move {
+            let txn = self.conn.begin().await?;
+            let client_info = client_info::get_or_create(client.client_info.clone(), &txn).await?;
+            let node = node::get_or_create(client.enr.node_id(), &txn).await?;
+            let audit = content_audit::create_pending(
+                task.content.id,
+                client_info.id,
+                node.id,
+                task.strategy.clone(),
+                client.supports_trace(),
+                Some(task.selected_at),
+                Some(timeout_ms),
+                &txn,
+            )
+            .await?;
+            txn.commit().await?;
+            Ok::<_, anyhow::Error>(audit)
+        })
+        .await?;
+        self.shutdown.start_audit(audit.id);
+        Ok(audit)
+    }
+
+    async fn finish(
+        &self,
+        started: content_audit::Model,
+        outcome: &AuditOutcome,
+    ) -> Result<content_audit::Model> {
+        let duration_ms = outcome.duration.as_millis().try_into().unwrap_or(i32::MAX);
+        let audit_id = started.id;
+        let audit = with_retries("result", || {
+            content_audit::set_result(
+                started.clone(),
+                outcome.result.clone(),
+                outcome.trace.clone(),
+                Some(duration_ms),
+                &self.conn,
+            )
+        })
+        .await;
+        // An audit whose result could not be recorded is left to be marked as stale.
+        self.shutdown.finish_audit(audit_id);
+        let audit = audit?;
+        if outcome.result != AuditResult::Errored {
+            display_audit_result(
+                &outcome.content,
+                outcome.result == AuditResult::Success,
+                &self.conn,
+            )
+            .await;
+        }
+        Ok(audit)
+    }
+}
+
+/// Performs an audit against a Portal node, recording it in the database.
+///
+/// Requests that error are recorded as `Errored`.
+///
+/// After auditing finishes the thread counter is deprecated. This
+/// applies even if the audit process encounters an error.
+///
+/// The Portal node is given the timeout of the type of content to find it. No audit is
+/// started once shutdown is triggered, and the shutdown waits for those started to end.
+///
+/// Returns the recorded audit, or None if it could not be recorded.
+pub(crate) async fn perform_audit(
+    active_threads: Arc<AtomicU8>,
+    task: AuditTask,
+    client: PortalClient,
+    timeouts: AuditTimeouts,
+    era1: Option<Era1Store>,
+    conn: DatabaseConnection,
+    shutdown: Shutdown,
+) -> Option<content_audit::Model> {
+    let Some(_in_flight) = shutdown.begin_audit() else {
+        debug!(
+            content.id = task.content.id,
+            "Shutting down, audit not started."
+        );
+        active_threads.fetch_sub(1, Ordering::Relaxed);
+        return None;
+    };
+    let timeout = timeouts.for_content(&task.content);
+    let era1 = era1.map(|era1| (era1, conn.clone()));
+    let sink = DatabaseSink { conn, shutdown };
+    let audit = audit_with_sink(&sink, task, client, timeout, era1).await;
+    active_threads.fetch_sub(1, Ordering::Relaxed);
+    audit
+}
+
+/// Performs an audit against a Portal node, recording it with the sink.
+///
+/// Successfully retrieved content is cross-checked with the era1 files, if given, using the
+/// database to look up block numbers.
+///
+/// Returns the sink's record of the audit, or None if it could not be recorded.
+pub(crate) async fn audit_with_sink<S: AuditSink>(
+    sink: &S,
+    task: AuditTask,
+    client: PortalClient,
+    timeout: Duration,
+    era1: Option<(Era1Store, DatabaseConnection)>,
+) -> Option<S::Record> {
+    let started = match sink.start(&task, &client, timeout).await {
+        Ok(started) => started,
+        Err(e) => {
+            error!(
+                content.key=?task.content,
+                err=?e,
+                "Could not record the start of the audit."
+            );
+            return None;
+        }
+    };
+    let outcome = audit_content(task, &client, timeout, era1).await;
+    debug!(
+        content.key = hex_encode(&outcome.content.content_key),
+        audit.strategy = ?outcome.strategy,
+        audit.result = ?outcome.result,
+        audit.started_at = %outcome.started_at,
+        audit.duration_ms = outcome.duration.as_millis(),
+        audit.trace_requested = outcome.trace_requested,
+        client.info = outcome.client_info,
+        client.node_id = %outcome.node_id,
+        "audit finished",
+    );
+    match sink.finish(started, &outcome).await {
+        Ok(record) => Some(record),
+        Err(e) => {
+            error!(
+                content.key=?outcome.content,
+                err=?e,
+                "Could not record audit result."
+            );
+            None
+        }
+    }
+}
+
+/// Asks the Portal node for the content of the task and judges its answer.
+///
+/// Lookups that take longer than the timeout are recorded as `Errored`, like requests that
+/// error. The timeout also covers the lookup of the header that block bodies and receipts are
+/// checked against.
+async fn audit_content(
+    task: AuditTask,
+    client: &PortalClient,
+    timeout: Duration,
+    era1: Option<(Era1Store, DatabaseConnection)>,
+) -> AuditOutcome {
+    debug!(
+        content.key = hex_encode(&task.content.content_key),
+        client.url =? client.api.client,
+        "auditing content",
+    );
+    let trace_requested = client.supports_trace();
+    let started_at = Utc::now();
+    let timer = Instant::now();
+    let lookup = async {
+        if trace_requested {
+            client
+                .api
+                .clone()
+                .get_content_with_trace(&task.content)
+                .await
+                .map_err(|e| {
+                    error!(
+                        content.key=hex_encode(&task.content.content_key),
+                        err=?e,
+                        "Problem requesting content with trace from Portal node."
+                    );
+                })
+        } else {
+            client
+                .api
+                .clone()
+                .get_content(&task.content)
+                .await
+                .map(|c| (c, "".to_owned()))
+                .map_err(|e| {
+                    error!(
+                        content.key=hex_encode(&task.content.content_key),
+                        err=?e,
+                        "Problem requesting content from Portal node."
+                    );
+                })
+        }
+    };
+    let response = match tokio::time::timeout(timeout, lookup).await {
+        Ok(response) => response,
+        Err(_) => {
+            warn!(
+                content.key = hex_encode(&task.content.content_key),
+                timeout.ms = timeout.as_millis(),
+                "Portal node did not answer before the audit timeout."
+            );
+            Err(())
+        }
+    };
+    let duration = timer.elapsed();
+
+    let (result, trace) = match response {
+        // If content was absent audit result is 'fail', if it was corrupt it is 'invalid'.
+        Ok((Some(content_bytes), trace)) => {
+            // Looking up the header to check the content against counts toward the timeout.
+            let validation = tokio::time::timeout(
+                timeout.saturating_sub(duration),
+                content_is_valid(&task.content, &content_bytes.raw, client),
+            )
+            .await;
+            match validation {
+                Ok(Ok(true)) => match era1 {
+                    Some((era1, conn)) => (
+                        verify_with_era1(era1, &task.content, content_bytes.raw, &conn).await,
+                        trace,
+                    ),
+                    None => (AuditResult::Success, trace),
+                },
+                Ok(Ok(false)) => (AuditResult::InvalidContent, trace),
+                Ok(Err(e)) => {
+                    error!(
+                        content.key=hex_encode(&task.content.content_key),
+                        err=?e,
+                        "Could not check the content returned by the Portal node."
+                    );
+                    (AuditResult::Errored, trace)
+                }
+                Err(_) => {
+                    warn!(
+                        content.key = hex_encode(&task.content.content_key),
+                        timeout.ms = timeout.as_millis(),
+                        "Content check did not finish before the audit timeout."
+                    );
+                    (AuditResult::Errored, trace)
+                }
+            }
+        }
+        Ok((None, trace)) => (AuditResult::Failure, trace),
+        Err(()) => (AuditResult::Errored, "".to_owned()),
+    };
+
+    AuditOutcome {
+        content: task.content,
+        strategy: task.strategy,
+        result,
+        trace,
+        trace_requested,
+        started_at,
+        duration,
+        client_info: client.client_info.clone(),
+        node_id: client.enr.node_id(),
+    }
+}
+
+/// Runs a database write of an audit, retrying it if it was aborted by a concurrent write.
+async fn with_retries<T, F, Fut>(write: &str, mut run: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempt = 1;
+    loop {
+        match run().await {
+            Err(e) if attempt < PERSIST_ATTEMPTS && is_retryable(&e) => {
+                warn!(
+                    audit.write = write,
+                    attempt,
+                    err=?e,
+                    "Audit write conflicted with a concurrent write, retrying."
+                );
+                sleep(Duration::from_millis(50 * 2u64.pow(attempt))).await;
+                attempt += 1;
+            }
+            outcome => return outcome,
+        }
+    }
+}
+
+/// Whether an error is a Postgres serialization failure or deadlock, which can be retried.
+fn is_retryable(error: &anyhow::Error) -> bool {
+    let runtime_error = match error.downcast_ref::<DbErr>() {
+        Some(DbErr::Exec(e)) | Some(DbErr::Query(e)) | Some(DbErr::Conn(e)) => e,
+        _ => return false,
+    };
+    match runtime_error {
+        RuntimeErr::SqlxError(e) => {
+            e.as_database_error()
+                .and_then(|e| e.code())
+                .is_some_and(|code| {
+                    RETRYABLE_ERROR_CODES
+                        .iter()
+                        .any(|retryable| code == *retryable)
+                })
+        }
+        _ => false,
+    }
+}
+
+/// Compares successfully retrieved history content with the canonical era1 data.
+///
+/// Content that cannot be checked (post-merge, no block metadata, missing era1 file)
+/// is treated as a success. Errors while checking it mark the audit as errored.
+async fn verify_with_era1(
+    era1: Era1Store,
+    content: &content::Model,
+    content_bytes: Vec<u8>,
+    conn: &DatabaseConnection,
+) -> AuditResult {
+    if content.protocol_id != SubProtocol::History {
+        return AuditResult::Success;
+    }
+    let block_number = match execution_metadata::get(content.id, conn).await {
+        Ok(Some(metadata)) if metadata.block_number < MERGE_BLOCK_HEIGHT => {
+            metadata.block_number as u64
+        }
+        Ok(_) => return AuditResult::Success,
+        Err(e) => {
+            error!(content.key=hex_encode(&content.content_key), err=?e, "Problem getting block metadata for era1 check.");
+            return AuditResult::Errored;
+        }
+    };
+    let content_key = match HistoryContentKey::try_from_bytes(&content.content_key) {
+        Ok(key) => key,
+        Err(e) => {
+            error!(content.key=hex_encode(&content.content_key), err=?e, "Failed to decode history content key.");
+            return AuditResult::Errored;
+        }
+    };
+
+    let era1_check = tokio::task::spawn_blocking(move || {
+        era1.content_matches(&content_key, &content_bytes, block_number)
+    })
+    .await;
+    match era1_check {
+        Ok(Ok(Some(true))) | Ok(Ok(None)) => AuditResult::Success,
+        Ok(Ok(Some(false))) => {
+            warn!(
+                content.key = hex_encode(&content.content_key),
+                block = block_number,
+                "Audited content does not match era1 data."
+            );
+            AuditResult::InvalidContent
+        }
+        Ok(Err(e)) => {
+            error!(content.key=hex_encode(&content.content_key), err=?e, "Problem comparing content with era1 data.");
+            AuditResult::Errored
+        }
+        Err(e) => {
+            error!(content.key=hex_encode(&content.content_key), err=?e, "era1 comparison task failed.");
+            AuditResult::Errored
+        }
+    }
+}
+
+/// Logs the result of a completed audit.
+async fn display_audit_result(
+    content: &content::Model,
+    audit_result: bool,
+    conn: &DatabaseConnection,
+) {
+    match content.protocol_id {
+        SubProtocol::History => {
+            display_history_audit_result(content, audit_result, conn).await;
+        }
+        SubProtocol::Beacon => {
+            info!(
+                content.key = hex_encode(&content.content_key),
+                audit.pass = audit_result,
+                content.protocol = "Beacon",
+            );
+        }
+        SubProtocol::State => {
+            info!(
+                content.key = hex_encode(&content.content_key),
+                audit.pass = audit_result,
+                content.protocol = "State",
+            );
+        }
+    }
+}
+
+async fn display_history_audit_result(
+    content: &content::Model,
+    audit_result: bool,
+    conn: &DatabaseConnection,
+) {
+    match execution_metadata::get(content.id, conn).await {
+        Ok(Some(b)) => {
+            info!(
+                content.key=hex_encode(&content.content_key),
+                audit.pass=?audit_result,
+                block = b.block_number,
+                "History content audit"
+            );
+        }
+        Ok(None) => {
+            info!(
+                content.key=hex_encode(&content.content_key),
+                audit.pass=?audit_result,
+                "Block metadata absent for history key."
+            );
+        }
+        Err(e) => error!(
+                    content.key=hex_encode(&content.content_key),
+                    err=?e,
+                    "Problem getting block metadata for history key."),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use enr::CombinedKey;
+    use ethportal_api::types::enr::Enr;
+    use glados_core::{connection::ConnectionSettings, jsonrpc::PortalApi};
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use entity::content_audit::HistorySelectionStrategy;
+
+    /// Keeps audits in memory instead of the database.
+    #[derive(Default)]
+    struct MockSink {
+        fail_start: bool,
+        started: Mutex<Vec<i32>>,
+        finished: Mutex<Vec<AuditOutcome>>,
+    }
+
+    impl AuditSink for MockSink {
+        type Record = i32;
+
+        async fn start(
+            &self,
+            task: &AuditTask,
+            _client: &PortalClient,
+            _timeout: Duration,
+        ) -> Result<i32> {
+            if self.fail_start {
+                anyhow::bail!("sink unavailable");
+            }
+            self.started.lock().unwrap().push(task.content.id);
+            Ok(task.content.id)
+        }
+
+        async fn finish(&self, started: i32, outcome: &AuditOutcome) -> Result<i32> {
+            self.finished.lock().unwrap().push(outcome.clone());
+            Ok(started)
+        }
+    }
+
+    /// A client for a Portal node that is not listening.
+    async fn unreachable_client() -> PortalClient {
+        client_at("http://127.0.0.1:1".to_owned()).await
+    }
+
+    async fn client_at(url: String) -> PortalClient {
+        PortalClient {
+            api: PortalApi::new(url, None, &ConnectionSettings::default(), false)
+                .await
+                .unwrap(),
+            client_info: "trin v0.1.0".to_owned(),
+            enr: Enr::builder()
+                .build(&CombinedKey::generate_secp256k1())
+                .unwrap(),
+        }
+    }
+
+    fn task() -> AuditTask {
+        AuditTask {
+            strategy: SelectionStrategy::History(HistorySelectionStrategy::Random),
+            content: content::Model {
+                id: 7,
+                protocol_id: SubProtocol::History,
+                content_key: vec![0x00; 33],
+                content_id: vec![0x01; 32],
+                first_available_at: Utc::now(),
+                source: None,
+                archived: false,
+            },
+            selected_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_node_records_errored_outcome() {
+        let sink = MockSink::default();
+        let client = unreachable_client().await;
+        let node_id = client.enr.node_id();
+
+        let record = audit_with_sink(&sink, task(), client, Duration::from_secs(5), None).await;
+
+        assert_eq!(record, Some(7));
+        assert_eq!(*sink.started.lock().unwrap(), vec![7]);
+        let finished = sink.finished.lock().unwrap();
+        assert_eq!(finished.len(), 1);
+        let outcome = &finished[0];
+        assert_eq!(outcome.result, AuditResult::Errored);
+        assert_eq!(outcome.content.id, 7);
+        assert!(outcome.trace.is_empty());
+        assert!(outcome.trace_requested);
+        assert_eq!(outcome.client_info, "trin v0.1.0");
+        assert_eq!(outcome.node_id, node_id);
+    }
+
+    #[tokio::test]
+    async fn test_failed_start_skips_audit() {
+        let sink = MockSink {
+            fail_start: true,
+            ..Default::default()
+        };
+
+        let record = audit_with_sink(
+            &sink,
+            task(),
+            unreachable_client().await,
+            Duration::from_secs(5),
+            None,
+        )
+        .await;
+
+        assert_eq!(record, None);
+        assert!(sink.finished.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_lookup_past_timeout_records_errored_outcome() {
+        // A Portal node that accepts requests but never answers them.
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut connections = vec![];
+            while let Ok((stream, _)) = listener.accept().await {
+                connections.push(stream);
+            }
+        });
+        let sink = MockSink::default();
+
+        let record = audit_with_sink(
+            &sink,
+            task(),
+            client_at(url).await,
+            Duration::from_millis(200),
+            None,
+        )
+        .await;
+
+        assert_eq!(record, Some(7));
+        let finished = sink.finished.lock().unwrap();
+        assert_eq!(finished.len(), 1);
+        assert_eq!(finished[0].result, AuditResult::Errored);
+        assert!(finished[0].duration < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,80 @@
+//! Time an audit gives the Portal node to find the content, by type of content.
+//!
+//! Large content like block bodies and receipts takes longer to transfer than headers, so it
+//! can be given more time, while lookups of small content that will not succeed are cut short.
+use std::collections::HashMap;
+
+use tokio::time::Duration;
+
+use entity::content;
+use glados_core::jsonrpc::REQUEST_TIMEOUT;
+
+/// Timeouts of audits, by the first byte of the content key.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AuditTimeouts {
+    /// For types of content without a timeout of their own.
+    pub default: Duration,
+    pub by_content_type: HashMap<u8, Duration>,
+}
+
+/// Audits of all types wait as long as Portal client requests are given.
+impl Default for AuditTimeouts {
+    fn default() -> Self {
+        AuditTimeouts {
+            default: REQUEST_TIMEOUT,
+            by_content_type: HashMap::new(),
+        }
+    }
+}
+
+impl AuditTimeouts {
+    /// Returns the timeout of an audit of the content.
+    pub fn for_content(&self, content: &content::Model) -> Duration {
+        content
+            .content_key
+            .first()
+            .and_then(|selector| self.by_content_type.get(selector))
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use entity::content::SubProtocol;
+
+    use super::*;
+
+    fn content(content_key: Vec<u8>) -> content::Model {
+        content::Model {
+            id: 1,
+            protocol_id: SubProtocol::History,
+            content_key,
+            content_id: vec![0x01; 32],
+            first_available_at: Utc::now(),
+            source: None,
+            archived: false,
+        }
+    }
+
+    #[test]
+    fn timeout_by_content_type() {
+        let timeouts = AuditTimeouts {
+            default: Duration::from_secs(20),
+            by_content_type: HashMap::from([(0x01, Duration::from_secs(90))]),
+        };
+        assert_eq!(
+            timeouts.for_content(&content(vec![0x01; 33])),
+            Duration::from_secs(90)
+        );
+        assert_eq!(
+            timeouts.for_content(&content(vec![0x00; 33])),
+            Duration::from_secs(20)
+        );
+        assert_eq!(
+            timeouts.for_content(&content(vec![])),
+            Duration::from_secs(20)
+        );
+    }
+}
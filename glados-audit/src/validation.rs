@@ -1,36 +1,66 @@
+//! Checks of the content values returned by audits, to tell corrupt content apart from absent
+//! content.
+//!
+//! Headers are checked against the block hash or block number of their key. Block bodies and
+//! receipts are checked against the roots of their header, which is looked up from the same
+//! Portal node and only trusted if it hashes to the block hash of the key.
+use alloy_primitives::B256;
+use anyhow::{anyhow, Result};
 use entity::content;
+use ethportal_api::types::execution::header::Header;
 use ethportal_api::utils::bytes::hex_encode;
 use ethportal_api::{BeaconContentKey, HistoryContentKey, OverlayContentKey};
 use ethportal_api::{BeaconContentValue, ContentValue, HistoryContentValue};
-use tracing::warn;
+use glados_core::jsonrpc::PortalClient;
+use tracing::{debug, warn};
 
 /// Checks that content bytes correspond to a correctly formatted
-/// content value.
-pub fn content_is_valid(content: &content::Model, content_bytes: &[u8]) -> bool {
+/// content value, that matches its key.
+///
+/// Block bodies and receipts whose header cannot be found are only checked for their format.
+/// Returns an error if the request for their header errors, as they could not be checked.
+pub async fn content_is_valid(
+    content: &content::Model,
+    content_bytes: &[u8],
+    client: &PortalClient,
+) -> Result<bool> {
     match content.protocol_id {
         content::SubProtocol::History => {
             let content_key = match HistoryContentKey::try_from_bytes(&content.content_key) {
                 Ok(key) => key,
                 Err(err) => {
                     warn!(err=?err, content.content_key=?content.content_key, "Failed to decode history content key.");
-                    return false;
+                    return Ok(false);
                 }
             };
-            validate_history(&content_key, content_bytes)
+            let header = match &content_key {
+                HistoryContentKey::BlockBody(key) => {
+                    get_trusted_header(key.block_hash, client).await?
+                }
+                HistoryContentKey::BlockReceipts(key) => {
+                    get_trusted_header(key.block_hash, client).await?
+                }
+                _ => None,
+            };
+            Ok(validate_history(
+                &content_key,
+                content_bytes,
+                header.as_ref(),
+            ))
         }
         content::SubProtocol::State => {
             warn!("State content validation not yet implemented.");
-            true
+            Ok(true)
         }
         content::SubProtocol::Beacon => {
             let content_key = match BeaconContentKey::try_from_bytes(&content.content_key) {
                 Ok(key) => key,
                 Err(err) => {
                     warn!(err=?err, content.content_key=?content.content_key, "Failed to decode beacon content key.");
-                    return false;
+                    return Ok(false);
                 }
             };
-            validate_beacon(&content_key, content_bytes)
+            Ok(validate_beacon(&content_key, content_bytes))
         }
     }
 }
@@ -72,7 +102,11 @@ fn validate_beacon(content_key: &BeaconContentKey, content_bytes: &[u8]) -> bool
     }
 }
 
-fn validate_history(content_key: &HistoryContentKey, content_bytes: &[u8]) -> bool {
+fn validate_history(
+    content_key: &HistoryContentKey,
+    content_bytes: &[u8],
+    header: Option<&Header>,
+) -> bool {
     // check deserialization is valid
     let content: HistoryContentValue = match HistoryContentValue::decode(content_key, content_bytes)
     {
@@ -86,33 +120,130 @@ fn validate_history(content_key: &HistoryContentKey, content_bytes: &[u8]) -> bo
     // check nature of content is valid
     match content {
         HistoryContentValue::BlockHeaderWithProof(h) => {
-            // Reconstruct the key using the block header contents (RLP then hash).
-            let computed_hash = h.header.hash();
-            let computed_key = HistoryContentKey::new_block_header_by_hash(computed_hash);
-            match content_key == &computed_key {
+            match header_matches_key(content_key, h.header.number, h.header.hash()) {
                 true => true,
                 false => {
                     warn!(
                         content.key = hex_encode(content_key.to_bytes()),
                         content.value = hex_encode(content_bytes),
-                        "computed header hash did not match expected"
+                        "computed header hash or number did not match expected"
+                    );
+                    false
+                }
+            }
+        }
+        HistoryContentValue::BlockBody(_) | HistoryContentValue::Receipts(_) => {
+            let Some(header) = header else {
+                return true;
+            };
+            match matches_header(&content, header) {
+                Ok(true) => true,
+                Ok(false) => {
+                    warn!(
+                        content.key = hex_encode(content_key.to_bytes()),
+                        "computed roots did not match the block header"
                     );
                     false
                 }
+                Err(e) => {
+                    warn!(content.key=hex_encode(content_key.to_bytes()), err=?e, "could not compute roots of history content");
+                    false
+                }
             }
         }
+    }
+}
+
+/// Whether a header with the given number and hash is the one its key asks for.
+///
+/// Headers looked up by number are checked against the number, the header proof being what ties
+/// that number to the header. Other headers are checked against the block hash of their key.
+fn header_matches_key(
+    content_key: &HistoryContentKey,
+    block_number: u64,
+    block_hash: B256,
+) -> bool {
+    // Reconstruct the key using the block header contents (RLP then hash, or number).
+    let computed_key = match content_key {
+        HistoryContentKey::BlockHeaderByNumber(_) => {
+            HistoryContentKey::new_block_header_by_number(block_number)
+        }
+        _ => HistoryContentKey::new_block_header_by_hash(block_hash),
+    };
+    content_key == &computed_key
+}
+
+/// Whether the roots of a block body or receipts match those of their block header.
+pub(crate) fn matches_header(content: &HistoryContentValue, header: &Header) -> Result<bool> {
+    Ok(match content {
         HistoryContentValue::BlockBody(b) => {
-            // Reconstruct the key using the block body contents.
-            let _computed_tx_root = b.transactions_root();
-            let _computed_uncles_root = b.uncles_root();
-            warn!("Need to call trusted provider to check block body correctness.");
-            true
+            b.transactions_root()? == header.transactions_root
+                && b.uncles_root() == header.uncles_hash
         }
-        HistoryContentValue::Receipts(r) => {
-            // Reconstruct the key using the block body contents.
-            let _computed_receipts_root = r.root();
-            warn!("Need to call trusted provider to check receipts correctness.");
-            true
+        HistoryContentValue::Receipts(r) => r.root()? == header.receipts_root,
+        HistoryContentValue::BlockHeaderWithProof(_) => false,
+    })
+}
+
+/// Looks up the header of a block from the Portal node, returning it only if it hashes to the
+/// block hash. Returns an error if the request errors.
+async fn get_trusted_header(block_hash: [u8; 32], client: &PortalClient) -> Result<Option<Header>> {
+    let content_key = HistoryContentKey::new_block_header_by_hash(block_hash);
+    let content = match client.api.get_history_content(content_key.clone()).await {
+        Ok(Some(content)) => content,
+        Ok(None) => {
+            debug!(
+                content.key = hex_encode(content_key.to_bytes()),
+                "Header not found, checking block content format only."
+            );
+            return Ok(None);
+        }
+        Err(e) => {
+            return Err(anyhow!(
+                "Problem requesting header {} to check block content: {e:?}",
+                hex_encode(content_key.to_bytes())
+            ));
+        }
+    };
+    match HistoryContentValue::decode(&content_key, &content.raw) {
+        Ok(HistoryContentValue::BlockHeaderWithProof(h))
+            if HistoryContentKey::new_block_header_by_hash(h.header.hash()) == content_key =>
+        {
+            Ok(Some(h.header))
         }
+        _ => {
+            warn!(
+                content.key = hex_encode(content_key.to_bytes()),
+                "Header returned to check block content is invalid."
+            );
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undecodable_history_content_is_invalid() {
+        let content_key = HistoryContentKey::new_block_header_by_hash([0x01; 32]);
+        assert!(!validate_history(&content_key, &[0x01, 0x02], None));
+    }
+
+    #[test]
+    fn test_header_matches_key() {
+        let block_hash = B256::repeat_byte(0x01);
+        let by_number = HistoryContentKey::new_block_header_by_number(1_000_000);
+        assert!(header_matches_key(&by_number, 1_000_000, block_hash));
+        assert!(!header_matches_key(&by_number, 1_000_001, block_hash));
+
+        let by_hash = HistoryContentKey::new_block_header_by_hash(block_hash);
+        assert!(header_matches_key(&by_hash, 1_000_000, block_hash));
+        assert!(!header_matches_key(
+            &by_hash,
+            1_000_000,
+            B256::repeat_byte(0x02)
+        ));
     }
 }
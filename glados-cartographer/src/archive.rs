@@ -0,0 +1,299 @@
+//! Moves the nodes found by old censuses to cold storage files, and loads them back.
+//!
+//! Each census is written to its own gzip compressed newline-delimited JSON file: a line with
+//! the census, then a line for each node it found, with the ENR it was found with. The census
+//! row and the stats computed from its nodes stay in the database, only its nodes are deleted,
+//! so archived censuses keep their place in the history.
+use std::{
+    fs::{self, File},
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, TimeDelta, Utc};
+use ethportal_api::{
+    utils::bytes::{hex_decode, hex_encode},
+    Enr,
+};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use sea_orm::{ActiveEnum, ActiveModelTrait, ActiveValue::Set, DatabaseConnection, EntityTrait};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use entity::{census, census_node, content::SubProtocol, record};
+
+/// A line of an archive file.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ArchiveLine {
+    Census(ArchivedCensus),
+    Node(ArchivedNode),
+}
+
+/// The census an archive file holds the nodes of, to recreate it if it was deleted since.
+#[derive(Debug, Serialize, Deserialize)]
+struct ArchivedCensus {
+    id: i32,
+    started_at: DateTime<Utc>,
+    duration: i32,
+    sub_network: i32,
+    num_checked: Option<i32>,
+    num_client_info_parsed: Option<i32>,
+    num_pongs: Option<i32>,
+    num_error_pongs: Option<i32>,
+    num_undecodable_pongs: Option<i32>,
+    num_unexpected_pongs: Option<i32>,
+    num_timeouts: Option<i32>,
+    /// Missing from archives written before client diversity was computed.
+    #[serde(default)]
+    client_effective_number: Option<f32>,
+    #[serde(default)]
+    client_entropy: Option<f32>,
+    /// Hex encoded. Missing from archives written before the target was kept.
+    #[serde(default)]
+    target_node_id: Option<String>,
+}
+
+/// A node found by the census, with the base64 encoded ENR it was found with.
+#[derive(Debug, Serialize, Deserialize)]
+struct ArchivedNode {
+    enr: String,
+    surveyed_at: DateTime<Utc>,
+    /// Hex encoded, big-endian.
+    data_radius: String,
+    data_radius_high: i64,
+    ping_attempts: Option<i32>,
+    ping_latency_ms: Option<i32>,
+    is_self: bool,
+    udp_reachable: Option<bool>,
+    tcp_reachable: Option<bool>,
+}
+
+impl From<&census::Model> for ArchivedCensus {
+    fn from(census: &census::Model) -> Self {
+        ArchivedCensus {
+            id: census.id,
+            started_at: census.started_at,
+            duration: census.duration,
+            sub_network: census.sub_network as i32,
+            num_checked: census.num_checked,
+            num_client_info_parsed: census.num_client_info_parsed,
+            num_pongs: census.num_pongs,
+            num_error_pongs: census.num_error_pongs,
+            num_undecodable_pongs: census.num_undecodable_pongs,
+            num_unexpected_pongs: census.num_unexpected_pongs,
+            num_timeouts: census.num_timeouts,
+            client_effective_number: census.client_effective_number,
+            client_entropy: census.client_entropy,
+            target_node_id: census.target_node_id.as_deref().map(hex_encode),
+        }
+    }
+}
+
+impl ArchivedNode {
+    fn new(node: &census_node::Model, record: &record::Model) -> Self {
+        ArchivedNode {
+            enr: record.raw.clone(),
+            surveyed_at: node.surveyed_at,
+            data_radius: hex_encode(&node.data_radius),
+            data_radius_high: node.data_radius_high,
+            ping_attempts: node.ping_attempts,
+            ping_latency_ms: node.ping_latency_ms,
+            is_self: node.is_self,
+            udp_reachable: node.udp_reachable,
+            tcp_reachable: node.tcp_reachable,
+        }
+    }
+}
+
+/// Path of the archive file of a census.
+fn archive_path(dir: &Path, census_id: i32) -> PathBuf {
+    dir.join(format!("census-{census_id}.jsonl.gz"))
+}
+
+/// Archives the nodes of every census started more than `older_than_days` days ago, and deletes
+/// them from the database.
+pub async fn archive_censuses(
+    dir: &Path,
+    older_than_days: u32,
+    conn: &DatabaseConnection,
+) -> Result<()> {
+    fs::create_dir_all(dir)?;
+    let before = Utc::now() - TimeDelta::days(older_than_days.into());
+    let censuses = census::get_unarchived_before(before, conn).await?;
+    info!(
+        censuses = censuses.len(),
+        before = %before,
+        "Archiving the nodes of old censuses."
+    );
+    for census in &censuses {
+        let nodes = census_node::get_with_records(census.id, conn).await?;
+        let path = archive_path(dir, census.id);
+        write_archive(&path, census, &nodes)?;
+        let deleted = census::archive_nodes(census.id, conn).await?;
+        if deleted != nodes.len() as u64 {
+            bail!(
+                "Census {} had {} nodes when archived, but {deleted} were deleted.",
+                census.id,
+                nodes.len()
+            );
+        }
+        info!(census.id = census.id, nodes = deleted, path = ?path, "Archived census.");
+    }
+    Ok(())
+}
+
+/// Writes the archive file of a census, then reads it back to check that it holds every node.
+///
+/// The file is only moved into place once it is verified, so a file at the archive path is
+/// always complete.
+fn write_archive(
+    path: &Path,
+    census: &census::Model,
+    nodes: &[(census_node::Model, record::Model)],
+) -> Result<()> {
+    let partial_path = path.with_extension("gz.partial");
+    let mut writer = BufWriter::new(GzEncoder::new(
+        File::create(&partial_path)?,
+        Compression::default(),
+    ));
+    serde_json::to_writer(
+        &mut writer,
+        &ArchiveLine::Census(ArchivedCensus::from(census)),
+    )?;
+    writer.write_all(b"\n")?;
+    for (node, record) in nodes {
+        serde_json::to_writer(
+            &mut writer,
+            &ArchiveLine::Node(ArchivedNode::new(node, record)),
+        )?;
+        writer.write_all(b"\n")?;
+    }
+    writer
+        .into_inner()
+        .map_err(|e| anyhow!("Could not flush archive file: {e}"))?
+        .finish()?
+        .sync_all()?;
+
+    let (archived_census, archived_nodes) = read_archive(&partial_path)?;
+    if archived_census.id != census.id || archived_nodes.len() != nodes.len() {
+        bail!(
+            "Archive of census {} holds census {} with {} nodes, expected {} nodes.",
+            census.id,
+            archived_census.id,
+            archived_nodes.len(),
+            nodes.len()
+        );
+    }
+    fs::rename(&partial_path, path)?;
+    Ok(())
+}
+
+/// Reads the census and nodes of an archive file.
+fn read_archive(path: &Path) -> Result<(ArchivedCensus, Vec<ArchivedNode>)> {
+    let reader = BufReader::new(GzDecoder::new(File::open(path)?));
+    let mut census = None;
+    let mut nodes = vec![];
+    for line in reader.lines() {
+        match serde_json::from_str(&line?)? {
+            ArchiveLine::Census(archived) => {
+                if census.replace(archived).is_some() {
+                    bail!("Archive file {path:?} holds more than one census.");
+                }
+            }
+            ArchiveLine::Node(node) => nodes.push(node),
+        }
+    }
+    let census = census.ok_or_else(|| anyhow!("Archive file {path:?} holds no census."))?;
+    Ok((census, nodes))
+}
+
+/// Puts back the nodes of the censuses archived in the given files.
+///
+/// Censuses deleted since they were archived are recreated. Censuses whose nodes were not
+/// archived, or were already loaded back, are skipped.
+pub async fn load_censuses(files: &[PathBuf], conn: &DatabaseConnection) -> Result<()> {
+    for path in files {
+        let (archived_census, archived_nodes) = read_archive(path)?;
+        match census::Entity::find_by_id(archived_census.id)
+            .one(conn)
+            .await?
+        {
+            Some(existing) if existing.nodes_archived_at.is_none() => {
+                warn!(
+                    census.id = existing.id,
+                    path = ?path,
+                    "The nodes of the census are not archived, skipping."
+                );
+                continue;
+            }
+            Some(_) => {}
+            None => {
+                recreate_census(&archived_census, conn).await?;
+            }
+        }
+
+        let enrs = archived_nodes
+            .iter()
+            .map(|node| {
+                Enr::from_str(&node.enr).map_err(|e| anyhow!("Could not decode archived ENR: {e}"))
+            })
+            .collect::<Result<Vec<Enr>>>()?;
+        let records = record::get_or_create_many(&enrs, conn).await?;
+        let sub_network = SubProtocol::try_from_value(&archived_census.sub_network)?;
+        let nodes = archived_nodes
+            .into_iter()
+            .zip(records)
+            .map(|(node, record)| {
+                Ok(census_node::Model {
+                    id: 0,
+                    census_id: archived_census.id,
+                    record_id: record.id,
+                    surveyed_at: node.surveyed_at,
+                    data_radius: hex_decode(&node.data_radius)?,
+                    data_radius_high: node.data_radius_high,
+                    sub_network,
+                    ping_attempts: node.ping_attempts,
+                    ping_latency_ms: node.ping_latency_ms,
+                    is_self: node.is_self,
+                    udp_reachable: node.udp_reachable,
+                    tcp_reachable: node.tcp_reachable,
+                })
+            })
+            .collect::<Result<Vec<census_node::Model>>>()?;
+        let num_nodes = nodes.len();
+        census::restore_nodes(archived_census.id, nodes, conn).await?;
+        info!(census.id = archived_census.id, nodes = num_nodes, path = ?path, "Loaded census.");
+    }
+    Ok(())
+}
+
+/// Recreates a census that was deleted after its nodes were archived, with its original id.
+async fn recreate_census(archived: &ArchivedCensus, conn: &DatabaseConnection) -> Result<()> {
+    census::ActiveModel {
+        id: Set(archived.id),
+        started_at: Set(archived.started_at),
+        duration: Set(archived.duration),
+        sub_network: Set(SubProtocol::try_from_value(&archived.sub_network)?),
+        num_checked: Set(archived.num_checked),
+        num_client_info_parsed: Set(archived.num_client_info_parsed),
+        num_pongs: Set(archived.num_pongs),
+        num_error_pongs: Set(archived.num_error_pongs),
+        num_undecodable_pongs: Set(archived.num_undecodable_pongs),
+        num_unexpected_pongs: Set(archived.num_unexpected_pongs),
+        num_timeouts: Set(archived.num_timeouts),
+        client_effective_number: Set(archived.client_effective_number),
+        client_entropy: Set(archived.client_entropy),
+        nodes_archived_at: Set(Some(Utc::now())),
+        target_node_id: Set(archived
+            .target_node_id
+            .as_deref()
+            .map(hex_decode)
+            .transpose()?),
+    }
+    .insert(conn)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,139 @@
+//! Resolves the IP address in the latest ENR of each node to a country and autonomous system,
+//! using MaxMind format databases.
+use std::{net::IpAddr, path::PathBuf, str::FromStr, time::Duration};
+
+use anyhow::{anyhow, Result};
+use ethportal_api::Enr;
+use maxminddb::{MaxMindDBError, Reader};
+use sea_orm::DatabaseConnection;
+use serde::Deserialize;
+use tokio::time;
+use tracing::{debug, error, info};
+
+use entity::node_geo;
+
+/// Time between looking for nodes that have not been located yet.
+const LOCATE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Records located per database query.
+const LOCATE_BATCH_SIZE: u64 = 1000;
+
+/// The fields of a database entry that are kept. Country databases (like GeoLite2-Country) fill
+/// `country`, ASN databases (like GeoLite2-ASN) fill the autonomous system fields.
+#[derive(Deserialize)]
+struct GeoEntry {
+    country: Option<GeoCountry>,
+    autonomous_system_number: Option<u32>,
+    autonomous_system_organization: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GeoCountry {
+    iso_code: Option<String>,
+}
+
+/// Where an IP address is, as far as the databases know.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Location {
+    pub country_code: Option<String>,
+    pub asn: Option<i32>,
+    pub asn_organization: Option<String>,
+}
+
+/// MaxMind format databases, looked up in the order they were given.
+pub struct GeoIp {
+    readers: Vec<Reader<Vec<u8>>>,
+}
+
+impl GeoIp {
+    pub fn open(paths: &[PathBuf]) -> Result<GeoIp> {
+        let readers = paths
+            .iter()
+            .map(|path| {
+                Reader::open_readfile(path)
+                    .map_err(|e| anyhow!("Could not open GeoIP database {}: {e}", path.display()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(GeoIp { readers })
+    }
+
+    /// Looks the IP address up in every database, keeping the first value found for each field.
+    pub fn locate(&self, ip: IpAddr) -> Result<Location> {
+        let mut location = Location::default();
+        for reader in &self.readers {
+            let entry: GeoEntry = match reader.lookup(ip) {
+                Ok(entry) => entry,
+                Err(MaxMindDBError::AddressNotFoundError(_)) => continue,
+                Err(e) => return Err(e.into()),
+            };
+            if location.country_code.is_none() {
+                location.country_code = entry.country.and_then(|country| country.iso_code);
+            }
+            if location.asn.is_none() {
+                location.asn = entry
+                    .autonomous_system_number
+                    .and_then(|asn| i32::try_from(asn).ok());
+            }
+            if location.asn_organization.is_none() {
+                location.asn_organization = entry.autonomous_system_organization;
+            }
+        }
+        Ok(location)
+    }
+}
+
+/// The IP address an ENR advertises, preferring IPv4.
+fn enr_ip(enr: &Enr) -> Option<IpAddr> {
+    enr.ip4()
+        .map(IpAddr::V4)
+        .or_else(|| enr.ip6().map(IpAddr::V6))
+}
+
+/// Locates the nodes whose latest ENR has not been located yet, then keeps checking for new
+/// ENRs.
+pub async fn locate_nodes(geoip: GeoIp, conn: DatabaseConnection) {
+    let mut interval = time::interval(LOCATE_INTERVAL);
+    loop {
+        interval.tick().await;
+        match locate_new_records(&geoip, &conn).await {
+            Ok(0) => {}
+            Ok(located) => info!(records = located, "Located nodes from their ENRs"),
+            Err(e) => error!(err=?e, "Could not locate nodes"),
+        }
+    }
+}
+
+/// Returns the number of records located.
+async fn locate_new_records(geoip: &GeoIp, conn: &DatabaseConnection) -> Result<usize> {
+    let mut located = 0;
+    loop {
+        let records = node_geo::get_unlocated(LOCATE_BATCH_SIZE, conn).await?;
+        for record in &records {
+            // A record without a usable IP address is saved with an unknown location, so it is
+            // not looked at again until the node publishes a new ENR.
+            let location = match Enr::from_str(&record.raw).ok().as_ref().and_then(enr_ip) {
+                Some(ip) => geoip.locate(ip)?,
+                None => {
+                    debug!(
+                        record.id = record.record_id,
+                        "No IP address to locate in ENR"
+                    );
+                    Location::default()
+                }
+            };
+            node_geo::upsert(
+                record.node_id,
+                Some(record.record_id),
+                location.country_code,
+                location.asn,
+                location.asn_organization,
+                conn,
+            )
+            .await?;
+        }
+        located += records.len();
+        if (records.len() as u64) < LOCATE_BATCH_SIZE {
+            return Ok(located);
+        }
+    }
+}
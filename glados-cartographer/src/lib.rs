@@ -2,76 +2,188 @@ use alloy_primitives::B256;
 use alloy_primitives::U256;
 use anyhow::{bail, Result};
 use chrono::{DateTime, Duration, Utc};
-use clap::Parser;
 use cli::Args;
 use cli::PortalSubnet;
 use enr::NodeId;
+use ethportal_api::generate_random_remote_enr;
+use ethportal_api::jsonrpsee::core::client::{ClientT, Error as ClientError};
 use ethportal_api::Enr;
-use ethportal_api::{generate_random_remote_enr, jsonrpsee::http_client::HttpClientBuilder};
-use ethportal_api::{BeaconNetworkApiClient, HistoryNetworkApiClient, StateNetworkApiClient};
+use ethportal_api::{
+    BeaconNetworkApiClient, Discv5ApiClient, HistoryNetworkApiClient, StateNetworkApiClient,
+};
 use sea_orm::DatabaseConnection;
 use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
-use std::time::Duration as StdDuration;
+use std::time::{Duration as StdDuration, Instant};
 use tokio::{
     sync::{
         mpsc::{self, Receiver, Sender},
-        RwLock, Semaphore,
+        oneshot, RwLock, Semaphore,
     },
     time::{self},
 };
 use tracing::{debug, error, info, warn};
 
-use entity::{census, census_node, record};
-use glados_core::jsonrpc::TransportConfig;
+use entity::{
+    census, census_alternate_enr,
+    census_client_stats::{self, LivenessCounts, PingOutcome, UNKNOWN_CLIENT},
+    census_incident::{self, CensusDiagnostics},
+    census_node::{self, NewCensusNode, PingMeasurement, Reachability},
+    census_node_error::{self, CensusNodeErrorKind, NodeFailure},
+    client_version_sighting, node, record,
+};
+use glados_core::{
+    connection::ConnectionSettings,
+    jsonrpc::{build_http_client, PortalHttpClient, TransportConfig},
+    proxy::ProxySettings,
+};
 
-use crate::cli::TransportType;
+use crate::{cli::TransportType, reachability::EndpointProber};
 
+pub mod archive;
 pub mod cli;
+pub mod geo;
+pub mod reachability;
+
+/// ENRs buffered before they are written to the database together.
+const RECORD_BATCH_SIZE: usize = 256;
+
+/// Longest time an ENR is buffered before it is written to the database.
+const RECORD_FLUSH_INTERVAL: StdDuration = StdDuration::from_secs(5);
+
+/// Longest time a request to the Portal node may take. Covers the recursive lookup that starts
+/// a census, which involves many nodes.
+const CENSUS_REQUEST_TIMEOUT: StdDuration = StdDuration::from_secs(62);
+
+/// Delay before the first retry of a failed request about a single node, doubled for each
+/// retry after it.
+const RETRY_BACKOFF: StdDuration = StdDuration::from_millis(250);
+
+/// Nodes that ran audits within this window are flagged as run by this deployment.
+const SELF_NODE_AUDIT_WINDOW: Duration = Duration::hours(1);
 
 /// Configuration created from CLI arguments.
 #[derive(Clone, Debug)]
 pub struct CartographerConfig {
     /// For Glados-related data.
     pub database_url: String,
-    /// For communication with a Portal Network node.
-    pub transport: TransportConfig,
+    /// For communication with the Portal Network nodes that census requests are spread across.
+    pub transports: Vec<TransportConfig>,
     /// Defines the rate at which the network is probed in seconds
     pub census_interval: u64,
     /// Total number of concurrent requests to portal client
     pub concurrency: usize,
     /// Which portal subnetwork to target
     pub subnetwork: PortalSubnet,
+    /// Routing table distances to enumerate for each node
+    pub distances: RangeInclusive<u16>,
+    /// Whether to adjust the lowest enumerated distance after each census
+    pub adaptive_distances: bool,
+    /// Percentage below the trailing average node count that records a census incident
+    pub incident_drop_percent: u8,
+    /// Number of previous censuses in the trailing average node count
+    pub incident_trailing_censuses: u64,
+    /// PING requests sent to a node before it is considered offline
+    pub ping_attempts: u8,
+    /// Longest time a PING request may take
+    pub ping_timeout: StdDuration,
+    /// Longest time a routing table request may take
+    pub enumeration_timeout: StdDuration,
+    /// Times a failed routing table request is retried
+    pub retries: u8,
+    /// Nodes run by this deployment, in addition to those detected for each census
+    pub self_node_ids: HashSet<NodeId>,
+    /// Whether to probe the endpoints declared in the ENRs of the nodes that answer a PING
+    pub probe_reachability: bool,
+    /// Proxies that connections to the Portal nodes go through
+    pub proxies: ProxySettings,
+    /// Whether to accept compressed responses from the Portal nodes
+    pub compression: bool,
+    /// MaxMind format databases to locate nodes with, none to leave them unlocated
+    pub geoip_dbs: Vec<PathBuf>,
 }
 
 impl CartographerConfig {
-    pub fn from_args() -> Result<CartographerConfig> {
-        let args = Args::parse();
-        let transport: TransportConfig = match args.transport {
-            TransportType::IPC => match args.ipc_path {
-                Some(p) => TransportConfig::IPC(p),
+    pub fn from_args(args: Args) -> Result<CartographerConfig> {
+        let transports: Vec<TransportConfig> = match args.transport {
+            None => bail!("The '--transport' flag is required to take censuses."),
+            Some(TransportType::IPC) => match args.ipc_path {
+                Some(p) => vec![TransportConfig::IPC(p)],
                 None => {
                     bail!("The '--ipc-path' flag is required if '--transport ipc' variant is selected.")
                 }
             },
-            TransportType::HTTP => match args.http_url {
-                Some(h) => TransportConfig::HTTP(h),
-                None => {
+            Some(TransportType::HTTP) => {
+                if args.http_url.is_empty() {
                     bail!("The '--http-url' flag is required if '--transport http' variant is selected.");
                 }
-            },
+                args.http_url
+                    .into_iter()
+                    .map(TransportConfig::HTTP)
+                    .collect()
+            }
         };
+        if args.min_distance > args.max_distance {
+            bail!(
+                "'--min-distance' ({}) must not be greater than '--max-distance' ({}).",
+                args.min_distance,
+                args.max_distance
+            );
+        }
+        let ping_timeout = StdDuration::from_millis(args.ping_timeout);
+        let enumeration_timeout = StdDuration::from_millis(args.enumeration_timeout);
+        if ping_timeout.max(enumeration_timeout) >= CENSUS_REQUEST_TIMEOUT {
+            bail!(
+                "'--ping-timeout' and '--enumeration-timeout' must be shorter than {} seconds.",
+                CENSUS_REQUEST_TIMEOUT.as_secs()
+            );
+        }
         Ok(CartographerConfig {
             database_url: args.database_url,
-            transport,
+            transports,
             census_interval: args.census_interval,
             concurrency: args.concurrency,
             subnetwork: args.subnetwork,
+            distances: args.min_distance..=args.max_distance,
+            adaptive_distances: args.adaptive_distances,
+            incident_drop_percent: args.incident_drop_percent,
+            incident_trailing_censuses: args.incident_trailing_censuses,
+            ping_attempts: args.ping_attempts,
+            ping_timeout,
+            enumeration_timeout,
+            retries: args.retries,
+            self_node_ids: args
+                .self_node_id
+                .iter()
+                .map(|node_id| NodeId::new(&node_id.0))
+                .collect(),
+            probe_reachability: args.probe_reachability,
+            proxies: ProxySettings {
+                default: args.proxy,
+                overrides: args.client_proxy,
+            },
+            compression: args.compression,
+            geoip_dbs: args.geoip_db,
         })
     }
 }
 
 pub async fn run_glados_cartographer(conn: DatabaseConnection, config: CartographerConfig) {
+    if !config.geoip_dbs.is_empty() {
+        match geo::GeoIp::open(&config.geoip_dbs) {
+            Ok(geoip) => {
+                tokio::spawn(geo::locate_nodes(geoip, conn.clone()));
+            }
+            Err(err) => {
+                error!(err=?err, "Could not open GeoIP databases");
+                return;
+            }
+        }
+    }
     tokio::spawn(orchestrate_dht_census(config, conn));
 
     debug!("setting up CTRL+C listener");
@@ -84,26 +196,97 @@ pub async fn run_glados_cartographer(conn: DatabaseConnection, config: Cartograp
 
 async fn orchestrate_dht_census(config: CartographerConfig, conn: DatabaseConnection) {
     let mut interval = time::interval(StdDuration::from_secs(config.census_interval));
+    let mut distances = config.distances.clone();
 
     loop {
         interval.tick().await;
 
-        perform_dht_census(config.clone(), conn.clone()).await;
+        let occupancy = perform_dht_census(config.clone(), distances.clone(), conn.clone()).await;
+        if !config.adaptive_distances {
+            continue;
+        }
+        if let Some(occupancy) = occupancy {
+            let adapted = adapt_distances(&distances, &occupancy);
+            if adapted != distances {
+                info!(
+                    from=?distances,
+                    to=?adapted,
+                    "Adjusting enumerated routing table distances"
+                );
+                distances = adapted;
+            }
+        }
+    }
+}
+
+/// Moves the lowest enumerated distance based on how many nodes each distance returned.
+///
+/// If the lowest distance returned any nodes, closer buckets may be occupied too, so one more
+/// distance is enumerated next time. If the two lowest distances returned none, the lowest is
+/// dropped, keeping one empty bucket as a margin. The highest distance never changes.
+fn adapt_distances(
+    distances: &RangeInclusive<u16>,
+    occupancy: &HashMap<u16, usize>,
+) -> RangeInclusive<u16> {
+    let (lowest, highest) = (*distances.start(), *distances.end());
+    let nodes_at = |distance: u16| occupancy.get(&distance).copied().unwrap_or(0);
+
+    if nodes_at(lowest) > 0 && lowest > 1 {
+        lowest - 1..=highest
+    } else if nodes_at(lowest) == 0 && nodes_at(lowest + 1) == 0 && lowest < highest {
+        lowest + 1..=highest
+    } else {
+        lowest..=highest
     }
 }
 
 struct DHTCensusRecord {
     enr: Enr,
-    record_id: i32,
     data_radius: U256,
     surveyed_at: DateTime<Utc>,
+    ping: PingMeasurement,
+    reachability: Reachability,
+}
+
+/// Why a node failed its liveness check.
+struct DHTCensusFailure {
+    kind: CensusNodeErrorKind,
+    surveyed_at: DateTime<Utc>,
+    attempts: i32,
+    duration_ms: i32,
+    message: String,
+}
+
+/// Requests to the task that writes ENRs to the database.
+#[derive(Debug)]
+enum RecordWrite {
+    /// Buffer an ENR, to be written with others.
+    Save(Enr),
+    /// Write all buffered ENRs, then signal completion.
+    Flush(oneshot::Sender<()>),
 }
 
 struct DHTCensus {
     known: RwLock<HashSet<[u8; 32]>>,
     pub alive: RwLock<HashMap<[u8; 32], DHTCensusRecord>>,
+    /// Every distinct ENR seen for each node, as a node may present different ENRs to
+    /// different peers.
+    enrs: RwLock<HashMap<[u8; 32], Vec<Enr>>>,
+    /// Database id of the ENR record of each node, once written.
+    records: RwLock<HashMap<[u8; 32], i32>>,
     finished: RwLock<HashSet<[u8; 32]>>,
     errored: RwLock<HashSet<[u8; 32]>>,
+    /// Why each node that failed its liveness check did so.
+    failures: RwLock<HashMap<[u8; 32], DHTCensusFailure>>,
+    /// Routing table distances enumerated for each node.
+    distances: RangeInclusive<u16>,
+    /// Number of ENRs returned at each distance, over all enumerated nodes.
+    occupancy: RwLock<HashMap<u16, usize>>,
+    /// Outcomes of the liveness checks of each client's nodes.
+    liveness: RwLock<HashMap<String, LivenessCounts>>,
+    /// Number of routing table distances requested, and how many of the requests failed.
+    enumerations: AtomicUsize,
+    enumeration_errors: AtomicUsize,
     pub started_at: DateTime<Utc>,
 }
 
@@ -118,19 +301,32 @@ struct DHTCensusStats {
 }
 
 impl DHTCensus {
-    fn new() -> Self {
+    fn new(distances: RangeInclusive<u16>) -> Self {
         let known: RwLock<HashSet<[u8; 32]>> = RwLock::new(HashSet::new());
         let alive: RwLock<HashMap<[u8; 32], DHTCensusRecord>> = RwLock::new(HashMap::new());
+        let enrs: RwLock<HashMap<[u8; 32], Vec<Enr>>> = RwLock::new(HashMap::new());
+        let records: RwLock<HashMap<[u8; 32], i32>> = RwLock::new(HashMap::new());
         let finished: RwLock<HashSet<[u8; 32]>> = RwLock::new(HashSet::new());
         let errored: RwLock<HashSet<[u8; 32]>> = RwLock::new(HashSet::new());
+        let failures: RwLock<HashMap<[u8; 32], DHTCensusFailure>> = RwLock::new(HashMap::new());
+        let occupancy: RwLock<HashMap<u16, usize>> = RwLock::new(HashMap::new());
+        let liveness: RwLock<HashMap<String, LivenessCounts>> = RwLock::new(HashMap::new());
 
         let started_at = chrono::offset::Utc::now();
 
         DHTCensus {
             known,
             alive,
+            enrs,
+            records,
             finished,
             errored,
+            failures,
+            distances,
+            occupancy,
+            liveness,
+            enumerations: AtomicUsize::new(0),
+            enumeration_errors: AtomicUsize::new(0),
             started_at,
         }
     }
@@ -186,20 +382,42 @@ impl DHTCensus {
         known.insert(node_id.raw())
     }
 
-    async fn add_alive(&self, enr: Enr, record_id: i32, data_radius: U256) {
+    async fn add_alive(
+        &self,
+        enr: Enr,
+        data_radius: U256,
+        ping: PingMeasurement,
+        reachability: Reachability,
+    ) {
         if self.alive.read().await.contains_key(&enr.node_id().raw()) {
             return;
         }
         let census_record = DHTCensusRecord {
             enr,
-            record_id,
             data_radius,
             surveyed_at: Utc::now(),
+            ping,
+            reachability,
         };
         let mut alive = self.alive.write().await;
         alive.insert(census_record.enr.node_id().raw(), census_record);
     }
 
+    async fn add_seen_enr(&self, enr: &Enr) {
+        let mut enrs = self.enrs.write().await;
+        let seen = enrs.entry(enr.node_id().raw()).or_default();
+        if !seen.contains(enr) {
+            seen.push(enr.clone());
+        }
+    }
+
+    async fn add_records(&self, records: impl IntoIterator<Item = (NodeId, i32)>) {
+        let mut stored = self.records.write().await;
+        for (node_id, record_id) in records {
+            stored.insert(node_id.raw(), record_id);
+        }
+    }
+
     async fn add_finished(&self, node_id: NodeId) -> bool {
         let mut finished = self.finished.write().await;
         finished.insert(node_id.raw())
@@ -209,6 +427,34 @@ impl DHTCensus {
         let mut errored = self.errored.write().await;
         errored.insert(node_id.raw())
     }
+
+    async fn add_failed(&self, node_id: NodeId, failure: DHTCensusFailure) -> bool {
+        let mut failures = self.failures.write().await;
+        failures.insert(node_id.raw(), failure);
+        drop(failures);
+        self.add_errored(node_id).await
+    }
+
+    async fn add_liveness(&self, enr: &Enr, outcome: PingOutcome) {
+        let client = census_client_stats::enr_client(enr);
+        let mut liveness = self.liveness.write().await;
+        liveness
+            .entry(client.unwrap_or(UNKNOWN_CLIENT).to_owned())
+            .or_default()
+            .add(client.is_some(), outcome);
+    }
+
+    fn add_enumeration(&self, failed: bool) {
+        self.enumerations.fetch_add(1, Ordering::Relaxed);
+        if failed {
+            self.enumeration_errors.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    async fn add_occupancy(&self, distance: u16, count: usize) {
+        let mut occupancy = self.occupancy.write().await;
+        *occupancy.entry(distance).or_default() += count;
+    }
 }
 
 /// Performs a full census of the DHT
@@ -217,29 +463,49 @@ impl DHTCensus {
 /// 2. Use RFN with a random node-id to initialize our view of the network
 /// 3. For each node-id, enumerate its routing table entries until we find empty buckets.
 /// 4. Track all seen node-ids until we find no new ones.
-async fn perform_dht_census(config: CartographerConfig, conn: DatabaseConnection) {
-    let client = match &config.transport {
-        TransportConfig::HTTP(http_url) => {
-            match HttpClientBuilder::default()
-                .request_timeout(StdDuration::from_secs(62))
-                .build(http_url.as_ref())
-            {
-                Ok(client) => {
-                    debug!(client.http_url=?http_url, "Portal JSON-RPC HTTP client initialized");
-                    client
-                }
-                Err(err) => {
-                    error!(client.http_url=?http_url, err=?err, "Error initializing Portal JSON-RPC HTTP client");
-                    return;
+///
+/// Returns the number of ENRs found at each enumerated distance, if the census completed.
+async fn perform_dht_census(
+    config: CartographerConfig,
+    distances: RangeInclusive<u16>,
+    conn: DatabaseConnection,
+) -> Option<HashMap<u16, usize>> {
+    // A single client per Portal node is shared by every request of the census, so that
+    // connections to the Portal node are reused.
+    let mut clients: Vec<PortalHttpClient> = vec![];
+    for transport in &config.transports {
+        let client = match transport {
+            TransportConfig::HTTP(http_url) => {
+                match build_http_client(
+                    http_url.as_ref(),
+                    CENSUS_REQUEST_TIMEOUT,
+                    None,
+                    &ConnectionSettings {
+                        proxy: config.proxies.for_client(http_url).cloned(),
+                        ..Default::default()
+                    },
+                    config.compression,
+                ) {
+                    Ok(client) => {
+                        debug!(client.http_url=?http_url, "Portal JSON-RPC HTTP client initialized");
+                        client
+                    }
+                    Err(err) => {
+                        error!(client.http_url=?http_url, err=?err, "Error initializing Portal JSON-RPC HTTP client");
+                        return None;
+                    }
                 }
             }
-        }
-        TransportConfig::IPC(_path) => panic!("not implemented"),
-    };
+            TransportConfig::IPC(_path) => panic!("not implemented"),
+        };
+        clients.push(client);
+    }
+    // The first Portal node is the one reported on for the census, e.g. in incidents.
+    let client = clients.first()?;
 
     let target_enr = generate_random_remote_enr().1;
     let target = target_enr.node_id();
-    let census = Arc::new(DHTCensus::new());
+    let census = Arc::new(DHTCensus::new(distances));
 
     // Initial un-processed ENRs to be pinged
     let (to_ping_tx, to_ping_rx): (Sender<Enr>, Receiver<Enr>) = mpsc::channel(256);
@@ -247,33 +513,51 @@ async fn perform_dht_census(config: CartographerConfig, conn: DatabaseConnection
     // ENRs that have been pinged and now need to have their routing tables enumerated
     let (to_enumerate_tx, to_enumerate_rx): (Sender<Enr>, Receiver<Enr>) = mpsc::channel(256);
 
+    // ENRs to be written to the database. Bounded, so a slow database holds back liveness checks.
+    let (to_record_tx, to_record_rx): (Sender<RecordWrite>, Receiver<RecordWrite>) =
+        mpsc::channel(RECORD_BATCH_SIZE * 4);
+
     info!(
         target.node_id=?B256::from(target.raw()),
         "Starting DHT census",
     );
 
-    // Initialize our search with a random-ish set of ENRs
-    let find_nodes = match config.subnetwork {
-        PortalSubnet::Beacon => BeaconNetworkApiClient::recursive_find_nodes(&client, target),
-        PortalSubnet::State => StateNetworkApiClient::recursive_find_nodes(&client, target),
-        PortalSubnet::History => HistoryNetworkApiClient::recursive_find_nodes(&client, target),
-    };
-
-    let initial_enrs = match find_nodes.await {
-        Ok(initial_enrs) => initial_enrs,
-        Err(err) => {
-            error!(target.node_id=?B256::from(target.raw()), err=?err, "Error during census initialization");
-            return;
+    // Initialize our search with a random-ish set of ENRs, from the routing tables of every
+    // Portal node so that none of them biases the census.
+    let mut initial_enrs: Vec<Enr> = vec![];
+    let mut initialized = false;
+    for client in &clients {
+        let find_nodes = match config.subnetwork {
+            PortalSubnet::Beacon => BeaconNetworkApiClient::recursive_find_nodes(client, target),
+            PortalSubnet::State => StateNetworkApiClient::recursive_find_nodes(client, target),
+            PortalSubnet::History => HistoryNetworkApiClient::recursive_find_nodes(client, target),
+        };
+        match find_nodes.await {
+            Ok(enrs) => {
+                initialized = true;
+                for enr in enrs {
+                    if census.add_known(enr.node_id()).await {
+                        initial_enrs.push(enr);
+                    }
+                }
+            }
+            Err(err) => {
+                warn!(target.node_id=?B256::from(target.raw()), err=?err, "Error finding initial nodes of census");
+            }
         }
-    };
+    }
+    if !initialized {
+        error!(target.node_id=?B256::from(target.raw()), "Error during census initialization");
+        return None;
+    }
 
+    let initial_nodes = initial_enrs.len();
     for enr in initial_enrs {
-        census.add_known(enr.node_id()).await;
         match to_ping_tx.send(enr).await {
             Ok(_) => (),
             Err(err) => {
                 error!(err=?err, "Error during census initialization");
-                return;
+                return None;
             }
         };
     }
@@ -283,16 +567,31 @@ async fn perform_dht_census(config: CartographerConfig, conn: DatabaseConnection
     let ping_limiter = Arc::new(Semaphore::new(num_permits));
     let enumeration_limiter = Arc::new(Semaphore::new(num_permits));
 
+    let record_handle =
+        tokio::task::spawn(write_records(to_record_rx, census.clone(), conn.to_owned()));
+    let prober = match config.probe_reachability {
+        true => match EndpointProber::start(config.ping_timeout).await {
+            Ok(prober) => Some(Arc::new(prober)),
+            Err(err) => {
+                error!(err=?err, "Could not start probing declared endpoints");
+                None
+            }
+        },
+        false => None,
+    };
     let ping_handle = tokio::task::spawn(orchestrate_liveliness_checks(
         to_ping_rx,
+        clients.clone(),
         to_enumerate_tx.clone(),
+        to_record_tx.clone(),
         census.clone(),
         config.to_owned(),
-        conn.to_owned(),
+        prober,
         ping_limiter.clone(),
     ));
     let enumerate_handle = tokio::task::spawn(orchestrate_routing_table_enumerations(
         to_enumerate_rx,
+        clients.clone(),
         to_ping_tx.clone(),
         census.clone(),
         config.to_owned(),
@@ -345,6 +644,19 @@ async fn perform_dht_census(config: CartographerConfig, conn: DatabaseConnection
     ping_handle.abort();
     enumerate_handle.abort();
 
+    // Every ENR was queued before its liveliness check finished, so once the queue is flushed
+    // all records of the census are stored.
+    let (flushed_tx, flushed_rx) = oneshot::channel();
+    if to_record_tx
+        .send(RecordWrite::Flush(flushed_tx))
+        .await
+        .is_err()
+        || flushed_rx.await.is_err()
+    {
+        error!("ENR writer stopped before the census finished");
+    }
+    record_handle.abort();
+
     let duration: u32 = census.duration().num_seconds().try_into().unwrap();
 
     let census_model =
@@ -352,50 +664,424 @@ async fn perform_dht_census(config: CartographerConfig, conn: DatabaseConnection
             Ok(census_model) => census_model,
             Err(err) => {
                 error!(err=?err, "Error saving census model to database");
-                return;
+                return None;
             }
         };
 
-    for (_, census_record) in census.alive.read().await.iter() {
-        match census_node::create(
-            census_model.id,
-            census_record.record_id,
-            census_record.data_radius,
-            census_record.surveyed_at,
-            config.subnetwork.into(),
-            &conn,
-        )
-        .await
-        {
-            Ok(census_node_model) => debug!(
-                census.id = census_model.id,
-                census.node.id = census_node_model.id,
-                "Saved new census_node record"
-            ),
-            Err(err) => error!(
+    let census_model = match census::set_target_node_id(census_model.clone(), target, &conn).await {
+        Ok(census_model) => census_model,
+        Err(err) => {
+            error!(census.id=census_model.id, err=?err, "Error saving census target node id");
+            census_model
+        }
+    };
+
+    let liveness = census.liveness.read().await;
+    let mut liveness_totals = LivenessCounts::default();
+    for counts in liveness.values() {
+        liveness_totals.merge(counts);
+    }
+    if let Err(err) = census_client_stats::create_many(census_model.id, &liveness, &conn).await {
+        error!(census.id=census_model.id, err=?err, "Error saving census client stats");
+    }
+    record_client_versions(&census, &census_model, &conn).await;
+    let census_model =
+        match census::set_liveness_counts(census_model.clone(), liveness_totals, &conn).await {
+            Ok(census_model) => census_model,
+            Err(err) => {
+                error!(census.id=census_model.id, err=?err, "Error saving census liveness counts");
+                census_model
+            }
+        };
+    let census_model = record_client_diversity(&census, census_model, &conn).await;
+
+    let diagnostics = CensusDiagnostics {
+        initial_nodes: initial_nodes as i32,
+        liveness_checks: liveness_totals.checked,
+        liveness_errors: liveness_totals.checked - liveness_totals.pongs,
+        enumerations: census.enumerations.load(Ordering::Relaxed) as i32,
+        enumeration_errors: census.enumeration_errors.load(Ordering::Relaxed) as i32,
+        routing_table: None,
+    };
+    let node_count = census.alive.read().await.len();
+    record_incident(
+        &census_model,
+        node_count,
+        diagnostics,
+        client,
+        &config,
+        &conn,
+    )
+    .await;
+
+    let self_node_ids = find_self_node_ids(&clients, &config, &conn).await;
+    record_canonical_enrs(&census, &census_model, &conn).await;
+    let records = census.records.read().await;
+    let mut nodes = vec![];
+    for (node_id, census_record) in census.alive.read().await.iter() {
+        let Some(record_id) = records.get(node_id).copied() else {
+            warn!(
                 census.id=census_model.id,
-                census_node.record_id=census_record.record_id,
-                census_node.data_radius=?census_record.data_radius,
-                census_node.surveyed_at=?census_record.surveyed_at,
-                err=?err,
-                "Error saving new census_node record"
-            ),
+                enr.node_id=?B256::from(*node_id),
+                "Skipping census_node record of node whose ENR was not saved"
+            );
+            continue;
         };
+        nodes.push(NewCensusNode {
+            record_id,
+            data_radius: census_record.data_radius,
+            surveyed_at: census_record.surveyed_at,
+            ping: census_record.ping,
+            is_self: self_node_ids.contains(node_id),
+            reachability: census_record.reachability,
+        });
+    }
+    match census_node::create_many(census_model.id, config.subnetwork.into(), &nodes, &conn).await {
+        Ok(()) => debug!(
+            census.id = census_model.id,
+            nodes = nodes.len(),
+            "Saved census_node records"
+        ),
+        Err(err) => error!(
+            census.id=census_model.id,
+            nodes=nodes.len(),
+            err=?err,
+            "Error saving census_node records"
+        ),
     }
 
+    record_failures(&census, &census_model, &records, &conn).await;
+
     info!("Census finished");
+    let occupancy = census.occupancy.read().await.clone();
+    Some(occupancy)
+}
+
+/// Sub-component of perform_dht_census()
+///
+/// Selects the ENR with the highest sequence number as canonical for the nodes that presented
+/// more than one ENR, so that their census_node records use it, and records the others as
+/// alternates. Among ENRs with the same sequence number, the first one seen is kept.
+async fn record_canonical_enrs(
+    census: &DHTCensus,
+    census_model: &census::Model,
+    conn: &DatabaseConnection,
+) {
+    let mut canonical: Vec<Enr> = vec![];
+    let mut alternates: Vec<Vec<Enr>> = vec![];
+    for seen in census.enrs.read().await.values() {
+        let Some((first, others)) = seen.split_first() else {
+            continue;
+        };
+        if others.is_empty() {
+            continue;
+        }
+        let mut best = first;
+        for enr in others {
+            if enr.seq() > best.seq() {
+                best = enr;
+            }
+        }
+        alternates.push(seen.iter().filter(|enr| *enr != best).cloned().collect());
+        canonical.push(best.clone());
+    }
+    if canonical.is_empty() {
+        return;
+    }
+    warn!(
+        census.id = census_model.id,
+        nodes = canonical.len(),
+        "Nodes presented more than one ENR in the census"
+    );
+
+    let all_enrs: Vec<Enr> = canonical
+        .iter()
+        .cloned()
+        .chain(alternates.iter().flatten().cloned())
+        .collect();
+    let records = match record::get_or_create_many(&all_enrs, conn).await {
+        Ok(records) => records,
+        Err(err) => {
+            error!(census.id=census_model.id, err=?err, "Error saving alternate ENRs");
+            return;
+        }
+    };
+    let (canonical_records, alternate_records) = records.split_at(canonical.len());
+    let mut alternate_records = alternate_records.iter();
+    let mut pairs: Vec<(i32, i32)> = vec![];
+    for (canonical_record, node_alternates) in canonical_records.iter().zip(&alternates) {
+        for alternate_record in alternate_records.by_ref().take(node_alternates.len()) {
+            // Records are stored by sequence number, so an alternate ENR with the same sequence
+            // number as the canonical one has no record of its own.
+            if alternate_record.id != canonical_record.id {
+                pairs.push((canonical_record.id, alternate_record.id));
+            }
+        }
+    }
+    census
+        .add_records(
+            canonical
+                .iter()
+                .zip(canonical_records)
+                .map(|(enr, record)| (enr.node_id(), record.id)),
+        )
+        .await;
+    if let Err(err) = census_alternate_enr::create_many(census_model.id, &pairs, conn).await {
+        error!(census.id=census_model.id, err=?err, "Error saving census alternate ENRs");
+    }
+}
+
+/// Sub-component of perform_dht_census()
+///
+/// Records why the nodes that dropped out of the census failed their liveness checks.
+async fn record_failures(
+    census: &DHTCensus,
+    census_model: &census::Model,
+    records: &HashMap<[u8; 32], i32>,
+    conn: &DatabaseConnection,
+) {
+    let failures: Vec<NodeFailure> = census
+        .failures
+        .read()
+        .await
+        .iter()
+        .filter_map(|(node_id, failure)| {
+            Some(NodeFailure {
+                record_id: records.get(node_id).copied()?,
+                surveyed_at: failure.surveyed_at,
+                kind: failure.kind,
+                attempts: failure.attempts,
+                duration_ms: failure.duration_ms,
+                message: Some(failure.message.clone()),
+            })
+        })
+        .collect();
+    if let Err(err) = census_node_error::create_many(census_model.id, &failures, conn).await {
+        error!(census.id=census_model.id, err=?err, "Error saving census node errors");
+    }
+}
+
+/// Sub-component of perform_dht_census()
+///
+/// Returns the ids of the nodes run by this deployment: those configured, the Portal nodes
+/// running the census, and the Portal nodes that recently ran audits.
+async fn find_self_node_ids<C: ClientT + Send + Sync>(
+    clients: &[C],
+    config: &CartographerConfig,
+    conn: &DatabaseConnection,
+) -> HashSet<[u8; 32]> {
+    let mut self_node_ids: HashSet<[u8; 32]> = config
+        .self_node_ids
+        .iter()
+        .map(|node_id| node_id.raw())
+        .collect();
+    for client in clients {
+        match Discv5ApiClient::node_info(client).await {
+            Ok(node_info) => {
+                self_node_ids.insert(node_info.enr.node_id().raw());
+            }
+            Err(err) => warn!(err=?err, "Error fetching node info of a census node"),
+        }
+    }
+    match node::get_auditing_since(Utc::now() - SELF_NODE_AUDIT_WINDOW, conn).await {
+        Ok(nodes) => self_node_ids.extend(nodes.iter().map(|node| node.get_node_id().raw())),
+        Err(err) => error!(err=?err, "Error looking up auditing nodes"),
+    }
+    debug!(
+        count = self_node_ids.len(),
+        "Found nodes run by this deployment"
+    );
+    self_node_ids
+}
+
+/// Sub-component of perform_dht_census()
+///
+/// Records the versions of the known clients run by the nodes the census found, to follow
+/// release rollouts.
+async fn record_client_versions(
+    census: &DHTCensus,
+    census_model: &census::Model,
+    conn: &DatabaseConnection,
+) {
+    let sightings: HashSet<(String, String)> = census
+        .alive
+        .read()
+        .await
+        .values()
+        .filter_map(|census_record| {
+            let client = census_client_stats::enr_client(&census_record.enr)?;
+            let version = census_client_stats::enr_client_version(&census_record.enr)?;
+            Some((client.to_owned(), version))
+        })
+        .collect();
+    if let Err(err) =
+        client_version_sighting::record_many(&sightings, census_model.started_at, conn).await
+    {
+        error!(census.id=census_model.id, err=?err, "Error saving client version sightings");
+    }
+}
+
+/// Sub-component of perform_dht_census()
+///
+/// Computes the diversity of the clients of the live nodes whose client is known, and records
+/// it with the census.
+async fn record_client_diversity(
+    census: &DHTCensus,
+    census_model: census::Model,
+    conn: &DatabaseConnection,
+) -> census::Model {
+    let mut client_counts: HashMap<&'static str, usize> = HashMap::new();
+    for census_record in census.alive.read().await.values() {
+        if let Some(client) = census_client_stats::enr_client(&census_record.enr) {
+            *client_counts.entry(client).or_default() += 1;
+        }
+    }
+    let Some(diversity) = census::ClientDiversity::from_counts(client_counts.into_values()) else {
+        return census_model;
+    };
+    match census::set_client_diversity(census_model.clone(), diversity, conn).await {
+        Ok(census_model) => census_model,
+        Err(err) => {
+            error!(census.id=census_model.id, err=?err, "Error saving census client diversity");
+            census_model
+        }
+    }
+}
+
+/// Sub-component of perform_dht_census()
+///
+/// Records an incident with the census diagnostics if the census found much fewer nodes than
+/// the censuses before it, adding the routing table of the Portal node running the census.
+async fn record_incident<C: ClientT + Send + Sync>(
+    census_model: &census::Model,
+    node_count: usize,
+    mut diagnostics: CensusDiagnostics,
+    client: &C,
+    config: &CartographerConfig,
+    conn: &DatabaseConnection,
+) {
+    let trailing_average = match census_incident::trailing_node_count(
+        config.subnetwork.into(),
+        census_model.started_at,
+        config.incident_trailing_censuses,
+        conn,
+    )
+    .await
+    {
+        Ok(Some(trailing_average)) => trailing_average,
+        Ok(None) => return,
+        Err(err) => {
+            error!(census.id=census_model.id, err=?err, "Error looking up trailing census node count");
+            return;
+        }
+    };
+    if !is_sudden_drop(node_count, trailing_average, config.incident_drop_percent) {
+        return;
+    }
+    warn!(
+        census.id = census_model.id,
+        node_count, trailing_average, "Census found far fewer nodes than usual, recording incident"
+    );
+
+    let routing_table = match config.subnetwork {
+        PortalSubnet::History => HistoryNetworkApiClient::routing_table_info(client).await,
+        PortalSubnet::Beacon => BeaconNetworkApiClient::routing_table_info(client).await,
+        PortalSubnet::State => StateNetworkApiClient::routing_table_info(client).await,
+    };
+    diagnostics.routing_table = match routing_table {
+        Ok(routing_table) => serde_json::to_string(&routing_table).ok(),
+        Err(err) => {
+            warn!(err=?err, "Error fetching routing table of the census node");
+            None
+        }
+    };
+    if let Err(err) = census_incident::create(
+        census_model.id,
+        node_count as i32,
+        trailing_average,
+        diagnostics,
+        conn,
+    )
+    .await
+    {
+        error!(census.id=census_model.id, err=?err, "Error saving census incident");
+    }
+}
+
+/// Whether a node count is more than `drop_percent` percent below the trailing average.
+fn is_sudden_drop(node_count: usize, trailing_average: f64, drop_percent: u8) -> bool {
+    (node_count as f64) < trailing_average * (1.0 - f64::from(drop_percent) / 100.0)
 }
 
 /// Sub-component of perform_dht_census()
 ///
+/// Writes queued ENRs to the database in batches, when enough have been buffered or the
+/// flush interval passes, and notes the record id of each node in the census.
+async fn write_records(
+    mut to_record_rx: mpsc::Receiver<RecordWrite>,
+    census: Arc<DHTCensus>,
+    conn: DatabaseConnection,
+) {
+    let mut buffer: Vec<Enr> = Vec::with_capacity(RECORD_BATCH_SIZE);
+    let mut interval = time::interval(RECORD_FLUSH_INTERVAL);
+    loop {
+        tokio::select! {
+            write = to_record_rx.recv() => match write {
+                Some(RecordWrite::Save(enr)) => {
+                    buffer.push(enr);
+                    if buffer.len() >= RECORD_BATCH_SIZE {
+                        flush_records(&mut buffer, &census, &conn).await;
+                    }
+                }
+                Some(RecordWrite::Flush(flushed_tx)) => {
+                    flush_records(&mut buffer, &census, &conn).await;
+                    let _ = flushed_tx.send(());
+                }
+                None => {
+                    flush_records(&mut buffer, &census, &conn).await;
+                    return;
+                }
+            },
+            _ = interval.tick() => flush_records(&mut buffer, &census, &conn).await,
+        }
+    }
+}
+
+async fn flush_records(buffer: &mut Vec<Enr>, census: &DHTCensus, conn: &DatabaseConnection) {
+    if buffer.is_empty() {
+        return;
+    }
+    let enrs = std::mem::take(buffer);
+    match record::get_or_create_many(&enrs, conn).await {
+        Ok(records) => {
+            debug!(count = enrs.len(), "Saved ENRs");
+            census
+                .add_records(
+                    enrs.iter()
+                        .zip(records)
+                        .map(|(enr, record)| (enr.node_id(), record.id)),
+                )
+                .await;
+        }
+        Err(err) => error!(count = enrs.len(), err=?err, "Error saving ENRs to database"),
+    }
+}
+
+/// Sub-component of perform_dht_census()
+///
+#[allow(clippy::too_many_arguments)]
 async fn orchestrate_liveliness_checks(
     mut to_ping_rx: mpsc::Receiver<Enr>,
+    clients: Vec<PortalHttpClient>,
     to_enumerate_tx: mpsc::Sender<Enr>,
+    to_record_tx: mpsc::Sender<RecordWrite>,
     census: Arc<DHTCensus>,
     config: CartographerConfig,
-    conn: DatabaseConnection,
+    prober: Option<Arc<EndpointProber>>,
     limiter: Arc<Semaphore>,
 ) {
+    // Spread the checks across the Portal nodes.
+    let mut cycle_of_clients = clients.iter().cycle();
     while let Some(enr) = to_ping_rx.recv().await {
         let permit = limiter
             .clone()
@@ -404,10 +1090,15 @@ async fn orchestrate_liveliness_checks(
             .expect("Unable to acquire permit");
         let handle = do_liveliness_check(
             enr,
+            cycle_of_clients
+                .next()
+                .expect("there is at least one client")
+                .clone(),
             to_enumerate_tx.clone(),
+            to_record_tx.clone(),
             census.clone(),
             config.clone(),
-            conn.clone(),
+            prober.clone(),
         );
         tokio::spawn(async move {
             handle.await;
@@ -418,56 +1109,60 @@ async fn orchestrate_liveliness_checks(
 
 async fn do_liveliness_check(
     enr: Enr,
+    client: PortalHttpClient,
     to_enumerate_tx: mpsc::Sender<Enr>,
+    to_record_tx: mpsc::Sender<RecordWrite>,
     census: Arc<DHTCensus>,
     config: CartographerConfig,
-    conn: DatabaseConnection,
+    prober: Option<Arc<EndpointProber>>,
 ) {
-    let client = match config.transport {
-        TransportConfig::HTTP(http_url) => {
-            match HttpClientBuilder::default()
-                .request_timeout(StdDuration::from_secs(2))
-                .build(http_url.as_ref())
-            {
-                Ok(client) => client,
-                Err(err) => {
-                    error!(client.http_url=?http_url, err=?err, "Error initializing Portal JSON-RPC HTTP client");
-                    census.add_errored(enr.node_id()).await;
-                    return;
-                }
-            }
-        }
-        TransportConfig::IPC(_path) => panic!("not implemented"),
-    };
+    census.add_seen_enr(&enr).await;
 
-    // Save record to database
-    let record_model = match record::get_or_create(&enr, &conn).await {
-        Ok(record_model) => {
-            debug!(enr.base64 = enr.to_base64(), "Saved ENR");
-            record_model
-        }
-        Err(err) => {
-            error!(enr.node_id=?B256::from(enr.node_id().raw()), err=?err, "Error saving ENR to database");
-            census.add_errored(enr.node_id()).await;
-            return;
-        }
-    };
+    // Queue record to be saved to database
+    if let Err(err) = to_record_tx.send(RecordWrite::Save(enr.clone())).await {
+        error!(enr.node_id=?B256::from(enr.node_id().raw()), err=?err, "Error queueing ENR to be saved");
+        census.add_errored(enr.node_id()).await;
+        return;
+    }
 
     // Perform liveliness check
     debug!(node_id=?B256::from(enr.node_id().raw()), "Liveliness check");
 
-    let ping = match config.subnetwork {
-        PortalSubnet::History => HistoryNetworkApiClient::ping(&client, enr.to_owned()),
-        PortalSubnet::Beacon => BeaconNetworkApiClient::ping(&client, enr.to_owned()),
-        PortalSubnet::State => StateNetworkApiClient::ping(&client, enr.to_owned()),
-    };
-    match ping.await {
-        Ok(pong_info) => {
-            debug!(node_id=?B256::from(enr.node_id().raw()), "Liveliness passed");
+    // Retry failed PINGs, up to the configured number of attempts.
+    let started_at = Instant::now();
+    let (result, attempts) = with_retries(
+        enr.node_id(),
+        config.ping_attempts,
+        config.ping_timeout,
+        || {
+            let ping = match config.subnetwork {
+                PortalSubnet::History => HistoryNetworkApiClient::ping(&client, enr.to_owned()),
+                PortalSubnet::Beacon => BeaconNetworkApiClient::ping(&client, enr.to_owned()),
+                PortalSubnet::State => StateNetworkApiClient::ping(&client, enr.to_owned()),
+            };
+            async move {
+                let sent_at = Instant::now();
+                ping.await.map(|pong_info| (pong_info, sent_at.elapsed()))
+            }
+        },
+    )
+    .await;
+    match result {
+        Ok((pong_info, latency)) => {
+            debug!(node_id=?B256::from(enr.node_id().raw()), attempts, "Liveliness passed");
+            census.add_liveness(&enr, PingOutcome::Pong).await;
 
             // Mark node as known to be alive
+            let ping = PingMeasurement {
+                attempts: attempts.into(),
+                latency_ms: latency.as_millis().try_into().unwrap_or(i32::MAX),
+            };
+            let reachability = match &prober {
+                Some(prober) => prober.probe(&enr).await,
+                None => Reachability::default(),
+            };
             census
-                .add_alive(enr.clone(), record_model.id, pong_info.data_radius)
+                .add_alive(enr.clone(), pong_info.data_radius, ping, reachability)
                 .await;
 
             // Send enr to process that enumerates its routing table
@@ -480,29 +1175,134 @@ async fn do_liveliness_check(
             }
         }
         Err(err) => {
-            warn!(node_id=?B256::from(enr.node_id().raw()), err=?err, "Liveliness failed");
+            let outcome = classify_ping_error(&err);
+            warn!(node_id=?B256::from(enr.node_id().raw()), attempts, outcome=?outcome, err=?err, "Liveliness failed");
+            census.add_liveness(&enr, outcome).await;
+
+            // Add node to error list, with why it failed.
+            let failure = DHTCensusFailure {
+                kind: classify_census_error(&err, outcome),
+                surveyed_at: Utc::now(),
+                attempts: attempts.into(),
+                duration_ms: started_at
+                    .elapsed()
+                    .as_millis()
+                    .try_into()
+                    .unwrap_or(i32::MAX),
+                message: err.to_string(),
+            };
+            census.add_failed(enr.node_id(), failure).await;
+        }
+    }
+}
+
+/// Fails a request about a single node with a timeout error if it takes longer than the given
+/// timeout. The shared client only enforces the longer [CENSUS_REQUEST_TIMEOUT].
+async fn with_node_timeout<T>(
+    timeout: StdDuration,
+    request: impl Future<Output = Result<T, ClientError>>,
+) -> Result<T, ClientError> {
+    time::timeout(timeout, request)
+        .await
+        .unwrap_or(Err(ClientError::RequestTimeout))
+}
 
-            // Add node to error list.
-            census.add_errored(enr.node_id()).await;
+/// Sends a request about a single node up to `attempts` times, until it succeeds. Failed
+/// requests are retried after [RETRY_BACKOFF], doubled for each retry, so that transient
+/// failures don't count against live nodes.
+///
+/// Returns the result of the last request, and the number of requests sent.
+async fn with_retries<T, F, Fut>(
+    node_id: NodeId,
+    attempts: u8,
+    timeout: StdDuration,
+    mut request: F,
+) -> (Result<T, ClientError>, u8)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, ClientError>>,
+{
+    let mut sent: u8 = 0;
+    loop {
+        sent += 1;
+        match with_node_timeout(timeout, request()).await {
+            Ok(response) => return (Ok(response), sent),
+            Err(err) if sent < attempts => {
+                debug!(node_id=?B256::from(node_id.raw()), attempts=sent, err=?err, "Request failed, retrying");
+                time::sleep(RETRY_BACKOFF * 2u32.pow(u32::from(sent) - 1)).await;
+            }
+            Err(err) => return (Err(err), sent),
         }
     }
 }
 
+/// Classifies a failed liveness check from the error returned by the Portal node.
+///
+/// The Portal node reports problems with the PONG as JSON-RPC errors, which are told apart by
+/// their message. Errors reaching the Portal node itself count as error responses.
+fn classify_ping_error(err: &ClientError) -> PingOutcome {
+    match err {
+        ClientError::RequestTimeout => PingOutcome::Timeout,
+        ClientError::ParseError(_) => PingOutcome::UndecodablePong,
+        ClientError::Call(err) => {
+            let message = err.message().to_lowercase();
+            if message.contains("timed out") || message.contains("timeout") {
+                PingOutcome::Timeout
+            } else if message.contains("decod") {
+                PingOutcome::UndecodablePong
+            } else if message.contains("unexpected") || message.contains("invalid response") {
+                PingOutcome::UnexpectedPong
+            } else {
+                PingOutcome::ErrorPong
+            }
+        }
+        _ => PingOutcome::ErrorPong,
+    }
+}
+
+/// Classifies why a node dropped out of a census, telling apart the nodes that could not be
+/// contacted at all from the other failed liveness checks.
+fn classify_census_error(err: &ClientError, outcome: PingOutcome) -> CensusNodeErrorKind {
+    if let ClientError::Call(err) = err {
+        let message = err.message().to_lowercase();
+        if message.contains("unreachable") || message.contains("no route") {
+            return CensusNodeErrorKind::Unreachable;
+        }
+    }
+    match outcome {
+        PingOutcome::Timeout => CensusNodeErrorKind::Timeout,
+        PingOutcome::UndecodablePong => CensusNodeErrorKind::DecodeError,
+        PingOutcome::UnexpectedPong => CensusNodeErrorKind::UnexpectedPong,
+        PingOutcome::Pong | PingOutcome::ErrorPong => CensusNodeErrorKind::ErrorPong,
+    }
+}
+
 async fn orchestrate_routing_table_enumerations(
     mut to_enumerate_rx: mpsc::Receiver<Enr>,
+    clients: Vec<PortalHttpClient>,
     to_ping_tx: mpsc::Sender<Enr>,
     census: Arc<DHTCensus>,
     config: CartographerConfig,
     limiter: Arc<Semaphore>,
 ) {
+    // Spread the enumerations across the Portal nodes.
+    let mut cycle_of_clients = clients.iter().cycle();
     while let Some(enr) = to_enumerate_rx.recv().await {
         let permit = limiter
             .clone()
             .acquire_owned()
             .await
             .expect("Unable to acquire permit");
-        let handle =
-            do_routing_table_enumeration(enr, to_ping_tx.clone(), census.clone(), config.clone());
+        let handle = do_routing_table_enumeration(
+            enr,
+            cycle_of_clients
+                .next()
+                .expect("there is at least one client")
+                .clone(),
+            to_ping_tx.clone(),
+            census.clone(),
+            config.clone(),
+        );
         tokio::spawn(async move {
             handle.await;
             drop(permit);
@@ -512,42 +1312,33 @@ async fn orchestrate_routing_table_enumerations(
 
 async fn do_routing_table_enumeration(
     enr: Enr,
+    client: PortalHttpClient,
     to_ping_tx: mpsc::Sender<Enr>,
     census: Arc<DHTCensus>,
     config: CartographerConfig,
 ) {
-    let client = match config.transport {
-        TransportConfig::HTTP(http_url) => {
-            match HttpClientBuilder::default()
-                .request_timeout(StdDuration::from_secs(2))
-                .build(http_url.as_ref())
-            {
-                Ok(client) => client,
-                Err(err) => {
-                    error!(client.http_url=?http_url, err=?err, "Error initializing Portal JSON-RPC HTTP client");
-                    census.add_errored(enr.node_id()).await;
-                    return;
-                }
-            }
-        }
-        TransportConfig::IPC(_path) => panic!("not implemented"),
-    };
-
     debug!(enr.node_id=?B256::from(enr.node_id().raw()), "Enumerating Routing Table");
 
-    for distance in 245..257 {
-        let find_nodes = match config.subnetwork {
-            PortalSubnet::History => {
-                HistoryNetworkApiClient::find_nodes(&client, enr.to_owned(), vec![distance])
-            }
-            PortalSubnet::Beacon => {
-                BeaconNetworkApiClient::find_nodes(&client, enr.to_owned(), vec![distance])
-            }
-            PortalSubnet::State => {
-                StateNetworkApiClient::find_nodes(&client, enr.to_owned(), vec![distance])
-            }
-        };
-        let enrs_at_distance = match find_nodes.await {
+    for distance in census.distances.clone() {
+        let (enrs_at_distance, _) = with_retries(
+            enr.node_id(),
+            config.retries.saturating_add(1),
+            config.enumeration_timeout,
+            || match config.subnetwork {
+                PortalSubnet::History => {
+                    HistoryNetworkApiClient::find_nodes(&client, enr.to_owned(), vec![distance])
+                }
+                PortalSubnet::Beacon => {
+                    BeaconNetworkApiClient::find_nodes(&client, enr.to_owned(), vec![distance])
+                }
+                PortalSubnet::State => {
+                    StateNetworkApiClient::find_nodes(&client, enr.to_owned(), vec![distance])
+                }
+            },
+        )
+        .await;
+        census.add_enumeration(enrs_at_distance.is_err());
+        let enrs_at_distance = match enrs_at_distance {
             Ok(result) => result,
             Err(msg) => {
                 warn!(enr.node_id=?B256::from(enr.node_id().raw()), distance=?distance, msg=?msg, "Error fetching routing table info");
@@ -555,7 +1346,9 @@ async fn do_routing_table_enumeration(
             }
         };
         debug!(enr.node_id=?B256::from(enr.node_id().raw()), distance=distance, count=enrs_at_distance.len(), "Routing Table Info");
+        census.add_occupancy(distance, enrs_at_distance.len()).await;
         for found_enr in enrs_at_distance {
+            census.add_seen_enr(&found_enr).await;
             if census.is_known(found_enr.node_id()).await {
                 continue;
             } else {
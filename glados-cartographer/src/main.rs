@@ -1,8 +1,13 @@
 use anyhow::Result;
+use clap::Parser;
 use sea_orm::Database;
 use tracing::{debug, info};
 
-use glados_cartographer::{run_glados_cartographer, CartographerConfig};
+use glados_cartographer::{
+    archive,
+    cli::{Args, Command},
+    run_glados_cartographer, CartographerConfig,
+};
 use migration::{Migrator, MigratorTrait};
 
 #[tokio::main]
@@ -16,8 +21,12 @@ async fn main() -> Result<()> {
     // CLI argument parsing
     //
     debug!("Parsing CLI arguments");
+    let mut args = Args::parse();
 
-    let config = CartographerConfig::from_args()?;
+    if let Some(command) = args.subcommand.take() {
+        return run_command(&args.database_url, command).await;
+    }
+    let config = CartographerConfig::from_args(args)?;
 
     //
     // Database Connection
@@ -39,3 +48,21 @@ async fn main() -> Result<()> {
     run_glados_cartographer(conn, config).await;
     Ok(())
 }
+
+async fn run_command(database_url: &str, command: Command) -> Result<()> {
+    debug!(database_url = database_url, "Connecting to database");
+    let conn = Database::connect(database_url).await?;
+    info!(
+        database_url = database_url,
+        "database connection established"
+    );
+
+    Migrator::up(&conn, None).await?;
+    match command {
+        Command::ArchiveCensuses {
+            dir,
+            older_than_days,
+        } => archive::archive_censuses(&dir, older_than_days, &conn).await,
+        Command::LoadCensuses { files } => archive::load_censuses(&files, &conn).await,
+    }
+}
@@ -0,0 +1,78 @@
+//! Direct probes of the endpoints that nodes declare in their ENRs.
+//!
+//! A node can answer the census while its declared endpoints are unreachable, like when it is
+//! behind a NAT and its packets are relayed through a session it opened itself. The Portal nodes
+//! used for the census already hold sessions with most of the nodes they PING, so the endpoints
+//! are probed from the cartographer instead: the UDP endpoint with a discv5 PING from a discv5
+//! instance with its own identity, which has to open a new session through the declared
+//! endpoint, and the TCP endpoint by connecting to it.
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    time::Duration,
+};
+
+use alloy_primitives::B256;
+use anyhow::{anyhow, Result};
+use discv5::{enr::CombinedKey, ConfigBuilder, Discv5, ListenConfig};
+use ethportal_api::Enr;
+use tokio::{net::TcpStream, time};
+use tracing::debug;
+
+use entity::census_node::Reachability;
+
+/// Probes the declared endpoints of nodes, for a single census.
+///
+/// Each census gets a new discv5 identity, so no session opened by an earlier probe is reused.
+/// The discv5 instance stops when the prober is dropped.
+pub struct EndpointProber {
+    discv5: Discv5,
+    timeout: Duration,
+}
+
+impl EndpointProber {
+    /// Starts a discv5 instance with a new identity on an ephemeral IPv4 UDP port.
+    pub async fn start(timeout: Duration) -> Result<Self> {
+        let key = CombinedKey::generate_secp256k1();
+        let enr = Enr::empty(&key)?;
+        let config = ConfigBuilder::new(ListenConfig::Ipv4 {
+            ip: Ipv4Addr::UNSPECIFIED,
+            port: 0,
+        })
+        .request_timeout(timeout)
+        .request_retries(0)
+        .build();
+        let mut discv5: Discv5 = Discv5::new(enr, key, config).map_err(|e| anyhow!(e))?;
+        discv5
+            .start()
+            .await
+            .map_err(|e| anyhow!("Could not start discv5: {e:?}"))?;
+        Ok(EndpointProber { discv5, timeout })
+    }
+
+    /// Probes the UDP and TCP endpoints declared in the ENR, at the same time.
+    ///
+    /// Endpoints that are not declared are not probed, nor are UDP endpoints only declared over
+    /// IPv6, as the discv5 instance listens on IPv4.
+    pub async fn probe(&self, enr: &Enr) -> Reachability {
+        let (udp, tcp) = tokio::join!(self.probe_udp(enr), self.probe_tcp(enr));
+        debug!(node_id=?B256::from(enr.node_id().raw()), ?udp, ?tcp, "Probed declared endpoints");
+        Reachability { udp, tcp }
+    }
+
+    async fn probe_udp(&self, enr: &Enr) -> Option<bool> {
+        enr.udp4_socket()?;
+        let ping = self.discv5.send_ping(enr.to_owned());
+        Some(matches!(time::timeout(self.timeout, ping).await, Ok(Ok(_))))
+    }
+
+    async fn probe_tcp(&self, enr: &Enr) -> Option<bool> {
+        let socket = enr
+            .tcp4_socket()
+            .map(SocketAddr::V4)
+            .or_else(|| enr.tcp6_socket().map(SocketAddr::V6))?;
+        Some(matches!(
+            time::timeout(self.timeout, TcpStream::connect(socket)).await,
+            Ok(Ok(_))
+        ))
+    }
+}
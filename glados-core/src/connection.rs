@@ -0,0 +1,169 @@
+//! The HTTP connections of the JSON-RPC clients of Portal nodes.
+//!
+//! The JSON-RPC HTTP client connects with default settings, so [ConnectionLayer] replaces its
+//! connections when they need tuning, like a connection pool bounded for nodes audited at high
+//! rates, HTTP/2 or a proxy.
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use http::Uri;
+use hyper::body::Incoming;
+use hyper_util::{
+    client::legacy::{connect::HttpConnector, Client},
+    rt::{TokioExecutor, TokioIo, TokioTimer},
+};
+use jsonrpsee::{
+    core::{http_helpers::HttpError, BoxError},
+    http_client::{
+        transport::{Error as TransportError, HttpBackend},
+        HttpBody, HttpRequest, HttpResponse,
+    },
+};
+use tokio::net::TcpStream;
+use tower::{Layer, Service};
+
+use crate::proxy::{Proxy, ProxyConnector, ProxyError};
+
+/// How the HTTP connections to a Portal node are made.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ConnectionSettings {
+    /// Proxy the connections go through, if any.
+    pub proxy: Option<Proxy>,
+    /// Most idle connections kept open for later requests. Unlimited by default.
+    pub max_idle_connections: Option<usize>,
+    /// Whether to speak HTTP/2 without negotiating it first, which multiplexes the requests
+    /// over a single connection. The Portal node must accept HTTP/2 over plain TCP.
+    pub http2: bool,
+    /// Interval of the HTTP/2 pings that keep connections open while idle, and detect dead
+    /// ones.
+    pub http2_keep_alive: Option<Duration>,
+}
+
+impl ConnectionSettings {
+    /// Whether the connections are made the way the JSON-RPC client would make them.
+    fn is_default(&self) -> bool {
+        self == &ConnectionSettings::default()
+    }
+}
+
+/// Middleware of the HTTP JSON-RPC client that makes its connections with the
+/// [ConnectionSettings].
+#[derive(Clone, Debug, Default)]
+pub struct ConnectionLayer {
+    settings: ConnectionSettings,
+    proxy: Option<ProxyConnector>,
+}
+
+impl ConnectionLayer {
+    pub fn new(settings: &ConnectionSettings) -> Result<Self, ProxyError> {
+        Ok(ConnectionLayer {
+            settings: settings.clone(),
+            proxy: settings.proxy.as_ref().map(Proxy::connector).transpose()?,
+        })
+    }
+}
+
+impl Layer<HttpBackend> for ConnectionLayer {
+    type Service = PortalHttpBackend;
+
+    fn layer(&self, backend: HttpBackend) -> Self::Service {
+        if self.settings.is_default() {
+            return PortalHttpBackend::Default(backend);
+        }
+        let connector = match &self.proxy {
+            Some(proxy) => Connector::Proxy(proxy.clone()),
+            None => {
+                let mut connector = HttpConnector::new();
+                connector.set_nodelay(true);
+                Connector::Direct(connector)
+            }
+        };
+        let mut builder = Client::builder(TokioExecutor::new());
+        builder
+            .pool_timer(TokioTimer::new())
+            .timer(TokioTimer::new());
+        if let Some(max_idle_connections) = self.settings.max_idle_connections {
+            builder.pool_max_idle_per_host(max_idle_connections);
+        }
+        if self.settings.http2 {
+            builder.http2_only(true);
+            if let Some(interval) = self.settings.http2_keep_alive {
+                builder
+                    .http2_keep_alive_interval(interval)
+                    .http2_keep_alive_while_idle(true);
+            }
+        }
+        PortalHttpBackend::Tuned(builder.build(connector))
+    }
+}
+
+/// Opens the connections to a Portal node, directly or through a proxy.
+#[derive(Clone, Debug)]
+pub enum Connector {
+    Direct(HttpConnector),
+    Proxy(ProxyConnector),
+}
+
+impl Service<Uri> for Connector {
+    type Response = TokioIo<TcpStream>;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        match self {
+            Connector::Direct(connector) => connector.poll_ready(cx).map_err(Into::into),
+            Connector::Proxy(connector) => connector.poll_ready(cx),
+        }
+    }
+
+    fn call(&mut self, target: Uri) -> Self::Future {
+        match self {
+            Connector::Direct(connector) => {
+                let connecting = connector.call(target);
+                Box::pin(async move { connecting.await.map_err(Into::into) })
+            }
+            Connector::Proxy(connector) => connector.call(target),
+        }
+    }
+}
+
+/// The HTTP client of a Portal node.
+#[derive(Clone, Debug)]
+pub enum PortalHttpBackend {
+    /// The JSON-RPC client's own, when the connections need no tuning.
+    Default(HttpBackend),
+    Tuned(Client<Connector, HttpBody>),
+}
+
+impl Service<HttpRequest> for PortalHttpBackend {
+    type Response = HttpResponse<Incoming>;
+    type Error = TransportError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        match self {
+            PortalHttpBackend::Default(backend) => backend.poll_ready(cx),
+            PortalHttpBackend::Tuned(client) => client
+                .poll_ready(cx)
+                .map_err(|e| TransportError::Http(HttpError::Stream(e.into()))),
+        }
+    }
+
+    fn call(&mut self, request: HttpRequest) -> Self::Future {
+        match self {
+            PortalHttpBackend::Default(backend) => backend.call(request),
+            PortalHttpBackend::Tuned(client) => {
+                let response = client.request(request);
+                Box::pin(async move {
+                    response
+                        .await
+                        .map_err(|e| TransportError::Http(HttpError::Stream(e.into())))
+                })
+            }
+        }
+    }
+}
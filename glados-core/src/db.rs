@@ -1,15 +1,15 @@
 use anyhow::{Error, Result};
 use chrono::{DateTime, Utc};
 use entity::{
-    content::{self, SubProtocol},
+    content::{self, ContentSource, SubProtocol},
     execution_metadata, state_roots,
 };
-use ethportal_api::{
-    utils::bytes::hex_encode, BlockBodyKey, BlockReceiptsKey, HistoryContentKey, OverlayContentKey,
-};
+use ethportal_api::{utils::bytes::hex_encode, OverlayContentKey};
 use sea_orm::DatabaseConnection;
 use tracing::{debug, error};
 
+use crate::history::{block_content_keys, HistoryContentKind};
+
 /// Stores the state root for the given block number.
 pub async fn store_state_root(
     block_number: i32,
@@ -22,61 +22,33 @@ pub async fn store_state_root(
 
 /// Stores the content keys and block metadata for the given block.
 ///
-/// The metadata included is the block number and hash under the execution
-/// header, body and receipts tables.
+/// The keys are those of every kind of content the History network stores for the block's era
+/// (see [crate::history::BlockEra::content_kinds]), each with the block number under the execution metadata
+/// table.
 ///
-/// Errors are logged.
+/// Errors are logged, and the content that could not be stored is left out.
 pub async fn store_block_keys(
     block_number: i32,
     block_hash: &[u8; 32],
     available_at: DateTime<Utc>,
+    source: ContentSource,
     conn: &DatabaseConnection,
-) -> Vec<content::Model> {
-    let header = HistoryContentKey::new_block_header_by_hash(*block_hash);
-    let body = HistoryContentKey::BlockBody(BlockBodyKey {
-        block_hash: *block_hash,
-    });
-    let receipts = HistoryContentKey::BlockReceipts(BlockReceiptsKey {
-        block_hash: *block_hash,
-    });
-
-    let header = store_content_key(
-        &header,
-        "block_header",
-        block_number,
-        available_at,
-        conn,
-        SubProtocol::History,
-    )
-    .await;
-    let body = store_content_key(
-        &body,
-        "block_body",
-        block_number,
-        available_at,
-        conn,
-        SubProtocol::History,
-    )
-    .await;
-    let receipts = store_content_key(
-        &receipts,
-        "block_receipts",
-        block_number,
-        available_at,
-        conn,
-        SubProtocol::History,
-    )
-    .await;
-
+) -> Vec<(HistoryContentKind, content::Model)> {
     let mut returned_values = vec![];
-    if let Some(header) = header {
-        returned_values.push(header);
-    }
-    if let Some(body) = body {
-        returned_values.push(body);
-    }
-    if let Some(receipts) = receipts {
-        returned_values.push(receipts);
+    for (kind, content_key) in block_content_keys(block_number, block_hash) {
+        let stored = store_content_key(
+            &content_key,
+            kind.as_text(),
+            block_number,
+            available_at,
+            source,
+            conn,
+            SubProtocol::History,
+        )
+        .await;
+        if let Some(content) = stored {
+            returned_values.push((kind, content));
+        }
     }
     returned_values
 }
@@ -89,11 +61,12 @@ pub async fn store_content_key<T: OverlayContentKey>(
     name: &str,
     block_number: i32,
     available_at: DateTime<Utc>,
+    source: ContentSource,
     conn: &DatabaseConnection,
     network: SubProtocol,
 ) -> Option<content::Model> {
     // Store key
-    match content::get_or_create(network, key, available_at, conn).await {
+    match content::get_or_create(network, key, available_at, source, conn).await {
         Ok(content_model) => {
             log_record_outcome(key, name, DbOutcome::Success);
             // Store metadata
@@ -0,0 +1,46 @@
+//! Features toggled per deployment through the `feature_flag` table.
+//!
+//! Risky features check their flag so they can be turned on in one environment before the
+//! others. Flags are read once at startup, so a toggle takes effect on the next restart:
+//!
+//! ```sql
+//! INSERT INTO feature_flag (name, enabled, description) VALUES ('my_feature', true, '...')
+//! ON CONFLICT (name) DO UPDATE SET enabled = EXCLUDED.enabled, updated_at = NOW();
+//! ```
+use anyhow::Result;
+use entity::feature_flag;
+use sea_orm::DatabaseConnection;
+use tracing::info;
+
+/// Offering content that failed its audit back to the network, with glados-audit `--reseed`.
+pub const OFFER_AUDITS: &str = "offer_audits";
+
+/// The flags of a deployment, as read at startup.
+#[derive(Clone, Debug, Default)]
+pub struct FeatureFlags {
+    flags: Vec<feature_flag::Model>,
+}
+
+impl FeatureFlags {
+    /// Reads the flags from the database and logs the enabled ones.
+    pub async fn load(conn: &DatabaseConnection) -> Result<Self> {
+        let flags = FeatureFlags {
+            flags: feature_flag::get_all(conn).await?,
+        };
+        let enabled: Vec<&str> = flags.enabled().map(|flag| flag.name.as_str()).collect();
+        if !enabled.is_empty() {
+            info!(flags = ?enabled, "Feature flags enabled");
+        }
+        Ok(flags)
+    }
+
+    /// Whether a flag is turned on. Flags missing from the table are off.
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.enabled().any(|flag| flag.name == name)
+    }
+
+    /// The flags that are turned on, by name.
+    pub fn enabled(&self) -> impl Iterator<Item = &feature_flag::Model> {
+        self.flags.iter().filter(|flag| flag.enabled)
+    }
+}
@@ -0,0 +1,176 @@
+//! The History network content that exists for each execution block, which depends on the era
+//! the block belongs to.
+use ethportal_api::{BlockBodyKey, BlockReceiptsKey, HistoryContentKey};
+
+/// First block produced after the merge.
+pub const MERGE_BLOCK_HEIGHT: i32 = 15537393;
+
+/// First block of the Shanghai upgrade, which added withdrawals to block bodies.
+pub const SHANGHAI_BLOCK_HEIGHT: i32 = 17034870;
+
+/// A span of blocks whose History network content has the same shape.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockEra {
+    /// Proof of work blocks. Bodies hold transactions and ommers (uncles), and headers are
+    /// proven by the pre-merge accumulator.
+    PreMerge,
+    /// Proof of stake blocks before Shanghai. Bodies hold transactions and an empty list of
+    /// ommers.
+    Merge,
+    /// Blocks from Shanghai on. Bodies hold transactions, an empty list of ommers, and
+    /// withdrawals.
+    Shanghai,
+}
+
+impl BlockEra {
+    pub fn from_block_number(block_number: i32) -> Self {
+        if block_number < MERGE_BLOCK_HEIGHT {
+            BlockEra::PreMerge
+        } else if block_number < SHANGHAI_BLOCK_HEIGHT {
+            BlockEra::Merge
+        } else {
+            BlockEra::Shanghai
+        }
+    }
+
+    /// The kinds of content stored for each block of the era.
+    ///
+    /// Headers can only be looked up by number where the header proof ties the number to the
+    /// header, which the pre-merge accumulator does. Later headers are only stored by hash.
+    pub fn content_kinds(&self) -> &'static [HistoryContentKind] {
+        match self {
+            BlockEra::PreMerge => &[
+                HistoryContentKind::BlockHeader,
+                HistoryContentKind::BlockHeaderByNumber,
+                HistoryContentKind::BlockBody,
+                HistoryContentKind::BlockReceipts,
+            ],
+            BlockEra::Merge | BlockEra::Shanghai => &[
+                HistoryContentKind::BlockHeader,
+                HistoryContentKind::BlockBody,
+                HistoryContentKind::BlockReceipts,
+            ],
+        }
+    }
+}
+
+/// A kind of History network content derived from a block.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HistoryContentKind {
+    /// The header with its proof, looked up by block hash.
+    BlockHeader,
+    /// The header with its proof, looked up by block number.
+    BlockHeaderByNumber,
+    BlockBody,
+    BlockReceipts,
+}
+
+impl HistoryContentKind {
+    pub fn as_text(&self) -> &'static str {
+        match self {
+            HistoryContentKind::BlockHeader => "block_header",
+            HistoryContentKind::BlockHeaderByNumber => "block_header_by_number",
+            HistoryContentKind::BlockBody => "block_body",
+            HistoryContentKind::BlockReceipts => "block_receipts",
+        }
+    }
+
+    pub fn content_key(&self, block_number: i32, block_hash: &[u8; 32]) -> HistoryContentKey {
+        match self {
+            HistoryContentKind::BlockHeader => {
+                HistoryContentKey::new_block_header_by_hash(*block_hash)
+            }
+            HistoryContentKind::BlockHeaderByNumber => {
+                HistoryContentKey::new_block_header_by_number(block_number as u64)
+            }
+            HistoryContentKind::BlockBody => HistoryContentKey::BlockBody(BlockBodyKey {
+                block_hash: *block_hash,
+            }),
+            HistoryContentKind::BlockReceipts => {
+                HistoryContentKey::BlockReceipts(BlockReceiptsKey {
+                    block_hash: *block_hash,
+                })
+            }
+        }
+    }
+}
+
+/// The content keys of everything the History network stores for a block.
+pub fn block_content_keys(
+    block_number: i32,
+    block_hash: &[u8; 32],
+) -> Vec<(HistoryContentKind, HistoryContentKey)> {
+    BlockEra::from_block_number(block_number)
+        .content_kinds()
+        .iter()
+        .map(|kind| (*kind, kind.content_key(block_number, block_hash)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_era() {
+        assert_eq!(BlockEra::from_block_number(0), BlockEra::PreMerge);
+        assert_eq!(
+            BlockEra::from_block_number(MERGE_BLOCK_HEIGHT - 1),
+            BlockEra::PreMerge
+        );
+        assert_eq!(
+            BlockEra::from_block_number(MERGE_BLOCK_HEIGHT),
+            BlockEra::Merge
+        );
+        assert_eq!(
+            BlockEra::from_block_number(SHANGHAI_BLOCK_HEIGHT - 1),
+            BlockEra::Merge
+        );
+        assert_eq!(
+            BlockEra::from_block_number(SHANGHAI_BLOCK_HEIGHT),
+            BlockEra::Shanghai
+        );
+    }
+
+    #[test]
+    fn test_block_content_keys() {
+        let block_hash = [1u8; 32];
+        let kinds = |block_number| -> Vec<HistoryContentKind> {
+            block_content_keys(block_number, &block_hash)
+                .into_iter()
+                .map(|(kind, _)| kind)
+                .collect()
+        };
+        assert_eq!(
+            kinds(1_000_000),
+            vec![
+                HistoryContentKind::BlockHeader,
+                HistoryContentKind::BlockHeaderByNumber,
+                HistoryContentKind::BlockBody,
+                HistoryContentKind::BlockReceipts,
+            ]
+        );
+        assert_eq!(
+            kinds(SHANGHAI_BLOCK_HEIGHT + 1),
+            vec![
+                HistoryContentKind::BlockHeader,
+                HistoryContentKind::BlockBody,
+                HistoryContentKind::BlockReceipts,
+            ]
+        );
+
+        let keys = block_content_keys(1_000_000, &block_hash);
+        assert_eq!(
+            keys[0].1,
+            HistoryContentKey::new_block_header_by_hash(block_hash)
+        );
+        assert_eq!(
+            keys[1].1,
+            HistoryContentKey::new_block_header_by_number(1_000_000)
+        );
+        assert_eq!(
+            keys[2].1,
+            HistoryContentKey::BlockBody(BlockBodyKey { block_hash })
+        );
+    }
+}
@@ -0,0 +1,300 @@
+//! JSON-RPC over the IPC socket of a Portal node, for Portal nodes running on the same host.
+//!
+//! The socket carries JSON-RPC messages back to back, without framing, so incoming messages
+//! are split where the JSON object (or batch array) at the front of the stream ends.
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use jsonrpsee::core::{
+    async_trait,
+    client::{Client, ClientBuilder, Error, ReceivedMessage, TransportReceiverT, TransportSenderT},
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{
+        unix::{OwnedReadHalf, OwnedWriteHalf},
+        UnixStream,
+    },
+    sync::Mutex,
+};
+
+/// Scheme of the Portal client strings that name an IPC socket, e.g.
+/// `ipc:///tmp/trin-jsonrpc.ipc`.
+pub const IPC_PREFIX: &str = "ipc://";
+
+/// Bytes read from the socket at a time.
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Returns the socket path of an `ipc://` Portal client string, or `None` for other schemes.
+///
+/// Any number of slashes may follow the scheme, as in `ipc:////tmp/trin-jsonrpc.ipc`, and the
+/// path is always absolute.
+pub fn socket_path(client_url: &str) -> Option<PathBuf> {
+    let path = client_url.strip_prefix(IPC_PREFIX)?;
+    let path = path.trim_start_matches('/');
+    if path.is_empty() {
+        return None;
+    }
+    Some(PathBuf::from(format!("/{path}")))
+}
+
+/// Client of the IPC socket of a Portal node.
+///
+/// When the node closes the socket, like when it restarts, the next request connects again.
+/// Requests in flight when the socket closed fail.
+#[derive(Debug)]
+pub struct IpcClient {
+    path: PathBuf,
+    request_timeout: Duration,
+    max_concurrent_requests: Option<usize>,
+    client: Mutex<Arc<Client>>,
+}
+
+impl IpcClient {
+    /// Connects to the IPC socket of a Portal node.
+    ///
+    /// As over HTTP, requests beyond `max_concurrent_requests` wait for an earlier one to finish.
+    pub async fn connect(
+        path: PathBuf,
+        request_timeout: Duration,
+        max_concurrent_requests: Option<usize>,
+    ) -> io::Result<Self> {
+        let client = connect(&path, request_timeout, max_concurrent_requests).await?;
+        Ok(IpcClient {
+            path,
+            request_timeout,
+            max_concurrent_requests,
+            client: Mutex::new(Arc::new(client)),
+        })
+    }
+
+    /// Returns the client of the socket, connecting again if the socket was closed.
+    pub async fn client(&self) -> Result<Arc<Client>, Error> {
+        let mut client = self.client.lock().await;
+        if !client.is_connected() {
+            *client = Arc::new(
+                connect(
+                    &self.path,
+                    self.request_timeout,
+                    self.max_concurrent_requests,
+                )
+                .await
+                .map_err(|e| Error::Transport(e.into()))?,
+            );
+        }
+        Ok(client.clone())
+    }
+}
+
+async fn connect(
+    path: &Path,
+    request_timeout: Duration,
+    max_concurrent_requests: Option<usize>,
+) -> io::Result<Client> {
+    let (reader, writer) = UnixStream::connect(path).await?.into_split();
+    let mut builder = ClientBuilder::default().request_timeout(request_timeout);
+    if let Some(max_concurrent_requests) = max_concurrent_requests {
+        builder = builder.max_concurrent_requests(max_concurrent_requests);
+    }
+    Ok(builder.build_with_tokio(
+        IpcSender { writer },
+        IpcReceiver {
+            reader,
+            splitter: MessageSplitter::default(),
+        },
+    ))
+}
+
+struct IpcSender {
+    writer: OwnedWriteHalf,
+}
+
+#[async_trait]
+impl TransportSenderT for IpcSender {
+    type Error = io::Error;
+
+    async fn send(&mut self, msg: String) -> Result<(), Self::Error> {
+        self.writer.write_all(msg.as_bytes()).await?;
+        self.writer.flush().await
+    }
+
+    async fn close(&mut self) -> Result<(), Self::Error> {
+        self.writer.shutdown().await
+    }
+}
+
+struct IpcReceiver {
+    reader: OwnedReadHalf,
+    splitter: MessageSplitter,
+}
+
+#[async_trait]
+impl TransportReceiverT for IpcReceiver {
+    type Error = io::Error;
+
+    async fn receive(&mut self) -> Result<ReceivedMessage, Self::Error> {
+        loop {
+            if let Some(message) = self.splitter.take_message()? {
+                return Ok(ReceivedMessage::Text(message));
+            }
+            let mut chunk = vec![0; READ_CHUNK_SIZE];
+            let read = self.reader.read(&mut chunk).await?;
+            if read == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "Portal node closed the IPC socket",
+                ));
+            }
+            self.splitter.buffer.extend_from_slice(&chunk[..read]);
+        }
+    }
+}
+
+/// Splits the bytes read from the socket into messages.
+///
+/// Scanning stops at the end of the bytes read so far and resumes there once more arrive, so
+/// each byte is scanned once however many reads a large message takes.
+#[derive(Debug, Default)]
+struct MessageSplitter {
+    /// Bytes read from the socket that do not make up a complete message yet.
+    buffer: Vec<u8>,
+    /// Bytes of `buffer` already scanned.
+    scanned: usize,
+    /// Nesting of objects and arrays at the end of the scanned bytes.
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+}
+
+impl MessageSplitter {
+    /// Removes the first complete message from the buffer and returns it, or `None` if the
+    /// buffer does not hold a complete message yet.
+    ///
+    /// Only the nesting is checked here; the JSON of the message is parsed by the client.
+    fn take_message(&mut self) -> io::Result<Option<String>> {
+        while self.scanned < self.buffer.len() {
+            let byte = self.buffer[self.scanned];
+            self.scanned += 1;
+            if self.in_string {
+                if self.escaped {
+                    self.escaped = false;
+                } else if byte == b'\\' {
+                    self.escaped = true;
+                } else if byte == b'"' {
+                    self.in_string = false;
+                }
+                continue;
+            }
+            match byte {
+                b'{' | b'[' => self.depth += 1,
+                b'}' | b']' if self.depth > 0 => {
+                    self.depth -= 1;
+                    if self.depth == 0 {
+                        let message: Vec<u8> = self.buffer.drain(..self.scanned).collect();
+                        self.scanned = 0;
+                        let message = String::from_utf8(message)
+                            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                        return Ok(Some(message.trim_start().to_owned()));
+                    }
+                }
+                b'"' if self.depth > 0 => self.in_string = true,
+                byte if self.depth > 0 || byte.is_ascii_whitespace() => {}
+                byte => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("expected a JSON-RPC message, got {:?}", char::from(byte)),
+                    ))
+                }
+            }
+        }
+        if self.depth == 0 {
+            // Only whitespace is left.
+            self.buffer.clear();
+            self.scanned = 0;
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_socket_path() {
+        assert_eq!(
+            socket_path("ipc:///tmp/trin-jsonrpc.ipc"),
+            Some(PathBuf::from("/tmp/trin-jsonrpc.ipc"))
+        );
+        assert_eq!(
+            socket_path("ipc:////tmp/trin-jsonrpc.ipc"),
+            Some(PathBuf::from("/tmp/trin-jsonrpc.ipc"))
+        );
+        assert_eq!(socket_path("ipc://"), None);
+        assert_eq!(socket_path("http://127.0.0.1:8545"), None);
+    }
+
+    fn splitter(bytes: &[u8]) -> MessageSplitter {
+        MessageSplitter {
+            buffer: bytes.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn splits_back_to_back_messages() {
+        let mut splitter = splitter(
+            br#"{"jsonrpc":"2.0","id":1,"result":"0x"}
+            {"jsonrpc":"2.0","id":2,"result":{"a":"}\"]"}}[{"jsonrpc":"2.0","id":3,"result":[]}]{"jsonrpc":"2.0","#,
+        );
+        assert_eq!(
+            splitter.take_message().unwrap().as_deref(),
+            Some(r#"{"jsonrpc":"2.0","id":1,"result":"0x"}"#)
+        );
+        assert_eq!(
+            splitter.take_message().unwrap().as_deref(),
+            Some(r#"{"jsonrpc":"2.0","id":2,"result":{"a":"}\"]"}}"#)
+        );
+        assert_eq!(
+            splitter.take_message().unwrap().as_deref(),
+            Some(r#"[{"jsonrpc":"2.0","id":3,"result":[]}]"#)
+        );
+        // The last message is incomplete, until the rest of it is read.
+        assert_eq!(splitter.take_message().unwrap(), None);
+        splitter
+            .buffer
+            .extend_from_slice(br#""id":4,"result":null}"#);
+        assert_eq!(
+            splitter.take_message().unwrap().as_deref(),
+            Some(r#"{"jsonrpc":"2.0","id":4,"result":null}"#)
+        );
+        assert_eq!(splitter.take_message().unwrap(), None);
+        assert!(splitter.buffer.is_empty());
+    }
+
+    #[test]
+    fn resumes_scanning_where_it_stopped() {
+        let message = br#"{"jsonrpc":"2.0","id":1,"result":["a\\",{"b":1}]}"#;
+        let mut splitter = MessageSplitter::default();
+        for (i, byte) in message.iter().enumerate() {
+            assert_eq!(splitter.take_message().unwrap(), None);
+            assert_eq!(splitter.scanned, i);
+            splitter.buffer.push(*byte);
+        }
+        assert_eq!(
+            splitter.take_message().unwrap().as_deref(),
+            Some(std::str::from_utf8(message).unwrap())
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_messages() {
+        assert!(splitter(b"not json").take_message().is_err());
+        assert!(splitter(b"{}}").take_message().unwrap().is_some());
+        assert!(splitter(b"}").take_message().is_err());
+    }
+}
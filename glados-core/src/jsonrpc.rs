@@ -1,20 +1,73 @@
-use std::{path::PathBuf, time::Duration};
+use std::{fmt, path::PathBuf, sync::Arc, time::Duration};
 
 use alloy_primitives::hex::FromHexError;
 use entity::content;
 use ethportal_api::types::enr::Enr;
 use ethportal_api::types::portal::TraceContentInfo;
-use ethportal_api::utils::bytes::ByteUtilsError;
+use ethportal_api::utils::bytes::{hex_encode, ByteUtilsError};
 use ethportal_api::{
     BeaconContentKey, BeaconNetworkApiClient, ContentKeyError, Discv5ApiClient, HistoryContentKey,
     HistoryNetworkApiClient, NodeInfo, OverlayContentKey, RoutingTableInfo, StateContentKey,
     StateNetworkApiClient, Web3ApiClient,
 };
-use jsonrpsee::http_client::{HttpClient, HttpClientBuilder};
+use jsonrpsee::{
+    core::{
+        async_trait,
+        client::{BatchResponse, ClientT},
+        params::BatchRequestBuilder,
+        traits::ToRpcParams,
+    },
+    http_client::{HttpClient, HttpClientBuilder},
+    rpc_params,
+};
+use serde::{de::DeserializeOwned, Deserialize};
 use serde_json::json;
 use thiserror::Error;
+use tower_http::decompression::{Decompression, DecompressionLayer};
 use url::Url;
 
+use crate::{
+    connection::{ConnectionLayer, ConnectionSettings, PortalHttpBackend},
+    ipc::{self, IpcClient},
+    proxy::ProxyError,
+};
+
+/// How long to wait for a Portal node to answer a request. Audits may give up on a request
+/// sooner, but not later.
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// HTTP JSON-RPC client of a Portal node, see [build_http_client].
+pub type PortalHttpClient = HttpClient<Decompression<PortalHttpBackend>>;
+
+/// Builds an HTTP JSON-RPC client of a Portal node, connecting to it with the `connection`
+/// settings.
+///
+/// With `compression`, requests accept gzip and deflate compressed responses, which are
+/// decompressed as they arrive. Traces of content lookups are large and compress well, which
+/// matters for remote Portal nodes. Nodes that don't compress their responses answer as usual.
+pub fn build_http_client(
+    url: impl AsRef<str>,
+    request_timeout: Duration,
+    max_concurrent_requests: Option<usize>,
+    connection: &ConnectionSettings,
+    compression: bool,
+) -> Result<PortalHttpClient, JsonRpcError> {
+    let decompression = DecompressionLayer::new()
+        .gzip(compression)
+        .deflate(compression);
+    let mut builder = HttpClientBuilder::default()
+        .request_timeout(request_timeout)
+        .set_http_middleware(
+            tower::ServiceBuilder::new()
+                .layer(decompression)
+                .layer(ConnectionLayer::new(connection)?),
+        );
+    if let Some(max_concurrent_requests) = max_concurrent_requests {
+        builder = builder.max_concurrent_requests(max_concurrent_requests);
+    }
+    Ok(builder.build(url)?)
+}
+
 /// Configuration details for connection to a Portal network node.
 #[derive(Clone, Debug)]
 pub enum TransportConfig {
@@ -22,9 +75,63 @@ pub enum TransportConfig {
     IPC(PathBuf),
 }
 
+/// JSON-RPC client of a Portal node, over HTTP or over the node's IPC socket.
+#[derive(Clone, Debug)]
+pub enum PortalRpcClient {
+    Http(Box<PortalHttpClient>),
+    Ipc(Arc<IpcClient>),
+}
+
+#[async_trait]
+impl ClientT for PortalRpcClient {
+    async fn notification<Params>(
+        &self,
+        method: &str,
+        params: Params,
+    ) -> Result<(), jsonrpsee::core::client::Error>
+    where
+        Params: ToRpcParams + Send,
+    {
+        match self {
+            PortalRpcClient::Http(client) => client.notification(method, params).await,
+            PortalRpcClient::Ipc(client) => {
+                client.client().await?.notification(method, params).await
+            }
+        }
+    }
+
+    async fn request<R, Params>(
+        &self,
+        method: &str,
+        params: Params,
+    ) -> Result<R, jsonrpsee::core::client::Error>
+    where
+        R: DeserializeOwned,
+        Params: ToRpcParams + Send,
+    {
+        match self {
+            PortalRpcClient::Http(client) => client.request(method, params).await,
+            PortalRpcClient::Ipc(client) => client.client().await?.request(method, params).await,
+        }
+    }
+
+    async fn batch_request<'a, R>(
+        &self,
+        batch: BatchRequestBuilder<'a>,
+    ) -> Result<BatchResponse<'a, R>, jsonrpsee::core::client::Error>
+    where
+        R: DeserializeOwned + fmt::Debug + 'a,
+    {
+        match self {
+            PortalRpcClient::Http(client) => client.batch_request(batch).await,
+            PortalRpcClient::Ipc(client) => client.client().await?.batch_request(batch).await,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct PortalApi {
-    pub client: HttpClient,
+    pub client: PortalRpcClient,
 }
 
 #[derive(Clone, Debug)]
@@ -78,7 +185,7 @@ pub enum JsonRpcError {
     #[error("received malformed response: {0}")]
     Malformed(serde_json::Error),
 
-    #[error("malformed portal client URL")]
+    #[error("malformed portal client URL `{url}`, use http:// or ipc://")]
     ClientURL { url: String },
 
     #[error("unable to use byte utils {0}")]
@@ -93,9 +200,18 @@ pub enum JsonRpcError {
         path: PathBuf,
     },
 
+    #[error("could not connect to IPC socket {path:?}")]
+    IpcConnectFailed {
+        source: std::io::Error,
+        path: PathBuf,
+    },
+
     #[error("Couldn't convert bytes to ContentKey")]
     ContentKeyError(#[from] ContentKeyError),
 
+    #[error("unusable proxy")]
+    Proxy(#[from] ProxyError),
+
     #[error("Query completed without finding content")]
     ContentNotFound { trace: Option<String> },
 }
@@ -119,9 +235,30 @@ pub struct Content {
     pub raw: Vec<u8>,
 }
 
+/// Response to `portal_historyPutContent`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PutContentInfo {
+    /// Number of peers the content was offered to.
+    pub peer_count: u32,
+    /// Whether the node also stored the content itself.
+    pub stored_locally: bool,
+}
+
 impl PortalClient {
-    pub async fn from(portal_client_url: String) -> Result<Self, JsonRpcError> {
-        let api = PortalApi::new(portal_client_url).await?;
+    pub async fn from(
+        portal_client_url: String,
+        max_concurrent_requests: Option<usize>,
+        connection: &ConnectionSettings,
+        compression: bool,
+    ) -> Result<Self, JsonRpcError> {
+        let api = PortalApi::new(
+            portal_client_url,
+            max_concurrent_requests,
+            connection,
+            compression,
+        )
+        .await?;
 
         let client_info = api.get_client_version().await?;
 
@@ -140,14 +277,39 @@ impl PortalClient {
 }
 
 impl PortalApi {
-    pub async fn new(client_url: String) -> Result<Self, JsonRpcError> {
+    /// Connects to a Portal node over HTTP, or over its IPC socket for `ipc://` URLs.
+    ///
+    /// Each request in flight holds its own HTTP/1.1 connection, so limiting the number of
+    /// concurrent requests also bounds the connections (and local ports) used for the node.
+    /// Requests beyond the limit wait for an earlier one to finish. Over HTTP/2, all requests
+    /// share one connection. The IPC socket is a single connection shared by all requests.
+    ///
+    /// The `connection` settings, like a proxy, only apply to HTTP. The IPC socket is local,
+    /// so it never goes through a proxy.
+    ///
+    /// With `compression`, HTTP responses may come compressed, see [build_http_client].
+    pub async fn new(
+        client_url: String,
+        max_concurrent_requests: Option<usize>,
+        connection: &ConnectionSettings,
+        compression: bool,
+    ) -> Result<Self, JsonRpcError> {
         let http_prefix = "http://";
-        let client = if client_url.strip_prefix(http_prefix).is_some() {
-            HttpClientBuilder::default()
-                .request_timeout(Duration::from_secs(120))
-                .build(client_url)?
+        let client = if let Some(path) = ipc::socket_path(&client_url) {
+            let client = IpcClient::connect(path.clone(), REQUEST_TIMEOUT, max_concurrent_requests)
+                .await
+                .map_err(|source| JsonRpcError::IpcConnectFailed { source, path })?;
+            PortalRpcClient::Ipc(Arc::new(client))
+        } else if client_url.strip_prefix(http_prefix).is_some() {
+            PortalRpcClient::Http(Box::new(build_http_client(
+                client_url,
+                REQUEST_TIMEOUT,
+                max_concurrent_requests,
+                connection,
+                compression,
+            )?))
         } else {
-            panic!("None supported RPC interface {client_url}, use http.");
+            return Err(JsonRpcError::ClientURL { url: client_url });
         };
 
         Ok(PortalApi { client })
@@ -221,6 +383,62 @@ impl PortalApi {
         }
     }
 
+    /// Looks up history content by key, for content that is not stored in the database.
+    pub async fn get_history_content(
+        &self,
+        content_key: HistoryContentKey,
+    ) -> Result<Option<Content>, JsonRpcError> {
+        match HistoryNetworkApiClient::get_content(&self.client, content_key).await {
+            Ok(content_info) => Ok(Some(Content {
+                raw: content_info.content.into(),
+            })),
+            Err(err) => match err.into() {
+                JsonRpcError::ContentNotFound { trace: _ } => Ok(None),
+                err => Err(err),
+            },
+        }
+    }
+
+    /// Looks up history content in the node's own storage, without querying the network.
+    pub async fn get_local_history_content(
+        &self,
+        content_key: HistoryContentKey,
+    ) -> Result<Option<Content>, JsonRpcError> {
+        match HistoryNetworkApiClient::local_content(&self.client, content_key).await {
+            Ok(content) => Ok(Some(Content {
+                raw: content.into(),
+            })),
+            Err(err) => match err.into() {
+                JsonRpcError::ContentNotFound { trace: _ } => Ok(None),
+                err => Err(err),
+            },
+        }
+    }
+
+    /// Gossips history content to the node's peers, returning the number of peers offered it.
+    pub async fn gossip_history_content(
+        &self,
+        content_key: HistoryContentKey,
+        content: Vec<u8>,
+    ) -> Result<u32, JsonRpcError> {
+        Ok(HistoryNetworkApiClient::gossip(&self.client, content_key, content.into()).await?)
+    }
+
+    /// Offers history content to the peers interested in it, with `portal_historyPutContent`.
+    pub async fn put_history_content(
+        &self,
+        content_key: HistoryContentKey,
+        content: Vec<u8>,
+    ) -> Result<PutContentInfo, JsonRpcError> {
+        Ok(self
+            .client
+            .request(
+                "portal_historyPutContent",
+                rpc_params![content_key.to_hex(), hex_encode(content)],
+            )
+            .await?)
+    }
+
     pub async fn get_content_with_trace(
         self,
         content: &content::Model,
@@ -1,3 +1,8 @@
+pub mod connection;
 pub mod db;
+pub mod feature_flags;
+pub mod history;
+pub mod ipc;
 pub mod jsonrpc;
+pub mod proxy;
 pub mod stats;
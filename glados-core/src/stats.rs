@@ -3,6 +3,7 @@ use std::fmt::Display;
 use chrono::{DateTime, Utc};
 
 use entity::{
+    client_info,
     content::{self, SubProtocol},
     content_audit::{
         self, AuditResult, BeaconSelectionStrategy, HistorySelectionStrategy, SelectionStrategy,
@@ -11,10 +12,11 @@ use entity::{
 };
 use sea_orm::{
     sea_query::{Expr, IntoCondition},
-    ColumnTrait, DatabaseConnection, DbErr, EntityTrait, JoinType, PaginatorTrait, QueryFilter,
-    QuerySelect, RelationTrait, Select,
+    ColumnTrait, DatabaseConnection, DbErr, EntityTrait, FromQueryResult, JoinType, PaginatorTrait,
+    QueryFilter, QueryOrder, QuerySelect, RelationTrait, Select,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 /// Generates a SeaORM select query for audits based on the provided filters.
 /// User can decide whether to retrieve or only count results.
@@ -25,6 +27,10 @@ pub fn filter_audits(filters: AuditFilters) -> Select<content_audit::Entity> {
     let audits = audits
         .join(JoinType::InnerJoin, content_audit::Relation::Content.def())
         .filter(content::Column::ProtocolId.eq(filters.network));
+    let audits = match filters.include_archived {
+        true => audits,
+        false => audits.filter(content::Column::Archived.eq(false)),
+    };
     // Strategy filters
     let audits = match filters.strategy {
         StrategyFilter::All => audits,
@@ -58,16 +64,23 @@ pub fn filter_audits(filters: AuditFilters) -> Select<content_audit::Entity> {
         SuccessFilter::Success => {
             audits.filter(content_audit::Column::Result.eq(AuditResult::Success))
         }
-        SuccessFilter::Failure => {
-            audits.filter(content_audit::Column::Result.eq(AuditResult::Failure))
+        SuccessFilter::Failure => audits.filter(
+            content_audit::Column::Result
+                .is_in([AuditResult::Failure, AuditResult::InvalidContent]),
+        ),
+        SuccessFilter::Errored => {
+            audits.filter(content_audit::Column::Result.eq(AuditResult::Errored))
+        }
+        SuccessFilter::Pending => {
+            audits.filter(content_audit::Column::Result.eq(AuditResult::Pending))
         }
     };
     // Content type filters
     match filters.content_type {
         ContentTypeFilter::All => audits,
-        ContentTypeFilter::Headers => {
-            audits.filter(Expr::cust("get_byte(content.content_key, 0) = 0x00").into_condition())
-        }
+        ContentTypeFilter::Headers => audits.filter(
+            Expr::cust("get_byte(content.content_key, 0) IN (0x00, 0x03)").into_condition(),
+        ),
         ContentTypeFilter::Bodies => {
             audits.filter(Expr::cust("get_byte(content.content_key, 0) = 0x01").into_condition())
         }
@@ -80,6 +93,15 @@ pub fn filter_audits(filters: AuditFilters) -> Select<content_audit::Entity> {
         ContentTypeFilter::BlockRoots => {
             audits.filter(Expr::cust("get_byte(content.content_key, 0) = 0x10").into_condition())
         }
+        ContentTypeFilter::UpdatesByRange => {
+            audits.filter(Expr::cust("get_byte(content.content_key, 0) = 0x11").into_condition())
+        }
+        ContentTypeFilter::FinalityUpdates => {
+            audits.filter(Expr::cust("get_byte(content.content_key, 0) = 0x12").into_condition())
+        }
+        ContentTypeFilter::OptimisticUpdates => {
+            audits.filter(Expr::cust("get_byte(content.content_key, 0) = 0x13").into_condition())
+        }
     }
 }
 
@@ -96,33 +118,66 @@ pub async fn get_audit_stats(
         .count(conn)
         .await? as u32;
 
-    let total_audits = filtered
+    let in_period = filtered.filter(content_audit::Column::CreatedAt.gt(cutoff));
+
+    let total_audits = in_period.clone().count(conn).await? as u32;
+
+    let total_passes = in_period
         .clone()
-        .filter(content_audit::Column::CreatedAt.gt(cutoff))
+        .filter(content_audit::Column::Result.eq(AuditResult::Success))
         .count(conn)
         .await? as u32;
 
-    let total_passes = filtered
-        .filter(content_audit::Column::CreatedAt.gt(cutoff))
+    // Content that was returned but did not match the canonical data counts as a failure.
+    let total_failures = in_period
+        .clone()
+        .filter(
+            content_audit::Column::Result
+                .is_in([AuditResult::Failure, AuditResult::InvalidContent]),
+        )
+        .count(conn)
+        .await? as u32;
+
+    // Traced lookups take a different RPC path, so they are measured separately.
+    let traced = in_period
+        .clone()
+        .filter(content_audit::Column::TraceRequested.eq(true));
+    let traced_passes = traced
+        .clone()
         .filter(content_audit::Column::Result.eq(AuditResult::Success))
         .count(conn)
         .await? as u32;
+    let traced_failures = traced
+        .filter(
+            content_audit::Column::Result
+                .is_in([AuditResult::Failure, AuditResult::InvalidContent]),
+        )
+        .count(conn)
+        .await? as u32;
 
-    let total_failures = total_audits - total_passes;
+    let total_errored = in_period
+        .clone()
+        .filter(content_audit::Column::Result.eq(AuditResult::Errored))
+        .count(conn)
+        .await? as u32;
+
+    let total_pending = in_period
+        .filter(content_audit::Column::Result.eq(AuditResult::Pending))
+        .count(conn)
+        .await? as u32;
 
     let audits_per_minute = (60 * total_audits)
         .checked_div(period.total_seconds())
         .unwrap_or(0);
 
-    let (pass_percent, fail_percent) = if total_audits == 0 {
-        (0.0, 0.0)
-    } else {
-        let total_audits = total_audits as f32;
-        (
-            (total_passes as f32) * 100.0 / total_audits,
-            (total_failures as f32) * 100.0 / total_audits,
-        )
-    };
+    // Pass and fail rates only consider audits that completed, so that errored or
+    // in-flight audits do not skew the measure of content availability.
+    let completed_audits = total_passes + total_failures;
+    let pass_percent = percent(total_passes, completed_audits);
+    let fail_percent = percent(total_failures, completed_audits);
+
+    let traced_audits = traced_passes + traced_failures;
+    let untraced_audits = completed_audits - traced_audits;
 
     Ok(AuditStats {
         period,
@@ -132,10 +187,58 @@ pub async fn get_audit_stats(
         pass_percent,
         total_failures,
         fail_percent,
+        traced_audits,
+        traced_pass_percent: percent(traced_passes, traced_audits),
+        untraced_audits,
+        untraced_pass_percent: percent(total_passes - traced_passes, untraced_audits),
+        total_errored,
+        total_pending,
         audits_per_minute,
     })
 }
 
+/// Calculates the outcomes of the given set of audits over the given period, for each auditing
+/// Portal client.
+pub async fn get_audit_stats_by_client(
+    filtered: Select<content_audit::Entity>,
+    period: Period,
+    conn: &DatabaseConnection,
+) -> Result<Vec<ClientAuditStats>, DbErr> {
+    filtered
+        .filter(content_audit::Column::CreatedAt.gt(period.cutoff_time()))
+        .join(
+            JoinType::LeftJoin,
+            content_audit::Relation::ClientInfo.def(),
+        )
+        .select_only()
+        .column_as(Expr::cust(client_info::CLIENT_NAME_SQL), "client")
+        .column_as(Expr::cust("COUNT(*)"), "total_audits")
+        .column_as(
+            Expr::cust("COUNT(*) FILTER (WHERE content_audit.result = 1)"),
+            "total_passes",
+        )
+        // Content that was returned but did not match the canonical data counts as a failure.
+        .column_as(
+            Expr::cust("COUNT(*) FILTER (WHERE content_audit.result IN (0, 4))"),
+            "total_failures",
+        )
+        .group_by(Expr::cust("client"))
+        .order_by_asc(Expr::cust("client"))
+        .into_model::<ClientAuditStats>()
+        .all(conn)
+        .await
+}
+
+/// Share of the whole as a percentage, or zero if the whole is zero.
+fn percent(part: u32, whole: u32) -> f32 {
+    if whole == 0 {
+        0.0
+    } else {
+        (part as f32) * 100.0 / (whole as f32)
+    }
+}
+
+#[derive(Serialize, ToSchema)]
 pub struct AuditStats {
     pub period: Period,
     pub new_content: u32,
@@ -144,9 +247,41 @@ pub struct AuditStats {
     pub pass_percent: f32,
     pub total_failures: u32,
     pub fail_percent: f32,
+    /// Completed audits that asked the node for a trace.
+    pub traced_audits: u32,
+    pub traced_pass_percent: f32,
+    /// Completed audits that did not ask the node for a trace.
+    pub untraced_audits: u32,
+    pub untraced_pass_percent: f32,
+    pub total_errored: u32,
+    pub total_pending: u32,
     pub audits_per_minute: u32,
 }
 
+/// Audit outcomes of one auditing Portal client.
+#[derive(Debug, FromQueryResult, Serialize)]
+pub struct ClientAuditStats {
+    /// Name of the client, like `trin`, or `unknown`.
+    pub client: String,
+    pub total_audits: i64,
+    pub total_passes: i64,
+    pub total_failures: i64,
+}
+
+impl ClientAuditStats {
+    /// Pass rate of the completed audits, leaving out errored and pending ones.
+    pub fn pass_percent(&self) -> f32 {
+        let completed = self.total_passes + self.total_failures;
+        if completed == 0 {
+            0.0
+        } else {
+            (self.total_passes as f32) * 100.0 / (completed as f32)
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
 pub enum Period {
     Hour,
     Day,
@@ -189,6 +324,9 @@ pub struct AuditFilters {
     pub content_type: ContentTypeFilter,
     pub success: SuccessFilter,
     pub network: SubProtocol,
+    /// Whether to include audits of archived content, which are hidden by default.
+    #[serde(default)]
+    pub include_archived: bool,
 }
 
 #[derive(Deserialize, Copy, Clone)]
@@ -206,6 +344,8 @@ pub enum SuccessFilter {
     All,
     Success,
     Failure,
+    Errored,
+    Pending,
 }
 
 #[derive(Deserialize, Copy, Clone)]
@@ -215,5 +355,12 @@ pub enum ContentTypeFilter {
     Bodies,
     Receipts,
     AccountTrieNodes,
+    /// Light client bootstraps, whose keys are block roots.
     BlockRoots,
+    /// Light client updates by range.
+    UpdatesByRange,
+    /// Light client finality updates.
+    FinalityUpdates,
+    /// Light client optimistic updates.
+    OptimisticUpdates,
 }
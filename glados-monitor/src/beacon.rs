@@ -1,6 +1,6 @@
 use anyhow::anyhow;
-use chrono::Utc;
-use entity::content::{self, SubProtocol};
+use chrono::{DateTime, TimeZone, Utc};
+use entity::content::{self, ContentSource, SubProtocol};
 
 use ethportal_api::{
     types::content_key::beacon::{LightClientFinalityUpdateKey, LightClientOptimisticUpdateKey},
@@ -15,7 +15,7 @@ use std::{
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 use tokio::time::sleep;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 /// Nimbus node to retrieve beacon data from.
 pub const PANDA_OPS_BEACON: &str = "https://nimbus.mainnet.ethpandaops.io";
@@ -23,6 +23,10 @@ pub const PANDA_OPS_BEACON: &str = "https://nimbus.mainnet.ethpandaops.io";
 const POLL_PERIOD_SECONDS: u64 = 1;
 // Beacon chain mainnet genesis time: Tue Dec 01 2020 12:00:23 GMT+0000
 pub const BEACON_GENESIS_TIME: u64 = 1606824023;
+/// Duration of a beacon chain slot.
+const SECONDS_PER_SLOT: u64 = 12;
+/// Number of slots in a sync committee period (256 epochs of 32 slots).
+const SLOTS_PER_PERIOD: u64 = 32 * 256;
 
 /// Checks for and stores new Beacon Light Client Bootstrap content keys.
 pub async fn follow_beacon_head(
@@ -39,7 +43,7 @@ pub async fn follow_beacon_head(
         "Retrieved initial block root: {}",
         latest_finalized_block_root
     );
-    store_bootstrap_content_key(&latest_finalized_block_root, conn.clone())
+    store_bootstrap_content_key(&latest_finalized_block_root, Utc::now(), conn.clone())
         .await
         .expect("Failed to store initial block root");
 
@@ -62,7 +66,8 @@ pub async fn follow_beacon_head(
             info!("New finalized block root: {}", latest_finalized_block_root);
 
             if let Err(err) =
-                store_bootstrap_content_key(&latest_finalized_block_root, conn.clone()).await
+                store_bootstrap_content_key(&latest_finalized_block_root, Utc::now(), conn.clone())
+                    .await
             {
                 error!("Failed to store bootstrap: {err:?}");
             }
@@ -71,14 +76,26 @@ pub async fn follow_beacon_head(
 }
 
 /// Stores a LightClientBootstrap content key for the given block hash if it doesn't already exist.
-async fn store_bootstrap_content_key(hash: &str, conn: DatabaseConnection) -> anyhow::Result<()> {
+async fn store_bootstrap_content_key(
+    hash: &str,
+    available_at: DateTime<Utc>,
+    conn: DatabaseConnection,
+) -> anyhow::Result<()> {
     let content_key = BeaconContentKey::LightClientBootstrap(LightClientBootstrapKey {
         block_hash: <[u8; 32]>::try_from(hex_decode(hash)?).map_err(|err| {
             anyhow::anyhow!("Failed to convert finalized block root to bytes: {err:?}")
         })?,
     });
 
-    match content::get_or_create(SubProtocol::Beacon, &content_key, Utc::now(), &conn).await {
+    match content::get_or_create(
+        SubProtocol::Beacon,
+        &content_key,
+        available_at,
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    {
         Ok(_) => debug!(
             content.key = hex_encode(content_key.to_bytes()),
             "Imported new beacon Bootstrap content key",
@@ -98,7 +115,15 @@ pub async fn store_lc_optimistic_update(
     let content_key = get_lc_optimistic_update_key(client, beacon_base_url).await?;
     let content_key = BeaconContentKey::LightClientOptimisticUpdate(content_key);
 
-    match content::get_or_create(SubProtocol::Beacon, &content_key, Utc::now(), &conn).await {
+    match content::get_or_create(
+        SubProtocol::Beacon,
+        &content_key,
+        Utc::now(),
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    {
         Ok(_) => {
             info!(
                 content.key = hex_encode(content_key.to_bytes()),
@@ -115,14 +140,29 @@ pub async fn store_lc_optimistic_update(
 
 /// Stores a LightClientUpdatesByRange content key for the current period if one doesnt already exist.
 pub async fn store_lc_update_by_range(conn: DatabaseConnection) -> anyhow::Result<()> {
-    let expected_period = expected_current_period();
+    store_lc_update_for_period(expected_current_period(), Utc::now(), conn).await
+}
 
+/// Stores a LightClientUpdatesByRange content key for a single period if one doesnt already exist.
+async fn store_lc_update_for_period(
+    period: u64,
+    available_at: DateTime<Utc>,
+    conn: DatabaseConnection,
+) -> anyhow::Result<()> {
     let content_key = BeaconContentKey::LightClientUpdatesByRange(LightClientUpdatesByRangeKey {
-        start_period: expected_period,
+        start_period: period,
         count: 1,
     });
 
-    match content::get_or_create(SubProtocol::Beacon, &content_key, Utc::now(), &conn).await {
+    match content::get_or_create(
+        SubProtocol::Beacon,
+        &content_key,
+        available_at,
+        ContentSource::Monitor,
+        &conn,
+    )
+    .await
+    {
         Ok(_) => {
             debug!(
                 content.key = hex_encode(content_key.to_bytes()),
@@ -137,6 +177,78 @@ pub async fn store_lc_update_by_range(conn: DatabaseConnection) -> anyhow::Resul
     }
 }
 
+/// Stores the content keys of historical sync committee periods, from `start_period` up to but not
+/// including `end_period`, so that audits cover more than the live head.
+///
+/// A LightClientUpdatesByRange key is stored for each period. If a beacon node is given, a
+/// LightClientBootstrap key is also stored for the checkpoint block at the start of each period.
+/// Keys are recorded as available from the end of their period.
+pub async fn backfill_beacon_command(
+    conn: DatabaseConnection,
+    start_period: u64,
+    end_period: u64,
+    beacon_base_url: Option<String>,
+) -> anyhow::Result<()> {
+    let current_period = expected_current_period();
+    if start_period >= end_period {
+        return Err(anyhow!(
+            "Start period {start_period} must be before end period {end_period}"
+        ));
+    }
+    if end_period > current_period {
+        return Err(anyhow!(
+            "End period {end_period} is after the current period {current_period}, which is covered by follow-beacon"
+        ));
+    }
+
+    let client = HttpClient::new();
+    let (mut updates, mut bootstraps) = (0, 0);
+    for period in start_period..end_period {
+        let available_at = period_start_time(period + 1);
+        store_lc_update_for_period(period, available_at, conn.clone()).await?;
+        updates += 1;
+
+        let Some(beacon_base_url) = &beacon_base_url else {
+            continue;
+        };
+        let slot = period * SLOTS_PER_PERIOD;
+        let block_root = match get_beacon_block_root(&client, beacon_base_url, slot).await {
+            Ok(block_root) => block_root,
+            Err(e) => {
+                warn!(period, slot, err=?e, "No checkpoint block root, skipping bootstrap");
+                continue;
+            }
+        };
+        store_bootstrap_content_key(&block_root, available_at, conn.clone()).await?;
+        bootstraps += 1;
+    }
+
+    info!(
+        start_period,
+        end_period, updates, bootstraps, "Backfilled beacon content keys"
+    );
+    Ok(())
+}
+
+/// Retrieve the root of the block at the given slot from the beacon node.
+async fn get_beacon_block_root(
+    client: &HttpClient,
+    beacon_base_url: &String,
+    slot: u64,
+) -> anyhow::Result<String> {
+    let url = format!("{}/eth/v1/beacon/blocks/{}/root", beacon_base_url, slot);
+    let response = client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    let response: Value = serde_json::from_str(&response)?;
+    let block_root: String = serde_json::from_value(response["data"]["root"].clone())?;
+    Ok(block_root)
+}
+
 /// Retrieve the latest finalized block root from the beacon node.
 async fn get_current_beacon_block_root(
     client: &HttpClient,
@@ -221,5 +333,13 @@ fn expected_current_period() -> u64 {
     let now = now.duration_since(UNIX_EPOCH).expect("Time went backwards");
     let since_genesis = now - std::time::Duration::from_secs(BEACON_GENESIS_TIME);
 
-    since_genesis.as_secs() / 12 / (32 * 256)
+    since_genesis.as_secs() / SECONDS_PER_SLOT / SLOTS_PER_PERIOD
+}
+
+/// Calculates when the given beacon period starts.
+fn period_start_time(period: u64) -> DateTime<Utc> {
+    let timestamp = BEACON_GENESIS_TIME + period * SLOTS_PER_PERIOD * SECONDS_PER_SLOT;
+    Utc.timestamp_opt(timestamp as i64, 0)
+        .single()
+        .expect("Period start time out of range")
 }
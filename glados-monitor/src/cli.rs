@@ -1,3 +1,6 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
 use clap::{Parser, Subcommand};
 
 #[derive(Parser, Debug)]
@@ -36,6 +39,26 @@ pub enum Commands {
 
     FollowBeaconPandaops {},
 
+    /// Stores beacon content keys for historical sync committee periods
+    BackfillBeacon {
+        #[arg(
+            long,
+            help = "The sync committee period with which the backfill will begin"
+        )]
+        start_period: u64,
+        #[arg(
+            long,
+            help = "The sync committee period (exclusive) with which the backfill will end"
+        )]
+        end_period: u64,
+        #[arg(
+            short,
+            long,
+            help = "Beacon node base URL, used to also store bootstraps for the checkpoint at the start of each period"
+        )]
+        beacon_base_url: Option<String>,
+    },
+
     /// Imports blocks from a remote provider
     BulkDownloadBlockData {
         #[arg(
@@ -84,4 +107,20 @@ pub enum Commands {
         #[arg(short, long, default_value = "100")]
         concurrency: u32,
     },
+
+    /// Imports hex encoded content keys, one per line, detecting the subprotocol of each
+    ImportKeys {
+        #[arg(
+            short,
+            long,
+            help = "File of content keys, or - to read them from stdin"
+        )]
+        file: PathBuf,
+        #[arg(
+            short,
+            long,
+            help = "When the content became available (RFC 3339), defaults to now"
+        )]
+        available_at: Option<DateTime<Utc>>,
+    },
 }
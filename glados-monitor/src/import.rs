@@ -0,0 +1,98 @@
+//! Imports content keys generated outside of Glados, like the keys gossiped by a bridge, so
+//! they can be audited.
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use entity::content::{self, ContentSource, SubProtocol};
+use ethportal_api::{BeaconContentKey, HistoryContentKey, OverlayContentKey, StateContentKey};
+use sea_orm::DatabaseConnection;
+use tokio::{
+    fs::File,
+    io::{self, AsyncBufRead, AsyncBufReadExt, BufReader},
+};
+use tracing::{info, warn};
+
+/// Path that reads the keys from stdin instead of a file.
+const STDIN_PATH: &str = "-";
+
+/// Imports the hex encoded content keys of a file, one per line, as first available at the given
+/// time.
+///
+/// The subprotocol of each key is the first one that decodes it. Blank lines and lines starting
+/// with `#` are skipped, and keys that do not decode are logged and skipped.
+pub async fn import_keys_command(
+    conn: DatabaseConnection,
+    file: PathBuf,
+    available_at: DateTime<Utc>,
+) -> Result<()> {
+    let reader: Box<dyn AsyncBufRead + Unpin + Send> = if file == Path::new(STDIN_PATH) {
+        Box::new(BufReader::new(io::stdin()))
+    } else {
+        Box::new(BufReader::new(File::open(&file).await.map_err(|e| {
+            anyhow!("Could not open key file {}: {e}", file.display())
+        })?))
+    };
+
+    let mut lines = reader.lines();
+    let mut line_number = 0;
+    let (mut history, mut state, mut beacon, mut invalid) = (0, 0, 0, 0);
+    while let Some(line) = lines.next_line().await? {
+        line_number += 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let key_hex = if line.starts_with("0x") {
+            line.to_owned()
+        } else {
+            format!("0x{line}")
+        };
+        match import_key(&key_hex, available_at, &conn).await {
+            Ok(SubProtocol::History) => history += 1,
+            Ok(SubProtocol::State) => state += 1,
+            Ok(SubProtocol::Beacon) => beacon += 1,
+            Err(e) => {
+                warn!(line = line_number, content.key = key_hex, err=?e, "Skipping content key");
+                invalid += 1;
+            }
+        }
+    }
+
+    info!(
+        history,
+        state,
+        beacon,
+        invalid,
+        "Imported content keys from {}",
+        file.display()
+    );
+    Ok(())
+}
+
+/// Stores a content key under the first subprotocol that decodes it, returning that subprotocol.
+async fn import_key(
+    key_hex: &str,
+    available_at: DateTime<Utc>,
+    conn: &DatabaseConnection,
+) -> Result<SubProtocol> {
+    if let Ok(key) = HistoryContentKey::try_from_hex(key_hex) {
+        store(SubProtocol::History, &key, available_at, conn).await
+    } else if let Ok(key) = StateContentKey::try_from_hex(key_hex) {
+        store(SubProtocol::State, &key, available_at, conn).await
+    } else if let Ok(key) = BeaconContentKey::try_from_hex(key_hex) {
+        store(SubProtocol::Beacon, &key, available_at, conn).await
+    } else {
+        Err(anyhow!("Not a history, state or beacon content key"))
+    }
+}
+
+async fn store<T: OverlayContentKey>(
+    sub_protocol: SubProtocol,
+    key: &T,
+    available_at: DateTime<Utc>,
+    conn: &DatabaseConnection,
+) -> Result<SubProtocol> {
+    content::get_or_create(sub_protocol, key, available_at, ContentSource::Import, conn).await?;
+    Ok(sub_protocol)
+}
@@ -3,6 +3,7 @@ use std::time::Duration;
 use alloy_primitives::B256;
 use anyhow::{anyhow, Result};
 use chrono::{DateTime, TimeZone, Utc};
+use entity::content::ContentSource;
 use futures::future::join_all;
 use glados_core::db::store_block_keys;
 use reqwest::header;
@@ -23,6 +24,7 @@ use url::Url;
 
 pub mod beacon;
 pub mod cli;
+pub mod import;
 pub mod state;
 
 pub async fn run_glados_monitor(conn: DatabaseConnection, w3: web3::Web3<web3::transports::Http>) {
@@ -124,7 +126,14 @@ async fn retrieve_new_blocks(
 
         let block_num =
             i32::try_from(block_number_to_retrieve).expect("Block num does not fit in i32.");
-        store_block_keys(block_num, &block_hash.0, block_time, &conn).await;
+        store_block_keys(
+            block_num,
+            &block_hash.0,
+            block_time,
+            ContentSource::Monitor,
+            &conn,
+        )
+        .await;
     }
 }
 
@@ -214,7 +223,14 @@ pub async fn bulk_download_block_data(
 
                 let block_number =
                     i32::try_from(block_number).expect("Block num does not fit in i32.");
-                store_block_keys(block_number, &block_hash.0, block_time, &conn).await;
+                store_block_keys(
+                    block_number,
+                    &block_hash.0,
+                    block_time,
+                    ContentSource::Import,
+                    &conn,
+                )
+                .await;
                 drop(permit);
             });
         }
@@ -259,7 +275,14 @@ pub async fn bulk_download_block_data(
 
                         let block_number =
                             i32::try_from(block_number).expect("Block num does not fit in i32.");
-                        store_block_keys(block_number, &block_hash.0, block_time, &conn).await;
+                        store_block_keys(
+                            block_number,
+                            &block_hash.0,
+                            block_time,
+                            ContentSource::Import,
+                            &conn,
+                        )
+                        .await;
                     })
                 })
                 .collect();
@@ -1,9 +1,11 @@
 use anyhow::Result;
+use chrono::Utc;
 use clap::Parser;
 use glados_monitor::{
-    beacon::{panda_ops_http, PANDA_OPS_BEACON},
+    beacon::{backfill_beacon_command, panda_ops_http, PANDA_OPS_BEACON},
     bulk_download_block_data,
     cli::{Cli, Commands},
+    import::import_keys_command,
     panda_ops_web3, run_glados_monitor, run_glados_monitor_beacon,
     state::{follow_head_state_command, populate_state_roots_range_command},
 };
@@ -62,6 +64,19 @@ async fn main() -> Result<()> {
         Some(Commands::FollowBeaconPandaops {}) => {
             task::spawn(follow_beacon_command_pandaops(conn))
         }
+        Some(Commands::BackfillBeacon {
+            start_period,
+            end_period,
+            beacon_base_url,
+        }) => {
+            info!("Backfilling beacon content keys");
+            task::spawn(backfill_beacon_command(
+                conn,
+                *start_period,
+                *end_period,
+                beacon_base_url.clone(),
+            ))
+        }
         Some(Commands::BulkDownloadBlockData {
             start_block_number,
             end_block_number,
@@ -96,6 +111,14 @@ async fn main() -> Result<()> {
                 *concurrency,
             ))
         }
+        Some(Commands::ImportKeys { file, available_at }) => {
+            info!("Importing content keys");
+            task::spawn(import_keys_command(
+                conn,
+                file.clone(),
+                available_at.unwrap_or_else(Utc::now),
+            ))
+        }
         &None => {
             info!("No command specified");
             task::spawn(do_nothing())
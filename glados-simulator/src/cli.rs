@@ -0,0 +1,88 @@
+use chrono::{DateTime, Utc};
+use clap::Parser;
+
+const DEFAULT_NODES: &str = "1000";
+const DEFAULT_CENSUSES: &str = "96";
+// 15 minutes, matching the default census interval of glados-cartographer.
+const DEFAULT_CENSUS_INTERVAL: &str = "900";
+const DEFAULT_BLOCKS: &str = "5000";
+const DEFAULT_AUDITS: &str = "50000";
+const DEFAULT_SEED: &str = "0";
+
+#[derive(Clone, Debug, PartialEq, Parser)]
+#[command(author, version, about = "Fills a database with synthetic Glados data", long_about = None)]
+pub struct Args {
+    #[arg(short, long)]
+    pub database_url: String,
+
+    #[arg(long, default_value = DEFAULT_NODES, help = "number of nodes in the network")]
+    pub nodes: u32,
+
+    #[arg(long, default_value = DEFAULT_CENSUSES, help = "number of censuses to generate")]
+    pub censuses: u32,
+
+    #[arg(long, default_value = DEFAULT_CENSUS_INTERVAL, help = "seconds between censuses")]
+    pub census_interval: u32,
+
+    #[arg(
+        long,
+        default_value = DEFAULT_BLOCKS,
+        help = "number of blocks whose header, body and receipts are added as content"
+    )]
+    pub blocks: u32,
+
+    #[arg(long, default_value = DEFAULT_AUDITS, help = "number of audits to generate")]
+    pub audits: u32,
+
+    #[arg(
+        long,
+        default_value = DEFAULT_SEED,
+        help = "random seed, so that a run can be repeated"
+    )]
+    pub seed: u64,
+
+    #[arg(long, help = "end of the simulated period (RFC 3339), defaults to now")]
+    pub end: Option<DateTime<Utc>>,
+
+    #[arg(
+        long,
+        help = "add to a database that already contains content, instead of refusing to run"
+    )]
+    pub allow_existing_data: bool,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DATABASE_URL: &str = "postgres://localhost:5432";
+
+    /// Tests that the default scale is used when no sizes are given.
+    #[test]
+    fn test_defaults() {
+        let result = Args::parse_from(["test", "--database-url", DATABASE_URL]);
+        let expected = Args {
+            database_url: DATABASE_URL.to_string(),
+            nodes: 1000,
+            censuses: 96,
+            census_interval: 900,
+            blocks: 5000,
+            audits: 50000,
+            seed: 0,
+            end: None,
+            allow_existing_data: false,
+        };
+        assert_eq!(result, expected);
+    }
+
+    /// Tests that the end of the simulated period is parsed, for repeatable runs.
+    #[test]
+    fn test_end() {
+        const END: &str = "2024-10-01T00:00:00Z";
+        let result = Args::parse_from(["test", "--database-url", DATABASE_URL, "--end", END]);
+        assert_eq!(
+            result.end,
+            Some(DateTime::parse_from_rfc3339(END).unwrap().into())
+        );
+    }
+}
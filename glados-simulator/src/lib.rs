@@ -0,0 +1,319 @@
+//! Fills a database with synthetic nodes, censuses, content and audits.
+//!
+//! Used to load-test glados-web against a realistically sized database, and to try out
+//! migrations against a populated schema before running them on the production database.
+//! Data is drawn from a seeded random number generator, so a run can be repeated exactly when
+//! given the same seed and end time.
+use std::net::Ipv4Addr;
+
+use alloy_primitives::U256;
+use anyhow::{bail, Result};
+use chrono::{DateTime, Duration, Utc};
+use enr::{k256::ecdsa::SigningKey, CombinedKey};
+use ethportal_api::types::enr::Enr;
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    rngs::StdRng,
+    Rng, SeedableRng,
+};
+use sea_orm::{ActiveValue::NotSet, DatabaseConnection, EntityTrait, PaginatorTrait, Set};
+use tracing::info;
+
+use entity::{
+    census, census_node, client_info,
+    content::{self, ContentSource, SubProtocol},
+    content_audit::{self, AuditResult, HistorySelectionStrategy, SelectionStrategy},
+    execution_metadata, record,
+};
+use glados_core::history::block_content_keys;
+
+use crate::cli::Args;
+
+pub mod cli;
+
+/// Rows inserted per statement when bulk inserting.
+const INSERT_BATCH_SIZE: usize = 1000;
+
+/// How long each census takes to survey the network, in seconds.
+const CENSUS_DURATION: u32 = 60;
+
+/// Number of nodes that audits are attributed to, like a small fleet of Glados auditors.
+const AUDITOR_COUNT: usize = 4;
+
+/// Client code, name, version and relative share of the network.
+const CLIENTS: [(&str, &str, &str, u32); 4] = [
+    ("t", "trin", "0.1.1", 50),
+    ("f", "fluffy", "0.1.0", 25),
+    ("u", "ultralight", "0.0.9", 15),
+    ("s", "shisui", "0.1.0", 10),
+];
+
+/// Audit strategies and their relative share of audits.
+const STRATEGIES: [(HistorySelectionStrategy, u32); 5] = [
+    (HistorySelectionStrategy::Latest, 4),
+    (HistorySelectionStrategy::Random, 2),
+    (HistorySelectionStrategy::RandomUnaudited, 1),
+    (HistorySelectionStrategy::FourFours, 2),
+    (HistorySelectionStrategy::SelectOldestUnaudited, 1),
+];
+
+/// Configuration created from CLI arguments.
+#[derive(Clone, Debug)]
+pub struct SimulatorConfig {
+    pub nodes: u32,
+    pub censuses: u32,
+    pub census_interval: Duration,
+    pub blocks: u32,
+    pub audits: u32,
+    pub seed: u64,
+    /// End of the simulated period, which censuses, content and audits lead up to.
+    pub end: DateTime<Utc>,
+    pub allow_existing_data: bool,
+}
+
+impl From<Args> for SimulatorConfig {
+    fn from(args: Args) -> Self {
+        SimulatorConfig {
+            nodes: args.nodes,
+            censuses: args.censuses,
+            census_interval: Duration::seconds(args.census_interval as i64),
+            blocks: args.blocks,
+            audits: args.audits,
+            seed: args.seed,
+            end: args.end.unwrap_or_else(Utc::now),
+            allow_existing_data: args.allow_existing_data,
+        }
+    }
+}
+
+/// A generated node, and how it behaves over the simulation.
+struct SimulatedNode {
+    node_id: i32,
+    record_id: i32,
+    client_info_id: i32,
+    /// Chance of the node being found by each census.
+    uptime: f64,
+    radius: U256,
+}
+
+/// A generated content key, and how likely audits are to find it.
+struct SimulatedContent {
+    id: i32,
+    available_at: DateTime<Utc>,
+    retrievability: f64,
+}
+
+pub async fn run_glados_simulator(conn: DatabaseConnection, config: SimulatorConfig) -> Result<()> {
+    let existing_content = content::Entity::find().count(&conn).await?;
+    if existing_content > 0 && !config.allow_existing_data {
+        bail!(
+            "Database already contains {existing_content} content keys. Pass --allow-existing-data to add simulated data anyway."
+        );
+    }
+
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let end = config.end;
+    let start = end - config.census_interval * config.censuses as i32;
+
+    let nodes = simulate_nodes(&mut rng, config.nodes, &conn).await?;
+    info!(count = nodes.len(), "Simulated nodes");
+    simulate_censuses(&mut rng, &nodes, start, &config, &conn).await?;
+    info!(count = config.censuses, "Simulated censuses");
+    let content = simulate_content(&mut rng, config.blocks, start, end, &conn).await?;
+    info!(count = content.len(), "Simulated content");
+    simulate_audits(&mut rng, &nodes, &content, end, config.audits, &conn).await?;
+    info!(count = config.audits, "Simulated audits");
+    Ok(())
+}
+
+/// Creates nodes with an ENR each, drawn from the client distribution of the network.
+async fn simulate_nodes(
+    rng: &mut StdRng,
+    count: u32,
+    conn: &DatabaseConnection,
+) -> Result<Vec<SimulatedNode>> {
+    let client_weights = WeightedIndex::new(CLIENTS.iter().map(|client| client.3))?;
+    let mut nodes = vec![];
+    for _ in 0..count {
+        let (code, name, version, _) = CLIENTS[client_weights.sample(rng)];
+        let client_info = client_info::get_or_create(format!("{name} v{version}"), conn).await?;
+
+        let key = CombinedKey::Secp256k1(SigningKey::random(&mut *rng));
+        let ip = Ipv4Addr::new(rng.gen_range(1..224), rng.gen(), rng.gen(), rng.gen());
+        let client = format!("{code} {version}");
+        let enr: Enr = {
+            let mut builder = Enr::builder();
+            builder
+                .ip4(ip)
+                .udp4(9009)
+                .add_value("c", &client.as_bytes())
+                .build(&key)?
+        };
+        let record = record::get_or_create(&enr, conn).await?;
+
+        // Most nodes are always online, the rest come and go.
+        let uptime = match rng.gen_bool(0.8) {
+            true => rng.gen_range(0.9..1.0),
+            false => rng.gen_range(0.2..0.8),
+        };
+        // Most nodes store everything, the rest a fraction of the keyspace.
+        let radius = match rng.gen_bool(0.6) {
+            true => U256::MAX,
+            false => U256::MAX >> rng.gen_range(1..16usize),
+        };
+        nodes.push(SimulatedNode {
+            node_id: record.node_id,
+            record_id: record.id,
+            client_info_id: client_info.id,
+            uptime,
+            radius,
+        });
+    }
+    Ok(nodes)
+}
+
+/// Creates censuses at regular intervals, each finding the nodes that happen to be online.
+async fn simulate_censuses(
+    rng: &mut StdRng,
+    nodes: &[SimulatedNode],
+    start: DateTime<Utc>,
+    config: &SimulatorConfig,
+    conn: &DatabaseConnection,
+) -> Result<()> {
+    for i in 0..config.censuses {
+        let started_at = start + config.census_interval * i as i32;
+        let census =
+            census::create(started_at, CENSUS_DURATION, SubProtocol::History, conn).await?;
+        let mut census_nodes = vec![];
+        for node in nodes {
+            if !rng.gen_bool(node.uptime) {
+                continue;
+            }
+            let surveyed_at =
+                started_at + Duration::seconds(rng.gen_range(0..CENSUS_DURATION as i64));
+            // Nodes that are often offline are also the ones that need PING retries.
+            let ping_attempts = match rng.gen_bool(node.uptime) {
+                true => 1,
+                false => rng.gen_range(2..=3),
+            };
+            census_nodes.push(census_node::ActiveModel {
+                id: NotSet,
+                census_id: Set(census.id),
+                record_id: Set(node.record_id),
+                surveyed_at: Set(surveyed_at),
+                data_radius: Set(node.radius.to_be_bytes::<32>().to_vec()),
+                data_radius_high: Set(node.radius.wrapping_shr(193).to::<i64>()),
+                sub_network: Set(SubProtocol::History),
+                ping_attempts: Set(Some(ping_attempts)),
+                ping_latency_ms: Set(Some(rng.gen_range(20..500))),
+                is_self: Set(false),
+                udp_reachable: Set(None),
+                tcp_reachable: Set(None),
+            });
+        }
+        for batch in census_nodes.chunks(INSERT_BATCH_SIZE) {
+            census_node::Entity::insert_many(batch.to_vec())
+                .exec(conn)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Creates the content keys of random blocks, as stored for the era of each block.
+async fn simulate_content(
+    rng: &mut StdRng,
+    blocks: u32,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    conn: &DatabaseConnection,
+) -> Result<Vec<SimulatedContent>> {
+    let span_seconds = (end - start).num_seconds().max(1);
+    let mut content = vec![];
+    for _ in 0..blocks {
+        let block_number: i32 = rng.gen_range(0..21_000_000);
+        let block_hash: [u8; 32] = rng.gen();
+        let available_at = start + Duration::seconds(rng.gen_range(0..span_seconds));
+        // Some blocks are poorly replicated, so all of their content is hard to find.
+        let retrievability = match rng.gen_bool(0.9) {
+            true => rng.gen_range(0.9..1.0),
+            false => rng.gen_range(0.0..0.5),
+        };
+        for (_, key) in block_content_keys(block_number, &block_hash) {
+            let model = content::get_or_create(
+                SubProtocol::History,
+                &key,
+                available_at,
+                ContentSource::Simulator,
+                conn,
+            )
+            .await?;
+            execution_metadata::get_or_create(model.id, block_number, conn).await?;
+            content.push(SimulatedContent {
+                id: model.id,
+                available_at,
+                retrievability,
+            });
+        }
+    }
+    Ok(content)
+}
+
+/// Creates audits of random content, made after the content became available.
+async fn simulate_audits(
+    rng: &mut StdRng,
+    nodes: &[SimulatedNode],
+    content: &[SimulatedContent],
+    end: DateTime<Utc>,
+    count: u32,
+    conn: &DatabaseConnection,
+) -> Result<()> {
+    if nodes.is_empty() || content.is_empty() {
+        return Ok(());
+    }
+    let auditors = &nodes[..nodes.len().min(AUDITOR_COUNT)];
+    let strategy_weights = WeightedIndex::new(STRATEGIES.iter().map(|strategy| strategy.1))?;
+
+    let mut audits = vec![];
+    for _ in 0..count {
+        let item = &content[rng.gen_range(0..content.len())];
+        let auditor = &auditors[rng.gen_range(0..auditors.len())];
+        let since_available = (end - item.available_at).num_seconds().max(1);
+        let result = if rng.gen_bool(0.01) {
+            AuditResult::Errored
+        } else if rng.gen_bool(item.retrievability) {
+            match rng.gen_bool(0.001) {
+                true => AuditResult::InvalidContent,
+                false => AuditResult::Success,
+            }
+        } else {
+            AuditResult::Failure
+        };
+        audits.push(content_audit::ActiveModel {
+            id: NotSet,
+            content_key: Set(item.id),
+            client_info: Set(Some(auditor.client_info_id)),
+            node: Set(Some(auditor.node_id)),
+            created_at: Set(
+                item.available_at + Duration::seconds(rng.gen_range(0..since_available))
+            ),
+            strategy_used: Set(Some(SelectionStrategy::History(
+                STRATEGIES[strategy_weights.sample(rng)].0.clone(),
+            ))),
+            result: Set(result),
+            trace: Set("".to_owned()),
+            trace_requested: Set(false),
+            error_reason: NotSet,
+            census_id: NotSet,
+            selection_lag_ms: NotSet,
+            duration_ms: NotSet,
+            timeout_ms: NotSet,
+        });
+    }
+    for batch in audits.chunks(INSERT_BATCH_SIZE) {
+        content_audit::Entity::insert_many(batch.to_vec())
+            .exec(conn)
+            .await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,19 @@
+use anyhow::Result;
+use clap::Parser;
+use sea_orm::Database;
+use tracing::info;
+
+use glados_simulator::{cli::Args, run_glados_simulator, SimulatorConfig};
+use migration::{Migrator, MigratorTrait};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    env_logger::init();
+    let args = Args::parse();
+
+    let conn = Database::connect(&args.database_url).await?;
+    info!("database connection established");
+    Migrator::up(&conn, None).await?;
+
+    run_glados_simulator(conn, SimulatorConfig::from(args)).await
+}
@@ -0,0 +1,102 @@
+// Charts the share of eligible keys each strategy has audited at least once, by day.
+
+function createCoverageChart(height, width, coverage) {
+    const marginTop = 40;
+    const marginRight = 120;
+    const marginBottom = 20;
+    const marginLeft = 40;
+
+    coverage.forEach(d => d.day = new Date(d.day));
+    const byStrategy = d3.group(coverage, d => d.strategy);
+
+    const x = d3.scaleTime()
+        .domain(d3.extent(coverage, d => d.day))
+        .range([marginLeft, width - marginRight]);
+
+    const y = d3.scaleLinear()
+        .domain([0, 100])
+        .range([height - marginBottom, marginTop]);
+
+    const colors = d3.scaleOrdinal(d3.schemeTableau10);
+
+    const svg = d3.create("svg")
+        .attr("width", width)
+        .attr("height", height)
+        .attr("viewBox", [0, 0, width, height])
+        .attr("style", "max-width: 100%; height: auto; height: intrinsic;");
+
+    svg.append("g")
+        .attr("transform", `translate(0,${height - marginBottom})`)
+        .call(d3.axisBottom(x).ticks(width / 80).tickSizeOuter(0));
+
+    svg.append("g")
+        .attr("transform", `translate(${marginLeft},0)`)
+        .call(d3.axisLeft(y).ticks(height / 40).tickFormat(d => d + "%"))
+        .call(g => g.select(".domain").remove())
+        .call(g => g.selectAll(".tick line").clone()
+            .attr("x2", width - marginLeft - marginRight)
+            .attr("stroke-opacity", 0.1))
+        .call(g => g.append("text")
+            .attr("x", -marginLeft)
+            .attr("y", 10)
+            .attr("fill", "currentColor")
+            .attr("text-anchor", "start")
+            .text("↑ Keys audited at least once"));
+
+    svg.append("text")
+        .attr("class", "graph-title")
+        .attr("text-anchor", "middle")
+        .attr("x", width / 2)
+        .attr("y", marginTop / 2)
+        .text("Audit Coverage, by day");
+
+    const line = d3.line()
+        .x(d => x(d.day))
+        .y(d => y(d.coverage_percent));
+
+    for (const [strategy, points] of byStrategy) {
+        svg.append("path")
+            .datum(points)
+            .attr("fill", "none")
+            .attr("stroke", colors(strategy))
+            .attr("stroke-width", 1.5)
+            .attr("d", line)
+            .append("title")
+            .text(strategy);
+    }
+
+    const legend = svg.selectAll(".legend")
+        .data(Array.from(byStrategy.keys()))
+        .enter().append("g")
+        .attr("class", "legend")
+        .attr("transform", (d, i) => `translate(${width - marginRight + 10}, ${(i * 20) + marginTop})`)
+        .style("font", "10px sans-serif");
+
+    legend.append("rect")
+        .attr("width", 18)
+        .attr("height", 18)
+        .attr("fill", d => colors(d));
+
+    legend.append("text")
+        .attr("x", 24)
+        .attr("y", 9)
+        .attr("dy", ".35em")
+        .text(d => d);
+
+    return svg.node();
+}
+
+async function auditCoverageChart() {
+    const timezone = Intl.DateTimeFormat().resolvedOptions().timeZone;
+    const response = await fetch(`api/audit-coverage/?tz=${encodeURIComponent(timezone)}`);
+    if (!response.ok) {
+        console.error('Could not fetch audit coverage:', response.status);
+        return;
+    }
+    const coverage = await response.json();
+    if (coverage.length === 0) {
+        console.log('No data available to plot the audit coverage chart');
+        return;
+    }
+    document.getElementById('audit-coverage-graph').appendChild(createCoverageChart(400, 670, coverage));
+}
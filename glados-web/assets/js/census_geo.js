@@ -0,0 +1,92 @@
+// Charts the number of nodes in each country, and lists the largest autonomous systems, for a
+// single census.
+
+const CENSUS_GEO_MAX_BARS = 20;
+
+function createCensusCountryChart(width, censusGeo) {
+    const marginTop = 20;
+    const marginRight = 40;
+    const marginBottom = 20;
+    const marginLeft = 60;
+    const barHeight = 20;
+
+    const countries = censusGeo.countries.slice(0, CENSUS_GEO_MAX_BARS);
+    if (censusGeo.unknown_country > 0) {
+        countries.push({ country_code: "Unknown", node_count: censusGeo.unknown_country });
+    }
+    const height = marginTop + marginBottom + countries.length * barHeight;
+
+    const x = d3.scaleLinear()
+        .domain([0, d3.max(countries, d => d.node_count)]).nice()
+        .range([marginLeft, width - marginRight]);
+
+    const y = d3.scaleBand()
+        .domain(countries.map(d => d.country_code))
+        .range([marginTop, height - marginBottom])
+        .padding(0.1);
+
+    const svg = d3.create("svg")
+        .attr("width", width)
+        .attr("height", height)
+        .attr("viewBox", [0, 0, width, height])
+        .attr("style", "max-width: 100%; height: auto; height: intrinsic;");
+
+    svg.append("g")
+        .attr("fill", "steelblue")
+        .selectAll("rect")
+        .data(countries)
+        .join("rect")
+        .attr("x", x(0))
+        .attr("y", d => y(d.country_code))
+        .attr("width", d => x(d.node_count) - x(0))
+        .attr("height", y.bandwidth())
+        .append("title")
+        .text(d => `${d.country_code}: ${d.node_count} nodes`);
+
+    svg.append("g")
+        .attr("font-size", 10)
+        .selectAll("text")
+        .data(countries)
+        .join("text")
+        .attr("x", d => x(d.node_count) + 4)
+        .attr("y", d => y(d.country_code) + y.bandwidth() / 2)
+        .attr("dy", "0.35em")
+        .text(d => d.node_count);
+
+    svg.append("g")
+        .attr("transform", `translate(0,${height - marginBottom})`)
+        .call(d3.axisBottom(x).ticks(width / 80).tickSizeOuter(0));
+
+    svg.append("g")
+        .attr("transform", `translate(${marginLeft},0)`)
+        .call(d3.axisLeft(y).tickSizeOuter(0));
+
+    return svg.node();
+}
+
+function createCensusAsnList(censusGeo) {
+    const list = document.createElement("ol");
+    for (const asn of censusGeo.asns.slice(0, CENSUS_GEO_MAX_BARS)) {
+        const item = document.createElement("li");
+        const organization = asn.organization ? ` ${asn.organization}` : "";
+        item.textContent = `AS${asn.asn}${organization}: ${asn.node_count} nodes`;
+        list.appendChild(item);
+    }
+    return list;
+}
+
+async function censusGeoChart(censusId) {
+    const response = await fetch(`/api/census-geo/?census-id=${censusId}`);
+    if (!response.ok) {
+        console.error('Could not fetch census locations:', response.status);
+        return;
+    }
+    const censusGeo = await response.json();
+    if (censusGeo.countries.length === 0 && censusGeo.asns.length === 0) {
+        console.log('No located nodes to plot the census location chart');
+        return;
+    }
+    document.getElementById('census-geo-card').classList.remove('d-none');
+    document.getElementById('census-geo-graph').appendChild(createCensusCountryChart(670, censusGeo));
+    document.getElementById('census-asn-list').appendChild(createCensusAsnList(censusGeo));
+}
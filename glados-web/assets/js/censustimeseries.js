@@ -364,7 +364,17 @@ function getClientStringFromDecodedEnr(decodedEnr) {
 // Fetch the census node records from the API.
 async function getCensusTimeSeriesData(numDaysAgo, subprotocol) {
 
-    const baseUrl = `census-node-timeseries-data/?days-ago=${numDaysAgo}&network=${subprotocol}`;
+    // Align the daily window to midnight in the viewer's timezone.
+    const timezone = Intl.DateTimeFormat().resolvedOptions().timeZone;
+    let baseUrl = `census-node-timeseries-data/?days-ago=${numDaysAgo}&network=${subprotocol}&tz=${encodeURIComponent(timezone)}`;
+    // Node filters and paging given to the explorer page are passed on to the API.
+    const pageParams = new URL(window.location).searchParams;
+    for (const param of ['client', 'nickname', 'node-id-prefix', 'protocol-version', 'supports', 'offset', 'limit']) {
+        const value = pageParams.get(param);
+        if (value) {
+            baseUrl += `&${param}=${encodeURIComponent(value)}`;
+        }
+    }
     return fetch(`${baseUrl}`)
         .then(response => {
             if (!response.ok) {
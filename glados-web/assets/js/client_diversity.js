@@ -0,0 +1,97 @@
+// Charts the client diversity indices of each census over time.
+
+function createClientDiversityChart(height, width, diversity) {
+    const marginTop = 40;
+    const marginRight = 160;
+    const marginBottom = 20;
+    const marginLeft = 40;
+
+    diversity.forEach(d => d.started_at = new Date(d.started_at));
+    const series = [
+        { name: "Effective number of clients", value: d => d.effective_number },
+        { name: "Entropy (bits)", value: d => d.entropy },
+    ];
+
+    const x = d3.scaleTime()
+        .domain(d3.extent(diversity, d => d.started_at))
+        .range([marginLeft, width - marginRight]);
+
+    const y = d3.scaleLinear()
+        .domain([0, d3.max(diversity, d => Math.max(d.effective_number, d.entropy))]).nice()
+        .range([height - marginBottom, marginTop]);
+
+    const colors = d3.scaleOrdinal(d3.schemeTableau10);
+
+    const svg = d3.create("svg")
+        .attr("width", width)
+        .attr("height", height)
+        .attr("viewBox", [0, 0, width, height])
+        .attr("style", "max-width: 100%; height: auto; height: intrinsic;");
+
+    svg.append("g")
+        .attr("transform", `translate(0,${height - marginBottom})`)
+        .call(d3.axisBottom(x).ticks(width / 80).tickSizeOuter(0));
+
+    svg.append("g")
+        .attr("transform", `translate(${marginLeft},0)`)
+        .call(d3.axisLeft(y).ticks(height / 40))
+        .call(g => g.select(".domain").remove())
+        .call(g => g.selectAll(".tick line").clone()
+            .attr("x2", width - marginLeft - marginRight)
+            .attr("stroke-opacity", 0.1));
+
+    svg.append("text")
+        .attr("class", "graph-title")
+        .attr("text-anchor", "middle")
+        .attr("x", width / 2)
+        .attr("y", marginTop / 2)
+        .text("Client Diversity, by census");
+
+    for (const s of series) {
+        const line = d3.line()
+            .x(d => x(d.started_at))
+            .y(d => y(s.value(d)));
+        svg.append("path")
+            .datum(diversity)
+            .attr("fill", "none")
+            .attr("stroke", colors(s.name))
+            .attr("stroke-width", 1.5)
+            .attr("d", line)
+            .append("title")
+            .text(s.name);
+    }
+
+    const legend = svg.selectAll(".legend")
+        .data(series.map(s => s.name))
+        .enter().append("g")
+        .attr("class", "legend")
+        .attr("transform", (d, i) => `translate(${width - marginRight + 10}, ${(i * 20) + marginTop})`)
+        .style("font", "10px sans-serif");
+
+    legend.append("rect")
+        .attr("width", 18)
+        .attr("height", 18)
+        .attr("fill", d => colors(d));
+
+    legend.append("text")
+        .attr("x", 24)
+        .attr("y", 9)
+        .attr("dy", ".35em")
+        .text(d => d);
+
+    return svg.node();
+}
+
+async function clientDiversityChart() {
+    const response = await fetch('api/census-client-diversity/');
+    if (!response.ok) {
+        console.error('Could not fetch client diversity:', response.status);
+        return;
+    }
+    const diversity = await response.json();
+    if (diversity.length === 0) {
+        console.log('No data available to plot the client diversity chart');
+        return;
+    }
+    document.getElementById('client-diversity-graph').appendChild(createClientDiversityChart(400, 670, diversity));
+}
@@ -0,0 +1,71 @@
+// Shows the audits of a subnetwork as they are recorded, newest first, from the `/ws/audits`
+// WebSocket.
+
+const LIVE_AUDITS_MAX_ROWS = 25;
+const LIVE_AUDITS_RECONNECT_MS = 5000;
+
+const LIVE_AUDIT_BADGES = {
+    Success: "success",
+    Fail: "danger",
+    Errored: "warning",
+    Invalid: "dark",
+};
+
+function createLiveAuditRow(audit) {
+    const row = document.createElement("tr");
+    const cell = (content) => {
+        const td = document.createElement("td");
+        if (content instanceof Node) {
+            td.appendChild(content);
+        } else {
+            td.textContent = content;
+        }
+        row.appendChild(td);
+        return td;
+    };
+    const link = (href, text) => {
+        const a = document.createElement("a");
+        a.href = href;
+        a.textContent = text;
+        return a;
+    };
+
+    cell(link(`/audit/id/${audit.id}`, audit.id));
+    cell(new Date(audit.created_at).toLocaleTimeString());
+    const key = link(`/content/key/${audit.content_key}/`, `${audit.content_key.slice(0, 18)}…`);
+    key.classList.add("font-monospace");
+    cell(key);
+    const badge = document.createElement("span");
+    badge.className = `badge text-bg-${LIVE_AUDIT_BADGES[audit.result] || "secondary"}`;
+    badge.textContent = audit.result;
+    cell(badge);
+    cell(audit.strategy);
+    cell(audit.client || "Unknown");
+    cell(audit.duration_ms === null ? "" : `${audit.duration_ms} ms`).classList.add("text-end");
+    return row;
+}
+
+function liveAudits(network) {
+    const body = document.getElementById("live-audits-body");
+    const status = document.getElementById("live-audits-status");
+    const scheme = window.location.protocol === "https:" ? "wss" : "ws";
+    const socket = new WebSocket(`${scheme}://${window.location.host}/ws/audits?network=${network}`);
+
+    socket.addEventListener("open", () => {
+        status.textContent = "Connected";
+        status.className = "badge text-bg-success";
+    });
+    socket.addEventListener("message", (event) => {
+        const audit = JSON.parse(event.data);
+        document.getElementById("live-audits-empty")?.remove();
+        body.prepend(createLiveAuditRow(audit));
+        while (body.rows.length > LIVE_AUDITS_MAX_ROWS) {
+            body.deleteRow(-1);
+        }
+    });
+    socket.addEventListener("close", () => {
+        status.textContent = "Reconnecting";
+        status.className = "badge text-bg-secondary";
+        setTimeout(() => liveAudits(network), LIVE_AUDITS_RECONNECT_MS);
+    });
+}
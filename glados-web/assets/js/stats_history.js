@@ -258,7 +258,9 @@ function convertDataForChart(data) {
 }
 
 function getStatsRecords(weeksAgo) {
-    const baseUrl = `api/stat-history/?weeks-ago=${weeksAgo}`;
+    // Align the weekly window to midnight in the viewer's timezone.
+    const timezone = Intl.DateTimeFormat().resolvedOptions().timeZone;
+    const baseUrl = `api/stat-history/?weeks-ago=${weeksAgo}&tz=${encodeURIComponent(timezone)}`;
 
     return fetch(baseUrl)
         .then(response => {
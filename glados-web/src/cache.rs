@@ -0,0 +1,175 @@
+//! HTTP caching for glados-web responses.
+//!
+//! Successful GET responses carry a weak ETag so browsers can revalidate without downloading
+//! the body again, and expensive aggregate endpoints are additionally held in an in-process
+//! cache for a short time so repeated requests do not re-run their queries.
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    body::{boxed, Body, Bytes, Empty, Full},
+    extract::State,
+    http::{header, HeaderMap, HeaderValue, Method, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tracing::error;
+
+/// Most responses held by the cache. The key includes the query string, so without a bound,
+/// requests with varying queries would grow the cache without limit.
+const MAX_CACHED_RESPONSES: usize = 1000;
+
+/// Recently generated response bodies, keyed by request path and query.
+pub struct ResponseCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, CachedResponse>>,
+}
+
+struct CachedResponse {
+    stored_at: Instant,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl ResponseCache {
+    pub fn new(ttl: Duration) -> Self {
+        ResponseCache {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the cached response for a key, if it has not expired. Expired entries are dropped.
+    fn get(&self, key: &str) -> Option<(HeaderMap, Bytes)> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|_, entry| entry.stored_at.elapsed() < self.ttl);
+        entries
+            .get(key)
+            .map(|entry| (entry.headers.clone(), entry.body.clone()))
+    }
+
+    /// Stores a response. Expired entries are dropped first, then the oldest ones if the cache
+    /// is still full.
+    fn insert(&self, key: String, headers: HeaderMap, body: Bytes) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|_, entry| entry.stored_at.elapsed() < self.ttl);
+        while entries.len() >= MAX_CACHED_RESPONSES && !entries.contains_key(&key) {
+            let Some(oldest) = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.stored_at)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            entries.remove(&oldest);
+        }
+        entries.insert(
+            key,
+            CachedResponse {
+                stored_at: Instant::now(),
+                headers,
+                body,
+            },
+        );
+    }
+}
+
+/// Serves GET requests from the response cache, storing successful responses on a miss.
+///
+/// Responses are cached per environment, as the same URL shows different data in each.
+pub async fn cache_response(
+    State(cache): State<Arc<ResponseCache>>,
+    request: Request<Body>,
+    next: Next<Body>,
+) -> Response {
+    if request.method() != Method::GET {
+        return next.run(request).await;
+    }
+    let environment = request
+        .extensions()
+        .get::<Arc<crate::state::State>>()
+        .map(|state| state.environment.as_str())
+        .unwrap_or_default();
+    let key = format!("{environment} {}", request.uri());
+    if let Some((headers, body)) = cache.get(&key) {
+        let mut response = Response::new(boxed(Full::from(body)));
+        *response.headers_mut() = headers;
+        return response;
+    }
+
+    let response = next.run(request).await;
+    if response.status() != StatusCode::OK {
+        return response;
+    }
+    let (parts, body) = response.into_parts();
+    let body = match hyper::body::to_bytes(body).await {
+        Ok(body) => body,
+        Err(e) => {
+            error!(cache.key=key, err=?e, "Could not read response body for caching");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    cache.insert(key, parts.headers.clone(), body.clone());
+    Response::from_parts(parts, boxed(Full::from(body)))
+}
+
+/// Adds a weak ETag to successful GET responses, and answers `304 Not Modified` when the
+/// request's `If-None-Match` already holds it.
+///
+/// The tag is computed from the uncompressed body, so it is weak: the same tag is used for
+/// every content encoding.
+pub async fn etag(request: Request<Body>, next: Next<Body>) -> Response {
+    if request.method() != Method::GET {
+        return next.run(request).await;
+    }
+    let if_none_match = request.headers().get(header::IF_NONE_MATCH).cloned();
+
+    let response = next.run(request).await;
+    if response.status() != StatusCode::OK {
+        return response;
+    }
+    let (mut parts, body) = response.into_parts();
+    let body = match hyper::body::to_bytes(body).await {
+        Ok(body) => body,
+        Err(e) => {
+            error!(err=?e, "Could not read response body for ETag");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let tag = weak_etag(&body);
+
+    if if_none_match.is_some_and(|value| etag_matches(&value, &tag)) {
+        parts.status = StatusCode::NOT_MODIFIED;
+        parts.headers.remove(header::CONTENT_LENGTH);
+        parts.headers.insert(header::ETAG, tag);
+        return Response::from_parts(parts, boxed(Empty::new()));
+    }
+    parts.headers.insert(header::ETAG, tag);
+    Response::from_parts(parts, boxed(Full::from(body)))
+}
+
+fn weak_etag(body: &[u8]) -> HeaderValue {
+    let mut hasher = DefaultHasher::new();
+    body.hash(&mut hasher);
+    HeaderValue::from_str(&format!("W/\"{:016x}\"", hasher.finish()))
+        .expect("hex digits are a valid header value")
+}
+
+/// Whether an `If-None-Match` header value includes the given tag, or is `*`.
+fn etag_matches(if_none_match: &HeaderValue, tag: &HeaderValue) -> bool {
+    let Ok(if_none_match) = if_none_match.to_str() else {
+        return false;
+    };
+    let Ok(tag) = tag.to_str() else {
+        return false;
+    };
+    let opaque_tag = tag.trim_start_matches("W/");
+    if_none_match
+        .split(',')
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == opaque_tag)
+}
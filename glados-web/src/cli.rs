@@ -1,8 +1,130 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    path::PathBuf,
+};
+
 use clap::Parser;
 
+use crate::{
+    explorer::{DEFAULT_BLOCK_URL_TEMPLATE, DEFAULT_SLOT_URL_TEMPLATE},
+    listen::Listen,
+};
+
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 pub struct Args {
     #[arg(short, long)]
     pub database_url: String,
+
+    #[arg(
+        long,
+        default_value = "default",
+        value_parser = parse_environment_name,
+        help = "name of the environment of --database-url, shown in the environment switcher"
+    )]
+    pub environment_name: String,
+
+    #[arg(
+        long = "environment",
+        value_name = "NAME=DATABASE_URL",
+        value_parser = parse_environment,
+        help = "an additional environment that can be selected in the UI, may be repeated"
+    )]
+    pub environments: Vec<(String, String)>,
+
+    #[arg(
+        long,
+        default_value = "60",
+        help = "seconds to cache responses of aggregate API endpoints"
+    )]
+    pub cache_ttl: u64,
+
+    #[arg(
+        long,
+        default_value = "2",
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "requests to each expensive page or endpoint (like the census timeseries) that run at once"
+    )]
+    pub heavy_endpoint_concurrency: u64,
+
+    #[arg(
+        long,
+        default_value = "8",
+        help = "requests to each expensive page or endpoint that wait for their turn, before more are turned away with 503"
+    )]
+    pub heavy_endpoint_queue: u64,
+
+    #[arg(
+        long,
+        default_value = DEFAULT_BLOCK_URL_TEMPLATE,
+        help = "explorer URL for an execution block, with {block} replaced by its number or hash"
+    )]
+    pub block_url_template: String,
+
+    #[arg(
+        long,
+        default_value = DEFAULT_SLOT_URL_TEMPLATE,
+        help = "explorer URL for a beacon slot, with {slot} replaced by its number or block root"
+    )]
+    pub slot_url_template: String,
+
+    #[arg(
+        long,
+        help = "bearer token required by the admin API, like setting the site banner; the admin API is disabled without one"
+    )]
+    pub admin_token: Option<String>,
+
+    #[arg(
+        long,
+        env = "GLADOS_WEB_LISTEN_ADDR",
+        default_value = "0.0.0.0",
+        help = "address to serve on"
+    )]
+    pub listen_addr: IpAddr,
+
+    #[arg(
+        long,
+        env = "GLADOS_WEB_PORT",
+        default_value = "3001",
+        help = "port to serve on"
+    )]
+    pub port: u16,
+
+    #[arg(
+        long,
+        env = "GLADOS_WEB_UNIX_SOCKET",
+        conflicts_with_all = ["listen_addr", "port"],
+        help = "unix socket to serve on instead of --listen-addr and --port, like for a reverse proxy on the same host"
+    )]
+    pub unix_socket: Option<PathBuf>,
+}
+
+impl Args {
+    pub fn listen(&self) -> Listen {
+        match &self.unix_socket {
+            Some(path) => Listen::Unix(path.clone()),
+            None => Listen::Tcp(SocketAddr::new(self.listen_addr, self.port)),
+        }
+    }
+}
+
+/// Environment names are kept to characters that can be stored in a cookie as they are.
+fn parse_environment_name(name: &str) -> Result<String, String> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "invalid environment name '{name}', use letters, digits, '-' and '_'"
+        ));
+    }
+    Ok(name.to_owned())
+}
+
+fn parse_environment(value: &str) -> Result<(String, String), String> {
+    let Some((name, database_url)) = value.split_once('=') else {
+        return Err(format!("expected NAME=DATABASE_URL, got '{value}'"));
+    };
+    Ok((parse_environment_name(name)?, database_url.to_owned()))
 }
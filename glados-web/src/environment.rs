@@ -0,0 +1,99 @@
+//! Serving several Glados databases, like mainnet and a testnet, from one glados-web.
+//!
+//! Each database is an environment with its own [State]. The environment of a request is chosen
+//! by the `glados_env` cookie, which the switcher in the navigation bar sets, and its state is
+//! added to the request for handlers to take with `Extension<Arc<State>>`.
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use axum::{
+    body::Body,
+    extract::State as MiddlewareState,
+    http::{header, HeaderValue, Request},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::state::State;
+
+/// Cookie holding the name of the selected environment.
+pub const ENVIRONMENT_COOKIE: &str = "glados_env";
+
+/// The environments that can be selected, the first being the default.
+pub struct Environments {
+    states: Vec<Arc<State>>,
+}
+
+impl Environments {
+    pub fn new(states: Vec<Arc<State>>) -> Result<Self> {
+        if states.is_empty() {
+            bail!("At least one environment is required.");
+        }
+        for (i, state) in states.iter().enumerate() {
+            if states[..i]
+                .iter()
+                .any(|other| other.environment == state.environment)
+            {
+                bail!("Environment '{}' is configured twice.", state.environment);
+            }
+        }
+        Ok(Environments { states })
+    }
+
+    pub fn default_state(&self) -> &Arc<State> {
+        &self.states[0]
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Arc<State>> {
+        self.states.iter().find(|state| state.environment == name)
+    }
+
+    pub fn states(&self) -> &[Arc<State>] {
+        &self.states
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.states
+            .iter()
+            .map(|state| state.environment.clone())
+            .collect()
+    }
+}
+
+/// Adds the state of the environment selected by the request's cookie, or of the default
+/// environment, to the request.
+///
+/// When there are several environments, the same URL returns different responses depending on
+/// the cookie, so responses say so for the benefit of HTTP caches.
+pub async fn select_environment(
+    MiddlewareState(environments): MiddlewareState<Arc<Environments>>,
+    mut request: Request<Body>,
+    next: Next<Body>,
+) -> Response {
+    let state = selected_environment(&request)
+        .and_then(|name| environments.get(&name))
+        .unwrap_or(environments.default_state())
+        .clone();
+    request.extensions_mut().insert(state);
+    request.extensions_mut().insert(environments.clone());
+
+    let mut response = next.run(request).await;
+    if environments.states().len() > 1 {
+        response
+            .headers_mut()
+            .append(header::VARY, HeaderValue::from_static("cookie"));
+    }
+    response
+}
+
+fn selected_environment(request: &Request<Body>) -> Option<String> {
+    request
+        .headers()
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|cookies| cookies.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(name, _)| *name == ENVIRONMENT_COOKIE)
+        .map(|(_, value)| value.to_owned())
+}
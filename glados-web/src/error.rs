@@ -0,0 +1,272 @@
+//! Errors returned by route handlers, and extractors for their path parameters.
+//!
+//! API routes render errors as a JSON `{"error": ...}` object, pages render them with the error
+//! page template. Path parameters are parsed by extractors so that a malformed id is reported
+//! as a bad request instead of a panic or an internal error.
+use std::{collections::HashMap, fmt::Display, str::FromStr};
+
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Path},
+    http::{request::Parts, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use ethportal_api::utils::bytes::hex_decode;
+use sea_orm::DbErr;
+use serde::Serialize;
+use tracing::{error, warn};
+use utoipa::ToSchema;
+
+use crate::templates::{ErrorTemplate, HtmlTemplate};
+
+/// An error returned by a route handler.
+#[derive(Debug)]
+pub enum WebError {
+    /// A path or query parameter could not be parsed.
+    InvalidParameter {
+        name: &'static str,
+        value: String,
+        reason: String,
+    },
+    /// A required query parameter was not given.
+    MissingParameter(&'static str),
+    /// The request is well formed, but not signed or authorized by the right key.
+    Forbidden(String),
+    /// The request conflicts with the current state of the item.
+    Conflict(String),
+    /// The requested item does not exist.
+    NotFound(String),
+    /// An admin route was called without the admin token.
+    Unauthorized,
+    /// The request can't be served because a service it depends on is not running.
+    Unavailable(String),
+    /// The database could not be queried.
+    Database(anyhow::Error),
+}
+
+impl WebError {
+    pub fn invalid_parameter(name: &'static str, value: &str, reason: impl Display) -> Self {
+        WebError::InvalidParameter {
+            name,
+            value: value.to_owned(),
+            reason: reason.to_string(),
+        }
+    }
+
+    pub fn not_found(what: impl Display) -> Self {
+        WebError::NotFound(what.to_string())
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            WebError::InvalidParameter { .. } | WebError::MissingParameter(_) => {
+                StatusCode::BAD_REQUEST
+            }
+            WebError::Forbidden(_) => StatusCode::FORBIDDEN,
+            WebError::Conflict(_) => StatusCode::CONFLICT,
+            WebError::NotFound(_) => StatusCode::NOT_FOUND,
+            WebError::Unauthorized => StatusCode::UNAUTHORIZED,
+            WebError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            WebError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// The message shown to the user. Database errors are only logged, as they may leak
+    /// details of the schema.
+    pub fn message(&self) -> String {
+        match self {
+            WebError::InvalidParameter {
+                name,
+                value,
+                reason,
+            } => format!("Invalid {name} \"{value}\": {reason}"),
+            WebError::MissingParameter(name) => format!("Missing {name} parameter"),
+            WebError::Forbidden(reason)
+            | WebError::Conflict(reason)
+            | WebError::Unavailable(reason) => reason.clone(),
+            WebError::NotFound(what) => format!("{what} not found"),
+            WebError::Unauthorized => "A valid admin token is required".to_owned(),
+            WebError::Database(_) => "The database could not be queried".to_owned(),
+        }
+    }
+
+    fn log(&self) {
+        match self {
+            WebError::InvalidParameter {
+                name,
+                value,
+                reason,
+            } => warn!(name, value, reason, "Invalid parameter"),
+            WebError::MissingParameter(name) => warn!(name, "Missing parameter"),
+            WebError::Forbidden(reason) => warn!(reason, "Forbidden request"),
+            WebError::Conflict(_) => {}
+            WebError::NotFound(_) => {}
+            WebError::Unauthorized => warn!("Admin request without a valid token"),
+            WebError::Unavailable(reason) => warn!(reason, "Service unavailable"),
+            WebError::Database(e) => error!(err=?e, "Database error"),
+        }
+    }
+}
+
+impl From<DbErr> for WebError {
+    fn from(e: DbErr) -> Self {
+        WebError::Database(e.into())
+    }
+}
+
+impl From<anyhow::Error> for WebError {
+    fn from(e: anyhow::Error) -> Self {
+        WebError::Database(e)
+    }
+}
+
+/// The body of the errors of API routes.
+#[derive(Serialize, ToSchema)]
+pub(crate) struct ErrorBody {
+    error: String,
+}
+
+/// Renders the error as JSON, for API routes.
+impl IntoResponse for WebError {
+    fn into_response(self) -> Response {
+        self.log();
+        let body = ErrorBody {
+            error: self.message(),
+        };
+        (self.status(), Json(body)).into_response()
+    }
+}
+
+/// An error rendered as an HTML page, for the routes that serve pages.
+#[derive(Debug)]
+pub struct PageError(pub WebError);
+
+impl From<WebError> for PageError {
+    fn from(e: WebError) -> Self {
+        PageError(e)
+    }
+}
+
+impl From<DbErr> for PageError {
+    fn from(e: DbErr) -> Self {
+        PageError(e.into())
+    }
+}
+
+impl From<anyhow::Error> for PageError {
+    fn from(e: anyhow::Error) -> Self {
+        PageError(e.into())
+    }
+}
+
+impl IntoResponse for PageError {
+    fn into_response(self) -> Response {
+        self.0.log();
+        let status = self.0.status();
+        let template = ErrorTemplate {
+            status: status.as_u16(),
+            reason: status.canonical_reason().unwrap_or("Error").to_owned(),
+            message: self.0.message(),
+        };
+        (status, HtmlTemplate(template)).into_response()
+    }
+}
+
+/// The rejection of a path parameter extractor, rendered as JSON for API routes and as a page
+/// otherwise.
+pub struct ParamRejection {
+    error: WebError,
+    is_api: bool,
+}
+
+impl IntoResponse for ParamRejection {
+    fn into_response(self) -> Response {
+        if self.is_api {
+            self.error.into_response()
+        } else {
+            PageError(self.error).into_response()
+        }
+    }
+}
+
+/// Extracts the single path parameter of a route as a string.
+async fn single_path_param<S: Send + Sync>(
+    name: &'static str,
+    parts: &mut Parts,
+    state: &S,
+) -> Result<String, ParamRejection> {
+    let is_api = parts.uri.path().starts_with("/api/");
+    Path::<String>::from_request_parts(parts, state)
+        .await
+        .map(|Path(value)| value)
+        .map_err(|e| ParamRejection {
+            error: WebError::invalid_parameter(name, parts.uri.path(), e),
+            is_api,
+        })
+}
+
+/// Parses a hex encoded parameter, with or without a `0x` prefix.
+pub fn parse_hex_param(name: &'static str, value: &str) -> Result<Vec<u8>, WebError> {
+    hex_decode(value).map_err(|e| WebError::invalid_parameter(name, value, e))
+}
+
+/// Parses a parameter with its `FromStr` implementation.
+pub fn parse_param<T>(name: &'static str, value: &str) -> Result<T, WebError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    value
+        .parse::<T>()
+        .map_err(|e| WebError::invalid_parameter(name, value, e))
+}
+
+/// Parses a required query parameter with its `FromStr` implementation.
+pub fn parse_required_param<T>(
+    params: &HashMap<String, String>,
+    name: &'static str,
+) -> Result<T, WebError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let value = params.get(name).ok_or(WebError::MissingParameter(name))?;
+    parse_param(name, value)
+}
+
+/// A hex encoded path parameter, like a node id or content key.
+pub struct HexParam {
+    /// The parameter as given, to show it back to the user.
+    pub hex: String,
+    pub bytes: Vec<u8>,
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for HexParam {
+    type Rejection = ParamRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let is_api = parts.uri.path().starts_with("/api/");
+        let hex = single_path_param("hex value", parts, state).await?;
+        let bytes =
+            parse_hex_param("hex value", &hex).map_err(|error| ParamRejection { error, is_api })?;
+        Ok(HexParam { hex, bytes })
+    }
+}
+
+/// A numeric id path parameter, like an audit id.
+pub struct IdParam(pub i32);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for IdParam {
+    type Rejection = ParamRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let is_api = parts.uri.path().starts_with("/api/");
+        let id = single_path_param("id", parts, state).await?;
+        parse_param("id", &id)
+            .map(IdParam)
+            .map_err(|error| ParamRejection { error, is_api })
+    }
+}
@@ -0,0 +1,100 @@
+//! Links from content pages to the same data on external block explorers.
+//!
+//! Link targets are configured as URL templates, so a deployment can point at any explorer
+//! (or a testnet explorer). `{block}` is replaced with an execution block number or hash, and
+//! `{slot}` with a beacon slot number or block root.
+use alloy_primitives::hex;
+use entity::content::{self, SubProtocol};
+use ethportal_api::{BeaconContentKey, HistoryContentKey, OverlayContentKey};
+
+pub const DEFAULT_BLOCK_URL_TEMPLATE: &str = "https://etherscan.io/block/{block}";
+pub const DEFAULT_SLOT_URL_TEMPLATE: &str = "https://beaconcha.in/slot/{slot}";
+
+/// URL templates of the explorers to link to.
+#[derive(Clone, Debug)]
+pub struct ExplorerLinks {
+    block_url_template: String,
+    slot_url_template: String,
+}
+
+/// A link to render next to content, e.g. "etherscan.io" pointing at the block page.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExternalLink {
+    pub label: String,
+    pub url: String,
+}
+
+impl ExplorerLinks {
+    pub fn new(block_url_template: String, slot_url_template: String) -> Self {
+        ExplorerLinks {
+            block_url_template,
+            slot_url_template,
+        }
+    }
+
+    /// Links for stored content, given the block number from its execution metadata if any.
+    ///
+    /// State content has no single block or slot to link to.
+    pub fn for_content(
+        &self,
+        content: &content::Model,
+        block_number: Option<i32>,
+    ) -> Vec<ExternalLink> {
+        match content.protocol_id {
+            SubProtocol::History => HistoryContentKey::try_from_bytes(&content.content_key)
+                .map(|key| self.for_history_key(&key, block_number))
+                .unwrap_or_default(),
+            SubProtocol::Beacon => BeaconContentKey::try_from_bytes(&content.content_key)
+                .map(|key| self.for_beacon_key(&key))
+                .unwrap_or_default(),
+            SubProtocol::State => vec![],
+        }
+    }
+
+    /// Links for a History content key, to its block by number when the execution metadata
+    /// is known, and otherwise by block hash.
+    pub fn for_history_key(
+        &self,
+        content_key: &HistoryContentKey,
+        block_number: Option<i32>,
+    ) -> Vec<ExternalLink> {
+        let block = match block_number {
+            Some(block_number) => block_number.to_string(),
+            None => {
+                #[allow(unreachable_patterns)]
+                let block_hash = match content_key {
+                    HistoryContentKey::BlockHeaderByHash(key) => key.block_hash,
+                    HistoryContentKey::BlockBody(key) => key.block_hash,
+                    HistoryContentKey::BlockReceipts(key) => key.block_hash,
+                    _ => return vec![],
+                };
+                hex::encode_prefixed(block_hash)
+            }
+        };
+        vec![link(&self.block_url_template, "{block}", &block)]
+    }
+
+    /// Links for a Beacon content key that refers to a single slot.
+    pub fn for_beacon_key(&self, content_key: &BeaconContentKey) -> Vec<ExternalLink> {
+        #[allow(unreachable_patterns)]
+        let slot = match content_key {
+            BeaconContentKey::LightClientBootstrap(key) => hex::encode_prefixed(key.block_hash),
+            BeaconContentKey::LightClientOptimisticUpdate(key) => key.signature_slot.to_string(),
+            BeaconContentKey::LightClientFinalityUpdate(key) => key.finalized_slot.to_string(),
+            _ => return vec![],
+        };
+        vec![link(&self.slot_url_template, "{slot}", &slot)]
+    }
+}
+
+fn link(template: &str, placeholder: &str, value: &str) -> ExternalLink {
+    let url = template.replace(placeholder, value);
+    // Label links by the explorer's host, falling back to the whole URL.
+    let label = url
+        .split("://")
+        .nth(1)
+        .and_then(|rest| rest.split('/').next())
+        .unwrap_or(&url)
+        .to_string();
+    ExternalLink { label, url }
+}
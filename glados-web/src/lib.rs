@@ -1,30 +1,48 @@
+use std::path::Path;
 use std::sync::Arc;
-use std::{net::SocketAddr, path::Path};
 
 use anyhow::{bail, Result};
 use axum::{
-    extract::Extension,
-    routing::{get, get_service},
+    http::{header, HeaderValue},
+    middleware,
+    routing::{delete, get, get_service, post, put},
     Router,
 };
-use tower_http::services::ServeDir;
+use tower_http::{
+    compression::CompressionLayer, services::ServeDir, set_header::SetResponseHeaderLayer,
+};
 use tracing::info;
 
 use alloy_primitives::U256;
 use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
 
+pub mod cache;
 pub mod cli;
+pub mod environment;
+pub mod error;
+pub mod explorer;
+pub mod listen;
+pub mod live;
+pub mod openapi;
 pub mod routes;
 pub mod state;
 pub mod templates;
+pub mod throttle;
 
-use crate::state::State;
-
-const SOCKET: &str = "0.0.0.0:3001";
+use crate::{
+    cache::ResponseCache,
+    environment::Environments,
+    listen::{serve, Listen},
+    state::State,
+    throttle::ConcurrencyLimit,
+};
 
 const ASSET_PATH_ENV_VAR: &str = "GLADOS_WEB_ASSETS_PATH";
 
-pub async fn run_glados_web(config: Arc<State>) -> Result<()> {
+/// Static assets only change on deploy.
+const STATIC_CACHE_CONTROL: &str = "public, max-age=3600";
+
+pub async fn run_glados_web(environments: Arc<Environments>, listen: Listen) -> Result<()> {
     let assets_path = match std::env::var(ASSET_PATH_ENV_VAR) {
         Ok(path) => Path::new(&path).to_path_buf(),
         Err(_) => {
@@ -40,28 +58,29 @@ pub async fn run_glados_web(config: Arc<State>) -> Result<()> {
 
     let serve_dir = get_service(ServeDir::new(assets_path)).handle_error(routes::handle_error);
 
-    let nodes_with_zero_high_bits = entity::node::Entity::find()
-        .filter(entity::node::Column::NodeIdHigh.eq(0))
-        .all(&config.database_connection)
-        .await
-        .unwrap();
-
-    info!(rows=?nodes_with_zero_high_bits.len(), "One time migration: setting high bits for node model");
-
-    for node_model in nodes_with_zero_high_bits {
-        let raw_node_id = U256::from_be_slice(&node_model.get_node_id().raw());
-        let node_id_high: i64 = raw_node_id.wrapping_shr(193).to::<i64>();
-
-        let mut node: entity::node::ActiveModel = node_model.into();
-        let previous_value = node.node_id_high;
-        node.node_id_high = Set(node_id_high);
-        let updated = node.update(&config.database_connection).await?;
-        info!(row.id=?updated.id, old=?previous_value, new=?updated.node_id_high, "Setting high bits");
+    for config in environments.states() {
+        set_node_id_high_bits(config).await?;
     }
 
+    // Expensive endpoints each get their own limit, shared by all environments.
+    let default_state = environments.default_state();
+    let heavy_endpoint_limit = || {
+        middleware::from_fn_with_state(
+            Arc::new(ConcurrencyLimit::new(
+                default_state.heavy_endpoint_concurrency,
+                default_state.heavy_endpoint_queue,
+            )),
+            throttle::limit_concurrency,
+        )
+    };
+
     // setup router
-    let app = Router::new()
-        .route("/", get(routes::network_overview))
+    let pages = Router::new()
+        // Builds the radius charts from the latest census.
+        .route(
+            "/",
+            get(routes::network_overview).layer(heavy_endpoint_limit()),
+        )
         .route("/census/census-list/", get(routes::census_explorer_list))
         .route("/census/", get(routes::single_census_view))
         .route("/census/explorer", get(routes::census_explorer))
@@ -81,29 +100,223 @@ pub async fn run_glados_web(config: Arc<State>) -> Result<()> {
             get(routes::contentkey_detail),
         )
         .route("/audit/id/:audit_id", get(routes::contentaudit_detail))
+        .route(
+            "/api/audit/:audit_id/trace.raw",
+            get(routes::audit_trace_raw),
+        )
         .route("/audits/", get(routes::contentaudit_dashboard))
         .route("/audits/filter/", get(routes::contentaudit_filter))
         .route(
-            "/api/hourly-success-rate/",
-            get(routes::hourly_success_rate),
+            "/api/saved-views/",
+            get(routes::saved_views).post(routes::create_saved_view),
+        )
+        .route("/api/saved-views/:id", delete(routes::delete_saved_view))
+        .route("/api/audit/", post(routes::request_audit))
+        .route("/api/audit/:task_id", get(routes::requested_audit))
+        .route(
+            "/api/audits/latest/",
+            get(routes::latest_audits).post(routes::latest_audits_for_keys),
+        )
+        .route("/sync/", get(routes::sync_dashboard))
+        .route("/diagnostics/", get(routes::diagnostics))
+        .route("/api/environments/", get(routes::environments))
+        .route("/api/banner/", get(routes::site_banner))
+        .route(
+            "/api/admin/banner/",
+            put(routes::set_site_banner).delete(routes::clear_site_banner),
+        )
+        .route("/api/census-incidents/", get(routes::census_incidents))
+        .route("/api/census-reliability/", get(routes::census_reliability))
+        .route("/api/census-node-errors/", get(routes::census_node_errors))
+        .route(
+            "/api/census-alternate-enrs/",
+            get(routes::census_alternate_enrs),
+        )
+        .route("/api/census-diff/", get(routes::census_diff))
+        .route(
+            "/api/unreachable-endpoints/",
+            get(routes::unreachable_endpoints),
+        )
+        .route("/api/node-operator/", post(routes::claim_node_operator))
+        .route(
+            "/api/stuck-pending-audits/",
+            get(routes::stuck_pending_audits),
         )
         .route(
             "/api/is-content-in-deadzone/:content_key",
             get(routes::is_content_in_deadzone),
         )
-        .route("/api/stat-history/", get(routes::get_audit_stats_handler))
         .route("/api/failed-keys/", get(routes::get_failed_keys_handler))
+        .route("/api/unstable-content/", get(routes::unstable_content))
+        .route("/api/round-trips/", get(routes::round_trips))
+        .route(
+            "/api/node/:node_id_hex/enr-history",
+            get(routes::node_enr_history),
+        )
+        .route(
+            "/api/node-reliability/:node_id_hex",
+            get(routes::node_reliability),
+        )
+        .route("/api/v1/node/:node_id_hex/", get(routes::api_node_detail))
+        .route(
+            "/api/v1/node/:node_id_hex/enrs/",
+            get(routes::api_node_enrs),
+        )
+        .route(
+            "/api/v1/content/key/:content_key_hex/audits/",
+            get(routes::api_content_key_audits),
+        )
+        .route("/api/v1/census/:census_id/", get(routes::api_census_detail))
+        .route("/api/openapi.json", get(openapi::openapi_json))
+        .route("/api/docs/", get(openapi::api_docs))
+        .layer(middleware::from_fn(cache::etag))
+        .layer(SetResponseHeaderLayer::overriding(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static("no-cache"),
+        ));
+
+    // Aggregates over many rows, which are cached in-process for a short time.
+    let cache_ttl = default_state.cache_ttl;
+    let response_cache = Arc::new(ResponseCache::new(cache_ttl));
+    let aggregate_cache_control =
+        HeaderValue::from_str(&format!("public, max-age={}", cache_ttl.as_secs()))?;
+    let aggregates = Router::new()
+        .route(
+            "/api/hourly-success-rate/",
+            get(routes::hourly_success_rate),
+        )
+        .route("/api/hourly-audit-stats/", get(routes::hourly_audit_stats))
+        .route("/api/stat-history/", get(routes::get_audit_stats_handler))
+        .route("/api/stats-beacon/", get(routes::beacon_audit_stats))
+        .route("/api/stats-by-client/", get(routes::audit_stats_by_client))
+        .route("/api/block-audit-stats/", get(routes::block_audit_stats))
+        .route("/api/audit-coverage/", get(routes::audit_coverage_stats))
+        .route("/api/selection-lag/", get(routes::selection_lag))
+        .route("/api/audit-latency/", get(routes::audit_latency))
+        .route("/api/coverage-history/", get(routes::coverage_history))
+        .route("/api/propagation-curve/", get(routes::propagation_curve))
+        .route("/api/reseed-summary/", get(routes::reseed_summary))
+        .route(
+            "/api/first-success-latency/",
+            get(routes::first_success_latency),
+        )
+        .route("/api/audit-comparison/", get(routes::audit_comparison))
+        .route(
+            "/api/transfer-failure-rates/",
+            get(routes::transfer_failure_rates),
+        )
+        .route("/api/served-by-client/", get(routes::served_by_client))
+        .route(
+            "/api/slowest-serving-nodes/",
+            get(routes::slowest_serving_nodes),
+        )
+        .route(
+            "/api/radius-discrepancies/",
+            get(routes::radius_discrepancies),
+        )
+        .route(
+            "/api/transfer-failures-by-content-type/",
+            get(routes::transfer_failures_by_content_type),
+        )
+        .route(
+            "/api/client-restart-frequency/",
+            get(routes::client_restart_frequency),
+        )
+        .route("/api/client-compliance/", get(routes::client_compliance))
+        .route(
+            "/api/client-versions/",
+            get(routes::client_version_timeline),
+        )
+        .route(
+            "/api/success-by-hour-of-week/",
+            get(routes::success_by_hour_of_week),
+        )
+        .route(
+            "/api/failed-keys-by-block-range/",
+            get(routes::failed_keys_by_block_range),
+        )
         .route(
             "/census/census-node-timeseries-data/",
-            get(routes::census_timeseries),
+            get(routes::census_timeseries).layer(heavy_endpoint_limit()),
+        )
+        .route(
+            "/api/census-country-diversity/",
+            get(routes::census_country_diversity),
+        )
+        .route(
+            "/api/census-asn-diversity/",
+            get(routes::census_asn_diversity),
+        )
+        .route(
+            "/api/census-client-diversity/",
+            get(routes::census_client_diversity),
+        )
+        .route("/api/census-geo/", get(routes::census_geo))
+        .route(
+            "/api/census-target-coverage/",
+            get(routes::census_target_coverage),
         )
+        .layer(middleware::from_fn_with_state(
+            response_cache,
+            cache::cache_response,
+        ))
+        .layer(middleware::from_fn(cache::etag))
+        .layer(SetResponseHeaderLayer::overriding(
+            header::CACHE_CONTROL,
+            aggregate_cache_control,
+        ));
+
+    // Exports are streamed, so they go without an ETag, which needs the whole body.
+    let exports = Router::new()
+        .route("/api/census/:id/export", get(routes::census_export))
+        .layer(SetResponseHeaderLayer::overriding(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static("no-cache"),
+        ));
+
+    let live = Router::new().route("/ws/audits", get(live::audits_ws));
+
+    let static_assets = Router::new()
         .nest_service("/static/", serve_dir.clone())
+        .layer(SetResponseHeaderLayer::overriding(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static(STATIC_CACHE_CONTROL),
+        ));
+
+    let app = Router::new()
+        .merge(pages)
+        .merge(aggregates)
+        .merge(exports)
+        .merge(live)
+        .merge(static_assets)
         .fallback_service(serve_dir)
-        .layer(Extension(config));
+        .layer(CompressionLayer::new())
+        .layer(middleware::from_fn_with_state(
+            environments,
+            environment::select_environment,
+        ));
+
+    serve(app, &listen).await
+}
 
-    let socket: SocketAddr = SOCKET.parse()?;
-    info!("Serving glados-web at {}", socket);
-    Ok(axum::Server::bind(&socket)
-        .serve(app.into_make_service())
-        .await?)
+/// One time migration: sets the high bits of nodes stored before they were recorded.
+async fn set_node_id_high_bits(config: &State) -> Result<()> {
+    let nodes_with_zero_high_bits = entity::node::Entity::find()
+        .filter(entity::node::Column::NodeIdHigh.eq(0))
+        .all(&config.database_connection)
+        .await?;
+
+    info!(environment=config.environment, rows=?nodes_with_zero_high_bits.len(), "One time migration: setting high bits for node model");
+
+    for node_model in nodes_with_zero_high_bits {
+        let raw_node_id = U256::from_be_slice(&node_model.get_node_id().raw());
+        let node_id_high: i64 = raw_node_id.wrapping_shr(193).to::<i64>();
+
+        let mut node: entity::node::ActiveModel = node_model.into();
+        let previous_value = node.node_id_high;
+        node.node_id_high = Set(node_id_high);
+        let updated = node.update(&config.database_connection).await?;
+        info!(row.id=?updated.id, old=?previous_value, new=?updated.node_id_high, "Setting high bits");
+    }
+    Ok(())
 }
@@ -0,0 +1,82 @@
+//! Where glados-web is served: a TCP address, or a unix socket for a reverse proxy on the same
+//! host.
+use std::{
+    fmt, io,
+    net::SocketAddr,
+    os::unix::fs::FileTypeExt,
+    path::{Path, PathBuf},
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use anyhow::{bail, Result};
+use axum::Router;
+use hyper::server::accept::Accept;
+use tokio::net::{UnixListener, UnixStream};
+use tracing::info;
+
+#[derive(Clone, Debug)]
+pub enum Listen {
+    Tcp(SocketAddr),
+    Unix(PathBuf),
+}
+
+impl fmt::Display for Listen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Listen::Tcp(socket) => write!(f, "{socket}"),
+            Listen::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+/// Serves the app until the server fails.
+pub async fn serve(app: Router, listen: &Listen) -> Result<()> {
+    let service = app.into_make_service();
+    match listen {
+        Listen::Tcp(socket) => {
+            let server = axum::Server::try_bind(socket)?;
+            info!("Serving glados-web at {listen}");
+            server.serve(service).await?;
+        }
+        Listen::Unix(path) => {
+            remove_stale_socket(path)?;
+            let listener = UnixListener::bind(path)?;
+            info!("Serving glados-web at {listen}");
+            axum::Server::builder(UnixAccept(listener))
+                .serve(service)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Removes the socket left behind by a previous run, which would otherwise fail the bind.
+fn remove_stale_socket(path: &Path) -> Result<()> {
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return Ok(());
+    };
+    if !metadata.file_type().is_socket() {
+        bail!("{} exists and is not a unix socket", path.display());
+    }
+    if std::os::unix::net::UnixStream::connect(path).is_ok() {
+        bail!("{} is in use by another server", path.display());
+    }
+    std::fs::remove_file(path)?;
+    Ok(())
+}
+
+struct UnixAccept(UnixListener);
+
+impl Accept for UnixAccept {
+    type Conn = UnixStream;
+    type Error = io::Error;
+
+    fn poll_accept(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
+        let (stream, _) = ready!(self.0.poll_accept(cx))?;
+        Poll::Ready(Some(Ok(stream)))
+    }
+}
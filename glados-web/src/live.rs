@@ -0,0 +1,130 @@
+//! Audits pushed to dashboards as they are recorded.
+//!
+//! Audits are recorded by glados-audit in another process, so they are learned of through the
+//! database: a trigger notifies [AUDIT_RECORDED_CHANNEL] with the id of each audit once its
+//! result is recorded. Each environment listens on its own database and broadcasts the audits
+//! to the clients connected to `/ws/audits`.
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use anyhow::Result;
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Extension, Query as HttpQuery,
+    },
+    response::Response,
+};
+use entity::{
+    content::SubProtocol,
+    content_audit::{self, RecordedAudit, AUDIT_RECORDED_CHANNEL},
+};
+use sqlx::postgres::PgListener;
+use tokio::{
+    sync::broadcast::{self, error::RecvError},
+    time::sleep,
+};
+use tracing::{debug, error, info, warn};
+
+use crate::{routes::get_subprotocol_from_params, state::State};
+
+/// Audits kept for clients that fall behind, after which they skip the oldest.
+const AUDIT_FEED_CAPACITY: usize = 256;
+
+/// Time to wait before listening again after losing the database connection.
+const RELISTEN_DELAY: Duration = Duration::from_secs(5);
+
+/// Recorded audits of an environment, sent to every connected client.
+pub type AuditFeed = broadcast::Sender<Arc<RecordedAudit>>;
+
+pub fn audit_feed() -> AuditFeed {
+    broadcast::channel(AUDIT_FEED_CAPACITY).0
+}
+
+/// Broadcasts the audits recorded in the environment's database to its audit feed.
+///
+/// Notifications are received on a connection of their own, outside of the pool.
+pub async fn listen_for_audits(state: Arc<State>, database_url: String) {
+    loop {
+        if let Err(e) = forward_audits(&state, &database_url).await {
+            error!(environment=state.environment, err=?e, "Stopped listening for recorded audits");
+        }
+        sleep(RELISTEN_DELAY).await;
+    }
+}
+
+async fn forward_audits(state: &State, database_url: &str) -> Result<()> {
+    let mut listener = PgListener::connect(database_url).await?;
+    listener.listen(AUDIT_RECORDED_CHANNEL).await?;
+    info!(
+        environment = state.environment,
+        "Listening for recorded audits"
+    );
+    loop {
+        let notification = listener.recv().await?;
+        // Audits are only looked up while someone is watching.
+        if state.audit_feed.receiver_count() == 0 {
+            continue;
+        }
+        let Ok(audit_id) = notification.payload().parse::<i32>() else {
+            warn!(
+                payload = notification.payload(),
+                "Invalid recorded audit notification"
+            );
+            continue;
+        };
+        if let Some(audit) =
+            content_audit::get_recorded(audit_id, &state.database_connection).await?
+        {
+            // Fails only when the last client left since the check above.
+            let _ = state.audit_feed.send(Arc::new(audit));
+        }
+    }
+}
+
+/// Streams the audits of a subnetwork as they are recorded, as JSON text messages. The
+/// subnetwork is chosen with `?network=`, History by default.
+pub async fn audits_ws(
+    ws: WebSocketUpgrade,
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Response {
+    let subprotocol = get_subprotocol_from_params(&http_args);
+    let audits = state.audit_feed.subscribe();
+    ws.on_upgrade(move |socket| push_audits(socket, audits, subprotocol))
+}
+
+async fn push_audits(
+    mut socket: WebSocket,
+    mut audits: broadcast::Receiver<Arc<RecordedAudit>>,
+    subprotocol: SubProtocol,
+) {
+    let sub_network = subprotocol.as_text();
+    loop {
+        tokio::select! {
+            audit = audits.recv() => match audit {
+                Ok(audit) if audit.sub_network == sub_network => {
+                    let message = match serde_json::to_string(audit.as_ref()) {
+                        Ok(message) => message,
+                        Err(e) => {
+                            error!(audit.id=audit.id, err=?e, "Could not serialize recorded audit");
+                            continue;
+                        }
+                    };
+                    if socket.send(Message::Text(message)).await.is_err() {
+                        return;
+                    }
+                }
+                Ok(_) => {}
+                Err(RecvError::Lagged(skipped)) => {
+                    debug!(skipped, "Live audit client fell behind, skipping audits");
+                }
+                Err(RecvError::Closed) => return,
+            },
+            // Clients send nothing but the closing of the connection.
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
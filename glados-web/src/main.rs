@@ -1,10 +1,14 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::Parser;
 use sea_orm::Database;
 
-use glados_web::{cli::Args, run_glados_web, state::State};
+use glados_core::feature_flags::FeatureFlags;
+use glados_web::{
+    cli::Args, environment::Environments, explorer::ExplorerLinks, live, run_glados_web,
+    state::State,
+};
 use migration::{Migrator, MigratorTrait};
 
 #[tokio::main]
@@ -13,15 +17,34 @@ async fn main() -> Result<()> {
     // parse command line arguments
     let args = Args::parse();
 
-    let conn = Database::connect(args.database_url)
-        .await
-        .expect("Database connection failed");
-    Migrator::up(&conn, None).await?;
+    let mut states = vec![];
+    let databases = std::iter::once((args.environment_name.clone(), args.database_url.clone()))
+        .chain(args.environments.clone());
+    for (environment, database_url) in databases {
+        let conn = Database::connect(&database_url)
+            .await
+            .with_context(|| format!("Database connection failed for {environment}"))?;
+        Migrator::up(&conn, None).await?;
+        let feature_flags = FeatureFlags::load(&conn).await?;
 
-    let config = Arc::new(State {
-        database_connection: conn,
-    });
+        let state = Arc::new(State {
+            environment,
+            database_connection: conn,
+            cache_ttl: Duration::from_secs(args.cache_ttl),
+            heavy_endpoint_concurrency: args.heavy_endpoint_concurrency as usize,
+            heavy_endpoint_queue: args.heavy_endpoint_queue as usize,
+            explorer_links: ExplorerLinks::new(
+                args.block_url_template.clone(),
+                args.slot_url_template.clone(),
+            ),
+            feature_flags,
+            admin_token: args.admin_token.clone(),
+            audit_feed: live::audit_feed(),
+        });
+        tokio::spawn(live::listen_for_audits(state.clone(), database_url));
+        states.push(state);
+    }
 
-    run_glados_web(config).await?;
+    run_glados_web(Arc::new(Environments::new(states)?), args.listen()).await?;
     Ok(())
 }
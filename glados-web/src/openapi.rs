@@ -0,0 +1,240 @@
+//! The OpenAPI description of the JSON API, served at `/api/openapi.json` and browsed with
+//! Swagger UI at `/api/docs/`.
+//!
+//! Each route is described by the `#[utoipa::path]` attribute of its handler in [routes].
+//! Schemas of entity types are named after their module, like `saved_view.Model`, as the model
+//! of every table is called `Model`. Entity types flattened into a response are inlined.
+use axum::Json;
+use chrono::{DateTime, Utc};
+use entity::{
+    audit_stats, audit_stats_client, census, census_client_stats, census_incident, census_node,
+    client_version_sighting, content_first_success, content_flapping, enr_change, node_geo,
+    node_operator, propagation_audit, reseed_attempt, saved_view, site_banner,
+};
+use glados_core::stats::{AuditStats, Period};
+use utoipa::{
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    IntoParams, Modify, OpenApi,
+};
+
+use crate::{
+    error::ErrorBody,
+    routes,
+    templates::{ApiDocsTemplate, HtmlTemplate},
+};
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Glados",
+        description = "Audits, censuses and stats of the Portal network subnetworks monitored by \
+            this Glados deployment."
+    ),
+    paths(
+        routes::node_reliability,
+        routes::node_enr_history,
+        routes::api_node_detail,
+        routes::api_node_enrs,
+        routes::claim_node_operator,
+        routes::client_version_timeline,
+        routes::slowest_serving_nodes,
+        routes::radius_discrepancies,
+        routes::census_incidents,
+        routes::census_reliability,
+        routes::census_diff,
+        routes::unreachable_endpoints,
+        routes::census_node_errors,
+        routes::census_export,
+        routes::census_alternate_enrs,
+        routes::api_census_detail,
+        routes::census_timeseries,
+        routes::is_content_in_deadzone,
+        routes::coverage_history,
+        routes::client_compliance,
+        routes::client_restart_frequency,
+        routes::census_country_diversity,
+        routes::census_asn_diversity,
+        routes::census_client_diversity,
+        routes::census_geo,
+        routes::census_target_coverage,
+        routes::request_audit,
+        routes::requested_audit,
+        routes::latest_audits,
+        routes::latest_audits_for_keys,
+        routes::api_content_key_audits,
+        routes::audit_trace_raw,
+        routes::saved_views,
+        routes::create_saved_view,
+        routes::delete_saved_view,
+        routes::get_failed_keys_handler,
+        routes::failed_keys_by_block_range,
+        routes::stuck_pending_audits,
+        routes::unstable_content,
+        routes::round_trips,
+        routes::hourly_success_rate,
+        routes::hourly_audit_stats,
+        routes::get_audit_stats_handler,
+        routes::beacon_audit_stats,
+        routes::audit_stats_by_client,
+        routes::block_audit_stats,
+        routes::audit_coverage_stats,
+        routes::selection_lag,
+        routes::audit_latency,
+        routes::propagation_curve,
+        routes::reseed_summary,
+        routes::first_success_latency,
+        routes::audit_comparison,
+        routes::success_by_hour_of_week,
+        routes::transfer_failure_rates,
+        routes::served_by_client,
+        routes::transfer_failures_by_content_type,
+        routes::environments,
+        routes::site_banner,
+        routes::set_site_banner,
+        routes::clear_site_banner,
+    ),
+    components(schemas(
+        ErrorBody,
+        // Nodes
+        routes::NodeReliabilityReport,
+        routes::EnrHistoryEntry,
+        routes::ApiNodeDetail,
+        routes::ApiEnr,
+        routes::NodeOperatorClaim,
+        routes::SlowServingNode,
+        node_operator::Model,
+        client_version_sighting::Model,
+        enr_change::EnrChangeEntry,
+        census_node::RadiusDiscrepancy,
+        // Censuses
+        routes::CensusReliability,
+        routes::UnreachableEndpoint,
+        routes::CensusNodeError,
+        routes::CensusAlternateEnr,
+        routes::ApiCensusDetail,
+        routes::ClientDiversityResult,
+        routes::CensusTimeSeriesData,
+        routes::CensusStatuses,
+        routes::DeadZoneResponse,
+        routes::CoverageHistoryEntry,
+        routes::ClientRestartFrequency,
+        routes::CensusDiversity,
+        census_incident::Model,
+        census_node::CensusDiff,
+        census_node::ExportedCensusNode,
+        census_client_stats::ClientComplianceDay,
+        census::CensusClientDiversity,
+        census::TargetCoverage,
+        node_geo::CensusGeo,
+        // Audits
+        routes::AuditRequest,
+        routes::RequestedAudit,
+        routes::LatestAuditsRequest,
+        routes::LatestAudit,
+        routes::LatestAuditSummary,
+        routes::ApiContentKeyAudits,
+        routes::ApiAudit,
+        routes::SavedViewRequest,
+        routes::FailedKeysBlockRange,
+        routes::StuckPendingAlert,
+        routes::StuckPendingAudit,
+        routes::RoundTrip,
+        saved_view::Model,
+        content_flapping::UnstableContent,
+        // Stats
+        AuditStats,
+        Period,
+        routes::BeaconStatsRecord,
+        routes::BlockAuditStats,
+        routes::AuditCoverageEntry,
+        routes::SelectionLagEntry,
+        routes::AuditLatency,
+        routes::LatencyEntry,
+        routes::AuditComparison,
+        routes::AuditComparisonRow,
+        routes::HourOfWeekSuccess,
+        routes::ClientTransferFailureRate,
+        routes::ServedByClientDay,
+        routes::TransferFailureDay,
+        audit_stats::Model,
+        audit_stats_client::Model,
+        propagation_audit::PropagationPoint,
+        reseed_attempt::ReseedSummary,
+        content_first_success::MonthlyFirstSuccess,
+        // Site
+        routes::EnvironmentsResponse,
+        routes::BannerRequest,
+        site_banner::Model,
+    )),
+    tags(
+        (name = "nodes", description = "Nodes and their ENRs, operators and performance"),
+        (name = "censuses", description = "Censuses of the nodes of a subnetwork"),
+        (name = "audits", description = "Audits of content, and requests for new ones"),
+        (name = "stats", description = "Audit stats over time"),
+        (name = "site", description = "Environments and the site banner"),
+    ),
+    modifiers(&AdminToken)
+)]
+pub struct ApiDoc;
+
+/// Admin routes take the admin token as a bearer token.
+struct AdminToken;
+
+impl Modify for AdminToken {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "admin",
+                SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+            );
+        }
+    }
+}
+
+/// The subnetwork read by [routes::get_subprotocol_from_params].
+#[derive(IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct NetworkParam {
+    /// `history`, `state` or `beacon`, History by default.
+    pub network: Option<String>,
+}
+
+/// Read by [routes::get_include_archived_from_params].
+#[derive(IntoParams)]
+#[into_params(parameter_in = Query, rename_all = "kebab-case")]
+pub struct IncludeArchivedParam {
+    /// Lists archived nodes and content, like those of experiments, with `true`.
+    pub include_archived: Option<bool>,
+}
+
+/// Read by [routes::get_exclude_self_from_params].
+#[derive(IntoParams)]
+#[into_params(parameter_in = Query, rename_all = "kebab-case")]
+pub struct ExcludeSelfParam {
+    /// Leaves out the nodes run by this deployment, like the Portal nodes used for audits, with
+    /// `true`.
+    pub exclude_self: Option<bool>,
+}
+
+/// The time range of reporting endpoints, which are otherwise a window of a set number of days
+/// up to now.
+#[derive(IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ReportingWindowParams {
+    /// Start of the time range, used with `end`.
+    pub start: Option<DateTime<Utc>>,
+    /// End of the time range, used with `start`.
+    pub end: Option<DateTime<Utc>>,
+    /// Timezone, like `Europe/Berlin`, whose next midnight the window ends at rather than now.
+    pub tz: Option<String>,
+}
+
+/// Serves the OpenAPI description of the JSON API.
+pub async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+/// Serves Swagger UI, to browse and try the JSON API.
+pub async fn api_docs() -> HtmlTemplate<ApiDocsTemplate> {
+    HtmlTemplate(ApiDocsTemplate {})
+}
@@ -1,50 +1,81 @@
 use alloy_primitives::{hex, B256, U256};
+use anyhow::anyhow;
 use axum::{
+    body::{self, Body, Bytes},
     extract::{Extension, Path, Query as HttpQuery},
-    http::StatusCode,
+    http::{header, HeaderMap, StatusCode},
     response::IntoResponse,
     Json,
 };
-use chrono::{DateTime, TimeZone, Utc};
+use chrono::{DateTime, TimeDelta, TimeZone, Utc};
 use enr::NodeId;
-use entity::{audit_stats, census, census_node, client_info, content::SubProtocol};
+use entity::{
+    audit_coverage,
+    audit_latest::{self, FailedKeysFilter, FailedKeysOrder},
+    audit_stats::{self, BeaconSuccessRates},
+    audit_stats_client, audit_task, audit_worker, block_audit, census, census_alternate_enr,
+    census_client_stats::{self, ClientComplianceDay},
+    census_incident, census_node, census_node_error, client_info, client_version_sighting,
+    content::{ContentSource, SubProtocol},
+    content_first_success::{self, MonthlyFirstSuccess},
+    enr_change,
+};
 use entity::{
     content,
-    content_audit::{self, AuditResult},
-    execution_metadata, key_value, node, record,
+    content_audit::{self, AuditResult, SelectionStrategy},
+    content_flapping,
+    coverage_sample::{self, CensusCoverage},
+    execution_metadata, key_value, node, node_geo, node_operator, node_serve_latency,
+    propagation_audit::{self, PropagationPoint},
+    record,
+    reseed_attempt::{self, ReseedSummary},
+    round_trip_audit, saved_view, site_banner, sync_audit, sync_audit_error, sync_audit_segment,
+    transfer_failure_daily,
 };
 use ethportal_api::types::{
     distance::{Distance, Metric, XorMetric},
+    enr::Enr,
     query_trace::QueryTrace,
 };
-use ethportal_api::utils::bytes::{hex_decode, hex_encode};
+use ethportal_api::utils::bytes::hex_encode;
 use ethportal_api::{jsonrpsee::core::__reexports::serde_json, BeaconContentKey, StateContentKey};
 use ethportal_api::{HistoryContentKey, OverlayContentKey};
 use glados_core::stats::{
-    filter_audits, get_audit_stats, AuditFilters, ContentTypeFilter, Period, StrategyFilter,
-    SuccessFilter,
+    filter_audits, get_audit_stats, AuditFilters, AuditStats, ContentTypeFilter, Period,
+    StrategyFilter, SuccessFilter,
 };
 use migration::{Alias, Order};
 use sea_orm::sea_query::{Expr, Query};
 use sea_orm::{sea_query::SimpleExpr, Statement};
 use sea_orm::{
-    ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, EntityTrait, FromQueryResult,
-    LoaderTrait, ModelTrait, QueryFilter, QueryOrder, QuerySelect,
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, DbErr, EntityTrait,
+    FromQueryResult, Iterable, LoaderTrait, ModelTrait, QueryFilter, QueryOrder, QuerySelect,
 };
-use serde::Serialize;
-use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::Formatter;
+use std::str::FromStr;
 use std::sync::Arc;
 use std::{fmt::Display, io};
 use tracing::{error, info, warn};
+use utoipa::ToSchema;
 
 use crate::templates::{
     AuditDashboardTemplate, AuditTableTemplate, CensusExplorerTemplate, ContentAuditDetailTemplate,
     ContentIdDetailTemplate, ContentIdListTemplate, ContentKeyDetailTemplate,
-    ContentKeyListTemplate, EnrDetailTemplate, HtmlTemplate, IndexTemplate, NodeDetailTemplate,
-    PaginatedCensusListTemplate, SingleCensusViewTemplate,
+    ContentKeyListTemplate, DiagnosticsTemplate, EnrDetailTemplate, HtmlTemplate, IndexTemplate,
+    NodeDetailTemplate, PaginatedCensusListTemplate, SingleCensusViewTemplate,
+    SyncDashboardTemplate,
+};
+use crate::{
+    environment::Environments,
+    error::{
+        parse_hex_param, parse_param, parse_required_param, HexParam, IdParam, PageError, WebError,
+    },
+    openapi::{ExcludeSelfParam, IncludeArchivedParam, NetworkParam, ReportingWindowParams},
+    state::State,
+    templates::AuditTuple,
 };
-use crate::{state::State, templates::AuditTuple};
 
 //
 // Routes
@@ -64,20 +95,103 @@ pub fn get_subprotocol_from_params(params: &HashMap<String, String>) -> SubProto
     }
 }
 
+/// Whether nodes run by this deployment, like the Portal nodes used for audits, should be left
+/// out of census stats. Set with `exclude-self=true`.
+pub fn get_exclude_self_from_params(params: &HashMap<String, String>) -> bool {
+    params
+        .get("exclude-self")
+        .is_some_and(|exclude_self| exclude_self == "true")
+}
+
+/// Whether archived nodes and content, like those of experiments, should be listed. They are
+/// hidden by default. Set with `include-archived=true`.
+pub fn get_include_archived_from_params(params: &HashMap<String, String>) -> bool {
+    params
+        .get("include-archived")
+        .is_some_and(|include_archived| include_archived == "true")
+}
+
+#[derive(FromQueryResult)]
+struct ReportingWindow {
+    window_start: DateTime<Utc>,
+    window_end: DateTime<Utc>,
+}
+
+/// Determines the time range covered by a reporting endpoint from the query parameters.
+///
+/// By default the window is the `window_days` days up to now, shifted back by `windows_ago`
+/// windows. With a `tz` parameter (e.g. `Europe/Berlin`), the window instead ends at the
+/// next midnight in that timezone, so daily boundaries in charts match the viewer's locale.
+/// Explicit `start` and `end` parameters (RFC 3339) take precedence over both.
+async fn get_reporting_window(
+    params: &HashMap<String, String>,
+    window_days: i32,
+    windows_ago: i32,
+    conn: &DatabaseConnection,
+) -> Result<(DateTime<Utc>, DateTime<Utc>), WebError> {
+    if let (Some(start), Some(end)) = (params.get("start"), params.get("end")) {
+        let parse = |name: &'static str, time: &String| {
+            DateTime::parse_from_rfc3339(time)
+                .map(|time| time.with_timezone(&Utc))
+                .map_err(|e| WebError::invalid_parameter(name, time, e))
+        };
+        let (start_time, end_time) = (parse("start", start)?, parse("end", end)?);
+        if start_time >= end_time {
+            return Err(WebError::invalid_parameter(
+                "end",
+                end,
+                format!("not after the start {start}"),
+            ));
+        }
+        return Ok((start_time, end_time));
+    }
+
+    let Some(timezone) = params.get("tz") else {
+        let window = TimeDelta::try_days(window_days as i64).ok_or_else(|| {
+            WebError::invalid_parameter("window", &window_days.to_string(), "too long")
+        })?;
+        let end = Utc::now() - window * windows_ago;
+        return Ok((end - window, end));
+    };
+
+    // Local midnight is computed by Postgres, which knows the timezone rules (including DST).
+    let window = ReportingWindow::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "
+        SELECT
+            (date_trunc('day', NOW() AT TIME ZONE $1) + INTERVAL '1 day'
+                - INTERVAL '1 day' * $2 * ($3 + 1)) AT TIME ZONE $1 AS window_start,
+            (date_trunc('day', NOW() AT TIME ZONE $1) + INTERVAL '1 day'
+                - INTERVAL '1 day' * $2 * $3) AT TIME ZONE $1 AS window_end
+        ",
+        vec![timezone.into(), window_days.into(), windows_ago.into()],
+    ))
+    .one(conn)
+    .await
+    .map_err(|e| WebError::invalid_parameter("tz", timezone, e))?
+    .ok_or_else(|| WebError::Database(anyhow!("No reporting window for timezone {timezone}")))?;
+    Ok((window.window_start, window.window_end))
+}
+
 pub async fn network_overview(
     params: HttpQuery<HashMap<String, String>>,
     Extension(state): Extension<Arc<State>>,
-) -> impl IntoResponse {
+) -> Result<HtmlTemplate<IndexTemplate>, PageError> {
     let subprotocol = get_subprotocol_from_params(&params);
 
     let client_diversity_data = match get_max_census_id(&state, subprotocol).await {
         None => vec![],
-        Some(max_census_id) => generate_client_diversity_data(&state, max_census_id.id)
-            .await
-            .unwrap(),
+        Some(max_census_id) => {
+            generate_client_diversity_data(
+                &state,
+                max_census_id.id,
+                get_exclude_self_from_params(&params),
+            )
+            .await?
+        }
     };
 
-    let radius_percentages = generate_radius_graph_data(&state, subprotocol).await;
+    let radius_percentages = generate_radius_graph_data(&state, subprotocol).await?;
     // Run queries for content dashboard data concurrently
     let (hour_stats, day_stats, week_stats) = tokio::join!(
         get_audit_stats(
@@ -86,6 +200,7 @@ pub async fn network_overview(
                 content_type: ContentTypeFilter::All,
                 success: SuccessFilter::All,
                 network: subprotocol,
+                include_archived: false,
             },),
             Period::Hour,
             &state.database_connection,
@@ -96,6 +211,7 @@ pub async fn network_overview(
                 content_type: ContentTypeFilter::All,
                 success: SuccessFilter::All,
                 network: subprotocol,
+                include_archived: false,
             },),
             Period::Day,
             &state.database_connection,
@@ -106,54 +222,50 @@ pub async fn network_overview(
                 content_type: ContentTypeFilter::All,
                 success: SuccessFilter::All,
                 network: subprotocol,
+                include_archived: false,
             },),
             Period::Week,
             &state.database_connection,
         ),
     );
-    // Get results from queries
-    let hour_stats = hour_stats.unwrap();
-    let day_stats = day_stats.unwrap();
-    let week_stats = week_stats.unwrap();
-
     let template = IndexTemplate {
         client_diversity_data,
         average_radius_chart: radius_percentages,
-        stats: [hour_stats, day_stats, week_stats],
+        stats: [hour_stats?, day_stats?, week_stats?],
     };
-    HtmlTemplate(template)
+    Ok(HtmlTemplate(template))
 }
 
+/// Number of nodes listed in the "closest nodes" table on the node detail page.
+const CLOSEST_NODE_COUNT: usize = 100;
+
 pub async fn node_detail(
-    Path(node_id_hex): Path<String>,
+    node_id: HexParam,
+    http_args: HttpQuery<HashMap<String, String>>,
     Extension(state): Extension<Arc<State>>,
-) -> Result<HtmlTemplate<NodeDetailTemplate>, StatusCode> {
-    let node_id = hex_decode(&node_id_hex).map_err(|e| {
-        error!(node_id=node_id_hex, err=?e, "Could not decode proved node_id");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+) -> Result<HtmlTemplate<NodeDetailTemplate>, PageError> {
+    let metric = match http_args.get("metric") {
+        None => node::DistanceMetric::default(),
+        Some(metric) => node::DistanceMetric::try_from(metric.as_str())
+            .map_err(|e| WebError::invalid_parameter("metric", metric, e))?,
+    };
     let node_model = node::Entity::find()
-        .filter(node::Column::NodeId.eq(node_id))
+        .filter(node::Column::NodeId.eq(node_id.bytes))
         .one(&state.database_connection)
-        .await
-        .map_err(|e| {
-            error!(node_id=node_id_hex, err=?e, "No record found for node_id");
-            StatusCode::NOT_FOUND
-        })
-        .unwrap()
-        .unwrap();
+        .await?
+        .ok_or_else(|| WebError::not_found(format!("Node {}", node_id.hex)))?;
     let enr_list = record::Entity::find()
         .filter(record::Column::NodeId.eq(node_model.id))
         .order_by_desc(record::Column::SequenceNumber)
         .all(&state.database_connection)
-        .await
-        .map_err(|e| {
-            error!(node.node_id=node_id_hex, node.db_id=node_model.id, err=?e, "Error looking up ENRs");
-            StatusCode::NOT_FOUND
-        })?;
-    let closest_node_list = node::closest_xor(node_model.get_node_id(), &state.database_connection)
-        .await
-        .unwrap();
+        .await?;
+    let closest_node_list = node::closest(
+        node_model.get_node_id(),
+        metric,
+        CLOSEST_NODE_COUNT,
+        &state.database_connection,
+    )
+    .await?;
 
     let latest_enr = enr_list.first().cloned();
 
@@ -163,61 +275,189 @@ pub async fn node_detail(
                 .filter(key_value::Column::RecordId.eq(enr.id))
                 .order_by_asc(key_value::Column::Key)
                 .all(&state.database_connection)
-                .await
-                .map_err(|e| {
-                    error!(enr.id=enr.id, err=?e, "Error looking up key_value pairs");
-                    StatusCode::INTERNAL_SERVER_ERROR
-                })?,
+                .await?,
         ),
         None => None,
     };
+    let operator = node_operator::get(node_model.id, &state.database_connection).await?;
+    let reliability_network = get_subprotocol_from_params(&http_args);
+    let reliability = census_node::get_node_reliability(
+        node_model.id,
+        reliability_network,
+        DEFAULT_RELIABILITY_CENSUSES,
+        &state.database_connection,
+    )
+    .await?;
+    let enr_changes = enr_change::get_by_node(node_model.id, &state.database_connection).await?;
     let template = NodeDetailTemplate {
         node: node_model,
         latest_enr,
         latest_enr_key_value_list,
         enr_list,
+        enr_changes,
         closest_node_list,
+        metric,
+        operator,
+        reliability_network,
+        reliability,
     };
     Ok(HtmlTemplate(template))
 }
 
+/// Default number of the latest censuses a node's reliability is computed over, a day of
+/// censuses at the cartographer's default interval.
+const DEFAULT_RELIABILITY_CENSUSES: u64 = 96;
+/// Maximum number of the latest censuses a node's reliability is computed over.
+const MAX_RELIABILITY_CENSUSES: u64 = 10_000;
+
+/// How reliably the latest censuses found a node, with its presence and reliability score.
+#[derive(Serialize, Debug, ToSchema)]
+pub struct NodeReliabilityReport {
+    #[serde(flatten)]
+    #[schema(inline)]
+    pub reliability: census_node::NodeReliability,
+    pub presence: Option<f64>,
+    pub score: Option<f64>,
+}
+
+/// Reports how reliably the latest censuses of a subnetwork found a node. Set the subnetwork
+/// with `network=` and the number of censuses with `censuses=`.
+#[utoipa::path(
+    get,
+    path = "/api/node-reliability/{node_id_hex}",
+    tag = "nodes",
+    params(
+        ("node_id_hex" = String, Path, description = "Node id, hex encoded"),
+        NetworkParam,
+        (
+            "censuses" = Option<u64>, Query,
+            description = "Latest censuses of the subnetwork to consider, 96 by default",
+        ),
+    ),
+    responses(
+        (status = 200, body = NodeReliabilityReport),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+        (status = 404, description = "Unknown node", body = ErrorBody),
+    ),
+)]
+pub async fn node_reliability(
+    node_id: HexParam,
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<NodeReliabilityReport>, WebError> {
+    let num_censuses = match http_args.get("censuses") {
+        None => DEFAULT_RELIABILITY_CENSUSES,
+        Some(censuses) => {
+            parse_param::<u64>("censuses", censuses)?.clamp(1, MAX_RELIABILITY_CENSUSES)
+        }
+    };
+    let node_model = node::Entity::find()
+        .filter(node::Column::NodeId.eq(node_id.bytes))
+        .one(&state.database_connection)
+        .await?
+        .ok_or_else(|| WebError::not_found(format!("Node {}", node_id.hex)))?;
+    let reliability = census_node::get_node_reliability(
+        node_model.id,
+        get_subprotocol_from_params(&http_args),
+        num_censuses,
+        &state.database_connection,
+    )
+    .await?;
+    Ok(Json(NodeReliabilityReport {
+        presence: reliability.presence(),
+        score: reliability.score(),
+        reliability,
+    }))
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct EnrHistoryEntry {
+    pub sequence_number: i32,
+    pub raw: String,
+    pub ip4: Option<String>,
+    pub udp4: Option<u16>,
+    pub ip6: Option<String>,
+    pub udp6: Option<u16>,
+    pub first_seen: Option<DateTime<Utc>>,
+    pub last_seen: Option<DateTime<Utc>>,
+    /// Number of censuses the ENR was seen in.
+    pub observations: i64,
+}
+
+/// Returns the ENRs of a node in the order they were observed, with their decoded IP
+/// addresses and ports, so that address churn can be followed over time.
+#[utoipa::path(
+    get,
+    path = "/api/node/{node_id_hex}/enr-history",
+    tag = "nodes",
+    params(
+        ("node_id_hex" = String, Path, description = "Node id, hex encoded"),
+    ),
+    responses(
+        (status = 200, body = [EnrHistoryEntry]),
+        (status = 404, description = "Unknown node", body = ErrorBody),
+    ),
+)]
+pub async fn node_enr_history(
+    node_id: HexParam,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Vec<EnrHistoryEntry>>, WebError> {
+    let node_model = node::Entity::find()
+        .filter(node::Column::NodeId.eq(node_id.bytes))
+        .one(&state.database_connection)
+        .await?
+        .ok_or_else(|| WebError::not_found(format!("Node {}", node_id.hex)))?;
+    let history = record::get_history(node_model.id, &state.database_connection).await?;
+
+    let entries = history
+        .into_iter()
+        .map(|observation| {
+            let enr = Enr::from_str(&observation.raw)
+                .map_err(|e| warn!(record.id=observation.record_id, err=?e, "Could not decode ENR"))
+                .ok();
+            EnrHistoryEntry {
+                sequence_number: observation.sequence_number,
+                ip4: enr
+                    .as_ref()
+                    .and_then(|enr| enr.ip4())
+                    .map(|ip| ip.to_string()),
+                udp4: enr.as_ref().and_then(|enr| enr.udp4()),
+                ip6: enr
+                    .as_ref()
+                    .and_then(|enr| enr.ip6())
+                    .map(|ip| ip.to_string()),
+                udp6: enr.as_ref().and_then(|enr| enr.udp6()),
+                raw: observation.raw,
+                first_seen: observation.first_seen,
+                last_seen: observation.last_seen,
+                observations: observation.observations,
+            }
+        })
+        .collect();
+    Ok(Json(entries))
+}
+
 pub async fn enr_detail(
-    Path((node_id_hex, enr_seq)): Path<(String, u64)>,
+    Path((node_id_hex, enr_seq)): Path<(String, String)>,
     Extension(state): Extension<Arc<State>>,
-) -> Result<HtmlTemplate<EnrDetailTemplate>, StatusCode> {
-    let node_id = hex_decode(&node_id_hex).map_err(|e| {
-        error!(node_id=node_id_hex, err=?e, "Could not decode proved node_id");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+) -> Result<HtmlTemplate<EnrDetailTemplate>, PageError> {
+    let node_id = parse_hex_param("node id", &node_id_hex)?;
+    let enr_seq = parse_param::<u64>("ENR sequence number", &enr_seq)?;
     let node_model = node::Entity::find()
         .filter(node::Column::NodeId.eq(node_id))
         .one(&state.database_connection)
-        .await
-        .map_err(|e| {
-            error!(node_id=node_id_hex, err=?e, "No record found for node_id");
-            StatusCode::NOT_FOUND
-        })
-        .unwrap()
-        .unwrap();
+        .await?
+        .ok_or_else(|| WebError::not_found(format!("Node {node_id_hex}")))?;
     let enr = record::Entity::find()
         .filter(record::Column::NodeId.eq(node_model.id.to_owned()))
         .filter(record::Column::SequenceNumber.eq(enr_seq))
         .one(&state.database_connection)
-        .await
-        .map_err(|e| {
-            error!(enr.node_id=node_id_hex, enr.seq=enr_seq, err=?e, "No record found for node_id and sequence_number");
-            StatusCode::NOT_FOUND
-        })
-        .unwrap()
-        .unwrap();
+        .await?
+        .ok_or_else(|| WebError::not_found(format!("ENR {enr_seq} of node {node_id_hex}")))?;
     let key_value_list = key_value::Entity::find()
         .filter(key_value::Column::RecordId.eq(enr.id))
         .all(&state.database_connection)
-        .await
-        .map_err(|e| {
-            error!(enr.id=enr.id, enr.node_id=node_id_hex, err=?e, "Error looking up key_value pairs");
-            StatusCode::NOT_FOUND
-        })?;
+        .await?;
 
     let template = EnrDetailTemplate {
         node: node_model,
@@ -230,16 +470,12 @@ pub async fn enr_detail(
 pub async fn get_recent_audits(
     num_audits: u64,
     conn: &DatabaseConnection,
-) -> Result<Vec<AuditTuple>, StatusCode> {
+) -> Result<Vec<AuditTuple>, WebError> {
     let recent_audits: Vec<content_audit::Model> = content_audit::Entity::find()
         .order_by_desc(content_audit::Column::CreatedAt)
         .limit(num_audits)
         .all(conn)
-        .await
-        .map_err(|e| {
-            error!(key.count=num_audits, err=?e, "Could not look up recent audits");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        .await?;
 
     get_audit_tuples_from_audit_models(recent_audits, conn).await
 }
@@ -247,7 +483,7 @@ pub async fn get_recent_audits(
 pub async fn get_audits_for_recent_content(
     num_content: u64,
     conn: &DatabaseConnection,
-) -> Result<Vec<AuditTuple>, StatusCode> {
+) -> Result<Vec<AuditTuple>, WebError> {
     // Get recent content that has been audited along with the audit.
     // Done in a single query and then split using unzip.
     let (recent_content, audits): (Vec<content::Model>, Vec<content_audit::Model>) =
@@ -257,22 +493,12 @@ pub async fn get_audits_for_recent_content(
             .filter(content_audit::Column::Result.is_not_null())
             .limit(num_content)
             .all(conn)
-            .await
-            .map_err(|e| {
-                error!(key.count=num_content, err=?e, "Could not look up latest keys with audits");
-                StatusCode::INTERNAL_SERVER_ERROR
-            })?
+            .await?
             .into_iter()
             .filter_map(|(content, audits)| audits.into_iter().next().map(|audit| (content, audit)))
             .unzip();
 
-    let client_info = audits
-        .load_one(client_info::Entity, conn)
-        .await
-        .map_err(|e| {
-            error!(key.count=audits.len(), err=?e, "Could not look up client info for recent audits");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+    let client_info = audits.load_one(client_info::Entity, conn).await?;
 
     let audit_tuples: Vec<AuditTuple> = itertools::izip!(audits, recent_content, client_info)
         .filter_map(|(audit, con, info)| {
@@ -290,17 +516,13 @@ pub async fn get_audits_for_recent_content(
 pub async fn get_recent_audit_successes(
     num_audits: u64,
     conn: &DatabaseConnection,
-) -> Result<Vec<AuditTuple>, StatusCode> {
+) -> Result<Vec<AuditTuple>, WebError> {
     let recent_audits: Vec<content_audit::Model> = content_audit::Entity::find()
         .order_by_desc(content_audit::Column::CreatedAt)
         .filter(content_audit::Column::Result.eq(AuditResult::Success))
         .limit(num_audits)
         .all(conn)
-        .await
-        .map_err(|e| {
-            error!(key.count=num_audits, err=?e, "Could not look up recent audit successes");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        .await?;
 
     get_audit_tuples_from_audit_models(recent_audits, conn).await
 }
@@ -308,17 +530,13 @@ pub async fn get_recent_audit_successes(
 pub async fn get_recent_audit_failures(
     num_audits: u64,
     conn: &DatabaseConnection,
-) -> Result<Vec<AuditTuple>, StatusCode> {
+) -> Result<Vec<AuditTuple>, WebError> {
     let recent_audits: Vec<content_audit::Model> = content_audit::Entity::find()
         .order_by_desc(content_audit::Column::CreatedAt)
         .filter(content_audit::Column::Result.eq(AuditResult::Failure))
         .limit(num_audits)
         .all(conn)
-        .await
-        .map_err(|e| {
-            error!(key.count=num_audits, err=?e, "Could not look up recent audit failures");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        .await?;
 
     get_audit_tuples_from_audit_models(recent_audits, conn).await
 }
@@ -326,78 +544,53 @@ pub async fn get_recent_audit_failures(
 pub async fn get_audit_tuples_from_audit_models(
     audits: Vec<content_audit::Model>,
     conn: &DatabaseConnection,
-) -> Result<Vec<AuditTuple>, StatusCode> {
+) -> Result<Vec<AuditTuple>, WebError> {
     // Get the corresponding content for each audit.
-    let content: Vec<Option<content::Model>> =
-        audits.load_one(content::Entity, conn).await.map_err(|e| {
-            error!(key.count=audits.len(), err=?e, "Could not look up content for recent audits");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+    let content: Vec<Option<content::Model>> = audits.load_one(content::Entity, conn).await?;
 
     // Get the corresponding client_info for each audit.
-    let client_info: Vec<Option<client_info::Model>> = audits
-        .load_one(client_info::Entity, conn)
-        .await
-        .map_err(|e| {
-            error!(key.count=audits.len(), err=?e, "Could not look up client info for recent audits");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+    let client_info: Vec<Option<client_info::Model>> =
+        audits.load_one(client_info::Entity, conn).await?;
 
     // Zip up the audits with their corresponding content and client info.
     // Filter out the (ideally zero) audits that do not have content or client info.
     let audit_tuples: Vec<AuditTuple> = itertools::izip!(audits, content, client_info)
-        .filter_map(|(audit, content, info)| content.map(|c| (audit, c, info.unwrap())))
+        .filter_map(|(audit, content, info)| Some((audit, content?, info?)))
         .collect();
 
     Ok(audit_tuples)
 }
 
 pub async fn contentid_list(
+    params: HttpQuery<HashMap<String, String>>,
     Extension(state): Extension<Arc<State>>,
-) -> Result<HtmlTemplate<ContentIdListTemplate>, StatusCode> {
+) -> Result<HtmlTemplate<ContentIdListTemplate>, PageError> {
     const KEY_COUNT: u64 = 50;
-    let contentid_list: Vec<content::Model> = content::Entity::find()
+    let mut query = content::Entity::find();
+    if !get_include_archived_from_params(&params) {
+        query = query.filter(content::Column::Archived.eq(false));
+    }
+    let contentid_list: Vec<content::Model> = query
         .order_by_asc(content::Column::ContentId)
         .limit(KEY_COUNT)
         .all(&state.database_connection)
-        .await
-        .map_err(|e| {
-            error!(key.count=KEY_COUNT, err=?e, "Could not look up ids");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        .await?;
     let template = ContentIdListTemplate { contentid_list };
     Ok(HtmlTemplate(template))
 }
 
 pub async fn contentid_detail(
-    Path(content_id_hex): Path<String>,
+    content_id: HexParam,
     Extension(state): Extension<Arc<State>>,
-) -> Result<HtmlTemplate<ContentIdDetailTemplate>, StatusCode> {
-    let content_id_raw = hex_decode(&content_id_hex).map_err(|e| {
-        error!(content.id=content_id_hex, err=?e, "Could not decode up id bytes");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-    let content_id = content::Entity::find()
-        .filter(content::Column::ContentId.eq(content_id_raw.clone()))
-        .one(&state.database_connection)
-        .await
-        .map_err(|e| {
-            error!(content.id=content_id_hex, err=?e, "Could not look up id");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?
-        .ok_or_else(|| {
-            error!(content.id = content_id_hex, "No data for id");
-            StatusCode::NOT_FOUND
-        })?;
-
+) -> Result<HtmlTemplate<ContentIdDetailTemplate>, PageError> {
     let contentkey_list = content::Entity::find()
-        .filter(content::Column::ContentId.eq(content_id_raw))
+        .filter(content::Column::ContentId.eq(content_id.bytes))
         .all(&state.database_connection)
-        .await
-        .map_err(|e| {
-            error!(content.id=content_id_hex, err=?e, "Could not content keys for id");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        .await?;
+    let content_id = contentkey_list
+        .first()
+        .cloned()
+        .ok_or_else(|| WebError::not_found(format!("Content id {}", content_id.hex)))?;
 
     let template = ContentIdDetailTemplate {
         content_id,
@@ -406,179 +599,995 @@ pub async fn contentid_detail(
     Ok(HtmlTemplate(template))
 }
 
+/// Lists the most recently stored content keys, optionally only those from one `source`.
 pub async fn contentkey_list(
+    params: HttpQuery<HashMap<String, String>>,
     Extension(state): Extension<Arc<State>>,
-) -> Result<HtmlTemplate<ContentKeyListTemplate>, StatusCode> {
+) -> Result<HtmlTemplate<ContentKeyListTemplate>, PageError> {
     const KEY_COUNT: u64 = 50;
-    let contentkey_list: Vec<content::Model> = content::Entity::find()
+    let source = match params.get("source") {
+        None => None,
+        Some(source) => Some(ContentSource::try_from(source).map_err(|_| {
+            WebError::invalid_parameter("source", source, "unknown content source")
+        })?),
+    };
+    let mut query = content::Entity::find();
+    if let Some(source) = source {
+        query = query.filter(content::Column::Source.eq(source));
+    }
+    if !get_include_archived_from_params(&params) {
+        query = query.filter(content::Column::Archived.eq(false));
+    }
+    let contentkey_list: Vec<content::Model> = query
         .order_by_desc(content::Column::Id)
         .limit(KEY_COUNT)
         .all(&state.database_connection)
-        .await
-        .map_err(|e| {
-            error!(key.count=KEY_COUNT, err=?e, "Could not look up keys");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
-    let template = ContentKeyListTemplate { contentkey_list };
+        .await?;
+    let template = ContentKeyListTemplate {
+        contentkey_list,
+        source,
+        sources: ContentSource::iter().collect(),
+    };
     Ok(HtmlTemplate(template))
 }
 
 pub async fn contentaudit_dashboard(
     params: HttpQuery<HashMap<String, String>>,
-) -> Result<HtmlTemplate<AuditDashboardTemplate>, StatusCode> {
+) -> Result<HtmlTemplate<AuditDashboardTemplate>, PageError> {
     let subprotocol = get_subprotocol_from_params(&params);
     let template = AuditDashboardTemplate { subprotocol };
     Ok(HtmlTemplate(template))
 }
 
-pub async fn census_explorer() -> Result<HtmlTemplate<CensusExplorerTemplate>, StatusCode> {
+/// Shows the progress of recent Sync audit runs, and the segments and errors of the latest run.
+pub async fn sync_dashboard(
+    Extension(state): Extension<Arc<State>>,
+) -> Result<HtmlTemplate<SyncDashboardTemplate>, PageError> {
+    const RUN_COUNT: u64 = 10;
+    const SEGMENT_COUNT: u64 = 50;
+    const ERROR_COUNT: u64 = 50;
+    let conn = &state.database_connection;
+    let sync_audits = sync_audit::get_recent(RUN_COUNT, conn).await?;
+    let (segments, errors) = match sync_audits.first() {
+        Some(latest) => {
+            let segments = sync_audit_segment::get_recent(latest.id, SEGMENT_COUNT, conn).await?;
+            let errors = sync_audit_error::get_recent(latest.id, ERROR_COUNT, conn).await?;
+            (segments, errors)
+        }
+        None => (vec![], vec![]),
+    };
+    let template = SyncDashboardTemplate {
+        sync_audits,
+        segments,
+        errors,
+    };
+    Ok(HtmlTemplate(template))
+}
+
+/// Shows the deployment settings of the environment, like its active feature flags.
+pub async fn diagnostics(
+    Extension(state): Extension<Arc<State>>,
+) -> HtmlTemplate<DiagnosticsTemplate> {
+    HtmlTemplate(DiagnosticsTemplate {
+        environment: state.environment.clone(),
+        feature_flags: state.feature_flags.enabled().cloned().collect(),
+    })
+}
+
+pub async fn census_explorer() -> Result<HtmlTemplate<CensusExplorerTemplate>, PageError> {
     let template = CensusExplorerTemplate {};
     Ok(HtmlTemplate(template))
 }
 
 /// Returns the success rate for the last hour as a percentage.
+#[utoipa::path(
+    get,
+    path = "/api/hourly-success-rate/",
+    tag = "stats",
+    responses(
+        (status = 200, body = f32),
+    ),
+)]
 pub async fn hourly_success_rate(
     Extension(state): Extension<Arc<State>>,
-) -> Result<Json<f32>, StatusCode> {
+) -> Result<Json<f32>, WebError> {
     let open_filter = content_audit::Entity::find();
-    let stats = get_audit_stats(open_filter, Period::Hour, &state.database_connection)
-        .await
-        .map_err(|e| {
-            error!("Could not look up hourly stats: {e}");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+    let stats = get_audit_stats(open_filter, Period::Hour, &state.database_connection).await?;
     Ok(Json(stats.pass_percent))
 }
 
-/// Retrieves key details to display.
-///
-/// At present this assumes it is a HistoryContentKey.
-pub async fn contentkey_detail(
-    Path(content_key_hex): Path<String>,
+/// Returns the full audit stats for the last hour, including errored and pending counts.
+#[utoipa::path(
+    get,
+    path = "/api/hourly-audit-stats/",
+    tag = "stats",
+    responses(
+        (status = 200, body = AuditStats),
+    ),
+)]
+pub async fn hourly_audit_stats(
     Extension(state): Extension<Arc<State>>,
-) -> Result<HtmlTemplate<ContentKeyDetailTemplate>, StatusCode> {
-    let content_key_raw = hex_decode(&content_key_hex).map_err(|e| {
-        error!(content.key=content_key_hex, err=?e, "Could not decode up key bytes");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+) -> Result<Json<AuditStats>, WebError> {
+    let open_filter = content_audit::Entity::find();
+    let stats = get_audit_stats(open_filter, Period::Hour, &state.database_connection).await?;
+    Ok(Json(stats))
+}
 
-    let content_key_model = content::Entity::find()
-        .filter(content::Column::ContentKey.eq(content_key_raw))
-        .one(&state.database_connection)
-        .await
-        .map_err(|e| {
-            error!(content.key=content_key_hex, err=?e, "Could not look up key");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?
-        .ok_or_else(|| {
-            error!(content.key = content_key_hex, "No data for key");
-            StatusCode::NOT_FOUND
-        })?;
+/// Maximum number of census incidents listed.
+const CENSUS_INCIDENT_LIST_LIMIT: u64 = 50;
 
-    let contentaudit_list = content_key_model
-        .find_related(content_audit::Entity)
-        .all(&state.database_connection)
-        .await
-        .map_err(|e| {
-            error!(content.key=content_key_hex, err=?e, "Could not look up audits for key");
-            StatusCode::NOT_FOUND
-        })?;
+/// Lists the latest censuses that found far fewer nodes than the censuses before them, with
+/// the diagnostics captured at the time.
+#[utoipa::path(
+    get,
+    path = "/api/census-incidents/",
+    tag = "censuses",
+    params(
+        NetworkParam,
+    ),
+    responses(
+        (status = 200, body = [census_incident::Model]),
+    ),
+)]
+pub async fn census_incidents(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Vec<census_incident::Model>>, WebError> {
+    let subprotocol = get_subprotocol_from_params(&http_args);
+    let incidents = census_incident::get_latest(
+        subprotocol,
+        CENSUS_INCIDENT_LIST_LIMIT,
+        &state.database_connection,
+    )
+    .await?;
+    Ok(Json(incidents))
+}
 
-    let (content_id, content_kind) =
-        if let Ok(content_key) = HistoryContentKey::try_from_hex(&content_key_hex) {
-            let content_id = hex_encode(content_key.content_id());
-            let content_kind = content_key.to_string();
-            (content_id, content_kind)
-        } else if let Ok(content_key) = StateContentKey::try_from_hex(&content_key_hex) {
-            let content_id = hex_encode(content_key.content_id());
-            let content_kind = content_key.to_string();
-            (content_id, content_kind)
-        } else if let Ok(content_key) = BeaconContentKey::try_from_hex(&content_key_hex) {
-            let content_id = hex_encode(content_key.content_id());
-            let content_kind = content_key.to_string();
-            (content_id, content_kind)
-        } else {
-            error!(
-                content.key = content_key_hex,
-                "Could not create key from bytes"
-            );
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
-        };
-    let metadata_model = execution_metadata::Entity::find()
-        .filter(execution_metadata::Column::Content.eq(content_key_model.id))
-        .one(&state.database_connection)
-        .await
-        .map_err(|e| {
-            error!(content.key=content_key_hex, err=?e, "No content metadata found");
-            StatusCode::NOT_FOUND
-        })?;
-    let block_number = metadata_model.map(|m| m.block_number);
+/// Number of censuses in the census reliability summary.
+const CENSUS_RELIABILITY_LIMIT: u64 = 50;
 
-    let template = ContentKeyDetailTemplate {
-        content_key: content_key_hex,
-        content_key_model,
-        contentaudit_list,
-        content_id,
-        content_kind,
-        block_number,
-    };
-    Ok(HtmlTemplate(template))
+/// How reliably a census reached the nodes it found.
+#[derive(Serialize, Debug, ToSchema)]
+pub struct CensusReliability {
+    #[serde(flatten)]
+    #[schema(inline)]
+    pub summary: census_node::CensusPingSummary,
+    /// Percentage of the nodes found that needed PING retries.
+    pub retried_percent: Option<f64>,
 }
 
-pub async fn contentaudit_detail(
-    Path(audit_id): Path<String>,
+/// Lists, for the latest censuses, how many nodes only answered after PING retries and how
+/// long their PINGs took, to measure the flakiness of the underlying transport.
+#[utoipa::path(
+    get,
+    path = "/api/census-reliability/",
+    tag = "censuses",
+    params(
+        NetworkParam,
+    ),
+    responses(
+        (status = 200, body = [CensusReliability]),
+    ),
+)]
+pub async fn census_reliability(
+    http_args: HttpQuery<HashMap<String, String>>,
     Extension(state): Extension<Arc<State>>,
-) -> Result<HtmlTemplate<ContentAuditDetailTemplate>, StatusCode> {
-    let audit_id = audit_id.parse::<i32>().unwrap();
-    info!("Audit ID: {}", audit_id);
-    let mut audit = match content_audit::Entity::find_by_id(audit_id)
-        .one(&state.database_connection)
-        .await
-    {
-        Ok(Some(audit)) => audit,
-        Ok(None) => return Err(StatusCode::from_u16(404).unwrap()),
-        Err(err) => {
-            error!(err=?err, "Failed to lookup audit");
-            return Err(StatusCode::from_u16(404).unwrap());
+) -> Result<Json<Vec<CensusReliability>>, WebError> {
+    let subprotocol = get_subprotocol_from_params(&http_args);
+    let summaries = census_node::get_ping_summaries(
+        subprotocol,
+        CENSUS_RELIABILITY_LIMIT,
+        &state.database_connection,
+    )
+    .await?;
+    Ok(Json(
+        summaries
+            .into_iter()
+            .map(|summary| CensusReliability {
+                retried_percent: summary.retried_percent(),
+                summary,
+            })
+            .collect(),
+    ))
+}
+
+/// Lists the nodes that joined, left, changed client version, or changed radius between two
+/// censuses of the same subnetwork, given as `from` and `to` census ids.
+#[utoipa::path(
+    get,
+    path = "/api/census-diff/",
+    tag = "censuses",
+    params(
+        ("from" = i32, Query, description = "Census to compare from"),
+        ("to" = i32, Query, description = "Census to compare to, of the same subnetwork"),
+        IncludeArchivedParam,
+    ),
+    responses(
+        (status = 200, body = census_node::CensusDiff),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+        (status = 404, description = "Unknown census", body = ErrorBody),
+    ),
+)]
+pub async fn census_diff(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<census_node::CensusDiff>, WebError> {
+    let from_census_id: i32 = parse_required_param(&http_args, "from")?;
+    let to_census_id: i32 = parse_required_param(&http_args, "to")?;
+
+    let mut sub_networks = vec![];
+    for census_id in [from_census_id, to_census_id] {
+        let census_model = census::Entity::find_by_id(census_id)
+            .one(&state.database_connection)
+            .await?
+            .ok_or_else(|| WebError::not_found(format!("Census {census_id}")))?;
+        sub_networks.push(census_model.sub_network);
+    }
+    if sub_networks[0] != sub_networks[1] {
+        return Err(WebError::invalid_parameter(
+            "to",
+            &to_census_id.to_string(),
+            "not a census of the same subnetwork",
+        ));
+    }
+
+    let diff = census_node::get_diff(
+        from_census_id,
+        to_census_id,
+        get_include_archived_from_params(&http_args),
+        &state.database_connection,
+    )
+    .await?;
+    Ok(Json(diff))
+}
+
+/// A node of a census whose declared endpoints did not answer direct probes.
+#[derive(Serialize, Debug, ToSchema)]
+pub struct UnreachableEndpoint {
+    pub node_id: String,
+    #[serde(flatten)]
+    #[schema(inline)]
+    pub endpoint: census_node::UnreachableEndpoint,
+}
+
+/// Lists the nodes of a census that answered through the Portal node, but whose UDP or TCP
+/// endpoint declared in their ENR did not answer a direct probe.
+#[utoipa::path(
+    get,
+    path = "/api/unreachable-endpoints/",
+    tag = "censuses",
+    params(
+        ("census-id" = i32, Query, description = "Census"),
+        IncludeArchivedParam,
+    ),
+    responses(
+        (status = 200, body = [UnreachableEndpoint]),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+    ),
+)]
+pub async fn unreachable_endpoints(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Vec<UnreachableEndpoint>>, WebError> {
+    let census_id: i32 = parse_required_param(&http_args, "census-id")?;
+    let endpoints = census_node::get_unreachable_endpoints(
+        census_id,
+        get_include_archived_from_params(&http_args),
+        &state.database_connection,
+    )
+    .await?;
+    Ok(Json(
+        endpoints
+            .into_iter()
+            .map(|endpoint| UnreachableEndpoint {
+                node_id: endpoint.node_id_as_hex(),
+                endpoint,
+            })
+            .collect(),
+    ))
+}
+
+/// A node that dropped out of a census, and why.
+#[derive(Serialize, Debug, ToSchema)]
+pub struct CensusNodeError {
+    pub node_id: String,
+    #[serde(flatten)]
+    #[schema(inline)]
+    pub failure: census_node_error::CensusNodeFailure,
+}
+
+/// Lists the nodes that failed their liveness checks in a census, with why they failed, to
+/// debug flaky clients.
+#[utoipa::path(
+    get,
+    path = "/api/census-node-errors/",
+    tag = "censuses",
+    params(
+        ("census-id" = i32, Query, description = "Census"),
+    ),
+    responses(
+        (status = 200, body = [CensusNodeError]),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+    ),
+)]
+pub async fn census_node_errors(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Vec<CensusNodeError>>, WebError> {
+    let census_id: i32 = parse_required_param(&http_args, "census-id")?;
+    let failures = census_node_error::get_by_census(census_id, &state.database_connection).await?;
+    Ok(Json(
+        failures
+            .into_iter()
+            .map(|failure| CensusNodeError {
+                node_id: failure.node_id_as_hex(),
+                failure,
+            })
+            .collect(),
+    ))
+}
+
+/// Census nodes read from the database per query while exporting a census.
+const EXPORT_PAGE_SIZE: u64 = 1000;
+
+/// Header of a census exported as CSV, naming the columns of [census_node_csv_row].
+const CENSUS_EXPORT_CSV_HEADER: &str =
+    "node_id,enr,client,client_version,os,data_radius,protocol_versions,surveyed_at\n";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ExportFormat {
+    Json,
+    Csv,
+}
+
+/// Streams all the nodes found by a census, with their ENRs and the client details read from
+/// them, as a JSON array (`?format=json`, the default) or CSV (`?format=csv`) file to download.
+///
+/// Nodes are read a page at a time, so that large censuses are not held in memory.
+#[utoipa::path(
+    get,
+    path = "/api/census/{id}/export",
+    tag = "censuses",
+    params(
+        ("id" = i32, Path, description = "Census"),
+        ("format" = Option<String>, Query, description = "`json` (the default) or `csv`"),
+    ),
+    responses(
+        (status = 200, content(
+            ("application/json" = [census_node::ExportedCensusNode]),
+            ("text/csv" = String),
+        )),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+        (status = 404, description = "Unknown census", body = ErrorBody),
+    ),
+)]
+pub async fn census_export(
+    IdParam(census_id): IdParam,
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<impl IntoResponse, WebError> {
+    let format = match http_args.get("format").map(String::as_str) {
+        None | Some("json") => ExportFormat::Json,
+        Some("csv") => ExportFormat::Csv,
+        Some(other) => {
+            return Err(WebError::invalid_parameter(
+                "format",
+                other,
+                "expected json or csv",
+            ))
         }
     };
+    let census = census::Entity::find_by_id(census_id)
+        .one(&state.database_connection)
+        .await?
+        .ok_or_else(|| WebError::not_found(format!("Census {census_id}")))?;
+    if census.nodes_archived_at.is_some() {
+        return Err(WebError::not_found(format!(
+            "Nodes of archived census {census_id}"
+        )));
+    }
 
-    let trace_string = &audit.trace;
-    let mut trace: Option<QueryTrace> = match serde_json::from_str(trace_string) {
-        Ok(trace) => Some(trace),
-        Err(err) => {
-            error!(trace=?trace_string, err=?err, "Failed to deserialize query trace.");
-            None
+    let (mut sender, export) = Body::channel();
+    let conn = state.database_connection.clone();
+    tokio::spawn(async move {
+        if let Err(e) = stream_census_export(census_id, format, &mut sender, &conn).await {
+            error!(census.id = census_id, err=?e, "Could not export census");
+            sender.abort();
         }
+    });
+    let (content_type, extension) = match format {
+        ExportFormat::Json => ("application/json", "json"),
+        ExportFormat::Csv => ("text/csv", "csv"),
     };
+    let headers = [
+        (header::CONTENT_TYPE, content_type.to_owned()),
+        (
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"glados-census-{census_id}.{extension}\""),
+        ),
+    ];
+    Ok((headers, body::boxed(export)))
+}
 
-    // If we were able to deserialize the trace, we can look up & interpolate the radius for the nodes in the trace.
-    if let Some(trace) = &mut trace {
-        // Get the timestamp of the query
-        let timestamp: DateTime<Utc> = Utc
-            .timestamp_millis_opt(trace.started_at_ms as i64)
-            .single()
-            .expect("Failed to convert timestamp to DateTime");
+async fn stream_census_export(
+    census_id: i32,
+    format: ExportFormat,
+    sender: &mut hyper::body::Sender,
+    conn: &DatabaseConnection,
+) -> anyhow::Result<()> {
+    sender
+        .send_data(Bytes::from_static(match format {
+            ExportFormat::Json => b"[",
+            ExportFormat::Csv => CENSUS_EXPORT_CSV_HEADER.as_bytes(),
+        }))
+        .await?;
+    let mut after_id = 0;
+    let mut first = true;
+    loop {
+        let nodes =
+            census_node::get_export_page(census_id, after_id, EXPORT_PAGE_SIZE, conn).await?;
+        let Some(last) = nodes.last() else {
+            break;
+        };
+        after_id = last.id;
+        let mut chunk = String::new();
+        for node in &nodes {
+            match format {
+                ExportFormat::Json => {
+                    if !first {
+                        chunk.push(',');
+                    }
+                    chunk.push_str(&serde_json::to_string(node)?);
+                }
+                ExportFormat::Csv => chunk.push_str(&census_node_csv_row(node)),
+            }
+            first = false;
+        }
+        sender.send_data(Bytes::from(chunk)).await?;
+        if (nodes.len() as u64) < EXPORT_PAGE_SIZE {
+            break;
+        }
+    }
+    if format == ExportFormat::Json {
+        sender.send_data(Bytes::from_static(b"]")).await?;
+    }
+    Ok(())
+}
 
-        // Do a query to get, for each node, the radius recorded closest to the time at which the trace took place.
-        let node_ids: Vec<Vec<u8>> = trace
-            .metadata
-            .keys()
-            .cloned()
-            .map(|x| x.raw().to_vec())
-            .collect();
-        let node_ids_str = format!(
-            "{{{}}}",
-            node_ids
+/// A census node as a CSV line, with protocol versions separated by semicolons.
+fn census_node_csv_row(node: &census_node::ExportedCensusNode) -> String {
+    let protocol_versions = node
+        .protocol_versions
+        .as_ref()
+        .map(|versions| {
+            versions
                 .iter()
-                .map(|id| format!("\\\\x{}", hex::encode(id)))
-                .collect::<Vec<String>>()
-                .join(",")
-        );
-        let nodes_with_radius: HashMap<NodeId, B256> =
-            match NodeWithRadius::find_by_statement(Statement::from_sql_and_values(
-                DbBackend::Postgres,
-                "
+                .map(u8::to_string)
+                .collect::<Vec<_>>()
+                .join(";")
+        })
+        .unwrap_or_default();
+    let surveyed_at = node.surveyed_at.to_rfc3339();
+    let fields = [
+        node.node_id.as_str(),
+        node.enr.as_str(),
+        node.client.as_deref().unwrap_or_default(),
+        node.client_version.as_deref().unwrap_or_default(),
+        node.os.as_deref().unwrap_or_default(),
+        node.data_radius.as_str(),
+        protocol_versions.as_str(),
+        surveyed_at.as_str(),
+    ];
+    let mut row = fields.map(csv_field).join(",");
+    row.push('\n');
+    row
+}
+
+/// Quotes a CSV field if it holds a separator, a quote or a line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+/// An ENR that a node presented in a census besides its canonical one.
+#[derive(Serialize, Debug, ToSchema)]
+pub struct CensusAlternateEnr {
+    pub node_id: String,
+    #[serde(flatten)]
+    #[schema(inline)]
+    pub alternate: census_alternate_enr::AlternateEnr,
+}
+
+/// Lists the nodes that presented more than one ENR in a census, with the ENR the census kept
+/// for them and the alternates.
+#[utoipa::path(
+    get,
+    path = "/api/census-alternate-enrs/",
+    tag = "censuses",
+    params(
+        ("census-id" = i32, Query, description = "Census"),
+    ),
+    responses(
+        (status = 200, body = [CensusAlternateEnr]),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+    ),
+)]
+pub async fn census_alternate_enrs(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Vec<CensusAlternateEnr>>, WebError> {
+    let census_id = http_args
+        .get("census-id")
+        .ok_or_else(|| WebError::invalid_parameter("census-id", "", "missing"))?;
+    let census_id = parse_param::<i32>("census-id", census_id)?;
+    let alternates =
+        census_alternate_enr::get_by_census(census_id, &state.database_connection).await?;
+    Ok(Json(
+        alternates
+            .into_iter()
+            .map(|alternate| CensusAlternateEnr {
+                node_id: alternate.node_id_as_hex(),
+                alternate,
+            })
+            .collect(),
+    ))
+}
+
+/// Maximum number of round trip audits listed.
+const ROUND_TRIP_LIST_LIMIT: u64 = 100;
+
+/// A round trip audit, with the time from gossiping the content to finding it.
+#[derive(Serialize, Debug, ToSchema)]
+pub struct RoundTrip {
+    #[serde(flatten)]
+    #[schema(inline)]
+    pub round_trip: round_trip_audit::Model,
+    pub latency_ms: Option<i64>,
+}
+
+/// Lists the latest round trip audits, in which content was gossiped and then audited until
+/// it was found.
+#[utoipa::path(
+    get,
+    path = "/api/round-trips/",
+    tag = "audits",
+    responses(
+        (status = 200, body = [RoundTrip]),
+    ),
+)]
+pub async fn round_trips(
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Vec<RoundTrip>>, WebError> {
+    let round_trips =
+        round_trip_audit::get_latest(ROUND_TRIP_LIST_LIMIT, &state.database_connection).await?;
+    Ok(Json(
+        round_trips
+            .into_iter()
+            .map(|round_trip| RoundTrip {
+                latency_ms: round_trip.latency_ms(),
+                round_trip,
+            })
+            .collect(),
+    ))
+}
+
+/// Default number of unstable content keys listed.
+const DEFAULT_UNSTABLE_CONTENT_LIMIT: u64 = 50;
+/// Maximum number of unstable content keys listed.
+const MAX_UNSTABLE_CONTENT_LIMIT: u64 = 1000;
+
+/// Lists the content keys whose audits most often flip between passing and failing, as last
+/// computed by glados-audit.
+///
+/// Takes the `network` and an optional `limit` parameter.
+#[utoipa::path(
+    get,
+    path = "/api/unstable-content/",
+    tag = "audits",
+    params(
+        NetworkParam,
+        IncludeArchivedParam,
+        (
+            "limit" = Option<u64>, Query,
+            description = "At most this many content keys, 50 by default",
+        ),
+    ),
+    responses(
+        (status = 200, body = [content_flapping::UnstableContent]),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+    ),
+)]
+pub async fn unstable_content(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Vec<content_flapping::UnstableContent>>, WebError> {
+    let subprotocol = get_subprotocol_from_params(&http_args);
+    let limit = match http_args.get("limit") {
+        None => DEFAULT_UNSTABLE_CONTENT_LIMIT,
+        Some(limit) => parse_param::<u64>("limit", limit)?.min(MAX_UNSTABLE_CONTENT_LIMIT),
+    };
+    let unstable = content_flapping::get_most_unstable(
+        subprotocol,
+        limit,
+        get_include_archived_from_params(&http_args),
+        &state.database_connection,
+    )
+    .await?;
+    Ok(Json(unstable))
+}
+
+/// Longest operator name accepted in a node operator claim.
+const MAX_OPERATOR_NAME_LENGTH: usize = 64;
+/// Longest operator contact accepted in a node operator claim.
+const MAX_OPERATOR_CONTACT_LENGTH: usize = 200;
+/// How old the signature of a node operator claim may be when it is submitted.
+const OPERATOR_CLAIM_MAX_AGE: TimeDelta = TimeDelta::hours(1);
+/// How far ahead of the server clock the signature of a claim may be dated.
+const OPERATOR_CLAIM_MAX_CLOCK_SKEW: TimeDelta = TimeDelta::minutes(5);
+
+/// Whether a name or contact of a node operator claim fits in `max_length` characters and has
+/// no control characters, which could break the pages and logs it is shown in.
+fn is_valid_operator_text(text: &str, max_length: usize) -> bool {
+    text.chars().count() <= max_length && !text.chars().any(char::is_control)
+}
+
+/// A claim by a node operator to label their node, signed with the node key.
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct NodeOperatorClaim {
+    /// ENR of the node, whose public key verifies the signature.
+    pub enr: String,
+    pub display_name: String,
+    pub contact: Option<String>,
+    /// Unix timestamp in seconds of when the claim was signed.
+    pub signed_at: i64,
+    /// Hex encoded signature of the [node_operator::claim_message].
+    pub signature: String,
+}
+
+/// Labels a node with its operator, once the claim is verified to be signed by the node key.
+#[utoipa::path(
+    post,
+    path = "/api/node-operator/",
+    tag = "nodes",
+    request_body = NodeOperatorClaim,
+    responses(
+        (status = 200, body = node_operator::Model),
+        (status = 400, description = "Invalid claim", body = ErrorBody),
+        (status = 403, description = "The claim is not signed by the node key", body = ErrorBody),
+        (status = 404, description = "The node has not been seen by glados", body = ErrorBody),
+        (
+            status = 409,
+            description = "The node has a claim signed at the same time or later",
+            body = ErrorBody,
+        ),
+    ),
+)]
+pub async fn claim_node_operator(
+    Extension(state): Extension<Arc<State>>,
+    Json(claim): Json<NodeOperatorClaim>,
+) -> Result<Json<node_operator::Model>, WebError> {
+    let enr =
+        Enr::from_str(&claim.enr).map_err(|e| WebError::invalid_parameter("ENR", &claim.enr, e))?;
+    let display_name = claim.display_name.trim().to_owned();
+    if display_name.is_empty() || !is_valid_operator_text(&display_name, MAX_OPERATOR_NAME_LENGTH) {
+        return Err(WebError::invalid_parameter(
+            "display name",
+            &display_name,
+            "empty, too long or with control characters",
+        ));
+    }
+    let contact = claim
+        .contact
+        .map(|contact| contact.trim().to_owned())
+        .filter(|contact| !contact.is_empty());
+    if contact
+        .as_ref()
+        .is_some_and(|contact| !is_valid_operator_text(contact, MAX_OPERATOR_CONTACT_LENGTH))
+    {
+        return Err(WebError::invalid_parameter(
+            "contact",
+            contact.as_deref().unwrap_or_default(),
+            "too long or with control characters",
+        ));
+    }
+    let signed_at = Utc
+        .timestamp_opt(claim.signed_at, 0)
+        .single()
+        .ok_or_else(|| {
+            WebError::invalid_parameter("signing time", &claim.signed_at.to_string(), "invalid")
+        })?;
+    let now = Utc::now();
+    if signed_at < now - OPERATOR_CLAIM_MAX_AGE || signed_at > now + OPERATOR_CLAIM_MAX_CLOCK_SKEW {
+        return Err(WebError::invalid_parameter(
+            "signing time",
+            &signed_at.to_rfc3339(),
+            "too old or in the future",
+        ));
+    }
+    let signature = parse_hex_param("signature", &claim.signature)?;
+
+    let message =
+        node_operator::claim_message(enr.node_id(), &display_name, contact.as_deref(), signed_at);
+    if !node_operator::verify_claim(&enr, &message, &signature) {
+        return Err(WebError::Forbidden(
+            "The claim is not signed by the node key".to_owned(),
+        ));
+    }
+
+    // Only nodes glados has seen can be labelled, so claims cannot fill the node table.
+    let node = node::Entity::find()
+        .filter(node::Column::NodeId.eq(enr.node_id().raw().to_vec()))
+        .one(&state.database_connection)
+        .await?
+        .ok_or_else(|| WebError::not_found(format!("Node {}", hex_encode(enr.node_id().raw()))))?;
+    // A signature older than the stored claim must not undo a later change.
+    let existing = node_operator::get(node.id, &state.database_connection).await?;
+    if existing.is_some_and(|existing| existing.signed_at >= signed_at) {
+        return Err(WebError::Conflict(
+            "The node has a claim signed at the same time or later".to_owned(),
+        ));
+    }
+    let operator = node_operator::upsert(
+        node.id,
+        display_name,
+        contact,
+        signature,
+        signed_at,
+        &state.database_connection,
+    )
+    .await?;
+    info!(
+        node.id = node.id,
+        operator = operator.display_name,
+        "Verified node operator claim"
+    );
+    Ok(Json(operator))
+}
+
+/// Default number of minutes after which a pending audit is considered stuck.
+const DEFAULT_STUCK_PENDING_MINUTES: i64 = 30;
+
+/// Maximum number of stuck audits listed in the alert response.
+const STUCK_PENDING_LIST_LIMIT: u64 = 100;
+
+#[derive(Serialize, ToSchema)]
+pub struct StuckPendingAudit {
+    id: i32,
+    content_key: i32,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct StuckPendingAlert {
+    /// Whether any audit has been pending for longer than the threshold.
+    alert: bool,
+    threshold_minutes: i64,
+    stuck_count: u64,
+    /// The oldest stuck audits, oldest first.
+    audits: Vec<StuckPendingAudit>,
+}
+
+/// Reports audits that have been `Pending` for longer than `threshold-minutes`.
+///
+/// Intended for alerting: a non-empty result usually means an audit worker crashed mid-audit.
+#[utoipa::path(
+    get,
+    path = "/api/stuck-pending-audits/",
+    tag = "audits",
+    params(
+        (
+            "threshold-minutes" = Option<i64>, Query,
+            description = "Minutes after which a pending audit is stuck, 30 by default",
+        ),
+    ),
+    responses(
+        (status = 200, body = StuckPendingAlert),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+    ),
+)]
+pub async fn stuck_pending_audits(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<StuckPendingAlert>, WebError> {
+    let threshold_minutes: i64 = match http_args.get("threshold-minutes") {
+        None => DEFAULT_STUCK_PENDING_MINUTES,
+        Some(minutes) => parse_param::<i64>("threshold-minutes", minutes)?,
+    };
+    let threshold = chrono::TimeDelta::try_minutes(threshold_minutes).ok_or_else(|| {
+        WebError::invalid_parameter(
+            "threshold-minutes",
+            &threshold_minutes.to_string(),
+            "too large",
+        )
+    })?;
+    let started_before = Utc::now() - threshold;
+
+    let (stuck_count, stuck_audits) = tokio::join!(
+        content_audit::count_stuck_pending(started_before, &state.database_connection),
+        content_audit::get_stuck_pending(
+            started_before,
+            STUCK_PENDING_LIST_LIMIT,
+            &state.database_connection
+        ),
+    );
+    let stuck_count = stuck_count?;
+    let stuck_audits = stuck_audits?;
+
+    Ok(Json(StuckPendingAlert {
+        alert: stuck_count > 0,
+        threshold_minutes,
+        stuck_count,
+        audits: stuck_audits
+            .into_iter()
+            .map(|audit| StuckPendingAudit {
+                id: audit.id,
+                content_key: audit.content_key,
+                created_at: audit.created_at,
+            })
+            .collect(),
+    }))
+}
+
+/// Retrieves key details to display.
+///
+/// At present this assumes it is a HistoryContentKey.
+pub async fn contentkey_detail(
+    content_key: HexParam,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<HtmlTemplate<ContentKeyDetailTemplate>, PageError> {
+    let HexParam {
+        hex: content_key_hex,
+        bytes: content_key_raw,
+    } = content_key;
+    let content_key_model = content::Entity::find()
+        .filter(content::Column::ContentKey.eq(content_key_raw))
+        .one(&state.database_connection)
+        .await?
+        .ok_or_else(|| WebError::not_found(format!("Content key {content_key_hex}")))?;
+
+    let contentaudit_list = content_key_model
+        .find_related(content_audit::Entity)
+        .all(&state.database_connection)
+        .await?;
+
+    let (content_id, content_kind) = describe_content_key(&content_key_hex)?;
+    let metadata_model = execution_metadata::Entity::find()
+        .filter(execution_metadata::Column::Content.eq(content_key_model.id))
+        .one(&state.database_connection)
+        .await?;
+    let block_number = metadata_model.map(|m| m.block_number);
+    let external_links = state
+        .explorer_links
+        .for_content(&content_key_model, block_number);
+
+    let template = ContentKeyDetailTemplate {
+        content_key: content_key_hex,
+        content_key_model,
+        contentaudit_list,
+        content_id,
+        content_kind,
+        block_number,
+        external_links,
+    };
+    Ok(HtmlTemplate(template))
+}
+
+/// Returns the content id and a description of a hex encoded content key.
+fn describe_content_key(content_key_hex: &str) -> Result<(String, String), WebError> {
+    if let Ok(content_key) = HistoryContentKey::try_from_hex(content_key_hex) {
+        Ok((
+            hex_encode(content_key.content_id()),
+            content_key.to_string(),
+        ))
+    } else if let Ok(content_key) = StateContentKey::try_from_hex(content_key_hex) {
+        Ok((
+            hex_encode(content_key.content_id()),
+            content_key.to_string(),
+        ))
+    } else if let Ok(content_key) = BeaconContentKey::try_from_hex(content_key_hex) {
+        Ok((
+            hex_encode(content_key.content_id()),
+            content_key.to_string(),
+        ))
+    } else {
+        Err(WebError::invalid_parameter(
+            "content key",
+            content_key_hex,
+            "not a History, State or Beacon content key",
+        ))
+    }
+}
+
+/// Returns the query trace of an audit as stored, as a JSON file to download.
+#[utoipa::path(
+    get,
+    path = "/api/audit/{audit_id}/trace.raw",
+    tag = "audits",
+    params(
+        ("audit_id" = i32, Path, description = "Audit"),
+    ),
+    responses(
+        (
+            status = 200,
+            description = "The trace as stored",
+            content_type = "application/json",
+            body = Object,
+        ),
+        (status = 404, description = "Unknown audit, or audit without a trace", body = ErrorBody),
+    ),
+)]
+pub async fn audit_trace_raw(
+    IdParam(audit_id): IdParam,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<impl IntoResponse, WebError> {
+    let audit = content_audit::Entity::find_by_id(audit_id)
+        .one(&state.database_connection)
+        .await?
+        .ok_or_else(|| WebError::not_found(format!("Audit {audit_id}")))?;
+    // Audits made without a tracing client have no trace.
+    if audit.trace.is_empty() {
+        return Err(WebError::not_found(format!("Trace of audit {audit_id}")));
+    }
+    let headers = [
+        (header::CONTENT_TYPE, "application/json".to_owned()),
+        (
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"glados-audit-{audit_id}-trace.json\""),
+        ),
+    ];
+    Ok((headers, audit.trace))
+}
+
+pub async fn contentaudit_detail(
+    IdParam(audit_id): IdParam,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<HtmlTemplate<ContentAuditDetailTemplate>, PageError> {
+    info!("Audit ID: {}", audit_id);
+    let mut audit = content_audit::Entity::find_by_id(audit_id)
+        .one(&state.database_connection)
+        .await?
+        .ok_or_else(|| WebError::not_found(format!("Audit {audit_id}")))?;
+
+    let trace_string = &audit.trace;
+    let mut trace: Option<QueryTrace> = match serde_json::from_str(trace_string) {
+        Ok(trace) => Some(trace),
+        Err(err) => {
+            error!(trace=?trace_string, err=?err, "Failed to deserialize query trace.");
+            None
+        }
+    };
+
+    // If we were able to deserialize the trace, we can look up & interpolate the radius for the nodes in the trace.
+    if let Some(trace) = &mut trace {
+        // Get the timestamp of the query
+        let timestamp: DateTime<Utc> = Utc
+            .timestamp_millis_opt(trace.started_at_ms as i64)
+            .single()
+            .expect("Failed to convert timestamp to DateTime");
+
+        // Do a query to get, for each node, the radius recorded in the census linked to the audit,
+        // or failing that, the radius recorded closest to the time at which the trace took place.
+        let node_ids: Vec<Vec<u8>> = trace
+            .metadata
+            .keys()
+            .cloned()
+            .map(|x| x.raw().to_vec())
+            .collect();
+        let node_ids_str = format!(
+            "{{{}}}",
+            node_ids
+                .iter()
+                .map(|id| format!("\\\\x{}", hex::encode(id)))
+                .collect::<Vec<String>>()
+                .join(",")
+        );
+        let nodes_with_radius: HashMap<NodeId, B256> =
+            match NodeWithRadius::find_by_statement(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "
                 SELECT DISTINCT ON (n.node_id)
                     n.node_id,
                     cn.data_radius
@@ -590,9 +1599,14 @@ pub async fn contentaudit_detail(
                     n.node_id = ANY($1::bytea[])
                 ORDER BY
                     n.node_id,
+                    cn.census_id IS NOT DISTINCT FROM $3 DESC,
                     ABS(EXTRACT(EPOCH FROM (cn.surveyed_at - $2::timestamp)))
                 ",
-                vec![node_ids_str.into(), timestamp.into()],
+                vec![
+                    node_ids_str.into(),
+                    timestamp.into(),
+                    audit.census_id.into(),
+                ],
             ))
             .all(&state.database_connection)
             .await
@@ -630,20 +1644,33 @@ pub async fn contentaudit_detail(
     let content = audit
         .find_related(content::Entity)
         .one(&state.database_connection)
-        .await
-        .unwrap()
-        .expect("Failed to get audit content key");
+        .await?
+        .ok_or_else(|| WebError::not_found(format!("Content of audit {audit_id}")))?;
 
     let execution_metadata = content
         .find_related(execution_metadata::Entity)
         .one(&state.database_connection)
-        .await
-        .unwrap();
+        .await?;
+
+    let census = audit
+        .find_related(census::Entity)
+        .one(&state.database_connection)
+        .await?;
+    let census_minutes_before_audit = census
+        .as_ref()
+        .map(|census| (audit.created_at - census.started_at).num_minutes());
+    let external_links = state.explorer_links.for_content(
+        &content,
+        execution_metadata.as_ref().map(|m| m.block_number),
+    );
 
     let template = ContentAuditDetailTemplate {
         audit,
         content,
         execution_metadata,
+        census,
+        census_minutes_before_audit,
+        external_links,
     };
     Ok(HtmlTemplate(template))
 }
@@ -654,202 +1681,2992 @@ pub struct NodeWithRadius {
     pub data_radius: Vec<u8>,
 }
 
-/// Takes an AuditFilter object generated from http query params
-/// Conditionally creates a query based on the filters
-pub async fn contentaudit_filter(
-    Extension(state): Extension<Arc<State>>,
-    filters: HttpQuery<AuditFilters>,
-) -> Result<HtmlTemplate<AuditTableTemplate>, StatusCode> {
-    let audits = filter_audits(filters.0);
-    let (hour_stats, day_stats, week_stats, filtered_audits) = tokio::join!(
-        get_audit_stats(audits.clone(), Period::Hour, &state.database_connection),
-        get_audit_stats(audits.clone(), Period::Day, &state.database_connection),
-        get_audit_stats(audits.clone(), Period::Week, &state.database_connection),
-        audits
-            .order_by_desc(content_audit::Column::CreatedAt)
-            .limit(30)
-            .all(&state.database_connection),
-    );
+/// Takes an AuditFilter object generated from http query params
+/// Conditionally creates a query based on the filters
+pub async fn contentaudit_filter(
+    Extension(state): Extension<Arc<State>>,
+    filters: HttpQuery<AuditFilters>,
+) -> Result<HtmlTemplate<AuditTableTemplate>, PageError> {
+    let audits = filter_audits(filters.0);
+    let (hour_stats, day_stats, week_stats, filtered_audits) = tokio::join!(
+        get_audit_stats(audits.clone(), Period::Hour, &state.database_connection),
+        get_audit_stats(audits.clone(), Period::Day, &state.database_connection),
+        get_audit_stats(audits.clone(), Period::Week, &state.database_connection),
+        audits
+            .order_by_desc(content_audit::Column::CreatedAt)
+            .limit(30)
+            .all(&state.database_connection),
+    );
+
+    let filtered_audits = filtered_audits?;
+    let hour_stats = hour_stats?;
+    let day_stats = day_stats?;
+    let week_stats = week_stats?;
+
+    let filtered_audits: Vec<AuditTuple> =
+        get_audit_tuples_from_audit_models(filtered_audits, &state.database_connection).await?;
+
+    let template = AuditTableTemplate {
+        stats: [hour_stats, day_stats, week_stats],
+        audits: filtered_audits,
+    };
+
+    Ok(HtmlTemplate(template))
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct EnvironmentsResponse {
+    /// The environment the request was served from.
+    pub current: String,
+    pub environments: Vec<String>,
+}
+
+/// Lists the environments that can be selected in the environment switcher.
+#[utoipa::path(
+    get,
+    path = "/api/environments/",
+    tag = "site",
+    responses(
+        (status = 200, body = EnvironmentsResponse),
+    ),
+)]
+pub async fn environments(
+    Extension(state): Extension<Arc<State>>,
+    Extension(environments): Extension<Arc<Environments>>,
+) -> Json<EnvironmentsResponse> {
+    Json(EnvironmentsResponse {
+        current: state.environment.clone(),
+        environments: environments.names(),
+    })
+}
+
+/// Longest name accepted for a saved view.
+const MAX_SAVED_VIEW_NAME_LENGTH: usize = 100;
+
+/// A hex encoded history content key to audit right away.
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct AuditRequest {
+    pub content_key: String,
+}
+
+/// An audit requested through the API, queued for the glados-audit workers.
+#[derive(Serialize, Debug, ToSchema)]
+pub struct RequestedAudit {
+    pub task_id: i32,
+    pub content_key: String,
+    pub queued_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    /// The audit recorded for the request, once a worker has performed it.
+    pub audit_id: Option<i32>,
+}
+
+impl RequestedAudit {
+    fn new(task: audit_task::Model, content: content::Model) -> Self {
+        RequestedAudit {
+            task_id: task.id,
+            content_key: hex_encode(content.content_key),
+            queued_at: task.created_at,
+            completed_at: task.completed_at,
+            audit_id: task.audit_id,
+        }
+    }
+}
+
+/// Time since its last heartbeat after which a glados-audit worker is assumed to be gone. The
+/// workers record one every 30 seconds.
+const AUDIT_WORKER_TIMEOUT: TimeDelta = TimeDelta::minutes(2);
+
+/// Audits a history content key right away, ahead of the audits selected by the strategies, to
+/// debug content reported missing by users. A key that is already queued is not queued again.
+///
+/// The audit is performed by a glados-audit worker of the task queue, and the request is
+/// rejected if no worker is running. The response has the id of the queued task right away, and
+/// `/api/audit/{task_id}` has the id of the audit once it is done.
+#[utoipa::path(
+    post,
+    path = "/api/audit/",
+    tag = "audits",
+    request_body = AuditRequest,
+    responses(
+        (status = 202, description = "The audit is queued", body = RequestedAudit),
+        (status = 400, description = "Invalid history content key", body = ErrorBody),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorBody),
+        (
+            status = 503,
+            description = "No glados-audit worker performs queued audits",
+            body = ErrorBody,
+        ),
+    ),
+    security(("admin" = [])),
+)]
+pub async fn request_audit(
+    Extension(state): Extension<Arc<State>>,
+    headers: HeaderMap,
+    Json(request): Json<AuditRequest>,
+) -> Result<(StatusCode, Json<RequestedAudit>), WebError> {
+    authorize_admin(&state, &headers)?;
+    let content_key = HistoryContentKey::try_from_hex(&request.content_key)
+        .map_err(|e| WebError::invalid_parameter("content_key", &request.content_key, e))?;
+    let workers = audit_worker::count_active(
+        Utc::now() - AUDIT_WORKER_TIMEOUT,
+        &state.database_connection,
+    )
+    .await?;
+    if workers == 0 {
+        return Err(WebError::Unavailable(
+            "No glados-audit worker with --task-queue is running to perform the audit".to_owned(),
+        ));
+    }
+    let content = content::get_or_create(
+        SubProtocol::History,
+        &content_key,
+        Utc::now(),
+        ContentSource::Audit,
+        &state.database_connection,
+    )
+    .await?;
+    let task = audit_task::request(content.id, &state.database_connection).await?;
+    info!(
+        content.key = request.content_key,
+        task.id = task.id,
+        "Queued requested audit"
+    );
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(RequestedAudit::new(task, content)),
+    ))
+}
+
+/// Returns the state of an audit requested through the API, with its audit id once done.
+#[utoipa::path(
+    get,
+    path = "/api/audit/{task_id}",
+    tag = "audits",
+    params(
+        (
+            "task_id" = i32, Path,
+            description = "Id of the requested audit, as returned when it was queued",
+        ),
+    ),
+    responses(
+        (status = 200, body = RequestedAudit),
+        (status = 404, description = "Unknown requested audit", body = ErrorBody),
+    ),
+)]
+pub async fn requested_audit(
+    IdParam(task_id): IdParam,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<RequestedAudit>, WebError> {
+    let task = audit_task::Entity::find_by_id(task_id)
+        .find_also_related(content::Entity)
+        .one(&state.database_connection)
+        .await?;
+    match task {
+        Some((task, Some(content))) => Ok(Json(RequestedAudit::new(task, content))),
+        _ => Err(WebError::not_found(format!("Requested audit {task_id}"))),
+    }
+}
+
+/// Most content keys whose latest audits can be looked up in one request.
+const MAX_LATEST_AUDIT_KEYS: usize = 1000;
+
+/// Hex encoded content keys whose latest audits to look up.
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct LatestAuditsRequest {
+    pub keys: Vec<String>,
+}
+
+/// The latest audit of a content key.
+#[derive(Serialize, Debug, ToSchema)]
+pub struct LatestAuditSummary {
+    pub audit_id: i32,
+    pub result: String,
+    pub strategy: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// Number of failed audits since the key was last found.
+    pub consecutive_failures: i32,
+}
+
+/// A requested content key, with its latest audit if it has been audited.
+#[derive(Serialize, Debug, ToSchema)]
+pub struct LatestAudit {
+    pub content_key: String,
+    pub audit: Option<LatestAuditSummary>,
+}
+
+/// Returns the latest audit of each content key given, comma separated, by the `keys`
+/// parameter, so that scripts can check a batch of keys at once.
+#[utoipa::path(
+    get,
+    path = "/api/audits/latest/",
+    tag = "audits",
+    params(
+        ("keys" = String, Query, description = "Content keys, hex encoded and comma separated"),
+        NetworkParam,
+    ),
+    responses(
+        (status = 200, body = [LatestAudit]),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+    ),
+)]
+pub async fn latest_audits(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Vec<LatestAudit>>, WebError> {
+    let keys = http_args
+        .get("keys")
+        .ok_or(WebError::MissingParameter("keys"))?
+        .split(',')
+        .map(|key| key.trim().to_owned())
+        .filter(|key| !key.is_empty())
+        .collect();
+    lookup_latest_audits(keys, get_subprotocol_from_params(&http_args), &state).await
+}
+
+/// Returns the latest audit of each content key in the request body, for batches of keys too
+/// large for a query string.
+#[utoipa::path(
+    post,
+    path = "/api/audits/latest/",
+    tag = "audits",
+    request_body = LatestAuditsRequest,
+    params(
+        NetworkParam,
+    ),
+    responses(
+        (status = 200, body = [LatestAudit]),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+    ),
+)]
+pub async fn latest_audits_for_keys(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+    Json(request): Json<LatestAuditsRequest>,
+) -> Result<Json<Vec<LatestAudit>>, WebError> {
+    lookup_latest_audits(
+        request.keys,
+        get_subprotocol_from_params(&http_args),
+        &state,
+    )
+    .await
+}
+
+/// Looks up the latest audits of hex encoded content keys, in the order the keys were given.
+async fn lookup_latest_audits(
+    keys: Vec<String>,
+    subprotocol: SubProtocol,
+    state: &Arc<State>,
+) -> Result<Json<Vec<LatestAudit>>, WebError> {
+    if keys.len() > MAX_LATEST_AUDIT_KEYS {
+        return Err(WebError::invalid_parameter(
+            "keys",
+            &format!("{} keys", keys.len()),
+            format!("at most {MAX_LATEST_AUDIT_KEYS} keys can be looked up at once"),
+        ));
+    }
+    let raw_keys = keys
+        .iter()
+        .map(|key| parse_hex_param("content key", key))
+        .collect::<Result<Vec<Vec<u8>>, _>>()?;
+
+    let latest: HashMap<Vec<u8>, audit_latest::Model> = audit_latest::get_by_content_keys(
+        raw_keys.clone(),
+        subprotocol,
+        &state.database_connection,
+    )
+    .await?
+    .into_iter()
+    .collect();
+
+    Ok(Json(
+        raw_keys
+            .iter()
+            .map(|raw_key| LatestAudit {
+                content_key: hex_encode(raw_key),
+                audit: latest.get(raw_key).map(|audit| LatestAuditSummary {
+                    audit_id: audit.audit_id,
+                    result: audit.result.as_text(),
+                    strategy: audit.strategy_used.as_ref().map(|s| s.as_text()),
+                    created_at: audit.created_at,
+                    consecutive_failures: audit.consecutive_failures,
+                }),
+            })
+            .collect(),
+    ))
+}
+
+/// A named set of audit dashboard filters to save.
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct SavedViewRequest {
+    pub name: String,
+    #[schema(inline)]
+    pub network: SubProtocol,
+    pub strategy: String,
+    pub content_type: String,
+    pub success: String,
+}
+
+/// Lists the saved audit dashboard views of the network given by the `network` parameter.
+#[utoipa::path(
+    get,
+    path = "/api/saved-views/",
+    tag = "audits",
+    params(
+        NetworkParam,
+    ),
+    responses(
+        (status = 200, body = [saved_view::Model]),
+    ),
+)]
+pub async fn saved_views(
+    params: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Vec<saved_view::Model>>, WebError> {
+    let network = get_subprotocol_from_params(&params);
+    let views = saved_view::get_all(network, &state.database_connection).await?;
+    Ok(Json(views))
+}
+
+/// Saves the filters of the audit dashboard under a name, replacing any view of that name.
+#[utoipa::path(
+    post,
+    path = "/api/saved-views/",
+    tag = "audits",
+    request_body = SavedViewRequest,
+    responses(
+        (status = 200, body = saved_view::Model),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+    ),
+)]
+pub async fn create_saved_view(
+    Extension(state): Extension<Arc<State>>,
+    Json(request): Json<SavedViewRequest>,
+) -> Result<Json<saved_view::Model>, WebError> {
+    let name = request.name.trim().to_owned();
+    if name.is_empty() || name.chars().count() > MAX_SAVED_VIEW_NAME_LENGTH {
+        return Err(WebError::invalid_parameter(
+            "name",
+            &name,
+            format!("empty or longer than {MAX_SAVED_VIEW_NAME_LENGTH} characters"),
+        ));
+    }
+    // Only store filters that `/audits/filter/` would accept.
+    let filters = serde_json::json!({
+        "network": request.network.as_text(),
+        "strategy": request.strategy,
+        "content_type": request.content_type,
+        "success": request.success,
+    });
+    if let Err(e) = serde_json::from_value::<AuditFilters>(filters.clone()) {
+        return Err(WebError::invalid_parameter(
+            "filters",
+            &filters.to_string(),
+            e,
+        ));
+    }
+    let view = saved_view::upsert(
+        name,
+        request.network,
+        request.strategy,
+        request.content_type,
+        request.success,
+        &state.database_connection,
+    )
+    .await?;
+    Ok(Json(view))
+}
+
+/// Deletes a saved audit dashboard view.
+#[utoipa::path(
+    delete,
+    path = "/api/saved-views/{id}",
+    tag = "audits",
+    params(
+        ("id" = i32, Path, description = "Saved view"),
+    ),
+    responses(
+        (status = 204, description = "The view is deleted"),
+        (status = 404, description = "Unknown saved view", body = ErrorBody),
+    ),
+)]
+pub async fn delete_saved_view(
+    IdParam(id): IdParam,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<StatusCode, WebError> {
+    if saved_view::delete(id, &state.database_connection).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(WebError::not_found(format!("Saved view {id}")))
+    }
+}
+
+/// Longest message accepted for the site banner.
+const MAX_BANNER_MESSAGE_LENGTH: usize = 500;
+
+/// A message to show at the top of every page, until it expires if given an expiry.
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct BannerRequest {
+    pub message: String,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Checks that an admin request carries the admin token as a bearer token. Admin routes are
+/// reported as not found when no admin token is configured.
+fn authorize_admin(state: &State, headers: &HeaderMap) -> Result<(), WebError> {
+    let Some(admin_token) = &state.admin_token else {
+        return Err(WebError::not_found("Admin API"));
+    };
+    let token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match token {
+        Some(token) if constant_time_eq(token.as_bytes(), admin_token.as_bytes()) => Ok(()),
+        _ => Err(WebError::Unauthorized),
+    }
+}
+
+/// Compares secrets without returning as soon as a byte differs, so that response times do
+/// not reveal how much of a guess was right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Returns the banner shown at the top of every page, if any.
+#[utoipa::path(
+    get,
+    path = "/api/banner/",
+    tag = "site",
+    responses(
+        (
+            status = 200,
+            description = "The banner, or null if none is shown",
+            body = Option<site_banner::Model>,
+        ),
+    ),
+)]
+pub async fn site_banner(
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Option<site_banner::Model>>, WebError> {
+    Ok(Json(
+        site_banner::get_active(Utc::now(), &state.database_connection).await?,
+    ))
+}
+
+/// Sets the banner shown at the top of every page, like a notice of maintenance that leaves a
+/// gap in the data.
+#[utoipa::path(
+    put,
+    path = "/api/admin/banner/",
+    tag = "site",
+    request_body = BannerRequest,
+    responses(
+        (status = 200, body = site_banner::Model),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorBody),
+    ),
+    security(("admin" = [])),
+)]
+pub async fn set_site_banner(
+    Extension(state): Extension<Arc<State>>,
+    headers: HeaderMap,
+    Json(request): Json<BannerRequest>,
+) -> Result<Json<site_banner::Model>, WebError> {
+    authorize_admin(&state, &headers)?;
+    let message = request.message.trim().to_owned();
+    if message.is_empty() || message.chars().count() > MAX_BANNER_MESSAGE_LENGTH {
+        return Err(WebError::invalid_parameter(
+            "message",
+            &message,
+            format!("must have 1 to {MAX_BANNER_MESSAGE_LENGTH} characters"),
+        ));
+    }
+    if let Some(expires_at) = request.expires_at {
+        if expires_at <= Utc::now() {
+            return Err(WebError::invalid_parameter(
+                "expires_at",
+                &expires_at.to_rfc3339(),
+                "must be in the future",
+            ));
+        }
+    }
+    let banner = site_banner::set(message, request.expires_at, &state.database_connection).await?;
+    info!(
+        banner.id = banner.id,
+        banner.message = banner.message,
+        "Set site banner"
+    );
+    Ok(Json(banner))
+}
+
+/// Stops showing the site banner.
+#[utoipa::path(
+    delete,
+    path = "/api/admin/banner/",
+    tag = "site",
+    responses(
+        (status = 204, description = "The banner is cleared"),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorBody),
+        (status = 404, description = "No banner is shown", body = ErrorBody),
+    ),
+    security(("admin" = [])),
+)]
+pub async fn clear_site_banner(
+    Extension(state): Extension<Arc<State>>,
+    headers: HeaderMap,
+) -> Result<StatusCode, WebError> {
+    authorize_admin(&state, &headers)?;
+    if site_banner::clear(&state.database_connection).await? {
+        info!("Cleared site banner");
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(WebError::not_found("Site banner"))
+    }
+}
+
+#[derive(FromQueryResult, Serialize, Debug)]
+pub struct DeadZoneData {
+    pub data_radius: Vec<u8>,
+    pub raw: String,
+    pub node_id: Vec<u8>,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct DeadZoneResponse {
+    pub sub_protocol: String,
+    /// The census whose radii were checked, the latest one for the content's subprotocol.
+    pub census_id: Option<i32>,
+    /// Number of nodes in the census that were checked.
+    pub nodes_checked: usize,
+    /// ENRs of the nodes whose radius covers the content.
+    pub enrs: Vec<String>,
+}
+
+/// Returns the nodes of the latest census that would accept a content key, by comparing the
+/// content id with each node's advertised radius.
+///
+/// The content key may be a History, State or Beacon key; the census is taken from the
+/// matching subprotocol.
+#[utoipa::path(
+    get,
+    path = "/api/is-content-in-deadzone/{content_key}",
+    tag = "censuses",
+    params(
+        (
+            "content_key" = String, Path,
+            description = "History, State or Beacon content key, hex encoded",
+        ),
+    ),
+    responses(
+        (status = 200, body = DeadZoneResponse),
+        (status = 400, description = "Invalid content key", body = ErrorBody),
+    ),
+)]
+pub async fn is_content_in_deadzone(
+    content_key: HexParam,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<DeadZoneResponse>, WebError> {
+    let (content_id, sub_protocol) =
+        if let Ok(key) = HistoryContentKey::try_from_bytes(&content_key.bytes) {
+            (key.content_id(), SubProtocol::History)
+        } else if let Ok(key) = StateContentKey::try_from_bytes(&content_key.bytes) {
+            (key.content_id(), SubProtocol::State)
+        } else if let Ok(key) = BeaconContentKey::try_from_bytes(&content_key.bytes) {
+            (key.content_id(), SubProtocol::Beacon)
+        } else {
+            return Err(WebError::invalid_parameter(
+                "content key",
+                &content_key.hex,
+                "not a History, State or Beacon content key",
+            ));
+        };
+
+    let latest_census = census::Entity::find()
+        .filter(census::Column::SubNetwork.eq(sub_protocol))
+        .order_by_desc(census::Column::StartedAt)
+        .one(&state.database_connection)
+        .await?;
+    let Some(latest_census) = latest_census else {
+        return Ok(Json(DeadZoneResponse {
+            sub_protocol: sub_protocol.as_text(),
+            census_id: None,
+            nodes_checked: 0,
+            enrs: vec![],
+        }));
+    };
+
+    let builder = state.database_connection.get_database_backend();
+    let mut select_dead_zone_data = Query::select();
+    select_dead_zone_data
+        .expr(Expr::col((
+            census_node::Entity,
+            census_node::Column::DataRadius,
+        )))
+        .expr(Expr::col((node::Entity, node::Column::NodeId)))
+        .expr(Expr::col((record::Entity, record::Column::Raw)))
+        .from(census_node::Entity)
+        .from(node::Entity)
+        .from(record::Entity)
+        .and_where(
+            Expr::col((census_node::Entity, census_node::Column::CensusId)).eq(latest_census.id),
+        )
+        .and_where(
+            Expr::col((census_node::Entity, census_node::Column::RecordId))
+                .eq(Expr::col((record::Entity, record::Column::Id))),
+        )
+        .and_where(
+            Expr::col((record::Entity, record::Column::NodeId))
+                .eq(Expr::col((node::Entity, node::Column::Id))),
+        );
+
+    let dead_zone_data_vec = DeadZoneData::find_by_statement(builder.build(&select_dead_zone_data))
+        .all(&state.database_connection)
+        .await?;
+
+    let nodes_checked = dead_zone_data_vec.len();
+    let mut enrs: Vec<String> = vec![];
+    for dead_zone_data in dead_zone_data_vec {
+        let radius = Distance::from(U256::from_be_slice(&dead_zone_data.data_radius));
+        let node_id = Distance::from(U256::from_be_slice(&dead_zone_data.node_id));
+        if XorMetric::distance(&content_id, &node_id.big_endian()) <= radius {
+            enrs.push(dead_zone_data.raw);
+        }
+    }
+
+    Ok(Json(DeadZoneResponse {
+        sub_protocol: sub_protocol.as_text(),
+        census_id: Some(latest_census.id),
+        nodes_checked,
+        enrs,
+    }))
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct CoverageHistoryEntry {
+    pub census_id: i32,
+    pub started_at: DateTime<Utc>,
+    pub num_samples: i32,
+    /// Average number of nodes whose radius covers a content id, the replication factor.
+    pub mean_nodes_covering: f64,
+    pub p10_nodes_covering: i32,
+    pub median_nodes_covering: i32,
+    pub p90_nodes_covering: i32,
+    /// Percentage of content ids that no node covers, in the deadzone.
+    pub uncovered_percent: f64,
+}
+
+impl CoverageHistoryEntry {
+    /// Summarizes the coverage samples of one census, ordered by number of nodes covering.
+    ///
+    /// There is at least one sample, as censuses are only listed if they were sampled.
+    fn from_samples(samples: &[CensusCoverage]) -> Self {
+        let num_samples: i32 = samples.iter().map(|s| s.num_samples).sum();
+        let percentile = |fraction: f64| {
+            let rank = (fraction * f64::from(num_samples)).ceil().max(1.0) as i32;
+            let mut seen = 0;
+            for sample in samples {
+                seen += sample.num_samples;
+                if seen >= rank {
+                    return sample.nodes_covering;
+                }
+            }
+            0
+        };
+        let covering_total: i64 = samples
+            .iter()
+            .map(|s| i64::from(s.nodes_covering) * i64::from(s.num_samples))
+            .sum();
+        let uncovered: i32 = samples
+            .iter()
+            .filter(|s| s.nodes_covering == 0)
+            .map(|s| s.num_samples)
+            .sum();
+        let (mean_nodes_covering, uncovered_percent) = match num_samples {
+            0 => (0.0, 0.0),
+            n => (
+                covering_total as f64 / f64::from(n),
+                100.0 * f64::from(uncovered) / f64::from(n),
+            ),
+        };
+        CoverageHistoryEntry {
+            census_id: samples[0].census_id,
+            started_at: samples[0].started_at,
+            num_samples,
+            mean_nodes_covering,
+            p10_nodes_covering: percentile(0.1),
+            median_nodes_covering: percentile(0.5),
+            p90_nodes_covering: percentile(0.9),
+            uncovered_percent,
+        }
+    }
+}
+
+/// Returns, for each census over a 30 day window, how many nodes cover randomly sampled content
+/// ids, to chart the replication factor over time.
+#[utoipa::path(
+    get,
+    path = "/api/coverage-history/",
+    tag = "censuses",
+    params(
+        NetworkParam,
+        (
+            "months-ago" = Option<i32>, Query,
+            description = "30 day windows to shift the window back by, 0 by default",
+        ),
+        ReportingWindowParams,
+    ),
+    responses(
+        (status = 200, body = [CoverageHistoryEntry]),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+    ),
+)]
+pub async fn coverage_history(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Vec<CoverageHistoryEntry>>, WebError> {
+    let months_ago: i32 = match http_args.get("months-ago") {
+        None => 0,
+        Some(months_ago) => months_ago.parse::<i32>().unwrap_or(0),
+    };
+    let subprotocol = get_subprotocol_from_params(&http_args);
+    let (start, end) =
+        get_reporting_window(&http_args, 30, months_ago, &state.database_connection).await?;
+    let samples =
+        coverage_sample::get_history(subprotocol, start, end, &state.database_connection).await?;
+    Ok(Json(
+        samples
+            .chunk_by(|a, b| a.census_id == b.census_id)
+            .map(CoverageHistoryEntry::from_samples)
+            .collect(),
+    ))
+}
+
+/// Percentage of new content found at each age after it became available, for content first
+/// available in the reporting window.
+#[utoipa::path(
+    get,
+    path = "/api/propagation-curve/",
+    tag = "stats",
+    params(
+        (
+            "weeks-ago" = Option<i32>, Query,
+            description = "Week windows to shift the window back by, 0 by default",
+        ),
+        ReportingWindowParams,
+    ),
+    responses(
+        (status = 200, body = [propagation_audit::PropagationPoint]),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+    ),
+)]
+pub async fn propagation_curve(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Vec<PropagationPoint>>, WebError> {
+    let weeks_ago: i32 = match http_args.get("weeks-ago") {
+        None => 0,
+        Some(weeks_ago) => weeks_ago.parse::<i32>().unwrap_or(0),
+    };
+    let (start, end) =
+        get_reporting_window(&http_args, 7, weeks_ago, &state.database_connection).await?;
+    let curve = propagation_audit::get_curve(start, end, &state.database_connection).await?;
+    Ok(Json(curve))
+}
+
+/// Returns the audit stats recorded in the reporting window.
+#[utoipa::path(
+    get,
+    path = "/api/stat-history/",
+    tag = "stats",
+    params(
+        (
+            "weeks-ago" = Option<i32>, Query,
+            description = "Week windows to shift the window back by, 0 by default",
+        ),
+        ReportingWindowParams,
+    ),
+    responses(
+        (status = 200, body = [audit_stats::Model]),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+    ),
+)]
+pub async fn get_audit_stats_handler(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Vec<audit_stats::Model>>, WebError> {
+    let weeks_ago: i32 = match http_args.get("weeks-ago") {
+        None => 0,
+        Some(days_ago) => days_ago.parse::<i32>().unwrap_or(0),
+    };
+    let (start, end) =
+        get_reporting_window(&http_args, 7, weeks_ago, &state.database_connection).await?;
+    let stats = audit_stats::get_stats_between(start, end, &state.database_connection).await?;
+
+    Ok(Json(stats))
+}
+
+/// Beacon audit success rates recorded at one time.
+#[derive(Serialize, Debug, ToSchema)]
+pub struct BeaconStatsRecord {
+    pub timestamp: DateTime<Utc>,
+    #[serde(flatten)]
+    #[schema(inline)]
+    pub success_rates: BeaconSuccessRates,
+}
+
+/// Returns the beacon audit success rates by type of light client content recorded in the
+/// reporting window. Records from before beacon audits were measured are left out.
+#[utoipa::path(
+    get,
+    path = "/api/stats-beacon/",
+    tag = "stats",
+    params(
+        (
+            "weeks-ago" = Option<i32>, Query,
+            description = "Week windows to shift the window back by, 0 by default",
+        ),
+        ReportingWindowParams,
+    ),
+    responses(
+        (status = 200, body = [BeaconStatsRecord]),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+    ),
+)]
+pub async fn beacon_audit_stats(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Vec<BeaconStatsRecord>>, WebError> {
+    let weeks_ago: i32 = match http_args.get("weeks-ago") {
+        None => 0,
+        Some(weeks_ago) => weeks_ago.parse::<i32>().unwrap_or(0),
+    };
+    let (start, end) =
+        get_reporting_window(&http_args, 7, weeks_ago, &state.database_connection).await?;
+    let stats = audit_stats::get_stats_between(start, end, &state.database_connection).await?;
+    Ok(Json(
+        stats
+            .iter()
+            .filter_map(|record| {
+                Some(BeaconStatsRecord {
+                    timestamp: record.timestamp,
+                    success_rates: record.beacon_success_rates()?,
+                })
+            })
+            .collect(),
+    ))
+}
+
+/// Returns the success rates of each auditing client recorded in the reporting window, to
+/// compare clients in multi-client deployments.
+#[utoipa::path(
+    get,
+    path = "/api/stats-by-client/",
+    tag = "stats",
+    params(
+        NetworkParam,
+        (
+            "weeks-ago" = Option<i32>, Query,
+            description = "Week windows to shift the window back by, 0 by default",
+        ),
+        ReportingWindowParams,
+    ),
+    responses(
+        (status = 200, body = [audit_stats_client::Model]),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+    ),
+)]
+pub async fn audit_stats_by_client(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Vec<audit_stats_client::Model>>, WebError> {
+    let weeks_ago: i32 = match http_args.get("weeks-ago") {
+        None => 0,
+        Some(weeks_ago) => weeks_ago.parse::<i32>().unwrap_or(0),
+    };
+    let subprotocol = get_subprotocol_from_params(&http_args);
+    let (start, end) =
+        get_reporting_window(&http_args, 7, weeks_ago, &state.database_connection).await?;
+    let stats =
+        audit_stats_client::get_stats_between(subprotocol, start, end, &state.database_connection)
+            .await?;
+    Ok(Json(stats))
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct BlockAuditStats {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub total: i64,
+    pub complete: i64,
+    pub partial: i64,
+    pub missing: i64,
+    /// Percentage of audited blocks whose header, body and receipts were all retrieved.
+    pub complete_percent: f64,
+}
+
+/// Returns how many blocks audited by the Block strategy were fully, partially or not at
+/// all retrievable, over a one day window.
+#[utoipa::path(
+    get,
+    path = "/api/block-audit-stats/",
+    tag = "stats",
+    params(
+        (
+            "days-ago" = Option<i32>, Query,
+            description = "Day windows to shift the window back by, 0 by default",
+        ),
+        ReportingWindowParams,
+    ),
+    responses(
+        (status = 200, body = BlockAuditStats),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+    ),
+)]
+pub async fn block_audit_stats(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<BlockAuditStats>, WebError> {
+    let days_ago: i32 = match http_args.get("days-ago") {
+        None => 0,
+        Some(days_ago) => days_ago.parse::<i32>().unwrap_or(0),
+    };
+    let (start, end) =
+        get_reporting_window(&http_args, 1, days_ago, &state.database_connection).await?;
+    let counts = block_audit::verdict_counts(start, end, &state.database_connection).await?;
+    Ok(Json(BlockAuditStats {
+        start,
+        end,
+        total: counts.total(),
+        complete: counts.complete,
+        partial: counts.partial,
+        missing: counts.missing,
+        complete_percent: counts.complete_percent(),
+    }))
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct AuditCoverageEntry {
+    pub day: DateTime<Utc>,
+    pub strategy: String,
+    pub num_eligible: i64,
+    pub num_audited: i64,
+    pub coverage_percent: f64,
+}
+
+/// Returns, for each strategy and day, the share of eligible keys that were audited at least
+/// once, over a 30 day window.
+#[utoipa::path(
+    get,
+    path = "/api/audit-coverage/",
+    tag = "stats",
+    params(
+        (
+            "months-ago" = Option<i32>, Query,
+            description = "30 day windows to shift the window back by, 0 by default",
+        ),
+        ReportingWindowParams,
+    ),
+    responses(
+        (status = 200, body = [AuditCoverageEntry]),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+    ),
+)]
+pub async fn audit_coverage_stats(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Vec<AuditCoverageEntry>>, WebError> {
+    let months_ago: i32 = match http_args.get("months-ago") {
+        None => 0,
+        Some(months_ago) => months_ago.parse::<i32>().unwrap_or(0),
+    };
+    let (start, end) =
+        get_reporting_window(&http_args, 30, months_ago, &state.database_connection).await?;
+    let coverage = audit_coverage::get_between(start, end, &state.database_connection).await?;
+    Ok(Json(
+        coverage
+            .into_iter()
+            .map(|c| AuditCoverageEntry {
+                day: c.day,
+                strategy: c.strategy.as_text(),
+                num_eligible: c.num_eligible,
+                num_audited: c.num_audited,
+                coverage_percent: c.coverage_percent(),
+            })
+            .collect(),
+    ))
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct SelectionLagEntry {
+    pub strategy: String,
+    pub num_audits: i64,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: i32,
+}
+
+/// Returns, for each strategy, percentiles of the time audits waited between being selected and
+/// being started, over a 1 day window.
+#[utoipa::path(
+    get,
+    path = "/api/selection-lag/",
+    tag = "stats",
+    params(
+        (
+            "days-ago" = Option<i32>, Query,
+            description = "Day windows to shift the window back by, 0 by default",
+        ),
+        ReportingWindowParams,
+    ),
+    responses(
+        (status = 200, body = [SelectionLagEntry]),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+    ),
+)]
+pub async fn selection_lag(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Vec<SelectionLagEntry>>, WebError> {
+    let days_ago: i32 = match http_args.get("days-ago") {
+        None => 0,
+        Some(days_ago) => days_ago.parse::<i32>().unwrap_or(0),
+    };
+    let (start, end) =
+        get_reporting_window(&http_args, 1, days_ago, &state.database_connection).await?;
+    let percentiles =
+        content_audit::get_selection_lag_percentiles(start, end, &state.database_connection)
+            .await?;
+    Ok(Json(
+        percentiles
+            .into_iter()
+            .map(|p| SelectionLagEntry {
+                strategy: p
+                    .strategy_used
+                    .map(|strategy| strategy.as_text())
+                    .unwrap_or_else(|| "Unknown".to_string()),
+                num_audits: p.num_audits,
+                p50_ms: p.p50_ms,
+                p90_ms: p.p90_ms,
+                p99_ms: p.p99_ms,
+                max_ms: p.max_ms,
+            })
+            .collect(),
+    ))
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct LatencyEntry {
+    pub name: String,
+    pub num_audits: i64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct AuditLatency {
+    pub by_content_type: Vec<LatencyEntry>,
+    pub by_client: Vec<LatencyEntry>,
+}
+
+/// Returns percentiles of how long Portal nodes took to answer audits over a 1 day window, by
+/// type of content and by auditing client.
+#[utoipa::path(
+    get,
+    path = "/api/audit-latency/",
+    tag = "stats",
+    params(
+        NetworkParam,
+        (
+            "days-ago" = Option<i32>, Query,
+            description = "Day windows to shift the window back by, 0 by default",
+        ),
+        ReportingWindowParams,
+    ),
+    responses(
+        (status = 200, body = AuditLatency),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+    ),
+)]
+pub async fn audit_latency(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<AuditLatency>, WebError> {
+    let days_ago: i32 = match http_args.get("days-ago") {
+        None => 0,
+        Some(days_ago) => days_ago.parse::<i32>().unwrap_or(0),
+    };
+    let subprotocol = get_subprotocol_from_params(&http_args);
+    let (start, end) =
+        get_reporting_window(&http_args, 1, days_ago, &state.database_connection).await?;
+    let percentiles =
+        content_audit::get_latency_percentiles(subprotocol, start, end, &state.database_connection)
+            .await?;
+
+    let mut latency = AuditLatency {
+        by_content_type: vec![],
+        by_client: vec![],
+    };
+    for p in percentiles {
+        let (name, entries) = match (p.selector, p.client) {
+            (Some(selector), _) => (
+                match subprotocol {
+                    SubProtocol::History => history_content_type(selector),
+                    _ => format!("{selector:#04x}"),
+                },
+                &mut latency.by_content_type,
+            ),
+            (None, Some(client)) => (client, &mut latency.by_client),
+            (None, None) => continue,
+        };
+        entries.push(LatencyEntry {
+            name,
+            num_audits: p.num_audits,
+            p50_ms: p.p50_ms,
+            p95_ms: p.p95_ms,
+            p99_ms: p.p99_ms,
+        });
+    }
+    Ok(Json(latency))
+}
+
+/// Returns the attempts to reseed failed content over a one week window by outcome, with how
+/// many of the reseeded keys were found by a later audit.
+#[utoipa::path(
+    get,
+    path = "/api/reseed-summary/",
+    tag = "stats",
+    params(
+        (
+            "weeks-ago" = Option<i32>, Query,
+            description = "Week windows to shift the window back by, 0 by default",
+        ),
+        ReportingWindowParams,
+    ),
+    responses(
+        (status = 200, body = [reseed_attempt::ReseedSummary]),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+    ),
+)]
+pub async fn reseed_summary(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Vec<ReseedSummary>>, WebError> {
+    let weeks_ago: i32 = match http_args.get("weeks-ago") {
+        None => 0,
+        Some(weeks_ago) => weeks_ago.parse::<i32>().unwrap_or(0),
+    };
+    let (start, end) =
+        get_reporting_window(&http_args, 7, weeks_ago, &state.database_connection).await?;
+    let summary = reseed_attempt::get_summary(start, end, &state.database_connection).await?;
+    Ok(Json(summary))
+}
+
+/// Returns, for each month, percentiles of how long the content that became available that
+/// month took to be first found by an audit, from the report filled in by
+/// `glados-audit backfill-first-success`.
+#[utoipa::path(
+    get,
+    path = "/api/first-success-latency/",
+    tag = "stats",
+    params(
+        NetworkParam,
+    ),
+    responses(
+        (status = 200, body = [content_first_success::MonthlyFirstSuccess]),
+    ),
+)]
+pub async fn first_success_latency(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Vec<MonthlyFirstSuccess>>, WebError> {
+    let subprotocol = get_subprotocol_from_params(&http_args);
+    let months =
+        content_first_success::get_monthly_percentiles(subprotocol, &state.database_connection)
+            .await?;
+    Ok(Json(months))
+}
+
+/// Success rates of one group of audits in the two compared windows.
+#[derive(Serialize, Debug, ToSchema)]
+pub struct AuditComparisonRow {
+    /// Type of content, or version of the auditing client.
+    pub label: String,
+    pub before_success: i64,
+    pub before_completed: i64,
+    pub before_success_percent: Option<f64>,
+    pub after_success: i64,
+    pub after_completed: i64,
+    pub after_success_percent: Option<f64>,
+    /// Change of the success rate, in percentage points.
+    pub delta_percent: Option<f64>,
+    pub z_score: Option<f64>,
+    /// Whether the change is significant at a 95% confidence level.
+    pub significant: bool,
+}
+
+impl AuditComparisonRow {
+    fn new(label: String, change: content_audit::SuccessRateChange) -> Self {
+        AuditComparisonRow {
+            label,
+            before_success: change.before_success,
+            before_completed: change.before_completed,
+            before_success_percent: change.before_percent(),
+            after_success: change.after_success,
+            after_completed: change.after_completed,
+            after_success_percent: change.after_percent(),
+            delta_percent: change.delta_percent(),
+            z_score: change.z_score(),
+            significant: change.is_significant(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct AuditComparison {
+    pub before_start: DateTime<Utc>,
+    pub before_end: DateTime<Utc>,
+    pub after_start: DateTime<Utc>,
+    pub after_end: DateTime<Utc>,
+    pub by_content_type: Vec<AuditComparisonRow>,
+    pub by_client: Vec<AuditComparisonRow>,
+}
+
+/// Compares audit success rates by type of content and by auditing client between two time
+/// windows, such as before and after a client release.
+///
+/// The windows are set with the `before-start`, `before-end`, `after-start` and `after-end`
+/// parameters (RFC 3339). Each change is tested with a two-proportion z-test, and flagged as
+/// significant at a 95% confidence level.
+#[utoipa::path(
+    get,
+    path = "/api/audit-comparison/",
+    tag = "stats",
+    params(
+        ("before-start" = DateTime<Utc>, Query, description = "Start of the window before"),
+        ("before-end" = DateTime<Utc>, Query, description = "End of the window before"),
+        ("after-start" = DateTime<Utc>, Query, description = "Start of the window after"),
+        ("after-end" = DateTime<Utc>, Query, description = "End of the window after"),
+        NetworkParam,
+    ),
+    responses(
+        (status = 200, body = AuditComparison),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+    ),
+)]
+pub async fn audit_comparison(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<AuditComparison>, WebError> {
+    let parse = |name: &'static str| {
+        parse_required_param::<DateTime<chrono::FixedOffset>>(&http_args, name)
+            .map(|time| time.with_timezone(&Utc))
+    };
+    let (before_start, before_end) = (parse("before-start")?, parse("before-end")?);
+    let (after_start, after_end) = (parse("after-start")?, parse("after-end")?);
+    if before_start >= before_end {
+        return Err(WebError::invalid_parameter(
+            "before-end",
+            &before_end.to_rfc3339(),
+            "not after before-start",
+        ));
+    }
+    if after_start >= after_end {
+        return Err(WebError::invalid_parameter(
+            "after-end",
+            &after_end.to_rfc3339(),
+            "not after after-start",
+        ));
+    }
+    let subprotocol = get_subprotocol_from_params(&http_args);
+
+    let (before, after) = tokio::try_join!(
+        content_audit::get_results_by_content_type_and_client(
+            subprotocol,
+            before_start,
+            before_end,
+            &state.database_connection,
+        ),
+        content_audit::get_results_by_content_type_and_client(
+            subprotocol,
+            after_start,
+            after_end,
+            &state.database_connection,
+        ),
+    )?;
+
+    let mut by_content_type: BTreeMap<String, content_audit::SuccessRateChange> = BTreeMap::new();
+    let mut by_client: BTreeMap<String, content_audit::SuccessRateChange> = BTreeMap::new();
+    for (results, is_after) in [(before, false), (after, true)] {
+        for result in results {
+            let content_type = match subprotocol {
+                SubProtocol::History => history_content_type(result.selector),
+                _ => format!("{:#04x}", result.selector),
+            };
+            for change in [
+                by_content_type.entry(content_type).or_default(),
+                by_client.entry(result.client.clone()).or_default(),
+            ] {
+                if is_after {
+                    change.after_success += result.success;
+                    change.after_completed += result.success + result.failure;
+                } else {
+                    change.before_success += result.success;
+                    change.before_completed += result.success + result.failure;
+                }
+            }
+        }
+    }
+
+    Ok(Json(AuditComparison {
+        before_start,
+        before_end,
+        after_start,
+        after_end,
+        by_content_type: by_content_type
+            .into_iter()
+            .map(|(label, change)| AuditComparisonRow::new(label, change))
+            .collect(),
+        by_client: by_client
+            .into_iter()
+            .map(|(label, change)| AuditComparisonRow::new(label, change))
+            .collect(),
+    }))
+}
+
+/// Default number of weeks of audits in the hour of week matrix.
+const DEFAULT_HOUR_OF_WEEK_WEEKS: i32 = 4;
+
+/// Largest number of weeks of audits in the hour of week matrix.
+const MAX_HOUR_OF_WEEK_WEEKS: i32 = 52;
+
+/// Audit success rates by day of the week and hour of the day.
+#[derive(Serialize, Debug, ToSchema)]
+pub struct HourOfWeekSuccess {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub timezone: String,
+    /// Days of the week of the rows, from Monday.
+    pub days: [&'static str; 7],
+    /// Completed audits, by day of the week then hour of the day.
+    pub audits: Vec<Vec<i64>>,
+    /// Percentage of completed audits that succeeded, by day of the week then hour of the day.
+    /// Empty for hours without completed audits.
+    pub success_percent: Vec<Vec<Option<f64>>>,
+}
+
+/// Returns a matrix of audit success rates by day of the week and hour of the day, to reveal
+/// periodic patterns like nightly jobs degrading the network.
+///
+/// Covers the last `weeks` weeks (4 by default), in the `tz` timezone (UTC by default).
+#[utoipa::path(
+    get,
+    path = "/api/success-by-hour-of-week/",
+    tag = "stats",
+    params(
+        NetworkParam,
+        ("weeks" = Option<i32>, Query, description = "Weeks to cover, 4 by default and at most 52"),
+        ReportingWindowParams,
+    ),
+    responses(
+        (status = 200, body = HourOfWeekSuccess),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+    ),
+)]
+pub async fn success_by_hour_of_week(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<HourOfWeekSuccess>, WebError> {
+    let weeks = match http_args.get("weeks") {
+        None => DEFAULT_HOUR_OF_WEEK_WEEKS,
+        Some(weeks) => weeks
+            .parse::<i32>()
+            .ok()
+            .filter(|weeks| (1..=MAX_HOUR_OF_WEEK_WEEKS).contains(weeks))
+            .ok_or_else(|| {
+                WebError::invalid_parameter(
+                    "weeks",
+                    weeks,
+                    format!("not between 1 and {MAX_HOUR_OF_WEEK_WEEKS}"),
+                )
+            })?,
+    };
+    let subprotocol = get_subprotocol_from_params(&http_args);
+    let timezone = http_args
+        .get("tz")
+        .cloned()
+        .unwrap_or_else(|| "UTC".to_owned());
+    let (start, end) =
+        get_reporting_window(&http_args, 7 * weeks, 0, &state.database_connection).await?;
+    let results = content_audit::get_results_by_hour_of_week(
+        subprotocol,
+        start,
+        end,
+        &timezone,
+        &state.database_connection,
+    )
+    .await?;
+
+    let mut audits = vec![vec![0; 24]; 7];
+    let mut success_percent = vec![vec![None; 24]; 7];
+    for result in results {
+        let (day, hour) = ((result.day_of_week - 1) as usize, result.hour as usize);
+        let completed = result.success + result.failure;
+        audits[day][hour] = completed;
+        success_percent[day][hour] = Some(100.0 * result.success as f64 / completed as f64);
+    }
+    Ok(Json(HourOfWeekSuccess {
+        start,
+        end,
+        timezone,
+        days: [
+            "Monday",
+            "Tuesday",
+            "Wednesday",
+            "Thursday",
+            "Friday",
+            "Saturday",
+            "Sunday",
+        ],
+        audits,
+        success_percent,
+    }))
+}
+
+/// Lists when each version of each client was first and last found by a census, to correlate
+/// network regressions with client releases. Narrowed down to one client with `client`.
+#[utoipa::path(
+    get,
+    path = "/api/client-versions/",
+    tag = "nodes",
+    params(
+        ("client" = Option<String>, Query, description = "Client name, like `trin`"),
+    ),
+    responses(
+        (status = 200, body = [client_version_sighting::Model]),
+    ),
+)]
+pub async fn client_version_timeline(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Vec<client_version_sighting::Model>>, WebError> {
+    let client = http_args.get("client").map(|client| client.to_lowercase());
+    let sightings =
+        client_version_sighting::get_timeline(client.as_deref(), &state.database_connection)
+            .await?;
+    Ok(Json(sightings))
+}
+
+/// Returns, for each client and day, how its nodes answered census liveness checks, over a 30
+/// day window.
+#[utoipa::path(
+    get,
+    path = "/api/client-compliance/",
+    tag = "censuses",
+    params(
+        NetworkParam,
+        (
+            "months-ago" = Option<i32>, Query,
+            description = "30 day windows to shift the window back by, 0 by default",
+        ),
+        ReportingWindowParams,
+    ),
+    responses(
+        (status = 200, body = [census_client_stats::ClientComplianceDay]),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+    ),
+)]
+pub async fn client_compliance(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Vec<ClientComplianceDay>>, WebError> {
+    let months_ago: i32 = match http_args.get("months-ago") {
+        None => 0,
+        Some(months_ago) => months_ago.parse::<i32>().unwrap_or(0),
+    };
+    let subprotocol = get_subprotocol_from_params(&http_args);
+    let (start, end) =
+        get_reporting_window(&http_args, 30, months_ago, &state.database_connection).await?;
+    let compliance = census_client_stats::get_daily_compliance(
+        start,
+        end,
+        subprotocol,
+        &state.database_connection,
+    )
+    .await?;
+    Ok(Json(compliance))
+}
+
+/// Number of keys in each page of failed keys.
+const FAILED_KEYS_PAGE_SIZE: u32 = 1000;
+
+/// Returns a page of the keys that have failed since they were last found, and were last audited
+/// within the last 24 hours, or since `failed-after`.
+///
+/// Keys can be narrowed down with `strategy`, `content-type` (like `block_body`, or the first
+/// byte of the key like `0x01`), `block-start` and `block-end` (both included),
+/// `available-after` and `available-before`, and `min-failures` in a row. They are ordered with
+/// `sort`: `latest` (the default), `oldest`, `most-failures`, `block` or `first-available`.
+/// Times are RFC 3339.
+#[utoipa::path(
+    get,
+    path = "/api/failed-keys/",
+    tag = "audits",
+    params(
+        NetworkParam,
+        IncludeArchivedParam,
+        ("strategy" = Option<String>, Query, description = "Selection strategy of the audits"),
+        (
+            "content-type" = Option<String>, Query,
+            description = "Type of content, like `block_body`, or the first byte of the key, like `0x01`",
+        ),
+        ("block-start" = Option<i32>, Query, description = "First block of the content"),
+        ("block-end" = Option<i32>, Query, description = "Last block of the content, included"),
+        (
+            "available-after" = Option<DateTime<Utc>>, Query,
+            description = "Content first available after this time",
+        ),
+        (
+            "available-before" = Option<DateTime<Utc>>, Query,
+            description = "Content first available before this time",
+        ),
+        (
+            "failed-after" = Option<DateTime<Utc>>, Query,
+            description = "Latest audit failed after this time, 24 hours ago by default",
+        ),
+        ("min-failures" = Option<i32>, Query, description = "Failed audits in a row"),
+        (
+            "sort" = Option<String>, Query,
+            description = "`latest` (the default), `oldest`, `most-failures`, `block` or `first-available`",
+        ),
+        ("page" = Option<u32>, Query, description = "Page of 1000 keys, from 1"),
+    ),
+    responses(
+        (status = 200, body = [String]),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+    ),
+)]
+pub async fn get_failed_keys_handler(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Vec<String>>, WebError> {
+    let subprotocol = get_subprotocol_from_params(&http_args);
+    let bad_request = |name: &'static str, value: &str| {
+        WebError::invalid_parameter(name, value, "invalid failed keys filter")
+    };
+    let parse_time = |name: &'static str| {
+        http_args
+            .get(name)
+            .map(|time| {
+                DateTime::parse_from_rfc3339(time)
+                    .map(|time| time.with_timezone(&Utc))
+                    .map_err(|_| bad_request(name, time))
+            })
+            .transpose()
+    };
+    let parse_number = |name: &'static str| {
+        http_args
+            .get(name)
+            .map(|number| number.parse::<i32>().map_err(|_| bad_request(name, number)))
+            .transpose()
+    };
+
+    let strategy = http_args
+        .get("strategy")
+        .map(|strategy| {
+            let parsed = match subprotocol {
+                SubProtocol::History => strategy.clone().try_into().map(SelectionStrategy::History),
+                SubProtocol::State => strategy.clone().try_into().map(SelectionStrategy::State),
+                SubProtocol::Beacon => strategy.clone().try_into().map(SelectionStrategy::Beacon),
+            };
+            parsed.map_err(|_| bad_request("strategy", strategy))
+        })
+        .transpose()?;
+    let selector = http_args
+        .get("content-type")
+        .map(|content_type| {
+            parse_content_type(content_type)
+                .ok_or_else(|| bad_request("content-type", content_type))
+        })
+        .transpose()?;
+    let order = match http_args.get("sort") {
+        None => FailedKeysOrder::default(),
+        Some(sort) => {
+            FailedKeysOrder::try_from(sort.as_str()).map_err(|_| bad_request("sort", sort))?
+        }
+    };
+    let page: u32 = match http_args.get("page") {
+        None => 1,
+        Some(page) => page.parse::<u32>().unwrap_or(1),
+    };
+    let filter = FailedKeysFilter {
+        sub_protocol: subprotocol,
+        strategy,
+        selector,
+        block_start: parse_number("block-start")?,
+        block_end: parse_number("block-end")?,
+        available_after: parse_time("available-after")?,
+        available_before: parse_time("available-before")?,
+        failed_after: Some(
+            parse_time("failed-after")?.unwrap_or_else(|| Utc::now() - TimeDelta::hours(24)),
+        ),
+        min_consecutive_failures: parse_number("min-failures")?.unwrap_or(0),
+        include_archived: get_include_archived_from_params(&http_args),
+    };
+
+    let failed_keys = audit_latest::get_failed_keys(
+        &filter,
+        order,
+        page,
+        FAILED_KEYS_PAGE_SIZE,
+        &state.database_connection,
+    )
+    .await?;
+
+    Ok(Json(failed_keys))
+}
+
+/// The first byte of content keys of a type, from its name like `block_body` or the byte itself
+/// like `0x01`.
+fn parse_content_type(content_type: &str) -> Option<i32> {
+    match content_type {
+        "block_header_by_hash" => Some(0x00),
+        "block_body" => Some(0x01),
+        "receipts" => Some(0x02),
+        "block_header_by_number" => Some(0x03),
+        _ => u8::from_str_radix(content_type.strip_prefix("0x")?, 16)
+            .ok()
+            .map(i32::from),
+    }
+}
+
+/// Default number of blocks in each range of failed keys.
+const DEFAULT_FAILED_KEYS_RANGE_SIZE: i32 = 100_000;
+
+/// Number of history keys of one type, within a range of blocks, that have failed since they
+/// were last found.
+#[derive(Serialize, Debug, ToSchema)]
+pub struct FailedKeysBlockRange {
+    pub start_block: i32,
+    /// Last block of the range, inclusive.
+    pub end_block: i32,
+    pub content_type: String,
+    pub failed_keys: i64,
+}
+
+/// Counts the history keys that have failed since they were last found by range of blocks and
+/// type of content, to plan backfills of specific ranges.
+///
+/// Takes an optional `range-size` parameter, the number of blocks in each range.
+#[utoipa::path(
+    get,
+    path = "/api/failed-keys-by-block-range/",
+    tag = "audits",
+    params(
+        IncludeArchivedParam,
+        (
+            "range-size" = Option<i32>, Query,
+            description = "Blocks in each range, 100000 by default",
+        ),
+    ),
+    responses(
+        (status = 200, body = [FailedKeysBlockRange]),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+    ),
+)]
+pub async fn failed_keys_by_block_range(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Vec<FailedKeysBlockRange>>, WebError> {
+    let range_size = match http_args.get("range-size") {
+        None => DEFAULT_FAILED_KEYS_RANGE_SIZE,
+        Some(size) => size
+            .parse::<i32>()
+            .ok()
+            .filter(|size| *size > 0)
+            .ok_or_else(|| WebError::invalid_parameter("range-size", size, "not positive"))?,
+    };
+    let ranges = audit_latest::get_failed_by_block_range(
+        range_size,
+        get_include_archived_from_params(&http_args),
+        &state.database_connection,
+    )
+    .await?;
+    Ok(Json(
+        ranges
+            .into_iter()
+            .map(|range| FailedKeysBlockRange {
+                start_block: range.range_start,
+                end_block: range.range_start.saturating_add(range_size - 1),
+                content_type: history_content_type(range.selector),
+                failed_keys: range.num_failed,
+            })
+            .collect(),
+    ))
+}
+
+/// Name of a type of history content, from the first byte of its keys.
+fn history_content_type(selector: i32) -> String {
+    match selector {
+        0x00 => "block_header_by_hash".to_owned(),
+        0x01 => "block_body".to_owned(),
+        0x02 => "receipts".to_owned(),
+        0x03 => "block_header_by_number".to_owned(),
+        _ => format!("{selector:#04x}"),
+    }
+}
+
+#[derive(FromQueryResult, Debug)]
+pub struct ClientTransferAttempts {
+    /// First character of the ENR client string, if the node's ENR advertised a client.
+    pub client_code: Option<i32>,
+    pub serve_attempts: i64,
+    pub failures: i64,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct ClientTransferFailureRate {
+    pub client_name: String,
+    pub serve_attempts: i64,
+    pub failures: i64,
+    pub failure_rate: f64,
+}
+
+/// The client name of a client code selected with [client_info::client_code_sql].
+fn client_name_from_code(client_code: Option<i32>) -> &'static str {
+    client_code
+        .and_then(|code| u8::try_from(code).ok())
+        .and_then(client_info::client_from_code)
+        .unwrap_or("unknown")
+}
+
+/// Returns transfer failure rates per client of the serving node over a one week window.
+///
+/// Each node that a trace shows content being received from, or that failed a transfer,
+/// counts as one serve attempt. Normalizing failures by attempts avoids penalizing
+/// clients that simply serve more content.
+#[utoipa::path(
+    get,
+    path = "/api/transfer-failure-rates/",
+    tag = "stats",
+    params(
+        NetworkParam,
+        (
+            "weeks-ago" = Option<i32>, Query,
+            description = "Week windows to shift the window back by, 0 by default",
+        ),
+        ReportingWindowParams,
+    ),
+    responses(
+        (status = 200, body = [ClientTransferFailureRate]),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+    ),
+)]
+pub async fn transfer_failure_rates(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Vec<ClientTransferFailureRate>>, WebError> {
+    let weeks_ago: i32 = match http_args.get("weeks-ago") {
+        None => 0,
+        Some(weeks_ago) => weeks_ago.parse::<i32>().unwrap_or(0),
+    };
+    let subprotocol = get_subprotocol_from_params(&http_args);
+    let (start, end) =
+        get_reporting_window(&http_args, 7, weeks_ago, &state.database_connection).await?;
+
+    let attempts = ClientTransferAttempts::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        &format!(
+            "
+        WITH traces AS (
+            SELECT content_audit.trace::jsonb AS trace
+            FROM content_audit
+            JOIN content ON content.id = content_audit.content_key
+            WHERE content_audit.created_at >= $1
+                AND content_audit.created_at < $2
+                AND content.protocol_id = $3
+                AND content_audit.trace <> ''
+        ),
+        attempts AS (
+            SELECT failure.key AS node_id, 1 AS failed
+            FROM traces, jsonb_each(traces.trace->'failures') AS failure
+            UNION ALL
+            SELECT traces.trace->>'receivedFrom' AS node_id, 0 AS failed
+            FROM traces
+            WHERE traces.trace->>'receivedFrom' IS NOT NULL
+                AND traces.trace->>'receivedFrom' <> traces.trace->>'origin'
+        ),
+        node_clients AS (
+            SELECT DISTINCT ON (node.id) node.node_id, key_value.value AS client
+            FROM node
+            JOIN record ON record.node_id = node.id
+            JOIN key_value ON key_value.record_id = record.id
+            WHERE convert_from(key_value.key, 'UTF8') = 'c'
+            ORDER BY node.id, record.sequence_number DESC
+        )
+        SELECT
+            {client_code} AS client_code,
+            COUNT(*) AS serve_attempts,
+            SUM(attempts.failed) AS failures
+        FROM attempts
+        LEFT JOIN node_clients
+            ON node_clients.node_id = decode(substr(attempts.node_id, 3), 'hex')
+        GROUP BY client_code
+        ",
+            client_code = client_info::client_code_sql("node_clients.client"),
+        ),
+        vec![start.into(), end.into(), subprotocol.into()],
+    ))
+    .all(&state.database_connection)
+    .await?;
+
+    let mut by_client: HashMap<&'static str, (i64, i64)> = HashMap::new();
+    for client_attempts in attempts {
+        let entry = by_client
+            .entry(client_name_from_code(client_attempts.client_code))
+            .or_default();
+        entry.0 += client_attempts.serve_attempts;
+        entry.1 += client_attempts.failures;
+    }
+
+    let mut rates: Vec<ClientTransferFailureRate> = by_client
+        .into_iter()
+        .map(
+            |(client_name, (serve_attempts, failures))| ClientTransferFailureRate {
+                client_name: client_name.to_string(),
+                serve_attempts,
+                failures,
+                failure_rate: match serve_attempts {
+                    0 => 0.0,
+                    _ => failures as f64 * 100.0 / serve_attempts as f64,
+                },
+            },
+        )
+        .collect();
+    rates.sort_by(|a, b| a.client_name.cmp(&b.client_name));
+
+    Ok(Json(rates))
+}
+
+/// Nodes listed for each client by the slowest serving nodes, by default and at most.
+const DEFAULT_SLOWEST_NODES_PER_CLIENT: usize = 10;
+const MAX_SLOWEST_NODES_PER_CLIENT: usize = 100;
+
+/// A node that was slow to respond to the lookups of audits.
+#[derive(Serialize, Debug, ToSchema)]
+pub struct SlowServingNode {
+    pub client_name: String,
+    #[serde(flatten)]
+    #[schema(inline)]
+    pub latency: node_serve_latency::NodeLatency,
+}
+
+/// Returns, for each client, the nodes that were slowest to respond to the lookups of audits
+/// over the last day, by their 95th percentile latency. Set the number of nodes per client
+/// with `limit=` (10 by default).
+///
+/// Nodes that responded fewer than `min-responses` times (5 by default) are left out, as their
+/// percentiles say little.
+#[utoipa::path(
+    get,
+    path = "/api/slowest-serving-nodes/",
+    tag = "nodes",
+    params(
+        NetworkParam,
+        (
+            "limit" = Option<usize>, Query,
+            description = "Nodes per client, 10 by default and at most 100",
+        ),
+        (
+            "min-responses" = Option<i32>, Query,
+            description = "Responses a node needs to be listed, 5 by default",
+        ),
+    ),
+    responses(
+        (status = 200, body = [SlowServingNode]),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+    ),
+)]
+pub async fn slowest_serving_nodes(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Vec<SlowServingNode>>, WebError> {
+    let limit = match http_args.get("limit") {
+        None => DEFAULT_SLOWEST_NODES_PER_CLIENT,
+        Some(limit) => parse_param::<usize>("limit", limit)?.clamp(1, MAX_SLOWEST_NODES_PER_CLIENT),
+    };
+    let min_responses = match http_args.get("min-responses") {
+        None => 5,
+        Some(min_responses) => parse_param::<i32>("min-responses", min_responses)?,
+    };
+    let latencies = node_serve_latency::get_slowest(
+        get_subprotocol_from_params(&http_args),
+        min_responses,
+        &state.database_connection,
+    )
+    .await?;
+
+    let mut listed: HashMap<&'static str, usize> = HashMap::new();
+    let mut slowest: Vec<SlowServingNode> = latencies
+        .into_iter()
+        .filter_map(|latency| {
+            let client_name = client_name_from_code(latency.client_code);
+            let num_listed = listed.entry(client_name).or_default();
+            if *num_listed >= limit {
+                return None;
+            }
+            *num_listed += 1;
+            Some(SlowServingNode {
+                client_name: client_name.to_string(),
+                latency,
+            })
+        })
+        .collect();
+    // The sort is stable, so each client's nodes stay slowest first.
+    slowest.sort_by(|a, b| a.client_name.cmp(&b.client_name));
+    Ok(Json(slowest))
+}
+
+/// Returns the nodes that traced audits over a one week window asked for content within their
+/// advertised radius, ranked by how often they did not serve it.
+///
+/// Nodes asked for fewer than `min-requests` keys within their radius (5 by default) are left
+/// out, as their rates say little.
+#[utoipa::path(
+    get,
+    path = "/api/radius-discrepancies/",
+    tag = "nodes",
+    params(
+        NetworkParam,
+        (
+            "weeks-ago" = Option<i32>, Query,
+            description = "Week windows to shift the window back by, 0 by default",
+        ),
+        ReportingWindowParams,
+        (
+            "min-requests" = Option<i64>, Query,
+            description = "Requests within its radius a node needs to be listed, 5 by default",
+        ),
+    ),
+    responses(
+        (status = 200, body = [census_node::RadiusDiscrepancy]),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+    ),
+)]
+pub async fn radius_discrepancies(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Vec<census_node::RadiusDiscrepancy>>, WebError> {
+    let weeks_ago: i32 = match http_args.get("weeks-ago") {
+        None => 0,
+        Some(weeks_ago) => weeks_ago.parse::<i32>().unwrap_or(0),
+    };
+    let min_requests: i64 = match http_args.get("min-requests") {
+        None => 5,
+        Some(min_requests) => min_requests.parse::<i64>().unwrap_or(5),
+    };
+    let subprotocol = get_subprotocol_from_params(&http_args);
+    let (start, end) =
+        get_reporting_window(&http_args, 7, weeks_ago, &state.database_connection).await?;
+    let discrepancies =
+        census_node::get_radius_discrepancies(subprotocol, start, end, &state.database_connection)
+            .await?;
+    Ok(Json(
+        discrepancies
+            .into_iter()
+            .filter(|node| node.requests_in_radius >= min_requests)
+            .collect(),
+    ))
+}
+
+#[derive(FromQueryResult, Debug)]
+pub struct ClientServedCount {
+    pub day: DateTime<Utc>,
+    /// First character of the ENR client string, if the node's ENR advertised a client.
+    pub client_code: Option<i32>,
+    pub served: i64,
+}
+
+/// Successful audits of a day that were served by nodes running one client.
+#[derive(Serialize, Debug, ToSchema)]
+pub struct ServedByClientDay {
+    pub day: DateTime<Utc>,
+    pub client_name: String,
+    pub served: i64,
+    /// Share of the day's attributed successful audits, as a percentage.
+    pub share_percent: f64,
+}
+
+/// Returns, for each day over a 30 day window, the share of successful audits served by nodes
+/// running each client, to show whether availability depends on one client implementation.
+///
+/// An audit is attributed to the node its trace shows the content being received from, so only
+/// traced audits where the content came from another node are counted.
+#[utoipa::path(
+    get,
+    path = "/api/served-by-client/",
+    tag = "stats",
+    params(
+        NetworkParam,
+        (
+            "months-ago" = Option<i32>, Query,
+            description = "30 day windows to shift the window back by, 0 by default",
+        ),
+        ReportingWindowParams,
+    ),
+    responses(
+        (status = 200, body = [ServedByClientDay]),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+    ),
+)]
+pub async fn served_by_client(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Vec<ServedByClientDay>>, WebError> {
+    let months_ago: i32 = match http_args.get("months-ago") {
+        None => 0,
+        Some(months_ago) => months_ago.parse::<i32>().unwrap_or(0),
+    };
+    let subprotocol = get_subprotocol_from_params(&http_args);
+    let (start, end) =
+        get_reporting_window(&http_args, 30, months_ago, &state.database_connection).await?;
+
+    let counts = ClientServedCount::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        &format!(
+            "
+        WITH served AS (
+            SELECT
+                date_trunc('day', content_audit.created_at) AS day,
+                content_audit.trace::jsonb->>'receivedFrom' AS node_id,
+                content_audit.trace::jsonb->>'origin' AS origin
+            FROM content_audit
+            JOIN content ON content.id = content_audit.content_key
+            WHERE content_audit.created_at >= $1
+                AND content_audit.created_at < $2
+                AND content.protocol_id = $3
+                AND content_audit.result = $4
+                AND content_audit.trace <> ''
+        ),
+        node_clients AS (
+            SELECT DISTINCT ON (node.id) node.node_id, key_value.value AS client
+            FROM node
+            JOIN record ON record.node_id = node.id
+            JOIN key_value ON key_value.record_id = record.id
+            WHERE convert_from(key_value.key, 'UTF8') = 'c'
+            ORDER BY node.id, record.sequence_number DESC
+        )
+        SELECT
+            served.day,
+            {client_code} AS client_code,
+            COUNT(*) AS served
+        FROM served
+        LEFT JOIN node_clients
+            ON node_clients.node_id = decode(substr(served.node_id, 3), 'hex')
+        WHERE served.node_id IS NOT NULL AND served.node_id <> served.origin
+        GROUP BY served.day, client_code
+        ",
+            client_code = client_info::client_code_sql("node_clients.client"),
+        ),
+        vec![
+            start.into(),
+            end.into(),
+            subprotocol.into(),
+            AuditResult::Success.into(),
+        ],
+    ))
+    .all(&state.database_connection)
+    .await?;
+
+    let mut by_day_and_client: BTreeMap<(DateTime<Utc>, &'static str), i64> = BTreeMap::new();
+    let mut by_day: HashMap<DateTime<Utc>, i64> = HashMap::new();
+    for count in counts {
+        *by_day_and_client
+            .entry((count.day, client_name_from_code(count.client_code)))
+            .or_default() += count.served;
+        *by_day.entry(count.day).or_default() += count.served;
+    }
+
+    Ok(Json(
+        by_day_and_client
+            .into_iter()
+            .map(|((day, client_name), served)| ServedByClientDay {
+                day,
+                client_name: client_name.to_string(),
+                served,
+                share_percent: served as f64 * 100.0 / by_day[&day] as f64,
+            })
+            .collect(),
+    ))
+}
+
+/// Transfers of one type of content during a day, and how many of them failed.
+#[derive(Serialize, Debug, ToSchema)]
+pub struct TransferFailureDay {
+    pub day: DateTime<Utc>,
+    pub content_type: String,
+    pub transfers: i64,
+    pub failures: i64,
+    pub failure_rate: f64,
+    /// Failed transfers by the failure reported in the traces, like `utpTransferFailed`.
+    pub failures_by_type: BTreeMap<String, i64>,
+}
+
+/// Returns, for each day and type of content, the share of transfers seen in audit traces that
+/// failed, over a 30 day window.
+///
+/// Reads the daily summaries recorded by glados-audit rather than the traces themselves.
+#[utoipa::path(
+    get,
+    path = "/api/transfer-failures-by-content-type/",
+    tag = "stats",
+    params(
+        NetworkParam,
+        (
+            "months-ago" = Option<i32>, Query,
+            description = "30 day windows to shift the window back by, 0 by default",
+        ),
+        ReportingWindowParams,
+    ),
+    responses(
+        (status = 200, body = [TransferFailureDay]),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+    ),
+)]
+pub async fn transfer_failures_by_content_type(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Vec<TransferFailureDay>>, WebError> {
+    let months_ago: i32 = match http_args.get("months-ago") {
+        None => 0,
+        Some(months_ago) => months_ago.parse::<i32>().unwrap_or(0),
+    };
+    let subprotocol = get_subprotocol_from_params(&http_args);
+    let (start, end) =
+        get_reporting_window(&http_args, 30, months_ago, &state.database_connection).await?;
+    let counts =
+        transfer_failure_daily::get_between(start, end, subprotocol, &state.database_connection)
+            .await?;
+
+    // Counts are ordered by day and content type, so each group is a run of rows.
+    let mut days: Vec<TransferFailureDay> = vec![];
+    for count in counts {
+        let content_type = match subprotocol {
+            SubProtocol::History => history_content_type(count.content_type),
+            _ => format!("{:#04x}", count.content_type),
+        };
+        let day = match days.last_mut() {
+            Some(day) if day.day == count.day && day.content_type == content_type => day,
+            _ => {
+                days.push(TransferFailureDay {
+                    day: count.day,
+                    content_type,
+                    transfers: 0,
+                    failures: 0,
+                    failure_rate: 0.0,
+                    failures_by_type: BTreeMap::new(),
+                });
+                days.last_mut().expect("a day was just added")
+            }
+        };
+        day.transfers += count.num_transfers;
+        if count.failure_type != transfer_failure_daily::NO_FAILURE {
+            day.failures += count.num_transfers;
+            day.failures_by_type
+                .insert(count.failure_type, count.num_transfers);
+        }
+    }
+    for day in days.iter_mut() {
+        if day.transfers > 0 {
+            day.failure_rate = day.failures as f64 * 100.0 / day.transfers as f64;
+        }
+    }
+
+    Ok(Json(days))
+}
+
+#[derive(FromQueryResult, Debug)]
+pub struct ClientRestarts {
+    /// First character of the ENR client string, if the node's ENR advertised a client.
+    pub client_code: Option<i32>,
+    pub nodes: i64,
+    pub restarts: i64,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct ClientRestartFrequency {
+    pub client_name: String,
+    pub nodes: i64,
+    pub restarts: i64,
+    /// Average number of restarts per node per day over the window.
+    pub restarts_per_node_per_day: f64,
+}
+
+/// Returns how often nodes of each client restarted over a one week window.
+///
+/// A node is counted as having restarted when its ENR changes between two consecutive
+/// censuses it appeared in, and either the sequence number went up while the IP stayed the
+/// same, or the advertised client version changed. IP changes alone are treated as
+/// network churn rather than restarts.
+#[utoipa::path(
+    get,
+    path = "/api/client-restart-frequency/",
+    tag = "censuses",
+    params(
+        NetworkParam,
+        ExcludeSelfParam,
+        (
+            "weeks-ago" = Option<i32>, Query,
+            description = "Week windows to shift the window back by, 0 by default",
+        ),
+        ReportingWindowParams,
+    ),
+    responses(
+        (status = 200, body = [ClientRestartFrequency]),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+    ),
+)]
+pub async fn client_restart_frequency(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Vec<ClientRestartFrequency>>, WebError> {
+    let weeks_ago: i32 = match http_args.get("weeks-ago") {
+        None => 0,
+        Some(weeks_ago) => weeks_ago.parse::<i32>().unwrap_or(0),
+    };
+    let subprotocol = get_subprotocol_from_params(&http_args);
+    let (start, end) =
+        get_reporting_window(&http_args, 7, weeks_ago, &state.database_connection).await?;
+
+    let restarts = ClientRestarts::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        &format!(
+            "
+        WITH appearances AS (
+            SELECT
+                record.node_id,
+                record.id AS record_id,
+                record.sequence_number,
+                LAG(record.id) OVER node_censuses AS previous_record_id,
+                LAG(record.sequence_number) OVER node_censuses AS previous_sequence_number
+            FROM census_node
+            JOIN census ON census.id = census_node.census_id
+            JOIN record ON record.id = census_node.record_id
+            WHERE census.sub_network = $3
+                AND census.started_at >= $1
+                AND census.started_at < $2
+                AND NOT (census_node.is_self AND $4)
+            WINDOW node_censuses AS (PARTITION BY record.node_id ORDER BY census.started_at)
+        ),
+        record_values AS (
+            SELECT
+                record_id,
+                (array_agg(value) FILTER (WHERE convert_from(key, 'UTF8') = 'ip'))[1] AS ip,
+                (array_agg(value) FILTER (WHERE convert_from(key, 'UTF8') = 'c'))[1] AS client
+            FROM key_value
+            WHERE convert_from(key, 'UTF8') IN ('ip', 'c')
+            GROUP BY record_id
+        ),
+        transitions AS (
+            SELECT
+                appearances.node_id,
+                current_values.client,
+                appearances.previous_record_id IS NOT NULL
+                    AND appearances.record_id <> appearances.previous_record_id
+                    AND (
+                        (appearances.sequence_number > appearances.previous_sequence_number
+                            AND current_values.ip IS NOT DISTINCT FROM previous_values.ip)
+                        OR current_values.client IS DISTINCT FROM previous_values.client
+                    ) AS restarted
+            FROM appearances
+            LEFT JOIN record_values AS current_values
+                ON current_values.record_id = appearances.record_id
+            LEFT JOIN record_values AS previous_values
+                ON previous_values.record_id = appearances.previous_record_id
+        )
+        SELECT
+            {client_code} AS client_code,
+            COUNT(DISTINCT node_id) AS nodes,
+            COUNT(*) FILTER (WHERE restarted) AS restarts
+        FROM transitions
+        GROUP BY client_code
+        ",
+            client_code = client_info::client_code_sql("client"),
+        ),
+        vec![
+            start.into(),
+            end.into(),
+            subprotocol.into(),
+            get_exclude_self_from_params(&http_args).into(),
+        ],
+    ))
+    .all(&state.database_connection)
+    .await?;
+
+    let mut by_client: HashMap<&'static str, (i64, i64)> = HashMap::new();
+    for client_restarts in restarts {
+        let entry = by_client
+            .entry(client_name_from_code(client_restarts.client_code))
+            .or_default();
+        entry.0 += client_restarts.nodes;
+        entry.1 += client_restarts.restarts;
+    }
+
+    let window_days = (end - start).num_seconds() as f64 / 86400.0;
+    let mut frequencies: Vec<ClientRestartFrequency> = by_client
+        .into_iter()
+        .map(|(client_name, (nodes, restarts))| ClientRestartFrequency {
+            client_name: client_name.to_string(),
+            nodes,
+            restarts,
+            restarts_per_node_per_day: match nodes {
+                0 => 0.0,
+                _ => restarts as f64 / nodes as f64 / window_days,
+            },
+        })
+        .collect();
+    frequencies.sort_by(|a, b| a.client_name.cmp(&b.client_name));
+
+    Ok(Json(frequencies))
+}
+
+#[derive(FromQueryResult, Debug)]
+pub struct CensusGroupCount {
+    pub census_id: i32,
+    pub census_time: DateTime<Utc>,
+    /// Country code or ASN, or None if the node has not been located.
+    pub group_label: Option<String>,
+    pub node_count: i64,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct CensusDiversity {
+    pub census_id: i32,
+    pub time: DateTime<Utc>,
+    pub total_nodes: i64,
+    /// Nodes without a known location.
+    pub unknown_nodes: i64,
+    /// Node count per country or ASN, largest first.
+    pub groups: Vec<(String, i64)>,
+    /// Percentage of located nodes in the three largest groups.
+    pub top_3_share: f64,
+}
+
+/// The attribute of a node's location that census nodes are grouped by.
+#[derive(Clone, Copy, Debug)]
+enum GeoGrouping {
+    Country,
+    Asn,
+}
+
+impl GeoGrouping {
+    fn sql_expression(&self) -> &'static str {
+        match self {
+            GeoGrouping::Country => "node_geo.country_code",
+            GeoGrouping::Asn => "'AS' || node_geo.asn::text",
+        }
+    }
+}
+
+/// Returns the number of nodes per country in each census over a 24 hour window.
+#[utoipa::path(
+    get,
+    path = "/api/census-country-diversity/",
+    tag = "censuses",
+    params(
+        NetworkParam,
+        ExcludeSelfParam,
+        (
+            "days-ago" = Option<i32>, Query,
+            description = "Day windows to shift the window back by, 0 by default",
+        ),
+        ReportingWindowParams,
+    ),
+    responses(
+        (status = 200, body = [CensusDiversity]),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+    ),
+)]
+pub async fn census_country_diversity(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Vec<CensusDiversity>>, WebError> {
+    census_diversity(GeoGrouping::Country, http_args, state).await
+}
+
+/// Returns the number of nodes per autonomous system in each census over a 24 hour window.
+#[utoipa::path(
+    get,
+    path = "/api/census-asn-diversity/",
+    tag = "censuses",
+    params(
+        NetworkParam,
+        ExcludeSelfParam,
+        (
+            "days-ago" = Option<i32>, Query,
+            description = "Day windows to shift the window back by, 0 by default",
+        ),
+        ReportingWindowParams,
+    ),
+    responses(
+        (status = 200, body = [CensusDiversity]),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+    ),
+)]
+pub async fn census_asn_diversity(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Vec<CensusDiversity>>, WebError> {
+    census_diversity(GeoGrouping::Asn, http_args, state).await
+}
+
+/// Returns the client diversity indices of each census over a one week window.
+#[utoipa::path(
+    get,
+    path = "/api/census-client-diversity/",
+    tag = "censuses",
+    params(
+        NetworkParam,
+        (
+            "weeks-ago" = Option<i32>, Query,
+            description = "Week windows to shift the window back by, 0 by default",
+        ),
+        ReportingWindowParams,
+    ),
+    responses(
+        (status = 200, body = [census::CensusClientDiversity]),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+    ),
+)]
+pub async fn census_client_diversity(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Vec<census::CensusClientDiversity>>, WebError> {
+    let weeks_ago: i32 = match http_args.get("weeks-ago") {
+        None => 0,
+        Some(weeks_ago) => weeks_ago.parse::<i32>().unwrap_or(0),
+    };
+    let subprotocol = get_subprotocol_from_params(&http_args);
+    let (start, end) =
+        get_reporting_window(&http_args, 7, weeks_ago, &state.database_connection).await?;
+    let diversity =
+        census::get_client_diversity(subprotocol, start, end, &state.database_connection).await?;
+    Ok(Json(diversity))
+}
+
+/// Returns the number of nodes found by a census in each country and autonomous system, for the
+/// latest census of the subnetwork unless a `census-id` is given.
+#[utoipa::path(
+    get,
+    path = "/api/census-geo/",
+    tag = "censuses",
+    params(
+        NetworkParam,
+        ExcludeSelfParam,
+        (
+            "census-id" = Option<i32>, Query,
+            description = "Census, the latest one of the subnetwork by default",
+        ),
+    ),
+    responses(
+        (status = 200, body = node_geo::CensusGeo),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+        (status = 404, description = "Unknown census", body = ErrorBody),
+    ),
+)]
+pub async fn census_geo(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<node_geo::CensusGeo>, WebError> {
+    let census_id = match http_args.get("census-id") {
+        Some(census_id) => parse_param::<i32>("census-id", census_id)?,
+        None => {
+            let subprotocol = get_subprotocol_from_params(&http_args);
+            get_max_census_id(&state, subprotocol)
+                .await
+                .ok_or_else(|| WebError::not_found("Census"))?
+                .id
+        }
+    };
+    let census_geo = node_geo::get_census_geo(
+        census_id,
+        get_exclude_self_from_params(&http_args),
+        &state.database_connection,
+    )
+    .await?;
+    Ok(Json(census_geo))
+}
+
+/// Shows how the nodes found by a census are spread around the node id it started from.
+#[utoipa::path(
+    get,
+    path = "/api/census-target-coverage/",
+    tag = "censuses",
+    params(
+        NetworkParam,
+        ExcludeSelfParam,
+        (
+            "census-id" = Option<i32>, Query,
+            description = "Census, the latest one of the subnetwork by default",
+        ),
+    ),
+    responses(
+        (status = 200, body = census::TargetCoverage),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+        (
+            status = 404,
+            description = "Unknown census, or census without a target node id",
+            body = ErrorBody,
+        ),
+    ),
+)]
+pub async fn census_target_coverage(
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<census::TargetCoverage>, WebError> {
+    let census_id = match http_args.get("census-id") {
+        Some(census_id) => parse_param::<i32>("census-id", census_id)?,
+        None => {
+            let subprotocol = get_subprotocol_from_params(&http_args);
+            get_max_census_id(&state, subprotocol)
+                .await
+                .ok_or_else(|| WebError::not_found("Census"))?
+                .id
+        }
+    };
+    let coverage = census::get_target_coverage(
+        census_id,
+        get_exclude_self_from_params(&http_args),
+        &state.database_connection,
+    )
+    .await?
+    .ok_or_else(|| WebError::not_found("Census target node id"))?;
+    Ok(Json(coverage))
+}
+
+async fn census_diversity(
+    grouping: GeoGrouping,
+    http_args: HttpQuery<HashMap<String, String>>,
+    state: Arc<State>,
+) -> Result<Json<Vec<CensusDiversity>>, WebError> {
+    let days_ago: i32 = match http_args.get("days-ago") {
+        None => 0,
+        Some(days_ago) => days_ago.parse::<i32>().unwrap_or(0),
+    };
+    let subprotocol = get_subprotocol_from_params(&http_args);
+    let (start, end) =
+        get_reporting_window(&http_args, 1, days_ago, &state.database_connection).await?;
 
-    let filtered_audits = filtered_audits.map_err(|e| {
-        error!(err=?e, "Could not look up audits");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-    let hour_stats = hour_stats.map_err(|e| {
-        error!(err=?e, "Could not look up audit hourly stats");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-    let day_stats = day_stats.map_err(|e| {
-        error!(err=?e, "Could not look up audit daily stats");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-    let week_stats = week_stats.map_err(|e| {
-        error!(err=?e, "Could not look up audit weekly stats");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    let group_counts = CensusGroupCount::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        &format!(
+            "
+            SELECT
+                census.id AS census_id,
+                census.started_at AS census_time,
+                {} AS group_label,
+                COUNT(*) AS node_count
+            FROM census
+            JOIN census_node ON census_node.census_id = census.id
+            JOIN record ON record.id = census_node.record_id
+            LEFT JOIN node_geo ON node_geo.node_id = record.node_id
+            WHERE census.sub_network = $3
+                AND census.started_at >= $1
+                AND census.started_at < $2
+                AND NOT (census_node.is_self AND $4)
+            GROUP BY census.id, census.started_at, group_label
+            ORDER BY census.started_at, census.id
+            ",
+            grouping.sql_expression()
+        ),
+        vec![
+            start.into(),
+            end.into(),
+            subprotocol.into(),
+            get_exclude_self_from_params(&http_args).into(),
+        ],
+    ))
+    .all(&state.database_connection)
+    .await?;
 
-    let filtered_audits: Vec<AuditTuple> =
-        get_audit_tuples_from_audit_models(filtered_audits, &state.database_connection).await?;
+    let mut censuses: Vec<CensusDiversity> = vec![];
+    for group_count in group_counts {
+        if censuses.last().map(|c| c.census_id) != Some(group_count.census_id) {
+            censuses.push(CensusDiversity {
+                census_id: group_count.census_id,
+                time: group_count.census_time,
+                total_nodes: 0,
+                unknown_nodes: 0,
+                groups: vec![],
+                top_3_share: 0.0,
+            });
+        }
+        let census = censuses.last_mut().expect("census was just added");
+        census.total_nodes += group_count.node_count;
+        match group_count.group_label {
+            Some(group) => census.groups.push((group, group_count.node_count)),
+            None => census.unknown_nodes += group_count.node_count,
+        }
+    }
 
-    let template = AuditTableTemplate {
-        stats: [hour_stats, day_stats, week_stats],
-        audits: filtered_audits,
-    };
+    for census in censuses.iter_mut() {
+        census
+            .groups
+            .sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        let located_nodes = census.total_nodes - census.unknown_nodes;
+        let top_3_nodes: i64 = census.groups.iter().take(3).map(|(_, count)| count).sum();
+        census.top_3_share = match located_nodes {
+            0 => 0.0,
+            _ => top_3_nodes as f64 * 100.0 / located_nodes as f64,
+        };
+    }
 
-    Ok(HtmlTemplate(template))
+    Ok(Json(censuses))
 }
 
-#[derive(FromQueryResult, Serialize, Debug)]
-pub struct DeadZoneData {
-    pub data_radius: Vec<u8>,
+//
+// JSON API, version 1
+//
+// The data of the node, content key and census pages, for scripts and other frontends. Fields
+// may be added to these responses, but are not renamed or removed within a version.
+
+/// An ENR of a node, with its key-value pairs.
+#[derive(Serialize, Debug, ToSchema)]
+pub struct ApiEnr {
+    pub sequence_number: i32,
     pub raw: String,
-    pub node_id: Vec<u8>,
+    /// Hex encoded values, by key.
+    pub key_values: BTreeMap<String, String>,
 }
 
-pub async fn is_content_in_deadzone(
-    Path(content_key): Path<String>,
-    Extension(state): Extension<Arc<State>>,
-) -> Result<Json<Vec<String>>, StatusCode> {
-    let builder = state.database_connection.get_database_backend();
-    let mut select_dead_zone_data = Query::select();
-    select_dead_zone_data
-        .expr(Expr::col((census_node::Entity, census_node::Column::DataRadius)))
-        .expr(Expr::col((node::Entity, node::Column::NodeId)))
-        .expr(Expr::col((record::Entity, record::Column::Raw)))
-        .from(census_node::Entity)
-        .from(node::Entity)
-        .from(record::Entity)
-        .from_subquery(
-            Query::select()
-                .from(census::Entity)
-                .expr_as(Expr::col(census::Column::StartedAt), Alias::new("started_at"))
-                .expr_as(Expr::col(census::Column::Duration), Alias::new("duration"))
-                .order_by(census::Column::StartedAt, Order::Desc)
-                .limit(1)
-                .take(),
-            Alias::new("latest_census"),
-        )
-        .and_where(
-            Expr::col((census_node::Entity, census_node::Column::SurveyedAt))
-                .gte(Expr::col((Alias::new("latest_census"), Alias::new("started_at")))),
-        )
-        .and_where(
-            Expr::col((census_node::Entity, census_node::Column::SurveyedAt))
-                .lt(Expr::cust(if builder == DbBackend::Sqlite {
-                    "STRFTIME('%Y-%m-%dT%H:%M:%S.%f', DATETIME(latest_census.started_at, '+' || latest_census.duration || ' seconds'))"
-                } else {
-                    "latest_census.started_at + latest_census.duration * interval '1 second'"
-                })),
-        )
-        .and_where(
-            Expr::col((census_node::Entity, census_node::Column::RecordId))
-                .eq(Expr::col((record::Entity, record::Column::Id))),
-        )
-        .and_where(
-            Expr::col((record::Entity, record::Column::NodeId))
-                .eq(Expr::col((node::Entity, node::Column::Id))),
-        );
-
-    let dead_zone_data_vec = DeadZoneData::find_by_statement(builder.build(&select_dead_zone_data))
-        .all(&state.database_connection)
-        .await
-        .unwrap();
-
-    let content_id = if let Ok(content_key) =
-        serde_json::from_value::<HistoryContentKey>(serde_json::json!(content_key))
-    {
-        content_key.content_id()
-    } else if let Ok(content_key) =
-        serde_json::from_value::<StateContentKey>(serde_json::json!(content_key))
-    {
-        content_key.content_id()
-    } else if let Ok(content_key) =
-        serde_json::from_value::<BeaconContentKey>(serde_json::json!(content_key))
-    {
-        content_key.content_id()
-    } else {
-        return Err(StatusCode::BAD_REQUEST);
-    };
-
-    let mut enrs: Vec<String> = vec![];
-    for dead_zone_data in dead_zone_data_vec {
-        let radius = Distance::from(U256::from_be_slice(&dead_zone_data.data_radius));
-        let node_id = Distance::from(U256::from_be_slice(&dead_zone_data.node_id));
-        if XorMetric::distance(&content_id, &node_id.big_endian()) <= radius {
-            enrs.push(dead_zone_data.raw);
+impl ApiEnr {
+    fn new(record: record::Model, key_values: Vec<key_value::Model>) -> Self {
+        ApiEnr {
+            sequence_number: record.sequence_number,
+            raw: record.raw,
+            key_values: key_values
+                .into_iter()
+                .map(|key_value| {
+                    (
+                        String::from_utf8_lossy(&key_value.key).into_owned(),
+                        key_value.value_as_hex(),
+                    )
+                })
+                .collect(),
         }
     }
+}
 
-    Ok(Json(enrs))
+/// A node, as shown on its page. The closest nodes are left out, as finding them scans every
+/// node.
+#[derive(Serialize, Debug, ToSchema)]
+pub struct ApiNodeDetail {
+    pub node_id: String,
+    pub nickname: Option<String>,
+    pub archived: bool,
+    pub latest_enr: Option<ApiEnr>,
+    /// Number of ENRs stored for the node, listed at `/api/v1/node/<node id>/enrs/`.
+    pub enr_count: usize,
+    pub enr_changes: Vec<enr_change::EnrChangeEntry>,
+    pub operator: Option<node_operator::Model>,
+    pub reliability: NodeReliabilityReport,
 }
 
-pub async fn get_audit_stats_handler(
+async fn find_node(node_id: HexParam, conn: &DatabaseConnection) -> Result<node::Model, WebError> {
+    node::Entity::find()
+        .filter(node::Column::NodeId.eq(node_id.bytes))
+        .one(conn)
+        .await?
+        .ok_or_else(|| WebError::not_found(format!("Node {}", node_id.hex)))
+}
+
+/// Returns a node with its latest ENR, address changes, operator and reliability. The
+/// reliability is over the latest 96 censuses of the subnetwork set with `network=`.
+#[utoipa::path(
+    get,
+    path = "/api/v1/node/{node_id_hex}/",
+    tag = "nodes",
+    params(
+        ("node_id_hex" = String, Path, description = "Node id, hex encoded"),
+        NetworkParam,
+    ),
+    responses(
+        (status = 200, body = ApiNodeDetail),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+        (status = 404, description = "Unknown node", body = ErrorBody),
+    ),
+)]
+pub async fn api_node_detail(
+    node_id: HexParam,
     http_args: HttpQuery<HashMap<String, String>>,
     Extension(state): Extension<Arc<State>>,
-) -> Result<Json<Vec<audit_stats::Model>>, StatusCode> {
-    let weeks_ago: i32 = match http_args.get("weeks-ago") {
-        None => 0,
-        Some(days_ago) => days_ago.parse::<i32>().unwrap_or(0),
+) -> Result<Json<ApiNodeDetail>, WebError> {
+    let conn = &state.database_connection;
+    let node_model = find_node(node_id, conn).await?;
+    let enr_list = record::Entity::find()
+        .filter(record::Column::NodeId.eq(node_model.id))
+        .order_by_desc(record::Column::SequenceNumber)
+        .all(conn)
+        .await?;
+    let enr_count = enr_list.len();
+    let latest_enr = match enr_list.into_iter().next() {
+        Some(enr) => {
+            let key_values = key_value::Entity::find()
+                .filter(key_value::Column::RecordId.eq(enr.id))
+                .order_by_asc(key_value::Column::Key)
+                .all(conn)
+                .await?;
+            Some(ApiEnr::new(enr, key_values))
+        }
+        None => None,
     };
-    let stats = audit_stats::get_recent_stats(&state.database_connection, weeks_ago)
-        .await
-        .map_err(|e| {
-            error!(err=?e, "Could not look up audit stat history");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })
-        .unwrap();
+    let reliability = census_node::get_node_reliability(
+        node_model.id,
+        get_subprotocol_from_params(&http_args),
+        DEFAULT_RELIABILITY_CENSUSES,
+        conn,
+    )
+    .await?;
+    Ok(Json(ApiNodeDetail {
+        node_id: node_model.node_id_as_hex(),
+        nickname: node_model.get_nickname(),
+        archived: node_model.archived,
+        latest_enr,
+        enr_count,
+        enr_changes: enr_change::get_by_node(node_model.id, conn).await?,
+        operator: node_operator::get(node_model.id, conn).await?,
+        reliability: NodeReliabilityReport {
+            presence: reliability.presence(),
+            score: reliability.score(),
+            reliability,
+        },
+    }))
+}
 
-    Ok(Json(stats))
+/// Returns the ENRs of a node with their key-value pairs, highest sequence number first.
+#[utoipa::path(
+    get,
+    path = "/api/v1/node/{node_id_hex}/enrs/",
+    tag = "nodes",
+    params(
+        ("node_id_hex" = String, Path, description = "Node id, hex encoded"),
+    ),
+    responses(
+        (status = 200, body = [ApiEnr]),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+        (status = 404, description = "Unknown node", body = ErrorBody),
+    ),
+)]
+pub async fn api_node_enrs(
+    node_id: HexParam,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<Vec<ApiEnr>>, WebError> {
+    let conn = &state.database_connection;
+    let node_model = find_node(node_id, conn).await?;
+    let enr_list = record::Entity::find()
+        .filter(record::Column::NodeId.eq(node_model.id))
+        .order_by_desc(record::Column::SequenceNumber)
+        .all(conn)
+        .await?;
+    let key_values = enr_list.load_many(key_value::Entity, conn).await?;
+    Ok(Json(
+        enr_list
+            .into_iter()
+            .zip(key_values)
+            .map(|(enr, key_values)| ApiEnr::new(enr, key_values))
+            .collect(),
+    ))
 }
 
-pub async fn get_failed_keys_handler(
-    http_args: HttpQuery<HashMap<String, String>>,
+/// An audit of a content key.
+#[derive(Serialize, Debug, ToSchema)]
+pub struct ApiAudit {
+    pub id: i32,
+    pub created_at: DateTime<Utc>,
+    pub result: String,
+    pub strategy: Option<String>,
+    /// Version string of the client of the Portal node audited, if known.
+    pub client: Option<String>,
+    /// Node id of the Portal node audited, if known.
+    pub node_id: Option<String>,
+    pub duration_ms: Option<i32>,
+    pub error_reason: Option<String>,
+}
+
+/// A content key, as shown on its page, with its audits.
+#[derive(Serialize, Debug, ToSchema)]
+pub struct ApiContentKeyAudits {
+    pub content_key: String,
+    pub content_id: String,
+    pub content_kind: String,
+    pub sub_network: String,
+    pub first_available_at: DateTime<Utc>,
+    pub block_number: Option<i32>,
+    /// Most recent first.
+    pub audits: Vec<ApiAudit>,
+}
+
+/// Returns a content key with all of its audits.
+#[utoipa::path(
+    get,
+    path = "/api/v1/content/key/{content_key_hex}/audits/",
+    tag = "audits",
+    params(
+        ("content_key_hex" = String, Path, description = "Content key, hex encoded"),
+    ),
+    responses(
+        (status = 200, body = ApiContentKeyAudits),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+        (status = 404, description = "Unknown content key", body = ErrorBody),
+    ),
+)]
+pub async fn api_content_key_audits(
+    content_key: HexParam,
     Extension(state): Extension<Arc<State>>,
-) -> Result<Json<Vec<String>>, StatusCode> {
-    let subprotocol = get_subprotocol_from_params(&http_args);
+) -> Result<Json<ApiContentKeyAudits>, WebError> {
+    let conn = &state.database_connection;
+    let HexParam {
+        hex: content_key_hex,
+        bytes: content_key_raw,
+    } = content_key;
+    let content_key_model = content::Entity::find()
+        .filter(content::Column::ContentKey.eq(content_key_raw))
+        .one(conn)
+        .await?
+        .ok_or_else(|| WebError::not_found(format!("Content key {content_key_hex}")))?;
+    let (content_id, content_kind) = describe_content_key(&content_key_hex)?;
+    let block_number = execution_metadata::Entity::find()
+        .filter(execution_metadata::Column::Content.eq(content_key_model.id))
+        .one(conn)
+        .await?
+        .map(|metadata| metadata.block_number);
 
-    let strategy: String = match http_args.get("strategy") {
-        // Set a default for each subprotocol
-        None => match subprotocol {
-            SubProtocol::History => "FourFours".to_string(),
-            SubProtocol::State => "StateRoots".to_string(),
-            SubProtocol::Beacon => "Latest".to_string(),
-        },
-        Some(strategy) => strategy.to_string(),
-    };
+    let audits = content_key_model
+        .find_related(content_audit::Entity)
+        .order_by_desc(content_audit::Column::CreatedAt)
+        .all(conn)
+        .await?;
+    let clients = audits.load_one(client_info::Entity, conn).await?;
+    let nodes = audits.load_one(node::Entity, conn).await?;
+    let audits = audits
+        .into_iter()
+        .zip(clients)
+        .zip(nodes)
+        .map(|((audit, client), node)| ApiAudit {
+            id: audit.id,
+            created_at: audit.created_at,
+            result: audit.result_label().to_owned(),
+            strategy: audit.strategy_used.as_ref().map(|s| s.as_text()),
+            client: client.map(|client| client.version_info),
+            node_id: node.map(|node| node.node_id_as_hex()),
+            duration_ms: audit.duration_ms,
+            error_reason: audit.error_reason,
+        })
+        .collect();
 
-    let page: u32 = match http_args.get("page") {
-        None => 1,
-        Some(page) => page.parse::<u32>().unwrap_or(1),
-    };
+    Ok(Json(ApiContentKeyAudits {
+        content_key: content_key_model.key_as_hex(),
+        content_id,
+        content_kind,
+        sub_network: content_key_model.protocol_id.as_text(),
+        first_available_at: content_key_model.first_available_at,
+        block_number,
+        audits,
+    }))
+}
 
-    let failed_keys =
-        content_audit::get_failed_keys(subprotocol, strategy, page, &state.database_connection)
-            .await
-            .map_err(|e| {
-                error!(err=?e, "Could not fetch failed keys");
-                StatusCode::INTERNAL_SERVER_ERROR
-            })?;
+/// A census, as shown on its page, with the ENRs of the nodes it found.
+#[derive(Serialize, Debug, ToSchema)]
+pub struct ApiCensusDetail {
+    pub id: i32,
+    pub sub_network: String,
+    pub started_at: DateTime<Utc>,
+    /// In seconds.
+    pub duration: i32,
+    pub target_node_id: Option<String>,
+    /// When the nodes of the census were archived, after which they are no longer listed.
+    pub nodes_archived_at: Option<DateTime<Utc>>,
+    pub node_count: usize,
+    pub client_diversity: Vec<ClientDiversityResult>,
+    pub incident: Option<census_incident::Model>,
+    pub target_coverage: Option<census::TargetCoverage>,
+    pub enrs: Vec<String>,
+}
 
-    Ok(Json(failed_keys))
+/// Returns a census with the ENRs of the nodes it found. The nodes run by this deployment are
+/// left out of the client diversity and target coverage with `exclude-self=true`.
+#[utoipa::path(
+    get,
+    path = "/api/v1/census/{census_id}/",
+    tag = "censuses",
+    params(
+        ("census_id" = i32, Path, description = "Census"),
+        ExcludeSelfParam,
+    ),
+    responses(
+        (status = 200, body = ApiCensusDetail),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+        (status = 404, description = "Unknown census", body = ErrorBody),
+    ),
+)]
+pub async fn api_census_detail(
+    IdParam(census_id): IdParam,
+    http_args: HttpQuery<HashMap<String, String>>,
+    Extension(state): Extension<Arc<State>>,
+) -> Result<Json<ApiCensusDetail>, WebError> {
+    let conn = &state.database_connection;
+    let exclude_self = get_exclude_self_from_params(&http_args);
+    let census_model = census::Entity::find_by_id(census_id)
+        .one(conn)
+        .await?
+        .ok_or_else(|| WebError::not_found(format!("Census {census_id}")))?;
+    let enrs: Vec<String> = RawEnr::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "
+        SELECT record.raw
+        FROM census_node
+        JOIN record ON record.id = census_node.record_id
+        WHERE census_node.census_id = $1
+        ORDER BY record.node_id
+        ",
+        vec![census_id.into()],
+    ))
+    .all(conn)
+    .await?
+    .into_iter()
+    .map(|enr| enr.raw)
+    .collect();
+
+    Ok(Json(ApiCensusDetail {
+        id: census_model.id,
+        sub_network: census_model.sub_network.as_text(),
+        started_at: census_model.started_at,
+        duration: census_model.duration,
+        target_node_id: census_model.target_node_id.as_deref().map(hex_encode),
+        nodes_archived_at: census_model.nodes_archived_at,
+        node_count: enrs.len(),
+        client_diversity: generate_client_diversity_data(&state, census_id, exclude_self).await?,
+        incident: census_incident::get_by_census(census_id, conn).await?,
+        target_coverage: census::get_target_coverage(census_id, exclude_self, conn).await?,
+        enrs,
+    }))
 }
 
 pub async fn census_explorer_list(
     params: HttpQuery<HashMap<String, String>>,
     Extension(state): Extension<Arc<State>>,
-) -> Result<HtmlTemplate<PaginatedCensusListTemplate>, StatusCode> {
+) -> Result<HtmlTemplate<PaginatedCensusListTemplate>, PageError> {
     let subprotocol = get_subprotocol_from_params(&params);
-    let max_census_id = match get_max_census_id(&state, subprotocol).await {
-        None => return Err(StatusCode::from_u16(404).unwrap()),
-        Some(max_census_id) => max_census_id,
-    };
+    let max_census_id = get_max_census_id(&state, subprotocol)
+        .await
+        .ok_or_else(|| WebError::not_found(format!("{} census", subprotocol.as_text())))?;
 
     let mut list_census_page_id: i32 = match params.get("page") {
-        None => return Err(StatusCode::from_u16(404).unwrap()),
-        Some(list_census_page_id) => match list_census_page_id.parse::<i32>() {
-            Ok(list_census_page_id) => list_census_page_id,
-            Err(_) => return Err(StatusCode::from_u16(404).unwrap()),
-        },
+        None => return Err(WebError::not_found("Census list page").into()),
+        Some(list_census_page_id) => list_census_page_id
+            .parse::<i32>()
+            .map_err(|_| WebError::not_found(format!("Census list page {list_census_page_id}")))?,
     };
 
     if list_census_page_id > max_census_id.id / 50 + 1 {
@@ -896,8 +4713,7 @@ pub async fn census_explorer_list(
     let paginated_census_list =
         PaginatedCensusListResult::find_by_statement(builder.build(&paginated_census_list))
             .all(&state.database_connection)
-            .await
-            .unwrap();
+            .await?;
 
     let template = PaginatedCensusListTemplate {
         census_data: paginated_census_list,
@@ -923,30 +4739,210 @@ pub struct RecordInfo {
     raw: String,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct CensusTimeSeriesData {
     node_ids_with_nicknames: Vec<(String, Option<String>)>,
     censuses: Vec<CensusStatuses>,
     enrs: HashMap<i32, String>,
+    /// Number of nodes matching the filters, including those outside the requested page.
+    total_nodes: usize,
+}
+
+/// Filters on the nodes of the census timeseries. Matching is case insensitive.
+#[derive(Debug)]
+struct CensusNodeFilter {
+    /// Client the node ran in any of its ENRs of the window, like "trin".
+    client: Option<String>,
+    /// Part of the node's nickname.
+    nickname: Option<String>,
+    /// Start of the hex node id, with or without the `0x` prefix.
+    node_id_prefix: Option<String>,
+    /// Portal wire protocol version advertised in any of the node's ENRs of the window.
+    protocol_version: Option<u8>,
+    /// Subnetworks, besides the one of the census, whose censuses found the node during the
+    /// window.
+    supports: Vec<SubProtocol>,
+}
+
+/// What the ENRs of a node during the window say about it.
+#[derive(Debug, Default)]
+struct NodeEnrInfo {
+    clients: HashSet<&'static str>,
+    protocol_versions: HashSet<u8>,
+}
+
+impl CensusNodeFilter {
+    /// Reads the filter from request parameters, failing if a protocol version or subnetwork
+    /// is invalid.
+    fn from_params(params: &HashMap<String, String>) -> Result<Self, WebError> {
+        let param = |name: &str| {
+            params
+                .get(name)
+                .map(|value| value.trim().to_lowercase())
+                .filter(|value| !value.is_empty())
+        };
+        let protocol_version = match param("protocol-version") {
+            None => None,
+            Some(version) => Some(parse_param("protocol-version", &version)?),
+        };
+        let supports = match param("supports") {
+            None => vec![],
+            Some(subprotocols) => subprotocols
+                .split(',')
+                .map(|subprotocol| {
+                    SubProtocol::try_from(&subprotocol.trim().to_owned()).map_err(|_| {
+                        WebError::invalid_parameter("supports", subprotocol, "unknown subnetwork")
+                    })
+                })
+                .collect::<Result<_, _>>()?,
+        };
+        Ok(CensusNodeFilter {
+            client: param("client"),
+            nickname: param("nickname"),
+            node_id_prefix: param("node-id-prefix")
+                .map(|prefix| prefix.trim_start_matches("0x").to_owned()),
+            protocol_version,
+            supports,
+        })
+    }
+
+    /// Whether matching needs the ENRs of the nodes to be decoded.
+    fn needs_enr_info(&self) -> bool {
+        self.client.is_some() || self.protocol_version.is_some()
+    }
+
+    /// Whether a node matches the filter. `supported` holds the ids of the nodes found in the
+    /// censuses of all the `supports` subnetworks, if any were asked for.
+    fn matches(
+        &self,
+        node_id: &str,
+        nickname: Option<&str>,
+        enr_info: Option<&NodeEnrInfo>,
+        supported: Option<&HashSet<String>>,
+    ) -> bool {
+        if let Some(prefix) = &self.node_id_prefix {
+            if !node_id
+                .trim_start_matches("0x")
+                .starts_with(prefix.as_str())
+            {
+                return false;
+            }
+        }
+        if let Some(part) = &self.nickname {
+            if !nickname.is_some_and(|nickname| nickname.to_lowercase().contains(part.as_str())) {
+                return false;
+            }
+        }
+        if let Some(client) = &self.client {
+            if !enr_info.is_some_and(|info| info.clients.contains(client.as_str())) {
+                return false;
+            }
+        }
+        if let Some(version) = &self.protocol_version {
+            if !enr_info.is_some_and(|info| info.protocol_versions.contains(version)) {
+                return false;
+            }
+        }
+        if let Some(supported) = supported {
+            if !supported.contains(node_id) {
+                return false;
+            }
+        }
+        true
+    }
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct CensusStatuses {
     census_id: i32,
     time: DateTime<Utc>,
     enr_statuses: Vec<Option<i32>>,
 }
 
+/// Returns the presence of each node in the censuses of a day.
+///
+/// Nodes can be narrowed down with the `client`, `nickname` and `node-id-prefix` parameters,
+/// by the Portal wire protocol version their ENRs advertise with `protocol-version`, and to
+/// those also found by the censuses of other subnetworks with `supports` (e.g.
+/// `supports=state,beacon`). They are paged through, ordered by node id, with `offset` and
+/// `limit`. All matching nodes are returned if no `limit` is given.
+#[utoipa::path(
+    get,
+    path = "/census/census-node-timeseries-data/",
+    tag = "censuses",
+    params(
+        NetworkParam,
+        IncludeArchivedParam,
+        (
+            "days-ago" = Option<i32>, Query,
+            description = "Day windows to shift the window back by, 0 by default",
+        ),
+        ReportingWindowParams,
+        ("client" = Option<String>, Query, description = "Client name, like `trin`"),
+        ("nickname" = Option<String>, Query, description = "Part of the nickname of the node"),
+        (
+            "node-id-prefix" = Option<String>, Query,
+            description = "Start of the node id, hex encoded",
+        ),
+        (
+            "protocol-version" = Option<u8>, Query,
+            description = "Portal wire protocol version advertised by the ENR",
+        ),
+        (
+            "supports" = Option<String>, Query,
+            description = "Other subnetworks the nodes were also found in, comma separated",
+        ),
+        ("offset" = Option<usize>, Query, description = "Nodes to skip, ordered by node id"),
+        (
+            "limit" = Option<usize>, Query,
+            description = "At most this many nodes, all of them by default",
+        ),
+    ),
+    responses(
+        (status = 200, body = CensusTimeSeriesData),
+        (status = 400, description = "Invalid parameter", body = ErrorBody),
+    ),
+)]
 pub async fn census_timeseries(
     http_args: HttpQuery<HashMap<String, String>>,
     Extension(state): Extension<Arc<State>>,
-) -> Result<Json<CensusTimeSeriesData>, StatusCode> {
+) -> Result<Json<CensusTimeSeriesData>, WebError> {
     let days_ago: i32 = match http_args.get("days-ago") {
         None => 0,
         Some(days_ago) => days_ago.parse::<i32>().unwrap_or(0),
     };
+    let offset: usize = match http_args.get("offset") {
+        None => 0,
+        Some(offset) => parse_param("offset", offset)?,
+    };
+    let limit: Option<usize> = match http_args.get("limit") {
+        None => None,
+        Some(limit) => Some(parse_param("limit", limit)?),
+    };
+    let filter = CensusNodeFilter::from_params(&http_args)?;
 
     let subprotocol = get_subprotocol_from_params(&http_args);
+    let (start, end) =
+        get_reporting_window(&http_args, 1, days_ago, &state.database_connection).await?;
+
+    // The nodes found by the censuses of every other subnetwork asked for.
+    let mut supported: Option<HashSet<String>> = None;
+    for other in &filter.supports {
+        let node_ids = census_node::get_node_ids_by_subprotocol(
+            *other,
+            start,
+            end,
+            &state.database_connection,
+        )
+        .await?
+        .into_iter()
+        .map(hex_encode)
+        .collect::<HashSet<String>>();
+        supported = Some(match supported {
+            None => node_ids,
+            Some(supported) => supported.intersection(&node_ids).cloned().collect(),
+        });
+    }
 
     // Load all censuses in the given 24 hour window with each node's presence status & ENR
     let node_statuses: Vec<NodeStatus> =
@@ -965,9 +4961,9 @@ pub async fn census_timeseries(
             FROM 
                 (
                     SELECT * FROM census
-                    WHERE sub_network = $2
-                    AND started_at >= NOW() - INTERVAL '1 day' * ($1 + 1)
-                    AND started_at < NOW() - INTERVAL '1 day' * $1
+                    WHERE sub_network = $3
+                    AND started_at >= $1
+                    AND started_at < $2
                 ) AS c
             LEFT JOIN 
                 census_node AS cn ON c.id = cn.census_id
@@ -975,16 +4971,18 @@ pub async fn census_timeseries(
                 record AS r ON r.id = cn.record_id
             LEFT JOIN 
                 node AS n ON n.id = r.node_id
+            WHERE n.id IS NULL OR $4 OR NOT n.archived
             ORDER BY 
                 c.started_at, n.node_id;",
-            vec![days_ago.into(), subprotocol.into()],
+            vec![
+                start.into(),
+                end.into(),
+                subprotocol.into(),
+                get_include_archived_from_params(&http_args).into(),
+            ],
         ))
         .all(&state.database_connection)
-        .await
-        .map_err(|e| {
-            error!(err=?e, "Failed to lookup census node timeseries data");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+        .await?;
 
     // Load all ENRs found in the census
     let record_ids = node_statuses
@@ -1009,40 +5007,92 @@ pub async fn census_timeseries(
         vec![record_ids_str.into()],
     ))
     .all(&state.database_connection)
-    .await
-    .map_err(|e| {
-        error!(err=?e, "Failed to lookup census node timeseries data");
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-    let enr_id_map: HashMap<i32, String> = records.into_iter().map(|r| (r.id, r.raw)).collect();
+    .await?;
+    let mut enr_id_map: HashMap<i32, String> = records.into_iter().map(|r| (r.id, r.raw)).collect();
 
-    let (node_ids, censuses) = decouple_nodes_and_censuses(node_statuses);
-    let node_ids_with_nicknames: Vec<(String, Option<String>)> = node_ids
-        .iter()
-        .map(|id| {
-            if id.len() != 66 {
-                return (id.clone(), None);
+    // The clients and protocol versions of each node during the window, only needed to filter
+    // by them.
+    let mut node_enr_info: HashMap<String, NodeEnrInfo> = HashMap::new();
+    if filter.needs_enr_info() {
+        for status in node_statuses.iter().filter(|status| status.present) {
+            let Some(enr) = enr_id_map
+                .get(&status.enr_id)
+                .and_then(|raw| Enr::from_str(raw).ok())
+            else {
+                continue;
+            };
+            let info = node_enr_info
+                .entry(hex_encode(&status.node_id))
+                .or_default();
+            if let Some(client) = census_client_stats::enr_client(&enr) {
+                info.clients.insert(client);
             }
-            // Node nickname mappings including full node IDs and shortened node IDs
-            let short_id = format!("{}..{}", &id[..6], &id[id.len() - 4..]);
-            let nickname: Option<String> =
-                if let Some(nickname) = node::NODE_NICKNAME_MAP.get(&short_id) {
-                    Some(nickname.clone())
-                } else {
-                    node::NODE_NICKNAME_MAP.get(id).cloned()
-                };
+            if let Some(versions) = census_client_stats::enr_protocol_versions(&enr) {
+                info.protocol_versions.extend(versions);
+            }
+        }
+    }
 
-            (id.clone(), nickname)
+    let (node_ids, mut censuses) = decouple_nodes_and_censuses(node_statuses);
+    let matching: Vec<(usize, String, Option<String>)> = node_ids
+        .into_iter()
+        .enumerate()
+        .map(|(index, id)| {
+            let nickname = node_nickname(&id);
+            (index, id, nickname)
+        })
+        .filter(|(_, id, nickname)| {
+            filter.matches(
+                id,
+                nickname.as_deref(),
+                node_enr_info.get(id),
+                supported.as_ref(),
+            )
         })
         .collect();
+    let total_nodes = matching.len();
+    let page: Vec<(usize, String, Option<String>)> = matching
+        .into_iter()
+        .skip(offset)
+        .take(limit.unwrap_or(usize::MAX))
+        .collect();
+
+    // Keep only the statuses and ENRs of the nodes in the page.
+    for census in censuses.iter_mut() {
+        census.enr_statuses = page
+            .iter()
+            .map(|(index, _, _)| census.enr_statuses[*index])
+            .collect();
+    }
+    let page_enr_ids: HashSet<i32> = censuses
+        .iter()
+        .flat_map(|census| census.enr_statuses.iter().flatten().copied())
+        .collect();
+    enr_id_map.retain(|id, _| page_enr_ids.contains(id));
 
     Ok(Json(CensusTimeSeriesData {
-        node_ids_with_nicknames,
+        node_ids_with_nicknames: page
+            .into_iter()
+            .map(|(_, id, nickname)| (id, nickname))
+            .collect(),
         censuses,
         enrs: enr_id_map,
+        total_nodes,
     }))
 }
 
+/// The nickname of a node, looked up by its full or shortened node id.
+fn node_nickname(id: &str) -> Option<String> {
+    if id.len() != 66 {
+        return None;
+    }
+    let short_id = format!("{}..{}", &id[..6], &id[id.len() - 4..]);
+    node::NODE_NICKNAME_MAP
+        .get(&short_id)
+        .or_else(|| node::NODE_NICKNAME_MAP.get(id))
+        .cloned()
+}
+
 /// Decouples census data from node data, now including ENR strings.
 type NodeIdString = String;
 fn decouple_nodes_and_censuses(
@@ -1067,7 +5117,8 @@ fn decouple_nodes_and_censuses(
         entry.1.insert(hex_id, enr_opt);
     }
 
-    let node_ids: Vec<String> = node_set.into_iter().collect();
+    let mut node_ids: Vec<String> = node_set.into_iter().collect();
+    node_ids.sort();
     let mut censuses: Vec<CensusStatuses> = vec![];
 
     for (census_id, (time, enr_statuses_map)) in census_map {
@@ -1091,31 +5142,42 @@ fn decouple_nodes_and_censuses(
 pub async fn single_census_view(
     params: HttpQuery<HashMap<String, String>>,
     Extension(state): Extension<Arc<State>>,
-) -> Result<HtmlTemplate<SingleCensusViewTemplate>, StatusCode> {
+) -> Result<HtmlTemplate<SingleCensusViewTemplate>, PageError> {
     let subprotocol = get_subprotocol_from_params(&params);
-    let max_census_id = match get_max_census_id(&state, subprotocol).await {
-        None => return Err(StatusCode::from_u16(404).unwrap()),
-        Some(max_census_id) => max_census_id,
-    };
+    let max_census_id = get_max_census_id(&state, subprotocol)
+        .await
+        .ok_or_else(|| WebError::not_found(format!("{} census", subprotocol.as_text())))?;
 
     let census_id: i32 = match params.get("census-id") {
-        None => return Err(StatusCode::from_u16(404).unwrap()),
+        None => return Err(WebError::not_found("Census").into()),
         Some(census_id) => match census_id.parse::<i32>() {
             Ok(census_id) => census_id,
             Err(_) => max_census_id.id,
         },
     };
 
-    let client_diversity_data = match generate_client_diversity_data(&state, census_id).await {
-        None => return Err(StatusCode::from_u16(404).unwrap()),
-        Some(client_diversity_data) => client_diversity_data,
-    };
+    let client_diversity_data =
+        generate_client_diversity_data(&state, census_id, get_exclude_self_from_params(&params))
+            .await?;
 
-    let enr_list =
-        match generate_enr_list_from_census_id(&state, Some(census_id), max_census_id).await {
-            None => return Err(StatusCode::from_u16(404).unwrap()),
-            Some(enr_list) => enr_list,
-        };
+    let enr_list = generate_enr_list_from_census_id(&state, Some(census_id), max_census_id)
+        .await?
+        .ok_or_else(|| WebError::not_found(format!("Census {census_id}")))?;
+
+    let incident = census_incident::get_by_census(census_id, &state.database_connection).await?;
+    let operators = node_operator::get_by_census(census_id, &state.database_connection).await?;
+
+    let node_errors = census_node_error::get_summary(census_id, &state.database_connection).await?;
+
+    let alternate_enr_nodes =
+        census_alternate_enr::count_nodes(census_id, &state.database_connection).await?;
+
+    let target_coverage = census::get_target_coverage(
+        census_id,
+        get_exclude_self_from_params(&params),
+        &state.database_connection,
+    )
+    .await?;
 
     let template = SingleCensusViewTemplate {
         client_diversity_data,
@@ -1124,6 +5186,11 @@ pub async fn single_census_view(
         census_id,
         max_census_id: max_census_id.id,
         created_at: get_created_data_from_census_id(&state, census_id).await,
+        incident,
+        operators,
+        node_errors,
+        alternate_enr_nodes,
+        target_coverage,
     };
 
     Ok(HtmlTemplate(template))
@@ -1133,7 +5200,7 @@ async fn generate_enr_list_from_census_id(
     state: &Arc<State>,
     census_id: Option<i32>,
     max_census_id: MaxCensusId,
-) -> Option<Vec<RawEnr>> {
+) -> Result<Option<Vec<RawEnr>>, DbErr> {
     let census_selection_query = match census_id {
         Some(census_id) => {
             if census_id >= 1 && census_id <= max_census_id.id {
@@ -1144,7 +5211,7 @@ async fn generate_enr_list_from_census_id(
                     .limit(1)
                     .take()
             } else {
-                return None;
+                return Ok(None);
             }
         }
         None => Query::select()
@@ -1173,12 +5240,11 @@ async fn generate_enr_list_from_census_id(
                 .eq(Expr::col((record::Entity, record::Column::Id))),
         );
 
-    Some(
+    Ok(Some(
         RawEnr::find_by_statement(builder.build(&enrs_from_census))
             .all(&state.database_connection)
-            .await
-            .unwrap(),
-    )
+            .await?,
+    ))
 }
 
 async fn get_created_data_from_census_id(state: &Arc<State>, census_id: i32) -> String {
@@ -1242,7 +5308,7 @@ pub struct CalculatedRadiusChartData {
     pub raw_enr: String,
 }
 
-#[derive(FromQueryResult, Serialize)]
+#[derive(FromQueryResult, Serialize, Debug, ToSchema)]
 pub struct ClientDiversityResult {
     pub client_name: String,
     pub client_count: i32,
@@ -1266,7 +5332,7 @@ impl Display for ClientDiversityResult {
 async fn generate_radius_graph_data(
     state: &Arc<State>,
     subprotocol: SubProtocol,
-) -> Vec<CalculatedRadiusChartData> {
+) -> Result<Vec<CalculatedRadiusChartData>, WebError> {
     let radius_chart_data = RadiusChartData::find_by_statement(Statement::from_sql_and_values( DbBackend::Postgres,
     "
         WITH latest_census AS (
@@ -1292,7 +5358,7 @@ async fn generate_radius_graph_data(
             AND census_node.record_id = record.id
             AND record.node_id = node.id
             ",
-     vec![subprotocol.into()])).all(&state.database_connection).await.unwrap();
+     vec![subprotocol.into()])).all(&state.database_connection).await?;
 
     let mut radius_percentages: Vec<CalculatedRadiusChartData> = vec![];
     for i in radius_chart_data {
@@ -1329,7 +5395,7 @@ async fn generate_radius_graph_data(
 
         let node_id_string = hex_encode(node_id_bytes);
         radius_percentages.push(CalculatedRadiusChartData {
-            data_radius: formatted_percentage.parse().unwrap(),
+            data_radius: formatted_percentage.parse().map_err(anyhow::Error::from)?,
             radius_top: i.data_radius[0],
             radius_lower_fraction,
             node_id: u64::from_be_bytes(node_id_high_bytes),
@@ -1338,7 +5404,7 @@ async fn generate_radius_graph_data(
         });
     }
 
-    radius_percentages
+    Ok(radius_percentages)
 }
 
 fn xor_distance_to_fraction(radius_high_bytes: [u8; 4]) -> f64 {
@@ -1367,14 +5433,15 @@ async fn get_max_census_id(state: &Arc<State>, subprotocol: SubProtocol) -> Opti
 async fn generate_client_diversity_data(
     state: &Arc<State>,
     census_id: i32,
-) -> Option<Vec<ClientDiversityResult>> {
-    Some(
-        ClientDiversityResult::find_by_statement(Statement::from_sql_and_values(DbBackend::Postgres,
+    exclude_self: bool,
+) -> Result<Vec<ClientDiversityResult>, DbErr> {
+    ClientDiversityResult::find_by_statement(Statement::from_sql_and_values(DbBackend::Postgres,
         "
             WITH left_table AS (
                 SELECT census_node.record_id
                 FROM census_node
                 WHERE census_node.census_id = $1
+                    AND NOT (census_node.is_self AND $2)
             ),
             right_table AS (
                 SELECT record_id, value
@@ -1387,7 +5454,6 @@ async fn generate_client_diversity_data(
             FROM left_table
             LEFT JOIN right_table ON left_table.record_id = right_table.record_id
             GROUP BY substr(substr(right_table.value, 1, 2), length(substr(right_table.value, 1, 2)), 1)
-            ", vec![census_id.into()])
-        ).all(&state.database_connection).await.unwrap(),
-    )
+            ", vec![census_id.into(), exclude_self.into()])
+        ).all(&state.database_connection).await
 }
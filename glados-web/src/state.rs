@@ -1,5 +1,26 @@
+use std::time::Duration;
+
+use glados_core::feature_flags::FeatureFlags;
 use sea_orm::DatabaseConnection;
 
+use crate::{explorer::ExplorerLinks, live::AuditFeed};
+
 pub struct State {
+    /// Name of the environment whose database this is, like "mainnet".
+    pub environment: String,
     pub database_connection: DatabaseConnection,
+    /// How long responses of aggregate API endpoints are cached.
+    pub cache_ttl: Duration,
+    /// Requests to each expensive endpoint that run at once.
+    pub heavy_endpoint_concurrency: usize,
+    /// Requests to each expensive endpoint that wait to run before more are turned away.
+    pub heavy_endpoint_queue: usize,
+    /// External explorers that content pages link to.
+    pub explorer_links: ExplorerLinks,
+    /// Feature flags of the environment, read from its database at startup.
+    pub feature_flags: FeatureFlags,
+    /// Bearer token required by the admin API routes, which are disabled without one.
+    pub admin_token: Option<String>,
+    /// Audits of the environment as they are recorded, for live dashboards.
+    pub audit_feed: AuditFeed,
 }
@@ -4,11 +4,13 @@ use axum::{
     response::{Html, IntoResponse, Response},
 };
 use entity::{
-    client_info,
-    content::{self, SubProtocol},
-    content_audit, execution_metadata, key_value, node, record,
+    census, census_incident, census_node, census_node_error, client_info,
+    content::{self, ContentSource, SubProtocol},
+    content_audit, enr_change, execution_metadata, feature_flag, key_value, node, node_operator,
+    record, sync_audit, sync_audit_error, sync_audit_segment,
 };
 
+use crate::explorer::ExternalLink;
 use crate::routes::{
     CalculatedRadiusChartData, ClientDiversityResult, PaginatedCensusListResult, RawEnr,
 };
@@ -39,12 +41,22 @@ pub struct SingleCensusViewTemplate {
     pub max_census_id: i32,
     pub node_count: i32,
     pub created_at: String,
+    pub incident: Option<census_incident::Model>,
+    pub operators: Vec<node_operator::CensusOperator>,
+    pub node_errors: Vec<census_node_error::CensusNodeErrorSummary>,
+    /// Number of nodes that presented more than one ENR in the census.
+    pub alternate_enr_nodes: u64,
+    pub target_coverage: Option<census::TargetCoverage>,
 }
 
 #[derive(Template)]
 #[template(path = "census_explorer.html")]
 pub struct CensusExplorerTemplate {}
 
+#[derive(Template)]
+#[template(path = "api_docs.html")]
+pub struct ApiDocsTemplate {}
+
 #[derive(Template)]
 #[template(path = "node_detail.html")]
 pub struct NodeDetailTemplate {
@@ -52,7 +64,14 @@ pub struct NodeDetailTemplate {
     pub latest_enr: Option<record::Model>,
     pub latest_enr_key_value_list: Option<Vec<key_value::Model>>,
     pub enr_list: Vec<record::Model>,
-    pub closest_node_list: Vec<node::ModelWithDistance>,
+    /// Changes of the announced IP address or port, most recent first.
+    pub enr_changes: Vec<enr_change::EnrChangeEntry>,
+    pub closest_node_list: Vec<node::ModelWithExactDistance>,
+    pub metric: node::DistanceMetric,
+    pub operator: Option<node_operator::Model>,
+    /// The subnetwork whose censuses the reliability is computed over.
+    pub reliability_network: SubProtocol,
+    pub reliability: census_node::NodeReliability,
 }
 
 #[derive(Template)]
@@ -84,12 +103,19 @@ pub struct ContentAuditDetailTemplate {
     pub audit: content_audit::Model,
     pub content: content::Model,
     pub execution_metadata: Option<execution_metadata::Model>,
+    /// The census radii are shown from, and how long before the audit it started.
+    pub census: Option<census::Model>,
+    pub census_minutes_before_audit: Option<i64>,
+    pub external_links: Vec<ExternalLink>,
 }
 
 #[derive(Template)]
 #[template(path = "contentkey_list.html")]
 pub struct ContentKeyListTemplate {
     pub contentkey_list: Vec<content::Model>,
+    /// The source the list is filtered to, if any.
+    pub source: Option<ContentSource>,
+    pub sources: Vec<ContentSource>,
 }
 
 #[derive(Template)]
@@ -114,6 +140,30 @@ pub struct ContentKeyDetailTemplate {
     pub content_kind: String,
     pub block_number: Option<i32>,
     pub contentaudit_list: Vec<content_audit::Model>,
+    pub external_links: Vec<ExternalLink>,
+}
+
+#[derive(Template)]
+#[template(path = "sync_dashboard.html")]
+pub struct SyncDashboardTemplate {
+    pub sync_audits: Vec<sync_audit::Model>,
+    pub segments: Vec<sync_audit_segment::Model>,
+    pub errors: Vec<sync_audit_error::Model>,
+}
+
+#[derive(Template)]
+#[template(path = "diagnostics.html")]
+pub struct DiagnosticsTemplate {
+    pub environment: String,
+    pub feature_flags: Vec<feature_flag::Model>,
+}
+
+#[derive(Template)]
+#[template(path = "error.html")]
+pub struct ErrorTemplate {
+    pub status: u16,
+    pub reason: String,
+    pub message: String,
 }
 
 pub struct HtmlTemplate<T: Template>(pub T);
@@ -0,0 +1,98 @@
+//! Concurrency limits for expensive endpoints.
+//!
+//! When a dashboard link is shared widely, many requests for the same heavy query can arrive
+//! at once. Each limited endpoint runs a few requests at a time, lets a few more wait for their
+//! turn, and turns the rest away with `503 Service Unavailable` rather than piling queries up
+//! on the shared database.
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::{header, HeaderValue, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tokio::sync::Semaphore;
+use tracing::warn;
+
+/// Seconds a client turned away is asked to wait before trying again.
+const RETRY_AFTER_SECONDS: &str = "5";
+
+/// The requests to a single endpoint that may run, and wait to run, at once.
+pub struct ConcurrencyLimit {
+    running: Semaphore,
+    queue_capacity: usize,
+    waiting: AtomicUsize,
+}
+
+impl ConcurrencyLimit {
+    pub fn new(concurrency: usize, queue_capacity: usize) -> Self {
+        ConcurrencyLimit {
+            running: Semaphore::new(concurrency),
+            queue_capacity,
+            waiting: AtomicUsize::new(0),
+        }
+    }
+}
+
+/// A place in the queue of an endpoint, given up when dropped, including when the client goes
+/// away while waiting.
+struct QueueSlot<'a>(&'a AtomicUsize);
+
+impl<'a> QueueSlot<'a> {
+    fn take(waiting: &'a AtomicUsize, capacity: usize) -> Option<Self> {
+        waiting
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |waiting| {
+                (waiting < capacity).then_some(waiting + 1)
+            })
+            .ok()
+            .map(|_| QueueSlot(waiting))
+    }
+}
+
+impl Drop for QueueSlot<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Runs the request once the endpoint has room for it, or answers `503 Service Unavailable`
+/// if its queue is full.
+pub async fn limit_concurrency(
+    State(limit): State<Arc<ConcurrencyLimit>>,
+    request: Request<Body>,
+    next: Next<Body>,
+) -> Response {
+    let _permit = match limit.running.try_acquire() {
+        Ok(permit) => permit,
+        Err(_) => {
+            let Some(slot) = QueueSlot::take(&limit.waiting, limit.queue_capacity) else {
+                warn!(
+                    path = request.uri().path(),
+                    "Endpoint busy, turning request away"
+                );
+                return (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    [(
+                        header::RETRY_AFTER,
+                        HeaderValue::from_static(RETRY_AFTER_SECONDS),
+                    )],
+                    "This page is in high demand, please try again in a few seconds.",
+                )
+                    .into_response();
+            };
+            let permit = limit
+                .running
+                .acquire()
+                .await
+                .expect("the semaphore is never closed");
+            drop(slot);
+            permit
+        }
+    };
+    next.run(request).await
+}
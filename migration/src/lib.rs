@@ -16,6 +16,56 @@ mod m20240720_111606_create_census_index;
 mod m20240814_121507_census_subnetwork;
 mod m20240919_121611_census_subnetwork_index;
 mod m20241010_151313_audit_stats_performance;
+mod m20241016_093512_audit_stats_errored_pending;
+mod m20241017_140226_create_audit_latest;
+mod m20241018_101245_create_node_geo;
+mod m20241019_083015_create_sync_audit;
+mod m20241020_121530_create_block_audit;
+mod m20241021_094410_content_audit_census;
+mod m20241022_081530_create_audit_coverage;
+mod m20241023_100512_failed_audit_backoff;
+mod m20241024_143020_content_source;
+mod m20241025_091200_create_saved_view;
+mod m20241026_112045_census_liveness_stats;
+mod m20241027_084510_create_census_incident;
+mod m20241028_102030_create_node_operator;
+mod m20241029_091530_create_content_flapping;
+mod m20241030_140215_create_round_trip_audit;
+mod m20241101_103045_create_feature_flag;
+mod m20241102_084512_content_audit_trace_requested;
+mod m20241103_152230_stale_pending_audits;
+mod m20241104_091015_create_audit_task;
+mod m20241105_134020_census_node_ping;
+mod m20241106_102215_create_client_version_sighting;
+mod m20241107_093045_create_transfer_failure_daily;
+mod m20241108_101530_census_node_is_self;
+mod m20241109_091020_create_census_node_error;
+mod m20241110_084530_census_node_reachability;
+mod m20241111_101045_content_audit_selection_lag;
+mod m20241112_093015_create_content_first_success;
+mod m20241113_084020_create_audit_stats_client;
+mod m20241114_102230_create_reseed_attempt;
+mod m20241115_091540_archived_node_and_content;
+mod m20241116_083012_content_audit_duration;
+mod m20241117_101530_client_info_parsed_version;
+mod m20241118_094505_create_coverage_sample;
+mod m20241119_083540_failed_keys_indexes;
+mod m20241120_091530_create_propagation_audit;
+mod m20241121_101045_census_nodes_archived_at;
+mod m20241122_093015_create_census_alternate_enr;
+mod m20241124_081530_content_audit_timeout;
+mod m20241125_074210_audit_stats_beacon;
+mod m20241126_093240_census_client_diversity;
+mod m20241127_101530_create_site_banner;
+mod m20241128_083015_node_geo_record;
+mod m20241129_094500_create_enr_change;
+mod m20241130_101500_census_target_node_id;
+mod m20241201_090000_content_audit_recorded_notify;
+mod m20241202_083000_create_node_serve_latency;
+mod m20241203_080000_key_value_record_key_unique;
+mod m20241203_090000_failure_streak_invalid_content;
+mod m20241203_100000_content_audit_fk_indexes;
+mod m20241204_080000_create_audit_worker;
 
 pub struct Migrator;
 
@@ -39,6 +89,56 @@ impl MigratorTrait for Migrator {
             Box::new(m20240814_121507_census_subnetwork::Migration),
             Box::new(m20240919_121611_census_subnetwork_index::Migration),
             Box::new(m20241010_151313_audit_stats_performance::Migration),
+            Box::new(m20241016_093512_audit_stats_errored_pending::Migration),
+            Box::new(m20241017_140226_create_audit_latest::Migration),
+            Box::new(m20241018_101245_create_node_geo::Migration),
+            Box::new(m20241019_083015_create_sync_audit::Migration),
+            Box::new(m20241020_121530_create_block_audit::Migration),
+            Box::new(m20241021_094410_content_audit_census::Migration),
+            Box::new(m20241022_081530_create_audit_coverage::Migration),
+            Box::new(m20241023_100512_failed_audit_backoff::Migration),
+            Box::new(m20241024_143020_content_source::Migration),
+            Box::new(m20241025_091200_create_saved_view::Migration),
+            Box::new(m20241026_112045_census_liveness_stats::Migration),
+            Box::new(m20241027_084510_create_census_incident::Migration),
+            Box::new(m20241028_102030_create_node_operator::Migration),
+            Box::new(m20241029_091530_create_content_flapping::Migration),
+            Box::new(m20241030_140215_create_round_trip_audit::Migration),
+            Box::new(m20241101_103045_create_feature_flag::Migration),
+            Box::new(m20241102_084512_content_audit_trace_requested::Migration),
+            Box::new(m20241103_152230_stale_pending_audits::Migration),
+            Box::new(m20241104_091015_create_audit_task::Migration),
+            Box::new(m20241105_134020_census_node_ping::Migration),
+            Box::new(m20241106_102215_create_client_version_sighting::Migration),
+            Box::new(m20241107_093045_create_transfer_failure_daily::Migration),
+            Box::new(m20241108_101530_census_node_is_self::Migration),
+            Box::new(m20241109_091020_create_census_node_error::Migration),
+            Box::new(m20241110_084530_census_node_reachability::Migration),
+            Box::new(m20241111_101045_content_audit_selection_lag::Migration),
+            Box::new(m20241112_093015_create_content_first_success::Migration),
+            Box::new(m20241113_084020_create_audit_stats_client::Migration),
+            Box::new(m20241114_102230_create_reseed_attempt::Migration),
+            Box::new(m20241115_091540_archived_node_and_content::Migration),
+            Box::new(m20241116_083012_content_audit_duration::Migration),
+            Box::new(m20241117_101530_client_info_parsed_version::Migration),
+            Box::new(m20241118_094505_create_coverage_sample::Migration),
+            Box::new(m20241119_083540_failed_keys_indexes::Migration),
+            Box::new(m20241120_091530_create_propagation_audit::Migration),
+            Box::new(m20241121_101045_census_nodes_archived_at::Migration),
+            Box::new(m20241122_093015_create_census_alternate_enr::Migration),
+            Box::new(m20241124_081530_content_audit_timeout::Migration),
+            Box::new(m20241125_074210_audit_stats_beacon::Migration),
+            Box::new(m20241126_093240_census_client_diversity::Migration),
+            Box::new(m20241127_101530_create_site_banner::Migration),
+            Box::new(m20241128_083015_node_geo_record::Migration),
+            Box::new(m20241129_094500_create_enr_change::Migration),
+            Box::new(m20241130_101500_census_target_node_id::Migration),
+            Box::new(m20241201_090000_content_audit_recorded_notify::Migration),
+            Box::new(m20241202_083000_create_node_serve_latency::Migration),
+            Box::new(m20241203_080000_key_value_record_key_unique::Migration),
+            Box::new(m20241203_090000_failure_streak_invalid_content::Migration),
+            Box::new(m20241203_100000_content_audit_fk_indexes::Migration),
+            Box::new(m20241204_080000_create_audit_worker::Migration),
         ]
     }
 }
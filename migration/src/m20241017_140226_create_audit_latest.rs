@@ -0,0 +1,132 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const INDEX_AUDIT_LATEST_RESULT: &str = "idx_audit_latest_result";
+
+/// Keeps `audit_latest` up to date with the most recent audit of each content key.
+const CREATE_TRIGGER: &str = "
+CREATE OR REPLACE FUNCTION audit_result_latest() RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO audit_latest (content_key, audit_id, strategy_used, result, created_at)
+    VALUES (NEW.content_key, NEW.id, NEW.strategy_used, NEW.result, NEW.created_at)
+    ON CONFLICT (content_key) DO UPDATE SET
+        audit_id = EXCLUDED.audit_id,
+        strategy_used = EXCLUDED.strategy_used,
+        result = EXCLUDED.result,
+        created_at = EXCLUDED.created_at
+    WHERE audit_latest.created_at <= EXCLUDED.created_at;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER audit_result_latest
+AFTER INSERT OR UPDATE OF result ON content_audit
+FOR EACH ROW EXECUTE FUNCTION audit_result_latest();
+";
+
+const DROP_TRIGGER: &str = "
+DROP TRIGGER IF EXISTS audit_result_latest ON content_audit;
+DROP FUNCTION IF EXISTS audit_result_latest();
+";
+
+const BACKFILL: &str = "
+INSERT INTO audit_latest (content_key, audit_id, strategy_used, result, created_at)
+SELECT DISTINCT ON (content_key) content_key, id, strategy_used, result, created_at
+FROM content_audit
+ORDER BY content_key, created_at DESC, id DESC
+ON CONFLICT (content_key) DO NOTHING;
+";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AuditLatest::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AuditLatest::ContentKey)
+                            .integer()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_audit_latest_content_key")
+                            .from(AuditLatest::Table, AuditLatest::ContentKey)
+                            .to(Content::Table, Content::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(ColumnDef::new(AuditLatest::AuditId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_audit_latest_audit_id")
+                            .from(AuditLatest::Table, AuditLatest::AuditId)
+                            .to(ContentAudit::Table, ContentAudit::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(ColumnDef::new(AuditLatest::StrategyUsed).integer())
+                    .col(ColumnDef::new(AuditLatest::Result).integer().not_null())
+                    .col(
+                        ColumnDef::new(AuditLatest::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(INDEX_AUDIT_LATEST_RESULT)
+                    .table(AuditLatest::Table)
+                    .col(AuditLatest::Result)
+                    .col(AuditLatest::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        let conn = manager.get_connection();
+        conn.execute_unprepared(CREATE_TRIGGER).await?;
+        conn.execute_unprepared(BACKFILL).await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(DROP_TRIGGER)
+            .await?;
+        manager
+            .drop_table(Table::drop().table(AuditLatest::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum AuditLatest {
+    Table,
+    ContentKey,
+    AuditId,
+    StrategyUsed,
+    Result,
+    CreatedAt,
+}
+
+#[derive(Iden)]
+enum Content {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum ContentAudit {
+    Table,
+    Id,
+}
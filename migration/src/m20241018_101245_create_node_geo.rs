@@ -0,0 +1,62 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(NodeGeo::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(NodeGeo::NodeId)
+                            .integer()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_node_geo_node_id")
+                            .from(NodeGeo::Table, NodeGeo::NodeId)
+                            .to(Node::Table, Node::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(ColumnDef::new(NodeGeo::CountryCode).string_len(2))
+                    .col(ColumnDef::new(NodeGeo::Asn).integer())
+                    .col(ColumnDef::new(NodeGeo::AsnOrganization).string())
+                    .col(
+                        ColumnDef::new(NodeGeo::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(NodeGeo::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum NodeGeo {
+    Table,
+    NodeId,
+    CountryCode,
+    Asn,
+    AsnOrganization,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum Node {
+    Table,
+    Id,
+}
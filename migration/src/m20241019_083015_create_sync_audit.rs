@@ -0,0 +1,222 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const INDEX_SEGMENT_SYNC_AUDIT: &str = "idx_sync_audit_segment_sync_audit_id";
+const INDEX_ERROR_SYNC_AUDIT: &str = "idx_sync_audit_error_sync_audit_id";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SyncAudit::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SyncAudit::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(SyncAudit::StartedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SyncAudit::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(SyncAudit::StartBlock).integer().not_null())
+                    .col(ColumnDef::new(SyncAudit::EndBlock).integer().not_null())
+                    .col(ColumnDef::new(SyncAudit::SegmentSize).integer().not_null())
+                    .col(ColumnDef::new(SyncAudit::NextBlock).integer().not_null())
+                    .col(ColumnDef::new(SyncAudit::Status).integer().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(SyncAuditSegment::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SyncAuditSegment::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(SyncAuditSegment::SyncAuditId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_sync_audit_segment_sync_audit_id")
+                            .from(SyncAuditSegment::Table, SyncAuditSegment::SyncAuditId)
+                            .to(SyncAudit::Table, SyncAudit::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(
+                        ColumnDef::new(SyncAuditSegment::StartBlock)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SyncAuditSegment::EndBlock)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SyncAuditSegment::CompletedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SyncAuditSegment::NumFound)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SyncAuditSegment::NumNotFound)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SyncAuditSegment::NumErrored)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(SyncAuditSegment::LatencyMinMs).integer())
+                    .col(ColumnDef::new(SyncAuditSegment::LatencyMeanMs).integer())
+                    .col(ColumnDef::new(SyncAuditSegment::LatencyMedianMs).integer())
+                    .col(ColumnDef::new(SyncAuditSegment::LatencyP99Ms).integer())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(INDEX_SEGMENT_SYNC_AUDIT)
+                    .table(SyncAuditSegment::Table)
+                    .col(SyncAuditSegment::SyncAuditId)
+                    .col(SyncAuditSegment::StartBlock)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(SyncAuditError::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SyncAuditError::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(SyncAuditError::SyncAuditId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_sync_audit_error_sync_audit_id")
+                            .from(SyncAuditError::Table, SyncAuditError::SyncAuditId)
+                            .to(SyncAudit::Table, SyncAudit::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(
+                        ColumnDef::new(SyncAuditError::BlockNumber)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(SyncAuditError::ContentKey).binary())
+                    .col(ColumnDef::new(SyncAuditError::Error).text().not_null())
+                    .col(
+                        ColumnDef::new(SyncAuditError::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(INDEX_ERROR_SYNC_AUDIT)
+                    .table(SyncAuditError::Table)
+                    .col(SyncAuditError::SyncAuditId)
+                    .col(SyncAuditError::BlockNumber)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SyncAuditError::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(SyncAuditSegment::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(SyncAudit::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum SyncAudit {
+    Table,
+    Id,
+    StartedAt,
+    UpdatedAt,
+    StartBlock,
+    EndBlock,
+    SegmentSize,
+    NextBlock,
+    Status,
+}
+
+#[derive(Iden)]
+enum SyncAuditSegment {
+    Table,
+    Id,
+    SyncAuditId,
+    StartBlock,
+    EndBlock,
+    CompletedAt,
+    NumFound,
+    NumNotFound,
+    NumErrored,
+    LatencyMinMs,
+    LatencyMeanMs,
+    LatencyMedianMs,
+    LatencyP99Ms,
+}
+
+#[derive(Iden)]
+enum SyncAuditError {
+    Table,
+    Id,
+    SyncAuditId,
+    BlockNumber,
+    ContentKey,
+    Error,
+    CreatedAt,
+}
@@ -0,0 +1,103 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const INDEX_CREATED_AT: &str = "idx_block_audit_created_at";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(BlockAudit::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(BlockAudit::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(BlockAudit::BlockNumber).integer().not_null())
+                    .col(
+                        ColumnDef::new(BlockAudit::HeaderAuditId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_block_audit_header_audit_id")
+                            .from(BlockAudit::Table, BlockAudit::HeaderAuditId)
+                            .to(ContentAudit::Table, ContentAudit::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(ColumnDef::new(BlockAudit::BodyAuditId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_block_audit_body_audit_id")
+                            .from(BlockAudit::Table, BlockAudit::BodyAuditId)
+                            .to(ContentAudit::Table, ContentAudit::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(
+                        ColumnDef::new(BlockAudit::ReceiptsAuditId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_block_audit_receipts_audit_id")
+                            .from(BlockAudit::Table, BlockAudit::ReceiptsAuditId)
+                            .to(ContentAudit::Table, ContentAudit::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(ColumnDef::new(BlockAudit::Verdict).integer().not_null())
+                    .col(
+                        ColumnDef::new(BlockAudit::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(INDEX_CREATED_AT)
+                    .table(BlockAudit::Table)
+                    .col(BlockAudit::CreatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(BlockAudit::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum BlockAudit {
+    Table,
+    Id,
+    BlockNumber,
+    HeaderAuditId,
+    BodyAuditId,
+    ReceiptsAuditId,
+    Verdict,
+    CreatedAt,
+}
+
+#[derive(Iden)]
+enum ContentAudit {
+    Table,
+    Id,
+}
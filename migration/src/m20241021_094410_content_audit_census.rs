@@ -0,0 +1,92 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Links each new audit to the latest census of its content's subnetwork started before the
+/// audit, which is the census its radii are taken from.
+const CREATE_TRIGGER: &str = "
+CREATE OR REPLACE FUNCTION content_audit_census() RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.census_id IS NULL THEN
+        NEW.census_id := (
+            SELECT census.id
+            FROM census
+            JOIN content ON content.id = NEW.content_key
+            WHERE census.sub_network = content.protocol_id
+                AND census.started_at <= NEW.created_at
+            ORDER BY census.started_at DESC
+            LIMIT 1
+        );
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER content_audit_census
+BEFORE INSERT ON content_audit
+FOR EACH ROW EXECUTE FUNCTION content_audit_census();
+";
+
+const DROP_TRIGGER: &str = "
+DROP TRIGGER IF EXISTS content_audit_census ON content_audit;
+DROP FUNCTION IF EXISTS content_audit_census();
+";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ContentAudit::Table)
+                    .add_column(ColumnDef::new(ContentAudit::CensusId).integer())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("FK_content_audit_census_id")
+                            .from_tbl(ContentAudit::Table)
+                            .from_col(ContentAudit::CensusId)
+                            .to_tbl(Census::Table)
+                            .to_col(Census::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared(CREATE_TRIGGER)
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(DROP_TRIGGER)
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ContentAudit::Table)
+                    .drop_foreign_key(Alias::new("FK_content_audit_census_id"))
+                    .drop_column(ContentAudit::CensusId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum ContentAudit {
+    Table,
+    CensusId,
+}
+
+#[derive(Iden)]
+enum Census {
+    Table,
+    Id,
+}
@@ -0,0 +1,77 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const INDEX_DAY_STRATEGY: &str = "idx_audit_coverage_day_strategy";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AuditCoverage::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AuditCoverage::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(AuditCoverage::Day)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(AuditCoverage::Strategy).integer().not_null())
+                    .col(
+                        ColumnDef::new(AuditCoverage::NumEligible)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AuditCoverage::NumAudited)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AuditCoverage::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(INDEX_DAY_STRATEGY)
+                    .table(AuditCoverage::Table)
+                    .col(AuditCoverage::Day)
+                    .col(AuditCoverage::Strategy)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AuditCoverage::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum AuditCoverage {
+    Table,
+    Id,
+    Day,
+    Strategy,
+    NumEligible,
+    NumAudited,
+    UpdatedAt,
+}
@@ -0,0 +1,157 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const INDEX_RETRY_AFTER: &str = "idx_audit_latest_retry_after";
+
+/// How long to wait before auditing a key again, after a number of consecutive failed audits:
+/// 1 hour, 6 hours, 1 day, then weekly.
+const CREATE_BACKOFF_FUNCTION: &str = "
+CREATE OR REPLACE FUNCTION failed_audit_backoff(failures INTEGER) RETURNS INTERVAL AS $$
+    SELECT CASE
+        WHEN failures <= 1 THEN INTERVAL '1 hour'
+        WHEN failures = 2 THEN INTERVAL '6 hours'
+        WHEN failures = 3 THEN INTERVAL '1 day'
+        ELSE INTERVAL '7 days'
+    END;
+$$ LANGUAGE sql IMMUTABLE STRICT;
+";
+
+/// Replaces the `audit_result_latest` trigger function so that it also tracks the failure
+/// streak of each key. Failures extend the streak, successes and invalid content end it, and
+/// pending or errored audits leave it unchanged.
+const REPLACE_TRIGGER_FUNCTION: &str = "
+CREATE OR REPLACE FUNCTION audit_result_latest() RETURNS TRIGGER AS $$
+DECLARE
+    failures INTEGER;
+BEGIN
+    SELECT consecutive_failures INTO failures
+    FROM audit_latest
+    WHERE content_key = NEW.content_key;
+    failures := COALESCE(failures, 0);
+    IF NEW.result = 0 THEN
+        failures := failures + 1;
+    ELSIF NEW.result IN (1, 4) THEN
+        failures := 0;
+    END IF;
+
+    INSERT INTO audit_latest (
+        content_key, audit_id, strategy_used, result, created_at, consecutive_failures, retry_after
+    )
+    VALUES (
+        NEW.content_key, NEW.id, NEW.strategy_used, NEW.result, NEW.created_at, failures,
+        CASE WHEN failures = 0 THEN NULL ELSE NEW.created_at + failed_audit_backoff(failures) END
+    )
+    ON CONFLICT (content_key) DO UPDATE SET
+        audit_id = EXCLUDED.audit_id,
+        strategy_used = EXCLUDED.strategy_used,
+        result = EXCLUDED.result,
+        created_at = EXCLUDED.created_at,
+        consecutive_failures = EXCLUDED.consecutive_failures,
+        retry_after = EXCLUDED.retry_after
+    WHERE audit_latest.created_at <= EXCLUDED.created_at;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+";
+
+/// Counts the failures since the last success of every key that has failed.
+const BACKFILL: &str = "
+WITH last_success AS (
+    SELECT content_key, MAX(created_at) AS created_at
+    FROM content_audit
+    WHERE result IN (1, 4)
+    GROUP BY content_key
+),
+streaks AS (
+    SELECT content_audit.content_key, COUNT(*)::integer AS failures
+    FROM content_audit
+    LEFT JOIN last_success ON last_success.content_key = content_audit.content_key
+    WHERE content_audit.result = 0
+        AND (last_success.created_at IS NULL OR content_audit.created_at > last_success.created_at)
+    GROUP BY content_audit.content_key
+)
+UPDATE audit_latest
+SET consecutive_failures = streaks.failures,
+    retry_after = audit_latest.created_at + failed_audit_backoff(streaks.failures)
+FROM streaks
+WHERE streaks.content_key = audit_latest.content_key;
+";
+
+const RESTORE_TRIGGER_FUNCTION: &str = "
+CREATE OR REPLACE FUNCTION audit_result_latest() RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO audit_latest (content_key, audit_id, strategy_used, result, created_at)
+    VALUES (NEW.content_key, NEW.id, NEW.strategy_used, NEW.result, NEW.created_at)
+    ON CONFLICT (content_key) DO UPDATE SET
+        audit_id = EXCLUDED.audit_id,
+        strategy_used = EXCLUDED.strategy_used,
+        result = EXCLUDED.result,
+        created_at = EXCLUDED.created_at
+    WHERE audit_latest.created_at <= EXCLUDED.created_at;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP FUNCTION IF EXISTS failed_audit_backoff(INTEGER);
+";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AuditLatest::Table)
+                    .add_column(
+                        ColumnDef::new(AuditLatest::ConsecutiveFailures)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .add_column(ColumnDef::new(AuditLatest::RetryAfter).timestamp_with_time_zone())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(INDEX_RETRY_AFTER)
+                    .table(AuditLatest::Table)
+                    .col(AuditLatest::RetryAfter)
+                    .to_owned(),
+            )
+            .await?;
+
+        let conn = manager.get_connection();
+        conn.execute_unprepared(CREATE_BACKOFF_FUNCTION).await?;
+        conn.execute_unprepared(REPLACE_TRIGGER_FUNCTION).await?;
+        conn.execute_unprepared(BACKFILL).await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(RESTORE_TRIGGER_FUNCTION)
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AuditLatest::Table)
+                    .drop_column(AuditLatest::ConsecutiveFailures)
+                    .drop_column(AuditLatest::RetryAfter)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum AuditLatest {
+    Table,
+    ConsecutiveFailures,
+    RetryAfter,
+}
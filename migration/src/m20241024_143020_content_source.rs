@@ -0,0 +1,50 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const INDEX_CONTENT_SOURCE: &str = "idx_content_source";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Left empty for existing keys, whose source is unknown.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Content::Table)
+                    .add_column(ColumnDef::new(Content::Source).integer())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name(INDEX_CONTENT_SOURCE)
+                    .table(Content::Table)
+                    .col(Content::Source)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(Index::drop().name(INDEX_CONTENT_SOURCE).to_owned())
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Content::Table)
+                    .drop_column(Content::Source)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum Content {
+    Table,
+    Source,
+}
@@ -0,0 +1,67 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const INDEX_NETWORK_NAME: &str = "idx_saved_view_network_name";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SavedView::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SavedView::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(SavedView::Name).text().not_null())
+                    .col(ColumnDef::new(SavedView::Network).integer().not_null())
+                    .col(ColumnDef::new(SavedView::Strategy).text().not_null())
+                    .col(ColumnDef::new(SavedView::ContentType).text().not_null())
+                    .col(ColumnDef::new(SavedView::Success).text().not_null())
+                    .col(
+                        ColumnDef::new(SavedView::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(INDEX_NETWORK_NAME)
+                    .table(SavedView::Table)
+                    .col(SavedView::Network)
+                    .col(SavedView::Name)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SavedView::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum SavedView {
+    Table,
+    Id,
+    Name,
+    Network,
+    Strategy,
+    ContentType,
+    Success,
+    UpdatedAt,
+}
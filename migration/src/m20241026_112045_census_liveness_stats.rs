@@ -0,0 +1,116 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const INDEX_CENSUS_CLIENT: &str = "idx_census_client_stats_census_client";
+
+/// The outcomes counted for each census, both in total on the census and per client.
+const COUNTS: [LivenessCount; 7] = [
+    LivenessCount::Checked,
+    LivenessCount::ClientInfoParsed,
+    LivenessCount::Pongs,
+    LivenessCount::ErrorPongs,
+    LivenessCount::UndecodablePongs,
+    LivenessCount::UnexpectedPongs,
+    LivenessCount::Timeouts,
+];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Left empty for censuses taken before liveness checks were counted.
+        let mut alter = Table::alter();
+        alter.table(Census::Table);
+        for count in COUNTS {
+            alter.add_column(ColumnDef::new(count).integer());
+        }
+        manager.alter_table(alter.to_owned()).await?;
+
+        let mut create = Table::create();
+        create
+            .table(CensusClientStats::Table)
+            .if_not_exists()
+            .col(
+                ColumnDef::new(CensusClientStats::Id)
+                    .integer()
+                    .not_null()
+                    .auto_increment()
+                    .primary_key(),
+            )
+            .col(
+                ColumnDef::new(CensusClientStats::CensusId)
+                    .integer()
+                    .not_null(),
+            )
+            .col(ColumnDef::new(CensusClientStats::Client).text().not_null());
+        for count in COUNTS {
+            create.col(ColumnDef::new(count).integer().not_null());
+        }
+        create.foreign_key(
+            ForeignKey::create()
+                .name("FK_census_client_stats_census_id")
+                .from(CensusClientStats::Table, CensusClientStats::CensusId)
+                .to(Census::Table, Census::Id)
+                .on_delete(ForeignKeyAction::Cascade)
+                .on_update(ForeignKeyAction::Cascade),
+        );
+        manager.create_table(create.to_owned()).await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(INDEX_CENSUS_CLIENT)
+                    .table(CensusClientStats::Table)
+                    .col(CensusClientStats::CensusId)
+                    .col(CensusClientStats::Client)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CensusClientStats::Table).to_owned())
+            .await?;
+        let mut alter = Table::alter();
+        alter.table(Census::Table);
+        for count in COUNTS {
+            alter.drop_column(count);
+        }
+        manager.alter_table(alter.to_owned()).await
+    }
+}
+
+#[derive(Iden)]
+enum Census {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum CensusClientStats {
+    Table,
+    Id,
+    CensusId,
+    Client,
+}
+
+#[derive(Iden, Clone, Copy)]
+enum LivenessCount {
+    #[iden = "num_checked"]
+    Checked,
+    #[iden = "num_client_info_parsed"]
+    ClientInfoParsed,
+    #[iden = "num_pongs"]
+    Pongs,
+    #[iden = "num_error_pongs"]
+    ErrorPongs,
+    #[iden = "num_undecodable_pongs"]
+    UndecodablePongs,
+    #[iden = "num_unexpected_pongs"]
+    UnexpectedPongs,
+    #[iden = "num_timeouts"]
+    Timeouts,
+}
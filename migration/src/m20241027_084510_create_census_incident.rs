@@ -0,0 +1,108 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(CensusIncident::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(CensusIncident::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(CensusIncident::CensusId)
+                            .integer()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(CensusIncident::NodeCount)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CensusIncident::TrailingAverage)
+                            .double()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CensusIncident::InitialNodes)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CensusIncident::LivenessChecks)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CensusIncident::LivenessErrors)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CensusIncident::Enumerations)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CensusIncident::EnumerationErrors)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(CensusIncident::RoutingTable).text())
+                    .col(
+                        ColumnDef::new(CensusIncident::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_census_incident_census_id")
+                            .from(CensusIncident::Table, CensusIncident::CensusId)
+                            .to(Census::Table, Census::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CensusIncident::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum Census {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum CensusIncident {
+    Table,
+    Id,
+    CensusId,
+    NodeCount,
+    TrailingAverage,
+    InitialNodes,
+    LivenessChecks,
+    LivenessErrors,
+    Enumerations,
+    EnumerationErrors,
+    RoutingTable,
+    CreatedAt,
+}
@@ -0,0 +1,68 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(NodeOperator::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(NodeOperator::NodeId)
+                            .integer()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_node_operator_node_id")
+                            .from(NodeOperator::Table, NodeOperator::NodeId)
+                            .to(Node::Table, Node::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(ColumnDef::new(NodeOperator::DisplayName).text().not_null())
+                    .col(ColumnDef::new(NodeOperator::Contact).text())
+                    .col(ColumnDef::new(NodeOperator::Signature).binary().not_null())
+                    .col(
+                        ColumnDef::new(NodeOperator::SignedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(NodeOperator::VerifiedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(NodeOperator::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum NodeOperator {
+    Table,
+    NodeId,
+    DisplayName,
+    Contact,
+    Signature,
+    SignedAt,
+    VerifiedAt,
+}
+
+#[derive(Iden)]
+enum Node {
+    Table,
+    Id,
+}
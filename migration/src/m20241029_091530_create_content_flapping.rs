@@ -0,0 +1,92 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const INDEX_FLAPPING_SCORE: &str = "idx_content_flapping_score";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ContentFlapping::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ContentFlapping::ContentKey)
+                            .integer()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_content_flapping_content_key")
+                            .from(ContentFlapping::Table, ContentFlapping::ContentKey)
+                            .to(Content::Table, Content::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(
+                        ColumnDef::new(ContentFlapping::NumAudits)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ContentFlapping::NumFlips)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ContentFlapping::FlappingScore)
+                            .double()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ContentFlapping::WindowStart)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ContentFlapping::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(INDEX_FLAPPING_SCORE)
+                    .table(ContentFlapping::Table)
+                    .col(ContentFlapping::FlappingScore)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ContentFlapping::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum ContentFlapping {
+    Table,
+    ContentKey,
+    NumAudits,
+    NumFlips,
+    FlappingScore,
+    WindowStart,
+    UpdatedAt,
+}
+
+#[derive(Iden)]
+enum Content {
+    Table,
+    Id,
+}
@@ -0,0 +1,105 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const INDEX_OFFERED_AT: &str = "idx_round_trip_audit_offered_at";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(RoundTripAudit::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(RoundTripAudit::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(RoundTripAudit::ContentKey)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_round_trip_audit_content_key")
+                            .from(RoundTripAudit::Table, RoundTripAudit::ContentKey)
+                            .to(Content::Table, Content::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(
+                        ColumnDef::new(RoundTripAudit::OfferedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(RoundTripAudit::NumPeers)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(RoundTripAudit::NumAttempts)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(RoundTripAudit::AuditId).integer())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_round_trip_audit_audit_id")
+                            .from(RoundTripAudit::Table, RoundTripAudit::AuditId)
+                            .to(ContentAudit::Table, ContentAudit::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(ColumnDef::new(RoundTripAudit::AvailableAt).timestamp_with_time_zone())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(INDEX_OFFERED_AT)
+                    .table(RoundTripAudit::Table)
+                    .col(RoundTripAudit::OfferedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RoundTripAudit::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum RoundTripAudit {
+    Table,
+    Id,
+    ContentKey,
+    OfferedAt,
+    NumPeers,
+    NumAttempts,
+    AuditId,
+    AvailableAt,
+}
+
+#[derive(Iden)]
+enum Content {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum ContentAudit {
+    Table,
+    Id,
+}
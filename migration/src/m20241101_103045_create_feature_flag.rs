@@ -0,0 +1,52 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(FeatureFlag::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(FeatureFlag::Name)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(FeatureFlag::Enabled)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(ColumnDef::new(FeatureFlag::Description).string())
+                    .col(
+                        ColumnDef::new(FeatureFlag::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(FeatureFlag::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum FeatureFlag {
+    Table,
+    Name,
+    Enabled,
+    Description,
+    UpdatedAt,
+}
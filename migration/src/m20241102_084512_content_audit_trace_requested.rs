@@ -0,0 +1,51 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Earlier audits only kept a trace when one was requested and returned, so audits whose trace
+/// request errored are left as not requested.
+const BACKFILL: &str = "
+UPDATE content_audit SET trace_requested = TRUE WHERE trace <> '';
+";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ContentAudit::Table)
+                    .add_column(
+                        ColumnDef::new(ContentAudit::TraceRequested)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .get_connection()
+            .execute_unprepared(BACKFILL)
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ContentAudit::Table)
+                    .drop_column(ContentAudit::TraceRequested)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum ContentAudit {
+    Table,
+    TraceRequested,
+}
@@ -0,0 +1,62 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ContentAudit::Table)
+                    .add_column_if_not_exists(ColumnDef::new(ContentAudit::ErrorReason).text())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AuditStats::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(AuditStats::NumStalePending)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AuditStats::Table)
+                    .drop_column(AuditStats::NumStalePending)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ContentAudit::Table)
+                    .drop_column(ContentAudit::ErrorReason)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum ContentAudit {
+    Table,
+    ErrorReason,
+}
+
+#[derive(Iden)]
+enum AuditStats {
+    Table,
+    NumStalePending,
+}
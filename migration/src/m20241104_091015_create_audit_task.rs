@@ -0,0 +1,108 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const INDEX_COMPLETED_AT: &str = "idx_audit_task_completed_at";
+
+/// A content key can only be queued once until its audit completes, so strategies that select
+/// unaudited keys do not queue a key again while it waits.
+const CREATE_OPEN_INDEX: &str = "
+CREATE UNIQUE INDEX IF NOT EXISTS idx_audit_task_open_content_key
+ON audit_task (content_key) WHERE completed_at IS NULL;
+";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AuditTask::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AuditTask::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(AuditTask::ContentKey).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_audit_task_content_key")
+                            .from(AuditTask::Table, AuditTask::ContentKey)
+                            .to(Content::Table, Content::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(ColumnDef::new(AuditTask::StrategyUsed).integer().not_null())
+                    .col(
+                        ColumnDef::new(AuditTask::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(AuditTask::ClaimedAt).timestamp_with_time_zone())
+                    .col(ColumnDef::new(AuditTask::ClaimedBy).text())
+                    .col(ColumnDef::new(AuditTask::CompletedAt).timestamp_with_time_zone())
+                    .col(ColumnDef::new(AuditTask::AuditId).integer())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_audit_task_audit_id")
+                            .from(AuditTask::Table, AuditTask::AuditId)
+                            .to(ContentAudit::Table, ContentAudit::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(INDEX_COMPLETED_AT)
+                    .table(AuditTask::Table)
+                    .col(AuditTask::CompletedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared(CREATE_OPEN_INDEX)
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AuditTask::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum AuditTask {
+    Table,
+    Id,
+    ContentKey,
+    StrategyUsed,
+    CreatedAt,
+    ClaimedAt,
+    ClaimedBy,
+    CompletedAt,
+    AuditId,
+}
+
+#[derive(Iden)]
+enum Content {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum ContentAudit {
+    Table,
+    Id,
+}
@@ -0,0 +1,38 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CensusNode::Table)
+                    .add_column_if_not_exists(ColumnDef::new(CensusNode::PingAttempts).integer())
+                    .add_column_if_not_exists(ColumnDef::new(CensusNode::PingLatencyMs).integer())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CensusNode::Table)
+                    .drop_column(CensusNode::PingAttempts)
+                    .drop_column(CensusNode::PingLatencyMs)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum CensusNode {
+    Table,
+    PingAttempts,
+    PingLatencyMs,
+}
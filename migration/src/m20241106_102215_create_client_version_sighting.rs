@@ -0,0 +1,74 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ClientVersionSighting::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ClientVersionSighting::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ClientVersionSighting::Client)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ClientVersionSighting::Version)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ClientVersionSighting::FirstSeenAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ClientVersionSighting::LastSeenAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name(IDX_CLIENT_VERSION)
+                    .table(ClientVersionSighting::Table)
+                    .col(ClientVersionSighting::Client)
+                    .col(ClientVersionSighting::Version)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ClientVersionSighting::Table).to_owned())
+            .await
+    }
+}
+
+const IDX_CLIENT_VERSION: &str = "idx_client_version_sighting-client_version";
+
+#[derive(Iden)]
+enum ClientVersionSighting {
+    Table,
+    Id,
+    Client,
+    Version,
+    FirstSeenAt,
+    LastSeenAt,
+}
@@ -0,0 +1,89 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const INDEX_DAY_GROUP: &str = "idx_transfer_failure_daily_day_group";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(TransferFailureDaily::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TransferFailureDaily::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(TransferFailureDaily::Day)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TransferFailureDaily::ProtocolId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TransferFailureDaily::ContentType)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TransferFailureDaily::FailureType)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TransferFailureDaily::NumTransfers)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TransferFailureDaily::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(INDEX_DAY_GROUP)
+                    .table(TransferFailureDaily::Table)
+                    .col(TransferFailureDaily::Day)
+                    .col(TransferFailureDaily::ProtocolId)
+                    .col(TransferFailureDaily::ContentType)
+                    .col(TransferFailureDaily::FailureType)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(TransferFailureDaily::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum TransferFailureDaily {
+    Table,
+    Id,
+    Day,
+    ProtocolId,
+    ContentType,
+    FailureType,
+    NumTransfers,
+    UpdatedAt,
+}
@@ -0,0 +1,111 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const INDEX_CENSUS: &str = "idx_census_node_error_census_id";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(CensusNodeError::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(CensusNodeError::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(CensusNodeError::CensusId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CensusNodeError::RecordId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CensusNodeError::SurveyedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(CensusNodeError::Kind).integer().not_null())
+                    .col(
+                        ColumnDef::new(CensusNodeError::Attempts)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CensusNodeError::DurationMs)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(CensusNodeError::Message).text())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_census_node_error_census_id")
+                            .from(CensusNodeError::Table, CensusNodeError::CensusId)
+                            .to(Census::Table, Census::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_census_node_error_record_id")
+                            .from(CensusNodeError::Table, CensusNodeError::RecordId)
+                            .to(Record::Table, Record::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(INDEX_CENSUS)
+                    .table(CensusNodeError::Table)
+                    .col(CensusNodeError::CensusId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CensusNodeError::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum CensusNodeError {
+    Table,
+    Id,
+    CensusId,
+    RecordId,
+    SurveyedAt,
+    Kind,
+    Attempts,
+    DurationMs,
+    Message,
+}
+
+#[derive(Iden)]
+enum Census {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Record {
+    Table,
+    Id,
+}
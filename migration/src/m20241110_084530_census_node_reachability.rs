@@ -0,0 +1,39 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Left empty for nodes whose declared endpoints were not probed.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CensusNode::Table)
+                    .add_column_if_not_exists(ColumnDef::new(CensusNode::UdpReachable).boolean())
+                    .add_column_if_not_exists(ColumnDef::new(CensusNode::TcpReachable).boolean())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CensusNode::Table)
+                    .drop_column(CensusNode::UdpReachable)
+                    .drop_column(CensusNode::TcpReachable)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum CensusNode {
+    Table,
+    UdpReachable,
+    TcpReachable,
+}
@@ -0,0 +1,38 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Left empty for audits recorded before the lag was measured.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ContentAudit::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(ContentAudit::SelectionLagMs).integer(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ContentAudit::Table)
+                    .drop_column(ContentAudit::SelectionLagMs)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum ContentAudit {
+    Table,
+    SelectionLagMs,
+}
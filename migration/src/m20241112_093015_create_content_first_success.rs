@@ -0,0 +1,94 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const INDEX_PROTOCOL_AVAILABLE_AT: &str = "idx_content_first_success_protocol_available_at";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // The first success is not tied to its audit, so the report outlives pruned audits.
+        manager
+            .create_table(
+                Table::create()
+                    .table(ContentFirstSuccess::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ContentFirstSuccess::ContentKey)
+                            .integer()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_content_first_success_content_key")
+                            .from(ContentFirstSuccess::Table, ContentFirstSuccess::ContentKey)
+                            .to(Content::Table, Content::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(
+                        ColumnDef::new(ContentFirstSuccess::ProtocolId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ContentFirstSuccess::FirstAvailableAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ContentFirstSuccess::FirstSuccessAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ContentFirstSuccess::DelaySeconds)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ContentFirstSuccess::ComputedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(INDEX_PROTOCOL_AVAILABLE_AT)
+                    .table(ContentFirstSuccess::Table)
+                    .col(ContentFirstSuccess::ProtocolId)
+                    .col(ContentFirstSuccess::FirstAvailableAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ContentFirstSuccess::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum ContentFirstSuccess {
+    Table,
+    ContentKey,
+    ProtocolId,
+    FirstAvailableAt,
+    FirstSuccessAt,
+    DelaySeconds,
+    ComputedAt,
+}
+
+#[derive(Iden)]
+enum Content {
+    Table,
+    Id,
+}
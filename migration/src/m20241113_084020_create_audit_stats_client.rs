@@ -0,0 +1,88 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const INDEX_TIMESTAMP_PROTOCOL: &str = "idx_audit_stats_client_timestamp_protocol";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AuditStatsClient::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AuditStatsClient::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(AuditStatsClient::Timestamp)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AuditStatsClient::ProtocolId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(AuditStatsClient::Client).string().not_null())
+                    .col(
+                        ColumnDef::new(AuditStatsClient::NumAudits)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AuditStatsClient::NumPasses)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AuditStatsClient::NumFailures)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AuditStatsClient::SuccessRate)
+                            .float()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(INDEX_TIMESTAMP_PROTOCOL)
+                    .table(AuditStatsClient::Table)
+                    .col(AuditStatsClient::Timestamp)
+                    .col(AuditStatsClient::ProtocolId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AuditStatsClient::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum AuditStatsClient {
+    Table,
+    Id,
+    Timestamp,
+    ProtocolId,
+    Client,
+    NumAudits,
+    NumPasses,
+    NumFailures,
+    SuccessRate,
+}
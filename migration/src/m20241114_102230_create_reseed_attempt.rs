@@ -0,0 +1,100 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const INDEX_CONTENT_KEY_ATTEMPTED_AT: &str = "idx_reseed_attempt_content_key_attempted_at";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ReseedAttempt::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ReseedAttempt::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ReseedAttempt::ContentKey)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_reseed_attempt_content_key")
+                            .from(ReseedAttempt::Table, ReseedAttempt::ContentKey)
+                            .to(Content::Table, Content::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(ColumnDef::new(ReseedAttempt::AuditId).integer())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_reseed_attempt_audit_id")
+                            .from(ReseedAttempt::Table, ReseedAttempt::AuditId)
+                            .to(ContentAudit::Table, ContentAudit::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(
+                        ColumnDef::new(ReseedAttempt::AttemptedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ReseedAttempt::Outcome).integer().not_null())
+                    .col(ColumnDef::new(ReseedAttempt::PeerCount).integer())
+                    .col(ColumnDef::new(ReseedAttempt::StoredLocally).boolean())
+                    .col(ColumnDef::new(ReseedAttempt::Error).string())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(INDEX_CONTENT_KEY_ATTEMPTED_AT)
+                    .table(ReseedAttempt::Table)
+                    .col(ReseedAttempt::ContentKey)
+                    .col(ReseedAttempt::AttemptedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ReseedAttempt::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum ReseedAttempt {
+    Table,
+    Id,
+    ContentKey,
+    AuditId,
+    AttemptedAt,
+    Outcome,
+    PeerCount,
+    StoredLocally,
+    Error,
+}
+
+#[derive(Iden)]
+enum Content {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum ContentAudit {
+    Table,
+    Id,
+}
@@ -0,0 +1,42 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Left empty until glados-audit parses the version strings recorded before, at startup.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ClientInfo::Table)
+                    .add_column_if_not_exists(ColumnDef::new(ClientInfo::ClientName).string())
+                    .add_column_if_not_exists(ColumnDef::new(ClientInfo::ClientVersion).string())
+                    .add_column_if_not_exists(ColumnDef::new(ClientInfo::ClientCommit).string())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ClientInfo::Table)
+                    .drop_column(ClientInfo::ClientName)
+                    .drop_column(ClientInfo::ClientVersion)
+                    .drop_column(ClientInfo::ClientCommit)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum ClientInfo {
+    Table,
+    ClientName,
+    ClientVersion,
+    ClientCommit,
+}
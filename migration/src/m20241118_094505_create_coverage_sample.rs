@@ -0,0 +1,83 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const INDEX_CENSUS_NODES_COVERING: &str = "idx_coverage_sample_census_id_nodes_covering";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(CoverageSample::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(CoverageSample::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(CoverageSample::CensusId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CoverageSample::NodesCovering)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CoverageSample::NumSamples)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_coverage_sample_census_id")
+                            .from(CoverageSample::Table, CoverageSample::CensusId)
+                            .to(Census::Table, Census::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(INDEX_CENSUS_NODES_COVERING)
+                    .table(CoverageSample::Table)
+                    .col(CoverageSample::CensusId)
+                    .col(CoverageSample::NodesCovering)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CoverageSample::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum CoverageSample {
+    Table,
+    Id,
+    CensusId,
+    NodesCovering,
+    NumSamples,
+}
+
+#[derive(Iden)]
+enum Census {
+    Table,
+    Id,
+}
@@ -0,0 +1,61 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const INDEX_RESULT_CREATED_AT: &str = "idx_audit_latest_result_created_at";
+const INDEX_RESULT_CONSECUTIVE_FAILURES: &str = "idx_audit_latest_result_consecutive_failures";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // For listing failing keys, by when they last failed or how many times in a row.
+        manager
+            .create_index(
+                Index::create()
+                    .name(INDEX_RESULT_CREATED_AT)
+                    .table(AuditLatest::Table)
+                    .col(AuditLatest::Result)
+                    .col(AuditLatest::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name(INDEX_RESULT_CONSECUTIVE_FAILURES)
+                    .table(AuditLatest::Table)
+                    .col(AuditLatest::Result)
+                    .col(AuditLatest::ConsecutiveFailures)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name(INDEX_RESULT_CONSECUTIVE_FAILURES)
+                    .table(AuditLatest::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_index(
+                Index::drop()
+                    .name(INDEX_RESULT_CREATED_AT)
+                    .table(AuditLatest::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum AuditLatest {
+    Table,
+    Result,
+    CreatedAt,
+    ConsecutiveFailures,
+}
@@ -0,0 +1,112 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const INDEX_CONTENT_KEY_OFFSET: &str = "idx_propagation_audit_content_key_offset_seconds";
+const INDEX_DUE_AT: &str = "idx_propagation_audit_due_at";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PropagationAudit::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PropagationAudit::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(PropagationAudit::ContentKey)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_propagation_audit_content_key")
+                            .from(PropagationAudit::Table, PropagationAudit::ContentKey)
+                            .to(Content::Table, Content::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(
+                        ColumnDef::new(PropagationAudit::OffsetSeconds)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PropagationAudit::DueAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(PropagationAudit::AuditId).integer())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_propagation_audit_audit_id")
+                            .from(PropagationAudit::Table, PropagationAudit::AuditId)
+                            .to(ContentAudit::Table, ContentAudit::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(ColumnDef::new(PropagationAudit::AuditedAt).timestamp_with_time_zone())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(INDEX_CONTENT_KEY_OFFSET)
+                    .table(PropagationAudit::Table)
+                    .col(PropagationAudit::ContentKey)
+                    .col(PropagationAudit::OffsetSeconds)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(INDEX_DUE_AT)
+                    .table(PropagationAudit::Table)
+                    .col(PropagationAudit::DueAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PropagationAudit::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum PropagationAudit {
+    Table,
+    Id,
+    ContentKey,
+    OffsetSeconds,
+    DueAt,
+    AuditId,
+    AuditedAt,
+}
+
+#[derive(Iden)]
+enum Content {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum ContentAudit {
+    Table,
+    Id,
+}
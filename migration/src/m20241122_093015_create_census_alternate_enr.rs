@@ -0,0 +1,110 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const INDEX_CENSUS_RECORD: &str = "idx_census_alternate_enr_census_id_record_id";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // ENRs that a node presented in a census besides its canonical one, the ENR with the
+        // highest sequence number.
+        manager
+            .create_table(
+                Table::create()
+                    .table(CensusAlternateEnr::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(CensusAlternateEnr::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(CensusAlternateEnr::CensusId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_census_alternate_enr_census_id")
+                            .from(CensusAlternateEnr::Table, CensusAlternateEnr::CensusId)
+                            .to(Census::Table, Census::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(
+                        ColumnDef::new(CensusAlternateEnr::CanonicalRecordId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_census_alternate_enr_canonical_record_id")
+                            .from(
+                                CensusAlternateEnr::Table,
+                                CensusAlternateEnr::CanonicalRecordId,
+                            )
+                            .to(Record::Table, Record::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(
+                        ColumnDef::new(CensusAlternateEnr::RecordId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_census_alternate_enr_record_id")
+                            .from(CensusAlternateEnr::Table, CensusAlternateEnr::RecordId)
+                            .to(Record::Table, Record::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(INDEX_CENSUS_RECORD)
+                    .table(CensusAlternateEnr::Table)
+                    .col(CensusAlternateEnr::CensusId)
+                    .col(CensusAlternateEnr::RecordId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CensusAlternateEnr::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum CensusAlternateEnr {
+    Table,
+    Id,
+    CensusId,
+    CanonicalRecordId,
+    RecordId,
+}
+
+#[derive(Iden)]
+enum Census {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Record {
+    Table,
+    Id,
+}
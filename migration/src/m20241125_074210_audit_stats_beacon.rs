@@ -0,0 +1,61 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Left empty for stats recorded before beacon audits were measured.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AuditStats::Table)
+                    .add_column_if_not_exists(ColumnDef::new(AuditStats::NumBeaconAudits).integer())
+                    .add_column_if_not_exists(
+                        ColumnDef::new(AuditStats::SuccessRateBeaconAll).float(),
+                    )
+                    .add_column_if_not_exists(
+                        ColumnDef::new(AuditStats::SuccessRateBeaconBootstraps).float(),
+                    )
+                    .add_column_if_not_exists(
+                        ColumnDef::new(AuditStats::SuccessRateBeaconUpdatesByRange).float(),
+                    )
+                    .add_column_if_not_exists(
+                        ColumnDef::new(AuditStats::SuccessRateBeaconFinalityUpdates).float(),
+                    )
+                    .add_column_if_not_exists(
+                        ColumnDef::new(AuditStats::SuccessRateBeaconOptimisticUpdates).float(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AuditStats::Table)
+                    .drop_column(AuditStats::NumBeaconAudits)
+                    .drop_column(AuditStats::SuccessRateBeaconAll)
+                    .drop_column(AuditStats::SuccessRateBeaconBootstraps)
+                    .drop_column(AuditStats::SuccessRateBeaconUpdatesByRange)
+                    .drop_column(AuditStats::SuccessRateBeaconFinalityUpdates)
+                    .drop_column(AuditStats::SuccessRateBeaconOptimisticUpdates)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum AuditStats {
+    Table,
+    NumBeaconAudits,
+    SuccessRateBeaconAll,
+    SuccessRateBeaconBootstraps,
+    SuccessRateBeaconUpdatesByRange,
+    SuccessRateBeaconFinalityUpdates,
+    SuccessRateBeaconOptimisticUpdates,
+}
@@ -0,0 +1,39 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Left empty for censuses taken before client diversity was computed.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Census::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Census::ClientEffectiveNumber).float())
+                    .add_column_if_not_exists(ColumnDef::new(Census::ClientEntropy).float())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Census::Table)
+                    .drop_column(Census::ClientEffectiveNumber)
+                    .drop_column(Census::ClientEntropy)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum Census {
+    Table,
+    ClientEffectiveNumber,
+    ClientEntropy,
+}
@@ -0,0 +1,47 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SiteBanner::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SiteBanner::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(SiteBanner::Message).text().not_null())
+                    .col(
+                        ColumnDef::new(SiteBanner::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(SiteBanner::ExpiresAt).timestamp_with_time_zone())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SiteBanner::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum SiteBanner {
+    Table,
+    Id,
+    Message,
+    CreatedAt,
+    ExpiresAt,
+}
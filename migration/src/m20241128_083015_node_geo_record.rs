@@ -0,0 +1,36 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Left empty for locations recorded before the record they were resolved from was kept.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NodeGeo::Table)
+                    .add_column_if_not_exists(ColumnDef::new(NodeGeo::RecordId).integer())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NodeGeo::Table)
+                    .drop_column(NodeGeo::RecordId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum NodeGeo {
+    Table,
+    RecordId,
+}
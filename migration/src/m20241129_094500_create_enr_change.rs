@@ -0,0 +1,120 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const INDEX_NEW_RECORD: &str = "idx_enr_change_new_record_id";
+const INDEX_NODE_CHANGED_AT: &str = "idx_enr_change_node_id_changed_at";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Changes of the IP address or UDP port a node announces, between an ENR and the one
+        // with the next lower sequence number. Addresses and ports are left empty when the ENR
+        // does not announce one.
+        manager
+            .create_table(
+                Table::create()
+                    .table(EnrChange::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(EnrChange::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(EnrChange::NodeId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_enr_change_node_id")
+                            .from(EnrChange::Table, EnrChange::NodeId)
+                            .to(Node::Table, Node::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(ColumnDef::new(EnrChange::OldRecordId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_enr_change_old_record_id")
+                            .from(EnrChange::Table, EnrChange::OldRecordId)
+                            .to(Record::Table, Record::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(ColumnDef::new(EnrChange::NewRecordId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("FK_enr_change_new_record_id")
+                            .from(EnrChange::Table, EnrChange::NewRecordId)
+                            .to(Record::Table, Record::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .col(ColumnDef::new(EnrChange::OldIp).text())
+                    .col(ColumnDef::new(EnrChange::NewIp).text())
+                    .col(ColumnDef::new(EnrChange::OldPort).integer())
+                    .col(ColumnDef::new(EnrChange::NewPort).integer())
+                    .col(
+                        ColumnDef::new(EnrChange::ChangedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(INDEX_NEW_RECORD)
+                    .table(EnrChange::Table)
+                    .col(EnrChange::NewRecordId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name(INDEX_NODE_CHANGED_AT)
+                    .table(EnrChange::Table)
+                    .col(EnrChange::NodeId)
+                    .col(EnrChange::ChangedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(EnrChange::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum EnrChange {
+    Table,
+    Id,
+    NodeId,
+    OldRecordId,
+    NewRecordId,
+    OldIp,
+    NewIp,
+    OldPort,
+    NewPort,
+    ChangedAt,
+}
+
+#[derive(Iden)]
+enum Node {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Record {
+    Table,
+    Id,
+}
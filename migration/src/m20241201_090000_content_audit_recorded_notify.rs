@@ -0,0 +1,45 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Notifies the `audit_recorded` channel with the id of each audit once its result is recorded,
+/// either when it is inserted with a result or when a `Pending` audit gets one.
+const CREATE_TRIGGER: &str = "
+CREATE OR REPLACE FUNCTION content_audit_recorded() RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.result <> 3 AND (TG_OP = 'INSERT' OR OLD.result IS DISTINCT FROM NEW.result) THEN
+        PERFORM pg_notify('audit_recorded', NEW.id::text);
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER content_audit_recorded
+AFTER INSERT OR UPDATE OF result ON content_audit
+FOR EACH ROW EXECUTE FUNCTION content_audit_recorded();
+";
+
+const DROP_TRIGGER: &str = "
+DROP TRIGGER IF EXISTS content_audit_recorded ON content_audit;
+DROP FUNCTION IF EXISTS content_audit_recorded();
+";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(CREATE_TRIGGER)
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(DROP_TRIGGER)
+            .await?;
+        Ok(())
+    }
+}
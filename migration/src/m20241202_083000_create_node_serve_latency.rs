@@ -0,0 +1,88 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const INDEX_PROTOCOL_NODE: &str = "idx_node_serve_latency_protocol_id_node_id";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Latency of the responses of each node to audit lookups, from the audit traces of a
+        // recent window. Nodes are kept by node id rather than by row, as traces include nodes
+        // that no census has found.
+        manager
+            .create_table(
+                Table::create()
+                    .table(NodeServeLatency::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(NodeServeLatency::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(NodeServeLatency::NodeId)
+                            .binary_len(32)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(NodeServeLatency::ProtocolId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(NodeServeLatency::NumResponses)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(NodeServeLatency::P50Ms).double().not_null())
+                    .col(ColumnDef::new(NodeServeLatency::P95Ms).double().not_null())
+                    .col(
+                        ColumnDef::new(NodeServeLatency::WindowStart)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(NodeServeLatency::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(INDEX_PROTOCOL_NODE)
+                    .table(NodeServeLatency::Table)
+                    .col(NodeServeLatency::ProtocolId)
+                    .col(NodeServeLatency::NodeId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(NodeServeLatency::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum NodeServeLatency {
+    Table,
+    Id,
+    NodeId,
+    ProtocolId,
+    NumResponses,
+    P50Ms,
+    P95Ms,
+    WindowStart,
+    UpdatedAt,
+}
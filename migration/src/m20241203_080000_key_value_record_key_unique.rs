@@ -0,0 +1,71 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const INDEX_RECORD_KEY: &str = "idx_key_value_record_id_key";
+
+/// The constraint created by the key_value migration is on (id, key) rather than on
+/// (record_id, key), so it never applied.
+const DROP_ID_KEY_CONSTRAINT: &str =
+    r#"ALTER TABLE key_value DROP CONSTRAINT IF EXISTS "idx-unique-record-and-key""#;
+const ADD_ID_KEY_CONSTRAINT: &str =
+    r#"ALTER TABLE key_value ADD CONSTRAINT "idx-unique-record-and-key" UNIQUE (id, key)"#;
+
+/// Keeps the first of the pairs stored more than once for a record and key.
+const DELETE_DUPLICATES: &str = "
+DELETE FROM key_value
+USING key_value AS kept
+WHERE key_value.record_id = kept.record_id
+    AND key_value.key = kept.key
+    AND key_value.id > kept.id";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Records are stored with their key-value pairs in batches that skip the pairs already
+        // stored, which needs a unique index on the record and key.
+        manager
+            .get_connection()
+            .execute_unprepared(DELETE_DUPLICATES)
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name(INDEX_RECORD_KEY)
+                    .table(KeyValue::Table)
+                    .col(KeyValue::RecordId)
+                    .col(KeyValue::Key)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .get_connection()
+            .execute_unprepared(DROP_ID_KEY_CONSTRAINT)
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(ADD_ID_KEY_CONSTRAINT)
+            .await?;
+        manager
+            .drop_index(
+                Index::drop()
+                    .name(INDEX_RECORD_KEY)
+                    .table(KeyValue::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum KeyValue {
+    Table,
+    RecordId,
+    Key,
+}
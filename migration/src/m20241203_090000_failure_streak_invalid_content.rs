@@ -0,0 +1,121 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Replaces the `audit_result_latest` trigger function so that invalid content extends the
+/// failure streak of a key, like a failure, rather than ending it. Only a success ends it.
+///
+/// The streak is updated by the upsert itself, from the row it conflicts with, so audits of the
+/// same key recorded at once cannot both read the old streak.
+const REPLACE_TRIGGER_FUNCTION: &str = "
+CREATE OR REPLACE FUNCTION audit_result_latest() RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO audit_latest (
+        content_key, audit_id, strategy_used, result, created_at, consecutive_failures, retry_after
+    )
+    VALUES (
+        NEW.content_key, NEW.id, NEW.strategy_used, NEW.result, NEW.created_at,
+        CASE WHEN NEW.result IN (0, 4) THEN 1 ELSE 0 END,
+        CASE WHEN NEW.result IN (0, 4) THEN NEW.created_at + failed_audit_backoff(1) END
+    )
+    ON CONFLICT (content_key) DO UPDATE SET
+        audit_id = EXCLUDED.audit_id,
+        strategy_used = EXCLUDED.strategy_used,
+        result = EXCLUDED.result,
+        created_at = EXCLUDED.created_at,
+        consecutive_failures = CASE
+            WHEN EXCLUDED.result IN (0, 4) THEN audit_latest.consecutive_failures + 1
+            WHEN EXCLUDED.result = 1 THEN 0
+            ELSE audit_latest.consecutive_failures
+        END,
+        retry_after = CASE
+            WHEN EXCLUDED.result IN (0, 4)
+                THEN EXCLUDED.created_at
+                    + failed_audit_backoff(audit_latest.consecutive_failures + 1)
+            WHEN EXCLUDED.result = 1 OR audit_latest.consecutive_failures = 0 THEN NULL
+            ELSE EXCLUDED.created_at + failed_audit_backoff(audit_latest.consecutive_failures)
+        END
+    WHERE audit_latest.created_at <= EXCLUDED.created_at;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+";
+
+/// Recounts the streaks of the keys that returned invalid content since their last success.
+const BACKFILL: &str = "
+WITH last_success AS (
+    SELECT content_key, MAX(created_at) AS created_at
+    FROM content_audit
+    WHERE result = 1
+    GROUP BY content_key
+),
+streaks AS (
+    SELECT content_audit.content_key, COUNT(*)::integer AS failures
+    FROM content_audit
+    LEFT JOIN last_success ON last_success.content_key = content_audit.content_key
+    WHERE content_audit.result IN (0, 4)
+        AND (last_success.created_at IS NULL OR content_audit.created_at > last_success.created_at)
+    GROUP BY content_audit.content_key
+)
+UPDATE audit_latest
+SET consecutive_failures = streaks.failures,
+    retry_after = audit_latest.created_at + failed_audit_backoff(streaks.failures)
+FROM streaks
+WHERE streaks.content_key = audit_latest.content_key
+    AND streaks.failures <> audit_latest.consecutive_failures;
+";
+
+/// The trigger function of the failed_audit_backoff migration.
+const RESTORE_TRIGGER_FUNCTION: &str = "
+CREATE OR REPLACE FUNCTION audit_result_latest() RETURNS TRIGGER AS $$
+DECLARE
+    failures INTEGER;
+BEGIN
+    SELECT consecutive_failures INTO failures
+    FROM audit_latest
+    WHERE content_key = NEW.content_key;
+    failures := COALESCE(failures, 0);
+    IF NEW.result = 0 THEN
+        failures := failures + 1;
+    ELSIF NEW.result IN (1, 4) THEN
+        failures := 0;
+    END IF;
+
+    INSERT INTO audit_latest (
+        content_key, audit_id, strategy_used, result, created_at, consecutive_failures, retry_after
+    )
+    VALUES (
+        NEW.content_key, NEW.id, NEW.strategy_used, NEW.result, NEW.created_at, failures,
+        CASE WHEN failures = 0 THEN NULL ELSE NEW.created_at + failed_audit_backoff(failures) END
+    )
+    ON CONFLICT (content_key) DO UPDATE SET
+        audit_id = EXCLUDED.audit_id,
+        strategy_used = EXCLUDED.strategy_used,
+        result = EXCLUDED.result,
+        created_at = EXCLUDED.created_at,
+        consecutive_failures = EXCLUDED.consecutive_failures,
+        retry_after = EXCLUDED.retry_after
+    WHERE audit_latest.created_at <= EXCLUDED.created_at;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let conn = manager.get_connection();
+        conn.execute_unprepared(REPLACE_TRIGGER_FUNCTION).await?;
+        conn.execute_unprepared(BACKFILL).await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(RESTORE_TRIGGER_FUNCTION)
+            .await?;
+        Ok(())
+    }
+}
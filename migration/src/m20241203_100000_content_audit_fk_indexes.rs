@@ -0,0 +1,70 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Indexes of the columns referencing `content_audit`, and `round_trip_audit.content_key`, so
+/// that deleting audits or content for the retention policy does not scan these tables for the
+/// rows to cascade to. The other columns referencing `content` are indexed already.
+const INDEXES: [(&str, &str, &str); 9] = [
+    ("idx_audit_latest_audit_id", "audit_latest", "audit_id"),
+    (
+        "idx_block_audit_header_audit_id",
+        "block_audit",
+        "header_audit_id",
+    ),
+    (
+        "idx_block_audit_body_audit_id",
+        "block_audit",
+        "body_audit_id",
+    ),
+    (
+        "idx_block_audit_receipts_audit_id",
+        "block_audit",
+        "receipts_audit_id",
+    ),
+    (
+        "idx_round_trip_audit_audit_id",
+        "round_trip_audit",
+        "audit_id",
+    ),
+    (
+        "idx_round_trip_audit_content_key",
+        "round_trip_audit",
+        "content_key",
+    ),
+    ("idx_audit_task_audit_id", "audit_task", "audit_id"),
+    ("idx_reseed_attempt_audit_id", "reseed_attempt", "audit_id"),
+    (
+        "idx_propagation_audit_audit_id",
+        "propagation_audit",
+        "audit_id",
+    ),
+];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for (name, table, column) in INDEXES {
+            manager
+                .create_index(
+                    Index::create()
+                        .name(name)
+                        .table(Alias::new(table))
+                        .col(Alias::new(column))
+                        .to_owned(),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for (name, table, _) in INDEXES {
+            manager
+                .drop_index(Index::drop().name(name).table(Alias::new(table)).to_owned())
+                .await?;
+        }
+        Ok(())
+    }
+}